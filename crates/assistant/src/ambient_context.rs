@@ -1,13 +1,16 @@
 mod current_project;
+mod project_instructions;
 mod recent_buffers;
 
 pub use current_project::*;
+pub use project_instructions::*;
 pub use recent_buffers::*;
 
 #[derive(Default)]
 pub struct AmbientContext {
     pub recent_buffers: RecentBuffersContext,
     pub current_project: CurrentProjectContext,
+    pub project_instructions: ProjectInstructionsContext,
 }
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
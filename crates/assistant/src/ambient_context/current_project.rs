@@ -1,17 +1,22 @@
-use std::path::{Path, PathBuf};
+use std::fmt::Write as _;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context as _, Result};
 use fs::Fs;
 use gpui::{AsyncAppContext, ModelContext, Task, WeakModel};
-use project::{Project, ProjectPath};
+use language::{ManifestProvider, ProjectManifest};
+use project::Project;
 use util::ResultExt;
 
 use crate::ambient_context::ContextUpdated;
 use crate::assistant_panel::Conversation;
 use crate::{LanguageModelRequestMessage, Role};
 
+/// The number of dependencies to list per manifest, as large projects can have hundreds.
+const MAX_DEPENDENCIES: usize = 30;
+
 /// Ambient context about the current project.
 pub struct CurrentProjectContext {
     pub enabled: bool,
@@ -30,10 +35,17 @@ impl Default for CurrentProjectContext {
     }
 }
 
+/// A manifest file found at the root of one of the project's worktrees.
+struct ManifestPath {
+    worktree_name: String,
+    provider: Arc<dyn ManifestProvider>,
+    abs_path: PathBuf,
+}
+
 impl CurrentProjectContext {
     /// Returns the [`CurrentProjectContext`] as a message to the language model.
     pub fn to_message(&self) -> Option<LanguageModelRequestMessage> {
-        self.enabled.then(|| LanguageModelRequestMessage {
+        (self.enabled && !self.message.is_empty()).then(|| LanguageModelRequestMessage {
             role: Role::System,
             content: self.message.clone(),
         })
@@ -57,99 +69,148 @@ impl CurrentProjectContext {
             const DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(100);
             cx.background_executor().timer(DEBOUNCE_TIMEOUT).await;
 
-            let Some(path_to_cargo_toml) = Self::path_to_cargo_toml(project, &mut cx).log_err()
-            else {
-                return;
-            };
-
-            let Some(path_to_cargo_toml) = path_to_cargo_toml
-                .ok_or_else(|| anyhow!("no Cargo.toml"))
-                .log_err()
-            else {
+            let Some(manifest_paths) = Self::manifest_paths(project, &mut cx).log_err() else {
                 return;
             };
 
-            let message_task = cx
+            let message = cx
                 .background_executor()
-                .spawn(async move { Self::build_message(fs, &path_to_cargo_toml).await });
-
-            if let Some(message) = message_task.await.log_err() {
-                conversation
-                    .update(&mut cx, |conversation, cx| {
-                        conversation.ambient_context.current_project.message = message;
-                        conversation.count_remaining_tokens(cx);
-                        cx.notify();
-                    })
-                    .log_err();
-            }
+                .spawn(async move { Self::build_message(fs, manifest_paths).await })
+                .await;
+
+            conversation
+                .update(&mut cx, |conversation, cx| {
+                    conversation.ambient_context.current_project.message = message;
+                    conversation.count_remaining_tokens(cx);
+                    cx.notify();
+                })
+                .log_err();
         }));
 
         ContextUpdated::Updating
     }
 
-    async fn build_message(fs: Arc<dyn Fs>, path_to_cargo_toml: &Path) -> Result<String> {
-        let buffer = fs.load(path_to_cargo_toml).await?;
-        let cargo_toml: cargo_toml::Manifest = toml::from_str(&buffer)?;
-
+    async fn build_message(fs: Arc<dyn Fs>, manifest_paths: Vec<ManifestPath>) -> String {
         let mut message = String::new();
-
-        let name = cargo_toml
-            .package
-            .as_ref()
-            .map(|package| package.name.as_str());
-        if let Some(name) = name {
-            message.push_str(&format!(" named \"{name}\""));
-        }
-        message.push_str(". ");
-
-        let description = cargo_toml
-            .package
-            .as_ref()
-            .and_then(|package| package.description.as_ref())
-            .and_then(|description| description.get().ok().cloned());
-        if let Some(description) = description.as_ref() {
-            message.push_str("It describes itself as ");
-            message.push_str(&format!("\"{description}\""));
-            message.push_str(". ");
-        }
-
-        let dependencies = cargo_toml.dependencies.keys().cloned().collect::<Vec<_>>();
-        if !dependencies.is_empty() {
-            message.push_str("The following dependencies are installed: ");
-            message.push_str(&dependencies.join(", "));
-            message.push_str(". ");
+        for manifest_path in manifest_paths {
+            let manifest = async {
+                let contents = fs.load(&manifest_path.abs_path).await?;
+                manifest_path
+                    .provider
+                    .parse(&contents)
+                    .with_context(|| format!("failed to parse {:?}", manifest_path.abs_path))
+            };
+            if let Some(manifest) = manifest.await.log_err() {
+                message.push_str(&describe_manifest(
+                    &manifest_path.worktree_name,
+                    manifest_path.provider.ecosystem(),
+                    &manifest,
+                ));
+                message.push('\n');
+            }
         }
-
-        Ok(message)
+        message
     }
 
-    fn path_to_cargo_toml(
+    /// Finds the manifests of all registered ecosystems at the roots of the project's worktrees.
+    fn manifest_paths(
         project: WeakModel<Project>,
         cx: &mut AsyncAppContext,
-    ) -> Result<Option<PathBuf>> {
+    ) -> Result<Vec<ManifestPath>> {
         cx.update(|cx| {
-            let worktree = project.update(cx, |project, _cx| {
-                project
-                    .worktrees()
-                    .next()
-                    .ok_or_else(|| anyhow!("no worktree"))
-            })??;
-
-            let path_to_cargo_toml = worktree.update(cx, |worktree, _cx| {
-                let cargo_toml = worktree.entry_for_path("Cargo.toml")?;
-                Some(ProjectPath {
-                    worktree_id: worktree.id(),
-                    path: cargo_toml.path.clone(),
-                })
-            });
-            let path_to_cargo_toml = path_to_cargo_toml.and_then(|path| {
-                project
-                    .update(cx, |project, cx| project.absolute_path(&path, cx))
-                    .ok()
-                    .flatten()
-            });
-
-            Ok(path_to_cargo_toml)
+            let project = project
+                .upgrade()
+                .ok_or_else(|| anyhow!("project was dropped"))?;
+            let project = project.read(cx);
+            let providers = project.languages().manifest_providers();
+
+            let mut manifest_paths = Vec::new();
+            for worktree in project.visible_worktrees(cx) {
+                let worktree = worktree.read(cx);
+                for provider in &providers {
+                    if let Some(entry) = worktree.entry_for_path(provider.file_name()) {
+                        manifest_paths.push(ManifestPath {
+                            worktree_name: worktree.root_name().to_string(),
+                            provider: provider.clone(),
+                            abs_path: worktree.abs_path().join(&entry.path),
+                        });
+                    }
+                }
+            }
+            Ok(manifest_paths)
         })?
     }
 }
+
+fn describe_manifest(worktree_name: &str, ecosystem: &str, manifest: &ProjectManifest) -> String {
+    let mut description = format!("The `{worktree_name}` folder contains a {ecosystem} project");
+    if let Some(name) = manifest.name.as_ref() {
+        write!(description, " named \"{name}\"").unwrap();
+    }
+    description.push_str(". ");
+
+    if let Some(project_description) = manifest.description.as_ref() {
+        write!(
+            description,
+            "It describes itself as \"{project_description}\". "
+        )
+        .unwrap();
+    }
+
+    if !manifest.members.is_empty() {
+        write!(
+            description,
+            "It is a workspace with the following members: {}. ",
+            manifest.members.join(", ")
+        )
+        .unwrap();
+    }
+
+    if !manifest.dependencies.is_empty() {
+        let shown_dependencies =
+            &manifest.dependencies[..manifest.dependencies.len().min(MAX_DEPENDENCIES)];
+        write!(
+            description,
+            "It has the following dependencies: {}",
+            shown_dependencies.join(", ")
+        )
+        .unwrap();
+        let hidden_count = manifest.dependencies.len() - shown_dependencies.len();
+        if hidden_count > 0 {
+            write!(description, " and {hidden_count} more").unwrap();
+        }
+        description.push_str(". ");
+    }
+
+    description.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_manifest() {
+        let manifest = ProjectManifest {
+            name: Some("zed".into()),
+            description: Some("A code editor".into()),
+            members: vec!["crates/*".into()],
+            dependencies: (0..MAX_DEPENDENCIES + 2)
+                .map(|ix| format!("dep-{ix}"))
+                .collect(),
+        };
+        let description = describe_manifest("zed", "Rust", &manifest);
+        assert!(description.starts_with(
+            "The `zed` folder contains a Rust project named \"zed\". \
+             It describes itself as \"A code editor\". \
+             It is a workspace with the following members: crates/*. \
+             It has the following dependencies: dep-0, dep-1,"
+        ));
+        assert!(description.ends_with(", dep-29 and 2 more."));
+
+        assert_eq!(
+            describe_manifest("app", "Go", &ProjectManifest::default()),
+            "The `app` folder contains a Go project."
+        );
+    }
+}
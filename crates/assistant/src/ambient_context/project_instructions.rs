@@ -0,0 +1,99 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use fs::Fs;
+use gpui::{AsyncAppContext, ModelContext, Task, WeakModel};
+use project::Project;
+use util::{paths::LOCAL_ASSISTANT_CONTEXT_RELATIVE_PATH, ResultExt};
+
+use crate::assistant_panel::Conversation;
+use crate::{LanguageModelRequestMessage, Role};
+
+/// Instructions that users write for the assistant in a `.zed/assistant_context.md` file at the
+/// root of a worktree.
+///
+/// Unlike the other ambient context, these can't be toggled off, and are included whenever they exist.
+#[derive(Default)]
+pub struct ProjectInstructionsContext {
+    pub message: String,
+    pub pending_message: Option<Task<()>>,
+}
+
+impl ProjectInstructionsContext {
+    /// Returns the [`ProjectInstructionsContext`] as a message to the language model.
+    pub fn to_message(&self) -> Option<LanguageModelRequestMessage> {
+        (!self.message.is_empty()).then(|| LanguageModelRequestMessage {
+            role: Role::System,
+            content: self.message.clone(),
+        })
+    }
+
+    /// Reloads the instructions from the worktrees of the given [`Project`].
+    pub fn update(
+        &mut self,
+        fs: Arc<dyn Fs>,
+        project: WeakModel<Project>,
+        cx: &mut ModelContext<Conversation>,
+    ) {
+        self.pending_message = Some(cx.spawn(|conversation, mut cx| async move {
+            let Some(instruction_paths) = Self::instruction_paths(project, &mut cx).log_err()
+            else {
+                return;
+            };
+
+            let message = cx
+                .background_executor()
+                .spawn(async move {
+                    let mut message = String::new();
+                    for (worktree_name, path) in instruction_paths {
+                        let Some(instructions) = fs.load(&path).await.log_err() else {
+                            continue;
+                        };
+                        if instructions.trim().is_empty() {
+                            continue;
+                        }
+                        message.push_str(&format!(
+                            "The user has provided the following instructions for the `{worktree_name}` folder:\n\n{}\n\n",
+                            instructions.trim()
+                        ));
+                    }
+                    message
+                })
+                .await;
+
+            conversation
+                .update(&mut cx, |conversation, cx| {
+                    conversation.ambient_context.project_instructions.message = message;
+                    conversation.count_remaining_tokens(cx);
+                    cx.notify();
+                })
+                .log_err();
+        }));
+    }
+
+    /// Finds the instruction files at the roots of the project's worktrees.
+    fn instruction_paths(
+        project: WeakModel<Project>,
+        cx: &mut AsyncAppContext,
+    ) -> Result<Vec<(String, PathBuf)>> {
+        cx.update(|cx| {
+            let project = project
+                .upgrade()
+                .ok_or_else(|| anyhow!("project was dropped"))?;
+            let instruction_paths = project
+                .read(cx)
+                .visible_worktrees(cx)
+                .filter_map(|worktree| {
+                    let worktree = worktree.read(cx);
+                    let entry = worktree.entry_for_path(*LOCAL_ASSISTANT_CONTEXT_RELATIVE_PATH)?;
+                    Some((
+                        worktree.root_name().to_string(),
+                        worktree.abs_path().join(&entry.path),
+                    ))
+                })
+                .collect();
+            Ok(instruction_paths)
+        })?
+    }
+}
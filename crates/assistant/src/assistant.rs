@@ -2,13 +2,20 @@ mod ambient_context;
 pub mod assistant_panel;
 pub mod assistant_settings;
 mod codegen;
+mod commit_message;
 mod completion_provider;
+mod prompt_library;
 mod prompts;
 mod saved_conversation;
 mod streaming_diff;
+mod terminal_inline_assistant;
+mod usage;
 
 pub use assistant_panel::AssistantPanel;
-use assistant_settings::{AnthropicModel, AssistantSettings, OpenAiModel, ZedDotDevModel};
+use assistant_settings::{
+    AnthropicModel, AssistantSettings, AvailableModel, GoogleModel, OllamaModel, OpenAiModel,
+    ZedDotDevModel,
+};
 use client::{proto, Client};
 use command_palette_hooks::CommandPaletteFilter;
 pub(crate) use completion_provider::*;
@@ -20,6 +27,7 @@ use std::{
     fmt::{self, Display},
     sync::Arc,
 };
+pub use usage::{estimate_tokens, track_usage};
 
 actions!(
     assistant,
@@ -33,6 +41,12 @@ actions!(
         InlineAssist,
         ToggleIncludeConversation,
         ToggleHistory,
+        InsertPrompt,
+        NewPrompt,
+        ImportPrompts,
+        ExportPrompts,
+        GenerateCommitMessage,
+        ShowUsage,
     ]
 );
 
@@ -74,6 +88,9 @@ pub enum LanguageModel {
     ZedDotDev(ZedDotDevModel),
     OpenAi(OpenAiModel),
     Anthropic(AnthropicModel),
+    Google(GoogleModel),
+    Ollama(OllamaModel),
+    OpenAiCompatible(AvailableModel),
 }
 
 impl Default for LanguageModel {
@@ -87,7 +104,10 @@ impl LanguageModel {
         match self {
             LanguageModel::OpenAi(model) => format!("openai/{}", model.id()),
             LanguageModel::Anthropic(model) => format!("anthropic/{}", model.id()),
+            LanguageModel::Google(model) => format!("google/{}", model.id()),
             LanguageModel::ZedDotDev(model) => format!("zed.dev/{}", model.id()),
+            LanguageModel::Ollama(model) => format!("ollama/{}", model.id()),
+            LanguageModel::OpenAiCompatible(model) => format!("openai_compatible/{}", model.id()),
         }
     }
 
@@ -95,7 +115,10 @@ impl LanguageModel {
         match self {
             LanguageModel::OpenAi(model) => model.display_name().into(),
             LanguageModel::Anthropic(model) => model.display_name().into(),
+            LanguageModel::Google(model) => model.display_name().into(),
             LanguageModel::ZedDotDev(model) => model.display_name().into(),
+            LanguageModel::Ollama(model) => model.display_name().into(),
+            LanguageModel::OpenAiCompatible(model) => model.display_name().into(),
         }
     }
 
@@ -103,7 +126,10 @@ impl LanguageModel {
         match self {
             LanguageModel::OpenAi(model) => model.max_token_count(),
             LanguageModel::Anthropic(model) => model.max_token_count(),
+            LanguageModel::Google(model) => model.max_token_count(),
             LanguageModel::ZedDotDev(model) => model.max_token_count(),
+            LanguageModel::Ollama(model) => model.max_token_count(),
+            LanguageModel::OpenAiCompatible(model) => model.max_token_count(),
         }
     }
 
@@ -111,12 +137,15 @@ impl LanguageModel {
         match self {
             LanguageModel::OpenAi(model) => model.id(),
             LanguageModel::Anthropic(model) => model.id(),
+            LanguageModel::Google(model) => model.id(),
             LanguageModel::ZedDotDev(model) => model.id(),
+            LanguageModel::Ollama(model) => model.id(),
+            LanguageModel::OpenAiCompatible(model) => model.id(),
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+#[derive(Clone, Serialize, Deserialize, Debug, Eq, PartialEq)]
 pub struct LanguageModelRequestMessage {
     pub role: Role,
     pub content: String,
@@ -137,7 +166,7 @@ impl LanguageModelRequestMessage {
     }
 }
 
-#[derive(Debug, Default, Serialize)]
+#[derive(Clone, Debug, Default, Serialize)]
 pub struct LanguageModelRequest {
     pub model: LanguageModel,
     pub messages: Vec<LanguageModelRequestMessage>,
@@ -229,6 +258,9 @@ pub fn init(client: Arc<Client>, cx: &mut AppContext) {
     AssistantSettings::register(cx);
     completion_provider::init(client, cx);
     assistant_panel::init(cx);
+    prompt_library::init(cx);
+    commit_message::init(cx);
+    usage::init(cx);
 
     CommandPaletteFilter::update_global(cx, |filter, _cx| {
         filter.hide_namespace(Assistant::NAMESPACE);
@@ -2,11 +2,16 @@ use crate::ambient_context::{AmbientContext, ContextUpdated, RecentBuffer};
 use crate::{
     assistant_settings::{AssistantDockPosition, AssistantSettings, ZedDotDevModel},
     codegen::{self, Codegen, CodegenKind},
-    prompts::generate_content_prompt,
-    Assist, CompletionProvider, CycleMessageRole, InlineAssist, LanguageModel,
-    LanguageModelRequest, LanguageModelRequestMessage, MessageId, MessageMetadata, MessageStatus,
-    QuoteSelection, ResetKey, Role, SavedConversation, SavedConversationMetadata, SavedMessage,
-    Split, ToggleFocus, ToggleHistory, ToggleIncludeConversation,
+    prompt_library::{default_prompts, render_prompt, PromptContext, PromptPicker},
+    prompts::{generate_content_prompt, generate_terminal_assistant_prompt},
+    terminal_inline_assistant::{
+        shell_name, TerminalCodegen, TerminalInlineAssistant, TerminalInlineAssistantEvent,
+    },
+    Assist, CompletionProvider, CycleMessageRole, ExportPrompts, ImportPrompts, InlineAssist,
+    InsertPrompt, LanguageModel, LanguageModelRequest, LanguageModelRequestMessage, MessageId,
+    MessageMetadata, MessageStatus, NewPrompt, QuoteSelection, ResetKey, Role, SavedConversation,
+    SavedConversationMetadata, SavedMessage, ShowUsage, Split, ToggleFocus, ToggleHistory,
+    ToggleIncludeConversation,
 };
 use anyhow::{anyhow, Result};
 use client::telemetry::Telemetry;
@@ -47,9 +52,13 @@ use std::{
     time::{Duration, Instant},
 };
 use telemetry_events::AssistantKind;
+use terminal_view::{terminal_panel::TerminalPanel, TerminalView};
 use theme::ThemeSettings;
 use ui::{popover_menu, prelude::*, ButtonLike, ContextMenu, Tab, TabBar, Tooltip};
-use util::{paths::CONVERSATIONS_DIR, post_inc, ResultExt, TryFutureExt};
+use util::{
+    paths::{CONVERSATIONS_DIR, LOCAL_ASSISTANT_CONTEXT_RELATIVE_PATH},
+    post_inc, ResultExt, TryFutureExt,
+};
 use uuid::Uuid;
 use workspace::{
     dock::{DockPosition, Panel, PanelEvent},
@@ -59,6 +68,8 @@ use workspace::{
 use workspace::{notifications::NotificationId, NewFile};
 
 const MAX_RECENT_BUFFERS: usize = 3;
+/// How many lines of terminal output to include when generating a terminal command.
+const TERMINAL_CONTEXT_LINES: usize = 50;
 
 pub fn init(cx: &mut AppContext) {
     cx.observe_new_views(
@@ -98,6 +109,7 @@ pub struct AssistantPanel {
     next_inline_assist_id: usize,
     pending_inline_assists: HashMap<usize, PendingInlineAssist>,
     pending_inline_assist_ids_by_editor: HashMap<WeakView<Editor>, Vec<usize>>,
+    pending_terminal_assists: HashMap<usize, PendingTerminalAssist>,
     include_conversation_in_next_inline_assist: bool,
     inline_prompt_history: VecDeque<String>,
     _watch_saved_conversations: Task<Result<()>>,
@@ -194,6 +206,7 @@ impl AssistantPanel {
                         next_inline_assist_id: 0,
                         pending_inline_assists: Default::default(),
                         pending_inline_assist_ids_by_editor: Default::default(),
+                        pending_terminal_assists: Default::default(),
                         include_conversation_in_next_inline_assist: false,
                         inline_prompt_history: Default::default(),
                         _watch_saved_conversations,
@@ -273,17 +286,21 @@ impl AssistantPanel {
                     }
                 });
 
-        let show_include_conversation;
-        let active_editor;
-        if let Some(conversation_editor) = conversation_editor {
-            active_editor = conversation_editor;
-            show_include_conversation = false;
+        let target = if let Some(terminal_view) = Self::focused_terminal_view(workspace, cx) {
+            InlineAssistTarget::Terminal(terminal_view)
+        } else if let Some(conversation_editor) = conversation_editor {
+            InlineAssistTarget::Editor {
+                editor: conversation_editor,
+                show_include_conversation: false,
+            }
         } else if let Some(workspace_editor) = workspace
             .active_item(cx)
             .and_then(|item| item.act_as::<Editor>(cx))
         {
-            active_editor = workspace_editor;
-            show_include_conversation = true;
+            InlineAssistTarget::Editor {
+                editor: workspace_editor,
+                show_include_conversation: true,
+            }
         } else {
             return;
         };
@@ -291,7 +308,7 @@ impl AssistantPanel {
 
         if assistant.update(cx, |assistant, cx| assistant.is_authenticated(cx)) {
             assistant.update(cx, |assistant, cx| {
-                assistant.new_inline_assist(&active_editor, &project, show_include_conversation, cx)
+                assistant.deploy_inline_assist(&target, &project, cx)
             });
         } else {
             let assistant = assistant.downgrade();
@@ -301,12 +318,7 @@ impl AssistantPanel {
                     .await?;
                 if assistant.update(&mut cx, |assistant, cx| assistant.is_authenticated(cx))? {
                     assistant.update(&mut cx, |assistant, cx| {
-                        assistant.new_inline_assist(
-                            &active_editor,
-                            &project,
-                            show_include_conversation,
-                            cx,
-                        )
+                        assistant.deploy_inline_assist(&target, &project, cx)
                     })?;
                 } else {
                     workspace.update(&mut cx, |workspace, cx| {
@@ -320,6 +332,38 @@ impl AssistantPanel {
         }
     }
 
+    /// The terminal that has focus, either in the terminal panel or in the center pane.
+    fn focused_terminal_view(
+        workspace: &Workspace,
+        cx: &WindowContext,
+    ) -> Option<View<TerminalView>> {
+        let panel_terminal = workspace
+            .panel::<TerminalPanel>(cx)
+            .and_then(|panel| panel.read(cx).pane().read(cx).active_item());
+        panel_terminal
+            .into_iter()
+            .chain(workspace.active_item(cx))
+            .filter_map(|item| item.downcast::<TerminalView>())
+            .find(|terminal_view| terminal_view.focus_handle(cx).contains_focused(cx))
+    }
+
+    fn deploy_inline_assist(
+        &mut self,
+        target: &InlineAssistTarget,
+        project: &Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        match target {
+            InlineAssistTarget::Editor {
+                editor,
+                show_include_conversation,
+            } => self.new_inline_assist(editor, project, *show_include_conversation, cx),
+            InlineAssistTarget::Terminal(terminal_view) => {
+                self.new_terminal_inline_assist(terminal_view, cx)
+            }
+        }
+    }
+
     fn new_inline_assist(
         &mut self,
         editor: &View<Editor>,
@@ -626,12 +670,7 @@ impl AssistantPanel {
                 .join("/")
         });
 
-        self.inline_prompt_history
-            .retain(|prompt| prompt != user_prompt);
-        self.inline_prompt_history.push_back(user_prompt.into());
-        if self.inline_prompt_history.len() > Self::INLINE_PROMPT_HISTORY_MAX_LEN {
-            self.inline_prompt_history.pop_front();
-        }
+        self.record_inline_prompt(user_prompt);
 
         let codegen = pending_assist.codegen.clone();
         let snapshot = editor.read(cx).buffer().read(cx).snapshot(cx);
@@ -717,6 +756,156 @@ impl AssistantPanel {
         .detach();
     }
 
+    fn record_inline_prompt(&mut self, user_prompt: &str) {
+        self.inline_prompt_history
+            .retain(|prompt| prompt != user_prompt);
+        self.inline_prompt_history.push_back(user_prompt.into());
+        if self.inline_prompt_history.len() > Self::INLINE_PROMPT_HISTORY_MAX_LEN {
+            self.inline_prompt_history.pop_front();
+        }
+    }
+
+    fn new_terminal_inline_assist(
+        &mut self,
+        terminal_view: &View<TerminalView>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let existing_assist = self
+            .pending_terminal_assists
+            .values()
+            .find(|pending_assist| pending_assist.terminal_view == terminal_view.downgrade());
+        if let Some(existing_assist) = existing_assist {
+            existing_assist.inline_assistant.focus_handle(cx).focus(cx);
+            return;
+        }
+
+        let inline_assist_id = post_inc(&mut self.next_inline_assist_id);
+        let terminal = terminal_view.read(cx).terminal().clone();
+        let telemetry = self.telemetry.clone();
+        let codegen = cx.new_model(|_| TerminalCodegen::new(terminal, Some(telemetry)));
+        let inline_assistant = cx.new_view(|cx| {
+            TerminalInlineAssistant::new(
+                inline_assist_id,
+                self.inline_prompt_history.clone(),
+                codegen.clone(),
+                cx,
+            )
+        });
+        terminal_view.update(cx, |terminal_view, cx| {
+            terminal_view.set_bottom_block(inline_assistant.clone().into(), cx)
+        });
+
+        self.pending_terminal_assists.insert(
+            inline_assist_id,
+            PendingTerminalAssist {
+                terminal_view: terminal_view.downgrade(),
+                inline_assistant: inline_assistant.clone(),
+                codegen,
+                _subscriptions: vec![cx.subscribe(
+                    &inline_assistant,
+                    Self::handle_terminal_inline_assistant_event,
+                )],
+            },
+        );
+    }
+
+    fn handle_terminal_inline_assistant_event(
+        &mut self,
+        inline_assistant: View<TerminalInlineAssistant>,
+        event: &TerminalInlineAssistantEvent,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let assist_id = inline_assistant.read(cx).id;
+        match event {
+            TerminalInlineAssistantEvent::Confirmed { prompt } => {
+                self.confirm_terminal_inline_assist(assist_id, prompt, cx);
+            }
+            TerminalInlineAssistantEvent::Executed => {
+                if let Some(pending_assist) = self.pending_terminal_assists.get(&assist_id) {
+                    pending_assist
+                        .codegen
+                        .update(cx, |codegen, cx| codegen.complete(cx));
+                }
+                self.finish_terminal_inline_assist(assist_id, false, cx);
+            }
+            TerminalInlineAssistantEvent::Canceled => {
+                self.finish_terminal_inline_assist(assist_id, true, cx);
+            }
+        }
+    }
+
+    fn confirm_terminal_inline_assist(
+        &mut self,
+        inline_assist_id: usize,
+        user_prompt: &str,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(pending_assist) = self.pending_terminal_assists.get(&inline_assist_id) else {
+            return;
+        };
+        let Some(terminal_view) = pending_assist.terminal_view.upgrade() else {
+            self.finish_terminal_inline_assist(inline_assist_id, false, cx);
+            return;
+        };
+        let codegen = pending_assist.codegen.clone();
+
+        let terminal = terminal_view.read(cx).terminal().read(cx);
+        let shell = shell_name(terminal, cx);
+        let working_directory = terminal
+            .get_cwd()
+            .map(|working_directory| working_directory.to_string_lossy().into_owned());
+        let latest_output = terminal.last_lines(TERMINAL_CONTEXT_LINES);
+        let prompt = generate_terminal_assistant_prompt(
+            user_prompt,
+            shell.as_deref(),
+            working_directory.as_deref(),
+            &latest_output,
+        );
+
+        self.record_inline_prompt(user_prompt);
+
+        let request = LanguageModelRequest {
+            model: self.model.clone(),
+            messages: vec![LanguageModelRequestMessage {
+                role: Role::User,
+                content: prompt,
+            }],
+            stop: Vec::new(),
+            temperature: 1.0,
+        };
+        codegen.update(cx, |codegen, cx| codegen.start(request, cx));
+    }
+
+    fn finish_terminal_inline_assist(
+        &mut self,
+        assist_id: usize,
+        undo: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(pending_assist) = self.pending_terminal_assists.remove(&assist_id) else {
+            return;
+        };
+
+        if undo {
+            pending_assist
+                .codegen
+                .update(cx, |codegen, cx| codegen.undo(cx));
+        }
+
+        if let Some(terminal_view) = pending_assist.terminal_view.upgrade() {
+            let was_focused = pending_assist
+                .inline_assistant
+                .focus_handle(cx)
+                .contains_focused(cx);
+            terminal_view.update(cx, |terminal_view, cx| {
+                terminal_view.clear_bottom_block(cx);
+                if was_focused {
+                    terminal_view.focus_handle(cx).focus(cx);
+                }
+            });
+        }
+    }
+
     fn update_highlights_for_editor(&self, editor: &View<Editor>, cx: &mut ViewContext<Self>) {
         let mut background_ranges = Vec::new();
         let mut foreground_ranges = Vec::new();
@@ -821,6 +1010,11 @@ impl AssistantPanel {
                 anthropic::Model::Claude3Sonnet => anthropic::Model::Claude3Haiku,
                 anthropic::Model::Claude3Haiku => anthropic::Model::Claude3Opus,
             }),
+            LanguageModel::Google(model) => LanguageModel::Google(match &model {
+                google_ai::Model::GeminiPro => google_ai::Model::Gemini15Pro,
+                google_ai::Model::Gemini15Pro => google_ai::Model::Gemini15Flash,
+                google_ai::Model::Gemini15Flash => google_ai::Model::GeminiPro,
+            }),
             LanguageModel::ZedDotDev(model) => LanguageModel::ZedDotDev(match &model {
                 ZedDotDevModel::Gpt3Point5Turbo => ZedDotDevModel::Gpt4,
                 ZedDotDevModel::Gpt4 => ZedDotDevModel::Gpt4Turbo,
@@ -836,6 +1030,17 @@ impl AssistantPanel {
                 }
                 ZedDotDevModel::Custom(_) => ZedDotDevModel::Gpt3Point5Turbo,
             }),
+            LanguageModel::Ollama(_) | LanguageModel::OpenAiCompatible(_) => {
+                let available_models = CompletionProvider::global(cx).available_models();
+                let next_index = available_models
+                    .iter()
+                    .position(|model| model.id() == self.model.id())
+                    .map_or(0, |index| (index + 1) % available_models.len());
+                match available_models.into_iter().nth(next_index) {
+                    Some(model) => model,
+                    None => return,
+                }
+            }
         };
 
         self.set_model(next_model, cx);
@@ -936,6 +1141,37 @@ impl AssistantPanel {
             .detach_and_log_err(cx);
     }
 
+    /// Inserts text at the cursor of the active conversation, starting a new one if needed,
+    /// and focuses the panel.
+    pub(crate) fn insert_text(
+        workspace: &mut Workspace,
+        text: &str,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some(panel) = workspace.panel::<AssistantPanel>(cx) else {
+            return;
+        };
+
+        // Activate the panel
+        if !panel.focus_handle(cx).contains_focused(cx) {
+            workspace.toggle_panel_focus::<AssistantPanel>(cx);
+        }
+
+        panel.update(cx, |panel, cx| {
+            if let Some(conversation) = panel
+                .active_conversation_editor()
+                .cloned()
+                .or_else(|| panel.new_conversation(cx))
+            {
+                conversation.update(cx, |conversation, cx| {
+                    conversation
+                        .editor
+                        .update(cx, |editor, cx| editor.insert(text, cx))
+                });
+            };
+        });
+    }
+
     fn active_conversation_editor(&self) -> Option<&View<ConversationEditor>> {
         Some(&self.active_conversation_editor.as_ref()?.editor)
     }
@@ -968,6 +1204,34 @@ impl AssistantPanel {
                         let assistant = assistant.clone();
                         move |cx| assistant.update(cx, |assistant, cx| assistant.show_history(cx))
                     })
+                    .entry("New Prompt", Some(Box::new(NewPrompt)), {
+                        let assistant = assistant.clone();
+                        move |cx| {
+                            assistant.focus_handle(cx).dispatch_action(&NewPrompt, cx);
+                        }
+                    })
+                    .entry("Import Prompts", Some(Box::new(ImportPrompts)), {
+                        let assistant = assistant.clone();
+                        move |cx| {
+                            assistant
+                                .focus_handle(cx)
+                                .dispatch_action(&ImportPrompts, cx);
+                        }
+                    })
+                    .entry("Export Prompts", Some(Box::new(ExportPrompts)), {
+                        let assistant = assistant.clone();
+                        move |cx| {
+                            assistant
+                                .focus_handle(cx)
+                                .dispatch_action(&ExportPrompts, cx);
+                        }
+                    })
+                    .entry("Usage", Some(Box::new(ShowUsage)), {
+                        let assistant = assistant.clone();
+                        move |cx| {
+                            assistant.focus_handle(cx).dispatch_action(&ShowUsage, cx);
+                        }
+                    })
                 })
                 .into()
             })
@@ -1005,6 +1269,16 @@ impl AssistantPanel {
                                 .ok();
                         }
                     })
+                    .entry("Insert Prompt", Some(Box::new(InsertPrompt)), {
+                        let workspace = workspace.clone();
+                        move |cx| {
+                            workspace
+                                .update(cx, |workspace, cx| {
+                                    PromptPicker::toggle(workspace, &InsertPrompt, cx)
+                                })
+                                .ok();
+                        }
+                    })
                 })
                 .into()
             })
@@ -1540,6 +1814,17 @@ impl Conversation {
         }
     }
 
+    fn update_project_instructions(
+        &mut self,
+        fs: Arc<dyn Fs>,
+        project: WeakModel<Project>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.ambient_context
+            .project_instructions
+            .update(fs, project, cx);
+    }
+
     fn set_recent_buffers(
         &mut self,
         buffers: impl IntoIterator<Item = Model<Buffer>>,
@@ -1660,7 +1945,7 @@ impl Conversation {
             }
 
             let request = self.to_completion_request(cx);
-            let stream = CompletionProvider::global(cx).complete(request);
+            let stream = CompletionProvider::global(cx).complete(request, cx);
             let assistant_message = self
                 .insert_message_after(last_message_id, Role::Assistant, MessageStatus::Pending, cx)
                 .unwrap();
@@ -1758,11 +2043,13 @@ impl Conversation {
     }
 
     fn to_completion_request(&self, cx: &mut ModelContext<Conversation>) -> LanguageModelRequest {
+        let project_instructions = self.ambient_context.project_instructions.to_message();
         let recent_buffers_context = self.ambient_context.recent_buffers.to_message();
         let current_project_context = self.ambient_context.current_project.to_message();
 
-        let messages = recent_buffers_context
+        let messages = project_instructions
             .into_iter()
+            .chain(recent_buffers_context)
             .chain(current_project_context)
             .chain(
                 self.messages(cx)
@@ -1792,6 +2079,21 @@ impl Conversation {
         }
     }
 
+    /// Turns the first message into a system message with the given text, followed by an empty
+    /// user message.
+    fn insert_system_prompt(&mut self, text: &str, cx: &mut ModelContext<Self>) {
+        let Some(first_message_id) = self.message_anchors.first().map(|message| message.id) else {
+            return;
+        };
+
+        self.buffer
+            .update(cx, |buffer, cx| buffer.edit([(0..0, text)], None, cx));
+        if let Some(metadata) = self.messages_metadata.get_mut(&first_message_id) {
+            metadata.role = Role::System;
+        }
+        self.insert_message_after(first_message_id, Role::User, MessageStatus::Done, cx);
+    }
+
     fn insert_message_after(
         &mut self,
         message_id: MessageId,
@@ -1966,7 +2268,7 @@ impl Conversation {
                 temperature: 1.0,
             };
 
-            let stream = CompletionProvider::global(cx).complete(request);
+            let stream = CompletionProvider::global(cx).complete(request, cx);
             self.pending_summary = cx.spawn(|this, mut cx| {
                 async move {
                     let mut messages = stream.await?;
@@ -2161,7 +2463,24 @@ impl ConversationEditor {
         let telemetry = workspace.read(cx).client().telemetry().clone();
         let conversation =
             cx.new_model(|cx| Conversation::new(model, language_registry, Some(telemetry), cx));
-        Self::for_conversation(conversation, fs, workspace, cx)
+
+        let default_prompts = default_prompts(cx);
+        if !default_prompts.is_empty() {
+            let context = PromptContext::for_workspace(workspace.read(cx), cx);
+            let system_prompt = default_prompts
+                .iter()
+                .map(|prompt| render_prompt(prompt, &context))
+                .collect::<Vec<_>>()
+                .join("\n\n");
+            conversation.update(cx, |conversation, cx| {
+                conversation.insert_system_prompt(&system_prompt, cx)
+            });
+        }
+
+        let this = Self::for_conversation(conversation, fs, workspace, cx);
+        this.editor
+            .update(cx, |editor, cx| editor.move_to_end(&Default::default(), cx));
+        this
     }
 
     fn for_conversation(
@@ -2183,6 +2502,7 @@ impl ConversationEditor {
             cx.subscribe(&conversation, Self::handle_conversation_event),
             cx.subscribe(&editor, Self::handle_editor_event),
             cx.subscribe(&workspace, Self::handle_workspace_event),
+            cx.subscribe(workspace.read(cx).project(), Self::handle_project_event),
         ];
 
         let mut this = Self {
@@ -2195,6 +2515,7 @@ impl ConversationEditor {
             _subscriptions,
         };
         this.update_recent_editors(cx);
+        this.update_project_instructions(cx);
         this.update_message_headers(cx);
         this
     }
@@ -2335,6 +2656,33 @@ impl ConversationEditor {
         }
     }
 
+    fn handle_project_event(
+        &mut self,
+        _: Model<Project>,
+        event: &project::Event,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let project::Event::WorktreeUpdatedEntries(_, changes) = event {
+            if changes
+                .iter()
+                .any(|(path, _, _)| path.as_ref() == *LOCAL_ASSISTANT_CONTEXT_RELATIVE_PATH)
+            {
+                self.update_project_instructions(cx);
+            }
+        }
+    }
+
+    fn update_project_instructions(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let project = workspace.read(cx).project().downgrade();
+        let fs = self.fs.clone();
+        self.conversation.update(cx, |conversation, cx| {
+            conversation.update_project_instructions(fs, project, cx)
+        });
+    }
+
     fn update_recent_editors(&mut self, cx: &mut ViewContext<ConversationEditor>) {
         let Some(workspace) = self.workspace.upgrade() else {
             return;
@@ -2596,25 +2944,14 @@ impl ConversationEditor {
             })
         };
 
-        // Activate the panel
-        if !panel.focus_handle(cx).contains_focused(cx) {
-            workspace.toggle_panel_focus::<AssistantPanel>(cx);
-        }
-
-        if let Some(text) = text {
-            panel.update(cx, |panel, cx| {
-                if let Some(conversation) = panel
-                    .active_conversation_editor()
-                    .cloned()
-                    .or_else(|| panel.new_conversation(cx))
-                {
-                    conversation.update(cx, |conversation, cx| {
-                        conversation
-                            .editor
-                            .update(cx, |editor, cx| editor.insert(&text, cx))
-                    });
-                };
-            });
+        match text {
+            Some(text) => AssistantPanel::insert_text(workspace, &text, cx),
+            None => {
+                // Activate the panel
+                if !panel.focus_handle(cx).contains_focused(cx) {
+                    workspace.toggle_panel_focus::<AssistantPanel>(cx);
+                }
+            }
         }
     }
 
@@ -3009,6 +3346,21 @@ struct PendingInlineAssist {
     project: WeakModel<Project>,
 }
 
+struct PendingTerminalAssist {
+    terminal_view: WeakView<TerminalView>,
+    inline_assistant: View<TerminalInlineAssistant>,
+    codegen: Model<TerminalCodegen>,
+    _subscriptions: Vec<Subscription>,
+}
+
+enum InlineAssistTarget {
+    Editor {
+        editor: View<Editor>,
+        show_include_conversation: bool,
+    },
+    Terminal(View<TerminalView>),
+}
+
 fn merge_ranges(ranges: &mut Vec<Range<Anchor>>, buffer: &MultiBufferSnapshot) {
     ranges.sort_unstable_by(|a, b| {
         a.start
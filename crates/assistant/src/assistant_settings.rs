@@ -1,7 +1,9 @@
-use std::fmt;
+use std::{collections::BTreeMap, fmt};
 
 pub use anthropic::Model as AnthropicModel;
+pub use google_ai::Model as GoogleModel;
 use gpui::Pixels;
+pub use ollama::Model as OllamaModel;
 pub use open_ai::Model as OpenAiModel;
 use schemars::{
     schema::{InstanceType, Metadata, Schema, SchemaObject},
@@ -136,6 +138,60 @@ impl ZedDotDevModel {
     }
 }
 
+/// A model served by a self-hosted provider, as listed in the settings.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct AvailableModel {
+    /// The model's id, as sent to the provider's API.
+    pub name: String,
+    /// The name shown in the assistant panel. Defaults to `name`.
+    #[serde(default)]
+    pub display_name: Option<String>,
+    /// The model's context length, in tokens.
+    pub max_tokens: usize,
+    /// The tiktoken model whose tokenizer approximates this model's, e.g. `gpt-4`.
+    ///
+    /// When unset, tokens are estimated from the length of the messages.
+    #[serde(default)]
+    pub tokenizer: Option<String>,
+}
+
+impl AvailableModel {
+    pub fn id(&self) -> &str {
+        &self.name
+    }
+
+    pub fn display_name(&self) -> &str {
+        self.display_name.as_deref().unwrap_or(&self.name)
+    }
+
+    pub fn max_token_count(&self) -> usize {
+        self.max_tokens
+    }
+}
+
+/// The price of a model, used to estimate the cost of the assistant's requests.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct ModelPrice {
+    /// The price in dollars of a million prompt tokens.
+    pub prompt: f64,
+    /// The price in dollars of a million completion tokens.
+    pub completion: f64,
+}
+
+/// How the assistant's token usage is priced and limited.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+#[serde(default)]
+pub struct UsageSettings {
+    /// The prices of the models, keyed by provider and model id, e.g. `openai/gpt-4o`.
+    ///
+    /// Requests to models without a price are recorded without a cost.
+    pub prices: BTreeMap<String, ModelPrice>,
+    /// The estimated cost in dollars after which a warning is shown, each month.
+    pub soft_monthly_budget: Option<f64>,
+    /// The estimated cost in dollars after which requests are refused, each month.
+    pub hard_monthly_budget: Option<f64>,
+}
+
 #[derive(Copy, Clone, Default, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum AssistantDockPosition {
@@ -171,6 +227,38 @@ pub enum AssistantProvider {
         #[serde(default)]
         low_speed_timeout_in_seconds: Option<u64>,
     },
+    #[serde(rename = "google")]
+    Google {
+        #[serde(default)]
+        default_model: GoogleModel,
+        #[serde(default = "google_ai_api_url")]
+        api_url: String,
+    },
+    #[serde(rename = "ollama")]
+    Ollama {
+        /// The model to use for new conversations. Defaults to the first model Ollama reports.
+        #[serde(default)]
+        default_model: Option<String>,
+        #[serde(default = "ollama_api_url")]
+        api_url: String,
+        #[serde(default)]
+        low_speed_timeout_in_seconds: Option<u64>,
+        /// Models to offer in addition to the ones pulled into Ollama, or to
+        /// override their context length.
+        #[serde(default)]
+        available_models: Vec<AvailableModel>,
+    },
+    #[serde(rename = "openai_compatible")]
+    OpenAiCompatible {
+        /// The model to use for new conversations. Defaults to the first available model.
+        #[serde(default)]
+        default_model: Option<String>,
+        api_url: String,
+        #[serde(default)]
+        low_speed_timeout_in_seconds: Option<u64>,
+        #[serde(default)]
+        available_models: Vec<AvailableModel>,
+    },
 }
 
 impl Default for AssistantProvider {
@@ -189,6 +277,14 @@ fn anthropic_api_url() -> String {
     anthropic::ANTHROPIC_API_URL.to_string()
 }
 
+fn google_ai_api_url() -> String {
+    google_ai::API_URL.to_string()
+}
+
+fn ollama_api_url() -> String {
+    ollama::OLLAMA_API_URL.to_string()
+}
+
 #[derive(Default, Debug, Deserialize, Serialize)]
 pub struct AssistantSettings {
     pub enabled: bool,
@@ -197,6 +293,7 @@ pub struct AssistantSettings {
     pub default_width: Pixels,
     pub default_height: Pixels,
     pub provider: AssistantProvider,
+    pub usage: UsageSettings,
 }
 
 /// Assistant panel settings
@@ -254,6 +351,7 @@ impl AssistantSettingsContent {
                         }
                     })
                 },
+                usage: None,
             },
         }
     }
@@ -288,6 +386,7 @@ impl Default for VersionedAssistantSettingsContent {
             default_width: None,
             default_height: None,
             provider: None,
+            usage: None,
         })
     }
 }
@@ -316,9 +415,12 @@ pub struct AssistantSettingsContentV1 {
     default_height: Option<f32>,
     /// The provider of the assistant service.
     ///
-    /// This can either be the internal `zed.dev` service or an external `openai` service,
-    /// each with their respective default models and configurations.
+    /// This can either be the internal `zed.dev` service, an external `openai`, `anthropic`
+    /// or `google` service, or a self-hosted `ollama` or `openai_compatible` server, each with their
+    /// respective default models and configurations.
     provider: Option<AssistantProvider>,
+    /// Prices and monthly budgets for the assistant's requests.
+    usage: Option<UsageSettings>,
 }
 
 #[derive(Clone, Serialize, Deserialize, JsonSchema, Debug)]
@@ -404,6 +506,9 @@ impl Settings for AssistantSettings {
                     }
                 }
             }
+            if let Some(usage) = value.usage.clone() {
+                settings.usage = usage;
+            }
         }
 
         Ok(settings)
@@ -504,5 +609,102 @@ mod tests {
                 default_model: ZedDotDevModel::Custom("custom".into())
             }
         );
+
+        cx.update_global::<SettingsStore, _>(|store, cx| {
+            store
+                .set_user_settings(
+                    r#"{
+                        "assistant": {
+                            "version": "1",
+                            "provider": {
+                                "name": "google",
+                                "default_model": "gemini-1.5-flash"
+                            }
+                        }
+                    }"#,
+                    cx,
+                )
+                .unwrap();
+        });
+        assert_eq!(
+            AssistantSettings::get_global(cx).provider,
+            AssistantProvider::Google {
+                default_model: GoogleModel::Gemini15Flash,
+                api_url: google_ai_api_url(),
+            }
+        );
+
+        // Self-hosted providers list their models explicitly.
+        cx.update_global::<SettingsStore, _>(|store, cx| {
+            store
+                .set_user_settings(
+                    r#"{
+                        "assistant": {
+                            "version": "1",
+                            "provider": {
+                                "name": "ollama",
+                                "available_models": [
+                                    { "name": "llama3:70b", "max_tokens": 8192 }
+                                ]
+                            }
+                        }
+                    }"#,
+                    cx,
+                )
+                .unwrap();
+        });
+        assert_eq!(
+            AssistantSettings::get_global(cx).provider,
+            AssistantProvider::Ollama {
+                default_model: None,
+                api_url: ollama_api_url(),
+                low_speed_timeout_in_seconds: None,
+                available_models: vec![AvailableModel {
+                    name: "llama3:70b".into(),
+                    display_name: None,
+                    max_tokens: 8192,
+                    tokenizer: None,
+                }],
+            }
+        );
+        cx.update_global::<SettingsStore, _>(|store, cx| {
+            store
+                .set_user_settings(
+                    r#"{
+                        "assistant": {
+                            "version": "1",
+                            "provider": {
+                                "name": "openai_compatible",
+                                "api_url": "http://localhost:8080/v1",
+                                "default_model": "mistral-7b",
+                                "available_models": [
+                                    {
+                                        "name": "mistral-7b",
+                                        "display_name": "Mistral",
+                                        "max_tokens": 32768,
+                                        "tokenizer": "gpt-4"
+                                    }
+                                ]
+                            }
+                        }
+                    }"#,
+                    cx,
+                )
+                .unwrap();
+        });
+        assert_eq!(
+            AssistantSettings::get_global(cx).provider,
+            AssistantProvider::OpenAiCompatible {
+                default_model: Some("mistral-7b".into()),
+                api_url: "http://localhost:8080/v1".into(),
+                low_speed_timeout_in_seconds: None,
+                available_models: vec![AvailableModel {
+                    name: "mistral-7b".into(),
+                    display_name: Some("Mistral".into()),
+                    max_tokens: 32768,
+                    tokenizer: Some("gpt-4".into()),
+                }],
+            }
+        );
     }
 }
@@ -112,7 +112,7 @@ impl Codegen {
             .unwrap_or_else(|| snapshot.indent_size_for_line(MultiBufferRow(selection_start.row)));
 
         let model_telemetry_id = prompt.model.telemetry_id();
-        let response = CompletionProvider::global(cx).complete(prompt);
+        let response = CompletionProvider::global(cx).complete(prompt, cx);
         let telemetry = self.telemetry.clone();
         self.generation = cx.spawn(|this, mut cx| {
             async move {
@@ -387,7 +387,7 @@ fn strip_invalid_spans_from_codeblock(
 mod tests {
     use std::sync::Arc;
 
-    use crate::FakeCompletionProvider;
+    use crate::{assistant_settings::AssistantSettings, FakeCompletionProvider};
 
     use super::*;
     use futures::stream::{self};
@@ -399,7 +399,7 @@ mod tests {
     };
     use rand::prelude::*;
     use serde::Serialize;
-    use settings::SettingsStore;
+    use settings::{Settings, SettingsStore};
 
     #[derive(Serialize)]
     pub struct DummyCompletionRequest {
@@ -412,6 +412,7 @@ mod tests {
         cx.set_global(cx.update(SettingsStore::test));
         cx.set_global(CompletionProvider::Fake(provider.clone()));
         cx.update(language_settings::init);
+        cx.update(AssistantSettings::register);
 
         let text = indoc! {"
             fn main() {
@@ -474,6 +475,7 @@ mod tests {
         cx.set_global(CompletionProvider::Fake(provider.clone()));
         cx.set_global(cx.update(SettingsStore::test));
         cx.update(language_settings::init);
+        cx.update(AssistantSettings::register);
 
         let text = indoc! {"
             fn main() {
@@ -533,6 +535,7 @@ mod tests {
         cx.set_global(CompletionProvider::Fake(provider.clone()));
         cx.set_global(cx.update(SettingsStore::test));
         cx.update(language_settings::init);
+        cx.update(AssistantSettings::register);
 
         let text = concat!(
             "fn main() {\n",
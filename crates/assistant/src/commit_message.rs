@@ -0,0 +1,303 @@
+use crate::{
+    prompts::{generate_commit_message_prompt, summarize_file_diff_prompt},
+    CompletionProvider, GenerateCommitMessage, LanguageModel, LanguageModelRequest,
+    LanguageModelRequestMessage, Role,
+};
+use anyhow::{anyhow, Result};
+use editor::Editor;
+use futures::StreamExt;
+use gpui::{AppContext, AsyncAppContext, View, ViewContext, VisualContext};
+use std::fmt::Write;
+use util::ResultExt;
+use workspace::{notifications::DetachAndPromptErr, Workspace};
+
+/// Diffs larger than this are summarized file by file without first counting their tokens.
+const MAX_DIFF_LEN: usize = 256 * 1024;
+/// The number of files that are summarized individually when the diff doesn't fit in the model's
+/// context. The remaining files are only listed by name.
+const MAX_SUMMARIZED_FILES: usize = 20;
+/// A conservative estimate, as diffs are dense in punctuation and tokenize poorly.
+const BYTES_PER_TOKEN: usize = 2;
+/// The number of tokens left for the commit message itself.
+const RESPONSE_TOKENS: usize = 1024;
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(
+        |workspace: &mut Workspace, _cx: &mut ViewContext<Workspace>| {
+            workspace.register_action(generate_commit_message);
+        },
+    )
+    .detach();
+}
+
+/// Drafts a commit message for the staged changes of the active repository, or for the unstaged
+/// changes if nothing is staged.
+///
+/// The message is inserted at the top of the active editor when it's editing a `COMMIT_EDITMSG`
+/// file, as it is when Zed is used as git's editor, and in a new buffer otherwise.
+fn generate_commit_message(
+    workspace: &mut Workspace,
+    _: &GenerateCommitMessage,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let active_item = workspace.active_item(cx);
+    let active_project_path = active_item.as_ref().and_then(|item| item.project_path(cx));
+    let commit_message_editor = active_item
+        .and_then(|item| item.act_as::<Editor>(cx))
+        .filter(|_| {
+            active_project_path
+                .as_ref()
+                .map_or(false, |path| path.path.ends_with("COMMIT_EDITMSG"))
+        });
+
+    let project = workspace.project().clone();
+    let repository = active_project_path
+        .and_then(|path| project.read(cx).get_repo(&path, cx))
+        .or_else(|| project.read(cx).get_first_worktree_root_repo(cx));
+    let provider = CompletionProvider::global(cx);
+    let is_authenticated = provider.is_authenticated();
+    let model = provider.default_model();
+
+    cx.spawn(|workspace, mut cx| async move {
+        if !is_authenticated {
+            return Err(anyhow!(
+                "The assistant needs to be configured before it can write commit messages"
+            ));
+        }
+        let repository =
+            repository.ok_or_else(|| anyhow!("There is no git repository in this project"))?;
+
+        let diff = cx
+            .background_executor()
+            .spawn(async move {
+                let repository = repository.lock();
+                let staged_diff = repository.diff(true)?;
+                if staged_diff.trim().is_empty() {
+                    repository.diff(false)
+                } else {
+                    Ok(staged_diff)
+                }
+            })
+            .await?;
+        if diff.trim().is_empty() {
+            return Err(anyhow!("There are no changes to describe"));
+        }
+
+        let prompt = commit_message_prompt(&diff, &model, &mut cx).await?;
+        let message = complete(&model, prompt, &mut cx).await?;
+        let message = format!("{}\n", message.trim());
+
+        if let Some(editor) = commit_message_editor {
+            editor.update(&mut cx, |editor, cx| {
+                editor.change_selections(None, cx, |selections| selections.select_ranges([0..0]));
+                editor.insert(&message, cx);
+            })?;
+        } else {
+            let buffer = project
+                .update(&mut cx, |project, cx| project.create_buffer(cx))?
+                .await?;
+            buffer.update(&mut cx, |buffer, cx| buffer.set_text(message, cx))?;
+            workspace.update(&mut cx, |workspace, cx| {
+                let editor: View<Editor> =
+                    cx.new_view(|cx| Editor::for_buffer(buffer, Some(project), cx));
+                workspace.add_item_to_active_pane(Box::new(editor), None, cx);
+            })?;
+        }
+
+        anyhow::Ok(())
+    })
+    .detach_and_prompt_err("Failed to generate a commit message", cx, |error, _| {
+        Some(error.to_string())
+    });
+}
+
+/// Builds the prompt for the commit message, summarizing each file's changes separately when the
+/// diff is too large for the model's context.
+async fn commit_message_prompt(
+    diff: &str,
+    model: &LanguageModel,
+    cx: &mut AsyncAppContext,
+) -> Result<String> {
+    let max_tokens = model.max_token_count().saturating_sub(RESPONSE_TOKENS);
+    if diff.len() <= MAX_DIFF_LEN {
+        let prompt = generate_commit_message_prompt(diff, false);
+        let request = request(model, prompt.clone());
+        let token_count = cx
+            .update(|cx| CompletionProvider::global(cx).count_tokens(request, cx))?
+            .await;
+        if token_count
+            .log_err()
+            .map_or(false, |count| count <= max_tokens)
+        {
+            return Ok(prompt);
+        }
+    }
+
+    let max_file_diff_len = max_tokens * BYTES_PER_TOKEN;
+    let file_diffs = split_diff_by_file(diff);
+    let mut summaries = String::new();
+    for (ix, file_diff) in file_diffs.iter().enumerate() {
+        if ix == MAX_SUMMARIZED_FILES {
+            let remaining_paths = file_diffs[ix..]
+                .iter()
+                .map(|file_diff| file_diff.path)
+                .collect::<Vec<_>>();
+            writeln!(
+                summaries,
+                "- Other changed files: {}",
+                remaining_paths.join(", ")
+            )
+            .unwrap();
+            break;
+        }
+
+        let prompt = summarize_file_diff_prompt(
+            file_diff.path,
+            truncate_diff(file_diff.diff, max_file_diff_len),
+        );
+        let summary = complete(model, prompt, cx).await?;
+        writeln!(summaries, "- {}: {}", file_diff.path, summary.trim()).unwrap();
+    }
+
+    Ok(generate_commit_message_prompt(&summaries, true))
+}
+
+fn request(model: &LanguageModel, prompt: String) -> LanguageModelRequest {
+    LanguageModelRequest {
+        model: model.clone(),
+        messages: vec![LanguageModelRequestMessage {
+            role: Role::User,
+            content: prompt,
+        }],
+        stop: vec![],
+        temperature: 1.0,
+    }
+}
+
+async fn complete(
+    model: &LanguageModel,
+    prompt: String,
+    cx: &mut AsyncAppContext,
+) -> Result<String> {
+    let request = request(model, prompt);
+    let mut chunks = cx
+        .update(|cx| CompletionProvider::global(cx).complete(request, cx))?
+        .await?;
+    let mut response = String::new();
+    while let Some(chunk) = chunks.next().await {
+        response.push_str(&chunk?);
+    }
+    Ok(strip_code_fence(&response).to_string())
+}
+
+/// Models sometimes wrap their answer in a code block despite being asked not to.
+fn strip_code_fence(response: &str) -> &str {
+    let response = response.trim();
+    match response
+        .strip_prefix("```")
+        .and_then(|response| response.strip_suffix("```"))
+    {
+        Some(fenced) => fenced
+            .split_once('\n')
+            .map_or(fenced, |(_language, body)| body)
+            .trim(),
+        None => response,
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct FileDiff<'a> {
+    path: &'a str,
+    diff: &'a str,
+}
+
+/// Splits the output of `git diff` into the diffs of each file.
+fn split_diff_by_file(diff: &str) -> Vec<FileDiff> {
+    let mut file_diffs = Vec::new();
+    let mut start = None;
+    let mut offset = 0;
+    for line in diff.split_inclusive('\n') {
+        if line.starts_with("diff --git ") {
+            if let Some(start) = start {
+                file_diffs.push(parse_file_diff(&diff[start..offset]));
+            }
+            start = Some(offset);
+        }
+        offset += line.len();
+    }
+    if let Some(start) = start {
+        file_diffs.push(parse_file_diff(&diff[start..]));
+    }
+    file_diffs
+}
+
+fn parse_file_diff(diff: &str) -> FileDiff {
+    let header = diff.lines().next().unwrap_or_default();
+    let path = header
+        .rsplit_once(" b/")
+        .map_or(header, |(_, path)| path.trim());
+    FileDiff { path, diff }
+}
+
+fn truncate_diff(diff: &str, max_len: usize) -> &str {
+    if diff.len() <= max_len {
+        return diff;
+    }
+    let mut end = max_len;
+    while !diff.is_char_boundary(end) {
+        end -= 1;
+    }
+    &diff[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_diff_by_file() {
+        let diff = "\
+diff --git a/src/main.rs b/src/main.rs
+index 1111111..2222222 100644
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1 +1 @@
+-fn main() {}
++fn main() { println!(\"hi\"); }
+diff --git a/README.md b/README.md
+new file mode 100644
+--- /dev/null
++++ b/README.md
+@@ -0,0 +1 @@
++# Hello
+";
+        let file_diffs = split_diff_by_file(diff);
+        assert_eq!(
+            file_diffs
+                .iter()
+                .map(|file_diff| file_diff.path)
+                .collect::<Vec<_>>(),
+            ["src/main.rs", "README.md"]
+        );
+        assert!(file_diffs[0]
+            .diff
+            .ends_with("+fn main() { println!(\"hi\"); }\n"));
+        assert!(file_diffs[1].diff.starts_with("diff --git a/README.md"));
+        assert!(split_diff_by_file("").is_empty());
+    }
+
+    #[test]
+    fn test_truncate_diff() {
+        assert_eq!(truncate_diff("+héllo", 3), "+h");
+        assert_eq!(truncate_diff("+hello", 10), "+hello");
+    }
+
+    #[test]
+    fn test_strip_code_fence() {
+        assert_eq!(
+            strip_code_fence("```text\nfix(editor): handle empty buffers\n```"),
+            "fix(editor): handle empty buffers"
+        );
+        assert_eq!(strip_code_fence("  feat: add x\n"), "feat: add x");
+    }
+}
@@ -1,17 +1,24 @@
 mod anthropic;
 #[cfg(test)]
 mod fake;
+mod google;
+mod ollama;
 mod open_ai;
+mod open_ai_compatible;
 mod zed;
 
 pub use anthropic::*;
 #[cfg(test)]
 pub use fake::*;
+pub use google::*;
+pub use ollama::*;
 pub use open_ai::*;
+pub use open_ai_compatible::*;
 pub use zed::*;
 
 use crate::{
     assistant_settings::{AssistantProvider, AssistantSettings},
+    usage::track_usage,
     LanguageModel, LanguageModelRequest,
 };
 use anyhow::Result;
@@ -55,6 +62,41 @@ pub fn init(client: Arc<Client>, cx: &mut AppContext) {
             low_speed_timeout_in_seconds.map(Duration::from_secs),
             settings_version,
         )),
+        AssistantProvider::Google {
+            default_model,
+            api_url,
+        } => CompletionProvider::Google(GoogleCompletionProvider::new(
+            default_model.clone(),
+            api_url.clone(),
+            client.http_client(),
+            settings_version,
+        )),
+        AssistantProvider::Ollama {
+            default_model,
+            api_url,
+            low_speed_timeout_in_seconds,
+            available_models,
+        } => CompletionProvider::Ollama(OllamaCompletionProvider::new(
+            default_model.clone(),
+            api_url.clone(),
+            available_models.clone(),
+            client.http_client(),
+            low_speed_timeout_in_seconds.map(Duration::from_secs),
+            settings_version,
+        )),
+        AssistantProvider::OpenAiCompatible {
+            default_model,
+            api_url,
+            low_speed_timeout_in_seconds,
+            available_models,
+        } => CompletionProvider::OpenAiCompatible(OpenAiCompatibleCompletionProvider::new(
+            default_model.clone(),
+            api_url.clone(),
+            available_models.clone(),
+            client.http_client(),
+            low_speed_timeout_in_seconds.map(Duration::from_secs),
+            settings_version,
+        )),
     };
     cx.set_global(provider);
 
@@ -92,6 +134,49 @@ pub fn init(client: Arc<Client>, cx: &mut AppContext) {
                         settings_version,
                     );
                 }
+                (
+                    CompletionProvider::Google(provider),
+                    AssistantProvider::Google {
+                        default_model,
+                        api_url,
+                    },
+                ) => {
+                    provider.update(default_model.clone(), api_url.clone(), settings_version);
+                }
+                (
+                    CompletionProvider::Ollama(provider),
+                    AssistantProvider::Ollama {
+                        default_model,
+                        api_url,
+                        low_speed_timeout_in_seconds,
+                        available_models,
+                    },
+                ) => {
+                    provider.update(
+                        default_model.clone(),
+                        api_url.clone(),
+                        available_models.clone(),
+                        low_speed_timeout_in_seconds.map(Duration::from_secs),
+                        settings_version,
+                    );
+                }
+                (
+                    CompletionProvider::OpenAiCompatible(provider),
+                    AssistantProvider::OpenAiCompatible {
+                        default_model,
+                        api_url,
+                        low_speed_timeout_in_seconds,
+                        available_models,
+                    },
+                ) => {
+                    provider.update(
+                        default_model.clone(),
+                        api_url.clone(),
+                        available_models.clone(),
+                        low_speed_timeout_in_seconds.map(Duration::from_secs),
+                        settings_version,
+                    );
+                }
                 (
                     CompletionProvider::ZedDotDev(provider),
                     AssistantProvider::ZedDotDev { default_model },
@@ -138,6 +223,58 @@ pub fn init(client: Arc<Client>, cx: &mut AppContext) {
                         settings_version,
                     ));
                 }
+                (
+                    _,
+                    AssistantProvider::Google {
+                        default_model,
+                        api_url,
+                    },
+                ) => {
+                    *provider = CompletionProvider::Google(GoogleCompletionProvider::new(
+                        default_model.clone(),
+                        api_url.clone(),
+                        client.http_client(),
+                        settings_version,
+                    ));
+                }
+                (
+                    _,
+                    AssistantProvider::Ollama {
+                        default_model,
+                        api_url,
+                        low_speed_timeout_in_seconds,
+                        available_models,
+                    },
+                ) => {
+                    *provider = CompletionProvider::Ollama(OllamaCompletionProvider::new(
+                        default_model.clone(),
+                        api_url.clone(),
+                        available_models.clone(),
+                        client.http_client(),
+                        low_speed_timeout_in_seconds.map(Duration::from_secs),
+                        settings_version,
+                    ));
+                }
+                (
+                    _,
+                    AssistantProvider::OpenAiCompatible {
+                        default_model,
+                        api_url,
+                        low_speed_timeout_in_seconds,
+                        available_models,
+                    },
+                ) => {
+                    *provider = CompletionProvider::OpenAiCompatible(
+                        OpenAiCompatibleCompletionProvider::new(
+                            default_model.clone(),
+                            api_url.clone(),
+                            available_models.clone(),
+                            client.http_client(),
+                            low_speed_timeout_in_seconds.map(Duration::from_secs),
+                            settings_version,
+                        ),
+                    );
+                }
             }
         })
     })
@@ -148,6 +285,9 @@ pub enum CompletionProvider {
     OpenAi(OpenAiCompletionProvider),
     Anthropic(AnthropicCompletionProvider),
     ZedDotDev(ZedDotDevCompletionProvider),
+    Google(GoogleCompletionProvider),
+    Ollama(OllamaCompletionProvider),
+    OpenAiCompatible(OpenAiCompatibleCompletionProvider),
     #[cfg(test)]
     Fake(FakeCompletionProvider),
 }
@@ -164,6 +304,9 @@ impl CompletionProvider {
             CompletionProvider::OpenAi(provider) => provider.settings_version(),
             CompletionProvider::Anthropic(provider) => provider.settings_version(),
             CompletionProvider::ZedDotDev(provider) => provider.settings_version(),
+            CompletionProvider::Google(provider) => provider.settings_version(),
+            CompletionProvider::Ollama(provider) => provider.settings_version(),
+            CompletionProvider::OpenAiCompatible(provider) => provider.settings_version(),
             #[cfg(test)]
             CompletionProvider::Fake(_) => unimplemented!(),
         }
@@ -174,6 +317,9 @@ impl CompletionProvider {
             CompletionProvider::OpenAi(provider) => provider.is_authenticated(),
             CompletionProvider::Anthropic(provider) => provider.is_authenticated(),
             CompletionProvider::ZedDotDev(provider) => provider.is_authenticated(),
+            CompletionProvider::Google(provider) => provider.is_authenticated(),
+            CompletionProvider::Ollama(provider) => provider.is_authenticated(),
+            CompletionProvider::OpenAiCompatible(provider) => provider.is_authenticated(),
             #[cfg(test)]
             CompletionProvider::Fake(_) => true,
         }
@@ -184,6 +330,9 @@ impl CompletionProvider {
             CompletionProvider::OpenAi(provider) => provider.authenticate(cx),
             CompletionProvider::Anthropic(provider) => provider.authenticate(cx),
             CompletionProvider::ZedDotDev(provider) => provider.authenticate(cx),
+            CompletionProvider::Google(provider) => provider.authenticate(cx),
+            CompletionProvider::Ollama(provider) => provider.authenticate(cx),
+            CompletionProvider::OpenAiCompatible(provider) => provider.authenticate(cx),
             #[cfg(test)]
             CompletionProvider::Fake(_) => Task::ready(Ok(())),
         }
@@ -194,6 +343,9 @@ impl CompletionProvider {
             CompletionProvider::OpenAi(provider) => provider.authentication_prompt(cx),
             CompletionProvider::Anthropic(provider) => provider.authentication_prompt(cx),
             CompletionProvider::ZedDotDev(provider) => provider.authentication_prompt(cx),
+            CompletionProvider::Google(provider) => provider.authentication_prompt(cx),
+            CompletionProvider::Ollama(provider) => provider.authentication_prompt(cx),
+            CompletionProvider::OpenAiCompatible(provider) => provider.authentication_prompt(cx),
             #[cfg(test)]
             CompletionProvider::Fake(_) => unimplemented!(),
         }
@@ -204,6 +356,9 @@ impl CompletionProvider {
             CompletionProvider::OpenAi(provider) => provider.reset_credentials(cx),
             CompletionProvider::Anthropic(provider) => provider.reset_credentials(cx),
             CompletionProvider::ZedDotDev(_) => Task::ready(Ok(())),
+            CompletionProvider::Google(provider) => provider.reset_credentials(cx),
+            CompletionProvider::Ollama(provider) => provider.reset_credentials(cx),
+            CompletionProvider::OpenAiCompatible(provider) => provider.reset_credentials(cx),
             #[cfg(test)]
             CompletionProvider::Fake(_) => Task::ready(Ok(())),
         }
@@ -218,11 +373,32 @@ impl CompletionProvider {
             CompletionProvider::ZedDotDev(provider) => {
                 LanguageModel::ZedDotDev(provider.default_model())
             }
+            CompletionProvider::Google(provider) => LanguageModel::Google(provider.default_model()),
+            CompletionProvider::Ollama(provider) => LanguageModel::Ollama(provider.default_model()),
+            CompletionProvider::OpenAiCompatible(provider) => {
+                LanguageModel::OpenAiCompatible(provider.default_model())
+            }
             #[cfg(test)]
             CompletionProvider::Fake(_) => unimplemented!(),
         }
     }
 
+    /// The models the user can switch between, for providers whose models
+    /// are configured rather than known ahead of time.
+    pub fn available_models(&self) -> Vec<LanguageModel> {
+        match self {
+            CompletionProvider::Ollama(provider) => provider
+                .available_models()
+                .map(LanguageModel::Ollama)
+                .collect(),
+            CompletionProvider::OpenAiCompatible(provider) => provider
+                .available_models()
+                .map(LanguageModel::OpenAiCompatible)
+                .collect(),
+            _ => vec![self.default_model()],
+        }
+    }
+
     pub fn count_tokens(
         &self,
         request: LanguageModelRequest,
@@ -232,21 +408,39 @@ impl CompletionProvider {
             CompletionProvider::OpenAi(provider) => provider.count_tokens(request, cx),
             CompletionProvider::Anthropic(provider) => provider.count_tokens(request, cx),
             CompletionProvider::ZedDotDev(provider) => provider.count_tokens(request, cx),
+            CompletionProvider::Google(provider) => provider.count_tokens(request, cx),
+            CompletionProvider::Ollama(provider) => provider.count_tokens(request, cx),
+            CompletionProvider::OpenAiCompatible(provider) => provider.count_tokens(request, cx),
             #[cfg(test)]
-            CompletionProvider::Fake(_) => unimplemented!(),
+            CompletionProvider::Fake(provider) => provider.count_tokens(request),
         }
     }
 
+    /// Streams the completion of the request, recording its token usage and refusing it when
+    /// the monthly budget has been spent.
     pub fn complete(
         &self,
         request: LanguageModelRequest,
+        cx: &AppContext,
     ) -> BoxFuture<'static, Result<BoxStream<'static, Result<String>>>> {
-        match self {
+        let telemetry_id = request.model.telemetry_id();
+        let prompt_tokens = self.count_tokens(request.clone(), cx);
+        let response = match self {
             CompletionProvider::OpenAi(provider) => provider.complete(request),
             CompletionProvider::Anthropic(provider) => provider.complete(request),
             CompletionProvider::ZedDotDev(provider) => provider.complete(request),
+            CompletionProvider::Google(provider) => provider.complete(request),
+            CompletionProvider::Ollama(provider) => provider.complete(request),
+            CompletionProvider::OpenAiCompatible(provider) => provider.complete(request),
             #[cfg(test)]
             CompletionProvider::Fake(provider) => provider.complete(),
-        }
+        };
+        track_usage(
+            telemetry_id,
+            prompt_tokens,
+            response,
+            |chunk: &String| Some(chunk.as_str()),
+            cx,
+        )
     }
 }
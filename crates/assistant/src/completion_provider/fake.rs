@@ -1,3 +1,4 @@
+use crate::LanguageModelRequest;
 use anyhow::Result;
 use futures::{channel::mpsc, future::BoxFuture, stream::BoxStream, FutureExt, StreamExt};
 use std::sync::Arc;
@@ -8,6 +9,16 @@ pub struct FakeCompletionProvider {
 }
 
 impl FakeCompletionProvider {
+    /// Counts one token per character of the request's messages.
+    pub fn count_tokens(&self, request: LanguageModelRequest) -> BoxFuture<'static, Result<usize>> {
+        let count = request
+            .messages
+            .iter()
+            .map(|message| message.content.chars().count())
+            .sum();
+        futures::future::ready(Ok(count)).boxed()
+    }
+
     pub fn complete(&self) -> BoxFuture<'static, Result<BoxStream<'static, Result<String>>>> {
         let (tx, rx) = mpsc::unbounded();
         *self.current_completion_tx.lock() = Some(tx);
@@ -0,0 +1,449 @@
+use crate::{
+    assistant_settings::GoogleModel, CompletionProvider, LanguageModel, LanguageModelRequest, Role,
+};
+use anyhow::{anyhow, Result};
+use editor::{Editor, EditorElement, EditorStyle};
+use futures::{future::BoxFuture, stream::BoxStream, FutureExt, StreamExt};
+use google_ai::{
+    count_tokens, stream_generate_content, Content, CountTokensRequest, GenerateContentRequest,
+    GenerationConfig, Part, Role as GoogleRole, TextPart,
+};
+use gpui::{AnyView, AppContext, FontStyle, FontWeight, Task, TextStyle, View, WhiteSpace};
+use http::HttpClient;
+use settings::Settings;
+use std::{env, sync::Arc};
+use theme::ThemeSettings;
+use ui::prelude::*;
+use util::ResultExt;
+
+pub struct GoogleCompletionProvider {
+    api_key: Option<String>,
+    api_url: String,
+    default_model: GoogleModel,
+    http_client: Arc<dyn HttpClient>,
+    settings_version: usize,
+}
+
+impl GoogleCompletionProvider {
+    pub fn new(
+        default_model: GoogleModel,
+        api_url: String,
+        http_client: Arc<dyn HttpClient>,
+        settings_version: usize,
+    ) -> Self {
+        Self {
+            api_key: None,
+            api_url,
+            default_model,
+            http_client,
+            settings_version,
+        }
+    }
+
+    pub fn update(&mut self, default_model: GoogleModel, api_url: String, settings_version: usize) {
+        self.default_model = default_model;
+        self.api_url = api_url;
+        self.settings_version = settings_version;
+    }
+
+    pub fn settings_version(&self) -> usize {
+        self.settings_version
+    }
+
+    pub fn is_authenticated(&self) -> bool {
+        self.api_key.is_some()
+    }
+
+    pub fn authenticate(&self, cx: &AppContext) -> Task<Result<()>> {
+        if self.is_authenticated() {
+            Task::ready(Ok(()))
+        } else {
+            let api_url = self.api_url.clone();
+            cx.spawn(|mut cx| async move {
+                let api_key = if let Ok(api_key) = env::var("GOOGLE_AI_API_KEY") {
+                    api_key
+                } else {
+                    let (_, api_key) = cx
+                        .update(|cx| cx.read_credentials(&api_url))?
+                        .await?
+                        .ok_or_else(|| anyhow!("credentials not found"))?;
+                    String::from_utf8(api_key)?
+                };
+                cx.update_global::<CompletionProvider, _>(|provider, _cx| {
+                    if let CompletionProvider::Google(provider) = provider {
+                        provider.api_key = Some(api_key);
+                    }
+                })
+            })
+        }
+    }
+
+    pub fn reset_credentials(&self, cx: &AppContext) -> Task<Result<()>> {
+        let delete_credentials = cx.delete_credentials(&self.api_url);
+        cx.spawn(|mut cx| async move {
+            delete_credentials.await.log_err();
+            cx.update_global::<CompletionProvider, _>(|provider, _cx| {
+                if let CompletionProvider::Google(provider) = provider {
+                    provider.api_key = None;
+                }
+            })
+        })
+    }
+
+    pub fn authentication_prompt(&self, cx: &mut WindowContext) -> AnyView {
+        cx.new_view(|cx| AuthenticationPrompt::new(self.api_url.clone(), cx))
+            .into()
+    }
+
+    pub fn default_model(&self) -> GoogleModel {
+        self.default_model.clone()
+    }
+
+    pub fn count_tokens(
+        &self,
+        request: LanguageModelRequest,
+        _cx: &AppContext,
+    ) -> BoxFuture<'static, Result<usize>> {
+        let (model, request) = self.to_google_ai_request(request);
+
+        let http_client = self.http_client.clone();
+        let api_key = self.api_key.clone();
+        let api_url = self.api_url.clone();
+        async move {
+            let api_key = api_key.ok_or_else(|| anyhow!("missing api key"))?;
+            // The count endpoint doesn't take a system instruction, so count it as a message.
+            let contents = request
+                .system_instruction
+                .into_iter()
+                .chain(request.contents)
+                .collect();
+            let response = count_tokens(
+                http_client.as_ref(),
+                &api_url,
+                &api_key,
+                model.id(),
+                CountTokensRequest { contents },
+            )
+            .await?;
+            Ok(response.total_tokens)
+        }
+        .boxed()
+    }
+
+    pub fn complete(
+        &self,
+        request: LanguageModelRequest,
+    ) -> BoxFuture<'static, Result<BoxStream<'static, Result<String>>>> {
+        let (model, request) = self.to_google_ai_request(request);
+
+        let http_client = self.http_client.clone();
+        let api_key = self.api_key.clone();
+        let api_url = self.api_url.clone();
+        async move {
+            let api_key = api_key.ok_or_else(|| anyhow!("missing api key"))?;
+            let response =
+                stream_generate_content(http_client, &api_url, &api_key, model.id(), request)
+                    .await?;
+            let stream = response
+                .filter_map(|response| async move {
+                    match response {
+                        Ok(response) => {
+                            let text = response
+                                .candidates?
+                                .into_iter()
+                                .next()?
+                                .content
+                                .parts
+                                .into_iter()
+                                .filter_map(|part| match part {
+                                    Part::TextPart(part) => Some(part.text),
+                                    Part::InlineDataPart(_) => None,
+                                })
+                                .collect::<String>();
+                            Some(Ok(text))
+                        }
+                        Err(error) => Some(Err(error)),
+                    }
+                })
+                .boxed();
+            Ok(stream)
+        }
+        .boxed()
+    }
+
+    fn to_google_ai_request(
+        &self,
+        request: LanguageModelRequest,
+    ) -> (GoogleModel, GenerateContentRequest) {
+        let model = match request.model {
+            LanguageModel::Google(model) => model,
+            _ => self.default_model(),
+        };
+
+        let mut system_message = String::new();
+        let mut contents: Vec<Content> = Vec::new();
+        for message in request.messages {
+            if message.content.is_empty() {
+                continue;
+            }
+
+            let role = match message.role {
+                Role::User => GoogleRole::User,
+                Role::Assistant => GoogleRole::Model,
+                Role::System => {
+                    if !system_message.is_empty() {
+                        system_message.push_str("\n\n");
+                    }
+                    system_message.push_str(&message.content);
+                    continue;
+                }
+            };
+
+            // Gemini requires the conversation to alternate between the user and the model.
+            if let Some(last_content) = contents.last_mut() {
+                if last_content.role == role {
+                    last_content.parts.push(Part::TextPart(TextPart {
+                        text: message.content,
+                    }));
+                    continue;
+                }
+            }
+
+            contents.push(Content {
+                parts: vec![Part::TextPart(TextPart {
+                    text: message.content,
+                })],
+                role,
+            });
+        }
+
+        let system_instruction = if system_message.is_empty() {
+            None
+        } else if model.supports_system_instruction() {
+            Some(Content {
+                parts: vec![Part::TextPart(TextPart {
+                    text: system_message,
+                })],
+                role: GoogleRole::User,
+            })
+        } else {
+            // Older models have no system role, so the prompt opens the conversation instead.
+            match contents.first_mut() {
+                Some(first_content) if first_content.role == GoogleRole::User => {
+                    first_content.parts.insert(
+                        0,
+                        Part::TextPart(TextPart {
+                            text: system_message,
+                        }),
+                    );
+                }
+                _ => contents.insert(
+                    0,
+                    Content {
+                        parts: vec![Part::TextPart(TextPart {
+                            text: system_message,
+                        })],
+                        role: GoogleRole::User,
+                    },
+                ),
+            }
+            None
+        };
+
+        let request = GenerateContentRequest {
+            contents,
+            system_instruction,
+            generation_config: Some(GenerationConfig {
+                candidate_count: Some(1),
+                stop_sequences: Some(request.stop),
+                max_output_tokens: None,
+                temperature: Some(request.temperature as f64),
+                top_p: None,
+                top_k: None,
+            }),
+            safety_settings: None,
+        };
+        (model, request)
+    }
+}
+
+struct AuthenticationPrompt {
+    api_key: View<Editor>,
+    api_url: String,
+}
+
+impl AuthenticationPrompt {
+    fn new(api_url: String, cx: &mut WindowContext) -> Self {
+        Self {
+            api_key: cx.new_view(|cx| {
+                let mut editor = Editor::single_line(cx);
+                editor.set_placeholder_text("AIzaSy00000000000000000000000000000000000", cx);
+                editor
+            }),
+            api_url,
+        }
+    }
+
+    fn save_api_key(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let api_key = self.api_key.read(cx).text(cx);
+        if api_key.is_empty() {
+            return;
+        }
+
+        let write_credentials = cx.write_credentials(&self.api_url, "Bearer", api_key.as_bytes());
+        cx.spawn(|_, mut cx| async move {
+            write_credentials.await?;
+            cx.update_global::<CompletionProvider, _>(|provider, _cx| {
+                if let CompletionProvider::Google(provider) = provider {
+                    provider.api_key = Some(api_key);
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn render_api_key_editor(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let settings = ThemeSettings::get_global(cx);
+        let text_style = TextStyle {
+            color: cx.theme().colors().text,
+            font_family: settings.ui_font.family.clone(),
+            font_features: settings.ui_font.features.clone(),
+            font_size: rems(0.875).into(),
+            font_weight: FontWeight::NORMAL,
+            font_style: FontStyle::Normal,
+            line_height: relative(1.3),
+            background_color: None,
+            underline: None,
+            strikethrough: None,
+            white_space: WhiteSpace::Normal,
+        };
+        EditorElement::new(
+            &self.api_key,
+            EditorStyle {
+                background: cx.theme().colors().editor_background,
+                local_player: cx.theme().players().local(),
+                text: text_style,
+                ..Default::default()
+            },
+        )
+    }
+}
+
+impl Render for AuthenticationPrompt {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        const INSTRUCTIONS: [&str; 4] = [
+            "To use the assistant panel or inline assistant, you need to add your Google AI API key.",
+            "You can create an API key at: https://aistudio.google.com/app/apikey",
+            "",
+            "Paste your Google AI API key below and hit enter to use the assistant:",
+        ];
+
+        v_flex()
+            .p_4()
+            .size_full()
+            .on_action(cx.listener(Self::save_api_key))
+            .children(
+                INSTRUCTIONS.map(|instruction| Label::new(instruction).size(LabelSize::Small)),
+            )
+            .child(
+                h_flex()
+                    .w_full()
+                    .my_2()
+                    .px_2()
+                    .py_1()
+                    .bg(cx.theme().colors().editor_background)
+                    .rounded_md()
+                    .child(self.render_api_key_editor(cx)),
+            )
+            .child(
+                Label::new(
+                    "You can also assign the GOOGLE_AI_API_KEY environment variable and restart Zed.",
+                )
+                .size(LabelSize::Small),
+            )
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new("Click on").size(LabelSize::Small))
+                    .child(Icon::new(IconName::Ai).size(IconSize::XSmall))
+                    .child(
+                        Label::new("in the status bar to close this panel.").size(LabelSize::Small),
+                    ),
+            )
+            .into_any()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LanguageModelRequestMessage;
+    use http::FakeHttpClient;
+
+    fn message(role: Role, content: &str) -> LanguageModelRequestMessage {
+        LanguageModelRequestMessage {
+            role,
+            content: content.into(),
+        }
+    }
+
+    fn texts(content: &Content) -> Vec<&str> {
+        content
+            .parts
+            .iter()
+            .map(|part| match part {
+                Part::TextPart(part) => part.text.as_str(),
+                Part::InlineDataPart(_) => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_to_google_ai_request() {
+        let provider = GoogleCompletionProvider::new(
+            GoogleModel::Gemini15Pro,
+            google_ai::API_URL.into(),
+            FakeHttpClient::with_404_response(),
+            0,
+        );
+        let messages = || {
+            vec![
+                message(Role::System, "Be brief."),
+                message(Role::User, "Hi"),
+                message(Role::User, "Are you there?"),
+                message(Role::Assistant, ""),
+                message(Role::Assistant, "Yes."),
+            ]
+        };
+
+        let (model, request) = provider.to_google_ai_request(LanguageModelRequest {
+            model: LanguageModel::Google(GoogleModel::Gemini15Flash),
+            messages: messages(),
+            stop: Vec::new(),
+            temperature: 1.0,
+        });
+        assert_eq!(model, GoogleModel::Gemini15Flash);
+        assert_eq!(
+            texts(request.system_instruction.as_ref().unwrap()),
+            ["Be brief."]
+        );
+        assert_eq!(request.contents.len(), 2);
+        assert_eq!(request.contents[0].role, GoogleRole::User);
+        assert_eq!(texts(&request.contents[0]), ["Hi", "Are you there?"]);
+        assert_eq!(request.contents[1].role, GoogleRole::Model);
+        assert_eq!(texts(&request.contents[1]), ["Yes."]);
+
+        // Gemini 1.0 has no system instruction, so the prompt goes in the first user turn.
+        let (_, request) = provider.to_google_ai_request(LanguageModelRequest {
+            model: LanguageModel::Google(GoogleModel::GeminiPro),
+            messages: messages(),
+            stop: Vec::new(),
+            temperature: 1.0,
+        });
+        assert!(request.system_instruction.is_none());
+        assert_eq!(
+            texts(&request.contents[0]),
+            ["Be brief.", "Hi", "Are you there?"]
+        );
+    }
+}
@@ -0,0 +1,390 @@
+use crate::{
+    assistant_settings::{AvailableModel, OllamaModel},
+    count_open_ai_tokens, CompletionProvider, LanguageModel, LanguageModelRequest, Role,
+};
+use anyhow::{anyhow, Result};
+use futures::{future::BoxFuture, stream::BoxStream, FutureExt, StreamExt};
+use gpui::{AnyView, AppContext, Task};
+use http::HttpClient;
+use ollama::{
+    get_models, stream_chat_completion, ChatMessage, ChatOptions, ChatRequest, LocalModelListing,
+};
+use std::{sync::Arc, time::Duration};
+use ui::prelude::*;
+
+pub struct OllamaCompletionProvider {
+    api_url: String,
+    default_model: Option<String>,
+    available_models: Vec<AvailableModel>,
+    models: Vec<OllamaModel>,
+    http_client: Arc<dyn HttpClient>,
+    low_speed_timeout: Option<Duration>,
+    settings_version: usize,
+}
+
+impl OllamaCompletionProvider {
+    pub fn new(
+        default_model: Option<String>,
+        api_url: String,
+        available_models: Vec<AvailableModel>,
+        http_client: Arc<dyn HttpClient>,
+        low_speed_timeout: Option<Duration>,
+        settings_version: usize,
+    ) -> Self {
+        Self {
+            api_url,
+            default_model,
+            available_models,
+            models: Vec::new(),
+            http_client,
+            low_speed_timeout,
+            settings_version,
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        default_model: Option<String>,
+        api_url: String,
+        available_models: Vec<AvailableModel>,
+        low_speed_timeout: Option<Duration>,
+        settings_version: usize,
+    ) {
+        // The models we know about belong to the old server, so fetch them again.
+        if api_url != self.api_url || available_models != self.available_models {
+            self.models.clear();
+        }
+        self.default_model = default_model;
+        self.api_url = api_url;
+        self.available_models = available_models;
+        self.low_speed_timeout = low_speed_timeout;
+        self.settings_version = settings_version;
+    }
+
+    pub fn settings_version(&self) -> usize {
+        self.settings_version
+    }
+
+    /// Ollama has no credentials, so we consider ourselves authenticated once
+    /// the server has told us which models it has.
+    pub fn is_authenticated(&self) -> bool {
+        !self.models.is_empty()
+    }
+
+    pub fn authenticate(&self, cx: &AppContext) -> Task<Result<()>> {
+        if self.is_authenticated() {
+            return Task::ready(Ok(()));
+        }
+
+        let http_client = self.http_client.clone();
+        let api_url = self.api_url.clone();
+        let low_speed_timeout = self.low_speed_timeout;
+        let available_models = self.available_models.clone();
+        cx.spawn(|mut cx| async move {
+            let listings = get_models(http_client.as_ref(), &api_url, low_speed_timeout).await?;
+            let models = merge_models(listings, &available_models);
+            if models.is_empty() {
+                return Err(anyhow!(
+                    "no models found at {api_url}, pull one with `ollama pull`"
+                ));
+            }
+
+            cx.update_global::<CompletionProvider, _>(|provider, _cx| {
+                if let CompletionProvider::Ollama(provider) = provider {
+                    provider.models = models;
+                }
+            })
+        })
+    }
+
+    pub fn reset_credentials(&self, cx: &AppContext) -> Task<Result<()>> {
+        cx.spawn(|mut cx| async move {
+            cx.update_global::<CompletionProvider, _>(|provider, _cx| {
+                if let CompletionProvider::Ollama(provider) = provider {
+                    provider.models.clear();
+                }
+            })
+        })
+    }
+
+    pub fn authentication_prompt(&self, cx: &mut WindowContext) -> AnyView {
+        cx.new_view(|_cx| DownloadOllamaMessage {
+            api_url: self.api_url.clone(),
+        })
+        .into()
+    }
+
+    pub fn default_model(&self) -> OllamaModel {
+        self.default_model
+            .as_ref()
+            .and_then(|name| self.models.iter().find(|model| &model.name == name))
+            .or_else(|| self.models.first())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn available_models(&self) -> impl Iterator<Item = OllamaModel> + '_ {
+        self.models.iter().cloned()
+    }
+
+    pub fn count_tokens(
+        &self,
+        request: LanguageModelRequest,
+        cx: &AppContext,
+    ) -> BoxFuture<'static, Result<usize>> {
+        count_open_ai_tokens(request, cx.background_executor())
+    }
+
+    pub fn complete(
+        &self,
+        request: LanguageModelRequest,
+    ) -> BoxFuture<'static, Result<BoxStream<'static, Result<String>>>> {
+        let request = self.to_ollama_request(request);
+
+        let http_client = self.http_client.clone();
+        let api_url = self.api_url.clone();
+        let low_speed_timeout = self.low_speed_timeout;
+        async move {
+            let request =
+                stream_chat_completion(http_client.as_ref(), &api_url, request, low_speed_timeout);
+            let response = request.await?;
+            let stream = response
+                .filter_map(|response| async move {
+                    match response {
+                        Ok(delta) => match delta.message {
+                            ChatMessage::Assistant { content } => Some(Ok(content)),
+                            _ => None,
+                        },
+                        Err(error) => Some(Err(error)),
+                    }
+                })
+                .boxed();
+            Ok(stream)
+        }
+        .boxed()
+    }
+
+    fn to_ollama_request(&self, request: LanguageModelRequest) -> ChatRequest {
+        let model = match request.model {
+            LanguageModel::Ollama(model) => model,
+            _ => self.default_model(),
+        };
+
+        ChatRequest {
+            model: model.name,
+            messages: request
+                .messages
+                .into_iter()
+                .map(|msg| match msg.role {
+                    Role::User => ChatMessage::User {
+                        content: msg.content,
+                    },
+                    Role::Assistant => ChatMessage::Assistant {
+                        content: msg.content,
+                    },
+                    Role::System => ChatMessage::System {
+                        content: msg.content,
+                    },
+                })
+                .collect(),
+            stream: true,
+            keep_alive: model.keep_alive,
+            options: Some(ChatOptions {
+                // Ollama truncates the prompt to 2048 tokens unless told otherwise.
+                num_ctx: Some(model.max_tokens),
+                stop: Some(request.stop),
+                temperature: Some(request.temperature),
+            }),
+        }
+    }
+}
+
+/// Combines the models pulled into Ollama with the ones listed in the settings,
+/// letting the settings override the name, context length and tokenizer of a pulled model.
+fn merge_models(
+    listings: Vec<LocalModelListing>,
+    available_models: &[AvailableModel],
+) -> Vec<OllamaModel> {
+    let mut models = listings
+        .into_iter()
+        .map(|listing| OllamaModel::new(&listing.name))
+        .collect::<Vec<_>>();
+    for available_model in available_models {
+        let model = if let Some(ix) = models
+            .iter()
+            .position(|model| model.name == available_model.name)
+        {
+            &mut models[ix]
+        } else {
+            models.push(OllamaModel::new(&available_model.name));
+            models.last_mut().unwrap()
+        };
+        model.display_name = available_model.display_name.clone();
+        model.max_tokens = available_model.max_tokens;
+        model.tokenizer = available_model.tokenizer.clone();
+    }
+    models
+}
+
+struct DownloadOllamaMessage {
+    api_url: String,
+}
+
+impl Render for DownloadOllamaMessage {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .p_4()
+            .size_full()
+            .gap_2()
+            .child(Label::new(format!(
+                "Could not find any models on the Ollama server at {}.",
+                self.api_url
+            )))
+            .child(
+                Label::new(
+                    "Make sure Ollama is running and pull a model with `ollama pull llama3`.",
+                )
+                .size(LabelSize::Small),
+            )
+            .child(
+                h_flex().child(
+                    Button::new("retry_ollama", "Retry")
+                        .style(ButtonStyle::Filled)
+                        .on_click(move |_, cx| {
+                            cx.update_global::<CompletionProvider, _>(|provider, cx| {
+                                provider.authenticate(cx).detach_and_log_err(cx)
+                            })
+                        }),
+                ),
+            )
+            .into_any()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{assistant_settings::AssistantSettings, LanguageModelRequestMessage};
+    use futures::AsyncReadExt;
+    use gpui::TestAppContext;
+    use http::FakeHttpClient;
+    use serde_json::json;
+    use settings::{Settings, SettingsStore};
+
+    #[gpui::test]
+    async fn test_ollama_completion(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            cx.set_global(SettingsStore::test(cx));
+            AssistantSettings::register(cx);
+        });
+
+        let http_client = FakeHttpClient::create(|request| async move {
+            match request.uri().path() {
+                "/api/tags" => Ok(http::Response::new(
+                    json!({
+                        "models": [
+                            { "name": "llama3:latest", "size": 1 },
+                            { "name": "phi3:latest", "size": 1 }
+                        ]
+                    })
+                    .to_string()
+                    .into(),
+                )),
+                "/api/chat" => {
+                    let mut body = String::new();
+                    request.into_body().read_to_string(&mut body).await.unwrap();
+                    let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+                    assert_eq!(body["model"], "phi3:latest");
+                    assert_eq!(body["options"]["num_ctx"], 4096);
+
+                    let deltas = [("Hello", false), (" world", false), ("", true)]
+                        .into_iter()
+                        .map(|(content, done)| {
+                            json!({
+                                "model": "phi3:latest",
+                                "message": { "role": "assistant", "content": content },
+                                "done": done
+                            })
+                            .to_string()
+                        })
+                        .collect::<Vec<_>>();
+                    Ok(http::Response::new(deltas.join("\n").into()))
+                }
+                _ => Ok(http::Response::builder()
+                    .status(404)
+                    .body(Default::default())
+                    .unwrap()),
+            }
+        });
+
+        let provider = OllamaCompletionProvider::new(
+            Some("phi3:latest".into()),
+            "http://ollama.test".into(),
+            vec![AvailableModel {
+                name: "phi3:latest".into(),
+                display_name: Some("Phi-3".into()),
+                max_tokens: 4096,
+                tokenizer: Some("gpt-4".into()),
+            }],
+            http_client,
+            None,
+            0,
+        );
+        cx.set_global(CompletionProvider::Ollama(provider));
+
+        cx.update(|cx| CompletionProvider::global(cx).authenticate(cx))
+            .await
+            .unwrap();
+        let model = cx.read(|cx| {
+            let provider = CompletionProvider::global(cx);
+            assert!(provider.is_authenticated());
+            assert_eq!(
+                provider
+                    .available_models()
+                    .iter()
+                    .map(|model| model.id().to_string())
+                    .collect::<Vec<_>>(),
+                ["llama3:latest", "phi3:latest"]
+            );
+            provider.default_model()
+        });
+        assert_eq!(model.max_token_count(), 4096);
+        assert_eq!(model.display_name(), "Phi-3");
+
+        let request = LanguageModelRequest {
+            model,
+            messages: vec![LanguageModelRequestMessage {
+                role: Role::User,
+                content: "Hi".into(),
+            }],
+            stop: Vec::new(),
+            temperature: 1.0,
+        };
+        let token_count = cx
+            .read(|cx| CompletionProvider::global(cx).count_tokens(request.clone(), cx))
+            .await
+            .unwrap();
+        let expected_token_count = tiktoken_rs::num_tokens_from_messages(
+            "gpt-4",
+            &[tiktoken_rs::ChatCompletionRequestMessage {
+                role: "user".into(),
+                content: Some("Hi".into()),
+                name: None,
+                function_call: None,
+            }],
+        )
+        .unwrap();
+        assert_eq!(token_count, expected_token_count);
+
+        let completion = cx.read(|cx| CompletionProvider::global(cx).complete(request, cx));
+        let chunks = completion
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(chunks.concat(), "Hello world");
+    }
+}
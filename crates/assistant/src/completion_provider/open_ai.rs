@@ -1,4 +1,4 @@
-use crate::assistant_settings::ZedDotDevModel;
+use crate::assistant_settings::{AvailableModel, OllamaModel, ZedDotDevModel};
 use crate::{
     assistant_settings::OpenAiModel, CompletionProvider, LanguageModel, LanguageModelRequest, Role,
 };
@@ -156,7 +156,7 @@ impl OpenAiCompletionProvider {
         };
 
         Request {
-            model,
+            model: model.id().into(),
             messages: request
                 .messages
                 .into_iter()
@@ -214,12 +214,42 @@ pub fn count_open_ai_tokens(
                     // same tokenizer as GPT-4.
                     tiktoken_rs::num_tokens_from_messages("gpt-4", &messages)
                 }
+                LanguageModel::OpenAiCompatible(AvailableModel {
+                    tokenizer: Some(tokenizer),
+                    ..
+                })
+                | LanguageModel::Ollama(OllamaModel {
+                    tokenizer: Some(tokenizer),
+                    ..
+                }) => tiktoken_rs::num_tokens_from_messages(&tokenizer, &messages),
+                LanguageModel::Ollama(_) | LanguageModel::OpenAiCompatible(_) => {
+                    // We don't know these models' tokenizers, so estimate from the text instead.
+                    Ok(estimate_token_count(&messages))
+                }
                 _ => tiktoken_rs::num_tokens_from_messages(request.model.id(), &messages),
             }
         })
         .boxed()
 }
 
+/// Roughly four characters per token, plus the overhead OpenAI's chat format adds per message.
+fn estimate_token_count(messages: &[tiktoken_rs::ChatCompletionRequestMessage]) -> usize {
+    const TOKENS_PER_MESSAGE: usize = 4;
+    const TOKENS_PER_REPLY: usize = 3;
+
+    messages
+        .iter()
+        .map(|message| {
+            let content_len = message
+                .content
+                .as_deref()
+                .map_or(0, |content| content.len());
+            content_len.div_ceil(4) + TOKENS_PER_MESSAGE
+        })
+        .sum::<usize>()
+        + TOKENS_PER_REPLY
+}
+
 impl From<Role> for open_ai::Role {
     fn from(val: Role) -> Self {
         match val {
@@ -0,0 +1,381 @@
+use crate::{
+    assistant_settings::AvailableModel, count_open_ai_tokens, CompletionProvider, LanguageModel,
+    LanguageModelRequest, Role,
+};
+use anyhow::Result;
+use editor::Editor;
+use futures::{future::BoxFuture, stream::BoxStream, FutureExt, StreamExt};
+use gpui::{AnyView, AppContext, Task, View};
+use http::HttpClient;
+use open_ai::{stream_completion, Request, RequestMessage};
+use std::{sync::Arc, time::Duration};
+use ui::prelude::*;
+use util::ResultExt;
+
+/// The context length assumed for a model that isn't listed in `available_models`.
+const DEFAULT_MAX_TOKENS: usize = 4096;
+
+/// Talks to any server implementing OpenAI's chat completions API, such as
+/// llama.cpp, vLLM or LM Studio.
+pub struct OpenAiCompatibleCompletionProvider {
+    api_key: Option<String>,
+    api_url: String,
+    default_model: Option<String>,
+    available_models: Vec<AvailableModel>,
+    http_client: Arc<dyn HttpClient>,
+    low_speed_timeout: Option<Duration>,
+    settings_version: usize,
+}
+
+impl OpenAiCompatibleCompletionProvider {
+    pub fn new(
+        default_model: Option<String>,
+        api_url: String,
+        available_models: Vec<AvailableModel>,
+        http_client: Arc<dyn HttpClient>,
+        low_speed_timeout: Option<Duration>,
+        settings_version: usize,
+    ) -> Self {
+        Self {
+            api_key: None,
+            api_url,
+            default_model,
+            available_models,
+            http_client,
+            low_speed_timeout,
+            settings_version,
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        default_model: Option<String>,
+        api_url: String,
+        available_models: Vec<AvailableModel>,
+        low_speed_timeout: Option<Duration>,
+        settings_version: usize,
+    ) {
+        if api_url != self.api_url {
+            self.api_key = None;
+        }
+        self.default_model = default_model;
+        self.api_url = api_url;
+        self.available_models = available_models;
+        self.low_speed_timeout = low_speed_timeout;
+        self.settings_version = settings_version;
+    }
+
+    pub fn settings_version(&self) -> usize {
+        self.settings_version
+    }
+
+    pub fn is_authenticated(&self) -> bool {
+        self.api_key.is_some()
+    }
+
+    /// Loads the API key stored for this server, if any. Most local servers
+    /// don't check the key, so a missing one is sent as an empty string.
+    pub fn authenticate(&self, cx: &AppContext) -> Task<Result<()>> {
+        if self.is_authenticated() {
+            return Task::ready(Ok(()));
+        }
+
+        let api_url = self.api_url.clone();
+        cx.spawn(|mut cx| async move {
+            let credentials = cx
+                .update(|cx| cx.read_credentials(&api_url))?
+                .await
+                .log_err()
+                .flatten();
+            let api_key = match credentials {
+                Some((_, api_key)) => String::from_utf8(api_key)?,
+                None => String::new(),
+            };
+            cx.update_global::<CompletionProvider, _>(|provider, _cx| {
+                if let CompletionProvider::OpenAiCompatible(provider) = provider {
+                    provider.api_key = Some(api_key);
+                }
+            })
+        })
+    }
+
+    pub fn reset_credentials(&self, cx: &AppContext) -> Task<Result<()>> {
+        let delete_credentials = cx.delete_credentials(&self.api_url);
+        cx.spawn(|mut cx| async move {
+            delete_credentials.await.log_err();
+            cx.update_global::<CompletionProvider, _>(|provider, _cx| {
+                if let CompletionProvider::OpenAiCompatible(provider) = provider {
+                    provider.api_key = None;
+                }
+            })
+        })
+    }
+
+    pub fn authentication_prompt(&self, cx: &mut WindowContext) -> AnyView {
+        cx.new_view(|cx| AuthenticationPrompt::new(self.api_url.clone(), cx))
+            .into()
+    }
+
+    pub fn default_model(&self) -> AvailableModel {
+        let default_model = self.default_model.as_ref().and_then(|name| {
+            self.available_models
+                .iter()
+                .find(|model| &model.name == name)
+        });
+        if let Some(model) = default_model.or_else(|| self.available_models.first()) {
+            model.clone()
+        } else {
+            AvailableModel {
+                name: self.default_model.clone().unwrap_or_default(),
+                display_name: None,
+                max_tokens: DEFAULT_MAX_TOKENS,
+                tokenizer: None,
+            }
+        }
+    }
+
+    pub fn available_models(&self) -> impl Iterator<Item = AvailableModel> + '_ {
+        self.available_models.iter().cloned()
+    }
+
+    pub fn count_tokens(
+        &self,
+        request: LanguageModelRequest,
+        cx: &AppContext,
+    ) -> BoxFuture<'static, Result<usize>> {
+        count_open_ai_tokens(request, cx.background_executor())
+    }
+
+    pub fn complete(
+        &self,
+        request: LanguageModelRequest,
+    ) -> BoxFuture<'static, Result<BoxStream<'static, Result<String>>>> {
+        let request = self.to_open_ai_request(request);
+
+        let http_client = self.http_client.clone();
+        let api_key = self.api_key.clone().unwrap_or_default();
+        let api_url = self.api_url.clone();
+        let low_speed_timeout = self.low_speed_timeout;
+        async move {
+            let request = stream_completion(
+                http_client.as_ref(),
+                &api_url,
+                &api_key,
+                request,
+                low_speed_timeout,
+            );
+            let response = request.await?;
+            let stream = response
+                .filter_map(|response| async move {
+                    match response {
+                        Ok(mut response) => Some(Ok(response.choices.pop()?.delta.content?)),
+                        Err(error) => Some(Err(error)),
+                    }
+                })
+                .boxed();
+            Ok(stream)
+        }
+        .boxed()
+    }
+
+    fn to_open_ai_request(&self, request: LanguageModelRequest) -> Request {
+        let model = match request.model {
+            LanguageModel::OpenAiCompatible(model) => model,
+            _ => self.default_model(),
+        };
+
+        Request {
+            model: model.name,
+            messages: request
+                .messages
+                .into_iter()
+                .map(|msg| match msg.role {
+                    Role::User => RequestMessage::User {
+                        content: msg.content,
+                    },
+                    Role::Assistant => RequestMessage::Assistant {
+                        content: Some(msg.content),
+                        tool_calls: Vec::new(),
+                    },
+                    Role::System => RequestMessage::System {
+                        content: msg.content,
+                    },
+                })
+                .collect(),
+            stream: true,
+            stop: request.stop,
+            temperature: request.temperature,
+            tools: Vec::new(),
+            tool_choice: None,
+        }
+    }
+}
+
+struct AuthenticationPrompt {
+    api_key: View<Editor>,
+    api_url: String,
+}
+
+impl AuthenticationPrompt {
+    fn new(api_url: String, cx: &mut WindowContext) -> Self {
+        Self {
+            api_key: cx.new_view(|cx| {
+                let mut editor = Editor::single_line(cx);
+                editor.set_placeholder_text("API key, or leave empty if none is needed", cx);
+                editor
+            }),
+            api_url,
+        }
+    }
+
+    fn save_api_key(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let api_key = self.api_key.read(cx).text(cx);
+        let write_credentials = if api_key.is_empty() {
+            None
+        } else {
+            Some(cx.write_credentials(&self.api_url, "Bearer", api_key.as_bytes()))
+        };
+        cx.spawn(|_, mut cx| async move {
+            if let Some(write_credentials) = write_credentials {
+                write_credentials.await?;
+            }
+            cx.update_global::<CompletionProvider, _>(|provider, _cx| {
+                if let CompletionProvider::OpenAiCompatible(provider) = provider {
+                    provider.api_key = Some(api_key);
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+}
+
+impl Render for AuthenticationPrompt {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .p_4()
+            .size_full()
+            .gap_2()
+            .on_action(cx.listener(Self::save_api_key))
+            .child(
+                Label::new(format!(
+                    "Enter the API key for {} and hit enter to use the assistant:",
+                    self.api_url
+                ))
+                .size(LabelSize::Small),
+            )
+            .child(
+                h_flex()
+                    .w_full()
+                    .px_2()
+                    .py_1()
+                    .bg(cx.theme().colors().editor_background)
+                    .rounded_md()
+                    .child(self.api_key.clone()),
+            )
+            .into_any()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{assistant_settings::AssistantSettings, LanguageModelRequestMessage};
+    use futures::AsyncReadExt;
+    use gpui::TestAppContext;
+    use http::FakeHttpClient;
+    use serde_json::json;
+    use settings::{Settings, SettingsStore};
+
+    #[gpui::test]
+    async fn test_open_ai_compatible_completion(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            cx.set_global(SettingsStore::test(cx));
+            AssistantSettings::register(cx);
+        });
+
+        let http_client = FakeHttpClient::create(|request| async move {
+            assert_eq!(request.uri().path(), "/v1/chat/completions");
+            assert_eq!(
+                request.headers()["Authorization"].to_str().unwrap(),
+                "Bearer secret"
+            );
+            let mut body = String::new();
+            request.into_body().read_to_string(&mut body).await.unwrap();
+            let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+            assert_eq!(body["model"], "codestral");
+
+            let events = ["Hello", " world"]
+                .into_iter()
+                .map(|content| {
+                    let event = json!({
+                        "created": 0,
+                        "model": "codestral",
+                        "choices": [{
+                            "index": 0,
+                            "delta": { "role": "assistant", "content": content },
+                            "finish_reason": null
+                        }]
+                    });
+                    format!("data: {event}\n\n")
+                })
+                .collect::<String>();
+            Ok(http::Response::new(
+                format!("{events}data: [DONE]\n\n").into(),
+            ))
+        });
+
+        let mut provider = OpenAiCompatibleCompletionProvider::new(
+            Some("codestral".into()),
+            "http://llm.test/v1".into(),
+            vec![
+                AvailableModel {
+                    name: "llama3".into(),
+                    display_name: None,
+                    max_tokens: 8192,
+                    tokenizer: None,
+                },
+                AvailableModel {
+                    name: "codestral".into(),
+                    display_name: Some("Codestral".into()),
+                    max_tokens: 32768,
+                    tokenizer: Some("gpt-4".into()),
+                },
+            ],
+            http_client,
+            None,
+            0,
+        );
+        provider.api_key = Some("secret".into());
+        cx.set_global(CompletionProvider::OpenAiCompatible(provider));
+
+        let model = cx.read(|cx| CompletionProvider::global(cx).default_model());
+        assert_eq!(model.display_name(), "Codestral");
+        assert_eq!(model.max_token_count(), 32768);
+
+        let request = || LanguageModelRequest {
+            model: model.clone(),
+            messages: vec![LanguageModelRequestMessage {
+                role: Role::User,
+                content: "Hi".into(),
+            }],
+            stop: Vec::new(),
+            temperature: 1.0,
+        };
+        let token_count = cx
+            .read(|cx| CompletionProvider::global(cx).count_tokens(request(), cx))
+            .await
+            .unwrap();
+        assert!(token_count > 0);
+
+        let completion = cx.read(|cx| CompletionProvider::global(cx).complete(request(), cx));
+        let chunks = completion
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(chunks.concat(), "Hello world");
+    }
+}
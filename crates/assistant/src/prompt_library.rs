@@ -0,0 +1,862 @@
+use crate::{
+    assistant_panel::AssistantPanel, ExportPrompts, ImportPrompts, InsertPrompt, NewPrompt,
+};
+use anyhow::Result;
+use collections::HashSet;
+use db::{define_connection, query, sqlez_macros::sql};
+use editor::{Editor, EditorElement, EditorStyle};
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    AppContext, AsyncWindowContext, DismissEvent, EventEmitter, FocusHandle, FocusableView,
+    FontStyle, FontWeight, Global, PathPromptOptions, Render, Task, TextStyle, View, ViewContext,
+    WeakView, WhiteSpace,
+};
+use language::{DiagnosticSeverity, Point};
+use multi_buffer::MultiBufferRow;
+use picker::{Picker, PickerDelegate};
+use settings::Settings;
+use std::{fmt::Write as _, sync::Arc};
+use theme::ThemeSettings;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing, Tooltip};
+use util::ResultExt;
+use workspace::{notifications::NotificationId, ModalView, Toast, Workspace};
+
+define_connection!(pub static ref PROMPT_LIBRARY_DB: PromptLibraryDb<()> =
+    &[sql!(
+        CREATE TABLE prompts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            title TEXT NOT NULL,
+            body TEXT NOT NULL,
+            is_default INTEGER NOT NULL DEFAULT 0
+        ) STRICT;
+    )];
+);
+
+impl PromptLibraryDb {
+    query! {
+        fn select_prompts() -> Result<Vec<(i64, String, String, bool)>> {
+            SELECT id, title, body, is_default FROM prompts ORDER BY title
+        }
+    }
+
+    query! {
+        pub async fn insert_prompt(title: String, body: String, is_default: bool) -> Result<()> {
+            INSERT INTO prompts(title, body, is_default) VALUES (?, ?, ?)
+        }
+    }
+
+    query! {
+        pub async fn update_prompt(title: String, body: String, id: i64) -> Result<()> {
+            UPDATE prompts SET title = ?, body = ? WHERE id = ?
+        }
+    }
+
+    query! {
+        pub async fn set_prompt_default(is_default: bool, id: i64) -> Result<()> {
+            UPDATE prompts SET is_default = ? WHERE id = ?
+        }
+    }
+
+    query! {
+        pub async fn delete_prompt(id: i64) -> Result<()> {
+            DELETE FROM prompts WHERE id = ?
+        }
+    }
+
+    pub fn prompts(&self) -> Result<Vec<Prompt>> {
+        Ok(self
+            .select_prompts()?
+            .into_iter()
+            .map(|(id, title, body, is_default)| Prompt {
+                id,
+                title,
+                body,
+                is_default,
+            })
+            .collect())
+    }
+
+    /// Returns the bodies of the prompts that should start every new conversation.
+    pub fn default_prompts(&self) -> Result<Vec<String>> {
+        Ok(self
+            .prompts()?
+            .into_iter()
+            .filter(|prompt| prompt.is_default)
+            .map(|prompt| prompt.body)
+            .collect())
+    }
+}
+
+/// A reusable prompt from the prompt library.
+///
+/// The body may reference `{{selection}}`, `{{file_path}}`, `{{language}}` and `{{diagnostics}}`,
+/// which are resolved against the active editor when the prompt is inserted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Prompt {
+    pub id: i64,
+    pub title: String,
+    pub body: String,
+    /// Whether this prompt is inserted as a system message at the start of new conversations.
+    pub is_default: bool,
+}
+
+impl Prompt {
+    /// Parses a prompt exported with [`Prompt::to_markdown`], or a plain markdown file, in which
+    /// case the title is taken from the file name.
+    fn from_markdown(contents: &str, fallback_title: &str) -> (String, String, bool) {
+        let contents = contents.replace("\r\n", "\n");
+        let mut title = fallback_title.to_string();
+        let mut is_default = false;
+        let mut body = contents.as_str();
+
+        if let Some(front_matter) = body.strip_prefix("---\n") {
+            if let Some((header, rest)) = front_matter.split_once("\n---\n") {
+                for line in header.lines() {
+                    match line.split_once(':') {
+                        Some(("title", value)) => title = value.trim().to_string(),
+                        Some(("default", value)) => is_default = value.trim() == "true",
+                        _ => {}
+                    }
+                }
+                body = rest;
+            }
+        }
+
+        (title, body.trim().to_string(), is_default)
+    }
+
+    fn to_markdown(&self) -> String {
+        format!(
+            "---\ntitle: {}\ndefault: {}\n---\n{}\n",
+            self.title, self.is_default, self.body
+        )
+    }
+
+    fn file_stem(&self) -> String {
+        let file_stem = self
+            .title
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '-'
+                }
+            })
+            .collect::<String>();
+        if file_stem.trim_matches('-').is_empty() {
+            format!("prompt-{}", self.id)
+        } else {
+            file_stem
+        }
+    }
+}
+
+/// Returns the names of the files the prompts are exported to, numbering the prompts whose titles
+/// would otherwise map to the same file.
+fn export_file_names(prompts: &[Prompt]) -> Vec<String> {
+    let mut used_file_names = HashSet::default();
+    prompts
+        .iter()
+        .map(|prompt| {
+            let file_stem = prompt.file_stem();
+            let mut file_name = format!("{file_stem}.md");
+            let mut discriminant = 2;
+            while !used_file_names.insert(file_name.clone()) {
+                file_name = format!("{file_stem}-{discriminant}.md");
+                discriminant += 1;
+            }
+            file_name
+        })
+        .collect()
+}
+
+/// The bodies of the default prompts, kept in memory so that starting a conversation doesn't read
+/// from the database.
+#[derive(Default)]
+struct DefaultPrompts(Vec<String>);
+
+impl Global for DefaultPrompts {}
+
+/// Returns the bodies of the prompts that should start every new conversation.
+pub fn default_prompts(cx: &AppContext) -> &[String] {
+    cx.try_global::<DefaultPrompts>()
+        .map_or(&[], |default_prompts| &default_prompts.0)
+}
+
+fn reload_default_prompts(cx: &mut AppContext) {
+    let default_prompts = cx
+        .background_executor()
+        .spawn(async move { PROMPT_LIBRARY_DB.default_prompts() });
+    cx.spawn(|cx| async move {
+        let default_prompts = default_prompts.await?;
+        cx.update(|cx| cx.set_global(DefaultPrompts(default_prompts)))
+    })
+    .detach_and_log_err(cx);
+}
+
+/// The values substituted for a prompt's variables, taken from the active editor.
+#[derive(Default)]
+pub struct PromptContext {
+    pub selection: Option<String>,
+    pub file_path: Option<String>,
+    pub language: Option<String>,
+    pub diagnostics: Option<String>,
+}
+
+impl PromptContext {
+    pub fn for_workspace(workspace: &Workspace, cx: &AppContext) -> Self {
+        let Some(editor) = workspace
+            .active_item(cx)
+            .and_then(|item| item.act_as::<Editor>(cx))
+        else {
+            return Self::default();
+        };
+
+        let editor = editor.read(cx);
+        let range = editor.selections.newest::<usize>(cx).range();
+        let buffer = editor.buffer().read(cx).snapshot(cx);
+
+        let selection = buffer.text_for_range(range.clone()).collect::<String>();
+        let file_path = buffer
+            .file_at(range.start)
+            .map(|file| file.path().to_string_lossy().into_owned());
+        let language = buffer
+            .language_at(range.start)
+            .map(|language| language.name().to_string());
+
+        let cursor_row = buffer.offset_to_point(range.end).row;
+        let mut diagnostics = String::new();
+        for entry in buffer.diagnostics_in_range::<_, Point>(
+            Point::new(cursor_row, 0)
+                ..Point::new(cursor_row, buffer.line_len(MultiBufferRow(cursor_row))),
+            false,
+        ) {
+            let severity = match entry.diagnostic.severity {
+                DiagnosticSeverity::ERROR => "error",
+                DiagnosticSeverity::WARNING => "warning",
+                _ => "info",
+            };
+            writeln!(
+                diagnostics,
+                "{severity} at line {}: {}",
+                entry.range.start.row + 1,
+                entry.diagnostic.message
+            )
+            .unwrap();
+        }
+
+        Self {
+            selection: (!selection.is_empty()).then_some(selection),
+            file_path,
+            language,
+            diagnostics: (!diagnostics.is_empty()).then(|| diagnostics.trim_end().to_string()),
+        }
+    }
+
+    fn variable(&self, name: &str) -> Option<&str> {
+        let value = match name {
+            "selection" => &self.selection,
+            "file_path" => &self.file_path,
+            "language" => &self.language,
+            "diagnostics" => &self.diagnostics,
+            _ => return None,
+        };
+        Some(value.as_deref().unwrap_or(""))
+    }
+}
+
+/// Replaces the `{{variable}}` placeholders in the template with their values in the given context.
+///
+/// Unknown variables are left untouched, so that prompts can still talk about templates.
+pub fn render_prompt(template: &str, context: &PromptContext) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        let after_start = &rest[start + 2..];
+        let Some(end) = after_start.find("}}") else {
+            rest = &rest[start..];
+            break;
+        };
+
+        let placeholder = &rest[start..start + 2 + end + 2];
+        match context.variable(after_start[..end].trim()) {
+            Some(value) => rendered.push_str(value),
+            None => rendered.push_str(placeholder),
+        }
+        rest = &after_start[end + 2..];
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+pub fn init(cx: &mut AppContext) {
+    reload_default_prompts(cx);
+    cx.observe_new_views(
+        |workspace: &mut Workspace, _cx: &mut ViewContext<Workspace>| {
+            workspace
+                .register_action(PromptPicker::toggle)
+                .register_action(PromptEditor::new_prompt)
+                .register_action(import_prompts)
+                .register_action(export_prompts);
+        },
+    )
+    .detach();
+}
+
+struct PromptLibraryToast;
+
+fn show_toast(
+    workspace: WeakView<Workspace>,
+    message: String,
+    cx: &mut AsyncWindowContext,
+) -> Result<()> {
+    workspace.update(cx, |workspace, cx| {
+        workspace.show_toast(
+            Toast::new(NotificationId::unique::<PromptLibraryToast>(), message),
+            cx,
+        )
+    })
+}
+
+fn import_prompts(workspace: &mut Workspace, _: &ImportPrompts, cx: &mut ViewContext<Workspace>) {
+    let paths = cx.prompt_for_paths(PathPromptOptions {
+        files: true,
+        directories: false,
+        multiple: true,
+    });
+    let fs = workspace.app_state().fs.clone();
+    cx.spawn(|workspace, mut cx| async move {
+        let Some(paths) = paths.await? else {
+            return Ok(());
+        };
+
+        let mut imported_count = 0;
+        for path in paths {
+            let contents = fs.load(&path).await?;
+            let fallback_title = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let (title, body, is_default) = Prompt::from_markdown(&contents, &fallback_title);
+            PROMPT_LIBRARY_DB
+                .insert_prompt(title, body, is_default)
+                .await?;
+            imported_count += 1;
+        }
+        cx.update(|cx| reload_default_prompts(cx))?;
+
+        show_toast(
+            workspace,
+            format!("Imported {imported_count} prompts into the prompt library"),
+            &mut cx,
+        )
+    })
+    .detach_and_log_err(cx);
+}
+
+fn export_prompts(workspace: &mut Workspace, _: &ExportPrompts, cx: &mut ViewContext<Workspace>) {
+    let paths = cx.prompt_for_paths(PathPromptOptions {
+        files: false,
+        directories: true,
+        multiple: false,
+    });
+    let fs = workspace.app_state().fs.clone();
+    cx.spawn(|workspace, mut cx| async move {
+        let Some(directory) = paths.await?.and_then(|paths| paths.into_iter().next()) else {
+            return Ok(());
+        };
+
+        let prompts = PROMPT_LIBRARY_DB.prompts()?;
+        for (prompt, file_name) in prompts.iter().zip(export_file_names(&prompts)) {
+            fs.atomic_write(directory.join(file_name), prompt.to_markdown())
+                .await?;
+        }
+
+        show_toast(
+            workspace,
+            format!(
+                "Exported {} prompts to {}",
+                prompts.len(),
+                directory.display()
+            ),
+            &mut cx,
+        )
+    })
+    .detach_and_log_err(cx);
+}
+
+pub struct PromptPicker {
+    picker: View<Picker<PromptPickerDelegate>>,
+}
+
+impl PromptPicker {
+    pub(crate) fn toggle(
+        workspace: &mut Workspace,
+        _: &InsertPrompt,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let workspace_handle = cx.view().downgrade();
+        workspace.toggle_modal(cx, |cx| {
+            let prompts = PROMPT_LIBRARY_DB.prompts().log_err().unwrap_or_default();
+            let delegate = PromptPickerDelegate {
+                prompt_picker: cx.view().downgrade(),
+                workspace: workspace_handle,
+                candidates: Vec::new(),
+                prompts,
+                matches: Vec::new(),
+                selected_index: 0,
+            };
+            let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+            Self { picker }
+        });
+    }
+}
+
+impl Render for PromptPicker {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for PromptPicker {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for PromptPicker {}
+impl ModalView for PromptPicker {}
+
+pub struct PromptPickerDelegate {
+    prompt_picker: WeakView<PromptPicker>,
+    workspace: WeakView<Workspace>,
+    prompts: Vec<Prompt>,
+    candidates: Vec<StringMatchCandidate>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl PromptPickerDelegate {
+    fn toggle_default(&mut self, ix: usize, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(mat) = self.matches.get(ix) else {
+            return;
+        };
+        let prompt = &mut self.prompts[mat.candidate_id];
+        prompt.is_default = !prompt.is_default;
+        let update = PROMPT_LIBRARY_DB.set_prompt_default(prompt.is_default, prompt.id);
+        cx.spawn(|_, mut cx| async move {
+            update.await?;
+            cx.update(|cx| reload_default_prompts(cx))
+        })
+        .detach_and_log_err(cx);
+        cx.notify();
+    }
+
+    fn edit(&mut self, ix: usize, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(mat) = self.matches.get(ix) else {
+            return;
+        };
+        let prompt = self.prompts[mat.candidate_id].clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace.toggle_modal(cx, |cx| PromptEditor::new(Some(prompt), cx))
+            })
+            .log_err();
+    }
+
+    fn delete(&mut self, ix: usize, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(mat) = self.matches.get(ix) else {
+            return;
+        };
+        let candidate_id = mat.candidate_id;
+        let prompt = self.prompts.remove(candidate_id);
+        let delete = PROMPT_LIBRARY_DB.delete_prompt(prompt.id);
+        cx.spawn(|_, mut cx| async move {
+            delete.await?;
+            cx.update(|cx| reload_default_prompts(cx))
+        })
+        .detach_and_log_err(cx);
+
+        self.candidates.remove(candidate_id);
+        self.matches.remove(ix);
+        for mat in &mut self.matches {
+            if mat.candidate_id > candidate_id {
+                mat.candidate_id -= 1;
+            }
+        }
+        self.selected_index = self
+            .selected_index
+            .min(self.matches.len().saturating_sub(1));
+        cx.notify();
+    }
+}
+
+impl PickerDelegate for PromptPickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Insert a prompt...".into()
+    }
+
+    fn no_matches_text(&self, _cx: &mut WindowContext) -> SharedString {
+        if self.prompts.is_empty() {
+            "The prompt library is empty. Create a prompt or import prompts from markdown files to get started.".into()
+        } else {
+            "No matching prompts".into()
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        self.candidates = self
+            .prompts
+            .iter()
+            .enumerate()
+            .map(|(id, prompt)| StringMatchCandidate::new(id, prompt.title.clone()))
+            .collect();
+        let candidates = self.candidates.clone();
+        let background = cx.background_executor().clone();
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if secondary {
+            self.toggle_default(self.selected_index, cx);
+            return;
+        }
+
+        if let Some(mat) = self.matches.get(self.selected_index) {
+            let template = self.prompts[mat.candidate_id].body.clone();
+            self.workspace
+                .update(cx, |workspace, cx| {
+                    let text =
+                        render_prompt(&template, &PromptContext::for_workspace(workspace, cx));
+                    AssistantPanel::insert_text(workspace, &text, cx);
+                })
+                .log_err();
+        }
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.prompt_picker
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = &self.matches[ix];
+        let prompt = &self.prompts[mat.candidate_id];
+        let default_tooltip = if prompt.is_default {
+            "Stop Including in New Conversations"
+        } else {
+            "Include in New Conversations"
+        };
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(HighlightedLabel::new(
+                    mat.string.clone(),
+                    mat.positions.clone(),
+                ))
+                .end_slot(
+                    h_flex()
+                        .gap_1()
+                        .child(
+                            IconButton::new("edit", IconName::Pencil)
+                                .icon_size(IconSize::Small)
+                                .on_click(cx.listener(move |this, _, cx| {
+                                    cx.stop_propagation();
+                                    this.delegate.edit(ix, cx);
+                                }))
+                                .tooltip(|cx| Tooltip::text("Edit Prompt", cx)),
+                        )
+                        .child(
+                            IconButton::new("toggle-default", IconName::Check)
+                                .icon_size(IconSize::Small)
+                                .selected(prompt.is_default)
+                                .on_click(cx.listener(move |this, _, cx| {
+                                    cx.stop_propagation();
+                                    this.delegate.toggle_default(ix, cx);
+                                }))
+                                .tooltip(move |cx| Tooltip::text(default_tooltip, cx)),
+                        )
+                        .child(
+                            IconButton::new("delete", IconName::Trash)
+                                .icon_size(IconSize::Small)
+                                .on_click(cx.listener(move |this, _, cx| {
+                                    cx.stop_propagation();
+                                    this.delegate.delete(ix, cx);
+                                }))
+                                .tooltip(|cx| Tooltip::text("Delete Prompt", cx)),
+                        ),
+                ),
+        )
+    }
+}
+
+/// A modal for writing a new prompt or editing an existing one.
+pub struct PromptEditor {
+    prompt_id: Option<i64>,
+    title_editor: View<Editor>,
+    body_editor: View<Editor>,
+}
+
+impl PromptEditor {
+    fn new_prompt(workspace: &mut Workspace, _: &NewPrompt, cx: &mut ViewContext<Workspace>) {
+        workspace.toggle_modal(cx, |cx| Self::new(None, cx));
+    }
+
+    fn new(prompt: Option<Prompt>, cx: &mut ViewContext<Self>) -> Self {
+        let title_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Title", cx);
+            if let Some(prompt) = &prompt {
+                editor.set_text(prompt.title.clone(), cx);
+            }
+            editor
+        });
+        let body_editor = cx.new_view(|cx| {
+            let mut editor = Editor::auto_height(16, cx);
+            editor.set_placeholder_text("Prompt, which may use {{selection}} or {{file_path}}", cx);
+            if let Some(prompt) = &prompt {
+                editor.set_text(prompt.body.clone(), cx);
+            }
+            editor
+        });
+        Self {
+            prompt_id: prompt.map(|prompt| prompt.id),
+            title_editor,
+            body_editor,
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let title = self.title_editor.read(cx).text(cx).trim().to_string();
+        let body = self.body_editor.read(cx).text(cx).trim().to_string();
+        if title.is_empty() {
+            cx.focus_view(&self.title_editor);
+            return;
+        }
+        if body.is_empty() {
+            cx.focus_view(&self.body_editor);
+            return;
+        }
+
+        let save = match self.prompt_id {
+            Some(id) => PROMPT_LIBRARY_DB.update_prompt(title, body, id),
+            None => PROMPT_LIBRARY_DB.insert_prompt(title, body, false),
+        };
+        cx.spawn(|_, mut cx| async move {
+            save.await?;
+            cx.update(|cx| reload_default_prompts(cx))
+        })
+        .detach_and_log_err(cx);
+        cx.emit(DismissEvent);
+    }
+
+    fn render_editor(&self, editor: &View<Editor>, cx: &ViewContext<Self>) -> impl IntoElement {
+        let settings = ThemeSettings::get_global(cx);
+        let text_style = TextStyle {
+            color: cx.theme().colors().text,
+            font_family: settings.ui_font.family.clone(),
+            font_features: settings.ui_font.features.clone(),
+            font_size: rems(0.875).into(),
+            font_weight: FontWeight::NORMAL,
+            font_style: FontStyle::Normal,
+            line_height: relative(1.3),
+            background_color: None,
+            underline: None,
+            strikethrough: None,
+            white_space: WhiteSpace::Normal,
+        };
+        div()
+            .px_2()
+            .py_1()
+            .rounded_md()
+            .border_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(EditorElement::new(
+                editor,
+                EditorStyle {
+                    background: cx.theme().colors().editor_background,
+                    local_player: cx.theme().players().local(),
+                    text: text_style,
+                    ..Default::default()
+                },
+            ))
+    }
+}
+
+impl Render for PromptEditor {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let title = if self.prompt_id.is_some() {
+            "Edit Prompt"
+        } else {
+            "New Prompt"
+        };
+        v_flex()
+            .key_context("PromptEditor")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .elevation_2(cx)
+            .w(rems(34.))
+            .p_2()
+            .gap_2()
+            .child(Label::new(title))
+            .child(self.render_editor(&self.title_editor, cx))
+            .child(self.render_editor(&self.body_editor, cx))
+            .child(
+                Label::new("Press enter to save, or shift-enter for a new line.")
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+    }
+}
+
+impl FocusableView for PromptEditor {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.title_editor.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for PromptEditor {}
+impl ModalView for PromptEditor {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_prompt() {
+        let context = PromptContext {
+            selection: Some("fn main() {}".into()),
+            file_path: Some("src/main.rs".into()),
+            language: Some("Rust".into()),
+            diagnostics: None,
+        };
+
+        assert_eq!(
+            render_prompt(
+                "Write a test for this {{language}} code from {{ file_path }}:\n{{selection}}",
+                &context
+            ),
+            "Write a test for this Rust code from src/main.rs:\nfn main() {}"
+        );
+        assert_eq!(
+            render_prompt("Fix these: {{diagnostics}}.", &context),
+            "Fix these: ."
+        );
+        assert_eq!(
+            render_prompt("Keep {{unknown}} and {{unclosed", &context),
+            "Keep {{unknown}} and {{unclosed"
+        );
+    }
+
+    #[test]
+    fn test_prompt_markdown() {
+        let prompt = Prompt {
+            id: 1,
+            title: "Review for unsafe code".into(),
+            body: "Review {{selection}} for unsound `unsafe` blocks.".into(),
+            is_default: true,
+        };
+        assert_eq!(prompt.file_stem(), "review-for-unsafe-code");
+        assert_eq!(
+            Prompt::from_markdown(&prompt.to_markdown(), "ignored"),
+            (prompt.title.clone(), prompt.body.clone(), true)
+        );
+
+        assert_eq!(
+            Prompt::from_markdown("Write a test in our style.\n", "write-test"),
+            (
+                "write-test".into(),
+                "Write a test in our style.".into(),
+                false
+            )
+        );
+
+        assert_eq!(
+            Prompt::from_markdown(
+                "---\r\ntitle: Explain\r\ndefault: true\r\n---\r\nExplain {{selection}}.\r\n",
+                "ignored"
+            ),
+            ("Explain".into(), "Explain {{selection}}.".into(), true)
+        );
+    }
+
+    #[test]
+    fn test_export_file_names() {
+        let prompts = ["Review", "review", "Review 2", "???"]
+            .into_iter()
+            .enumerate()
+            .map(|(id, title)| Prompt {
+                id: id as i64,
+                title: title.into(),
+                body: String::new(),
+                is_default: false,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            export_file_names(&prompts),
+            ["review.md", "review-2.md", "review-2-2.md", "prompt-3.md"]
+        );
+    }
+}
@@ -93,3 +93,89 @@ pub fn generate_content_prompt(
 
     Ok(prompt)
 }
+
+pub fn generate_terminal_assistant_prompt(
+    user_prompt: &str,
+    shell: Option<&str>,
+    working_directory: Option<&str>,
+    latest_output: &str,
+) -> String {
+    let mut prompt = String::new();
+    writeln!(prompt, "You are an expert terminal user.").unwrap();
+    writeln!(
+        prompt,
+        "You will be given a description of a command and you need to respond with a command that matches the description."
+    )
+    .unwrap();
+    writeln!(
+        prompt,
+        "Do not include markdown blocks or any other text formatting in your response, always respond with a single command that can be executed in the given shell."
+    )
+    .unwrap();
+    writeln!(prompt, "Current OS is '{}'.", std::env::consts::OS).unwrap();
+    if let Some(shell) = shell {
+        writeln!(prompt, "Current shell is '{shell}'.").unwrap();
+    }
+    if let Some(working_directory) = working_directory {
+        writeln!(
+            prompt,
+            "Current working directory is '{working_directory}'."
+        )
+        .unwrap();
+    }
+    if !latest_output.trim().is_empty() {
+        writeln!(
+            prompt,
+            "Latest lines of the terminal output:\n```\n{}\n```",
+            latest_output.trim()
+        )
+        .unwrap();
+    }
+    writeln!(prompt, "Here is the description of the command:").unwrap();
+    prompt.push_str(user_prompt);
+    prompt
+}
+
+pub fn generate_commit_message_prompt(changes: &str, is_summarized: bool) -> String {
+    let mut prompt = String::new();
+    writeln!(
+        prompt,
+        "You are an expert software engineer writing a git commit message."
+    )
+    .unwrap();
+    writeln!(
+        prompt,
+        "Follow the Conventional Commits format: a subject line of the form `type(scope): summary` of at most 72 characters, where type is one of feat, fix, docs, style, refactor, perf, test, build, ci or chore, followed by a blank line and a short body explaining what changed and why."
+    )
+    .unwrap();
+    writeln!(
+        prompt,
+        "Respond with the commit message only, without markdown blocks or any other text."
+    )
+    .unwrap();
+    if is_summarized {
+        writeln!(
+            prompt,
+            "The diff was too large to include, so here is a summary of the changes to each file:"
+        )
+        .unwrap();
+        prompt.push_str(changes);
+    } else {
+        writeln!(prompt, "Here is the diff of the changes:\n```diff").unwrap();
+        prompt.push_str(changes);
+        writeln!(prompt, "\n```").unwrap();
+    }
+    prompt
+}
+
+pub fn summarize_file_diff_prompt(path: &str, diff: &str) -> String {
+    let mut prompt = String::new();
+    writeln!(
+        prompt,
+        "Summarize the following changes to `{path}` in one sentence, focusing on their intent rather than on individual lines."
+    )
+    .unwrap();
+    writeln!(prompt, "Respond with the summary only.").unwrap();
+    writeln!(prompt, "```diff\n{diff}\n```").unwrap();
+    prompt
+}
@@ -0,0 +1,432 @@
+use crate::{CompletionProvider, LanguageModelRequest};
+use anyhow::{anyhow, Result};
+use client::telemetry::Telemetry;
+use collections::VecDeque;
+use editor::{
+    actions::{MoveDown, MoveUp},
+    Editor, EditorElement, EditorEvent, EditorStyle,
+};
+use futures::StreamExt;
+use gpui::{
+    relative, rems, AppContext, EventEmitter, FocusHandle, FocusableView, FontStyle, FontWeight,
+    Model, ModelContext, Subscription, Task, TextStyle, View, WhiteSpace,
+};
+use settings::Settings;
+use std::{path::Path, sync::Arc, time::Instant};
+use terminal::{
+    terminal_settings::{Shell, TerminalSettings},
+    Terminal,
+};
+use theme::ThemeSettings;
+use ui::{prelude::*, Tooltip};
+
+/// Clears the line that is being edited at the shell prompt, by moving to its end (ctrl-e)
+/// and deleting back to its start (ctrl-u), as ctrl-u alone only deletes up to the cursor.
+const CLEAR_INPUT: &str = "\x05\x15";
+/// Submits the line that is being edited at the shell prompt.
+const CARRIAGE_RETURN: &str = "\x0d";
+
+/// Streams a generated command into the terminal's prompt, without running it.
+pub struct TerminalCodegen {
+    terminal: Model<Terminal>,
+    command: String,
+    error: Option<anyhow::Error>,
+    generation: Task<()>,
+    idle: bool,
+    telemetry: Option<Arc<Telemetry>>,
+}
+
+impl TerminalCodegen {
+    pub fn new(terminal: Model<Terminal>, telemetry: Option<Arc<Telemetry>>) -> Self {
+        Self {
+            terminal,
+            command: String::new(),
+            error: None,
+            generation: Task::ready(()),
+            idle: true,
+            telemetry,
+        }
+    }
+
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    pub fn idle(&self) -> bool {
+        self.idle
+    }
+
+    pub fn error(&self) -> Option<&anyhow::Error> {
+        self.error.as_ref()
+    }
+
+    pub fn start(&mut self, prompt: LanguageModelRequest, cx: &mut ModelContext<Self>) {
+        if !self.terminal.read(cx).is_at_prompt() {
+            self.error = Some(anyhow!(
+                "the terminal is running a program, wait for the shell prompt"
+            ));
+            cx.notify();
+            return;
+        }
+
+        let model_telemetry_id = prompt.model.telemetry_id();
+        let response = CompletionProvider::global(cx).complete(prompt, cx);
+        let telemetry = self.telemetry.clone();
+        self.generation = cx.spawn(|this, mut cx| async move {
+            let request_start = Instant::now();
+            let mut response_latency = None;
+            let generate = async {
+                let mut chunks = response.await?;
+                let mut response = String::new();
+                while let Some(chunk) = chunks.next().await {
+                    response_latency.get_or_insert_with(|| request_start.elapsed());
+                    response.push_str(&chunk?);
+                    let command = extract_command(&response)?;
+                    this.update(&mut cx, |this, cx| this.set_command(command, cx))??;
+                }
+                anyhow::Ok(())
+            };
+
+            let result = generate.await;
+            if let Some(telemetry) = telemetry {
+                telemetry.report_assistant_event(
+                    None,
+                    telemetry_events::AssistantKind::InlineTerminal,
+                    model_telemetry_id,
+                    response_latency,
+                    result.as_ref().err().map(|error| error.to_string()),
+                );
+            }
+            this.update(&mut cx, |this, cx| {
+                this.idle = true;
+                if let Err(error) = result {
+                    this.error = Some(error);
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+        self.error.take();
+        self.idle = false;
+        cx.notify();
+    }
+
+    fn set_command(&mut self, command: String, cx: &mut ModelContext<Self>) -> Result<()> {
+        if command == self.command {
+            return Ok(());
+        }
+        // The input would be read by the running program instead, which may act on it.
+        if !self.terminal.read(cx).is_at_prompt() {
+            return Err(anyhow!("the shell prompt is no longer active"));
+        }
+        self.terminal.update(cx, |terminal, _| {
+            terminal.input(format!("{CLEAR_INPUT}{command}"))
+        });
+        self.command = command;
+        cx.notify();
+        Ok(())
+    }
+
+    /// Runs the generated command.
+    pub fn complete(&mut self, cx: &mut ModelContext<Self>) {
+        if self.idle && !self.command.is_empty() && self.terminal.read(cx).is_at_prompt() {
+            self.terminal
+                .update(cx, |terminal, _| terminal.input(CARRIAGE_RETURN.into()));
+        }
+    }
+
+    /// Stops generating and removes the generated command from the prompt.
+    pub fn undo(&mut self, cx: &mut ModelContext<Self>) {
+        self.generation = Task::ready(());
+        self.idle = true;
+        if !self.command.is_empty() {
+            if self.terminal.read(cx).is_at_prompt() {
+                self.terminal
+                    .update(cx, |terminal, _| terminal.input(CLEAR_INPUT.into()));
+            }
+            self.command.clear();
+        }
+        cx.notify();
+    }
+}
+
+/// Turns a model response into a single line that can be previewed at the shell prompt
+/// without being executed. Lines continued with a backslash are joined, and a response with
+/// several commands is rejected, as entering its line breaks at the prompt would run them.
+fn extract_command(response: &str) -> Result<String> {
+    let mut command = String::new();
+    for line in response.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("```") {
+            continue;
+        }
+
+        if !command.is_empty() {
+            let Some(continued) = command.strip_suffix('\\') else {
+                return Err(anyhow!("the response contains more than one command"));
+            };
+            command.truncate(continued.trim_end().len());
+            command.push(' ');
+        }
+        command.extend(line.chars().filter(|ch| !ch.is_control()));
+    }
+    Ok(command)
+}
+
+/// The name of the shell running in the terminal, as configured in the terminal settings,
+/// or as reported by the terminal's process otherwise.
+pub fn shell_name(terminal: &Terminal, cx: &AppContext) -> Option<String> {
+    match &TerminalSettings::get_global(cx).shell {
+        Shell::Program(program) | Shell::WithArguments { program, .. } => Path::new(program)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned()),
+        Shell::System => terminal
+            .pty_info
+            .current
+            .as_ref()
+            .map(|info| info.name.clone()),
+    }
+}
+
+pub enum TerminalInlineAssistantEvent {
+    Confirmed { prompt: String },
+    Executed,
+    Canceled,
+}
+
+/// The prompt shown below a terminal, used to generate a command at its shell prompt.
+pub struct TerminalInlineAssistant {
+    pub id: usize,
+    prompt_editor: View<Editor>,
+    confirmed_prompt: Option<String>,
+    prompt_history: VecDeque<String>,
+    prompt_history_ix: Option<usize>,
+    pending_prompt: String,
+    codegen: Model<TerminalCodegen>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl EventEmitter<TerminalInlineAssistantEvent> for TerminalInlineAssistant {}
+
+impl Render for TerminalInlineAssistant {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let codegen = self.codegen.read(cx);
+        let status_icon = if let Some(error) = codegen.error() {
+            let error_message = SharedString::from(error.to_string());
+            div()
+                .id("error")
+                .tooltip(move |cx| Tooltip::text(error_message.clone(), cx))
+                .child(Icon::new(IconName::XCircle).color(Color::Error))
+                .into_any_element()
+        } else if !codegen.idle() {
+            Icon::new(IconName::Spinner)
+                .color(Color::Muted)
+                .into_any_element()
+        } else {
+            Icon::new(IconName::Ai)
+                .color(Color::Muted)
+                .into_any_element()
+        };
+        let hint = self.can_execute(cx).then(|| {
+            Label::new("Enter to run, Escape to discard")
+                .size(LabelSize::Small)
+                .color(Color::Muted)
+        });
+
+        h_flex()
+            .w_full()
+            .py_2()
+            .px_3()
+            .gap_2()
+            .border_t_1()
+            .border_color(cx.theme().colors().border)
+            .bg(cx.theme().colors().editor_background)
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::move_up))
+            .on_action(cx.listener(Self::move_down))
+            .child(status_icon)
+            .child(div().flex_1().child(self.render_prompt_editor(cx)))
+            .children(hint)
+    }
+}
+
+impl FocusableView for TerminalInlineAssistant {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.prompt_editor.focus_handle(cx)
+    }
+}
+
+impl TerminalInlineAssistant {
+    pub fn new(
+        id: usize,
+        prompt_history: VecDeque<String>,
+        codegen: Model<TerminalCodegen>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let prompt_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Describe the command to generate…", cx);
+            editor
+        });
+        cx.focus_view(&prompt_editor);
+
+        let subscriptions = vec![
+            cx.observe(&codegen, Self::handle_codegen_changed),
+            cx.subscribe(&prompt_editor, Self::handle_prompt_editor_events),
+        ];
+
+        Self {
+            id,
+            prompt_editor,
+            confirmed_prompt: None,
+            prompt_history,
+            prompt_history_ix: None,
+            pending_prompt: String::new(),
+            codegen,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    /// Whether the generated command matches the current prompt, so that confirming runs it.
+    fn can_execute(&self, cx: &AppContext) -> bool {
+        let codegen = self.codegen.read(cx);
+        codegen.idle()
+            && codegen.error().is_none()
+            && !codegen.command().is_empty()
+            && self.confirmed_prompt.as_deref() == Some(self.pending_prompt.as_str())
+    }
+
+    fn handle_prompt_editor_events(
+        &mut self,
+        _: View<Editor>,
+        event: &EditorEvent,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let EditorEvent::Edited = event {
+            self.pending_prompt = self.prompt_editor.read(cx).text(cx);
+            cx.notify();
+        }
+    }
+
+    fn handle_codegen_changed(&mut self, _: Model<TerminalCodegen>, cx: &mut ViewContext<Self>) {
+        let is_read_only = !self.codegen.read(cx).idle();
+        self.prompt_editor.update(cx, |editor, cx| {
+            if editor.read_only(cx) != is_read_only {
+                editor.set_read_only(is_read_only);
+            }
+        });
+        cx.notify();
+    }
+
+    fn cancel(&mut self, _: &editor::actions::Cancel, cx: &mut ViewContext<Self>) {
+        cx.emit(TerminalInlineAssistantEvent::Canceled);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        if !self.codegen.read(cx).idle() {
+            return;
+        }
+
+        if self.can_execute(cx) {
+            cx.emit(TerminalInlineAssistantEvent::Executed);
+        } else {
+            let prompt = self.prompt_editor.read(cx).text(cx);
+            if prompt.trim().is_empty() {
+                return;
+            }
+            self.pending_prompt = prompt.clone();
+            self.confirmed_prompt = Some(prompt.clone());
+            cx.emit(TerminalInlineAssistantEvent::Confirmed { prompt });
+            cx.notify();
+        }
+    }
+
+    fn move_up(&mut self, _: &MoveUp, cx: &mut ViewContext<Self>) {
+        if let Some(ix) = self.prompt_history_ix {
+            if ix > 0 {
+                self.prompt_history_ix = Some(ix - 1);
+                let prompt = self.prompt_history[ix - 1].clone();
+                self.set_prompt(&prompt, cx);
+            }
+        } else if !self.prompt_history.is_empty() {
+            self.prompt_history_ix = Some(self.prompt_history.len() - 1);
+            let prompt = self.prompt_history[self.prompt_history.len() - 1].clone();
+            self.set_prompt(&prompt, cx);
+        }
+    }
+
+    fn move_down(&mut self, _: &MoveDown, cx: &mut ViewContext<Self>) {
+        if let Some(ix) = self.prompt_history_ix {
+            if ix < self.prompt_history.len() - 1 {
+                self.prompt_history_ix = Some(ix + 1);
+                let prompt = self.prompt_history[ix + 1].clone();
+                self.set_prompt(&prompt, cx);
+            } else {
+                self.prompt_history_ix = None;
+                let pending_prompt = self.pending_prompt.clone();
+                self.set_prompt(&pending_prompt, cx);
+            }
+        }
+    }
+
+    fn set_prompt(&mut self, prompt: &str, cx: &mut ViewContext<Self>) {
+        self.prompt_editor.update(cx, |editor, cx| {
+            editor.buffer().update(cx, |buffer, cx| {
+                let len = buffer.len(cx);
+                buffer.edit([(0..len, prompt)], None, cx);
+            });
+        });
+    }
+
+    fn render_prompt_editor(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let settings = ThemeSettings::get_global(cx);
+        let text_style = TextStyle {
+            color: if self.prompt_editor.read(cx).read_only(cx) {
+                cx.theme().colors().text_disabled
+            } else {
+                cx.theme().colors().text
+            },
+            font_family: settings.ui_font.family.clone(),
+            font_features: settings.ui_font.features.clone(),
+            font_size: rems(0.875).into(),
+            font_weight: FontWeight::NORMAL,
+            font_style: FontStyle::Normal,
+            line_height: relative(1.3),
+            background_color: None,
+            underline: None,
+            strikethrough: None,
+            white_space: WhiteSpace::Normal,
+        };
+        EditorElement::new(
+            &self.prompt_editor,
+            EditorStyle {
+                background: cx.theme().colors().editor_background,
+                local_player: cx.theme().players().local(),
+                text: text_style,
+                ..Default::default()
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_command() {
+        assert_eq!(extract_command("ls -la\n").unwrap(), "ls -la");
+        assert_eq!(
+            extract_command("```bash\nfind . -name '*.rs' | wc -l\n```").unwrap(),
+            "find . -name '*.rs' | wc -l"
+        );
+        assert_eq!(
+            extract_command("cargo build \\\n  --release").unwrap(),
+            "cargo build --release"
+        );
+        assert!(extract_command("mkdir out\ncd out\n").is_err());
+        assert!(extract_command("ls\nThis lists the files.").is_err());
+        assert_eq!(extract_command("echo hi\r\n").unwrap(), "echo hi");
+    }
+}
@@ -0,0 +1,590 @@
+use crate::{
+    assistant_settings::{AssistantSettings, ModelPrice},
+    ShowUsage,
+};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, Local, TimeZone};
+use db::{define_connection, query, sqlez_macros::sql};
+use futures::{future::BoxFuture, stream::BoxStream, FutureExt, StreamExt};
+use gpui::{
+    AppContext, BackgroundExecutor, DismissEvent, EventEmitter, FocusHandle, FocusableView, Global,
+    Render, Task, ViewContext,
+};
+use settings::Settings;
+use ui::prelude::*;
+use util::ResultExt;
+use workspace::{notifications::NotificationId, ModalView, Toast, Workspace};
+
+define_connection!(pub static ref USAGE_DB: UsageDb<()> =
+    &[sql!(
+        CREATE TABLE usage (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp INTEGER NOT NULL,
+            day TEXT NOT NULL,
+            provider TEXT NOT NULL,
+            model TEXT NOT NULL,
+            project TEXT,
+            prompt_tokens INTEGER NOT NULL,
+            completion_tokens INTEGER NOT NULL,
+            cost REAL
+        ) STRICT;
+
+        CREATE INDEX usage_timestamp ON usage(timestamp);
+    )];
+);
+
+impl UsageDb {
+    query! {
+        pub async fn record_usage(
+            timestamp: i64,
+            day: String,
+            provider: String,
+            model: String,
+            project: Option<String>,
+            prompt_tokens: i64,
+            completion_tokens: i64,
+            cost: Option<f64>
+        ) -> Result<()> {
+            INSERT INTO usage(
+                timestamp, day, provider, model, project, prompt_tokens, completion_tokens, cost
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        }
+    }
+
+    query! {
+        pub async fn cost_since(timestamp: i64) -> Result<Option<f64>> {
+            SELECT COALESCE(SUM(cost), 0.0) FROM usage WHERE timestamp >= ?
+        }
+    }
+
+    query! {
+        fn usage_by_day(since: i64) -> Result<Vec<(String, i64, i64, f64)>> {
+            SELECT day, SUM(prompt_tokens), SUM(completion_tokens), COALESCE(SUM(cost), 0.0)
+            FROM usage
+            WHERE timestamp >= ?
+            GROUP BY day
+            ORDER BY day DESC
+        }
+    }
+
+    query! {
+        fn usage_by_project(since: i64) -> Result<Vec<(Option<String>, i64, i64, f64)>> {
+            SELECT project, SUM(prompt_tokens), SUM(completion_tokens), COALESCE(SUM(cost), 0.0)
+            FROM usage
+            WHERE timestamp >= ?
+            GROUP BY project
+            ORDER BY 4 DESC, 2 DESC
+        }
+    }
+
+    query! {
+        fn usage_by_model(since: i64) -> Result<Vec<(String, String, i64, i64, f64)>> {
+            SELECT provider, model, SUM(prompt_tokens), SUM(completion_tokens), COALESCE(SUM(cost), 0.0)
+            FROM usage
+            WHERE timestamp >= ?
+            GROUP BY provider, model
+            ORDER BY 5 DESC, 3 DESC
+        }
+    }
+}
+
+/// Estimates the cost in dollars of a request from the model's price per million tokens.
+fn estimate_cost(price: &ModelPrice, prompt_tokens: usize, completion_tokens: usize) -> f64 {
+    (prompt_tokens as f64 * price.prompt + completion_tokens as f64 * price.completion) / 1_000_000.
+}
+
+fn start_of_month(now: DateTime<Local>) -> i64 {
+    Local
+        .with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .earliest()
+        .map_or(0, |start| start.timestamp())
+}
+
+/// Records the tokens used by a completion once its response stops streaming, whether it was
+/// streamed to the end or dropped early, after checking that the monthly budgets allow the
+/// request to be sent at all.
+///
+/// The telemetry id names the provider and the model, as in `openai/gpt-4o`, and
+/// `completion_text` extracts the text from each chunk of the response so its tokens can be
+/// counted.
+pub fn track_usage<T: 'static + Send>(
+    telemetry_id: String,
+    prompt_tokens: BoxFuture<'static, Result<usize>>,
+    response: BoxFuture<'static, Result<BoxStream<'static, Result<T>>>>,
+    completion_text: fn(&T) -> Option<&str>,
+    cx: &AppContext,
+) -> BoxFuture<'static, Result<BoxStream<'static, Result<T>>>> {
+    let budget = check_budget(cx);
+    let (provider, model) = telemetry_id
+        .split_once('/')
+        .unwrap_or((telemetry_id.as_str(), ""));
+    let usage = PendingUsage {
+        prompt_tokens,
+        completion: String::new(),
+        provider: provider.to_string(),
+        model: model.to_string(),
+        project: active_project_name(cx),
+        price: AssistantSettings::get_global(cx)
+            .usage
+            .prices
+            .get(&telemetry_id)
+            .copied(),
+    };
+    let executor = cx.background_executor().clone();
+
+    async move {
+        budget.await?;
+        let chunks = response.await?;
+        let guard = UsageGuard {
+            usage: Some(usage),
+            executor,
+        };
+        let chunks =
+            futures::stream::unfold((chunks, guard), move |(mut chunks, mut guard)| async move {
+                let chunk = chunks.next().await?;
+                if let (Ok(chunk), Some(usage)) = (&chunk, guard.usage.as_mut()) {
+                    if let Some(text) = completion_text(chunk) {
+                        usage.completion.push_str(text);
+                    }
+                }
+                Some((chunk, (chunks, guard)))
+            });
+        Ok(chunks.boxed())
+    }
+    .boxed()
+}
+
+/// The usage of a completion whose response is still streaming.
+struct PendingUsage {
+    prompt_tokens: BoxFuture<'static, Result<usize>>,
+    completion: String,
+    provider: String,
+    model: String,
+    project: Option<String>,
+    price: Option<ModelPrice>,
+}
+
+impl PendingUsage {
+    async fn record(self) {
+        let prompt_tokens = self.prompt_tokens.await.log_err().unwrap_or_default();
+        let completion_tokens = estimate_tokens(&self.completion);
+        let cost = self
+            .price
+            .map(|price| estimate_cost(&price, prompt_tokens, completion_tokens));
+        let now = Local::now();
+        USAGE_DB
+            .record_usage(
+                now.timestamp(),
+                now.format("%Y-%m-%d").to_string(),
+                self.provider,
+                self.model,
+                self.project,
+                prompt_tokens as i64,
+                completion_tokens as i64,
+                cost,
+            )
+            .await
+            .log_err();
+    }
+}
+
+/// Records the usage of a completion when its response stream is dropped.
+struct UsageGuard {
+    usage: Option<PendingUsage>,
+    executor: BackgroundExecutor,
+}
+
+impl Drop for UsageGuard {
+    fn drop(&mut self) {
+        if let Some(usage) = self.usage.take() {
+            self.executor.spawn(usage.record()).detach();
+        }
+    }
+}
+
+/// Estimates the number of tokens in a message, for providers that don't count them.
+pub fn estimate_tokens(content: &str) -> usize {
+    // Like `count_tokens`, approximate the tokenizers of models that tiktoken doesn't know about
+    // with GPT-4's.
+    let message = tiktoken_rs::ChatCompletionRequestMessage {
+        role: "assistant".into(),
+        content: Some(content.into()),
+        name: None,
+        function_call: None,
+    };
+    tiktoken_rs::num_tokens_from_messages("gpt-4", &[message])
+        .log_err()
+        .unwrap_or_else(|| content.len().div_ceil(4))
+}
+
+/// Fails if this month's hard budget has been spent, and warns once a month if the soft budget
+/// has. The month's spending is read on the database's thread rather than the main thread.
+fn check_budget(cx: &AppContext) -> Task<Result<()>> {
+    let settings = &AssistantSettings::get_global(cx).usage;
+    let soft_budget = settings.soft_monthly_budget;
+    let hard_budget = settings.hard_monthly_budget;
+    if soft_budget.is_none() && hard_budget.is_none() {
+        return Task::ready(Ok(()));
+    }
+
+    cx.spawn(|mut cx| async move {
+        let now = Local::now();
+        let spent = USAGE_DB
+            .cost_since(start_of_month(now))
+            .await?
+            .unwrap_or_default();
+        if let Some(budget) = hard_budget {
+            if spent >= budget {
+                return Err(anyhow!(
+                    "The assistant's monthly budget of ${budget:.2} has been reached, with an estimated ${spent:.2} spent this month. Raise `assistant.usage.hard_monthly_budget` in your settings to keep using it."
+                ));
+            }
+        }
+
+        if let Some(budget) = soft_budget {
+            if spent >= budget {
+                let month = now.format("%Y-%m").to_string();
+                cx.update(|cx| warn_about_budget(month, budget, spent, cx))
+                    .log_err();
+            }
+        }
+
+        Ok(())
+    })
+}
+
+/// The month for which the soft budget warning was last shown, so that it's only shown once.
+#[derive(Default)]
+struct BudgetWarning {
+    month: Option<String>,
+}
+
+impl Global for BudgetWarning {}
+
+fn warn_about_budget(month: String, budget: f64, spent: f64, cx: &mut AppContext) {
+    let warning = cx.default_global::<BudgetWarning>();
+    if warning.month.as_ref() == Some(&month) {
+        return;
+    }
+    warning.month = Some(month);
+
+    let Some(workspace) = cx
+        .active_window()
+        .and_then(|window| window.downcast::<Workspace>())
+    else {
+        return;
+    };
+    workspace
+        .update(cx, |workspace, cx| {
+            workspace.show_toast(
+                Toast::new(
+                    NotificationId::unique::<BudgetWarning>(),
+                    format!(
+                        "The assistant has used an estimated ${spent:.2} this month, over the ${budget:.2} budget"
+                    ),
+                ),
+                cx,
+            )
+        })
+        .log_err();
+}
+
+fn active_project_name(cx: &AppContext) -> Option<String> {
+    let workspace = cx.active_window()?.downcast::<Workspace>()?;
+    let project = workspace.read(cx).ok()?.project().read(cx);
+    let worktree_names = project
+        .visible_worktrees(cx)
+        .map(|worktree| worktree.read(cx).root_name().to_string())
+        .collect::<Vec<_>>();
+    (!worktree_names.is_empty()).then(|| worktree_names.join(", "))
+}
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(
+        |workspace: &mut Workspace, _cx: &mut ViewContext<Workspace>| {
+            workspace.register_action(|workspace, _: &ShowUsage, cx| {
+                workspace.toggle_modal(cx, UsageView::new);
+            });
+        },
+    )
+    .detach();
+}
+
+struct UsageRow {
+    label: String,
+    prompt_tokens: i64,
+    completion_tokens: i64,
+    cost: f64,
+}
+
+/// Summarizes this month's usage of the assistant by day, project and model.
+pub struct UsageView {
+    focus_handle: FocusHandle,
+    spent: f64,
+    by_day: Vec<UsageRow>,
+    by_project: Vec<UsageRow>,
+    by_model: Vec<UsageRow>,
+}
+
+impl UsageView {
+    fn new(cx: &mut ViewContext<Self>) -> Self {
+        let since = start_of_month(Local::now());
+        let by_day: Vec<UsageRow> = USAGE_DB
+            .usage_by_day(since)
+            .log_err()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(day, prompt_tokens, completion_tokens, cost)| UsageRow {
+                label: day,
+                prompt_tokens,
+                completion_tokens,
+                cost,
+            })
+            .collect();
+        let by_project = USAGE_DB
+            .usage_by_project(since)
+            .log_err()
+            .unwrap_or_default()
+            .into_iter()
+            .map(
+                |(project, prompt_tokens, completion_tokens, cost)| UsageRow {
+                    label: project.unwrap_or_else(|| "No project".into()),
+                    prompt_tokens,
+                    completion_tokens,
+                    cost,
+                },
+            )
+            .collect();
+        let by_model = USAGE_DB
+            .usage_by_model(since)
+            .log_err()
+            .unwrap_or_default()
+            .into_iter()
+            .map(
+                |(provider, model, prompt_tokens, completion_tokens, cost)| UsageRow {
+                    label: format!("{provider}/{model}"),
+                    prompt_tokens,
+                    completion_tokens,
+                    cost,
+                },
+            )
+            .collect();
+
+        Self {
+            focus_handle: cx.focus_handle(),
+            spent: by_day.iter().map(|row| row.cost).sum(),
+            by_day,
+            by_project,
+            by_model,
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_section(title: &'static str, rows: &[UsageRow]) -> impl IntoElement {
+        v_flex()
+            .gap_1()
+            .child(Label::new(title).size(LabelSize::Small).color(Color::Muted))
+            .children(rows.iter().map(|row| {
+                h_flex()
+                    .justify_between()
+                    .gap_2()
+                    .child(Label::new(row.label.clone()))
+                    .child(
+                        Label::new(format!(
+                            "{} prompt + {} completion tokens · ${:.2}",
+                            row.prompt_tokens, row.completion_tokens, row.cost
+                        ))
+                        .color(Color::Muted),
+                    )
+            }))
+    }
+}
+
+impl Render for UsageView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let usage_settings = &AssistantSettings::get_global(cx).usage;
+        let mut summary = format!("An estimated ${:.2} spent this month", self.spent);
+        if let Some(budget) = usage_settings
+            .hard_monthly_budget
+            .or(usage_settings.soft_monthly_budget)
+        {
+            summary.push_str(&format!(", out of a ${budget:.2} budget"));
+        }
+
+        v_flex()
+            .key_context("UsageView")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::cancel))
+            .elevation_3(cx)
+            .w(rems(40.))
+            .p_4()
+            .gap_3()
+            .child(Headline::new("Assistant Usage").size(HeadlineSize::Small))
+            .child(Label::new(summary))
+            .map(|this| {
+                if self.by_day.is_empty() {
+                    this.child(
+                        Label::new("The assistant hasn't been used this month").color(Color::Muted),
+                    )
+                } else {
+                    this.child(
+                        div()
+                            .id("usage-sections")
+                            .max_h(rems(32.))
+                            .overflow_y_scroll()
+                            .child(
+                                v_flex()
+                                    .gap_3()
+                                    .child(Self::render_section("By Day", &self.by_day))
+                                    .child(Self::render_section("By Project", &self.by_project))
+                                    .child(Self::render_section("By Model", &self.by_model)),
+                            ),
+                    )
+                }
+            })
+    }
+}
+
+impl FocusableView for UsageView {
+    fn focus_handle(&self, _cx: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<DismissEvent> for UsageView {}
+impl ModalView for UsageView {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        assistant_settings::OpenAiModel, CompletionProvider, FakeCompletionProvider, LanguageModel,
+        LanguageModelRequest, LanguageModelRequestMessage, Role,
+    };
+    use gpui::{BorrowAppContext, TestAppContext};
+    use settings::SettingsStore;
+
+    #[gpui::test]
+    async fn test_track_usage(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        let provider = FakeCompletionProvider::default();
+        cx.update(|cx| {
+            cx.set_global(SettingsStore::test(cx));
+            AssistantSettings::register(cx);
+            cx.set_global(CompletionProvider::Fake(provider.clone()));
+        });
+        // A dollar per token, so the cost is the total number of tokens.
+        set_usage_settings(
+            r#"{
+                "prices": {
+                    "openai/gpt-4-turbo-preview": { "prompt": 1000000, "completion": 1000000 }
+                }
+            }"#,
+            cx,
+        );
+
+        // Usage is recorded when a response is dropped before it has been fully streamed.
+        let mut response = cx
+            .update(|cx| CompletionProvider::global(cx).complete(request(), cx))
+            .await
+            .unwrap();
+        provider.send_completion("Hello".into());
+        provider.send_completion(" world".into());
+        assert_eq!(response.next().await.unwrap().unwrap(), "Hello");
+        drop(response);
+        cx.run_until_parked();
+        USAGE_DB.write(|_| ()).await;
+
+        let completion_tokens = estimate_tokens("Hello");
+        let cost = (5 + completion_tokens) as f64;
+        let usage = USAGE_DB
+            .usage_by_model(0)
+            .unwrap()
+            .into_iter()
+            .find(|(provider, model, ..)| provider == "openai" && model == "gpt-4-turbo-preview")
+            .unwrap();
+        assert_eq!(
+            usage,
+            (
+                "openai".to_string(),
+                "gpt-4-turbo-preview".to_string(),
+                5,
+                completion_tokens as i64,
+                cost
+            )
+        );
+
+        // Requests are refused once the hard budget has been spent.
+        set_usage_settings(&format!(r#"{{ "hard_monthly_budget": {cost} }}"#), cx);
+        let error = cx
+            .update(|cx| CompletionProvider::global(cx).complete(request(), cx))
+            .await
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("monthly budget"), "{error}");
+
+        // Spending past the soft budget only warns.
+        set_usage_settings(
+            &format!(
+                r#"{{ "soft_monthly_budget": {cost}, "hard_monthly_budget": {} }}"#,
+                cost + 100.
+            ),
+            cx,
+        );
+        let response = cx
+            .update(|cx| CompletionProvider::global(cx).complete(request(), cx))
+            .await;
+        assert!(response.is_ok());
+        cx.run_until_parked();
+        cx.update(|cx| {
+            let month = Local::now().format("%Y-%m").to_string();
+            assert_eq!(cx.global::<BudgetWarning>().month, Some(month));
+        });
+    }
+
+    fn request() -> LanguageModelRequest {
+        LanguageModelRequest {
+            model: LanguageModel::OpenAi(OpenAiModel::FourTurbo),
+            messages: vec![LanguageModelRequestMessage {
+                role: Role::User,
+                content: "Hi!!!".into(),
+            }],
+            stop: Vec::new(),
+            temperature: 1.0,
+        }
+    }
+
+    fn set_usage_settings(usage: &str, cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store
+                    .set_user_settings(
+                        &format!(r#"{{ "assistant": {{ "version": "1", "usage": {usage} }} }}"#),
+                        cx,
+                    )
+                    .unwrap();
+            })
+        });
+    }
+
+    #[test]
+    fn test_estimate_cost() {
+        let price = ModelPrice {
+            prompt: 5.,
+            completion: 15.,
+        };
+        assert_eq!(estimate_cost(&price, 1_000_000, 0), 5.);
+        assert_eq!(estimate_cost(&price, 200_000, 100_000), 2.5);
+    }
+
+    #[test]
+    fn test_start_of_month() {
+        let now = Local.with_ymd_and_hms(2024, 3, 17, 12, 30, 0).unwrap();
+        let start = Local.timestamp_opt(start_of_month(now), 0).unwrap();
+        assert_eq!((start.year(), start.month(), start.day()), (2024, 3, 1));
+        assert!(start <= now);
+    }
+}
@@ -11,13 +11,16 @@ use crate::ui::UserOrAssistant;
 use ::ui::{div, prelude::*, Color, Tooltip, ViewContext};
 use anyhow::{Context, Result};
 use assistant_tooling::{
-    AttachmentRegistry, ProjectContext, ToolFunctionCall, ToolRegistry, UserAttachment,
+    AttachmentRegistry, LanguageModelTool as _, ProjectContext, ToolFunctionCall, ToolRegistry,
+    UserAttachment,
 };
-use attachments::ActiveEditorAttachmentTool;
+use attachments::{ActiveEditorAttachmentTool, ContextServerResourcesAttachment};
 use client::{proto, Client, UserStore};
 use collections::HashMap;
 use completion_provider::*;
+use context_servers::{ContextServerManager, ContextServerStatus};
 use editor::Editor;
+use extension::ExtensionStore;
 use feature_flags::FeatureFlagAppExt as _;
 use file_icons::FileIcons;
 use fs::Fs;
@@ -35,8 +38,11 @@ use semantic_index::{CloudEmbeddingProvider, ProjectIndex, ProjectIndexDebugView
 use serde::{Deserialize, Serialize};
 use settings::Settings;
 use std::sync::Arc;
-use tools::{AnnotationTool, CreateBufferTool, ProjectIndexTool};
-use ui::{ActiveFileButton, Composer, ProjectIndexButton};
+use tools::{
+    register_context_server_tools, AnnotationTool, CreateBufferTool, DiagnosticsTool,
+    EditFilesTool, ProjectIndexTool, RunTaskTool, SymbolsTool,
+};
+use ui::{ActiveFileButton, Composer, ContextServersButton, ProjectIndexButton};
 use util::paths::CONVERSATIONS_DIR;
 use util::{maybe, paths::EMBEDDINGS_DIR, ResultExt};
 use workspace::{
@@ -67,6 +73,7 @@ gpui::impl_actions!(assistant2, [Submit]);
 
 pub fn init(client: Arc<Client>, cx: &mut AppContext) {
     AssistantSettings::register(cx);
+    semantic_index::init(cx);
 
     cx.spawn(|mut cx| {
         let client = client.clone();
@@ -134,7 +141,7 @@ impl AssistantPanel {
                 })
                 .detach();
 
-                let mut tool_registry = ToolRegistry::new();
+                let tool_registry = ToolRegistry::new();
                 tool_registry
                     .register(ProjectIndexTool::new(project_index.clone()))
                     .unwrap();
@@ -144,10 +151,23 @@ impl AssistantPanel {
                 tool_registry
                     .register(AnnotationTool::new(workspace.clone(), project.clone()))
                     .unwrap();
+                tool_registry
+                    .register(EditFilesTool::new(workspace.clone(), project.clone()))
+                    .unwrap();
+                tool_registry
+                    .register(DiagnosticsTool::new(project.clone()))
+                    .unwrap();
+                tool_registry
+                    .register(SymbolsTool::new(project.clone()))
+                    .unwrap();
+                tool_registry
+                    .register(RunTaskTool::new(workspace.clone(), project.clone()))
+                    .unwrap();
 
                 let mut attachment_registry = AttachmentRegistry::new();
                 attachment_registry
                     .register(ActiveEditorAttachmentTool::new(workspace.clone(), cx));
+                attachment_registry.register(ContextServerResourcesAttachment);
 
                 Self::new(
                     project.read(cx).fs().clone(),
@@ -259,6 +279,7 @@ pub struct AssistantChat {
     saved_conversations: View<SavedConversations>,
     saved_conversations_open: bool,
     project_index_button: View<ProjectIndexButton>,
+    context_servers_button: Option<View<ContextServersButton>>,
     active_file_button: Option<View<ActiveFileButton>>,
     user_store: Model<UserStore>,
     next_message_id: MessageId,
@@ -266,6 +287,10 @@ pub struct AssistantChat {
     editing_message: Option<EditingMessage>,
     pending_completion: Option<Task<()>>,
     tool_registry: Arc<ToolRegistry>,
+    /// The names of the tools registered for each running context server.
+    context_server_tools: HashMap<Arc<str>, Vec<String>>,
+    /// The names of the tools registered for installed extensions.
+    extension_tools: Vec<String>,
     attachment_registry: Arc<AttachmentRegistry>,
     project_index: Model<ProjectIndex>,
     markdown_style: MarkdownStyle,
@@ -304,6 +329,28 @@ impl AssistantChat {
             ProjectIndexButton::new(project_index.clone(), tool_registry.clone(), cx)
         });
 
+        let mut context_server_tools = HashMap::default();
+        let context_servers_button = ContextServerManager::try_global(cx).map(|manager| {
+            for server in manager.read(cx).servers() {
+                if server.status == ContextServerStatus::Running {
+                    let tool_names = register_context_server_tools(&tool_registry, server);
+                    context_server_tools.insert(server.id.clone(), tool_names);
+                }
+            }
+            cx.subscribe(&manager, Self::handle_context_server_event)
+                .detach();
+            cx.new_view(|cx| ContextServersButton::new(manager, cx))
+        });
+
+        if let Some(extension_store) = ExtensionStore::try_global(cx) {
+            cx.subscribe(&extension_store, |this, _, event, cx| {
+                if let extension::Event::ExtensionsUpdated = event {
+                    this.register_extension_tools(cx);
+                }
+            })
+            .detach();
+        }
+
         let active_file_button = match workspace.upgrade() {
             Some(workspace) => {
                 Some(cx.new_view(
@@ -331,7 +378,7 @@ impl AssistantChat {
         })
         .detach_and_log_err(cx);
 
-        Self {
+        let mut this = Self {
             model,
             messages: Vec::new(),
             composer_editor: cx.new_view(|cx| {
@@ -347,6 +394,7 @@ impl AssistantChat {
             fs,
             language_registry,
             project_index_button,
+            context_servers_button,
             active_file_button,
             project_index,
             next_message_id: MessageId(0),
@@ -355,6 +403,8 @@ impl AssistantChat {
             pending_completion: None,
             attachment_registry,
             tool_registry,
+            context_server_tools,
+            extension_tools: Vec::new(),
             markdown_style: MarkdownStyle {
                 code_block: gpui::TextStyleRefinement {
                     font_family: Some("Zed Mono".into()),
@@ -391,7 +441,57 @@ impl AssistantChat {
                     selection
                 },
             },
+        };
+        this.register_extension_tools(cx);
+        this
+    }
+
+    /// Replaces the tools registered for extensions with the ones that are currently installed.
+    fn register_extension_tools(&mut self, cx: &mut ViewContext<Self>) {
+        for tool_name in self.extension_tools.drain(..) {
+            self.tool_registry.unregister(&tool_name);
+        }
+
+        let Some(extension_store) = ExtensionStore::try_global(cx) else {
+            return;
+        };
+        let Some(project) = self.project_index.read(cx).project().upgrade() else {
+            return;
+        };
+        for tool in extension_store.read(cx).assistant_tools(&project) {
+            let name = tool.name();
+            if self.tool_registry.register(tool).log_err().is_some() {
+                self.extension_tools.push(name);
+            }
+        }
+        cx.notify();
+    }
+
+    fn handle_context_server_event(
+        &mut self,
+        manager: Model<ContextServerManager>,
+        event: &context_servers::Event,
+        cx: &mut ViewContext<Self>,
+    ) {
+        match event {
+            context_servers::Event::ServerStarted { server_id } => {
+                if let Some(server) = manager.read(cx).server(server_id) {
+                    let tool_names = register_context_server_tools(&self.tool_registry, server);
+                    self.context_server_tools
+                        .insert(server_id.clone(), tool_names);
+                }
+            }
+            context_servers::Event::ServerStopped { server_id } => {
+                for tool_name in self
+                    .context_server_tools
+                    .remove(server_id)
+                    .unwrap_or_default()
+                {
+                    self.tool_registry.unregister(&tool_name);
+                }
+            }
         }
+        cx.notify();
     }
 
     fn message_for_id(&self, id: MessageId) -> Option<&ChatMessage> {
@@ -479,6 +579,10 @@ impl AssistantChat {
 
         let mode = *mode;
         self.pending_completion = Some(cx.spawn(move |this, mut cx| async move {
+            Self::run_slash_command(this.clone(), &mut cx)
+                .await
+                .log_err();
+
             let attachments_task = this.update(&mut cx, |this, cx| {
                 let attachment_registry = this.attachment_registry.clone();
                 attachment_registry.call_all_attachment_tools(cx)
@@ -519,6 +623,40 @@ impl AssistantChat {
         }));
     }
 
+    /// If the last user message starts with `/name`, where `name` is a slash command
+    /// provided by an extension or a prompt provided by a context server, runs the
+    /// command and replaces the message with its output.
+    async fn run_slash_command(this: WeakView<Self>, cx: &mut AsyncWindowContext) -> Result<()> {
+        let pending_command = this.update(cx, |this, cx| {
+            let Some(ChatMessage::User(message)) = this.messages.last() else {
+                return None;
+            };
+            let (name, argument) = parse_slash_command(message.body.read(cx).source())?;
+            let name = name.to_string();
+            let extension_command = ExtensionStore::try_global(cx).and_then(|extension_store| {
+                extension_store
+                    .read(cx)
+                    .slash_commands()
+                    .into_iter()
+                    .find(|command| command.name() == name)
+            });
+            let output = if let Some(command) = extension_command {
+                let project = this.project_index.read(cx).project().upgrade()?;
+                command.run(argument, &project, cx)
+            } else {
+                run_context_server_prompt(&name, argument, cx)?
+            };
+            Some((message.body.clone(), output))
+        })?;
+
+        let Some((body, output)) = pending_command else {
+            return Ok(());
+        };
+        let output = output.await?;
+        body.update(cx, |body, cx| body.reset(output, cx))?;
+        Ok(())
+    }
+
     async fn request_completion(
         this: WeakView<Self>,
         mode: SubmitMode,
@@ -556,6 +694,7 @@ impl AssistantChat {
                         Vec::new(),
                         1.0,
                         tool_definitions,
+                        cx,
                     )
                 });
 
@@ -833,6 +972,7 @@ impl AssistantChat {
                             return element.child(Composer::new(
                                 editing_message.body.clone(),
                                 self.project_index_button.clone(),
+                                self.context_servers_button.clone(),
                                 self.active_file_button.clone(),
                                 crate::ui::ModelSelector::new(
                                     cx.view().downgrade(),
@@ -1133,6 +1273,7 @@ impl Render for AssistantChat {
             .child(Composer::new(
                 self.composer_editor.clone(),
                 self.project_index_button.clone(),
+                self.context_servers_button.clone(),
                 self.active_file_button.clone(),
                 crate::ui::ModelSelector::new(cx.view().downgrade(), self.model.clone())
                     .into_any_element(),
@@ -1151,6 +1292,47 @@ impl MessageId {
     }
 }
 
+/// Splits a message of the form `/name argument` into the command's name and its argument.
+fn parse_slash_command(text: &str) -> Option<(&str, Option<String>)> {
+    let text = text.trim_start().strip_prefix('/')?;
+    let (name, argument) = match text.split_once(char::is_whitespace) {
+        Some((name, argument)) => (name, Some(argument.trim()).filter(|a| !a.is_empty())),
+        None => (text.trim_end(), None),
+    };
+    if name.is_empty() {
+        return None;
+    }
+    Some((name, argument.map(ToString::to_string)))
+}
+
+/// Runs the prompt with the given name from a running context server, passing the
+/// slash command's argument as the prompt's first argument.
+fn run_context_server_prompt(
+    name: &str,
+    argument: Option<String>,
+    cx: &mut AppContext,
+) -> Option<Task<Result<String>>> {
+    let manager = ContextServerManager::try_global(cx)?;
+    let (protocol, prompt) = manager.read(cx).servers().find_map(|server| {
+        let prompt = server.prompts.iter().find(|prompt| prompt.name == name)?;
+        Some((server.protocol()?, prompt.clone()))
+    })?;
+
+    let mut arguments = HashMap::default();
+    if let (Some(first_argument), Some(argument)) = (prompt.arguments.first(), argument) {
+        arguments.insert(first_argument.name.clone(), argument);
+    }
+    Some(cx.background_executor().spawn(async move {
+        let response = protocol.get_prompt(&prompt.name, arguments).await?;
+        Ok(response
+            .messages
+            .iter()
+            .map(|message| message.content.to_text())
+            .collect::<Vec<_>>()
+            .join("\n\n"))
+    }))
+}
+
 enum ChatMessage {
     User(UserMessage),
     Assistant(AssistantMessage),
@@ -1181,3 +1363,26 @@ struct AssistantMessage {
     pub messages: Vec<AssistantMessagePart>,
     pub error: Option<SharedString>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_slash_command() {
+        assert_eq!(parse_slash_command("/issues"), Some(("issues", None)));
+        assert_eq!(parse_slash_command("  /issues  \n"), Some(("issues", None)));
+        assert_eq!(
+            parse_slash_command("/issue 123"),
+            Some(("issue", Some("123".to_string())))
+        );
+        assert_eq!(
+            parse_slash_command("/issue\n  the title\nand body \n"),
+            Some(("issue", Some("the title\nand body".to_string())))
+        );
+        assert_eq!(parse_slash_command("/issue   "), Some(("issue", None)));
+        assert_eq!(parse_slash_command("/"), None);
+        assert_eq!(parse_slash_command("/ issue"), None);
+        assert_eq!(parse_slash_command("what does /issue do?"), None);
+    }
+}
@@ -1,3 +1,5 @@
 mod active_file;
+mod context_server;
 
 pub use active_file::*;
+pub use context_server::*;
@@ -0,0 +1,149 @@
+use std::{fmt::Write as _, sync::Arc};
+
+use anyhow::{Context as _, Result};
+use assistant_tooling::{AttachmentOutput, LanguageModelAttachment, ProjectContext};
+use context_servers::{ContextServerManager, ContextServerStatus};
+use futures::future::join_all;
+use gpui::{Render, Task, View};
+use serde::{Deserialize, Serialize};
+use ui::{prelude::*, ButtonLike, Tooltip, WindowContext};
+
+/// Resources are attached to every message, so cap how many are read at once.
+const MAX_RESOURCE_COUNT: usize = 20;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AttachedResource {
+    server_id: Arc<str>,
+    uri: String,
+    name: String,
+    text: String,
+}
+
+pub struct ContextServerResourcesView {
+    resources: Vec<AttachedResource>,
+    error: Option<anyhow::Error>,
+}
+
+impl Render for ContextServerResourcesView {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        if let Some(error) = &self.error {
+            return div().child(error.to_string()).into_any_element();
+        }
+
+        h_flex()
+            .gap_1()
+            .children(self.resources.iter().enumerate().map(|(ix, resource)| {
+                let uri: SharedString = resource.uri.clone().into();
+                let server_id: SharedString = resource.server_id.to_string().into();
+                ButtonLike::new(("context-server-resource", ix))
+                    .child(
+                        h_flex()
+                            .gap_1()
+                            .child(Icon::new(IconName::Server).size(IconSize::XSmall))
+                            .child(Label::new(resource.name.clone()).size(LabelSize::Small)),
+                    )
+                    .tooltip(move |cx| Tooltip::with_meta(uri.clone(), None, server_id.clone(), cx))
+            }))
+            .into_any_element()
+    }
+}
+
+impl AttachmentOutput for ContextServerResourcesView {
+    fn generate(&self, _project: &mut ProjectContext, _cx: &mut WindowContext) -> String {
+        let mut output = String::new();
+        for resource in &self.resources {
+            writeln!(
+                output,
+                "Resource {} ({}) from {}:\n```\n{}\n```",
+                resource.name, resource.uri, resource.server_id, resource.text
+            )
+            .unwrap();
+        }
+        output
+    }
+}
+
+/// Attaches the resources of context servers that are configured with `attach_resources`.
+pub struct ContextServerResourcesAttachment;
+
+impl LanguageModelAttachment for ContextServerResourcesAttachment {
+    type Output = Vec<AttachedResource>;
+    type View = ContextServerResourcesView;
+
+    fn name(&self) -> Arc<str> {
+        "context-server-resources".into()
+    }
+
+    fn run(&self, cx: &mut WindowContext) -> Task<Result<Vec<AttachedResource>>> {
+        let Some(manager) = ContextServerManager::try_global(cx) else {
+            return Task::ready(Ok(Vec::new()));
+        };
+
+        let reads = manager
+            .read(cx)
+            .servers()
+            .filter(|server| {
+                server.config.attach_resources && server.status == ContextServerStatus::Running
+            })
+            .filter_map(|server| Some((server, server.protocol()?)))
+            .flat_map(|(server, protocol)| {
+                server.resources.iter().map(move |resource| {
+                    let server_id = server.id.clone();
+                    let protocol = protocol.clone();
+                    let resource = resource.clone();
+                    async move {
+                        let contents =
+                            protocol
+                                .read_resource(&resource.uri)
+                                .await
+                                .with_context(|| {
+                                    format!("failed to read {} from {server_id}", resource.uri)
+                                })?;
+                        anyhow::Ok(AttachedResource {
+                            server_id,
+                            uri: resource.uri,
+                            name: resource.name,
+                            text: contents
+                                .iter()
+                                .map(|contents| contents.to_text())
+                                .collect::<Vec<_>>()
+                                .join("\n"),
+                        })
+                    }
+                })
+            })
+            .take(MAX_RESOURCE_COUNT)
+            .collect::<Vec<_>>();
+
+        // Attach the resources that could be read, rather than none of them when one fails.
+        cx.background_executor().spawn(async move {
+            let mut resources = Vec::new();
+            let mut first_error = None;
+            for result in join_all(reads).await {
+                match result {
+                    Ok(resource) => resources.push(resource),
+                    Err(error) => {
+                        log::error!("{error:?}");
+                        first_error.get_or_insert(error);
+                    }
+                }
+            }
+            match first_error {
+                Some(error) if resources.is_empty() => Err(error),
+                _ => Ok(resources),
+            }
+        })
+    }
+
+    fn view(
+        &self,
+        output: Result<Vec<AttachedResource>>,
+        cx: &mut WindowContext,
+    ) -> View<Self::View> {
+        let (resources, error) = match output {
+            Ok(resources) => (resources, None),
+            Err(error) => (Vec::new(), Some(error)),
+        };
+        cx.new_view(|_cx| ContextServerResourcesView { resources, error })
+    }
+}
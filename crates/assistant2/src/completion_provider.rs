@@ -1,4 +1,5 @@
 use anyhow::Result;
+use assistant::{estimate_tokens, track_usage};
 use assistant_tooling::ToolFunctionDefinition;
 use client::{proto, Client};
 use futures::{future::BoxFuture, stream::BoxStream, FutureExt, StreamExt};
@@ -27,6 +28,8 @@ impl CompletionProvider {
         self.0.available_models()
     }
 
+    /// Streams the completion of the messages, recording its token usage and refusing it when
+    /// the assistant's monthly budget has been spent.
     pub fn complete(
         &self,
         model: String,
@@ -34,15 +37,37 @@ impl CompletionProvider {
         stop: Vec<String>,
         temperature: f32,
         tools: Vec<ToolFunctionDefinition>,
+        cx: &AppContext,
     ) -> BoxFuture<'static, Result<BoxStream<'static, Result<proto::LanguageModelResponseMessage>>>>
     {
-        self.0.complete(model, messages, stop, temperature, tools)
+        let telemetry_id = format!("{}/{}", self.0.provider_id(), model);
+        let prompt_tokens = messages
+            .iter()
+            .map(|message| match message {
+                CompletionMessage::Assistant { content, .. } => {
+                    content.as_deref().map_or(0, estimate_tokens)
+                }
+                CompletionMessage::User { content }
+                | CompletionMessage::System { content }
+                | CompletionMessage::Tool { content, .. } => estimate_tokens(content),
+            })
+            .sum();
+        let response = self.0.complete(model, messages, stop, temperature, tools);
+        track_usage(
+            telemetry_id,
+            futures::future::ready(Ok(prompt_tokens)).boxed(),
+            response,
+            |delta: &proto::LanguageModelResponseMessage| delta.content.as_deref(),
+            cx,
+        )
     }
 }
 
 impl Global for CompletionProvider {}
 
 pub trait CompletionProviderBackend: 'static {
+    /// Identifies the provider in the assistant's usage settings and records, as in `zed.dev`.
+    fn provider_id(&self) -> &'static str;
     fn default_model(&self) -> String;
     fn available_models(&self) -> Vec<String>;
     fn complete(
@@ -66,6 +91,10 @@ impl CloudCompletionProvider {
 }
 
 impl CompletionProviderBackend for CloudCompletionProvider {
+    fn provider_id(&self) -> &'static str {
+        "zed.dev"
+    }
+
     fn default_model(&self) -> String {
         "gpt-4-turbo".into()
     }
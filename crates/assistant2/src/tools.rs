@@ -1,7 +1,57 @@
 mod annotate_code;
+mod context_server;
 mod create_buffer;
+mod diagnostics;
+mod edit_files;
 mod project_index;
+mod run_task;
+mod symbols;
 
 pub use annotate_code::*;
+pub use context_server::*;
 pub use create_buffer::*;
+pub use diagnostics::*;
+pub use edit_files::*;
 pub use project_index::*;
+pub use run_task::*;
+pub use symbols::*;
+
+use anyhow::{anyhow, Result};
+use gpui::AppContext;
+use language::BufferSnapshot;
+use project::{Project, ProjectPath};
+use std::{fmt::Write as _, ops::Range, path::Path};
+
+/// Resolves a path given by the model, which may or may not start with the
+/// name of a worktree, to an existing file in the project.
+fn project_path_for(project: &Project, path: &str, cx: &AppContext) -> Result<ProjectPath> {
+    let path = Path::new(path);
+    for worktree in project.visible_worktrees(cx) {
+        let worktree = worktree.read(cx);
+        let relative_path = path.strip_prefix(worktree.root_name()).unwrap_or(path);
+        if worktree.entry_for_path(relative_path).is_some() {
+            return Ok(ProjectPath {
+                worktree_id: worktree.id(),
+                path: relative_path.into(),
+            });
+        }
+    }
+    Err(anyhow!("no file found at {}", path.display()))
+}
+
+/// Renders the given rows of a buffer with line numbers, plus `context_lines`
+/// rows on either side.
+fn numbered_excerpt(snapshot: &BufferSnapshot, rows: Range<u32>, context_lines: u32) -> String {
+    let start_row = rows.start.saturating_sub(context_lines);
+    let end_row = (rows.end + context_lines).min(snapshot.max_point().row);
+    let mut excerpt = String::new();
+    for row in start_row..=end_row {
+        let line = snapshot
+            .text_for_range(
+                language::Point::new(row, 0)..language::Point::new(row, snapshot.line_len(row)),
+            )
+            .collect::<String>();
+        writeln!(&mut excerpt, "{:>5} | {}", row + 1, line).unwrap();
+    }
+    excerpt
+}
@@ -0,0 +1,167 @@
+use anyhow::{anyhow, Context as _, Result};
+use assistant_tooling::{
+    LanguageModelTool, ProjectContext, ToolFunctionDefinition, ToolRegistry, ToolView,
+};
+use context_servers::{types, ContextServer, ContextServerManager};
+use gpui::{Task, View};
+use schemars::schema_for;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use ui::prelude::*;
+use util::ResultExt as _;
+
+/// A tool provided by a context server.
+pub struct ContextServerTool {
+    server_id: Arc<str>,
+    tool: types::Tool,
+}
+
+impl ContextServerTool {
+    pub fn new(server_id: Arc<str>, tool: types::Tool) -> Self {
+        Self { server_id, tool }
+    }
+}
+
+/// Registers the tools provided by a running context server, returning the names
+/// of the tools that were registered.
+pub fn register_context_server_tools(
+    tool_registry: &ToolRegistry,
+    server: &ContextServer,
+) -> Vec<String> {
+    server
+        .tools
+        .iter()
+        .filter_map(|tool| {
+            tool_registry
+                .register(ContextServerTool::new(server.id.clone(), tool.clone()))
+                .with_context(|| {
+                    format!("failed to register tool from context server {}", server.id)
+                })
+                .log_err()?;
+            Some(tool.name.clone())
+        })
+        .collect()
+}
+
+impl LanguageModelTool for ContextServerTool {
+    type View = ContextServerToolView;
+
+    fn name(&self) -> String {
+        self.tool.name.clone()
+    }
+
+    fn description(&self) -> String {
+        self.tool.description.clone().unwrap_or_default()
+    }
+
+    fn definition(&self) -> ToolFunctionDefinition {
+        let parameters = serde_json::from_value(self.tool.input_schema.clone())
+            .with_context(|| format!("invalid input schema for tool {}", self.tool.name))
+            .log_err()
+            .unwrap_or_else(|| schema_for!(serde_json::Value));
+        ToolFunctionDefinition {
+            name: self.name(),
+            description: self.description(),
+            parameters,
+        }
+    }
+
+    fn view(&self, cx: &mut WindowContext) -> View<Self::View> {
+        cx.new_view(|_cx| ContextServerToolView {
+            server_id: self.server_id.clone(),
+            tool_name: self.tool.name.clone(),
+            input: serde_json::Value::Null,
+            output: None,
+        })
+    }
+}
+
+pub struct ContextServerToolView {
+    server_id: Arc<str>,
+    tool_name: String,
+    input: serde_json::Value,
+    output: Option<Result<String>>,
+}
+
+impl Render for ContextServerToolView {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let status = match &self.output {
+            None => Label::new("Running…").color(Color::Muted),
+            Some(Ok(_)) => Label::new("Done").color(Color::Muted),
+            Some(Err(error)) => Label::new(format!("Failed: {error}")).color(Color::Error),
+        };
+        h_flex()
+            .gap_2()
+            .child(Icon::new(IconName::Server))
+            .child(Label::new(self.tool_name.clone()))
+            .child(Label::new(self.server_id.to_string()).color(Color::Muted))
+            .child(status)
+    }
+}
+
+impl ToolView for ContextServerToolView {
+    type Input = serde_json::Value;
+    type SerializedState = SerializedContextServerToolOutput;
+
+    fn generate(&self, _: &mut ProjectContext, _: &mut ViewContext<Self>) -> String {
+        match &self.output {
+            None => "The tool did not run".to_string(),
+            Some(Ok(output)) => output.clone(),
+            Some(Err(error)) => format!("Failed to run tool: {error:?}"),
+        }
+    }
+
+    fn set_input(&mut self, input: Self::Input, cx: &mut ViewContext<Self>) {
+        self.input = input;
+        cx.notify();
+    }
+
+    fn execute(&mut self, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        let protocol = ContextServerManager::try_global(cx)
+            .and_then(|manager| manager.read(cx).server(&self.server_id)?.protocol())
+            .ok_or_else(|| anyhow!("context server {} is not running", self.server_id));
+        let tool_name = self.tool_name.clone();
+        let input = self.input.clone();
+        cx.spawn(|this, mut cx| async move {
+            let output = async { protocol?.call_tool(&tool_name, input).await }.await;
+            this.update(&mut cx, |this, cx| {
+                this.output = Some(output);
+                cx.notify();
+            })
+        })
+    }
+
+    fn serialize(&self, _cx: &mut ViewContext<Self>) -> Self::SerializedState {
+        SerializedContextServerToolOutput {
+            output: self
+                .output
+                .as_ref()
+                .and_then(|output| output.as_ref().ok().cloned()),
+            error_message: self
+                .output
+                .as_ref()
+                .and_then(|output| output.as_ref().err())
+                .map(|error| error.to_string()),
+        }
+    }
+
+    fn deserialize(
+        &mut self,
+        output: Self::SerializedState,
+        cx: &mut ViewContext<Self>,
+    ) -> Result<()> {
+        self.output = match (output.output, output.error_message) {
+            (_, Some(error_message)) => Some(Err(anyhow!("{}", error_message))),
+            (Some(output), None) => Some(Ok(output)),
+            (None, None) => None,
+        };
+        cx.notify();
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SerializedContextServerToolOutput {
+    output: Option<String>,
+    error_message: Option<String>,
+}
@@ -0,0 +1,281 @@
+use anyhow::Result;
+use assistant_tooling::{LanguageModelTool, ProjectContext, ToolView};
+use collections::HashSet;
+use gpui::{prelude::*, Model, Task, View};
+use language::{DiagnosticSeverity, OffsetRangeExt};
+use project::{Project, ProjectPath};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use ui::prelude::*;
+
+use super::{numbered_excerpt, project_path_for};
+
+const CONTEXT_LINE_COUNT: u32 = 3;
+const MAX_DIAGNOSTIC_COUNT: usize = 50;
+
+pub struct DiagnosticsTool {
+    project: Model<Project>,
+}
+
+impl DiagnosticsTool {
+    pub fn new(project: Model<Project>) -> Self {
+        Self { project }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+pub struct DiagnosticsInput {
+    /// Only report diagnostics for this file, relative to the root of the project.
+    /// Omit it to report diagnostics for the whole project.
+    #[serde(default)]
+    path: Option<String>,
+    /// Whether to report warnings as well as errors.
+    #[serde(default)]
+    include_warnings: bool,
+}
+
+impl LanguageModelTool for DiagnosticsTool {
+    type View = DiagnosticsView;
+
+    fn name(&self) -> String {
+        "get_diagnostics".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Get the errors, and optionally warnings, that language servers currently report for the project, along with the code around each one.".to_string()
+    }
+
+    fn view(&self, cx: &mut WindowContext) -> View<Self::View> {
+        cx.new_view(|_cx| DiagnosticsView {
+            project: self.project.clone(),
+            input: DiagnosticsInput::default(),
+            output: None,
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReportedDiagnostic {
+    path: String,
+    line: u32,
+    is_error: bool,
+    message: String,
+    excerpt: String,
+}
+
+pub struct DiagnosticsView {
+    project: Model<Project>,
+    input: DiagnosticsInput,
+    output: Option<Result<Vec<ReportedDiagnostic>>>,
+}
+
+impl DiagnosticsView {
+    fn collect_diagnostics(
+        &self,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<Vec<ReportedDiagnostic>>> {
+        let include_warnings = self.input.include_warnings;
+        let project = self.project.clone();
+        let paths = self.project.update(cx, |project, cx| {
+            let filter = self
+                .input
+                .path
+                .as_deref()
+                .map(|path| project_path_for(project, path, cx))
+                .transpose()?;
+            // Paths have a summary for each language server, so they can appear several times.
+            let mut seen_paths = HashSet::default();
+            let paths = project
+                .diagnostic_summaries(false, cx)
+                .filter(|(path, _, summary)| {
+                    filter.as_ref().map_or(true, |filter| filter == path)
+                        && (summary.error_count > 0
+                            || (include_warnings && summary.warning_count > 0))
+                })
+                .map(|(path, _, _)| path)
+                .filter(|path| seen_paths.insert(path.clone()))
+                .collect::<Vec<ProjectPath>>();
+            anyhow::Ok(paths)
+        });
+
+        cx.spawn(|_, mut cx| async move {
+            let mut diagnostics = Vec::new();
+            for path in paths? {
+                if diagnostics.len() >= MAX_DIAGNOSTIC_COUNT {
+                    break;
+                }
+
+                let buffer = project
+                    .update(&mut cx, |project, cx| project.open_buffer(path.clone(), cx))?
+                    .await?;
+                let snapshot = buffer.update(&mut cx, |buffer, _| buffer.snapshot())?;
+                for (_, group) in snapshot.diagnostic_groups(None) {
+                    let entry = &group.entries[group.primary_ix];
+                    let is_error = entry.diagnostic.severity == DiagnosticSeverity::ERROR;
+                    if !is_error
+                        && !(include_warnings
+                            && entry.diagnostic.severity == DiagnosticSeverity::WARNING)
+                    {
+                        continue;
+                    }
+
+                    let range = entry.range.to_point(&snapshot);
+                    diagnostics.push(ReportedDiagnostic {
+                        path: path.path.to_string_lossy().into_owned(),
+                        line: range.start.row + 1,
+                        is_error,
+                        message: entry.diagnostic.message.clone(),
+                        excerpt: numbered_excerpt(
+                            &snapshot,
+                            range.start.row..range.end.row,
+                            CONTEXT_LINE_COUNT,
+                        ),
+                    });
+                }
+            }
+            diagnostics.truncate(MAX_DIAGNOSTIC_COUNT);
+            Ok(diagnostics)
+        })
+    }
+}
+
+impl Render for DiagnosticsView {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        match &self.output {
+            None => Label::new("Collecting diagnostics…").into_any_element(),
+            Some(Err(error)) => Label::new(format!("Failed to collect diagnostics: {error}"))
+                .color(Color::Error)
+                .into_any_element(),
+            Some(Ok(diagnostics)) => {
+                let error_count = diagnostics.iter().filter(|d| d.is_error).count();
+                v_flex()
+                    .child(Label::new(format!(
+                        "Found {} errors and {} warnings",
+                        error_count,
+                        diagnostics.len() - error_count
+                    )))
+                    .children(diagnostics.iter().map(|diagnostic| {
+                        h_flex()
+                            .gap_1()
+                            .child(
+                                Icon::new(if diagnostic.is_error {
+                                    IconName::XCircle
+                                } else {
+                                    IconName::ExclamationTriangle
+                                })
+                                .size(IconSize::Small)
+                                .color(if diagnostic.is_error {
+                                    Color::Error
+                                } else {
+                                    Color::Warning
+                                }),
+                            )
+                            .child(
+                                Label::new(format!("{}:{}", diagnostic.path, diagnostic.line))
+                                    .color(Color::Muted),
+                            )
+                            .child(Label::new(
+                                diagnostic
+                                    .message
+                                    .lines()
+                                    .next()
+                                    .unwrap_or_default()
+                                    .to_string(),
+                            ))
+                    }))
+                    .into_any_element()
+            }
+        }
+    }
+}
+
+impl ToolView for DiagnosticsView {
+    type Input = DiagnosticsInput;
+    type SerializedState = SerializedDiagnostics;
+
+    fn generate(&self, _: &mut ProjectContext, _: &mut ViewContext<Self>) -> String {
+        match &self.output {
+            None => "No diagnostics collected".to_string(),
+            Some(Err(error)) => format!("Failed to collect diagnostics: {error:?}"),
+            Some(Ok(diagnostics)) if diagnostics.is_empty() => {
+                "There are no diagnostics in the project".to_string()
+            }
+            Some(Ok(diagnostics)) => {
+                let mut output = String::new();
+                for diagnostic in diagnostics {
+                    writeln!(
+                        &mut output,
+                        "{}:{}: {}: {}\n```\n{}```",
+                        diagnostic.path,
+                        diagnostic.line,
+                        if diagnostic.is_error {
+                            "error"
+                        } else {
+                            "warning"
+                        },
+                        diagnostic.message,
+                        diagnostic.excerpt
+                    )
+                    .unwrap();
+                }
+                if diagnostics.len() == MAX_DIAGNOSTIC_COUNT {
+                    output.push_str("Only the first diagnostics were included.\n");
+                }
+                output
+            }
+        }
+    }
+
+    fn set_input(&mut self, input: Self::Input, cx: &mut ViewContext<Self>) {
+        self.input = input;
+        cx.notify();
+    }
+
+    fn execute(&mut self, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        let diagnostics = self.collect_diagnostics(cx);
+        cx.spawn(|this, mut cx| async move {
+            let diagnostics = diagnostics.await;
+            this.update(&mut cx, |this, cx| {
+                this.output = Some(diagnostics);
+                cx.notify();
+            })
+        })
+    }
+
+    fn serialize(&self, _cx: &mut ViewContext<Self>) -> Self::SerializedState {
+        match &self.output {
+            Some(Ok(diagnostics)) => SerializedDiagnostics {
+                diagnostics: diagnostics.clone(),
+                error_message: None,
+            },
+            Some(Err(error)) => SerializedDiagnostics {
+                diagnostics: Vec::new(),
+                error_message: Some(error.to_string()),
+            },
+            None => SerializedDiagnostics {
+                diagnostics: Vec::new(),
+                error_message: None,
+            },
+        }
+    }
+
+    fn deserialize(
+        &mut self,
+        output: Self::SerializedState,
+        cx: &mut ViewContext<Self>,
+    ) -> Result<()> {
+        self.output = Some(match output.error_message {
+            Some(error_message) => Err(anyhow::anyhow!("{}", error_message)),
+            None => Ok(output.diagnostics),
+        });
+        cx.notify();
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SerializedDiagnostics {
+    diagnostics: Vec<ReportedDiagnostic>,
+    error_message: Option<String>,
+}
@@ -0,0 +1,859 @@
+use anyhow::{anyhow, bail, Result};
+use assistant_tooling::{LanguageModelTool, ProjectContext, ToolView};
+use editor::{
+    actions::ExpandAllHunkDiffs,
+    display_map::{BlockContext, BlockDisposition, BlockProperties, BlockStyle},
+    Editor, MultiBuffer,
+};
+use gpui::{prelude::*, AnyElement, AppContext, AsyncWindowContext, Model, Task, View, WeakView};
+use language::{Anchor, Buffer, BufferSnapshot, OffsetRangeExt, Point};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write as _, ops::Range};
+use ui::{prelude::*, Tooltip};
+use util::ResultExt;
+use workspace::Workspace;
+
+use super::project_path_for;
+
+const CONTEXT_LINE_COUNT: u32 = 3;
+
+pub struct EditFilesTool {
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+}
+
+impl EditFilesTool {
+    pub fn new(workspace: WeakView<Workspace>, project: Model<Project>) -> Self {
+        Self { workspace, project }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+pub struct EditFilesInput {
+    /// A short summary of the changes, shown to the user while they review them.
+    #[serde(default = "default_title")]
+    title: String,
+    /// The files to change, each with the edits to make to it.
+    files: Vec<FileEdits>,
+}
+
+fn default_title() -> String {
+    "Proposed Edits".to_string()
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+struct FileEdits {
+    /// Path to an existing file, relative to the root of the project.
+    path: String,
+    /// The edits to make to the file. Edits must not overlap.
+    edits: Vec<FileEdit>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum FileEdit {
+    /// Replace some text that appears exactly once in the file.
+    Replace {
+        /// The exact text to replace, including whitespace. Include enough surrounding
+        /// lines for it to be unique within the file.
+        old_text: String,
+        /// The text to replace it with.
+        new_text: String,
+    },
+    /// Replace a range of lines in the file.
+    Lines {
+        /// The first line to replace, starting from 1.
+        start_line: u32,
+        /// The last line to replace, inclusive.
+        end_line: u32,
+        /// The text to replace the lines with, without a trailing newline.
+        new_text: String,
+    },
+}
+
+impl FileEdit {
+    fn resolve(&self, snapshot: &BufferSnapshot, text: &str) -> Result<(Range<usize>, String)> {
+        match self {
+            FileEdit::Replace { old_text, new_text } => {
+                if old_text.is_empty() {
+                    bail!("old_text must not be empty");
+                }
+                let mut matches = text.match_indices(old_text.as_str());
+                let Some((start, _)) = matches.next() else {
+                    bail!("could not find {old_text:?}");
+                };
+                let other_match_count = matches.count();
+                if other_match_count > 0 {
+                    bail!(
+                        "{old_text:?} appears {} times, include more surrounding lines so that it's unique",
+                        other_match_count + 1
+                    );
+                }
+                Ok((start..start + old_text.len(), new_text.clone()))
+            }
+            FileEdit::Lines {
+                start_line,
+                end_line,
+                new_text,
+            } => {
+                let row_count = snapshot.max_point().row + 1;
+                if *start_line == 0 || start_line > end_line || *end_line > row_count {
+                    bail!("invalid line range {start_line}-{end_line}, the file has {row_count} lines");
+                }
+                let start = Point::new(start_line - 1, 0);
+                let end = Point::new(end_line - 1, snapshot.line_len(end_line - 1));
+                Ok((
+                    snapshot.point_to_offset(start)..snapshot.point_to_offset(end),
+                    new_text.clone(),
+                ))
+            }
+        }
+    }
+}
+
+impl LanguageModelTool for EditFilesTool {
+    type View = EditFilesView;
+
+    fn name(&self) -> String {
+        "edit_files".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Propose edits to existing files in the project. The edits are shown to the user as a diff, and only the hunks they accept are applied. Prefer `replace` edits with enough context to be unique.".to_string()
+    }
+
+    fn view(&self, cx: &mut WindowContext) -> View<Self::View> {
+        cx.new_view(|_cx| EditFilesView {
+            workspace: self.workspace.clone(),
+            project: self.project.clone(),
+            input: EditFilesInput::default(),
+            files: Vec::new(),
+            saved_files: Vec::new(),
+            editor: None,
+            error: None,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HunkStatus {
+    #[default]
+    Pending,
+    Accepted,
+    Rejected,
+}
+
+struct ProposedFile {
+    path: String,
+    /// The project's buffer for the file, where accepted hunks are applied.
+    buffer: Model<Buffer>,
+    /// A copy of the file with every hunk applied, diffed against the original
+    /// text so the editor can render the hunks.
+    proposal: Model<Buffer>,
+    hunks: Vec<ProposedHunk>,
+}
+
+struct ProposedHunk {
+    range: Range<Anchor>,
+    proposal_range: Range<Anchor>,
+    old_text: String,
+    new_text: String,
+    status: HunkStatus,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct FileReview {
+    path: String,
+    hunks: Vec<HunkStatus>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SerializedState {
+    error_message: Option<String>,
+    files: Vec<FileReview>,
+}
+
+pub struct EditFilesView {
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    input: EditFilesInput,
+    files: Vec<ProposedFile>,
+    /// Decisions restored from a saved conversation, whose edits can no longer be reviewed.
+    saved_files: Vec<FileReview>,
+    editor: Option<View<Editor>>,
+    error: Option<anyhow::Error>,
+}
+
+impl EditFilesView {
+    async fn propose_edits(
+        this: WeakView<Self>,
+        input: EditFilesInput,
+        cx: &mut AsyncWindowContext,
+    ) -> Result<()> {
+        let project = this.update(cx, |this, _| this.project.clone())?;
+
+        let mut files = Vec::new();
+        for file_edits in input.files {
+            let project_path = project.update(cx, |project, cx| {
+                project_path_for(project, &file_edits.path, cx)
+            })??;
+            let buffer = project
+                .update(cx, |project, cx| project.open_buffer(project_path, cx))?
+                .await?;
+            let (file, diff) = cx.update(|cx| propose_file_edits(file_edits, buffer, cx))??;
+            if let Some(diff) = diff {
+                diff.await;
+            }
+            files.push(file);
+        }
+
+        this.update(cx, |this, cx| {
+            this.files = files;
+            this.open_review_editor(cx);
+            cx.notify();
+        })
+    }
+
+    fn open_review_editor(&mut self, cx: &mut ViewContext<Self>) {
+        let title = self.input.title.clone();
+        let multibuffer = cx
+            .new_model(|_| MultiBuffer::new(0, language::Capability::ReadWrite).with_title(title));
+        let hunk_positions = multibuffer.update(cx, |multibuffer, cx| {
+            self.files
+                .iter()
+                .map(|file| {
+                    let snapshot = file.proposal.read(cx).snapshot();
+                    let ranges = file
+                        .hunks
+                        .iter()
+                        .map(|hunk| hunk.proposal_range.to_point(&snapshot))
+                        .collect();
+                    multibuffer.push_excerpts_with_context_lines(
+                        file.proposal.clone(),
+                        ranges,
+                        CONTEXT_LINE_COUNT,
+                        cx,
+                    )
+                })
+                .collect::<Vec<_>>()
+        });
+
+        let view = cx.view().downgrade();
+        let blocks = hunk_positions
+            .into_iter()
+            .enumerate()
+            .flat_map(|(file_ix, ranges)| {
+                let view = view.clone();
+                ranges.into_iter().enumerate().map(move |(hunk_ix, range)| {
+                    let view = view.clone();
+                    BlockProperties {
+                        position: range.start,
+                        height: 1,
+                        style: BlockStyle::Sticky,
+                        render: Box::new(move |cx| {
+                            render_hunk_controls(&view, file_ix, hunk_ix, cx)
+                        }),
+                        disposition: BlockDisposition::Above,
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let editor = cx.new_view(|cx| {
+            let mut editor = Editor::for_multibuffer(multibuffer, Some(self.project.clone()), cx);
+            editor.set_read_only(true);
+            editor.insert_blocks(blocks, None, cx);
+            editor.expand_all_hunk_diffs(&ExpandAllHunkDiffs, cx);
+            editor
+        });
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace.add_item_to_active_pane(Box::new(editor.clone()), None, cx);
+            })
+            .log_err();
+        self.editor = Some(editor);
+    }
+
+    fn review(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(editor) = self.editor.clone() else {
+            return;
+        };
+        self.workspace
+            .update(cx, |workspace, cx| {
+                if !workspace.activate_item(&editor, cx) {
+                    workspace.add_item_to_active_pane(Box::new(editor), None, cx);
+                }
+            })
+            .log_err();
+    }
+
+    fn hunk_status(&self, file_ix: usize, hunk_ix: usize) -> Option<HunkStatus> {
+        Some(self.files.get(file_ix)?.hunks.get(hunk_ix)?.status)
+    }
+
+    fn accept_hunk(&mut self, file_ix: usize, hunk_ix: usize, cx: &mut ViewContext<Self>) {
+        let was_dirty = self.is_file_dirty(file_ix, cx);
+        if self.apply_hunk(file_ix, hunk_ix, cx) {
+            if !was_dirty {
+                self.save_file(file_ix, cx);
+            }
+            self.hunks_changed(cx);
+        }
+    }
+
+    fn is_file_dirty(&self, file_ix: usize, cx: &AppContext) -> bool {
+        self.files
+            .get(file_ix)
+            .map_or(false, |file| file.buffer.read(cx).is_dirty())
+    }
+
+    /// Applies the hunk to the project's buffer, returning whether it was pending.
+    fn apply_hunk(&mut self, file_ix: usize, hunk_ix: usize, cx: &mut ViewContext<Self>) -> bool {
+        let Some(file) = self.files.get_mut(file_ix) else {
+            return false;
+        };
+        let Some(hunk) = file.hunks.get_mut(hunk_ix) else {
+            return false;
+        };
+        if hunk.status != HunkStatus::Pending {
+            return false;
+        }
+
+        hunk.status = HunkStatus::Accepted;
+        let edit = (hunk.range.clone(), hunk.new_text.clone());
+        file.buffer
+            .update(cx, |buffer, cx| buffer.edit([edit], None, cx));
+        true
+    }
+
+    /// Saves the file once hunks have been accepted, so the edits reach the disk.
+    /// Not called for files that already had unsaved changes, as that would save
+    /// the user's changes along with the accepted hunks.
+    fn save_file(&mut self, file_ix: usize, cx: &mut ViewContext<Self>) {
+        let Some(file) = self.files.get(file_ix) else {
+            return;
+        };
+        let buffer = file.buffer.clone();
+        self.project
+            .update(cx, |project, cx| project.save_buffer(buffer, cx))
+            .detach_and_log_err(cx);
+    }
+
+    fn reject_hunk(&mut self, file_ix: usize, hunk_ix: usize, cx: &mut ViewContext<Self>) {
+        let Some(file) = self.files.get_mut(file_ix) else {
+            return;
+        };
+        let Some(hunk) = file.hunks.get_mut(hunk_ix) else {
+            return;
+        };
+        if hunk.status != HunkStatus::Pending {
+            return;
+        }
+
+        // Restore the original text in the proposal, so the hunk disappears from the diff.
+        hunk.status = HunkStatus::Rejected;
+        let edit = (hunk.proposal_range.clone(), hunk.old_text.clone());
+        file.proposal.update(cx, |proposal, cx| {
+            proposal.edit([edit], None, cx);
+            if let Some(diff) = proposal.git_diff_recalc(cx) {
+                diff.detach();
+            }
+        });
+        self.hunks_changed(cx);
+    }
+
+    fn decide_all(&mut self, status: HunkStatus, cx: &mut ViewContext<Self>) {
+        for file_ix in 0..self.files.len() {
+            let was_dirty = self.is_file_dirty(file_ix, cx);
+            let mut applied = false;
+            for hunk_ix in 0..self.files[file_ix].hunks.len() {
+                match status {
+                    HunkStatus::Accepted => applied |= self.apply_hunk(file_ix, hunk_ix, cx),
+                    HunkStatus::Rejected => self.reject_hunk(file_ix, hunk_ix, cx),
+                    HunkStatus::Pending => {}
+                }
+            }
+            if applied && !was_dirty {
+                self.save_file(file_ix, cx);
+            }
+        }
+        self.hunks_changed(cx);
+    }
+
+    fn hunks_changed(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(editor) = &self.editor {
+            editor.update(cx, |_, cx| cx.notify());
+        }
+        cx.notify();
+    }
+
+    fn file_reviews(&self) -> Vec<FileReview> {
+        if self.files.is_empty() {
+            return self.saved_files.clone();
+        }
+
+        self.files
+            .iter()
+            .map(|file| FileReview {
+                path: file.path.clone(),
+                hunks: file.hunks.iter().map(|hunk| hunk.status).collect(),
+            })
+            .collect()
+    }
+}
+
+fn propose_file_edits(
+    file_edits: FileEdits,
+    buffer: Model<Buffer>,
+    cx: &mut WindowContext,
+) -> Result<(ProposedFile, Option<Task<()>>)> {
+    let snapshot = buffer.read(cx).snapshot();
+    let text = snapshot.text();
+
+    let mut edits = file_edits
+        .edits
+        .iter()
+        .map(|edit| edit.resolve(&snapshot, &text))
+        .collect::<Result<Vec<_>>>()
+        .map_err(|error| anyhow!("invalid edit to {}: {error}", file_edits.path))?;
+    edits.sort_by_key(|(range, _)| range.start);
+    if edits.windows(2).any(|pair| pair[0].0.end > pair[1].0.start) {
+        bail!("edits to {} overlap", file_edits.path);
+    }
+
+    let language = snapshot.language().cloned();
+    let proposal = cx.new_model(|cx| {
+        let mut proposal = Buffer::local(text.clone(), cx);
+        proposal.set_language(language, cx);
+        proposal.set_diff_base(Some(text.clone()), cx);
+        proposal
+    });
+    let proposal_snapshot = proposal.read(cx).snapshot();
+
+    let hunks = edits
+        .into_iter()
+        .map(|(range, new_text)| ProposedHunk {
+            range: snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end),
+            proposal_range: proposal_snapshot.anchor_before(range.start)
+                ..proposal_snapshot.anchor_after(range.end),
+            old_text: text[range].to_string(),
+            new_text,
+            status: HunkStatus::Pending,
+        })
+        .collect::<Vec<_>>();
+    let diff = proposal.update(cx, |proposal, cx| {
+        proposal.edit(
+            hunks
+                .iter()
+                .map(|hunk| (hunk.proposal_range.clone(), hunk.new_text.clone())),
+            None,
+            cx,
+        );
+        proposal.git_diff_recalc(cx)
+    });
+
+    Ok((
+        ProposedFile {
+            path: file_edits.path,
+            buffer,
+            proposal,
+            hunks,
+        },
+        diff,
+    ))
+}
+
+fn render_hunk_controls(
+    view: &WeakView<EditFilesView>,
+    file_ix: usize,
+    hunk_ix: usize,
+    cx: &mut BlockContext,
+) -> AnyElement {
+    let status = view
+        .upgrade()
+        .and_then(|view| view.read(cx).hunk_status(file_ix, hunk_ix))
+        .unwrap_or_default();
+    let gutter_width = cx.gutter_dimensions.width;
+
+    let controls = match status {
+        HunkStatus::Pending => h_flex()
+            .gap_1()
+            .child(
+                Button::new(("accept-hunk", cx.block_id), "Accept")
+                    .icon(IconName::Check)
+                    .icon_color(Color::Created)
+                    .on_click({
+                        let view = view.clone();
+                        move |_, cx| {
+                            view.update(cx, |this, cx| this.accept_hunk(file_ix, hunk_ix, cx))
+                                .ok();
+                        }
+                    }),
+            )
+            .child(
+                Button::new(("reject-hunk", cx.block_id), "Reject")
+                    .icon(IconName::Close)
+                    .icon_color(Color::Deleted)
+                    .on_click({
+                        let view = view.clone();
+                        move |_, cx| {
+                            view.update(cx, |this, cx| this.reject_hunk(file_ix, hunk_ix, cx))
+                                .ok();
+                        }
+                    }),
+            )
+            .into_any_element(),
+        HunkStatus::Accepted => Label::new("Accepted")
+            .color(Color::Created)
+            .into_any_element(),
+        HunkStatus::Rejected => Label::new("Rejected")
+            .color(Color::Deleted)
+            .into_any_element(),
+    };
+
+    h_flex()
+        .w_full()
+        .pl(gutter_width)
+        .child(controls)
+        .into_any_element()
+}
+
+fn describe_hunks(hunks: &[HunkStatus]) -> String {
+    let count = |status| hunks.iter().filter(|hunk| **hunk == status).count();
+    format!(
+        "{} accepted, {} rejected, {} pending",
+        count(HunkStatus::Accepted),
+        count(HunkStatus::Rejected),
+        count(HunkStatus::Pending)
+    )
+}
+
+impl Render for EditFilesView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        if let Some(error) = &self.error {
+            return Label::new(format!("Failed to propose edits: {error}"))
+                .color(Color::Error)
+                .into_any_element();
+        }
+
+        let reviews = self.file_reviews();
+        let has_pending_hunks = !self.files.is_empty()
+            && reviews
+                .iter()
+                .any(|review| review.hunks.contains(&HunkStatus::Pending));
+
+        v_flex()
+            .gap_2()
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Icon::new(IconName::FileDoc))
+                    .child(Label::new(self.input.title.clone()))
+                    .when(self.editor.is_some(), |this| {
+                        this.child(
+                            IconButton::new("review-edits", IconName::ArrowUpRight)
+                                .tooltip(|cx| Tooltip::text("Review Edits", cx))
+                                .on_click(cx.listener(|this, _, cx| this.review(cx))),
+                        )
+                    }),
+            )
+            .children(reviews.into_iter().map(|review| {
+                h_flex()
+                    .gap_2()
+                    .child(Label::new(review.path).color(Color::Muted))
+                    .child(
+                        Label::new(describe_hunks(&review.hunks))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+            }))
+            .when(has_pending_hunks, |this| {
+                this.child(
+                    h_flex()
+                        .gap_1()
+                        .child(Button::new("accept-all", "Accept All").on_click(
+                            cx.listener(|this, _, cx| this.decide_all(HunkStatus::Accepted, cx)),
+                        ))
+                        .child(Button::new("reject-all", "Reject All").on_click(
+                            cx.listener(|this, _, cx| this.decide_all(HunkStatus::Rejected, cx)),
+                        )),
+                )
+            })
+            .into_any_element()
+    }
+}
+
+impl ToolView for EditFilesView {
+    type Input = EditFilesInput;
+    type SerializedState = SerializedState;
+
+    fn generate(&self, _: &mut ProjectContext, _: &mut ViewContext<Self>) -> String {
+        if let Some(error) = &self.error {
+            return format!("Failed to propose edits: {error:?}");
+        }
+
+        let mut output =
+            "Proposed edits to the following files. The user reviews each hunk, and only accepted hunks are applied:\n"
+                .to_string();
+        for review in self.file_reviews() {
+            writeln!(
+                &mut output,
+                "* {}: {}",
+                review.path,
+                describe_hunks(&review.hunks)
+            )
+            .unwrap();
+        }
+        output
+    }
+
+    fn set_input(&mut self, input: Self::Input, cx: &mut ViewContext<Self>) {
+        self.input = input;
+        cx.notify();
+    }
+
+    fn execute(&mut self, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        let input = self.input.clone();
+        cx.spawn(|this, mut cx| async move {
+            let result = Self::propose_edits(this.clone(), input, &mut cx).await;
+            this.update(&mut cx, |this, cx| {
+                if let Err(error) = result {
+                    this.error = Some(error);
+                }
+                cx.notify();
+            })
+        })
+    }
+
+    fn serialize(&self, _cx: &mut ViewContext<Self>) -> Self::SerializedState {
+        SerializedState {
+            error_message: self.error.as_ref().map(|error| error.to_string()),
+            files: self.file_reviews(),
+        }
+    }
+
+    fn deserialize(
+        &mut self,
+        output: Self::SerializedState,
+        cx: &mut ViewContext<Self>,
+    ) -> Result<()> {
+        if let Some(error_message) = output.error_message {
+            self.error = Some(anyhow!("{}", error_message));
+        }
+        self.saved_files = output.files;
+        cx.notify();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::{FakeFs, Fs};
+    use gpui::TestAppContext;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::path::Path;
+
+    #[gpui::test]
+    fn test_resolve_edits(cx: &mut gpui::AppContext) {
+        let text = "one\ntwo\nthree\ntwo\n";
+        let buffer = cx.new_model(|cx| Buffer::local(text, cx));
+        let snapshot = buffer.read(cx).snapshot();
+        let resolve = |edit: FileEdit| {
+            edit.resolve(&snapshot, text)
+                .map_err(|error| error.to_string())
+        };
+
+        assert_eq!(
+            resolve(FileEdit::Replace {
+                old_text: "three".into(),
+                new_text: "3".into()
+            }),
+            Ok((8..13, "3".into()))
+        );
+        assert_eq!(
+            resolve(FileEdit::Replace {
+                old_text: "four".into(),
+                new_text: "4".into()
+            }),
+            Err("could not find \"four\"".into())
+        );
+        assert_eq!(
+            resolve(FileEdit::Replace {
+                old_text: "two".into(),
+                new_text: "2".into()
+            }),
+            Err(
+                "\"two\" appears 2 times, include more surrounding lines so that it's unique"
+                    .into()
+            )
+        );
+        assert_eq!(
+            resolve(FileEdit::Replace {
+                old_text: "two\nthree".into(),
+                new_text: "2\n3".into()
+            }),
+            Ok((4..13, "2\n3".into()))
+        );
+
+        assert_eq!(
+            resolve(FileEdit::Lines {
+                start_line: 2,
+                end_line: 3,
+                new_text: "2\n3".into()
+            }),
+            Ok((4..13, "2\n3".into()))
+        );
+        assert!(resolve(FileEdit::Lines {
+            start_line: 0,
+            end_line: 1,
+            new_text: String::new()
+        })
+        .is_err());
+        assert!(resolve(FileEdit::Lines {
+            start_line: 3,
+            end_line: 2,
+            new_text: String::new()
+        })
+        .is_err());
+        assert!(resolve(FileEdit::Lines {
+            start_line: 1,
+            end_line: 6,
+            new_text: String::new()
+        })
+        .is_err());
+    }
+
+    #[gpui::test]
+    async fn test_review_edits(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "a.txt": "one\ntwo\nthree\nfour\nfive\nsix\nseven\neight\nnine\nten\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+
+        let tool = EditFilesTool::new(workspace.downgrade(), project.clone());
+        let view = cx.update(|cx| tool.view(cx));
+        let input: EditFilesInput = serde_json::from_value(json!({
+            "files": [{
+                "path": "a.txt",
+                "edits": [
+                    { "kind": "replace", "old_text": "two", "new_text": "2" },
+                    { "kind": "lines", "start_line": 9, "end_line": 10, "new_text": "9\n10" },
+                ]
+            }]
+        }))
+        .unwrap();
+        view.update(cx, |view, cx| view.set_input(input, cx));
+        view.update(cx, |view, cx| view.execute(cx)).await.unwrap();
+        view.update(cx, |view, cx| {
+            assert!(view.error.is_none());
+            assert_eq!(view.files[0].hunks.len(), 2);
+            assert!(view.editor.is_some());
+        });
+
+        // Accepted hunks are applied and saved, and rejected ones are dropped.
+        view.update(cx, |view, cx| {
+            view.accept_hunk(0, 0, cx);
+            view.reject_hunk(0, 1, cx);
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            fs.load(Path::new("/root/a.txt")).await.unwrap(),
+            "one\n2\nthree\nfour\nfive\nsix\nseven\neight\nnine\nten\n"
+        );
+        view.update(cx, |view, cx| {
+            assert_eq!(
+                view.hunk_status(0, 0).zip(view.hunk_status(0, 1)),
+                Some((HunkStatus::Accepted, HunkStatus::Rejected))
+            );
+            assert!(!view.files[0].buffer.read(cx).is_dirty());
+            assert!(view
+                .generate(
+                    &mut ProjectContext::new(project.downgrade(), fs.clone()),
+                    cx
+                )
+                .contains("* a.txt: 1 accepted, 1 rejected, 0 pending"));
+        });
+
+        // Accepting a hunk in a file with unsaved changes leaves it unsaved.
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/root/a.txt", cx)
+            })
+            .await
+            .unwrap();
+        buffer.update(cx, |buffer, cx| {
+            let len = buffer.len();
+            buffer.edit([(len..len, "eleven\n")], None, cx)
+        });
+        let view = cx.update(|cx| tool.view(cx));
+        let input: EditFilesInput = serde_json::from_value(json!({
+            "files": [{
+                "path": "a.txt",
+                "edits": [{ "kind": "replace", "old_text": "four", "new_text": "4" }]
+            }]
+        }))
+        .unwrap();
+        view.update(cx, |view, cx| view.set_input(input, cx));
+        view.update(cx, |view, cx| view.execute(cx)).await.unwrap();
+        view.update(cx, |view, cx| view.accept_hunk(0, 0, cx));
+        cx.run_until_parked();
+        assert_eq!(
+            fs.load(Path::new("/root/a.txt")).await.unwrap(),
+            "one\n2\nthree\nfour\nfive\nsix\nseven\neight\nnine\nten\n"
+        );
+        buffer.update(cx, |buffer, _| {
+            assert!(buffer.is_dirty());
+            assert_eq!(
+                buffer.text(),
+                "one\n2\nthree\n4\nfive\nsix\nseven\neight\nnine\nten\neleven\n"
+            );
+        });
+
+        // Ambiguous edits are reported to the model.
+        let view = cx.update(|cx| tool.view(cx));
+        let input: EditFilesInput = serde_json::from_value(json!({
+            "files": [{
+                "path": "a.txt",
+                "edits": [{ "kind": "replace", "old_text": "e", "new_text": "E" }]
+            }]
+        }))
+        .unwrap();
+        view.update(cx, |view, cx| view.set_input(input, cx));
+        view.update(cx, |view, cx| view.execute(cx)).await.unwrap();
+        view.update(cx, |view, cx| {
+            let output = view.generate(
+                &mut ProjectContext::new(project.downgrade(), fs.clone()),
+                cx,
+            );
+            assert!(output.contains("invalid edit to a.txt"), "{output}");
+            assert!(
+                output.contains("include more surrounding lines"),
+                "{output}"
+            );
+        });
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+        });
+    }
+}
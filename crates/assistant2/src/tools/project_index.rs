@@ -279,7 +279,8 @@ impl ToolView for ProjectIndexView {
 
         // TODO: wire the filters into the search here instead of processing after.
         // Otherwise we'll get zero results sometimes.
-        let search = project_index.search(self.input.query.clone(), DEFAULT_SEARCH_LIMIT, cx);
+        let search =
+            project_index.search_hybrid(self.input.query.clone(), DEFAULT_SEARCH_LIMIT, cx);
 
         let includes = self.input.includes.clone();
         let excludes = self.input.excludes.clone();
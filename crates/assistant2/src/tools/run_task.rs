@@ -0,0 +1,457 @@
+use anyhow::{anyhow, Context as _, Result};
+use assistant_tooling::{LanguageModelTool, ProjectContext, ToolView};
+use futures::{
+    channel::{mpsc, oneshot},
+    StreamExt as _,
+};
+use gpui::{prelude::*, AsyncWindowContext, Model, Task, View, WeakView};
+use project::{Project, TaskSourceKind};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use task::{ResolvedTask, TaskContext};
+use terminal::{TaskStatus, Terminal};
+use ui::prelude::*;
+use workspace::{
+    tasks::{schedule_resolved_task, task_cwd},
+    Workspace,
+};
+
+/// Only the end of a task's output is sent to the model, as that's where errors usually are.
+const MAX_OUTPUT_LINE_COUNT: usize = 200;
+
+pub struct RunTaskTool {
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+}
+
+impl RunTaskTool {
+    pub fn new(workspace: WeakView<Workspace>, project: Model<Project>) -> Self {
+        Self { workspace, project }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+pub struct RunTaskInput {
+    /// The label of the task to run. If no task has this label, the labels of
+    /// the available tasks are returned instead.
+    label: String,
+}
+
+impl LanguageModelTool for RunTaskTool {
+    type View = RunTaskView;
+
+    fn name(&self) -> String {
+        "run_task".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Run one of the project's tasks, such as a build or test command, in a terminal. The user must approve the task before it runs. Returns the task's exit status and the end of its output.".to_string()
+    }
+
+    fn view(&self, cx: &mut WindowContext) -> View<Self::View> {
+        cx.new_view(|_cx| RunTaskView {
+            workspace: self.workspace.clone(),
+            project: self.project.clone(),
+            input: RunTaskInput::default(),
+            command_label: None,
+            approval_tx: None,
+            outcome: None,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskOutcomeStatus {
+    Declined,
+    Succeeded,
+    Failed,
+    /// The terminal closed before the task reported its exit status.
+    Unknown,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TaskOutcome {
+    status: TaskOutcomeStatus,
+    output: String,
+}
+
+pub struct RunTaskView {
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    input: RunTaskInput,
+    command_label: Option<String>,
+    approval_tx: Option<oneshot::Sender<bool>>,
+    outcome: Option<Result<TaskOutcome>>,
+}
+
+impl RunTaskView {
+    fn resolve_task(&self, cx: &mut ViewContext<Self>) -> Result<(TaskSourceKind, ResolvedTask)> {
+        let tasks = self
+            .project
+            .read(cx)
+            .task_inventory()
+            .read(cx)
+            .list_tasks(None, None);
+        let Some((kind, template)) = tasks
+            .iter()
+            .find(|(_, template)| template.label == self.input.label)
+        else {
+            let labels = tasks
+                .iter()
+                .map(|(_, template)| format!("`{}`", template.label))
+                .collect::<Vec<_>>();
+            return Err(anyhow!(
+                "no task is labeled `{}`, the available tasks are: {}",
+                self.input.label,
+                labels.join(", ")
+            ));
+        };
+
+        let cwd = self
+            .workspace
+            .update(cx, |workspace, cx| task_cwd(workspace, cx))??;
+        let task_context = TaskContext {
+            cwd,
+            task_variables: Default::default(),
+        };
+        let resolved_task = template
+            .resolve_task(&kind.to_id_base(), &task_context)
+            .with_context(|| format!("could not resolve task `{}`", template.label))?;
+        Ok((kind.clone(), resolved_task))
+    }
+
+    fn respond(&mut self, approved: bool, cx: &mut ViewContext<Self>) {
+        if let Some(approval_tx) = self.approval_tx.take() {
+            approval_tx.send(approved).ok();
+        }
+        cx.notify();
+    }
+
+    async fn run_task(
+        this: WeakView<Self>,
+        kind: TaskSourceKind,
+        resolved_task: ResolvedTask,
+        cx: &mut AsyncWindowContext,
+    ) -> Result<TaskOutcome> {
+        let (workspace, project) =
+            this.update(cx, |this, _| (this.workspace.clone(), this.project.clone()))?;
+
+        // The terminal panel spawns the task's terminal asynchronously, possibly only after
+        // a previous run of the task finishes, so wait for the project to create it.
+        let (terminal_tx, mut terminal_rx) = mpsc::unbounded();
+        let _subscription = cx.update(|cx| {
+            cx.subscribe(&project, move |_, event: &project::Event, _| {
+                if let project::Event::TerminalCreated(terminal) = event {
+                    terminal_tx.unbounded_send(terminal.clone()).ok();
+                }
+            })
+        })?;
+        let task_id = resolved_task.id.clone();
+        workspace.update(cx, |workspace, cx| {
+            schedule_resolved_task(workspace, kind, resolved_task, false, cx)
+        })?;
+
+        let terminal = loop {
+            let terminal = terminal_rx
+                .next()
+                .await
+                .ok_or_else(|| anyhow!("the project was closed before the task started"))?;
+            let is_task_terminal = terminal.read_with(cx, |terminal, _| {
+                terminal.task().map_or(false, |task| task.id == task_id)
+            })?;
+            if is_task_terminal {
+                break terminal;
+            }
+        };
+
+        terminal
+            .update(cx, |terminal, cx| terminal.wait_for_completed_task(cx))?
+            .await;
+        terminal.read_with(cx, |terminal, _| task_outcome(terminal))
+    }
+}
+
+fn task_outcome(terminal: &Terminal) -> TaskOutcome {
+    let status = match terminal.task().map(|task| task.status) {
+        Some(TaskStatus::Completed { success: true }) => TaskOutcomeStatus::Succeeded,
+        Some(TaskStatus::Completed { success: false }) => TaskOutcomeStatus::Failed,
+        _ => TaskOutcomeStatus::Unknown,
+    };
+
+    let text = terminal.text_content();
+    let lines = text.trim_end().lines().collect::<Vec<_>>();
+    let output = lines[lines.len().saturating_sub(MAX_OUTPUT_LINE_COUNT)..]
+        .iter()
+        .map(|line| line.trim_end())
+        .collect::<Vec<_>>()
+        .join("\n");
+    TaskOutcome { status, output }
+}
+
+impl Render for RunTaskView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let header = h_flex()
+            .gap_2()
+            .child(Icon::new(IconName::Terminal))
+            .child(Label::new(self.input.label.clone()))
+            .children(
+                self.command_label
+                    .clone()
+                    .map(|command_label| Label::new(command_label).color(Color::Muted)),
+            );
+
+        let status = if self.approval_tx.is_some() {
+            h_flex()
+                .gap_1()
+                .child(
+                    Button::new("run-task", "Run")
+                        .icon(IconName::Play)
+                        .icon_color(Color::Created)
+                        .on_click(cx.listener(|this, _, cx| this.respond(true, cx))),
+                )
+                .child(
+                    Button::new("decline-task", "Don't Run")
+                        .on_click(cx.listener(|this, _, cx| this.respond(false, cx))),
+                )
+                .into_any_element()
+        } else {
+            match &self.outcome {
+                None => Label::new("Running…").into_any_element(),
+                Some(Err(error)) => Label::new(format!("Failed to run task: {error}"))
+                    .color(Color::Error)
+                    .into_any_element(),
+                Some(Ok(outcome)) => match outcome.status {
+                    TaskOutcomeStatus::Declined => Label::new("Not run").color(Color::Muted),
+                    TaskOutcomeStatus::Succeeded => {
+                        Label::new("Finished successfully").color(Color::Created)
+                    }
+                    TaskOutcomeStatus::Failed => Label::new("Failed").color(Color::Error),
+                    TaskOutcomeStatus::Unknown => Label::new("Finished").color(Color::Muted),
+                }
+                .into_any_element(),
+            }
+        };
+
+        v_flex().gap_1().child(header).child(status)
+    }
+}
+
+impl ToolView for RunTaskView {
+    type Input = RunTaskInput;
+    type SerializedState = SerializedTaskOutcome;
+
+    fn generate(&self, _: &mut ProjectContext, _: &mut ViewContext<Self>) -> String {
+        match &self.outcome {
+            None => "The task did not run".to_string(),
+            Some(Err(error)) => format!("Failed to run task: {error:?}"),
+            Some(Ok(outcome)) => {
+                let status = match outcome.status {
+                    TaskOutcomeStatus::Declined => {
+                        return "The user chose not to run the task".to_string()
+                    }
+                    TaskOutcomeStatus::Succeeded => "finished successfully",
+                    TaskOutcomeStatus::Failed => "failed",
+                    TaskOutcomeStatus::Unknown => "finished without reporting an exit status",
+                };
+                format!(
+                    "Task `{}` {status}. Its output was:\n```\n{}\n```",
+                    self.input.label, outcome.output
+                )
+            }
+        }
+    }
+
+    fn set_input(&mut self, input: Self::Input, cx: &mut ViewContext<Self>) {
+        self.input = input;
+        cx.notify();
+    }
+
+    fn execute(&mut self, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        let (kind, resolved_task) = match self.resolve_task(cx) {
+            Ok(task) => task,
+            Err(error) => {
+                self.outcome = Some(Err(error));
+                cx.notify();
+                return Task::ready(Ok(()));
+            }
+        };
+        self.command_label = resolved_task
+            .resolved
+            .as_ref()
+            .map(|spawn_in_terminal| spawn_in_terminal.command_label.clone());
+
+        let (approval_tx, approval_rx) = oneshot::channel();
+        self.approval_tx = Some(approval_tx);
+        cx.notify();
+
+        cx.spawn(|this, mut cx| async move {
+            let approved = approval_rx.await.unwrap_or(false);
+            let outcome = if approved {
+                Self::run_task(this.clone(), kind, resolved_task, &mut cx).await
+            } else {
+                Ok(TaskOutcome {
+                    status: TaskOutcomeStatus::Declined,
+                    output: String::new(),
+                })
+            };
+            this.update(&mut cx, |this, cx| {
+                this.outcome = Some(outcome);
+                cx.notify();
+            })
+        })
+    }
+
+    fn serialize(&self, _cx: &mut ViewContext<Self>) -> Self::SerializedState {
+        SerializedTaskOutcome {
+            command_label: self.command_label.clone(),
+            outcome: self
+                .outcome
+                .as_ref()
+                .and_then(|outcome| outcome.as_ref().ok().cloned()),
+            error_message: self
+                .outcome
+                .as_ref()
+                .and_then(|outcome| outcome.as_ref().err())
+                .map(|error| error.to_string()),
+        }
+    }
+
+    fn deserialize(
+        &mut self,
+        output: Self::SerializedState,
+        cx: &mut ViewContext<Self>,
+    ) -> Result<()> {
+        self.command_label = output.command_label;
+        self.outcome = match (output.outcome, output.error_message) {
+            (_, Some(error_message)) => Some(Err(anyhow!("{}", error_message))),
+            (Some(outcome), None) => Some(Ok(outcome)),
+            (None, None) => None,
+        };
+        cx.notify();
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SerializedTaskOutcome {
+    command_label: Option<String>,
+    outcome: Option<TaskOutcome>,
+    error_message: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::{cell::RefCell, rc::Rc};
+
+    #[gpui::test]
+    async fn test_run_task(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/root",
+            json!({
+                ".zed": {
+                    "tasks.json": r#"[{ "label": "greet", "command": "echo", "args": ["hello"] }]"#,
+                },
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/root".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        cx.run_until_parked();
+
+        // Stand in for the terminal panel. The project's directory is fake, so the task
+        // runs in the current one.
+        let terminals = Rc::new(RefCell::new(Vec::new()));
+        let _subscription = cx.update(|cx| {
+            let terminals = terminals.clone();
+            let project = project.clone();
+            cx.subscribe(&workspace, move |_, event: &workspace::Event, cx| {
+                if let workspace::Event::SpawnTask(spawn_in_terminal) = event {
+                    let mut spawn_in_terminal = spawn_in_terminal.clone();
+                    spawn_in_terminal.cwd = None;
+                    let window = cx.window_handle();
+                    let terminal = project.update(cx, |project, cx| {
+                        project.create_terminal(None, Some(spawn_in_terminal), window, cx)
+                    });
+                    terminals.borrow_mut().push(terminal.unwrap());
+                }
+            })
+        });
+
+        let tool = RunTaskTool::new(workspace.downgrade(), project.clone());
+        let view = cx.update(|cx| tool.view(cx));
+
+        // Tasks that don't exist aren't run, and the model is told which ones do.
+        view.update(cx, |view, cx| {
+            view.set_input(
+                RunTaskInput {
+                    label: "build".into(),
+                },
+                cx,
+            )
+        });
+        view.update(cx, |view, cx| view.execute(cx)).await.unwrap();
+        view.update(cx, |view, _| {
+            assert_eq!(
+                view.outcome
+                    .as_ref()
+                    .unwrap()
+                    .as_ref()
+                    .unwrap_err()
+                    .to_string(),
+                "no task is labeled `build`, the available tasks are: `greet`"
+            );
+        });
+
+        // Tasks only run once the user approves them.
+        view.update(cx, |view, cx| {
+            view.set_input(
+                RunTaskInput {
+                    label: "greet".into(),
+                },
+                cx,
+            )
+        });
+        let execute = view.update(cx, |view, cx| view.execute(cx));
+        view.update(cx, |view, cx| {
+            assert_eq!(view.command_label.as_deref(), Some("echo hello"));
+            view.respond(false, cx);
+        });
+        execute.await.unwrap();
+        view.update(cx, |view, _| {
+            let outcome = view.outcome.as_ref().unwrap().as_ref().unwrap();
+            assert_eq!(outcome.status, TaskOutcomeStatus::Declined);
+        });
+        assert!(terminals.borrow().is_empty());
+
+        let execute = view.update(cx, |view, cx| view.execute(cx));
+        view.update(cx, |view, cx| view.respond(true, cx));
+        execute.await.unwrap();
+        view.update(cx, |view, _| {
+            let outcome = view.outcome.as_ref().unwrap().as_ref().unwrap();
+            assert_eq!(outcome.status, TaskOutcomeStatus::Succeeded);
+            assert!(outcome.output.contains("hello"), "{:?}", outcome.output);
+        });
+        assert_eq!(terminals.borrow().len(), 1);
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            terminal::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+        });
+    }
+}
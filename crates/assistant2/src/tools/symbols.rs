@@ -0,0 +1,304 @@
+use anyhow::{anyhow, Result};
+use assistant_tooling::{LanguageModelTool, ProjectContext, ToolView};
+use gpui::{prelude::*, AppContext, AsyncWindowContext, Model, Task, View};
+use language::{Bias, Location, OffsetRangeExt};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use ui::prelude::*;
+
+use super::{numbered_excerpt, project_path_for};
+
+const MAX_DEFINITION_COUNT: usize = 10;
+const MAX_REFERENCE_COUNT: usize = 30;
+/// Long definitions, such as whole functions, are cut off after this many lines.
+const MAX_DEFINITION_LINE_COUNT: u32 = 30;
+
+pub struct SymbolsTool {
+    project: Model<Project>,
+}
+
+impl SymbolsTool {
+    pub fn new(project: Model<Project>) -> Self {
+        Self { project }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+pub struct SymbolsInput {
+    /// The name of the symbol to look up, such as a function, type or variable.
+    symbol: String,
+    /// A file, relative to the root of the project, in which the symbol is used.
+    /// When given, the symbol is resolved from its first use in that file, which
+    /// finds local variables and disambiguates common names. Otherwise the symbol
+    /// is searched for across the whole project.
+    #[serde(default)]
+    path: Option<String>,
+    /// Whether to find the places where the symbol is referenced, as well as its definition.
+    #[serde(default)]
+    include_references: bool,
+}
+
+impl LanguageModelTool for SymbolsTool {
+    type View = SymbolsView;
+
+    fn name(&self) -> String {
+        "find_symbol".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Find the definition of a symbol, and optionally all references to it, using the project's language servers.".to_string()
+    }
+
+    fn view(&self, cx: &mut WindowContext) -> View<Self::View> {
+        cx.new_view(|_cx| SymbolsView {
+            project: self.project.clone(),
+            input: SymbolsInput::default(),
+            output: None,
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReportedLocation {
+    path: String,
+    line: u32,
+    excerpt: String,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct SymbolLocations {
+    definitions: Vec<ReportedLocation>,
+    references: Vec<ReportedLocation>,
+}
+
+pub struct SymbolsView {
+    project: Model<Project>,
+    input: SymbolsInput,
+    output: Option<Result<SymbolLocations>>,
+}
+
+impl SymbolsView {
+    async fn find_symbol(
+        project: Model<Project>,
+        input: SymbolsInput,
+        cx: &mut AsyncWindowContext,
+    ) -> Result<SymbolLocations> {
+        // Find a place where the symbol occurs, from which we can ask for references.
+        let (definitions, origin) = if let Some(path) = &input.path {
+            let project_path =
+                project.update(cx, |project, cx| project_path_for(project, path, cx))??;
+            let buffer = project
+                .update(cx, |project, cx| project.open_buffer(project_path, cx))?
+                .await?;
+            let offset = buffer
+                .read_with(cx, |buffer, _| find_word(&buffer.text(), &input.symbol))?
+                .ok_or_else(|| anyhow!("{} does not appear in {path}", input.symbol))?;
+            let links = project
+                .update(cx, |project, cx| project.definition(&buffer, offset, cx))?
+                .await?;
+            let definitions = links
+                .into_iter()
+                .map(|link| link.target)
+                .collect::<Vec<_>>();
+            let origin = Location {
+                range: buffer.read_with(cx, |buffer, _| {
+                    buffer.anchor_before(offset)..buffer.anchor_after(offset)
+                })?,
+                buffer,
+            };
+            (definitions, origin)
+        } else {
+            let mut symbols = project
+                .update(cx, |project, cx| project.symbols(&input.symbol, cx))?
+                .await?;
+            symbols.retain(|symbol| symbol.name == input.symbol);
+            symbols.truncate(MAX_DEFINITION_COUNT);
+
+            let mut definitions = Vec::new();
+            for symbol in &symbols {
+                let buffer = project
+                    .update(cx, |project, cx| project.open_buffer_for_symbol(symbol, cx))?
+                    .await?;
+                let range = buffer.read_with(cx, |buffer, _| {
+                    let start = buffer.clip_point_utf16(symbol.range.start, Bias::Left);
+                    let end = buffer.clip_point_utf16(symbol.range.end, Bias::Left);
+                    buffer.anchor_before(start)..buffer.anchor_after(end)
+                })?;
+                definitions.push(Location { buffer, range });
+            }
+            let origin = definitions
+                .first()
+                .cloned()
+                .ok_or_else(|| anyhow!("no symbol named {} was found", input.symbol))?;
+            (definitions, origin)
+        };
+
+        let references = if input.include_references {
+            project
+                .update(cx, |project, cx| {
+                    project.references(&origin.buffer, origin.range.start, cx)
+                })?
+                .await?
+        } else {
+            Vec::new()
+        };
+
+        cx.update(|cx| SymbolLocations {
+            definitions: definitions
+                .iter()
+                .take(MAX_DEFINITION_COUNT)
+                .map(|location| report_location(location, MAX_DEFINITION_LINE_COUNT, 2, cx))
+                .collect(),
+            references: references
+                .iter()
+                .take(MAX_REFERENCE_COUNT)
+                .map(|location| report_location(location, 0, 1, cx))
+                .collect(),
+        })
+    }
+}
+
+/// Finds the first occurrence of `word` in `text` that isn't part of a longer identifier.
+fn find_word(text: &str, word: &str) -> Option<usize> {
+    let is_identifier_char = |c: char| c.is_alphanumeric() || c == '_';
+    text.match_indices(word).map(|(ix, _)| ix).find(|&ix| {
+        let before = text[..ix].chars().next_back();
+        let after = text[ix + word.len()..].chars().next();
+        !before.map_or(false, is_identifier_char) && !after.map_or(false, is_identifier_char)
+    })
+}
+
+fn report_location(
+    location: &Location,
+    max_line_count: u32,
+    context_lines: u32,
+    cx: &AppContext,
+) -> ReportedLocation {
+    let buffer = location.buffer.read(cx);
+    let snapshot = buffer.snapshot();
+    let range = location.range.to_point(&snapshot);
+    let end_row = range.end.row.min(range.start.row + max_line_count);
+    ReportedLocation {
+        path: buffer
+            .file()
+            .map(|file| file.full_path(cx).to_string_lossy().into_owned())
+            .unwrap_or_else(|| "untitled".to_string()),
+        line: range.start.row + 1,
+        excerpt: numbered_excerpt(&snapshot, range.start.row..end_row, context_lines),
+    }
+}
+
+fn write_locations(output: &mut String, heading: &str, locations: &[ReportedLocation]) {
+    writeln!(output, "{heading}:").unwrap();
+    for location in locations {
+        writeln!(
+            output,
+            "{}:{}\n```\n{}```",
+            location.path, location.line, location.excerpt
+        )
+        .unwrap();
+    }
+}
+
+impl Render for SymbolsView {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        match &self.output {
+            None => Label::new(format!("Looking up {}…", self.input.symbol)).into_any_element(),
+            Some(Err(error)) => Label::new(format!("Failed to find symbol: {error}"))
+                .color(Color::Error)
+                .into_any_element(),
+            Some(Ok(locations)) => h_flex()
+                .gap_2()
+                .child(Icon::new(IconName::Code))
+                .child(Label::new(self.input.symbol.clone()))
+                .child(
+                    Label::new(format!(
+                        "{} definitions, {} references",
+                        locations.definitions.len(),
+                        locations.references.len()
+                    ))
+                    .color(Color::Muted),
+                )
+                .into_any_element(),
+        }
+    }
+}
+
+impl ToolView for SymbolsView {
+    type Input = SymbolsInput;
+    type SerializedState = SerializedSymbolLocations;
+
+    fn generate(&self, _: &mut ProjectContext, _: &mut ViewContext<Self>) -> String {
+        match &self.output {
+            None => "No symbols found".to_string(),
+            Some(Err(error)) => format!("Failed to find symbol: {error:?}"),
+            Some(Ok(locations)) => {
+                let mut output = String::new();
+                if locations.definitions.is_empty() {
+                    writeln!(&mut output, "No definition found").unwrap();
+                } else {
+                    write_locations(&mut output, "Definitions", &locations.definitions);
+                }
+                if self.input.include_references {
+                    write_locations(&mut output, "References", &locations.references);
+                }
+                output
+            }
+        }
+    }
+
+    fn set_input(&mut self, input: Self::Input, cx: &mut ViewContext<Self>) {
+        self.input = input;
+        cx.notify();
+    }
+
+    fn execute(&mut self, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        let project = self.project.clone();
+        let input = self.input.clone();
+        cx.spawn(|this, mut cx| async move {
+            let locations = Self::find_symbol(project, input, &mut cx).await;
+            this.update(&mut cx, |this, cx| {
+                this.output = Some(locations);
+                cx.notify();
+            })
+        })
+    }
+
+    fn serialize(&self, _cx: &mut ViewContext<Self>) -> Self::SerializedState {
+        match &self.output {
+            Some(Ok(locations)) => SerializedSymbolLocations {
+                locations: locations.clone(),
+                error_message: None,
+            },
+            Some(Err(error)) => SerializedSymbolLocations {
+                locations: SymbolLocations::default(),
+                error_message: Some(error.to_string()),
+            },
+            None => SerializedSymbolLocations {
+                locations: SymbolLocations::default(),
+                error_message: None,
+            },
+        }
+    }
+
+    fn deserialize(
+        &mut self,
+        output: Self::SerializedState,
+        cx: &mut ViewContext<Self>,
+    ) -> Result<()> {
+        self.output = Some(match output.error_message {
+            Some(error_message) => Err(anyhow!("{}", error_message)),
+            None => Ok(output.locations),
+        });
+        cx.notify();
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SerializedSymbolLocations {
+    locations: SymbolLocations,
+    error_message: Option<String>,
+}
@@ -1,5 +1,5 @@
 use crate::{
-    ui::{ActiveFileButton, ProjectIndexButton},
+    ui::{ActiveFileButton, ContextServersButton, ProjectIndexButton},
     AssistantChat, CompletionProvider,
 };
 use editor::{Editor, EditorElement, EditorStyle};
@@ -12,6 +12,7 @@ use ui::{popover_menu, prelude::*, ButtonLike, ContextMenu, Divider, TextSize, T
 pub struct Composer {
     editor: View<Editor>,
     project_index_button: View<ProjectIndexButton>,
+    context_servers_button: Option<View<ContextServersButton>>,
     active_file_button: Option<View<ActiveFileButton>>,
     model_selector: AnyElement,
 }
@@ -20,19 +21,23 @@ impl Composer {
     pub fn new(
         editor: View<Editor>,
         project_index_button: View<ProjectIndexButton>,
+        context_servers_button: Option<View<ContextServersButton>>,
         active_file_button: Option<View<ActiveFileButton>>,
         model_selector: AnyElement,
     ) -> Self {
         Self {
             editor,
             project_index_button,
+            context_servers_button,
             active_file_button,
             model_selector,
         }
     }
 
     fn render_tools(&mut self, _cx: &mut WindowContext) -> impl IntoElement {
-        h_flex().child(self.project_index_button.clone())
+        h_flex()
+            .child(self.project_index_button.clone())
+            .children(self.context_servers_button.clone())
     }
 
     fn render_attachment_tools(&mut self, _cx: &mut WindowContext) -> impl IntoElement {
@@ -0,0 +1,118 @@
+use context_servers::{ContextServerManager, ContextServerStatus};
+use gpui::{prelude::*, Model};
+use std::sync::Arc;
+use ui::{popover_menu, prelude::*, ButtonLike, ContextMenu, Indicator, Tooltip};
+
+/// Shows whether the configured context servers are running, and lets the user
+/// restart them.
+pub struct ContextServersButton {
+    manager: Model<ContextServerManager>,
+}
+
+impl ContextServersButton {
+    pub fn new(manager: Model<ContextServerManager>, cx: &mut ViewContext<Self>) -> Self {
+        cx.observe(&manager, |_, _, cx| cx.notify()).detach();
+        Self { manager }
+    }
+}
+
+impl Render for ContextServersButton {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let manager = self.manager.read(cx);
+        let statuses = manager
+            .servers()
+            .map(|server| server.status.clone())
+            .collect::<Vec<_>>();
+        if statuses.is_empty() {
+            return div().into_any_element();
+        }
+
+        let indicator_color = if statuses
+            .iter()
+            .any(|status| matches!(status, ContextServerStatus::Failed(_)))
+        {
+            Color::Error
+        } else if statuses.contains(&ContextServerStatus::Starting) {
+            Color::Warning
+        } else {
+            Color::Success
+        };
+
+        let manager = self.manager.clone();
+        popover_menu("context-servers")
+            .menu(move |cx| {
+                let manager = manager.clone();
+                ContextMenu::build(cx, move |mut menu, cx| {
+                    menu = menu.header("Context Servers");
+                    for server in manager.read(cx).servers() {
+                        let server_id = server.id.clone();
+                        let (status, color) = match &server.status {
+                            ContextServerStatus::Starting => ("Starting…".into(), Color::Warning),
+                            ContextServerStatus::Running => (
+                                SharedString::from(format!(
+                                    "{} tools, {} resources, {} prompts",
+                                    server.tools.len(),
+                                    server.resources.len(),
+                                    server.prompts.len()
+                                )),
+                                Color::Success,
+                            ),
+                            ContextServerStatus::Failed(error) => (error.clone(), Color::Error),
+                        };
+                        menu = menu.custom_entry(
+                            {
+                                let server_id = server_id.clone();
+                                move |_| {
+                                    h_flex()
+                                        .gap_2()
+                                        .child(Indicator::dot().color(color))
+                                        .child(Label::new(server_id.to_string()))
+                                        .child(
+                                            Label::new(status.clone())
+                                                .size(LabelSize::Small)
+                                                .color(Color::Muted),
+                                        )
+                                        .into_any_element()
+                                }
+                            },
+                            {
+                                let manager = manager.clone();
+                                move |cx| restart_server(&manager, &server_id, cx)
+                            },
+                        );
+                    }
+                    menu
+                })
+                .into()
+            })
+            .trigger(
+                ButtonLike::new("context-servers-button")
+                    .child(
+                        ui::IconWithIndicator::new(
+                            Icon::new(IconName::Server)
+                                .size(IconSize::XSmall)
+                                .color(Color::Default),
+                            Some(Indicator::dot().color(indicator_color)),
+                        )
+                        .indicator_border_color(Some(gpui::transparent_black())),
+                    )
+                    .tooltip(|cx| {
+                        Tooltip::with_meta(
+                            "Context Servers",
+                            None,
+                            "Click a server to restart it",
+                            cx,
+                        )
+                    }),
+            )
+            .into_any_element()
+    }
+}
+
+fn restart_server(
+    manager: &Model<ContextServerManager>,
+    server_id: &Arc<str>,
+    cx: &mut WindowContext,
+) {
+    manager.update(cx, |manager, cx| manager.restart_server(server_id, cx));
+}
@@ -7,6 +7,7 @@ use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::value::RawValue;
 use std::{
     any::TypeId,
+    cell::RefCell,
     collections::HashMap,
     fmt::Display,
     mem,
@@ -14,8 +15,12 @@ use std::{
 };
 use ui::ViewContext;
 
+/// The tools available to the language model.
+///
+/// Tools can be registered and unregistered after the registry has been shared,
+/// as tools provided by external processes come and go while the assistant is open.
 pub struct ToolRegistry {
-    registered_tools: HashMap<String, RegisteredTool>,
+    registered_tools: RefCell<HashMap<String, RegisteredTool>>,
 }
 
 #[derive(Default)]
@@ -128,12 +133,12 @@ struct RegisteredTool {
 impl ToolRegistry {
     pub fn new() -> Self {
         Self {
-            registered_tools: HashMap::new(),
+            registered_tools: RefCell::new(HashMap::new()),
         }
     }
 
     pub fn set_tool_enabled<T: 'static + LanguageModelTool>(&self, is_enabled: bool) {
-        for tool in self.registered_tools.values() {
+        for tool in self.registered_tools.borrow().values() {
             if tool.type_id == TypeId::of::<T>() {
                 tool.enabled.store(is_enabled, SeqCst);
                 return;
@@ -142,7 +147,7 @@ impl ToolRegistry {
     }
 
     pub fn is_tool_enabled<T: 'static + LanguageModelTool>(&self) -> bool {
-        for tool in self.registered_tools.values() {
+        for tool in self.registered_tools.borrow().values() {
             if tool.type_id == TypeId::of::<T>() {
                 return tool.enabled.load(SeqCst);
             }
@@ -152,6 +157,7 @@ impl ToolRegistry {
 
     pub fn definitions(&self) -> Vec<ToolFunctionDefinition> {
         self.registered_tools
+            .borrow()
             .values()
             .filter(|tool| tool.enabled.load(SeqCst))
             .map(|tool| tool.definition.clone())
@@ -170,7 +176,7 @@ impl ToolRegistry {
         }
         if let Some(arguments) = arguments {
             if call.arguments.is_empty() {
-                if let Some(tool) = self.registered_tools.get(&call.name) {
+                if let Some(tool) = self.registered_tools.borrow().get(&call.name) {
                     let view = (tool.build_view)(cx);
                     call.state = ToolFunctionCallState::KnownTool(view);
                 } else {
@@ -259,7 +265,8 @@ impl ToolRegistry {
         call: &SavedToolFunctionCall,
         cx: &mut WindowContext,
     ) -> Result<ToolFunctionCall> {
-        let Some(tool) = self.registered_tools.get(&call.name) else {
+        let registered_tools = self.registered_tools.borrow();
+        let Some(tool) = registered_tools.get(&call.name) else {
             return Err(anyhow!("no such tool {}", call.name));
         };
 
@@ -286,7 +293,7 @@ impl ToolRegistry {
         })
     }
 
-    pub fn register<T: 'static + LanguageModelTool>(&mut self, tool: T) -> Result<()> {
+    pub fn register<T: 'static + LanguageModelTool>(&self, tool: T) -> Result<()> {
         let name = tool.name();
         let registered_tool = RegisteredTool {
             type_id: TypeId::of::<T>(),
@@ -295,13 +302,22 @@ impl ToolRegistry {
             build_view: Box::new(move |cx: &mut WindowContext| Box::new(tool.view(cx))),
         };
 
-        let previous = self.registered_tools.insert(name.clone(), registered_tool);
-        if previous.is_some() {
+        let mut registered_tools = self.registered_tools.borrow_mut();
+        if registered_tools.contains_key(&name) {
             return Err(anyhow!("already registered a tool with name {}", name));
         }
+        registered_tools.insert(name, registered_tool);
 
         return Ok(());
     }
+
+    /// Removes the tool with the given name, returning whether it was registered.
+    ///
+    /// Calls to the tool that have already been made keep working, but the model
+    /// can no longer call it.
+    pub fn unregister(&self, name: &str) -> bool {
+        self.registered_tools.borrow_mut().remove(name).is_some()
+    }
 }
 
 impl<T: ToolView> InternalToolView for View<T> {
@@ -461,11 +477,33 @@ mod test {
         }
     }
 
+    #[gpui::test]
+    fn test_unregister_tool() {
+        let registry = ToolRegistry::new();
+        let tool = || WeatherTool {
+            current_weather: WeatherResult {
+                location: "San Francisco".to_string(),
+                temperature: 21.0,
+                unit: "Celsius".to_string(),
+            },
+        };
+        registry.register(tool()).unwrap();
+        assert!(registry.register(tool()).is_err());
+        assert_eq!(registry.definitions().len(), 1);
+
+        assert!(registry.unregister("get_current_weather"));
+        assert!(!registry.unregister("get_current_weather"));
+        assert!(registry.definitions().is_empty());
+
+        registry.register(tool()).unwrap();
+        assert_eq!(registry.definitions().len(), 1);
+    }
+
     #[gpui::test]
     async fn test_openai_weather_example(cx: &mut TestAppContext) {
         let (_, cx) = cx.add_window_view(|_cx| EmptyView);
 
-        let mut registry = ToolRegistry::new();
+        let registry = ToolRegistry::new();
         registry
             .register(WeatherTool {
                 current_weather: WeatherResult {
@@ -1,10 +1,14 @@
-use editor::Editor;
+mod sibling_entries;
+
+use editor::{Anchor, AnchorRangeExt as _, Editor};
 use gpui::{
-    Element, EventEmitter, IntoElement, ParentElement, Render, StyledText, Subscription,
+    Element, EventEmitter, IntoElement, ParentElement, Render, StyledText, Subscription, View,
     ViewContext,
 };
 use itertools::Itertools;
-use std::cmp;
+use language::{Outline, OutlineItem};
+use project::{Item as _, ProjectPath};
+use std::{cmp, path::PathBuf};
 use theme::ActiveTheme;
 use ui::{prelude::*, ButtonLike, ButtonStyle, Label, Tooltip};
 use workspace::{
@@ -30,6 +34,134 @@ impl Breadcrumbs {
 
 impl EventEmitter<ToolbarItemEvent> for Breadcrumbs {}
 
+/// What's shown when an editor's breadcrumb is clicked.
+#[derive(Clone, Debug, PartialEq)]
+enum BreadcrumbTarget {
+    /// The siblings of a file or directory in the path of the editor's file.
+    Entry(ProjectPath),
+    /// The siblings of the symbol at this depth among those containing the cursor.
+    Symbol(usize),
+}
+
+impl BreadcrumbTarget {
+    fn deploy(&self, editor: View<Editor>, cx: &mut WindowContext) {
+        match self {
+            BreadcrumbTarget::Entry(path) => {
+                if let Some(workspace) = editor.read(cx).workspace() {
+                    sibling_entries::deploy(workspace, path.clone(), cx);
+                }
+            }
+            BreadcrumbTarget::Symbol(depth) => {
+                if let Some(outline) = sibling_symbols(&editor, *depth, cx) {
+                    outline::deploy(editor, outline, cx);
+                }
+            }
+        }
+    }
+
+    fn tooltip_text(&self) -> &'static str {
+        match self {
+            BreadcrumbTarget::Entry(_) => "Show sibling files",
+            BreadcrumbTarget::Symbol(_) => "Show sibling symbols",
+        }
+    }
+}
+
+/// Assigns targets to an editor's breadcrumbs, splitting the leading path breadcrumb into one
+/// breadcrumb per path component.
+fn editor_breadcrumb_targets(
+    mut segments: Vec<BreadcrumbText>,
+    project_path: Option<ProjectPath>,
+) -> Vec<(BreadcrumbText, Option<BreadcrumbTarget>)> {
+    if segments.is_empty() {
+        return Vec::new();
+    }
+
+    let path_segment = segments.remove(0);
+    let mut result = Vec::new();
+    match project_path.and_then(|project_path| {
+        let path_text = project_path.path.to_string_lossy();
+        let prefix = path_segment.text.strip_suffix(path_text.as_ref())?;
+        Some((
+            prefix
+                .trim_end_matches(std::path::MAIN_SEPARATOR)
+                .to_string(),
+            project_path,
+        ))
+    }) {
+        Some((prefix, project_path)) => {
+            // When the worktree root's name is shown, it's not an entry of the worktree.
+            if !prefix.is_empty() {
+                result.push((
+                    BreadcrumbText {
+                        text: prefix,
+                        highlights: None,
+                        font: path_segment.font.clone(),
+                    },
+                    None,
+                ));
+            }
+
+            let mut ancestor = PathBuf::new();
+            for component in project_path.path.components() {
+                ancestor.push(component);
+                result.push((
+                    BreadcrumbText {
+                        text: component.as_os_str().to_string_lossy().into_owned(),
+                        highlights: None,
+                        font: path_segment.font.clone(),
+                    },
+                    Some(BreadcrumbTarget::Entry(ProjectPath {
+                        worktree_id: project_path.worktree_id,
+                        path: ancestor.as_path().into(),
+                    })),
+                ));
+            }
+        }
+        None => result.push((path_segment, None)),
+    }
+
+    result.extend(
+        segments
+            .into_iter()
+            .enumerate()
+            .map(|(depth, segment)| (segment, Some(BreadcrumbTarget::Symbol(depth)))),
+    );
+    result
+}
+
+/// Returns the items in the editor's outline that share a parent with the symbol at the given
+/// depth among those containing the cursor.
+fn sibling_symbols(
+    editor: &View<Editor>,
+    depth: usize,
+    cx: &WindowContext,
+) -> Option<Outline<Anchor>> {
+    let editor = editor.read(cx);
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let cursor = editor.selections.newest_anchor().head();
+    let (_, symbols) = snapshot.symbols_containing(cursor, Some(&cx.theme().syntax()))?;
+    symbols.get(depth)?;
+    let parent_range = depth
+        .checked_sub(1)
+        .map(|parent_depth| symbols[parent_depth].range.to_offset(&snapshot));
+
+    let siblings = snapshot
+        .outline(Some(&cx.theme().syntax()))?
+        .items
+        .into_iter()
+        .filter(|item| {
+            item.depth == depth
+                && parent_range.as_ref().map_or(true, |parent_range| {
+                    let range = item.range.to_offset(&snapshot);
+                    parent_range.start <= range.start && range.end <= parent_range.end
+                })
+        })
+        .map(|item| OutlineItem { depth: 0, ..item })
+        .collect();
+    Some(Outline::new(siblings))
+}
+
 impl Render for Breadcrumbs {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
         const MAX_SEGMENTS: usize = 12;
@@ -37,9 +169,25 @@ impl Render for Breadcrumbs {
         let Some(active_item) = self.active_item.as_ref() else {
             return element;
         };
-        let Some(mut segments) = active_item.breadcrumbs(cx.theme(), cx) else {
+        let Some(segments) = active_item.breadcrumbs(cx.theme(), cx) else {
             return element;
         };
+        let editor = active_item.downcast::<Editor>();
+        let mut segments = match &editor {
+            Some(editor) => {
+                let project_path = editor
+                    .read(cx)
+                    .buffer()
+                    .read(cx)
+                    .as_singleton()
+                    .and_then(|buffer| buffer.read(cx).project_path(cx));
+                editor_breadcrumb_targets(segments, project_path)
+            }
+            None => segments
+                .into_iter()
+                .map(|segment| (segment, None))
+                .collect(),
+        };
 
         let prefix_end_ix = cmp::min(segments.len(), MAX_SEGMENTS / 2);
         let suffix_start_ix = cmp::max(
@@ -49,53 +197,58 @@ impl Render for Breadcrumbs {
         if suffix_start_ix > prefix_end_ix {
             segments.splice(
                 prefix_end_ix..suffix_start_ix,
-                Some(BreadcrumbText {
-                    text: "⋯".into(),
-                    highlights: None,
-                    font: None,
-                }),
+                Some((
+                    BreadcrumbText {
+                        text: "⋯".into(),
+                        highlights: None,
+                        font: None,
+                    },
+                    None,
+                )),
             );
         }
 
-        let highlighted_segments = segments.into_iter().map(|segment| {
-            let mut text_style = cx.text_style();
-            if let Some(font) = segment.font {
-                text_style.font_family = font.family;
-                text_style.font_features = font.features;
-                text_style.font_style = font.style;
-                text_style.font_weight = font.weight;
-            }
-            text_style.color = Color::Muted.color(cx);
+        let segments = segments
+            .into_iter()
+            .enumerate()
+            .map(|(ix, (segment, target))| {
+                let mut text_style = cx.text_style();
+                if let Some(font) = segment.font {
+                    text_style.font_family = font.family;
+                    text_style.font_features = font.features;
+                    text_style.font_style = font.style;
+                    text_style.font_weight = font.weight;
+                }
+                text_style.color = Color::Muted.color(cx);
 
-            StyledText::new(segment.text.replace('\n', "␤"))
-                .with_highlights(&text_style, segment.highlights.unwrap_or_default())
-                .into_any()
-        });
-        let breadcrumbs = Itertools::intersperse_with(highlighted_segments, || {
+                let text = StyledText::new(segment.text.replace('\n', "␤"))
+                    .with_highlights(&text_style, segment.highlights.unwrap_or_default());
+                match target.zip(editor.as_ref()) {
+                    Some((target, editor)) => {
+                        let editor = editor.downgrade();
+                        let tooltip_text = target.tooltip_text();
+                        ButtonLike::new(("breadcrumb", ix))
+                            .child(text)
+                            .style(ButtonStyle::Subtle)
+                            .on_click(move |_, cx| {
+                                if let Some(editor) = editor.upgrade() {
+                                    target.deploy(editor, cx);
+                                }
+                            })
+                            .tooltip(move |cx| Tooltip::text(tooltip_text, cx))
+                            .into_any_element()
+                    }
+                    None => text.into_any(),
+                }
+            });
+        let breadcrumbs = Itertools::intersperse_with(segments, || {
             Label::new("›").color(Color::Muted).into_any_element()
         });
 
-        let breadcrumbs_stack = h_flex().gap_1().children(breadcrumbs);
-        match active_item
-            .downcast::<Editor>()
-            .map(|editor| editor.downgrade())
-        {
-            Some(editor) => element.child(
-                ButtonLike::new("toggle outline view")
-                    .child(breadcrumbs_stack)
-                    .style(ButtonStyle::Subtle)
-                    .on_click(move |_, cx| {
-                        if let Some(editor) = editor.upgrade() {
-                            outline::toggle(editor, &outline::Toggle, cx)
-                        }
-                    })
-                    .tooltip(|cx| Tooltip::for_action("Show symbol outline", &outline::Toggle, cx)),
-            ),
-            None => element
-                // Match the height of the `ButtonLike` in the other arm.
-                .h(rems_from_px(22.))
-                .child(breadcrumbs_stack),
-        }
+        // Match the height of the `ButtonLike`s used for clickable breadcrumbs.
+        element
+            .h(rems_from_px(22.))
+            .child(h_flex().gap_1().children(breadcrumbs))
     }
 }
 
@@ -136,3 +289,164 @@ impl ToolbarItemView for Breadcrumbs {
         self.pane_focused = pane_focused;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use indoc::indoc;
+    use language::{tree_sitter_rust, Language, LanguageConfig, LanguageMatcher};
+    use project::{FakeFs, Project, WorktreeId};
+    use serde_json::json;
+    use std::{path::Path, sync::Arc};
+    use workspace::{AppState, Workspace};
+
+    #[test]
+    fn test_editor_breadcrumb_targets() {
+        let worktree_id = WorktreeId::from_usize(1);
+        let entry = |path: &str| {
+            Some(BreadcrumbTarget::Entry(ProjectPath {
+                worktree_id,
+                path: Path::new(path).into(),
+            }))
+        };
+        let project_path = ProjectPath {
+            worktree_id,
+            path: Path::new("src/main.rs").into(),
+        };
+
+        assert_eq!(
+            targets(
+                &["src/main.rs", "impl Foo", "fn bar"],
+                Some(project_path.clone())
+            ),
+            vec![
+                ("src".to_string(), entry("src")),
+                ("main.rs".to_string(), entry("src/main.rs")),
+                ("impl Foo".to_string(), Some(BreadcrumbTarget::Symbol(0))),
+                ("fn bar".to_string(), Some(BreadcrumbTarget::Symbol(1))),
+            ]
+        );
+        assert_eq!(
+            targets(&["root/src/main.rs"], Some(project_path)),
+            vec![
+                ("root".to_string(), None),
+                ("src".to_string(), entry("src")),
+                ("main.rs".to_string(), entry("src/main.rs")),
+            ]
+        );
+        assert_eq!(
+            targets(&["untitled", "fn main"], None),
+            vec![
+                ("untitled".to_string(), None),
+                ("fn main".to_string(), Some(BreadcrumbTarget::Symbol(0))),
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_sibling_symbols(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            AppState::test(cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "a.rs": indoc! {"
+                    struct A {
+                        a1: i32,
+                        a2: i32,
+                    }
+                    struct B {
+                        b1: i32,
+                    }
+                "}
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        project.read_with(cx, |project, _| project.languages().add(rust_lang()));
+
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let worktree_id = project.read_with(cx, |project, cx| {
+            project.worktrees().next().unwrap().read(cx).id()
+        });
+        let editor = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_path((worktree_id, "a.rs"), None, true, cx)
+            })
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap();
+        cx.run_until_parked();
+
+        editor.update(cx, |editor, cx| {
+            editor.change_selections(None, cx, |s| {
+                s.select_ranges([language::Point::new(2, 4)..language::Point::new(2, 4)])
+            });
+        });
+        assert_eq!(sibling_names(&editor, 0, cx), vec!["struct A", "struct B"]);
+        assert_eq!(sibling_names(&editor, 1, cx), vec!["a1", "a2"]);
+        assert_eq!(sibling_names(&editor, 2, cx), Vec::<String>::new());
+    }
+
+    fn targets(
+        segments: &[&str],
+        project_path: Option<ProjectPath>,
+    ) -> Vec<(String, Option<BreadcrumbTarget>)> {
+        let segments = segments
+            .iter()
+            .map(|text| BreadcrumbText {
+                text: text.to_string(),
+                highlights: None,
+                font: None,
+            })
+            .collect();
+        editor_breadcrumb_targets(segments, project_path)
+            .into_iter()
+            .map(|(segment, target)| (segment.text, target))
+            .collect()
+    }
+
+    fn sibling_names(
+        editor: &View<Editor>,
+        depth: usize,
+        cx: &mut VisualTestContext,
+    ) -> Vec<String> {
+        cx.update(|cx| {
+            sibling_symbols(editor, depth, cx)
+                .map(|outline| outline.items.into_iter().map(|item| item.text).collect())
+                .unwrap_or_default()
+        })
+    }
+
+    fn rust_lang() -> Arc<Language> {
+        Arc::new(
+            Language::new(
+                LanguageConfig {
+                    name: "Rust".into(),
+                    matcher: LanguageMatcher {
+                        path_suffixes: vec!["rs".to_string()],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                Some(tree_sitter_rust::language()),
+            )
+            .with_outline_query(
+                r#"
+                (struct_item "struct" @context name: (_) @name) @item
+                (field_declaration name: (_) @name) @item
+                "#,
+            )
+            .unwrap(),
+        )
+    }
+}
@@ -0,0 +1,341 @@
+use fuzzy::{StringMatch, StringMatchCandidate};
+use gpui::{AppContext, DismissEvent, Model, Task, View, WeakView};
+use picker::{Picker, PickerDelegate};
+use project::{Project, ProjectPath, WorktreeId};
+use std::{path::Path, sync::Arc};
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::Workspace;
+
+/// Shows a picker of the files and directories next to the given entry, with that entry selected.
+pub(crate) fn deploy(workspace: View<Workspace>, path: ProjectPath, cx: &mut WindowContext) {
+    workspace.update(cx, |workspace, cx| {
+        let project = workspace.project().clone();
+        let handle = cx.view().downgrade();
+        workspace.toggle_modal(cx, move |cx| {
+            let directory = Arc::from(path.path.parent().unwrap_or(Path::new("")));
+            let delegate = SiblingEntriesDelegate::new(handle, project, path);
+            let mut picker = Picker::uniform_list(delegate, cx).width(rems(34.));
+            picker.delegate.load_entries(directory, cx);
+            picker.refresh(cx);
+            picker
+        });
+    });
+}
+
+struct SiblingEntry {
+    name: String,
+    path: Arc<Path>,
+    is_dir: bool,
+}
+
+pub(crate) struct SiblingEntriesDelegate {
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    worktree_id: WorktreeId,
+    entries: Vec<SiblingEntry>,
+    /// The entry that's selected while the query is empty.
+    current_path: Option<Arc<Path>>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+    load_directory: Task<()>,
+}
+
+impl SiblingEntriesDelegate {
+    fn new(workspace: WeakView<Workspace>, project: Model<Project>, path: ProjectPath) -> Self {
+        Self {
+            workspace,
+            project,
+            worktree_id: path.worktree_id,
+            entries: Vec::new(),
+            current_path: Some(path.path),
+            matches: Vec::new(),
+            selected_index: 0,
+            load_directory: Task::ready(()),
+        }
+    }
+
+    /// Lists the entries in the directory. Directories that the worktree hasn't scanned yet are
+    /// loaded first, and the picker is refreshed once their entries are known.
+    fn load_entries(&mut self, directory: Arc<Path>, cx: &mut ViewContext<Picker<Self>>) {
+        self.entries = self.child_entries(&directory, cx);
+        self.load_directory = Task::ready(());
+
+        let unloaded_entry_id = self
+            .project
+            .read(cx)
+            .entry_for_path(
+                &ProjectPath {
+                    worktree_id: self.worktree_id,
+                    path: directory.clone(),
+                },
+                cx,
+            )
+            .filter(|entry| entry.kind.is_unloaded())
+            .map(|entry| entry.id);
+        let Some(entry_id) = unloaded_entry_id else {
+            return;
+        };
+        let worktree_id = self.worktree_id;
+        let Some(expand) = self.project.update(cx, |project, cx| {
+            project.expand_entry(worktree_id, entry_id, cx)
+        }) else {
+            return;
+        };
+        self.load_directory = cx.spawn(|picker, mut cx| async move {
+            expand.await.log_err();
+            picker
+                .update(&mut cx, |picker, cx| {
+                    picker.delegate.entries = picker.delegate.child_entries(&directory, cx);
+                    picker.refresh(cx);
+                })
+                .ok();
+        });
+    }
+
+    /// Returns the entries in the directory, with subdirectories first.
+    fn child_entries(&self, directory: &Path, cx: &AppContext) -> Vec<SiblingEntry> {
+        let worktree = self.project.read(cx).worktree_for_id(self.worktree_id, cx);
+        let Some(worktree) = worktree else {
+            return Vec::new();
+        };
+
+        let mut entries = worktree
+            .read(cx)
+            .child_entries(directory)
+            .map(|entry| SiblingEntry {
+                name: entry
+                    .path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                path: entry.path.clone(),
+                is_dir: entry.is_dir(),
+            })
+            .collect::<Vec<_>>();
+        entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+        entries
+    }
+}
+
+impl PickerDelegate for SiblingEntriesDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Search this directory...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        if query.is_empty() {
+            self.matches = self
+                .entries
+                .iter()
+                .enumerate()
+                .map(|(id, entry)| StringMatch {
+                    candidate_id: id,
+                    score: Default::default(),
+                    positions: Default::default(),
+                    string: entry.name.clone(),
+                })
+                .collect();
+            self.selected_index = self
+                .entries
+                .iter()
+                .position(|entry| Some(&entry.path) == self.current_path.as_ref())
+                .unwrap_or(0);
+            Task::ready(())
+        } else {
+            let candidates = self
+                .entries
+                .iter()
+                .enumerate()
+                .map(|(id, entry)| StringMatchCandidate::new(id, entry.name.clone()))
+                .collect::<Vec<_>>();
+            let executor = cx.background_executor().clone();
+            cx.spawn(|picker, mut cx| async move {
+                let matches = fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    candidates.len(),
+                    &Default::default(),
+                    executor,
+                )
+                .await;
+                picker
+                    .update(&mut cx, |picker, cx| {
+                        picker.delegate.matches = matches;
+                        picker.delegate.selected_index = 0;
+                        cx.notify();
+                    })
+                    .log_err();
+            })
+        }
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(entry) = self
+            .matches
+            .get(self.selected_index)
+            .and_then(|mat| self.entries.get(mat.candidate_id))
+        else {
+            return;
+        };
+
+        // Directories are opened in the picker, so that a file can be found by walking down the
+        // tree from one of the breadcrumbs.
+        if entry.is_dir {
+            let directory = entry.path.clone();
+            self.current_path = None;
+            self.load_entries(directory, cx);
+            cx.defer(|picker, cx| {
+                if picker.query(cx).is_empty() {
+                    picker.refresh(cx);
+                } else {
+                    picker.set_query("", cx);
+                }
+            });
+            return;
+        }
+
+        let project_path = ProjectPath {
+            worktree_id: self.worktree_id,
+            path: entry.path.clone(),
+        };
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace
+                    .open_path(project_path, None, true, cx)
+                    .detach_and_log_err(cx);
+            })
+            .ok();
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, _: &mut ViewContext<Picker<Self>>) {}
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = &self.matches[ix];
+        let entry = &self.entries[mat.candidate_id];
+        let icon = if entry.is_dir {
+            IconName::Folder
+        } else {
+            IconName::File
+        };
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .start_slot(Icon::new(icon).color(Color::Muted))
+                .child(HighlightedLabel::new(
+                    entry.name.clone(),
+                    mat.positions.clone(),
+                )),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use project::{FakeFs, WorktreeSettings};
+    use serde_json::json;
+    use settings::SettingsStore;
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_sibling_entries_in_unloaded_directory(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            AppState::test(cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<WorktreeSettings>(cx, |settings| {
+                    settings.lazy_file_scan = Some(true);
+                });
+            });
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "a.txt": "",
+                "src": {
+                    "lib.rs": "",
+                    "util": { "mod.rs": "" },
+                },
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let worktree_id = project.read_with(cx, |project, cx| {
+            let worktree = project.worktrees().next().unwrap().read(cx);
+            assert!(worktree.entry_for_path("src").unwrap().kind.is_unloaded());
+            worktree.id()
+        });
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+
+        let path = ProjectPath {
+            worktree_id,
+            path: Path::new("a.txt").into(),
+        };
+        cx.update(|cx| deploy(workspace.clone(), path, cx));
+        let picker = workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_modal::<Picker<SiblingEntriesDelegate>>(cx)
+                .unwrap()
+        });
+        picker.update(cx, |picker, _| {
+            assert_eq!(match_names(picker), ["src", "a.txt"]);
+            assert_eq!(picker.delegate.selected_index, 1);
+        });
+
+        // Opening a directory that hasn't been scanned yet lists its entries once it's loaded.
+        picker.update(cx, |picker, cx| {
+            picker.delegate.selected_index = 0;
+            picker.delegate.confirm(false, cx);
+        });
+        cx.run_until_parked();
+        picker.update(cx, |picker, _| {
+            assert_eq!(match_names(picker), ["util", "lib.rs"]);
+        });
+
+        cx.simulate_input("lib");
+        cx.run_until_parked();
+        picker.update(cx, |picker, _| {
+            assert_eq!(match_names(picker), ["lib.rs"]);
+        });
+    }
+
+    fn match_names(picker: &Picker<SiblingEntriesDelegate>) -> Vec<&str> {
+        picker
+            .delegate
+            .matches
+            .iter()
+            .map(|mat| mat.string.as_str())
+            .collect()
+    }
+}
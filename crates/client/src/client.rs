@@ -11,7 +11,7 @@ use async_tungstenite::tungstenite::{
     http::{Request, StatusCode},
 };
 use clock::SystemClock;
-use collections::HashMap;
+use collections::{HashMap, HashSet};
 use futures::{
     channel::oneshot, future::LocalBoxFuture, AsyncReadExt, FutureExt, SinkExt, Stream, StreamExt,
     TryFutureExt as _, TryStreamExt,
@@ -268,6 +268,10 @@ struct ClientState {
     entities_by_type_and_remote_id: HashMap<(TypeId, u64), WeakSubscriber>,
     models_by_message_type: HashMap<TypeId, AnyWeakModel>,
     entity_types_by_message_type: HashMap<TypeId, TypeId>,
+    /// The message types whose handlers respond to them as requests.
+    request_handler_types: HashSet<TypeId>,
+    /// Whether the client is connected straight to another peer rather than to the server.
+    is_direct_connection: bool,
     #[allow(clippy::type_complexity)]
     message_handlers: HashMap<
         TypeId,
@@ -344,6 +348,8 @@ impl Default for ClientState {
             models_by_message_type: Default::default(),
             entities_by_type_and_remote_id: Default::default(),
             entity_types_by_message_type: Default::default(),
+            request_handler_types: Default::default(),
+            is_direct_connection: false,
             message_handlers: Default::default(),
         }
     }
@@ -721,6 +727,10 @@ impl Client {
             + Sync,
         F: 'static + Future<Output = Result<M::Response>>,
     {
+        self.state
+            .write()
+            .request_handler_types
+            .insert(TypeId::of::<M>());
         self.add_message_handler(model, move |handle, envelope, this, cx| {
             Self::respond_to_request(
                 envelope.receipt(),
@@ -788,6 +798,10 @@ impl Client {
         H: 'static + Fn(Model<E>, TypedEnvelope<M>, Arc<Self>, AsyncAppContext) -> F + Send + Sync,
         F: 'static + Future<Output = Result<M::Response>>,
     {
+        self.state
+            .write()
+            .request_handler_types
+            .insert(TypeId::of::<M>());
         self.add_model_message_handler(move |entity, envelope, client, cx| {
             Self::respond_to_request::<M, _>(
                 envelope.receipt(),
@@ -900,7 +914,7 @@ impl Client {
                         }
 
                         futures::select_biased! {
-                            result = self.set_connection(conn, cx).fuse() => result,
+                            result = self.set_connection(conn, false, cx).fuse() => result,
                             _ = timeout => {
                                 self.set_status(Status::ConnectionError, cx);
                                 Err(anyhow!("timed out waiting on hello message from server"))
@@ -935,18 +949,55 @@ impl Client {
         }
     }
 
+    /// Connects to another peer over a connection that doesn't go through the server, such as
+    /// one to a headless instance of Zed that was launched over SSH. Each side introduces itself
+    /// with the peer id that the other side knows it by, in place of the server's hello.
+    pub async fn connect_directly(
+        self: &Arc<Self>,
+        connection: Connection,
+        cx: &AsyncAppContext,
+    ) -> Result<()> {
+        if self.status().borrow().is_connected() {
+            return Err(anyhow!("client is already connected"));
+        }
+        self.set_status(Status::Connecting, cx);
+        if let Err(error) = self.set_connection(connection, true, cx).await {
+            self.set_status(Status::ConnectionError, cx);
+            return Err(error);
+        }
+        Ok(())
+    }
+
     async fn set_connection(
         self: &Arc<Self>,
         conn: Connection,
+        is_direct: bool,
         cx: &AsyncAppContext,
     ) -> Result<()> {
         let executor = cx.background_executor();
         log::info!("add connection to peer");
-        let (connection_id, handle_io, mut incoming) = self.peer.add_connection(conn, {
+        let create_timer = {
             let executor = executor.clone();
             move |duration| executor.timer(duration)
-        });
+        };
+        let (connection_id, handle_io, mut incoming) = if is_direct {
+            let (connection_id, handle_io, incoming) =
+                self.peer.add_direct_connection(conn, create_timer);
+            (connection_id, handle_io.boxed(), incoming)
+        } else {
+            let (connection_id, handle_io, incoming) = self.peer.add_connection(conn, create_timer);
+            (connection_id, handle_io.boxed(), incoming)
+        };
         let handle_io = executor.spawn(handle_io);
+        self.state.write().is_direct_connection = is_direct;
+        if is_direct {
+            self.peer.send(
+                connection_id,
+                proto::Hello {
+                    peer_id: Some(connection_id.into()),
+                },
+            )?;
+        }
 
         let peer_id = async {
             log::info!("waiting for server hello");
@@ -1021,6 +1072,10 @@ impl Client {
                             this.set_status(Status::SignedOut, &cx);
                         }
                     }
+                    Err(err) if is_direct => {
+                        log::error!("direct connection error: {:?}", err);
+                        this.set_status(Status::SignedOut, &cx);
+                    }
                     Err(err) => {
                         log::error!("connection error: {:?}", err);
                         this.set_status(Status::ConnectionLost, &cx);
@@ -1429,6 +1484,13 @@ impl Client {
         let type_name = message.payload_type_name();
         let payload_type_id = message.payload_type_id();
         let sender_id = message.original_sender_id();
+        // A peer that's connected directly expects a response to requests that we handle as
+        // messages, since there's no server to respond on our behalf.
+        let needs_acknowledgement = state.is_direct_connection
+            && proto::is_request_message(type_name)
+            && !state.request_handler_types.contains(&payload_type_id);
+        let connection_id = message.sender_id();
+        let message_id = message.message_id();
 
         let mut subscriber = None;
 
@@ -1490,8 +1552,15 @@ impl Client {
                 sender_id,
                 type_name
             );
+            let peer = needs_acknowledgement.then(|| self.peer.clone());
             cx.spawn(move |_| async move {
-                    match future.await {
+                    let result = future.await;
+                    if let Some(peer) = peer {
+                        let response =
+                            result.as_ref().map(|_| ()).map_err(|error| error.to_proto());
+                        peer.acknowledge(connection_id, message_id, response).log_err();
+                    }
+                    match result {
                         Ok(()) => {
                             log::debug!(
                                 "rpc message handled. client_id:{}, sender_id:{:?}, type:{}",
@@ -6,7 +6,10 @@ pub fn language_model_request_to_open_ai(
     request: proto::CompleteWithLanguageModel,
 ) -> Result<open_ai::Request> {
     Ok(open_ai::Request {
-        model: open_ai::Model::from_id(&request.model).unwrap_or(open_ai::Model::FourTurbo),
+        model: open_ai::Model::from_id(&request.model)
+            .unwrap_or(open_ai::Model::FourTurbo)
+            .id()
+            .into(),
         messages: request
             .messages
             .into_iter()
@@ -99,6 +102,7 @@ pub fn language_model_request_to_google_ai(
             .into_iter()
             .map(language_model_request_message_to_google_ai)
             .collect::<Result<Vec<_>>>()?,
+        system_instruction: None,
         generation_config: None,
         safety_settings: None,
     })
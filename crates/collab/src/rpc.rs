@@ -4404,10 +4404,12 @@ async fn complete_with_google_ai(
     session: UserSession,
     api_key: Arc<str>,
 ) -> Result<()> {
+    let model = google_ai::Model::from_id(&request.model).unwrap_or(google_ai::Model::GeminiPro);
     let mut stream = google_ai::stream_generate_content(
         session.http_client.clone(),
         google_ai::API_URL,
         api_key.as_ref(),
+        model.id(),
         crate::ai::language_model_request_to_google_ai(request)?,
     )
     .await
@@ -4613,10 +4615,12 @@ async fn count_tokens_with_language_model(
 
     let api_key = google_ai_api_key
         .ok_or_else(|| anyhow!("no Google AI API key configured on the server"))?;
+    let model = google_ai::Model::from_id(&request.model).unwrap_or(google_ai::Model::GeminiPro);
     let tokens_response = google_ai::count_tokens(
         session.http_client.as_ref(),
         google_ai::API_URL,
         &api_key,
+        model.id(),
         crate::ai::count_tokens_request_to_google_ai(request)?,
     )
     .await?;
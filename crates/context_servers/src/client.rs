@@ -0,0 +1,504 @@
+use anyhow::{anyhow, Context as _, Result};
+use collections::HashMap;
+use futures::{
+    channel::oneshot, future::Shared, io::BufWriter, select, AsyncRead, AsyncWrite, Future,
+    FutureExt,
+};
+use gpui::{AsyncAppContext, BackgroundExecutor, Task};
+use lsp::RequestId;
+use parking_lot::Mutex;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{value::RawValue, Value};
+use smol::{
+    channel,
+    io::{AsyncBufReadExt, BufReader},
+    process,
+};
+use std::{
+    fmt,
+    path::PathBuf,
+    process::Stdio,
+    sync::{
+        atomic::{AtomicI32, Ordering::SeqCst},
+        Arc,
+    },
+    time::Duration,
+};
+use util::TryFutureExt;
+
+const JSON_RPC_VERSION: &str = "2.0";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+/// How long to wait for the server to exit after it closes its connection.
+const EXIT_TIMEOUT: Duration = Duration::from_secs(5);
+/// The JSON-RPC error code for requests whose method the receiver doesn't implement.
+const METHOD_NOT_FOUND: i32 = -32601;
+
+type ResponseHandler = Box<dyn Send + FnOnce(Result<String, Error>)>;
+
+/// A command that launches a context server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContextServerBinary {
+    pub executable: PathBuf,
+    pub args: Vec<String>,
+    pub env: Option<HashMap<String, String>>,
+}
+
+/// A JSON-RPC connection to a context server process, framed the same way as
+/// language server messages.
+pub struct Client {
+    name: Arc<str>,
+    next_id: AtomicI32,
+    outbound_tx: channel::Sender<String>,
+    response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
+    executor: BackgroundExecutor,
+    stopped: Shared<oneshot::Receiver<String>>,
+    _io_task: Task<()>,
+}
+
+#[derive(Serialize)]
+struct Request<'a, T> {
+    jsonrpc: &'static str,
+    id: RequestId,
+    method: &'a str,
+    params: T,
+}
+
+#[derive(Serialize)]
+struct Notification<'a, T> {
+    jsonrpc: &'static str,
+    method: &'a str,
+    params: T,
+}
+
+#[derive(Serialize)]
+struct ErrorResponse {
+    jsonrpc: &'static str,
+    id: RequestId,
+    error: Error,
+}
+
+/// An incoming message, which is either a response to one of our requests, or a
+/// request or notification from the server.
+#[derive(Deserialize)]
+struct AnyMessage<'a> {
+    #[serde(default)]
+    id: Option<RequestId>,
+    #[serde(default, borrow)]
+    method: Option<&'a str>,
+    #[serde(default)]
+    error: Option<Error>,
+    #[serde(default, borrow)]
+    result: Option<&'a RawValue>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Error {
+    #[serde(default)]
+    code: i32,
+    message: String,
+}
+
+impl Client {
+    /// Starts the context server process and begins exchanging messages with it.
+    ///
+    /// The server isn't usable until it has been initialized, see [`crate::protocol::ModelContextProtocol`].
+    pub fn new(name: Arc<str>, binary: ContextServerBinary, cx: AsyncAppContext) -> Result<Self> {
+        log::info!(
+            "starting context server {name}. executable: {:?}, args: {:?}",
+            binary.executable,
+            &binary.args
+        );
+
+        let mut command = process::Command::new(&binary.executable);
+        command
+            .args(&binary.args)
+            .envs(binary.env.unwrap_or_default())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        let mut server = command.spawn().with_context(|| {
+            format!(
+                "failed to spawn context server {name}. executable: {:?}, args: {:?}",
+                binary.executable, &binary.args
+            )
+        })?;
+
+        let stdin = server.stdin.take().unwrap();
+        let stdout = server.stdout.take().unwrap();
+        let stderr = server.stderr.take().unwrap();
+        let exit = async move {
+            match server.status().await {
+                Ok(status) => format!("the server exited with {status}"),
+                Err(error) => format!("the server exited: {error}"),
+            }
+        };
+
+        Ok(Self::new_internal(
+            name,
+            stdin,
+            stdout,
+            stderr,
+            exit,
+            cx.background_executor(),
+        ))
+    }
+
+    /// Exchanges messages with a server over the given streams. The server is considered
+    /// stopped once its output ends or its input can't be written to, after which `exit` is
+    /// awaited to describe why. Dropping `exit` must stop the server.
+    fn new_internal<Stdin, Stdout, Stderr>(
+        name: Arc<str>,
+        stdin: Stdin,
+        stdout: Stdout,
+        stderr: Stderr,
+        exit: impl 'static + Send + Future<Output = String>,
+        executor: &BackgroundExecutor,
+    ) -> Self
+    where
+        Stdin: AsyncWrite + Unpin + Send + 'static,
+        Stdout: AsyncRead + Unpin + Send + 'static,
+        Stderr: AsyncRead + Unpin + Send + 'static,
+    {
+        let (outbound_tx, outbound_rx) = channel::unbounded::<String>();
+        let response_handlers =
+            Arc::new(Mutex::new(Some(HashMap::<_, ResponseHandler>::default())));
+
+        let stdout_input_task = executor.spawn({
+            let name = name.clone();
+            let response_handlers = response_handlers.clone();
+            let outbound_tx = outbound_tx.clone();
+            Self::handle_input(name, stdout, response_handlers, outbound_tx).log_err()
+        });
+        let stderr_input_task = executor.spawn(Self::handle_stderr(name.clone(), stderr).log_err());
+        let input_task = executor.spawn(async move {
+            let (stdout, stderr) = futures::join!(stdout_input_task, stderr_input_task);
+            stdout.or(stderr)
+        });
+        let output_task = executor
+            .spawn(Self::handle_output(stdin, outbound_rx, response_handlers.clone()).log_err());
+
+        let (stopped_tx, stopped_rx) = oneshot::channel();
+        let io_task = executor.spawn({
+            let name = name.clone();
+            let executor = executor.clone();
+            async move {
+                futures::future::select(input_task, output_task).await;
+                let mut timeout = executor.timer(EXIT_TIMEOUT).fuse();
+                let reason = select! {
+                    reason = exit.fuse() => reason,
+                    _ = timeout => "the server closed its connection".to_string(),
+                };
+                log::info!("context server {name} stopped: {reason}");
+                stopped_tx.send(reason).ok();
+            }
+        });
+
+        Self {
+            name,
+            next_id: Default::default(),
+            outbound_tx,
+            response_handlers,
+            executor: executor.clone(),
+            stopped: stopped_rx.shared(),
+            _io_task: io_task,
+        }
+    }
+
+    pub fn name(&self) -> &Arc<str> {
+        &self.name
+    }
+
+    /// Resolves with the reason the server stopped, once it has.
+    pub fn stopped(&self) -> impl 'static + Future<Output = String> {
+        let stopped = self.stopped.clone();
+        async move {
+            stopped
+                .await
+                .unwrap_or_else(|_| "the client was dropped".to_string())
+        }
+    }
+
+    async fn handle_input<Stdout>(
+        name: Arc<str>,
+        stdout: Stdout,
+        response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
+        outbound_tx: channel::Sender<String>,
+    ) -> Result<()>
+    where
+        Stdout: AsyncRead + Unpin + Send + 'static,
+    {
+        let mut stdout = BufReader::new(stdout);
+        let _clear_response_handlers = util::defer({
+            let response_handlers = response_handlers.clone();
+            move || {
+                response_handlers.lock().take();
+            }
+        });
+        let mut buffer = Vec::new();
+        loop {
+            lsp::read_message(&mut stdout, &mut buffer).await?;
+
+            let Ok(message) = serde_json::from_slice::<AnyMessage>(&buffer) else {
+                log::warn!(
+                    "failed to deserialize message from context server {name}:\n{}",
+                    String::from_utf8_lossy(&buffer)
+                );
+                continue;
+            };
+
+            match (message.id, message.method) {
+                // We don't implement any of the requests that servers can make of their
+                // clients, so tell the server rather than leaving the request hanging.
+                (Some(id), Some(method)) => {
+                    log::info!("context server {name} sent unsupported request {method}");
+                    let response = serde_json::to_string(&ErrorResponse {
+                        jsonrpc: JSON_RPC_VERSION,
+                        id,
+                        error: Error {
+                            code: METHOD_NOT_FOUND,
+                            message: format!("unsupported method {method}"),
+                        },
+                    })?;
+                    outbound_tx.try_send(response).ok();
+                }
+                (Some(id), None) => {
+                    if let Some(handler) = response_handlers
+                        .lock()
+                        .as_mut()
+                        .and_then(|handlers| handlers.remove(&id))
+                    {
+                        if let Some(error) = message.error {
+                            handler(Err(error));
+                        } else if let Some(result) = message.result {
+                            handler(Ok(result.get().into()));
+                        } else {
+                            handler(Ok("null".into()));
+                        }
+                    }
+                }
+                (None, Some(method)) => {
+                    log::debug!("context server {name} sent notification {method}");
+                }
+                (None, None) => {
+                    log::warn!("context server {name} sent a message with no id or method");
+                }
+            }
+
+            // Don't starve other tasks when receiving lots of messages at once.
+            smol::future::yield_now().await;
+        }
+    }
+
+    async fn handle_stderr<Stderr>(name: Arc<str>, stderr: Stderr) -> Result<()>
+    where
+        Stderr: AsyncRead + Unpin + Send + 'static,
+    {
+        let mut stderr = BufReader::new(stderr);
+        let mut buffer = String::new();
+        loop {
+            buffer.clear();
+            if stderr.read_line(&mut buffer).await? == 0 {
+                return Ok(());
+            }
+            log::debug!("context server {name} stderr: {}", buffer.trim_end());
+        }
+    }
+
+    async fn handle_output<Stdin>(
+        stdin: Stdin,
+        outbound_rx: channel::Receiver<String>,
+        response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
+    ) -> Result<()>
+    where
+        Stdin: AsyncWrite + Unpin + Send + 'static,
+    {
+        let mut stdin = BufWriter::new(stdin);
+        let _clear_response_handlers = util::defer({
+            let response_handlers = response_handlers.clone();
+            move || {
+                response_handlers.lock().take();
+            }
+        });
+        while let Ok(message) = outbound_rx.recv().await {
+            log::trace!("outgoing context server message: {message}");
+            lsp::write_message(&mut stdin, &message).await?;
+        }
+        Ok(())
+    }
+
+    /// Sends a request to the server and waits for its response.
+    pub async fn request<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: impl Serialize,
+    ) -> Result<T> {
+        let id = RequestId::Int(self.next_id.fetch_add(1, SeqCst));
+        let message = serde_json::to_string(&Request {
+            jsonrpc: JSON_RPC_VERSION,
+            id: id.clone(),
+            method,
+            params,
+        })?;
+
+        let (tx, rx) = oneshot::channel();
+        self.response_handlers
+            .lock()
+            .as_mut()
+            .ok_or_else(|| anyhow!("context server {} shut down", self.name))?
+            .insert(
+                id.clone(),
+                Box::new(move |result| {
+                    tx.send(result).ok();
+                }),
+            );
+
+        self.outbound_tx
+            .try_send(message)
+            .with_context(|| format!("failed to write to context server {}", self.name))?;
+
+        let mut timeout = self.executor.timer(REQUEST_TIMEOUT).fuse();
+        let response = select! {
+            response = rx.fuse() => response.context("context server shut down")?,
+            _ = timeout => {
+                if let Some(handlers) = self.response_handlers.lock().as_mut() {
+                    handlers.remove(&id);
+                }
+                return Err(anyhow!(
+                    "context server {} did not respond to {method} within {REQUEST_TIMEOUT:?}",
+                    self.name
+                ));
+            }
+        };
+
+        match response {
+            Ok(response) => serde_json::from_str(&response).with_context(|| {
+                format!(
+                    "failed to deserialize response to {method} from context server {}",
+                    self.name
+                )
+            }),
+            Err(error) => Err(anyhow!("{method} failed: {}", error.message)),
+        }
+    }
+
+    /// Sends a notification to the server.
+    pub fn notify(&self, method: &str, params: impl Serialize) -> Result<()> {
+        let message = serde_json::to_string(&Notification {
+            jsonrpc: JSON_RPC_VERSION,
+            method,
+            params,
+        })?;
+        self.outbound_tx
+            .try_send(message)
+            .with_context(|| format!("failed to write to context server {}", self.name))?;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Client {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Client")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Parameters for requests and notifications that take none.
+pub(crate) fn no_params() -> Value {
+    Value::Object(Default::default())
+}
+
+/// Answers a fake server's requests, given their method and parameters.
+#[cfg(test)]
+pub(crate) type FakeHandler = Arc<dyn Send + Sync + Fn(&str, Value) -> Result<Value, String>>;
+
+/// A context server that runs in this process, for tests. The server stops when it is dropped.
+#[cfg(test)]
+pub(crate) struct FakeServer {
+    _task: Task<()>,
+}
+
+#[cfg(test)]
+impl FakeServer {
+    pub(crate) fn new(
+        name: Arc<str>,
+        handler: FakeHandler,
+        executor: &BackgroundExecutor,
+    ) -> (Client, Self) {
+        let (stdin_writer, stdin_reader) = async_pipe::pipe();
+        let (mut stdout_writer, stdout_reader) = async_pipe::pipe();
+        let client = Client::new_internal(
+            name,
+            stdin_writer,
+            stdout_reader,
+            futures::io::empty(),
+            futures::future::ready("the server exited".to_string()),
+            executor,
+        );
+
+        let task = executor.spawn(async move {
+            let mut stdin = BufReader::new(stdin_reader);
+            let mut buffer = Vec::new();
+            while lsp::read_message(&mut stdin, &mut buffer).await.is_ok() {
+                let message: Value = serde_json::from_slice(&buffer).unwrap();
+                let (Some(id), Some(method)) = (message.get("id"), message["method"].as_str())
+                else {
+                    continue;
+                };
+                let response = match handler(method, message["params"].clone()) {
+                    Ok(result) => serde_json::json!({
+                        "jsonrpc": JSON_RPC_VERSION,
+                        "id": id,
+                        "result": result,
+                    }),
+                    Err(message) => serde_json::json!({
+                        "jsonrpc": JSON_RPC_VERSION,
+                        "id": id,
+                        "error": { "code": -32000, "message": message },
+                    }),
+                };
+                if lsp::write_message(&mut stdout_writer, &response.to_string())
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
+        (client, Self { _task: task })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use serde_json::json;
+
+    #[gpui::test]
+    async fn test_client_requests(cx: &mut TestAppContext) {
+        let (client, server) = FakeServer::new(
+            "test".into(),
+            Arc::new(|method: &str, params: Value| match method {
+                "echo" => Ok(params),
+                _ => Err(format!("unknown method {method}")),
+            }),
+            &cx.executor(),
+        );
+
+        let response: Value = client.request("echo", json!({ "a": 1 })).await.unwrap();
+        assert_eq!(response, json!({ "a": 1 }));
+        let error = client
+            .request::<Value>("other", json!({}))
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "other failed: unknown method other");
+
+        // Requests fail once the server has stopped.
+        let stopped = client.stopped();
+        drop(server);
+        assert_eq!(stopped.await, "the server exited");
+        assert!(client.request::<Value>("echo", json!({})).await.is_err());
+    }
+}
@@ -0,0 +1,60 @@
+//! Context servers are local processes that provide tools, resources and prompts
+//! to the assistant, speaking JSON-RPC over stdio.
+
+mod client;
+mod manager;
+mod protocol;
+pub mod types;
+
+use anyhow::Result;
+use collections::HashMap;
+use gpui::AppContext;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+use std::{path::PathBuf, sync::Arc};
+
+pub use client::{Client, ContextServerBinary};
+pub use manager::{ContextServer, ContextServerManager, ContextServerStatus, Event};
+pub use protocol::ModelContextProtocol;
+
+pub fn init(cx: &mut AppContext) {
+    ContextServerSettings::register(cx);
+    ContextServerManager::init_global(cx);
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq, JsonSchema)]
+pub struct ContextServerSettings {
+    /// The context servers to start, keyed by a name of your choosing.
+    #[serde(default)]
+    pub context_servers: HashMap<Arc<str>, ServerConfig>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default, Clone, PartialEq, Eq, JsonSchema)]
+pub struct ServerConfig {
+    /// The path of the executable to run.
+    pub command: PathBuf,
+    /// The arguments to pass to the executable.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Environment variables to set for the process.
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Whether to attach all of the server's resources to every message sent to the assistant.
+    ///
+    /// Default: false
+    #[serde(default)]
+    pub attach_resources: bool,
+}
+
+impl Settings for ContextServerSettings {
+    const KEY: Option<&'static str> = None;
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _cx: &mut AppContext) -> Result<Self> {
+        SettingsSources::<Self::FileContent>::json_merge_with(
+            [sources.default].into_iter().chain(sources.user),
+        )
+    }
+}
@@ -0,0 +1,380 @@
+use crate::{
+    client::{Client, ContextServerBinary},
+    protocol::ModelContextProtocol,
+    types, ContextServerSettings, ServerConfig,
+};
+use collections::BTreeMap;
+use gpui::{
+    AppContext, Context as _, EventEmitter, Global, Model, ModelContext, SharedString, Task,
+};
+use release_channel::AppVersion;
+use settings::{Settings, SettingsStore};
+use std::sync::Arc;
+
+struct GlobalContextServerManager(Model<ContextServerManager>);
+
+impl Global for GlobalContextServerManager {}
+
+/// Starts the context servers configured in the settings, and restarts or
+/// stops them when their configuration changes.
+pub struct ContextServerManager {
+    servers: BTreeMap<Arc<str>, ContextServer>,
+    #[cfg(test)]
+    fake_servers: collections::HashMap<
+        std::path::PathBuf,
+        (
+            crate::client::FakeHandler,
+            futures::channel::mpsc::UnboundedSender<crate::client::FakeServer>,
+        ),
+    >,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ContextServerStatus {
+    Starting,
+    Running,
+    Failed(SharedString),
+}
+
+pub struct ContextServer {
+    pub id: Arc<str>,
+    pub config: ServerConfig,
+    pub status: ContextServerStatus,
+    pub tools: Vec<types::Tool>,
+    pub resources: Vec<types::Resource>,
+    pub prompts: Vec<types::Prompt>,
+    protocol: Option<Arc<ModelContextProtocol>>,
+    _start_task: Task<()>,
+}
+
+impl ContextServer {
+    /// Returns the connection to the server, once it is running.
+    pub fn protocol(&self) -> Option<Arc<ModelContextProtocol>> {
+        self.protocol.clone()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Event {
+    /// The server finished starting, and its tools, resources and prompts are available.
+    ServerStarted { server_id: Arc<str> },
+    /// The server was stopped, so its tools, resources and prompts are no longer available.
+    ServerStopped { server_id: Arc<str> },
+}
+
+impl EventEmitter<Event> for ContextServerManager {}
+
+impl ContextServerManager {
+    pub(crate) fn init_global(cx: &mut AppContext) {
+        let manager = cx.new_model(|cx| {
+            cx.observe_global::<SettingsStore>(|this: &mut Self, cx| this.sync_servers(cx))
+                .detach();
+            let mut this = Self {
+                servers: BTreeMap::default(),
+                #[cfg(test)]
+                fake_servers: Default::default(),
+            };
+            this.sync_servers(cx);
+            this
+        });
+        cx.set_global(GlobalContextServerManager(manager));
+    }
+
+    pub fn global(cx: &AppContext) -> Model<Self> {
+        cx.global::<GlobalContextServerManager>().0.clone()
+    }
+
+    pub fn try_global(cx: &AppContext) -> Option<Model<Self>> {
+        cx.try_global::<GlobalContextServerManager>()
+            .map(|manager| manager.0.clone())
+    }
+
+    pub fn servers(&self) -> impl Iterator<Item = &ContextServer> {
+        self.servers.values()
+    }
+
+    pub fn server(&self, server_id: &str) -> Option<&ContextServer> {
+        self.servers.get(server_id)
+    }
+
+    /// Runs servers whose command is the given one in this process, answering their requests
+    /// with the handler. The servers are sent to the returned receiver as they're started.
+    #[cfg(test)]
+    pub(crate) fn register_fake_server(
+        &mut self,
+        command: impl Into<std::path::PathBuf>,
+        handler: crate::client::FakeHandler,
+    ) -> futures::channel::mpsc::UnboundedReceiver<crate::client::FakeServer> {
+        let (servers_tx, servers_rx) = futures::channel::mpsc::unbounded();
+        self.fake_servers
+            .insert(command.into(), (handler, servers_tx));
+        servers_rx
+    }
+
+    /// Stops the server, if it is running, and starts it again.
+    pub fn restart_server(&mut self, server_id: &str, cx: &mut ModelContext<Self>) {
+        if let Some(server) = self.servers.remove(server_id) {
+            if server.protocol.is_some() {
+                cx.emit(Event::ServerStopped {
+                    server_id: server.id.clone(),
+                });
+            }
+            self.start_server(server.id, server.config, cx);
+            cx.notify();
+        }
+    }
+
+    fn sync_servers(&mut self, cx: &mut ModelContext<Self>) {
+        let configs = ContextServerSettings::get_global(cx)
+            .context_servers
+            .clone();
+
+        let stale_servers = self
+            .servers
+            .iter()
+            .filter(|(id, server)| configs.get(*id) != Some(&server.config))
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<_>>();
+        for server_id in stale_servers {
+            if let Some(server) = self.servers.remove(&server_id) {
+                log::info!("stopping context server {server_id}");
+                if server.protocol.is_some() {
+                    cx.emit(Event::ServerStopped { server_id });
+                }
+            }
+        }
+
+        for (server_id, config) in configs {
+            if !self.servers.contains_key(&server_id) {
+                self.start_server(server_id, config, cx);
+            }
+        }
+        cx.notify();
+    }
+
+    async fn initialize(
+        client: Client,
+        client_info: types::Implementation,
+    ) -> anyhow::Result<(
+        ModelContextProtocol,
+        Vec<types::Tool>,
+        Vec<types::Resource>,
+        Vec<types::Prompt>,
+    )> {
+        let protocol = ModelContextProtocol::initialize(client, client_info).await?;
+        let (tools, resources, prompts) = futures::try_join!(
+            protocol.list_tools(),
+            protocol.list_resources(),
+            protocol.list_prompts()
+        )?;
+        Ok((protocol, tools, resources, prompts))
+    }
+
+    fn start_server(&mut self, id: Arc<str>, config: ServerConfig, cx: &mut ModelContext<Self>) {
+        let client_info = types::Implementation {
+            name: "Zed".to_string(),
+            version: AppVersion::global(cx).to_string(),
+        };
+        let binary = ContextServerBinary {
+            executable: config.command.clone(),
+            args: config.args.clone(),
+            env: Some(config.env.clone()),
+        };
+
+        #[cfg(test)]
+        let fake_server = self
+            .fake_servers
+            .get(&config.command)
+            .map(|(handler, servers_tx)| (handler.clone(), servers_tx.clone()));
+
+        // The task is dropped, stopping the server, when the server is removed. Once the server
+        // is running, the task waits for it to stop.
+        let start_task = cx.spawn({
+            let id = id.clone();
+            |this, mut cx| async move {
+                let started = async {
+                    #[cfg(test)]
+                    if let Some((handler, servers_tx)) = fake_server {
+                        let (client, server) = crate::client::FakeServer::new(
+                            id.clone(),
+                            handler,
+                            cx.background_executor(),
+                        );
+                        servers_tx.unbounded_send(server).ok();
+                        return Self::initialize(client, client_info).await;
+                    }
+
+                    let client = Client::new(id.clone(), binary, cx.clone())?;
+                    Self::initialize(client, client_info).await
+                }
+                .await;
+                let stopped = started
+                    .as_ref()
+                    .ok()
+                    .map(|(protocol, ..)| protocol.stopped());
+
+                this.update(&mut cx, |this, cx| {
+                    let Some(server) = this.servers.get_mut(&id) else {
+                        return;
+                    };
+                    match started {
+                        Ok((protocol, tools, resources, prompts)) => {
+                            log::info!(
+                                "started context server {id} with {} tools, {} resources and {} prompts",
+                                tools.len(),
+                                resources.len(),
+                                prompts.len()
+                            );
+                            server.protocol = Some(Arc::new(protocol));
+                            server.tools = tools;
+                            server.resources = resources;
+                            server.prompts = prompts;
+                            server.status = ContextServerStatus::Running;
+                            cx.emit(Event::ServerStarted {
+                                server_id: id.clone(),
+                            });
+                        }
+                        Err(error) => {
+                            log::error!("failed to start context server {id}: {error:?}");
+                            server.status = ContextServerStatus::Failed(error.to_string().into());
+                        }
+                    }
+                    cx.notify();
+                })
+                .ok();
+
+                let Some(stopped) = stopped else {
+                    return;
+                };
+                let reason = stopped.await;
+                this.update(&mut cx, |this, cx| {
+                    let Some(server) = this.servers.get_mut(&id) else {
+                        return;
+                    };
+                    log::error!("context server {id} stopped: {reason}");
+                    server.protocol = None;
+                    server.tools.clear();
+                    server.resources.clear();
+                    server.prompts.clear();
+                    server.status = ContextServerStatus::Failed(reason.into());
+                    cx.emit(Event::ServerStopped { server_id: id });
+                    cx.notify();
+                })
+                .ok();
+            }
+        });
+
+        self.servers.insert(
+            id.clone(),
+            ContextServer {
+                id,
+                config,
+                status: ContextServerStatus::Starting,
+                tools: Vec::new(),
+                resources: Vec::new(),
+                prompts: Vec::new(),
+                protocol: None,
+                _start_task: start_task,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt as _;
+    use gpui::{BorrowAppContext as _, TestAppContext};
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    #[gpui::test]
+    async fn test_server_lifecycle(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            cx.set_global(SettingsStore::test(cx));
+            release_channel::init("0.0.0", cx);
+            crate::init(cx);
+        });
+        let manager = cx.update(ContextServerManager::global);
+        let mut fake_servers = manager.update(cx, |manager, _| {
+            manager.register_fake_server(
+                "fake-server",
+                Arc::new(|method: &str, _: serde_json::Value| match method {
+                    "initialize" => Ok(json!({
+                        "protocolVersion": types::PROTOCOL_VERSION,
+                        "capabilities": { "tools": {} },
+                    })),
+                    "tools/list" => Ok(json!({
+                        "tools": [{ "name": "search", "inputSchema": { "type": "object" } }]
+                    })),
+                    _ => Err(format!("unknown method {method}")),
+                }),
+            )
+        });
+        let events = Arc::new(Mutex::new(Vec::new()));
+        cx.update(|cx| {
+            let events = events.clone();
+            cx.subscribe(&manager, move |_, event, _| {
+                events.lock().unwrap().push(event.clone())
+            })
+            .detach();
+        });
+
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store
+                    .set_user_settings(
+                        r#"{ "context_servers": { "fake": { "command": "fake-server" } } }"#,
+                        cx,
+                    )
+                    .unwrap();
+            })
+        });
+        let fake_server = fake_servers.next().await.unwrap();
+        cx.run_until_parked();
+        manager.read_with(cx, |manager, _| {
+            let server = manager.server("fake").unwrap();
+            assert_eq!(server.status, ContextServerStatus::Running);
+            assert!(server.protocol().is_some());
+            assert_eq!(
+                server
+                    .tools
+                    .iter()
+                    .map(|tool| tool.name.as_str())
+                    .collect::<Vec<_>>(),
+                ["search"]
+            );
+        });
+        assert!(matches!(
+            events.lock().unwrap().as_slice(),
+            [Event::ServerStarted { server_id }] if server_id.as_ref() == "fake"
+        ));
+
+        // When the server stops, its tools are removed and its failure is shown.
+        drop(fake_server);
+        cx.run_until_parked();
+        manager.read_with(cx, |manager, _| {
+            let server = manager.server("fake").unwrap();
+            assert_eq!(
+                server.status,
+                ContextServerStatus::Failed("the server exited".into())
+            );
+            assert!(server.protocol().is_none());
+            assert!(server.tools.is_empty());
+        });
+        assert!(matches!(
+            events.lock().unwrap().as_slice(),
+            [Event::ServerStarted { .. }, Event::ServerStopped { server_id }]
+                if server_id.as_ref() == "fake"
+        ));
+
+        // Restarting the server starts a new process.
+        manager.update(cx, |manager, cx| manager.restart_server("fake", cx));
+        let _fake_server = fake_servers.next().await.unwrap();
+        cx.run_until_parked();
+        manager.read_with(cx, |manager, _| {
+            let server = manager.server("fake").unwrap();
+            assert_eq!(server.status, ContextServerStatus::Running);
+        });
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{
+    client::{no_params, Client},
+    types,
+};
+use anyhow::{anyhow, Result};
+use collections::HashMap;
+use futures::Future;
+
+/// A context server that has completed the initialization handshake.
+#[derive(Debug)]
+pub struct ModelContextProtocol {
+    client: Client,
+    initialize: types::InitializeResponse,
+}
+
+impl ModelContextProtocol {
+    /// Performs the initialization handshake, after which the server accepts other requests.
+    pub async fn initialize(client: Client, client_info: types::Implementation) -> Result<Self> {
+        let initialize: types::InitializeResponse = client
+            .request(
+                "initialize",
+                types::InitializeParams {
+                    protocol_version: types::PROTOCOL_VERSION,
+                    capabilities: types::ClientCapabilities::default(),
+                    client_info,
+                },
+            )
+            .await?;
+        if initialize.protocol_version != types::PROTOCOL_VERSION {
+            log::warn!(
+                "context server {} uses protocol version {}, expected {}",
+                client.name(),
+                initialize.protocol_version,
+                types::PROTOCOL_VERSION
+            );
+        }
+        client.notify("notifications/initialized", no_params())?;
+
+        Ok(Self { client, initialize })
+    }
+
+    /// Resolves with the reason the server stopped, once it has.
+    pub fn stopped(&self) -> impl 'static + Future<Output = String> {
+        self.client.stopped()
+    }
+
+    pub fn server_info(&self) -> Option<&types::Implementation> {
+        self.initialize.server_info.as_ref()
+    }
+
+    pub fn supports_tools(&self) -> bool {
+        self.initialize.capabilities.tools.is_some()
+    }
+
+    pub fn supports_resources(&self) -> bool {
+        self.initialize.capabilities.resources.is_some()
+    }
+
+    pub fn supports_prompts(&self) -> bool {
+        self.initialize.capabilities.prompts.is_some()
+    }
+
+    pub async fn list_tools(&self) -> Result<Vec<types::Tool>> {
+        if !self.supports_tools() {
+            return Ok(Vec::new());
+        }
+        let response: types::ListToolsResponse =
+            self.client.request("tools/list", no_params()).await?;
+        Ok(response.tools)
+    }
+
+    /// Calls a tool, returning its output as text.
+    pub async fn call_tool(&self, name: &str, arguments: serde_json::Value) -> Result<String> {
+        let response: types::CallToolResponse = self
+            .client
+            .request("tools/call", types::CallToolParams { name, arguments })
+            .await?;
+        let output = response
+            .content
+            .iter()
+            .map(types::Content::to_text)
+            .collect::<Vec<_>>()
+            .join("\n");
+        if response.is_error {
+            Err(anyhow!("{output}"))
+        } else {
+            Ok(output)
+        }
+    }
+
+    pub async fn list_resources(&self) -> Result<Vec<types::Resource>> {
+        if !self.supports_resources() {
+            return Ok(Vec::new());
+        }
+        let response: types::ListResourcesResponse =
+            self.client.request("resources/list", no_params()).await?;
+        Ok(response.resources)
+    }
+
+    pub async fn read_resource(&self, uri: &str) -> Result<Vec<types::ResourceContents>> {
+        let response: types::ReadResourceResponse = self
+            .client
+            .request("resources/read", types::ReadResourceParams { uri })
+            .await?;
+        Ok(response.contents)
+    }
+
+    pub async fn list_prompts(&self) -> Result<Vec<types::Prompt>> {
+        if !self.supports_prompts() {
+            return Ok(Vec::new());
+        }
+        let response: types::ListPromptsResponse =
+            self.client.request("prompts/list", no_params()).await?;
+        Ok(response.prompts)
+    }
+
+    pub async fn get_prompt(
+        &self,
+        name: &str,
+        arguments: HashMap<String, String>,
+    ) -> Result<types::GetPromptResponse> {
+        self.client
+            .request("prompts/get", types::GetPromptParams { name, arguments })
+            .await
+    }
+}
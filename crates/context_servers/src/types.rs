@@ -0,0 +1,247 @@
+//! The messages exchanged with context servers, which follow the Model Context Protocol.
+
+use collections::HashMap;
+use serde::{Deserialize, Serialize};
+
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InitializeParams {
+    pub protocol_version: &'static str,
+    pub capabilities: ClientCapabilities,
+    pub client_info: Implementation,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ClientCapabilities {}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Implementation {
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InitializeResponse {
+    pub protocol_version: String,
+    #[serde(default)]
+    pub capabilities: ServerCapabilities,
+    pub server_info: Option<Implementation>,
+}
+
+/// The features a server offers. Each is present if the server supports it.
+#[derive(Debug, Default, Deserialize)]
+pub struct ServerCapabilities {
+    pub tools: Option<serde_json::Value>,
+    pub resources: Option<serde_json::Value>,
+    pub prompts: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tool {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// A JSON schema for the tool's arguments.
+    pub input_schema: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListToolsResponse {
+    pub tools: Vec<Tool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CallToolParams<'a> {
+    pub name: &'a str,
+    pub arguments: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallToolResponse {
+    pub content: Vec<Content>,
+    #[serde(default)]
+    pub is_error: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Content {
+    Text {
+        text: String,
+    },
+    Image {
+        data: String,
+        #[serde(rename = "mimeType")]
+        mime_type: String,
+    },
+    Resource {
+        resource: ResourceContents,
+    },
+}
+
+impl Content {
+    /// Returns the content as text, as images can't be forwarded to the model.
+    pub fn to_text(&self) -> String {
+        match self {
+            Content::Text { text } => text.clone(),
+            Content::Image { mime_type, .. } => format!("[{mime_type} image]"),
+            Content::Resource { resource } => resource.to_text(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Resource {
+    pub uri: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub mime_type: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListResourcesResponse {
+    pub resources: Vec<Resource>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadResourceParams<'a> {
+    pub uri: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReadResourceResponse {
+    pub contents: Vec<ResourceContents>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceContents {
+    pub uri: String,
+    #[serde(default)]
+    pub mime_type: Option<String>,
+    #[serde(default)]
+    pub text: Option<String>,
+    /// Base64-encoded binary contents.
+    #[serde(default)]
+    pub blob: Option<String>,
+}
+
+impl ResourceContents {
+    pub fn to_text(&self) -> String {
+        match &self.text {
+            Some(text) => text.clone(),
+            None => format!("[binary contents of {}]", self.uri),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Prompt {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub arguments: Vec<PromptArgument>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptArgument {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListPromptsResponse {
+    pub prompts: Vec<Prompt>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GetPromptParams<'a> {
+    pub name: &'a str,
+    pub arguments: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GetPromptResponse {
+    #[serde(default)]
+    pub description: Option<String>,
+    pub messages: Vec<PromptMessage>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PromptMessage {
+    pub role: Role,
+    pub content: Content,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    User,
+    Assistant,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_deserialize_call_tool_response() {
+        let response: CallToolResponse = serde_json::from_value(json!({
+            "content": [
+                { "type": "text", "text": "3 rows" },
+                { "type": "image", "data": "aGk=", "mimeType": "image/png" },
+                {
+                    "type": "resource",
+                    "resource": { "uri": "postgres://users/schema", "text": "id integer" }
+                }
+            ],
+            "isError": true
+        }))
+        .unwrap();
+        assert!(response.is_error);
+        assert_eq!(
+            response
+                .content
+                .iter()
+                .map(Content::to_text)
+                .collect::<Vec<_>>(),
+            ["3 rows", "[image/png image]", "id integer"]
+        );
+    }
+
+    #[test]
+    fn test_deserialize_prompts() {
+        let response: ListPromptsResponse = serde_json::from_value(json!({
+            "prompts": [
+                { "name": "explain-table", "arguments": [{ "name": "table", "required": true }] },
+                { "name": "summarize-logs" }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(response.prompts.len(), 2);
+        assert!(response.prompts[0].arguments[0].required);
+        assert!(response.prompts[1].arguments.is_empty());
+
+        let response: GetPromptResponse = serde_json::from_value(json!({
+            "messages": [
+                { "role": "user", "content": { "type": "text", "text": "Explain users" } }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(response.messages[0].role, Role::User);
+        assert_eq!(response.messages[0].content.to_text(), "Explain users");
+    }
+}
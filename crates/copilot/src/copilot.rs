@@ -1255,7 +1255,11 @@ mod tests {
             self.abs_path.clone()
         }
 
-        fn load(&self, _: &AppContext) -> Task<Result<String>> {
+        fn load(
+            &self,
+            _: language::Encoding,
+            _: &AppContext,
+        ) -> Task<Result<(String, language::Encoding)>> {
             unimplemented!()
         }
 
@@ -623,6 +623,15 @@ impl Item for Editor {
 
             Some(util::truncate_and_trailoff(&description, MAX_TAB_TITLE_LEN))
         });
+        let loading_status = self.buffer().read(cx).as_singleton().and_then(|buffer| {
+            let buffer = buffer.read(cx);
+            let percentage = buffer.loading_progress()? * 100.;
+            Some(if buffer.loading_error().is_some() {
+                (format!("Failed at {percentage:.0}%"), Color::Error)
+            } else {
+                (format!("{percentage:.0}%"), Color::Muted)
+            })
+        });
 
         h_flex()
             .gap_2()
@@ -638,6 +647,9 @@ impl Item for Editor {
                         .color(Color::Muted),
                 )
             })
+            .when_some(loading_status, |this, (status, color)| {
+                this.child(Label::new(status).size(LabelSize::XSmall).color(color))
+            })
             .into_any_element()
     }
 
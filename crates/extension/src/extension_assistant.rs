@@ -0,0 +1,235 @@
+use crate::{
+    extension_manifest::{AssistantToolManifestEntry, SlashCommandManifestEntry},
+    wasm_host::WasmExtension,
+};
+use anyhow::{anyhow, Context as _, Result};
+use assistant_tooling::{LanguageModelTool, ProjectContext, ToolFunctionDefinition, ToolView};
+use futures::FutureExt as _;
+use gpui::{AppContext, Model, Task, View};
+use language::LspAdapterDelegate;
+use project::Project;
+use schemars::schema_for;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use ui::prelude::*;
+use util::ResultExt as _;
+use wasmtime_wasi::WasiView as _;
+
+/// An assistant tool declared in an extension's manifest and implemented in its Wasm.
+pub struct ExtensionAssistantTool {
+    pub(crate) extension: WasmExtension,
+    pub(crate) name: Arc<str>,
+    pub(crate) entry: AssistantToolManifestEntry,
+    pub(crate) project: Model<Project>,
+}
+
+impl LanguageModelTool for ExtensionAssistantTool {
+    type View = ExtensionToolView;
+
+    fn name(&self) -> String {
+        self.name.to_string()
+    }
+
+    fn description(&self) -> String {
+        self.entry.description.clone()
+    }
+
+    fn definition(&self) -> ToolFunctionDefinition {
+        let parameters = serde_json::from_value(self.entry.input_schema.clone())
+            .with_context(|| format!("invalid input schema for assistant tool {}", self.name))
+            .log_err()
+            .unwrap_or_else(|| schema_for!(serde_json::Value));
+        ToolFunctionDefinition {
+            name: self.name(),
+            description: self.description(),
+            parameters,
+        }
+    }
+
+    fn view(&self, cx: &mut WindowContext) -> View<Self::View> {
+        cx.new_view(|_cx| ExtensionToolView {
+            extension: self.extension.clone(),
+            tool_name: self.name.clone(),
+            project: self.project.clone(),
+            input: serde_json::Value::Null,
+            output: None,
+        })
+    }
+}
+
+pub struct ExtensionToolView {
+    extension: WasmExtension,
+    tool_name: Arc<str>,
+    project: Model<Project>,
+    input: serde_json::Value,
+    output: Option<Result<String>>,
+}
+
+impl Render for ExtensionToolView {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let status = match &self.output {
+            None => Label::new("Running…").color(Color::Muted),
+            Some(Ok(_)) => Label::new("Done").color(Color::Muted),
+            Some(Err(error)) => Label::new(format!("Failed: {error}")).color(Color::Error),
+        };
+        h_flex()
+            .gap_2()
+            .child(Icon::new(IconName::Bolt))
+            .child(Label::new(self.tool_name.to_string()))
+            .child(Label::new(self.extension.manifest.name.clone()).color(Color::Muted))
+            .child(status)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SerializedExtensionToolOutput {
+    output: Option<String>,
+    error_message: Option<String>,
+}
+
+impl ToolView for ExtensionToolView {
+    type Input = serde_json::Value;
+    type SerializedState = SerializedExtensionToolOutput;
+
+    fn generate(&self, _: &mut ProjectContext, _: &mut ViewContext<Self>) -> String {
+        match &self.output {
+            None => "The tool did not run".to_string(),
+            Some(Ok(output)) => output.clone(),
+            Some(Err(error)) => format!("Failed to run tool: {error:?}"),
+        }
+    }
+
+    fn set_input(&mut self, input: Self::Input, cx: &mut ViewContext<Self>) {
+        self.input = input;
+        cx.notify();
+    }
+
+    fn execute(&mut self, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        let extension = self.extension.clone();
+        let tool_name = self.tool_name.clone();
+        let input = self.input.to_string();
+        let delegate = worktree_delegate(&self.project, cx);
+        cx.spawn(|this, mut cx| async move {
+            let output = async {
+                let delegate = delegate?;
+                extension
+                    .call(move |extension, store| {
+                        async move {
+                            let resource = store.data_mut().table().push(delegate)?;
+                            extension
+                                .call_run_assistant_tool(store, &tool_name, &input, resource)
+                                .await?
+                                .map_err(|error| anyhow!("{error}"))
+                        }
+                        .boxed()
+                    })
+                    .await
+            }
+            .await;
+            this.update(&mut cx, |this, cx| {
+                this.output = Some(output);
+                cx.notify();
+            })
+        })
+    }
+
+    fn serialize(&self, _cx: &mut ViewContext<Self>) -> Self::SerializedState {
+        SerializedExtensionToolOutput {
+            output: self
+                .output
+                .as_ref()
+                .and_then(|output| output.as_ref().ok().cloned()),
+            error_message: self
+                .output
+                .as_ref()
+                .and_then(|output| output.as_ref().err())
+                .map(|error| error.to_string()),
+        }
+    }
+
+    fn deserialize(
+        &mut self,
+        output: Self::SerializedState,
+        cx: &mut ViewContext<Self>,
+    ) -> Result<()> {
+        self.output = match (output.output, output.error_message) {
+            (_, Some(error_message)) => Some(Err(anyhow!("{}", error_message))),
+            (Some(output), None) => Some(Ok(output)),
+            (None, None) => None,
+        };
+        cx.notify();
+        Ok(())
+    }
+}
+
+/// A slash command declared in an extension's manifest and implemented in its Wasm.
+#[derive(Clone)]
+pub struct ExtensionSlashCommand {
+    pub(crate) extension: WasmExtension,
+    pub(crate) name: Arc<str>,
+    pub(crate) entry: SlashCommandManifestEntry,
+}
+
+impl ExtensionSlashCommand {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn description(&self) -> &str {
+        &self.entry.description
+    }
+
+    pub fn requires_argument(&self) -> bool {
+        self.entry.requires_argument
+    }
+
+    /// Runs the command against the project's first worktree, returning the
+    /// text to insert into the user's message.
+    pub fn run(
+        &self,
+        argument: Option<String>,
+        project: &Model<Project>,
+        cx: &mut AppContext,
+    ) -> Task<Result<String>> {
+        if self.requires_argument() && argument.is_none() {
+            return Task::ready(Err(anyhow!("/{} requires an argument", self.name)));
+        }
+
+        let extension = self.extension.clone();
+        let command_name = self.name.clone();
+        let delegate = worktree_delegate(project, cx);
+        cx.background_executor().spawn(async move {
+            let delegate = delegate?;
+            extension
+                .call(move |extension, store| {
+                    async move {
+                        let resource = store.data_mut().table().push(delegate)?;
+                        extension
+                            .call_run_slash_command(
+                                store,
+                                &command_name,
+                                argument.as_deref(),
+                                resource,
+                            )
+                            .await?
+                            .map_err(|error| anyhow!("{error}"))
+                    }
+                    .boxed()
+                })
+                .await
+        })
+    }
+}
+
+fn worktree_delegate(
+    project: &Model<Project>,
+    cx: &mut AppContext,
+) -> Result<Arc<dyn LspAdapterDelegate>> {
+    project.update(cx, |project, cx| {
+        let worktree = project
+            .visible_worktrees(cx)
+            .next()
+            .ok_or_else(|| anyhow!("no worktree is open"))?;
+        Ok(project.lsp_adapter_delegate(&worktree, cx))
+    })
+}
@@ -74,6 +74,10 @@ pub struct ExtensionManifest {
     pub grammars: BTreeMap<Arc<str>, GrammarManifestEntry>,
     #[serde(default)]
     pub language_servers: BTreeMap<LanguageServerName, LanguageServerManifestEntry>,
+    #[serde(default)]
+    pub assistant_tools: BTreeMap<Arc<str>, AssistantToolManifestEntry>,
+    #[serde(default)]
+    pub slash_commands: BTreeMap<Arc<str>, SlashCommandManifestEntry>,
 }
 
 #[derive(Clone, Default, PartialEq, Eq, Debug, Deserialize, Serialize)]
@@ -110,6 +114,26 @@ pub struct LanguageServerManifestEntry {
     pub code_action_kinds: Option<Vec<lsp::CodeActionKind>>,
 }
 
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct AssistantToolManifestEntry {
+    /// The description shown to the language model, so it knows when to use the tool.
+    pub description: String,
+    /// A JSON schema for the tool's input.
+    #[serde(default = "default_tool_input_schema")]
+    pub input_schema: serde_json::Value,
+}
+
+fn default_tool_input_schema() -> serde_json::Value {
+    serde_json::json!({ "type": "object", "properties": {} })
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct SlashCommandManifestEntry {
+    pub description: String,
+    #[serde(default)]
+    pub requires_argument: bool,
+}
+
 impl LanguageServerManifestEntry {
     /// Returns the list of languages for the language server.
     ///
@@ -190,5 +214,72 @@ fn manifest_from_old_manifest(
             .map(|grammar_name| (grammar_name, Default::default()))
             .collect(),
         language_servers: Default::default(),
+        assistant_tools: Default::default(),
+        slash_commands: Default::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_assistant_tools_and_slash_commands() {
+        let manifest: ExtensionManifest = toml::from_str(
+            r#"
+            id = "test-extension"
+            name = "Test Extension"
+            version = "0.1.0"
+            schema_version = 1
+
+            [assistant_tools.search_issues]
+            description = "Searches the project's issues."
+            input_schema = { type = "object", properties = { query = { type = "string" } } }
+
+            [assistant_tools.list_issues]
+            description = "Lists the open issues."
+
+            [slash_commands.issue]
+            description = "Inserts an issue."
+            requires_argument = true
+
+            [slash_commands.issues]
+            description = "Inserts the open issues."
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            manifest.assistant_tools.get("search_issues"),
+            Some(&AssistantToolManifestEntry {
+                description: "Searches the project's issues.".into(),
+                input_schema: json!({
+                    "type": "object",
+                    "properties": { "query": { "type": "string" } }
+                }),
+            })
+        );
+        assert_eq!(
+            manifest.assistant_tools.get("list_issues"),
+            Some(&AssistantToolManifestEntry {
+                description: "Lists the open issues.".into(),
+                input_schema: json!({ "type": "object", "properties": {} }),
+            })
+        );
+        assert_eq!(
+            manifest.slash_commands.get("issue"),
+            Some(&SlashCommandManifestEntry {
+                description: "Inserts an issue.".into(),
+                requires_argument: true,
+            })
+        );
+        assert_eq!(
+            manifest.slash_commands.get("issues"),
+            Some(&SlashCommandManifestEntry {
+                description: "Inserts the open issues.".into(),
+                requires_argument: false,
+            })
+        );
     }
 }
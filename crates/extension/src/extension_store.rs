@@ -1,3 +1,4 @@
+mod extension_assistant;
 pub mod extension_builder;
 mod extension_lsp_adapter;
 mod extension_manifest;
@@ -34,6 +35,7 @@ use language::{
     QUERY_FILENAME_PREFIXES,
 };
 use node_runtime::NodeRuntime;
+use project::Project;
 use semantic_version::SemanticVersion;
 use serde::{Deserialize, Serialize};
 use settings::Settings;
@@ -53,8 +55,10 @@ use wasm_host::{
     WasmExtension, WasmHost,
 };
 
+pub use extension_assistant::{ExtensionAssistantTool, ExtensionSlashCommand, ExtensionToolView};
 pub use extension_manifest::{
-    ExtensionLibraryKind, ExtensionManifest, GrammarManifestEntry, OldExtensionManifest,
+    AssistantToolManifestEntry, ExtensionLibraryKind, ExtensionManifest, GrammarManifestEntry,
+    OldExtensionManifest, SlashCommandManifestEntry,
 };
 pub use extension_settings::ExtensionSettings;
 
@@ -409,6 +413,41 @@ impl ExtensionStore {
             .filter_map(|(name, theme)| theme.extension.as_ref().eq(extension_id).then_some(name))
     }
 
+    /// Returns the assistant tools provided by loaded extensions, bound to the given project.
+    pub fn assistant_tools(&self, project: &Model<Project>) -> Vec<ExtensionAssistantTool> {
+        self.wasm_extensions
+            .iter()
+            .flat_map(|(manifest, extension)| {
+                manifest
+                    .assistant_tools
+                    .iter()
+                    .map(|(name, entry)| ExtensionAssistantTool {
+                        extension: extension.clone(),
+                        name: name.clone(),
+                        entry: entry.clone(),
+                        project: project.clone(),
+                    })
+            })
+            .collect()
+    }
+
+    /// Returns the slash commands provided by loaded extensions.
+    pub fn slash_commands(&self) -> Vec<ExtensionSlashCommand> {
+        self.wasm_extensions
+            .iter()
+            .flat_map(|(manifest, extension)| {
+                manifest
+                    .slash_commands
+                    .iter()
+                    .map(|(name, entry)| ExtensionSlashCommand {
+                        extension: extension.clone(),
+                        name: name.clone(),
+                        entry: entry.clone(),
+                    })
+            })
+            .collect()
+    }
+
     pub fn fetch_extensions(
         &self,
         search: Option<&str>,
@@ -103,6 +103,34 @@ pub trait Extension: Send + Sync {
     ) -> Option<CodeLabel> {
         None
     }
+
+    /// Runs the given assistant tool, returning the output to send back to the
+    /// language model.
+    ///
+    /// The tool must be declared under `assistant_tools` in the extension's
+    /// `extension.toml`, along with the JSON schema that `input` conforms to.
+    fn run_assistant_tool(
+        &mut self,
+        tool_name: &str,
+        _input: serde_json::Value,
+        _worktree: &Worktree,
+    ) -> Result<String> {
+        Err(format!("unknown assistant tool: {tool_name}"))
+    }
+
+    /// Runs the given slash command, returning the text to insert into the
+    /// user's message.
+    ///
+    /// The command must be declared under `slash_commands` in the extension's
+    /// `extension.toml`.
+    fn run_slash_command(
+        &mut self,
+        command_name: &str,
+        _argument: Option<String>,
+        _worktree: &Worktree,
+    ) -> Result<String> {
+        Err(format!("unknown slash command: {command_name}"))
+    }
 }
 
 /// Registers the provided type as a Zed extension.
@@ -140,7 +168,7 @@ pub static ZED_API_VERSION: [u8; 6] = *include_bytes!(concat!(env!("OUT_DIR"), "
 mod wit {
     wit_bindgen::generate!({
         skip: ["init-extension"],
-        path: "./wit/since_v0.0.6",
+        path: "./wit/since_v0.0.7",
     });
 }
 
@@ -208,6 +236,23 @@ impl wit::Guest for Component {
         }
         Ok(labels)
     }
+
+    fn run_assistant_tool(
+        tool_name: String,
+        input: String,
+        worktree: &Worktree,
+    ) -> Result<String, String> {
+        let input = serde_json::from_str(&input).map_err(|error| error.to_string())?;
+        extension().run_assistant_tool(&tool_name, input, worktree)
+    }
+
+    fn run_slash_command(
+        command_name: String,
+        argument: Option<String>,
+        worktree: &Worktree,
+    ) -> Result<String, String> {
+        extension().run_slash_command(&command_name, argument, worktree)
+    }
 }
 
 /// The ID of a language server.
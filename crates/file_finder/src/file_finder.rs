@@ -1,18 +1,28 @@
 #[cfg(test)]
 mod file_finder_tests;
 
+mod frecency;
 mod new_path_prompt;
+mod persistence;
 
 use collections::{HashMap, HashSet};
-use editor::{scroll::Autoscroll, Bias, Editor};
+use editor::{scroll::Autoscroll, Bias, Editor, EditorEvent};
+use frecency::Frecency;
+use futures::{
+    channel::mpsc,
+    future::{self, Either, Shared},
+    FutureExt as _, StreamExt as _,
+};
 use fuzzy::{CharBag, PathMatch, PathMatchCandidate};
 use gpui::{
-    actions, rems, Action, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView,
-    Model, Modifiers, ModifiersChangedEvent, ParentElement, Render, Styled, Task, View,
-    ViewContext, VisualContext, WeakView,
+    actions, rems, Action, AppContext, AsyncWindowContext, DismissEvent, EventEmitter, FocusHandle,
+    FocusableView, Model, Modifiers, ModifiersChangedEvent, ParentElement, Render, Styled, Task,
+    View, ViewContext, VisualContext, WeakView,
 };
 use itertools::Itertools;
+use language::OutlineItem;
 use new_path_prompt::NewPathPrompt;
+use persistence::FILE_FINDER_DB;
 use picker::{Picker, PickerDelegate};
 use project::{PathMatchCandidateSet, Project, ProjectPath, WorktreeId};
 use settings::Settings;
@@ -23,11 +33,12 @@ use std::{
         atomic::{self, AtomicBool},
         Arc,
     },
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 use text::Point;
 use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
 use util::{paths::PathLikeWithPosition, post_inc, ResultExt};
-use workspace::{item::PreviewTabsSettings, ModalView, Workspace};
+use workspace::{item::PreviewTabsSettings, ModalView, Workspace, WorkspaceId};
 
 actions!(file_finder, [Toggle, SelectPrev]);
 
@@ -44,7 +55,8 @@ pub fn init(cx: &mut AppContext) {
 }
 
 impl FileFinder {
-    fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+    fn register(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+        Self::record_opened_files(cx);
         workspace.register_action(|workspace, _: &Toggle, cx| {
             let Some(file_finder) = workspace.active_modal::<Self>(cx) else {
                 Self::open(workspace, cx);
@@ -60,6 +72,46 @@ impl FileFinder {
         });
     }
 
+    /// Records each file that becomes active in the workspace, for ranking files by frecency.
+    fn record_opened_files(cx: &mut ViewContext<Workspace>) {
+        let mut last_opened_path = None;
+        cx.subscribe(
+            &cx.view().clone(),
+            move |workspace, _, event: &workspace::Event, cx| {
+                if !matches!(event, workspace::Event::ActiveItemChanged) {
+                    return;
+                }
+                let Some(abs_path) = workspace
+                    .active_item(cx)
+                    .and_then(|item| item.project_path(cx))
+                    .and_then(|project_path| {
+                        workspace
+                            .project()
+                            .read(cx)
+                            .absolute_path(&project_path, cx)
+                    })
+                else {
+                    return;
+                };
+                if last_opened_path.as_ref() == Some(&abs_path) {
+                    return;
+                }
+                last_opened_path = Some(abs_path.clone());
+
+                let workspace_id = workspace.database_id();
+                cx.background_executor()
+                    .spawn(async move {
+                        FILE_FINDER_DB
+                            .record_open(workspace_id, abs_path, unix_timestamp())
+                            .await
+                            .log_err();
+                    })
+                    .detach();
+            },
+        )
+        .detach();
+    }
+
     fn open(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
         let project = workspace.project().read(cx);
 
@@ -85,10 +137,12 @@ impl FileFinder {
 
         let project = workspace.project().clone();
         let weak_workspace = cx.view().downgrade();
+        let workspace_id = workspace.database_id();
         workspace.toggle_modal(cx, |cx| {
             let delegate = FileFinderDelegate::new(
                 cx.view().downgrade(),
                 weak_workspace,
+                workspace_id,
                 project,
                 currently_opened_path,
                 history_items,
@@ -161,6 +215,49 @@ pub struct FileFinderDelegate {
     has_changed_selected_index: bool,
     cancel_flag: Arc<AtomicBool>,
     history_items: Vec<FoundPath>,
+    frecency: Arc<Frecency>,
+    unloaded_files: Option<Shared<Task<Arc<[UnloadedFileSet]>>>>,
+}
+
+/// Files within the directories that a lazily scanned worktree hasn't loaded yet, listed straight
+/// from the file system so that they can be found by name.
+struct UnloadedFileSet {
+    worktree_id: usize,
+    prefix: Arc<str>,
+    files: Vec<(Arc<Path>, CharBag)>,
+}
+
+impl<'a> fuzzy::PathMatchCandidateSet<'a> for UnloadedFileSet {
+    type Candidates = UnloadedFileCandidates<'a>;
+
+    fn id(&self) -> usize {
+        self.worktree_id
+    }
+
+    fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    fn prefix(&self) -> Arc<str> {
+        self.prefix.clone()
+    }
+
+    fn candidates(&'a self, start: usize) -> Self::Candidates {
+        UnloadedFileCandidates(self.files[start..].iter())
+    }
+}
+
+struct UnloadedFileCandidates<'a>(std::slice::Iter<'a, (Arc<Path>, CharBag)>);
+
+impl<'a> Iterator for UnloadedFileCandidates<'a> {
+    type Item = PathMatchCandidate<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(path, char_bag)| PathMatchCandidate {
+            path,
+            char_bag: *char_bag,
+        })
+    }
 }
 
 /// Use a custom ordering for file finder: the regular one
@@ -362,6 +459,104 @@ impl FoundPath {
 
 const MAX_RECENT_SELECTIONS: usize = 20;
 
+/// How long to wait for a file to be parsed before giving up on finding a symbol in it.
+const SYMBOL_PARSE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Splits a `symbol@file` query into the symbol and the file query. Queries where the part
+/// before the `@` isn't an identifier, like `@types/node`, are left as they are.
+fn split_symbol_query(query: &str) -> (Option<&str>, &str) {
+    match query.split_once('@') {
+        Some((symbol, file_query))
+            if !symbol.is_empty()
+                && !file_query.is_empty()
+                && symbol
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '_' || c == ':') =>
+        {
+            (Some(symbol), file_query)
+        }
+        _ => (None, query),
+    }
+}
+
+/// Moves the caret to the symbol, waiting for the file to be parsed if the symbol isn't in its
+/// outline yet.
+async fn jump_to_symbol(
+    editor: View<Editor>,
+    symbol: &str,
+    cx: &mut AsyncWindowContext,
+) -> Option<()> {
+    let (reparsed_tx, mut reparsed_rx) = mpsc::unbounded();
+    let _subscription = cx
+        .update(|cx| {
+            cx.subscribe(&editor, move |_, event: &EditorEvent, _| {
+                if matches!(event, EditorEvent::Reparsed) {
+                    reparsed_tx.unbounded_send(()).ok();
+                }
+            })
+        })
+        .ok()?;
+
+    loop {
+        let position = editor
+            .update(cx, |editor, cx| {
+                let outline = editor.buffer().read(cx).snapshot(cx).outline(None)?;
+                symbol_position(&outline.items, symbol)
+            })
+            .ok()?;
+        if let Some(position) = position {
+            return editor
+                .update(cx, |editor, cx| {
+                    editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                        s.select_ranges([position..position])
+                    });
+                })
+                .ok();
+        }
+
+        let timeout = cx.background_executor().timer(SYMBOL_PARSE_TIMEOUT);
+        match future::select(reparsed_rx.next(), timeout).await {
+            Either::Left((Some(()), _)) => {}
+            _ => return None,
+        }
+    }
+}
+
+/// Finds the start of the first outline item named `symbol`, which may be qualified with the names
+/// of the items that contain it, like `Editor::new`.
+fn symbol_position<T: Clone>(items: &[OutlineItem<T>], symbol: &str) -> Option<T> {
+    fn names<T>(item: &OutlineItem<T>) -> impl Iterator<Item = &str> {
+        item.name_ranges
+            .iter()
+            .filter_map(|range| item.text.get(range.clone()))
+    }
+
+    let mut segments = symbol.rsplit("::").filter(|segment| !segment.is_empty());
+    let name = segments.next()?;
+    let qualifiers = segments.collect::<Vec<_>>();
+    let mut ancestors = Vec::<&OutlineItem<T>>::new();
+    for item in items {
+        ancestors.truncate(item.depth);
+        if names(item).any(|item_name| item_name == name)
+            && qualifiers.iter().all(|qualifier| {
+                ancestors.iter().any(|ancestor| {
+                    names(ancestor).any(|ancestor_name| ancestor_name == *qualifier)
+                })
+            })
+        {
+            return Some(item.range.start.clone());
+        }
+        ancestors.push(item);
+    }
+    None
+}
+
+fn unix_timestamp() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64)
+}
+
 #[cfg(not(test))]
 fn history_file_exists(abs_path: &PathBuf) -> bool {
     abs_path.exists()
@@ -381,6 +576,8 @@ pub enum Event {
 struct FileSearchQuery {
     raw_query: String,
     file_query_end: Option<usize>,
+    /// The symbol to jump to in the opened file, given as `symbol@file`.
+    symbol: Option<String>,
 }
 
 impl FileSearchQuery {
@@ -396,12 +593,14 @@ impl FileFinderDelegate {
     fn new(
         file_finder: WeakView<FileFinder>,
         workspace: WeakView<Workspace>,
+        workspace_id: WorkspaceId,
         project: Model<Project>,
         currently_opened_path: Option<FoundPath>,
         history_items: Vec<FoundPath>,
         cx: &mut ViewContext<FileFinder>,
     ) -> Self {
         Self::subscribe_to_updates(&project, cx);
+        Self::load_frecency(workspace_id, cx);
         Self {
             file_finder,
             workspace,
@@ -416,17 +615,44 @@ impl FileFinderDelegate {
             selected_index: 0,
             cancel_flag: Arc::new(AtomicBool::new(false)),
             history_items,
+            frecency: Arc::default(),
+            unloaded_files: None,
         }
     }
 
+    fn load_frecency(workspace_id: WorkspaceId, cx: &mut ViewContext<FileFinder>) {
+        cx.spawn(|file_finder, mut cx| async move {
+            let opens = cx
+                .background_executor()
+                .spawn(async move { FILE_FINDER_DB.get_opens(workspace_id) })
+                .await
+                .log_err()
+                .filter(|opens| !opens.is_empty())?;
+            let frecency = Arc::new(Frecency::new(opens, unix_timestamp()));
+            file_finder
+                .update(&mut cx, |file_finder, cx| {
+                    file_finder.picker.update(cx, |picker, cx| {
+                        picker.delegate.frecency = frecency;
+                        picker.refresh(cx);
+                    })
+                })
+                .ok()
+        })
+        .detach();
+    }
+
     fn subscribe_to_updates(project: &Model<Project>, cx: &mut ViewContext<FileFinder>) {
         cx.subscribe(project, |file_finder, _, event, cx| {
             match event {
-                project::Event::WorktreeUpdatedEntries(_, _)
-                | project::Event::WorktreeAdded
-                | project::Event::WorktreeRemoved(_) => file_finder
+                project::Event::WorktreeUpdatedEntries(_, _) => file_finder
                     .picker
                     .update(cx, |picker, cx| picker.refresh(cx)),
+                project::Event::WorktreeAdded | project::Event::WorktreeRemoved(_) => {
+                    file_finder.picker.update(cx, |picker, cx| {
+                        picker.delegate.unloaded_files = None;
+                        picker.refresh(cx);
+                    })
+                }
                 _ => {}
             };
         })
@@ -448,10 +674,19 @@ impl FileFinderDelegate {
             .visible_worktrees(cx)
             .collect::<Vec<_>>();
         let include_root_name = worktrees.len() > 1;
+        let path_query = query.path_like.path_query();
+        let unloaded_files = if path_query.contains(std::path::is_separator) {
+            self.expand_unscanned_dirs(path_query, include_root_name, cx);
+            None
+        } else {
+            Some(self.unloaded_files(include_root_name, cx))
+        };
+        let mut worktree_abs_paths = HashMap::default();
         let candidate_sets = worktrees
             .into_iter()
             .map(|worktree| {
                 let worktree = worktree.read(cx);
+                worktree_abs_paths.insert(worktree.id().to_usize(), worktree.abs_path());
                 PathMatchCandidateSet {
                     snapshot: worktree.snapshot(),
                     include_ignored: worktree
@@ -467,19 +702,53 @@ impl FileFinderDelegate {
         self.cancel_flag.store(true, atomic::Ordering::Relaxed);
         self.cancel_flag = Arc::new(AtomicBool::new(false));
         let cancel_flag = self.cancel_flag.clone();
+        let frecency = self.frecency.clone();
         cx.spawn(|picker, mut cx| async move {
-            let matches = fuzzy::match_path_sets(
+            let mut matches = fuzzy::match_path_sets(
                 candidate_sets.as_slice(),
                 query.path_like.path_query(),
-                relative_to,
+                relative_to.clone(),
                 false,
                 100,
                 &cancel_flag,
                 cx.background_executor().clone(),
             )
-            .await
-            .into_iter()
-            .map(ProjectPanelOrdMatch);
+            .await;
+            if let Some(unloaded_files) = unloaded_files {
+                let unloaded_files = unloaded_files.await;
+                let unloaded_matches = fuzzy::match_path_sets(
+                    unloaded_files.as_ref(),
+                    query.path_like.path_query(),
+                    relative_to,
+                    false,
+                    100,
+                    &cancel_flag,
+                    cx.background_executor().clone(),
+                )
+                .await;
+                // Files that were listed before their directory got loaded may be matched twice.
+                let loaded_paths = matches
+                    .iter()
+                    .map(|path_match| (path_match.worktree_id, path_match.path.clone()))
+                    .collect::<HashSet<_>>();
+                matches.extend(unloaded_matches.into_iter().filter(|path_match| {
+                    !loaded_paths.contains(&(path_match.worktree_id, path_match.path.clone()))
+                }));
+            }
+            let mut matches = matches
+                .into_iter()
+                .map(|mut path_match| {
+                    if let Some(worktree_abs_path) = worktree_abs_paths.get(&path_match.worktree_id)
+                    {
+                        path_match.score = frecency.adjust_score(
+                            path_match.score,
+                            &worktree_abs_path.join(&path_match.path),
+                        );
+                    }
+                    ProjectPanelOrdMatch(path_match)
+                })
+                .collect::<Vec<_>>();
+            matches.sort_by(|a, b| b.cmp(a));
             let did_cancel = cancel_flag.load(atomic::Ordering::Relaxed);
             picker
                 .update(&mut cx, |picker, cx| {
@@ -491,6 +760,92 @@ impl FileFinderDelegate {
         })
     }
 
+    /// When only a file name is typed, there's no path along which to expand the directories that
+    /// the worktree hasn't scanned yet, so their files are listed straight from the file system
+    /// instead. This is only done once while the file finder is open.
+    fn unloaded_files(
+        &mut self,
+        include_root_name: bool,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Shared<Task<Arc<[UnloadedFileSet]>>> {
+        self.unloaded_files
+            .get_or_insert_with(|| {
+                let project = self.project.read(cx);
+                let fs = project.fs().clone();
+                let snapshots = project
+                    .visible_worktrees(cx)
+                    .filter_map(|worktree| Some(worktree.read(cx).as_local()?.snapshot()))
+                    .collect::<Vec<_>>();
+                cx.background_executor()
+                    .spawn(async move {
+                        let mut file_sets = Vec::new();
+                        for snapshot in snapshots {
+                            let mut files = Vec::new();
+                            for dir in snapshot.unloaded_dirs() {
+                                for path in snapshot.files_in_unloaded_dir(dir, fs.as_ref()).await {
+                                    let char_bag = CharBag::from_iter(
+                                        path.to_string_lossy().to_lowercase().chars(),
+                                    );
+                                    files.push((path, char_bag));
+                                }
+                            }
+                            if !files.is_empty() {
+                                file_sets.push(UnloadedFileSet {
+                                    worktree_id: snapshot.id().to_usize(),
+                                    prefix: if include_root_name {
+                                        format!("{}/", snapshot.root_name()).into()
+                                    } else {
+                                        "".into()
+                                    },
+                                    files,
+                                });
+                            }
+                        }
+                        file_sets.into()
+                    })
+                    .shared()
+            })
+            .clone()
+    }
+
+    /// Directories that the worktree hasn't scanned yet have no files to match, so expand the
+    /// deepest one along the path being typed. The matches are refreshed once it's loaded, which
+    /// expands the next directory along the path, if any.
+    fn expand_unscanned_dirs(
+        &self,
+        path_query: &str,
+        include_root_name: bool,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) {
+        let Some(dir_end) = path_query.rfind(std::path::is_separator) else {
+            return;
+        };
+        let dir_path = Path::new(&path_query[..dir_end]);
+        self.project.update(cx, |project, cx| {
+            let unscanned_dirs = project
+                .visible_worktrees(cx)
+                .filter_map(|worktree| {
+                    let worktree = worktree.read(cx);
+                    let dir_path = if include_root_name {
+                        dir_path.strip_prefix(worktree.root_name()).ok()?
+                    } else {
+                        dir_path
+                    };
+                    let entry = dir_path
+                        .ancestors()
+                        .find_map(|ancestor| worktree.entry_for_path(ancestor))?;
+                    (entry.kind.is_unloaded() && !entry.is_ignored && !entry.is_external)
+                        .then(|| (worktree.id(), entry.id))
+                })
+                .collect::<Vec<_>>();
+            for (worktree_id, entry_id) in unscanned_dirs {
+                if let Some(task) = project.expand_entry(worktree_id, entry_id, cx) {
+                    task.detach_and_log_err(cx);
+                }
+            }
+        });
+    }
+
     fn set_search_matches(
         &mut self,
         search_id: usize,
@@ -764,6 +1119,7 @@ impl PickerDelegate for FileFinderDelegate {
             cx.notify();
             Task::ready(())
         } else {
+            let (symbol, raw_query) = split_symbol_query(raw_query);
             let query = PathLikeWithPosition::parse_str(raw_query, |path_like_str| {
                 Ok::<_, std::convert::Infallible>(FileSearchQuery {
                     raw_query: raw_query.to_owned(),
@@ -772,6 +1128,7 @@ impl PickerDelegate for FileFinderDelegate {
                     } else {
                         Some(path_like_str.len())
                     },
+                    symbol: symbol.map(ToOwned::to_owned),
                 })
             })
             .expect("infallible");
@@ -873,6 +1230,10 @@ impl PickerDelegate for FileFinderDelegate {
                     .and_then(|query| query.column)
                     .unwrap_or(0)
                     .saturating_sub(1);
+                let symbol = self
+                    .latest_search_query
+                    .as_ref()
+                    .and_then(|query| query.path_like.symbol.clone());
                 let finder = self.file_finder.clone();
 
                 cx.spawn(|_, mut cx| async move {
@@ -894,6 +1255,9 @@ impl PickerDelegate for FileFinderDelegate {
                         }
                     }
                     finder.update(&mut cx, |_, cx| cx.emit(DismissEvent)).ok()?;
+                    if let Some((symbol, editor)) = symbol.zip(item.downcast::<Editor>()) {
+                        jump_to_symbol(editor, &symbol, &mut cx).await;
+                    }
 
                     Some(())
                 })
@@ -1038,4 +1402,17 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_split_symbol_query() {
+        assert_eq!(split_symbol_query("main@lib.rs"), (Some("main"), "lib.rs"));
+        assert_eq!(
+            split_symbol_query("Editor::new@editor.rs:10"),
+            (Some("Editor::new"), "editor.rs:10")
+        );
+        assert_eq!(split_symbol_query("@types/node"), (None, "@types/node"));
+        assert_eq!(split_symbol_query("main@"), (None, "main@"));
+        assert_eq!(split_symbol_query("a/b@c.rs"), (None, "a/b@c.rs"));
+        assert_eq!(split_symbol_query("lib.rs"), (None, "lib.rs"));
+    }
 }
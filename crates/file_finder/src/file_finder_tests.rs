@@ -3,6 +3,7 @@ use std::{assert_eq, future::IntoFuture, path::Path, time::Duration};
 use super::*;
 use editor::Editor;
 use gpui::{Entity, TestAppContext, VisualTestContext};
+use language::{Language, LanguageConfig, LanguageMatcher};
 use menu::{Confirm, SelectNext, SelectPrev};
 use project::FS_WATCH_LATENCY;
 use serde_json::json;
@@ -333,6 +334,98 @@ async fn test_row_column_numbers_query_outside_file(cx: &mut TestAppContext) {
         });
 }
 
+#[gpui::test]
+async fn test_symbol_query_jumps_to_symbol(cx: &mut TestAppContext) {
+    let app_state = init_test(cx);
+    app_state
+        .fs
+        .as_fake()
+        .insert_tree(
+            "/src",
+            json!({
+                "main.rs": "fn new() {}\n\nstruct Point;\n\nimpl Point {\n    fn new() -> Self {\n        Point\n    }\n}\n",
+                "other.rs": "",
+            }),
+        )
+        .await;
+
+    let project = Project::test(app_state.fs.clone(), ["/src".as_ref()], cx).await;
+    project.read_with(cx, |project, _| project.languages().add(rust_lang()));
+
+    let (picker, workspace, cx) = build_find_picker(project, cx);
+
+    cx.simulate_input("Point::new@main");
+    picker.update(cx, |picker, _| {
+        assert_eq!(
+            collect_search_matches(picker).search_only(),
+            vec![PathBuf::from("main.rs")]
+        );
+        let latest_search_query = picker.delegate.latest_search_query.as_ref().unwrap();
+        assert_eq!(latest_search_query.path_like.raw_query, "main");
+        assert_eq!(
+            latest_search_query.path_like.symbol.as_deref(),
+            Some("Point::new")
+        );
+    });
+
+    cx.dispatch_action(Confirm);
+    cx.run_until_parked();
+
+    let editor = cx.update(|cx| workspace.read(cx).active_item_as::<Editor>(cx).unwrap());
+    editor.update(cx, |editor, cx| {
+        let selections = editor.selections.all_adjusted(cx);
+        assert_eq!(selections.len(), 1);
+        assert_eq!(
+            selections[0].head(),
+            Point::new(5, 4),
+            "The caret should be on the method, not the function with the same name"
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_frecency_ranks_frequently_opened_files_first(cx: &mut TestAppContext) {
+    let app_state = init_test(cx);
+    app_state
+        .fs
+        .as_fake()
+        .insert_tree(
+            "/root",
+            json!({
+                "dir_a": { "lib.rs": "" },
+                "dir_b": { "lib.rs": "" },
+            }),
+        )
+        .await;
+
+    let project = Project::test(app_state.fs.clone(), ["/root".as_ref()], cx).await;
+    let (picker, _, cx) = build_find_picker(project, cx);
+
+    cx.simulate_input("lib");
+    picker.update(cx, |picker, _| {
+        assert_eq!(
+            collect_search_matches(picker).search_only(),
+            vec![PathBuf::from("dir_a/lib.rs"), PathBuf::from("dir_b/lib.rs")]
+        );
+    });
+
+    picker.update(cx, |picker, cx| {
+        let now = unix_timestamp();
+        picker.delegate.frecency = Arc::new(Frecency::new(
+            [(PathBuf::from("/root/dir_b/lib.rs"), 3, now)],
+            now,
+        ));
+        picker.refresh(cx);
+    });
+    cx.run_until_parked();
+    picker.update(cx, |picker, _| {
+        assert_eq!(
+            collect_search_matches(picker).search_only(),
+            vec![PathBuf::from("dir_b/lib.rs"), PathBuf::from("dir_a/lib.rs")]
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_matching_cancellation(cx: &mut TestAppContext) {
     let app_state = init_test(cx);
@@ -452,6 +545,77 @@ async fn test_ignored_root(cx: &mut TestAppContext) {
     picker.update(cx, |picker, _| assert_eq!(picker.delegate.matches.len(), 7));
 }
 
+#[gpui::test]
+async fn test_lazily_scanned_directories(cx: &mut TestAppContext) {
+    let app_state = init_test(cx);
+    cx.update(|cx| {
+        cx.update_global::<settings::SettingsStore, _>(|store, cx| {
+            store.update_user_settings::<project::WorktreeSettings>(cx, |settings| {
+                settings.lazy_file_scan = Some(true);
+            });
+        });
+    });
+    app_state
+        .fs
+        .as_fake()
+        .insert_tree(
+            "/root",
+            json!({
+                "a": {
+                    "other.txt": "",
+                    "b": {
+                        "file.txt": "",
+                    },
+                },
+                "c": {
+                    "d": {
+                        "deep.txt": "",
+                    },
+                },
+            }),
+        )
+        .await;
+
+    let project = Project::test(app_state.fs.clone(), ["/root".as_ref()], cx).await;
+    project.read_with(cx, |project, cx| {
+        let worktree = project.worktrees().next().unwrap().read(cx);
+        assert!(worktree.entry_for_path("a").unwrap().kind.is_unloaded());
+    });
+
+    let (picker, _, cx) = build_find_picker(project, cx);
+
+    // Typing a path loads the directories along it, one at a time.
+    cx.simulate_input("a/b/fi");
+    cx.background_executor.run_until_parked();
+    picker.update(cx, |picker, _| {
+        assert_eq!(
+            collect_search_matches(picker).search_only(),
+            vec![PathBuf::from("a/b/file.txt")],
+        );
+    });
+
+    // Typing only a file name finds files in directories that haven't been loaded, without
+    // loading them.
+    picker
+        .update(cx, |picker, cx| {
+            picker.delegate.spawn_search(test_path_like("deep"), cx)
+        })
+        .await;
+    picker.update(cx, |picker, cx| {
+        assert_eq!(
+            collect_search_matches(picker).search_only(),
+            vec![PathBuf::from("c/d/deep.txt")],
+        );
+        let worktree = picker.delegate.project.read(cx).worktrees().next().unwrap();
+        assert!(worktree
+            .read(cx)
+            .entry_for_path("c")
+            .unwrap()
+            .kind
+            .is_unloaded());
+    });
+}
+
 #[gpui::test]
 async fn test_single_file_worktrees(cx: &mut TestAppContext) {
     let app_state = init_test(cx);
@@ -1744,6 +1908,36 @@ fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
     })
 }
 
+fn rust_lang() -> Arc<Language> {
+    Arc::new(
+        Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["rs".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Some(tree_sitter_rust::language()),
+        )
+        .with_outline_query(
+            r#"
+            (struct_item
+                "struct" @context
+                name: (_) @name) @item
+            (impl_item
+                "impl" @context
+                type: (_) @name) @item
+            (function_item
+                "fn" @context
+                name: (_) @name) @item
+            "#,
+        )
+        .unwrap(),
+    )
+}
+
 fn test_path_like(test_str: &str) -> PathLikeWithPosition<FileSearchQuery> {
     PathLikeWithPosition::parse_str(test_str, |path_like_str| {
         Ok::<_, std::convert::Infallible>(FileSearchQuery {
@@ -1753,6 +1947,7 @@ fn test_path_like(test_str: &str) -> PathLikeWithPosition<FileSearchQuery> {
             } else {
                 Some(path_like_str.len())
             },
+            symbol: None,
         })
     })
     .unwrap()
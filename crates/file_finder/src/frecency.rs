@@ -0,0 +1,99 @@
+use collections::HashMap;
+use std::path::{Path, PathBuf};
+
+const DAY_SECS: i64 = 24 * 60 * 60;
+
+/// The most that frecency can multiply a match's score by, so that a file that's opened all the
+/// time can't outrank a much better match.
+const MAX_BOOST: f64 = 0.5;
+/// The frecency at which a match gets half of the maximum boost.
+const HALF_BOOST_FRECENCY: f64 = 100.0;
+
+/// How often and how recently the files in a workspace were opened, which is used to rank the
+/// files that are opened the most above other matches of similar quality.
+#[derive(Debug, Default)]
+pub(crate) struct Frecency {
+    opens: HashMap<PathBuf, FileOpens>,
+    now: i64,
+}
+
+#[derive(Debug)]
+struct FileOpens {
+    count: i64,
+    last_opened_at: i64,
+}
+
+impl Frecency {
+    /// Builds the frecency of files from their absolute paths, open counts and the times they
+    /// were last opened, in seconds since the Unix epoch.
+    pub fn new(opens: impl IntoIterator<Item = (PathBuf, i64, i64)>, now: i64) -> Self {
+        Self {
+            opens: opens
+                .into_iter()
+                .map(|(abs_path, count, last_opened_at)| {
+                    (
+                        abs_path,
+                        FileOpens {
+                            count,
+                            last_opened_at,
+                        },
+                    )
+                })
+                .collect(),
+            now,
+        }
+    }
+
+    /// The number of times the file was opened, weighted by how long ago it was last opened.
+    pub fn score(&self, abs_path: &Path) -> f64 {
+        let Some(opens) = self.opens.get(abs_path) else {
+            return 0.0;
+        };
+        let age_in_days = (self.now - opens.last_opened_at).max(0) / DAY_SECS;
+        let recency_weight = match age_in_days {
+            0..=4 => 100.0,
+            5..=14 => 70.0,
+            15..=31 => 50.0,
+            32..=90 => 30.0,
+            _ => 10.0,
+        };
+        opens.count.max(0) as f64 * recency_weight
+    }
+
+    /// Blends the file's frecency into the score of a match for it.
+    pub fn adjust_score(&self, match_score: f64, abs_path: &Path) -> f64 {
+        let frecency = self.score(abs_path);
+        match_score * (1.0 + MAX_BOOST * frecency / (frecency + HALF_BOOST_FRECENCY))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frecency_score() {
+        let now = 1_000 * DAY_SECS;
+        let frecency = Frecency::new(
+            [
+                (PathBuf::from("/a/recent.rs"), 2, now - DAY_SECS),
+                (PathBuf::from("/a/last_month.rs"), 2, now - 20 * DAY_SECS),
+                (PathBuf::from("/a/frequent.rs"), 10, now - 200 * DAY_SECS),
+            ],
+            now,
+        );
+
+        assert_eq!(frecency.score(Path::new("/a/recent.rs")), 200.0);
+        assert_eq!(frecency.score(Path::new("/a/last_month.rs")), 100.0);
+        assert_eq!(frecency.score(Path::new("/a/frequent.rs")), 100.0);
+        assert_eq!(frecency.score(Path::new("/a/never.rs")), 0.0);
+
+        assert_eq!(frecency.adjust_score(0.5, Path::new("/a/never.rs")), 0.5);
+        assert_eq!(
+            frecency.adjust_score(0.5, Path::new("/a/last_month.rs")),
+            0.625
+        );
+        let boosted = frecency.adjust_score(0.5, Path::new("/a/recent.rs"));
+        assert!(boosted > 0.625 && boosted < 0.75);
+    }
+}
@@ -0,0 +1,43 @@
+use std::path::PathBuf;
+
+use db::{define_connection, query, sqlez_macros::sql};
+use workspace::{WorkspaceDb, WorkspaceId};
+
+define_connection! {
+    pub static ref FILE_FINDER_DB: FileFinderDb<WorkspaceDb> =
+        &[sql!(
+            CREATE TABLE file_finder_opens (
+                workspace_id INTEGER,
+                abs_path BLOB NOT NULL,
+                open_count INTEGER NOT NULL,
+                last_opened_at INTEGER NOT NULL,
+                PRIMARY KEY(workspace_id, abs_path),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+            ) STRICT;
+        )];
+}
+
+impl FileFinderDb {
+    query! {
+        pub async fn record_open(
+            workspace_id: WorkspaceId,
+            abs_path: PathBuf,
+            opened_at: i64
+        ) -> Result<()> {
+            INSERT INTO file_finder_opens(workspace_id, abs_path, open_count, last_opened_at)
+            VALUES (?1, ?2, 1, ?3)
+            ON CONFLICT(workspace_id, abs_path) DO UPDATE SET
+                open_count = open_count + 1,
+                last_opened_at = excluded.last_opened_at
+        }
+    }
+
+    query! {
+        pub fn get_opens(workspace_id: WorkspaceId) -> Result<Vec<(PathBuf, i64, i64)>> {
+            SELECT abs_path, open_count, last_opened_at
+            FROM file_finder_opens
+            WHERE workspace_id = ?
+        }
+    }
+}
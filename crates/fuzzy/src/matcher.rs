@@ -129,9 +129,9 @@ impl<'a> Matcher<'a> {
         let mut lowercase_prefix = lowercase_prefix.iter();
         let mut lowercase_candidate = lowercase_candidate.iter();
         for (i, char) in self.lowercase_query.iter().enumerate().rev() {
-            if let Some(j) = lowercase_candidate.rposition(|c| c == char) {
+            if let Some(j) = lowercase_candidate.rposition(|c| query_char_matches(*char, *c)) {
                 self.last_positions[i] = j + lowercase_prefix.len();
-            } else if let Some(j) = lowercase_prefix.rposition(|c| c == char) {
+            } else if let Some(j) = lowercase_prefix.rposition(|c| query_char_matches(*char, *c)) {
                 self.last_positions[i] = j;
             } else {
                 return false;
@@ -216,13 +216,13 @@ impl<'a> Matcher<'a> {
             } else {
                 path_cased[j - prefix.len()]
             };
-            let is_path_sep = path_char == '/' || path_char == '\\';
+            let is_path_sep = is_path_separator(path_char);
 
             if query_idx == 0 && is_path_sep {
                 last_slash = j;
             }
 
-            if query_char == path_char || (is_path_sep && query_char == '_' || query_char == '\\') {
+            if query_char_matches(query_char, path_char) {
                 let curr = if j < prefix.len() {
                     prefix[j]
                 } else {
@@ -237,10 +237,21 @@ impl<'a> Matcher<'a> {
                         path[j - 1 - prefix.len()]
                     };
 
-                    if last == '/' {
+                    let next = if j + 1 < prefix.len() {
+                        prefix.get(j + 1)
+                    } else {
+                        path.get(j + 1 - prefix.len())
+                    };
+
+                    if is_path_separator(last) {
                         char_score = 0.9;
                     } else if (last == '-' || last == '_' || last == ' ' || last.is_numeric())
                         || (last.is_lowercase() && curr.is_uppercase())
+                        || (last.is_alphabetic() && curr.is_numeric())
+                        // The start of a word following an acronym, like the `P` in `HTTPParser`.
+                        || (last.is_uppercase()
+                            && curr.is_uppercase()
+                            && next.map_or(false, |next| next.is_lowercase()))
                     {
                         char_score = 0.8;
                     } else if last == '.' {
@@ -257,8 +268,14 @@ impl<'a> Matcher<'a> {
 
                 // Apply a severe penalty if the case doesn't match.
                 // This will make the exact matches have higher score than the case-insensitive and the
-                // path insensitive matches.
-                if (self.smart_case || curr == '/') && self.query[query_idx] != curr {
+                // path insensitive matches. Either separator in the query matches both separators
+                // in the path, so they aren't penalized for differing.
+                let mismatched = if is_path_sep {
+                    !is_path_separator(self.query[query_idx])
+                } else {
+                    self.smart_case && self.query[query_idx] != curr
+                };
+                if mismatched {
                     char_score *= 0.001;
                 }
 
@@ -312,6 +329,17 @@ impl<'a> Matcher<'a> {
     }
 }
 
+fn is_path_separator(c: char) -> bool {
+    c == '/' || c == '\\'
+}
+
+/// Whether a lowercase query character matches a lowercase path character. Path separators match
+/// each other, and an underscore in the query also matches a separator.
+fn query_char_matches(query_char: char, path_char: char) -> bool {
+    query_char == path_char
+        || (is_path_separator(path_char) && (query_char == '_' || is_path_separator(query_char)))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{PathMatch, PathMatchCandidate};
@@ -383,6 +411,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_match_path_separators() {
+        let paths = vec!["src/lib.rs", "src\\main.rs", "src_lib.rs"];
+
+        // Either separator in the query matches both kinds of separator in the path.
+        assert_eq!(
+            match_single_path_query("src/m", true, &paths),
+            vec![("src\\main.rs", vec![0, 1, 2, 3, 4])]
+        );
+        assert_eq!(
+            match_single_path_query("src\\l", true, &paths),
+            vec![("src/lib.rs", vec![0, 1, 2, 3, 4])]
+        );
+
+        // An underscore matches a separator, but not as well as an underscore.
+        assert_eq!(
+            match_single_path_query("src_l", false, &paths),
+            vec![
+                ("src_lib.rs", vec![0, 1, 2, 3, 4]),
+                ("src/lib.rs", vec![0, 1, 2, 3, 4]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_match_acronym_boundaries() {
+        // The start of the word after an acronym is preferred over a letter within it.
+        let paths = vec!["HTTPParser"];
+        assert_eq!(
+            match_single_path_query("hp", false, &paths),
+            vec![("HTTPParser", vec![0, 4])]
+        );
+    }
+
     #[test]
     fn test_lowercase_longer_than_uppercase() {
         // This character has more chars in lower-case than in upper-case.
@@ -1,6 +1,6 @@
 use crate::blame::Blame;
 use crate::GitHostingProviderRegistry;
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use collections::HashMap;
 use git2::{BranchType, StatusShow};
 use parking_lot::Mutex;
@@ -9,12 +9,16 @@ use serde::{Deserialize, Serialize};
 use std::{
     cmp::Ordering,
     path::{Component, Path, PathBuf},
+    process::Command,
     sync::Arc,
     time::SystemTime,
 };
 use sum_tree::{MapSeekTarget, TreeMap};
 use util::{paths::PathExt, ResultExt};
 
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+
 pub use git2::Repository as LibGitRepository;
 
 #[derive(Clone, Debug, Hash, PartialEq)]
@@ -62,6 +66,10 @@ pub trait GitRepository: Send {
     fn create_branch(&self, _: &str) -> Result<()>;
 
     fn blame(&self, path: &Path, content: Rope) -> Result<crate::blame::Blame>;
+
+    /// Returns the unified diff of the changes staged in the index when `staged` is true,
+    /// or of the unstaged changes in the working directory otherwise.
+    fn diff(&self, staged: bool) -> Result<String>;
 }
 
 impl std::fmt::Debug for dyn GitRepository {
@@ -259,6 +267,35 @@ impl GitRepository for RealGitRepository {
             self.hosting_provider_registry.clone(),
         )
     }
+
+    fn diff(&self, staged: bool) -> Result<String> {
+        let working_directory = self
+            .repository
+            .workdir()
+            .context("failed to get git working directory")?;
+
+        let mut command = Command::new(&self.git_binary_path);
+        command
+            .current_dir(working_directory)
+            .args(["diff", "--no-color", "--no-ext-diff"]);
+        if staged {
+            command.arg("--cached");
+        }
+
+        #[cfg(windows)]
+        command.creation_flags(windows::Win32::System::Threading::CREATE_NO_WINDOW.0);
+
+        let output = command
+            .output()
+            .map_err(|e| anyhow!("Failed to start git diff process: {}", e))?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "git diff process failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
 }
 
 fn matches_index(repo: &LibGitRepository, path: &RepoPath, mtime: SystemTime) -> bool {
@@ -304,6 +341,8 @@ pub struct FakeGitRepositoryState {
     pub blames: HashMap<PathBuf, Blame>,
     pub worktree_statuses: HashMap<RepoPath, GitFileStatus>,
     pub branch_name: Option<String>,
+    pub staged_diff: String,
+    pub unstaged_diff: String,
 }
 
 impl FakeGitRepository {
@@ -377,6 +416,15 @@ impl GitRepository for FakeGitRepository {
             .with_context(|| format!("failed to get blame for {:?}", path))
             .cloned()
     }
+
+    fn diff(&self, staged: bool) -> Result<String> {
+        let state = self.state.lock();
+        Ok(if staged {
+            state.staged_diff.clone()
+        } else {
+            state.unstaged_diff.clone()
+        })
+    }
 }
 
 fn check_path_to_repo_path_errors(relative_file_path: &Path) -> Result<()> {
@@ -7,15 +7,74 @@ use serde::{Deserialize, Serialize};
 
 pub const API_URL: &str = "https://generativelanguage.googleapis.com";
 
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub enum Model {
+    #[serde(rename = "gemini-pro", alias = "gemini-1.0-pro")]
+    GeminiPro,
+    #[default]
+    #[serde(rename = "gemini-1.5-pro-latest", alias = "gemini-1.5-pro")]
+    Gemini15Pro,
+    #[serde(rename = "gemini-1.5-flash-latest", alias = "gemini-1.5-flash")]
+    Gemini15Flash,
+}
+
+impl Model {
+    pub fn from_id(id: &str) -> Result<Self> {
+        if id.starts_with("gemini-1.5-pro") {
+            Ok(Self::Gemini15Pro)
+        } else if id.starts_with("gemini-1.5-flash") {
+            Ok(Self::Gemini15Flash)
+        } else if id == "gemini-pro" || id.starts_with("gemini-1.0-pro") {
+            Ok(Self::GeminiPro)
+        } else {
+            Err(anyhow!("invalid model id: {id}"))
+        }
+    }
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            Self::GeminiPro => "gemini-pro",
+            Self::Gemini15Pro => "gemini-1.5-pro-latest",
+            Self::Gemini15Flash => "gemini-1.5-flash-latest",
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Self::GeminiPro => "Gemini 1.0 Pro",
+            Self::Gemini15Pro => "Gemini 1.5 Pro",
+            Self::Gemini15Flash => "Gemini 1.5 Flash",
+        }
+    }
+
+    pub fn max_token_count(&self) -> usize {
+        match self {
+            Self::GeminiPro => 30_720,
+            Self::Gemini15Pro | Self::Gemini15Flash => 1_048_576,
+        }
+    }
+
+    /// Whether the model accepts a separate `systemInstruction`, rather than
+    /// needing system prompts folded into the conversation.
+    pub fn supports_system_instruction(&self) -> bool {
+        match self {
+            Self::GeminiPro => false,
+            Self::Gemini15Pro | Self::Gemini15Flash => true,
+        }
+    }
+}
+
 pub async fn stream_generate_content(
     client: Arc<dyn HttpClient>,
     api_url: &str,
     api_key: &str,
+    model: &str,
     request: GenerateContentRequest,
 ) -> Result<BoxStream<'static, Result<GenerateContentResponse>>> {
     let uri = format!(
-        "{}/v1beta/models/gemini-pro:streamGenerateContent?alt=sse&key={}",
-        api_url, api_key
+        "{}/v1beta/models/{}:streamGenerateContent?alt=sse&key={}",
+        api_url, model, api_key
     );
 
     let request = serde_json::to_string(&request)?;
@@ -51,15 +110,16 @@ pub async fn stream_generate_content(
     }
 }
 
-pub async fn count_tokens<T: HttpClient>(
-    client: &T,
+pub async fn count_tokens(
+    client: &dyn HttpClient,
     api_url: &str,
     api_key: &str,
+    model: &str,
     request: CountTokensRequest,
 ) -> Result<CountTokensResponse> {
     let uri = format!(
-        "{}/v1beta/models/gemini-pro:countTokens?key={}",
-        api_url, api_key
+        "{}/v1beta/models/{}:countTokens?key={}",
+        api_url, model, api_key
     );
     let request = serde_json::to_string(&request)?;
     let mut response = client.post_json(&uri, request.into()).await?;
@@ -94,6 +154,8 @@ pub enum Task {
 #[serde(rename_all = "camelCase")]
 pub struct GenerateContentRequest {
     pub contents: Vec<Content>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_instruction: Option<Content>,
     pub generation_config: Option<GenerationConfig>,
     pub safety_settings: Option<Vec<SafetySetting>>,
 }
@@ -123,7 +185,7 @@ pub struct Content {
     pub role: Role,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub enum Role {
     User,
@@ -1,3 +1,5 @@
+mod ssh_server;
+
 use anyhow::{anyhow, Result};
 use client::DevServerProjectId;
 use client::{user::UserStore, Client, ClientSettings};
@@ -16,6 +18,8 @@ use settings::{Settings, SettingsStore};
 use std::{collections::HashMap, sync::Arc};
 use util::{ResultExt, TryFutureExt};
 
+pub use ssh_server::{serve, serve_stdio};
+
 pub struct DevServer {
     client: Arc<Client>,
     app_state: AppState,
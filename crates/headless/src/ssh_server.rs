@@ -0,0 +1,278 @@
+use crate::AppState;
+use anyhow::{anyhow, Result};
+use client::Client;
+use gpui::{AppContext, AsyncAppContext, Context, Model, ModelContext, Task};
+use postage::stream::Stream;
+use project::Project;
+use rpc::{proto, Connection, TypedEnvelope};
+use std::{collections::HashMap, sync::Arc};
+
+/// Hosts projects for an instance of the app that's connected directly to this process, rather
+/// than through the collaboration server.
+struct SshServer {
+    app_state: AppState,
+    projects: HashMap<u64, Model<Project>>,
+    _subscriptions: Vec<client::Subscription>,
+}
+
+/// Serves projects over the standard input and output of this process, which is how the app
+/// talks to a headless server that it launched over SSH.
+pub fn serve_stdio(
+    client: Arc<Client>,
+    app_state: AppState,
+    cx: &mut AppContext,
+) -> Task<Result<()>> {
+    let connection = Connection::framed(
+        smol::Unblock::new(std::io::stdin()),
+        smol::Unblock::new(std::io::stdout()),
+    );
+    serve(connection, client, app_state, cx)
+}
+
+/// Serves projects over the given connection until the app disconnects.
+pub fn serve(
+    connection: Connection,
+    client: Arc<Client>,
+    app_state: AppState,
+    cx: &mut AppContext,
+) -> Task<Result<()>> {
+    let server = cx.new_model(|cx| SshServer::new(client.clone(), app_state, cx));
+    cx.spawn(|cx| async move {
+        client.connect_directly(connection, &cx).await?;
+        let mut status = client.status();
+        while let Some(status) = status.recv().await {
+            if status.is_signed_out() {
+                break;
+            }
+        }
+        drop(server);
+        Ok(())
+    })
+}
+
+impl SshServer {
+    fn new(client: Arc<Client>, app_state: AppState, cx: &mut ModelContext<Self>) -> Self {
+        Self {
+            _subscriptions: vec![
+                client.add_request_handler(cx.weak_model(), Self::handle_join_ssh_project)
+            ],
+            projects: Default::default(),
+            app_state,
+        }
+    }
+
+    async fn handle_join_ssh_project(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::JoinSshProject>,
+        client: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::JoinProjectResponse> {
+        let project_id = envelope.payload.project_id;
+        let path = shellexpand::tilde(&envelope.payload.path).to_string();
+        let project = this.update(&mut cx, |this, cx| {
+            if this.projects.contains_key(&project_id) {
+                return Err(anyhow!("project {project_id} is already open"));
+            }
+            Ok(Project::local(
+                client,
+                this.app_state.node_runtime.clone(),
+                this.app_state.user_store.clone(),
+                this.app_state.languages.clone(),
+                this.app_state.fs.clone(),
+                cx,
+            ))
+        })??;
+
+        project
+            .update(&mut cx, |project, cx| {
+                project.find_or_create_local_worktree(&path, true, cx)
+            })?
+            .await?;
+        project.update(&mut cx, |project, cx| project.shared(project_id, cx))??;
+        let worktrees =
+            project.read_with(&cx, |project, cx| project.worktree_metadata_protos(cx))?;
+        this.update(&mut cx, |this, _| this.projects.insert(project_id, project))?;
+
+        // The app is the project's only guest. Language servers that are already running are
+        // announced when the project is shared.
+        Ok(proto::JoinProjectResponse {
+            project_id,
+            replica_id: 1,
+            worktrees,
+            collaborators: Vec::new(),
+            language_servers: Vec::new(),
+            role: proto::ChannelRole::Member.into(),
+            dev_server_project_id: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use client::UserStore;
+    use clock::FakeSystemClock;
+    use fs::{FakeFs, Fs};
+    use futures::StreamExt as _;
+    use gpui::TestAppContext;
+    use http::FakeHttpClient;
+    use language::{FakeLspAdapter, Language, LanguageConfig, LanguageMatcher, LanguageRegistry};
+    use node_runtime::FakeNodeRuntime;
+    use project::terminals::SshCommand;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::path::Path;
+
+    #[gpui::test]
+    async fn test_ssh_project(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
+        let fs = FakeFs::new(server_cx.executor());
+        fs.insert_tree(
+            "/code",
+            json!({
+                "project1": {
+                    "README.md": "# project 1",
+                    "src": {
+                        "lib.rs": "fn one() -> usize { 1 }"
+                    }
+                }
+            }),
+        )
+        .await;
+
+        // Connect the app and the headless server over a local loopback connection instead of
+        // over SSH.
+        let (client_conn, server_conn, _kill) = Connection::in_memory(cx.executor());
+        let server_client = init_test(server_cx);
+        let languages = Arc::new(LanguageRegistry::test(server_cx.executor()));
+        languages.add(rust_lang());
+        let mut fake_servers = languages.register_fake_lsp_adapter(
+            "Rust",
+            FakeLspAdapter {
+                name: "the-language-server",
+                ..Default::default()
+            },
+        );
+        server_cx.update(|cx| {
+            let app_state = AppState {
+                node_runtime: FakeNodeRuntime::new(),
+                user_store: cx.new_model(|cx| UserStore::new(server_client.clone(), cx)),
+                languages,
+                fs: fs.clone(),
+            };
+            serve(server_conn, server_client, app_state, cx).detach_and_log_err(cx);
+        });
+
+        let client = init_test(cx);
+        client
+            .connect_directly(client_conn, &cx.to_async())
+            .await
+            .unwrap();
+        let user_store = cx.new_model(|cx| UserStore::new(client.clone(), cx));
+        let project = Project::ssh(
+            "/code/project1".into(),
+            SshCommand {
+                arguments: vec!["example.com".into()],
+            },
+            client,
+            user_store,
+            Arc::new(LanguageRegistry::test(cx.executor())),
+            FakeFs::new(cx.executor()),
+            cx.to_async(),
+        )
+        .await
+        .unwrap();
+        cx.executor().run_until_parked();
+
+        let worktree_id = project.read_with(cx, |project, cx| {
+            assert!(project.is_remote());
+            let worktree = project.worktrees().next().unwrap().read(cx);
+            assert_eq!(worktree.root_name(), "project1");
+            assert_eq!(
+                worktree
+                    .paths()
+                    .map(|path| path.as_ref())
+                    .collect::<Vec<_>>(),
+                [
+                    Path::new("README.md"),
+                    Path::new("src"),
+                    Path::new("src/lib.rs")
+                ]
+            );
+            worktree.id()
+        });
+
+        // Edits made in the app are saved on the remote machine.
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_buffer((worktree_id, "src/lib.rs"), cx)
+            })
+            .await
+            .unwrap();
+        buffer.update(cx, |buffer, cx| {
+            assert_eq!(buffer.text(), "fn one() -> usize { 1 }");
+            buffer.edit([(0..0, "pub ")], None, cx);
+        });
+        project
+            .update(cx, |project, cx| project.save_buffer(buffer, cx))
+            .await
+            .unwrap();
+        assert_eq!(
+            fs.load("/code/project1/src/lib.rs".as_ref()).await.unwrap(),
+            "pub fn one() -> usize { 1 }"
+        );
+
+        // Opening the buffer started a language server on the remote machine, whose
+        // diagnostics reach the app.
+        let fake_server = fake_servers.next().await.unwrap();
+        fake_server.notify::<lsp::notification::PublishDiagnostics>(
+            lsp::PublishDiagnosticsParams {
+                uri: lsp::Url::from_file_path("/code/project1/src/lib.rs").unwrap(),
+                version: None,
+                diagnostics: vec![lsp::Diagnostic {
+                    range: lsp::Range::new(lsp::Position::new(0, 7), lsp::Position::new(0, 10)),
+                    severity: Some(lsp::DiagnosticSeverity::ERROR),
+                    message: "unused function".to_string(),
+                    ..Default::default()
+                }],
+            },
+        );
+        cx.executor().run_until_parked();
+        project.read_with(cx, |project, cx| {
+            let status = project.language_server_statuses().next().unwrap();
+            assert_eq!(status.name, "the-language-server");
+            assert_eq!(project.diagnostic_summary(false, cx).error_count, 1);
+        });
+    }
+
+    fn rust_lang() -> Arc<Language> {
+        Arc::new(Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["rs".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            None,
+        ))
+    }
+
+    fn init_test(cx: &mut TestAppContext) -> Arc<Client> {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            release_channel::init("0.0.0", cx);
+            language::init(cx);
+            Project::init_settings(cx);
+
+            let client = Client::new(
+                Arc::new(FakeSystemClock::default()),
+                FakeHttpClient::with_404_response(),
+                cx,
+            );
+            Project::register_client_handlers(&client);
+            client
+        })
+    }
+}
@@ -0,0 +1,591 @@
+mod hex_item;
+
+use anyhow::anyhow;
+use editor::{actions::SelectAll, Editor};
+use gpui::{
+    actions, uniform_list, AnyElement, AppContext, EntityId, EventEmitter, FocusHandle,
+    FocusableView, KeyDownEvent, Model, MouseButton, MouseDownEvent, Render, Subscription, Task,
+    UniformListScrollHandle, View, VisualContext,
+};
+pub use hex_item::HexItem;
+use hex_item::{parse_byte_pattern, parse_offset};
+use project::{Project, ProjectPath};
+use settings::Settings;
+use std::ops::Range;
+use theme::ThemeSettings;
+use ui::prelude::*;
+use workspace::{
+    item::{Item, ItemEvent, ProjectItem, TabContentParams},
+    Workspace, WorkspaceId,
+};
+
+const BYTES_PER_ROW: usize = 16;
+const ROWS_PER_PAGE: usize = 32;
+
+actions!(
+    hex_editor,
+    [OpenInHexEditor, GoToOffset, FindBytes, Undo, Redo]
+);
+
+pub fn init(cx: &mut AppContext) {
+    workspace::register_project_item::<HexEditor>(cx);
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(HexEditor::open_active_file);
+    })
+    .detach();
+}
+
+/// Which representation of the bytes keyboard input applies to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Column {
+    Hex,
+    Ascii,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum QueryKind {
+    GoToOffset,
+    FindBytes,
+}
+
+struct QueryBar {
+    kind: QueryKind,
+    editor: View<Editor>,
+    error: Option<SharedString>,
+}
+
+/// Shows a file's bytes as rows of offsets, hex digits and ASCII characters, and lets them be
+/// overwritten in either column.
+pub struct HexEditor {
+    item: Model<HexItem>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    /// The offset of the byte where the selection started.
+    selection_tail: usize,
+    /// The offset of the byte under the cursor, which is the other end of the selection.
+    cursor: usize,
+    column: Column,
+    /// Whether the first hex digit of the byte under the cursor has been typed.
+    pending_low_digit: bool,
+    query_bar: Option<QueryBar>,
+    _observe_item: Subscription,
+}
+
+impl HexEditor {
+    pub fn new(item: Model<HexItem>, cx: &mut ViewContext<Self>) -> Self {
+        let observe_item = cx.observe(&item, |this, item, cx| {
+            let last_offset = item.read(cx).len().saturating_sub(1);
+            this.cursor = this.cursor.min(last_offset);
+            this.selection_tail = this.selection_tail.min(last_offset);
+            cx.emit(ItemEvent::UpdateTab);
+            cx.notify();
+        });
+        Self {
+            item,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: UniformListScrollHandle::new(),
+            selection_tail: 0,
+            cursor: 0,
+            column: Column::Hex,
+            pending_low_digit: false,
+            query_bar: None,
+            _observe_item: observe_item,
+        }
+    }
+
+    /// Opens the file of the active item in the hex editor, even if it looks like text.
+    fn open_active_file(
+        workspace: &mut Workspace,
+        _: &OpenInHexEditor,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some(project_path) = workspace
+            .active_item(cx)
+            .and_then(|item| item.project_path(cx))
+        else {
+            return;
+        };
+        let open = HexItem::open(workspace.project().clone(), project_path, cx);
+        cx.spawn(|workspace, mut cx| async move {
+            let item = open.await?;
+            workspace.update(&mut cx, |workspace, cx| {
+                let pane = workspace.active_pane().clone();
+                workspace.open_project_item::<HexEditor>(pane, item, cx);
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn selection(&self) -> Range<usize> {
+        self.selection_tail.min(self.cursor)..self.selection_tail.max(self.cursor) + 1
+    }
+
+    fn select(&mut self, tail: usize, cursor: usize, cx: &mut ViewContext<Self>) {
+        self.selection_tail = tail;
+        self.cursor = cursor;
+        self.pending_low_digit = false;
+        self.scroll_handle.scroll_to_item(cursor / BYTES_PER_ROW);
+        cx.notify();
+    }
+
+    fn move_cursor(&mut self, offset: usize, extend_selection: bool, cx: &mut ViewContext<Self>) {
+        let offset = offset.min(self.item.read(cx).len().saturating_sub(1));
+        let tail = if extend_selection {
+            self.selection_tail
+        } else {
+            offset
+        };
+        self.select(tail, offset, cx);
+    }
+
+    fn key_down(&mut self, event: &KeyDownEvent, cx: &mut ViewContext<Self>) {
+        let keystroke = &event.keystroke;
+        let modifiers = keystroke.modifiers;
+        if modifiers.control || modifiers.alt || modifiers.platform || modifiers.function {
+            return;
+        }
+
+        let cursor = self.cursor;
+        let row_start = cursor - cursor % BYTES_PER_ROW;
+        let target = match keystroke.key.as_str() {
+            "left" => cursor.saturating_sub(1),
+            "right" => cursor + 1,
+            "up" => cursor.saturating_sub(BYTES_PER_ROW),
+            "down" => cursor + BYTES_PER_ROW,
+            "pageup" => cursor.saturating_sub(BYTES_PER_ROW * ROWS_PER_PAGE),
+            "pagedown" => cursor + BYTES_PER_ROW * ROWS_PER_PAGE,
+            "home" => row_start,
+            "end" => row_start + BYTES_PER_ROW - 1,
+            "tab" => {
+                self.column = match self.column {
+                    Column::Hex => Column::Ascii,
+                    Column::Ascii => Column::Hex,
+                };
+                self.pending_low_digit = false;
+                cx.stop_propagation();
+                cx.notify();
+                return;
+            }
+            _ => {
+                let input = keystroke.ime_key.as_deref().unwrap_or(&keystroke.key);
+                let mut chars = input.chars();
+                if let (Some(input), None) = (chars.next(), chars.next()) {
+                    if self.input(input, cx) {
+                        cx.stop_propagation();
+                    }
+                }
+                return;
+            }
+        };
+        self.move_cursor(target, modifiers.shift, cx);
+        cx.stop_propagation();
+    }
+
+    /// Overwrites the byte under the cursor with a typed hex digit or ASCII character, returning
+    /// whether the input was accepted.
+    fn input(&mut self, input: char, cx: &mut ViewContext<Self>) -> bool {
+        let offset = self.cursor;
+        let Some(old_byte) = self.item.read(cx).bytes().get(offset).copied() else {
+            return false;
+        };
+
+        match self.column {
+            Column::Hex => {
+                let Some(digit) = input.to_digit(16).map(|digit| digit as u8) else {
+                    return false;
+                };
+                let merge = self.pending_low_digit;
+                let byte = if merge {
+                    (old_byte & 0xf0) | digit
+                } else {
+                    (digit << 4) | (old_byte & 0x0f)
+                };
+                self.item
+                    .update(cx, |item, cx| item.overwrite(offset, byte, merge, cx));
+                if merge {
+                    self.move_cursor(offset + 1, false, cx);
+                } else {
+                    self.select(offset, offset, cx);
+                    self.pending_low_digit = true;
+                }
+            }
+            Column::Ascii => {
+                if !input.is_ascii() || input.is_ascii_control() {
+                    return false;
+                }
+                self.item.update(cx, |item, cx| {
+                    item.overwrite(offset, input as u8, false, cx)
+                });
+                self.move_cursor(offset + 1, false, cx);
+            }
+        }
+        true
+    }
+
+    fn undo(&mut self, _: &Undo, cx: &mut ViewContext<Self>) {
+        if let Some(offset) = self.item.update(cx, |item, cx| item.undo(cx)) {
+            self.move_cursor(offset, false, cx);
+        }
+    }
+
+    fn redo(&mut self, _: &Redo, cx: &mut ViewContext<Self>) {
+        if let Some(offset) = self.item.update(cx, |item, cx| item.redo(cx)) {
+            self.move_cursor(offset, false, cx);
+        }
+    }
+
+    fn go_to_offset(&mut self, _: &GoToOffset, cx: &mut ViewContext<Self>) {
+        self.deploy_query_bar(QueryKind::GoToOffset, cx);
+    }
+
+    fn find_bytes(&mut self, _: &FindBytes, cx: &mut ViewContext<Self>) {
+        self.deploy_query_bar(QueryKind::FindBytes, cx);
+    }
+
+    fn deploy_query_bar(&mut self, kind: QueryKind, cx: &mut ViewContext<Self>) {
+        let editor = match &self.query_bar {
+            Some(query_bar) if query_bar.kind == kind => query_bar.editor.clone(),
+            _ => {
+                let editor = cx.new_view(|cx| {
+                    let mut editor = Editor::single_line(cx);
+                    editor.set_placeholder_text(
+                        match kind {
+                            QueryKind::GoToOffset => "Offset, in decimal or 0x-prefixed hex",
+                            QueryKind::FindBytes => "Hex bytes, like 7f 45 4c 46, or \"text\"",
+                        },
+                        cx,
+                    );
+                    editor
+                });
+                self.query_bar = Some(QueryBar {
+                    kind,
+                    editor: editor.clone(),
+                    error: None,
+                });
+                editor
+            }
+        };
+        editor.update(cx, |editor, cx| editor.select_all(&SelectAll, cx));
+        cx.focus_view(&editor);
+        cx.notify();
+    }
+
+    fn confirm_query(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let Some(query_bar) = &self.query_bar else {
+            return;
+        };
+        let kind = query_bar.kind;
+        let query = query_bar.editor.read(cx).text(cx);
+        let item = self.item.read(cx);
+
+        let result = match kind {
+            QueryKind::GoToOffset => parse_offset(&query).and_then(|offset| {
+                if offset < item.len() {
+                    Ok(offset..offset + 1)
+                } else {
+                    Err(anyhow!("the file is only {:#x} bytes long", item.len()))
+                }
+            }),
+            QueryKind::FindBytes => parse_byte_pattern(&query).and_then(|pattern| {
+                item.find(&pattern, self.selection().start + 1)
+                    .map(|offset| offset..offset + pattern.len())
+                    .ok_or_else(|| anyhow!("no matches"))
+            }),
+        };
+
+        match result {
+            Ok(range) => {
+                self.select(range.start, range.end - 1, cx);
+                if kind == QueryKind::GoToOffset {
+                    self.dismiss_query_bar(&menu::Cancel, cx);
+                } else if let Some(query_bar) = &mut self.query_bar {
+                    query_bar.error = None;
+                }
+            }
+            Err(error) => {
+                if let Some(query_bar) = &mut self.query_bar {
+                    query_bar.error = Some(error.to_string().into());
+                }
+                cx.notify();
+            }
+        }
+    }
+
+    fn dismiss_query_bar(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        if self.query_bar.take().is_some() {
+            cx.focus(&self.focus_handle);
+            cx.notify();
+        }
+    }
+
+    fn render_query_bar(&self, query_bar: &QueryBar, cx: &mut ViewContext<Self>) -> AnyElement {
+        h_flex()
+            .px_2()
+            .py_1()
+            .gap_2()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .on_action(cx.listener(Self::confirm_query))
+            .on_action(cx.listener(Self::dismiss_query_bar))
+            .child(
+                Label::new(match query_bar.kind {
+                    QueryKind::GoToOffset => "Go to Offset",
+                    QueryKind::FindBytes => "Find Bytes",
+                })
+                .color(Color::Muted),
+            )
+            .child(div().flex_1().child(query_bar.editor.clone()))
+            .when_some(query_bar.error.clone(), |this, error| {
+                this.child(Label::new(error).color(Color::Error))
+            })
+            .into_any_element()
+    }
+
+    fn render_row(&self, row: usize, cx: &mut ViewContext<Self>) -> AnyElement {
+        let start = row * BYTES_PER_ROW;
+        let bytes = self.item.read(cx).bytes();
+        let row_bytes = bytes[start..(start + BYTES_PER_ROW).min(bytes.len())].to_vec();
+        let selection = self.selection();
+        let focused = self.focus_handle.is_focused(cx);
+        let player = cx.theme().players().local();
+
+        let cell = |offset: usize, column: Column, text: String, cx: &ViewContext<Self>| {
+            let cell = div()
+                .px_0p5()
+                .border_1()
+                .border_color(gpui::transparent_black())
+                .child(text);
+            if offset >= start + row_bytes.len() {
+                return cell.invisible();
+            }
+            cell.when(selection.contains(&offset), |cell| {
+                cell.bg(player.selection)
+            })
+            .when(
+                offset == self.cursor && column == self.column && focused,
+                |cell| cell.border_color(player.cursor),
+            )
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(move |this, event: &MouseDownEvent, cx| {
+                    this.column = column;
+                    this.move_cursor(offset, event.modifiers.shift, cx);
+                    cx.focus(&this.focus_handle);
+                }),
+            )
+        };
+
+        h_flex()
+            .gap_4()
+            .child(
+                div()
+                    .text_color(cx.theme().colors().text_muted)
+                    .child(format!("{start:08x}")),
+            )
+            .child(h_flex().children((0..BYTES_PER_ROW).map(|ix| {
+                let offset = start + ix;
+                let text = row_bytes
+                    .get(ix)
+                    .map_or("00".to_string(), |byte| format!("{byte:02x}"));
+                cell(offset, Column::Hex, text, cx)
+                    .when(ix == BYTES_PER_ROW / 2, |cell| cell.ml_2())
+            })))
+            .child(h_flex().children((0..BYTES_PER_ROW).map(|ix| {
+                let offset = start + ix;
+                let text = row_bytes.get(ix).map_or(".".to_string(), |byte| {
+                    if byte.is_ascii_graphic() || *byte == b' ' {
+                        char::from(*byte).to_string()
+                    } else {
+                        ".".to_string()
+                    }
+                });
+                cell(offset, Column::Ascii, text, cx).px_0()
+            })))
+            .into_any_element()
+    }
+
+    fn render_status(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let len = self.item.read(cx).len();
+        let selection = self.selection();
+        let status = if len == 0 {
+            "Empty file".to_string()
+        } else if selection.len() > 1 {
+            format!(
+                "{:#x}-{:#x} ({} bytes selected of {len})",
+                selection.start,
+                selection.end - 1,
+                selection.len()
+            )
+        } else {
+            format!("Offset {:#x} of {len} bytes", self.cursor)
+        };
+        h_flex()
+            .px_2()
+            .py_1()
+            .border_t_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                Label::new(status)
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+    }
+}
+
+impl Render for HexEditor {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let row_count = self.item.read(cx).len().div_ceil(BYTES_PER_ROW);
+        let buffer_font = ThemeSettings::get_global(cx).buffer_font.family.clone();
+        let query_bar = self
+            .query_bar
+            .as_ref()
+            .map(|query_bar| self.render_query_bar(query_bar, cx));
+
+        v_flex()
+            .key_context("HexEditor")
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .on_action(cx.listener(Self::undo))
+            .on_action(cx.listener(Self::redo))
+            .on_action(cx.listener(Self::go_to_offset))
+            .on_action(cx.listener(Self::find_bytes))
+            .children(query_bar)
+            // Keystrokes are only handled while the rows are focused, so that text typed into
+            // the query bar doesn't edit the file.
+            .child(
+                div()
+                    .flex_1()
+                    .min_h_0()
+                    .p_2()
+                    .track_focus(&self.focus_handle)
+                    .on_key_down(cx.listener(Self::key_down))
+                    .font_family(buffer_font)
+                    .text_buffer(cx)
+                    .child(
+                        uniform_list(
+                            cx.view().clone(),
+                            "hex-rows",
+                            row_count,
+                            |this, range, cx| range.map(|row| this.render_row(row, cx)).collect(),
+                        )
+                        .size_full()
+                        .track_scroll(self.scroll_handle.clone()),
+                    ),
+            )
+            .child(self.render_status(cx))
+    }
+}
+
+impl EventEmitter<ItemEvent> for HexEditor {}
+
+impl FocusableView for HexEditor {
+    fn focus_handle(&self, _cx: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for HexEditor {
+    type Event = ItemEvent;
+
+    fn tab_content(&self, params: TabContentParams, cx: &WindowContext) -> AnyElement {
+        let abs_path = self.item.read(cx).abs_path();
+        let title = abs_path
+            .file_name()
+            .unwrap_or_else(|| abs_path.as_os_str())
+            .to_string_lossy()
+            .to_string();
+        Label::new(title)
+            .single_line()
+            .color(if params.selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .italic(params.preview)
+            .into_any_element()
+    }
+
+    fn tab_tooltip_text(&self, cx: &AppContext) -> Option<SharedString> {
+        Some(
+            self.item
+                .read(cx)
+                .abs_path()
+                .to_string_lossy()
+                .to_string()
+                .into(),
+        )
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &AppContext,
+        f: &mut dyn FnMut(EntityId, &dyn project::Item),
+    ) {
+        f(self.item.entity_id(), self.item.read(cx))
+    }
+
+    fn is_singleton(&self, _cx: &AppContext) -> bool {
+        true
+    }
+
+    fn clone_on_split(
+        &self,
+        _workspace_id: WorkspaceId,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<View<Self>> {
+        Some(cx.new_view(|cx| Self::new(self.item.clone(), cx)))
+    }
+
+    fn is_dirty(&self, cx: &AppContext) -> bool {
+        self.item.read(cx).is_dirty()
+    }
+
+    fn can_save(&self, _cx: &AppContext) -> bool {
+        true
+    }
+
+    fn save(
+        &mut self,
+        _format: bool,
+        _project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        self.item.update(cx, |item, cx| item.save(cx))
+    }
+
+    fn save_as(
+        &mut self,
+        _project: Model<Project>,
+        _path: ProjectPath,
+        _cx: &mut ViewContext<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        Task::ready(Err(anyhow!("the hex editor can't save to a new path")))
+    }
+
+    fn reload(
+        &mut self,
+        _project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        self.item.update(cx, |item, cx| item.reload(cx))
+    }
+}
+
+impl ProjectItem for HexEditor {
+    type Item = HexItem;
+
+    fn for_project_item(
+        _project: Model<Project>,
+        item: Model<Self::Item>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new(item, cx)
+    }
+}
@@ -0,0 +1,457 @@
+use anyhow::{anyhow, Result};
+use fs::Fs;
+use gpui::{AppContext, Context, Model, ModelContext, Task};
+use project::{Project, ProjectEntryId, ProjectPath};
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Extensions of files that are almost never text, which are opened in the hex editor rather
+/// than as a buffer. Images are left to the image viewer, and other files can be opened in the
+/// hex editor with [`crate::OpenInHexEditor`].
+const BINARY_EXTENSIONS: &[&str] = &[
+    "7z", "a", "bin", "bz2", "class", "db", "dll", "dylib", "exe", "gz", "jar", "lib", "mp3",
+    "mp4", "o", "otf", "pdb", "pyc", "so", "sqlite", "tar", "ttf", "wasm", "wav", "woff", "woff2",
+    "xz", "zip",
+];
+
+/// The largest file that the hex editor opens, since it holds all of a file's bytes in memory.
+const MAX_FILE_LEN: u64 = 64 * 1024 * 1024;
+
+/// A change to a single byte, recorded so that it can be undone.
+#[derive(Clone, Copy, Debug)]
+struct ByteEdit {
+    offset: usize,
+    old: u8,
+    new: u8,
+}
+
+/// The contents of a binary file that's open in the hex editor.
+pub struct HexItem {
+    fs: Arc<dyn Fs>,
+    abs_path: PathBuf,
+    project_path: ProjectPath,
+    entry_id: Option<ProjectEntryId>,
+    bytes: Vec<u8>,
+    undo_stack: Vec<ByteEdit>,
+    redo_stack: Vec<ByteEdit>,
+    /// The depth of the undo stack when the file was last loaded or saved, or `None` if that
+    /// state can no longer be reached by undoing or redoing.
+    saved_undo_depth: Option<usize>,
+}
+
+impl project::Item for HexItem {
+    fn try_open(
+        project: &Model<Project>,
+        path: &ProjectPath,
+        cx: &mut AppContext,
+    ) -> Option<Task<gpui::Result<Model<Self>>>> {
+        if !project.read(cx).is_local() {
+            return None;
+        }
+        let has_binary_extension = path
+            .path
+            .extension()
+            .and_then(OsStr::to_str)
+            .map_or(false, |extension| {
+                BINARY_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+            });
+        if !has_binary_extension {
+            return None;
+        }
+        Some(Self::open(project.clone(), path.clone(), cx))
+    }
+
+    fn entry_id(&self, _: &AppContext) -> Option<ProjectEntryId> {
+        self.entry_id
+    }
+
+    fn project_path(&self, _: &AppContext) -> Option<ProjectPath> {
+        Some(self.project_path.clone())
+    }
+}
+
+impl HexItem {
+    /// Loads a file's bytes, regardless of whether it looks like a binary file.
+    pub fn open(
+        project: Model<Project>,
+        path: ProjectPath,
+        cx: &mut AppContext,
+    ) -> Task<Result<Model<Self>>> {
+        cx.spawn(|mut cx| async move {
+            let (fs, abs_path, entry_id) = project.read_with(&cx, |project, cx| {
+                if !project.is_local() {
+                    return Err(anyhow!("the hex editor only supports local projects"));
+                }
+                let abs_path = project
+                    .absolute_path(&path, cx)
+                    .ok_or_else(|| anyhow!("failed to find the absolute path"))?;
+                let entry_id = project.entry_for_path(&path, cx).map(|entry| entry.id);
+                anyhow::Ok((project.fs().clone(), abs_path, entry_id))
+            })??;
+            let bytes = load_bytes(fs.as_ref(), &abs_path).await?;
+
+            cx.new_model(|_| Self {
+                fs,
+                abs_path,
+                project_path: path,
+                entry_id,
+                bytes,
+                undo_stack: Vec::new(),
+                redo_stack: Vec::new(),
+                saved_undo_depth: Some(0),
+            })
+        })
+    }
+
+    pub fn abs_path(&self) -> &PathBuf {
+        &self.abs_path
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.saved_undo_depth != Some(self.undo_stack.len())
+    }
+
+    /// Replaces the byte at `offset`. When `merge` is true and the last edit was to the same
+    /// byte, the two edits are undone together, such as when typing both digits of a byte.
+    pub fn overwrite(&mut self, offset: usize, byte: u8, merge: bool, cx: &mut ModelContext<Self>) {
+        let Some(old) = self.bytes.get(offset).copied() else {
+            return;
+        };
+        self.bytes[offset] = byte;
+        self.redo_stack.clear();
+        if self
+            .saved_undo_depth
+            .map_or(false, |depth| depth > self.undo_stack.len())
+        {
+            self.saved_undo_depth = None;
+        }
+
+        match self.undo_stack.last_mut() {
+            Some(last_edit)
+                if merge
+                    && last_edit.offset == offset
+                    && self.saved_undo_depth != Some(self.undo_stack.len()) =>
+            {
+                last_edit.new = byte;
+            }
+            _ => self.undo_stack.push(ByteEdit {
+                offset,
+                old,
+                new: byte,
+            }),
+        }
+        cx.notify();
+    }
+
+    /// Reverts the last edit, returning the offset of the byte it changed.
+    pub fn undo(&mut self, cx: &mut ModelContext<Self>) -> Option<usize> {
+        let edit = self.undo_stack.pop()?;
+        self.bytes[edit.offset] = edit.old;
+        self.redo_stack.push(edit);
+        cx.notify();
+        Some(edit.offset)
+    }
+
+    /// Reapplies the last undone edit, returning the offset of the byte it changed.
+    pub fn redo(&mut self, cx: &mut ModelContext<Self>) -> Option<usize> {
+        let edit = self.redo_stack.pop()?;
+        self.bytes[edit.offset] = edit.new;
+        self.undo_stack.push(edit);
+        cx.notify();
+        Some(edit.offset)
+    }
+
+    pub fn save(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        let fs = self.fs.clone();
+        let abs_path = self.abs_path.clone();
+        let bytes = self.bytes.clone();
+        let undo_depth = self.undo_stack.len();
+        cx.spawn(|this, mut cx| async move {
+            fs.save_bytes(&abs_path, &bytes).await?;
+            this.update(&mut cx, |this, cx| {
+                this.saved_undo_depth = Some(undo_depth);
+                cx.notify();
+            })
+        })
+    }
+
+    /// Discards any edits and loads the file's bytes again.
+    pub fn reload(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        let fs = self.fs.clone();
+        let abs_path = self.abs_path.clone();
+        cx.spawn(|this, mut cx| async move {
+            let bytes = load_bytes(fs.as_ref(), &abs_path).await?;
+            this.update(&mut cx, |this, cx| {
+                this.bytes = bytes;
+                this.undo_stack.clear();
+                this.redo_stack.clear();
+                this.saved_undo_depth = Some(0);
+                cx.notify();
+            })
+        })
+    }
+
+    /// Finds the next occurrence of `pattern` at or after `start`, wrapping around to the start
+    /// of the file.
+    pub fn find(&self, pattern: &[u8], start: usize) -> Option<usize> {
+        find_bytes(&self.bytes, pattern, start)
+    }
+}
+
+/// Reads all of a file's bytes, unless it's larger than the hex editor supports.
+async fn load_bytes(fs: &dyn Fs, abs_path: &Path) -> Result<Vec<u8>> {
+    let metadata = fs
+        .metadata(abs_path)
+        .await?
+        .ok_or_else(|| anyhow!("{abs_path:?} does not exist"))?;
+    if metadata.len > MAX_FILE_LEN {
+        return Err(anyhow!(
+            "{abs_path:?} is too large for the hex editor ({} bytes, at most {MAX_FILE_LEN} are supported)",
+            metadata.len
+        ));
+    }
+    fs.load_bytes(abs_path).await
+}
+
+pub(crate) fn find_bytes(bytes: &[u8], pattern: &[u8], start: usize) -> Option<usize> {
+    if pattern.is_empty() || pattern.len() > bytes.len() {
+        return None;
+    }
+    let position = |range_start: usize, range_end: usize| {
+        bytes[range_start..range_end]
+            .windows(pattern.len())
+            .position(|window| window == pattern)
+            .map(|ix| range_start + ix)
+    };
+    let start = start.min(bytes.len());
+    position(start, bytes.len())
+        .or_else(|| position(0, (start + pattern.len() - 1).min(bytes.len())))
+}
+
+/// Parses a search query into the bytes to search for. A query wrapped in double quotes matches
+/// its text, and anything else is read as hex digits, optionally grouped and prefixed with `0x`.
+pub(crate) fn parse_byte_pattern(query: &str) -> Result<Vec<u8>> {
+    let query = query.trim();
+    if let Some(text) = query
+        .strip_prefix('"')
+        .and_then(|query| query.strip_suffix('"'))
+    {
+        return Ok(text.as_bytes().to_vec());
+    }
+
+    let mut bytes = Vec::new();
+    for group in query.split_whitespace() {
+        let digits = group.strip_prefix("0x").unwrap_or(group);
+        if digits.len() % 2 != 0 {
+            return Err(anyhow!("{group:?} has an odd number of hex digits"));
+        }
+        for ix in (0..digits.len()).step_by(2) {
+            let byte = digits
+                .get(ix..ix + 2)
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                .ok_or_else(|| anyhow!("{group:?} is not a hex byte sequence"))?;
+            bytes.push(byte);
+        }
+    }
+    if bytes.is_empty() {
+        return Err(anyhow!("no bytes to search for"));
+    }
+    Ok(bytes)
+}
+
+/// Parses an offset entered by the user, in hex when prefixed with `0x` and in decimal otherwise.
+pub(crate) fn parse_offset(query: &str) -> Result<usize> {
+    let query = query.trim();
+    let offset = match query.strip_prefix("0x") {
+        Some(digits) => usize::from_str_radix(digits, 16),
+        None => query.parse(),
+    };
+    offset.map_err(|_| anyhow!("{query:?} is not a valid offset"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::path::Path;
+
+    #[gpui::test]
+    async fn test_open_binary_files(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "notes.dat": "plain text",
+                "lib.so": "not actually binary",
+            }),
+        )
+        .await;
+        // The start of an ELF header, which isn't valid UTF-8 and contains NUL bytes.
+        fs.insert_file(
+            "/dir/a.out",
+            b"\x7fELF\x02\x01\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x03\x00\x3e\x00\xf0\x8b\x00\x00"
+                .to_vec(),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let worktree_id = project.read_with(cx, |project, cx| {
+            project.worktrees().next().unwrap().read(cx).id()
+        });
+        let try_open = |path: &str, cx: &mut TestAppContext| {
+            let path = ProjectPath {
+                worktree_id,
+                path: Path::new(path).into(),
+            };
+            cx.update(|cx| <HexItem as project::Item>::try_open(&project, &path, cx))
+        };
+
+        // Files with a known binary extension are always opened, without looking at their
+        // contents.
+        let item = try_open("lib.so", cx).unwrap().await.unwrap();
+        item.read_with(cx, |item, _| {
+            assert_eq!(item.bytes(), b"not actually binary")
+        });
+
+        // Other files are left to the other items, even if they look binary.
+        assert!(try_open("a.out", cx).is_none());
+        assert!(try_open("notes.dat", cx).is_none());
+
+        // They can still be opened in the hex editor explicitly.
+        let path = ProjectPath {
+            worktree_id,
+            path: Path::new("a.out").into(),
+        };
+        let item = cx
+            .update(|cx| HexItem::open(project.clone(), path, cx))
+            .await
+            .unwrap();
+        item.read_with(cx, |item, _| assert_eq!(&item.bytes()[..4], b"\x7fELF"));
+    }
+
+    #[gpui::test]
+    async fn test_edit_undo_redo_and_save(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_file("/dir/data.bin", vec![0x00, 0x11, 0x22, 0x33])
+            .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let worktree_id = project.read_with(cx, |project, cx| {
+            project.worktrees().next().unwrap().read(cx).id()
+        });
+        let path = ProjectPath {
+            worktree_id,
+            path: Path::new("data.bin").into(),
+        };
+        let item = cx
+            .update(|cx| HexItem::open(project.clone(), path, cx))
+            .await
+            .unwrap();
+
+        item.update(cx, |item, cx| {
+            assert!(!item.is_dirty());
+
+            // Typing both digits of a byte is undone as a single edit.
+            item.overwrite(1, 0xa0, false, cx);
+            item.overwrite(1, 0xab, true, cx);
+            item.overwrite(2, 0xcd, true, cx);
+            assert_eq!(item.bytes(), [0x00, 0xab, 0xcd, 0x33]);
+            assert!(item.is_dirty());
+
+            assert_eq!(item.undo(cx), Some(2));
+            assert_eq!(item.undo(cx), Some(1));
+            assert_eq!(item.bytes(), [0x00, 0x11, 0x22, 0x33]);
+            assert!(!item.is_dirty());
+            assert_eq!(item.undo(cx), None);
+
+            assert_eq!(item.redo(cx), Some(1));
+            assert_eq!(item.bytes(), [0x00, 0xab, 0x22, 0x33]);
+            assert!(item.is_dirty());
+        });
+
+        item.update(cx, |item, cx| item.save(cx)).await.unwrap();
+        assert_eq!(
+            fs.load_bytes("/dir/data.bin".as_ref()).await.unwrap(),
+            [0x00, 0xab, 0x22, 0x33]
+        );
+        item.update(cx, |item, cx| {
+            assert!(!item.is_dirty());
+
+            // An edit after saving isn't merged into the saved one.
+            item.overwrite(1, 0xff, true, cx);
+            assert!(item.is_dirty());
+            item.undo(cx);
+            assert_eq!(item.bytes(), [0x00, 0xab, 0x22, 0x33]);
+            assert!(!item.is_dirty());
+
+            // Undoing past the save, then making a different edit, makes the saved state
+            // unreachable.
+            item.undo(cx);
+            item.overwrite(3, 0x44, false, cx);
+            item.undo(cx);
+            assert_eq!(item.bytes(), [0x00, 0x11, 0x22, 0x33]);
+            assert!(item.is_dirty());
+        });
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            Project::init_settings(cx);
+        });
+    }
+
+    #[test]
+    fn test_parse_byte_pattern() {
+        assert_eq!(
+            parse_byte_pattern("de ad BE ef").unwrap(),
+            [0xde, 0xad, 0xbe, 0xef]
+        );
+        assert_eq!(
+            parse_byte_pattern("0xdead 0x00").unwrap(),
+            [0xde, 0xad, 0x00]
+        );
+        assert_eq!(parse_byte_pattern("\"ELF\"").unwrap(), b"ELF");
+        assert!(parse_byte_pattern("abc").is_err());
+        assert!(parse_byte_pattern("zz").is_err());
+        assert!(parse_byte_pattern("  ").is_err());
+    }
+
+    #[test]
+    fn test_parse_offset() {
+        assert_eq!(parse_offset("0x1f").unwrap(), 31);
+        assert_eq!(parse_offset(" 42 ").unwrap(), 42);
+        assert!(parse_offset("0xg").is_err());
+        assert!(parse_offset("-1").is_err());
+    }
+
+    #[test]
+    fn test_find_bytes() {
+        let bytes = b"abcabcab";
+        assert_eq!(find_bytes(bytes, b"abc", 0), Some(0));
+        assert_eq!(find_bytes(bytes, b"abc", 1), Some(3));
+        assert_eq!(find_bytes(bytes, b"abc", 4), Some(0));
+        assert_eq!(find_bytes(bytes, b"cab", 6), Some(2));
+        assert_eq!(find_bytes(bytes, b"xyz", 0), None);
+        assert_eq!(find_bytes(bytes, b"", 0), None);
+    }
+}
@@ -90,6 +90,16 @@ pub struct Buffer {
     /// The version vector when this buffer was last loaded from
     /// or saved to disk.
     saved_version: clock::Global,
+    /// The character encoding of the buffer's file, which is preserved
+    /// when the buffer is saved or reloaded.
+    encoding: Encoding,
+    /// Whether the buffer's file is large enough that the buffer is read-only,
+    /// loaded incrementally, and never parsed or diffed.
+    large_file: bool,
+    /// How much of the buffer's large file has been loaded, while it's loading.
+    loading_progress: Option<f32>,
+    /// Why the buffer's large file stopped loading before reaching its end.
+    loading_error: Option<String>,
     transaction_depth: usize,
     was_dirty_before_starting_transaction: Option<bool>,
     reload_task: Option<Task<Result<()>>>,
@@ -375,8 +385,8 @@ pub trait LocalFile: File {
     /// Returns the absolute path of this file.
     fn abs_path(&self, cx: &AppContext) -> PathBuf;
 
-    /// Loads the file's contents from disk.
-    fn load(&self, cx: &AppContext) -> Task<Result<String>>;
+    /// Loads the file's contents from disk, decoding them with the given encoding.
+    fn load(&self, encoding: Encoding, cx: &AppContext) -> Task<Result<(String, Encoding)>>;
 
     /// Called when the buffer is reloaded from disk.
     fn buffer_reloaded(
@@ -575,6 +585,10 @@ impl Buffer {
         ));
         this.saved_version = proto::deserialize_version(&message.saved_version);
         this.saved_mtime = message.saved_mtime.map(|time| time.into());
+        if let Some(encoding) = message.encoding.as_deref().and_then(Encoding::from_name) {
+            this.encoding = encoding;
+        }
+        this.large_file = message.large_file;
         Ok(this)
     }
 
@@ -588,6 +602,8 @@ impl Buffer {
             line_ending: proto::serialize_line_ending(self.line_ending()) as i32,
             saved_version: proto::serialize_version(&self.saved_version),
             saved_mtime: self.saved_mtime.map(|time| time.into()),
+            encoding: Some(self.encoding.to_string()),
+            large_file: self.large_file,
         }
     }
 
@@ -638,6 +654,20 @@ impl Buffer {
         })
     }
 
+    /// Assign an encoding to the buffer, returning the buffer.
+    pub fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Marks the buffer as holding a large file that will be loaded incrementally,
+    /// returning the buffer.
+    pub fn with_large_file(mut self) -> Self {
+        self.large_file = true;
+        self.loading_progress = Some(0.);
+        self
+    }
+
     /// Assign a language to the buffer, returning the buffer.
     pub fn with_language(mut self, language: Arc<Language>, cx: &mut ModelContext<Self>) -> Self {
         self.set_language(Some(language), cx);
@@ -666,6 +696,10 @@ impl Buffer {
         Self {
             saved_mtime,
             saved_version: buffer.version(),
+            encoding: Encoding::default(),
+            large_file: false,
+            loading_progress: None,
+            loading_error: None,
             reload_task: None,
             transaction_depth: 0,
             was_dirty_before_starting_transaction: None,
@@ -751,6 +785,69 @@ impl Buffer {
         self.saved_mtime
     }
 
+    /// The character encoding of the buffer's file.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Whether the buffer holds a large file, for which syntax parsing, git
+    /// diffs and language servers are disabled.
+    pub fn is_large_file(&self) -> bool {
+        self.large_file
+    }
+
+    /// The fraction of the buffer's large file that has been loaded, while it's loading.
+    pub fn loading_progress(&self) -> Option<f32> {
+        self.loading_progress
+    }
+
+    /// The error that stopped the buffer's large file from loading completely, in which case the
+    /// buffer only holds the text loaded before it.
+    pub fn loading_error(&self) -> Option<&str> {
+        self.loading_error.as_deref()
+    }
+
+    /// Records that the buffer's large file couldn't be loaded completely. The loading progress is
+    /// kept and the buffer is marked as conflicting with its file, so that it isn't mistaken for
+    /// the file's whole contents.
+    pub fn fail_loading(&mut self, error: String, cx: &mut ModelContext<Self>) {
+        self.loading_error = Some(error);
+        self.has_conflict = true;
+        cx.emit(Event::DirtyChanged);
+        cx.notify();
+    }
+
+    /// Appends text that was loaded from the buffer's large file, without
+    /// recording it in the undo history or marking the buffer as dirty.
+    ///
+    /// Loading the file isn't an edit, so no operation is sent to collaborators.
+    pub fn append_loaded_text(
+        &mut self,
+        text: &str,
+        line_ending: LineEnding,
+        progress: Option<f32>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let old_version = self.version();
+        let end = self.len();
+        self.text.edit([(end..end, text)]);
+        if let Some(transaction_id) = self.finalize_last_transaction().map(|t| t.id) {
+            self.forget_transaction(transaction_id);
+        }
+        self.text.set_line_ending(line_ending);
+        self.saved_version = self.version();
+        self.loading_progress = progress;
+        self.did_edit(&old_version, false, cx);
+        cx.emit(Event::DirtyChanged);
+        cx.notify();
+    }
+
+    /// Assign the encoding with which the buffer will be saved.
+    pub fn set_encoding(&mut self, encoding: Encoding, cx: &mut ModelContext<Self>) {
+        self.encoding = encoding;
+        cx.notify();
+    }
+
     /// Assign a language to the buffer.
     pub fn set_language(&mut self, language: Option<Arc<Language>>, cx: &mut ModelContext<Self>) {
         self.parse_count += 1;
@@ -794,17 +891,45 @@ impl Buffer {
         cx: &mut ModelContext<Self>,
     ) -> oneshot::Receiver<Option<Transaction>> {
         let (tx, rx) = futures::channel::oneshot::channel();
+        let reload = self.reload_with_encoding(self.encoding, cx);
+        cx.spawn(|_, _| async move {
+            if let Ok(Ok(transaction)) = reload.await {
+                tx.send(transaction).ok();
+            }
+        })
+        .detach();
+        rx
+    }
+
+    /// Reloads the contents of the buffer from disk, decoding them with the given encoding.
+    /// Fails if the file's contents aren't valid in that encoding, leaving the buffer unchanged.
+    pub fn reload_with_encoding(
+        &mut self,
+        encoding: Encoding,
+        cx: &mut ModelContext<Self>,
+    ) -> oneshot::Receiver<Result<Option<Transaction>>> {
+        let (tx, rx) = futures::channel::oneshot::channel();
+        if self.large_file {
+            tx.send(Ok(None)).ok();
+            return rx;
+        }
         let prev_version = self.text.version();
         self.reload_task = Some(cx.spawn(|this, mut cx| async move {
             let Some((new_mtime, new_text)) = this.update(&mut cx, |this, cx| {
                 let file = this.file.as_ref()?.as_local()?;
-                Some((file.mtime(), file.load(cx)))
+                Some((file.mtime(), file.load(encoding, cx)))
             })?
             else {
                 return Ok(());
             };
 
-            let new_text = new_text.await?;
+            let (new_text, new_encoding) = match new_text.await {
+                Ok(loaded) => loaded,
+                Err(error) => {
+                    tx.send(Err(error)).ok();
+                    return Ok(());
+                }
+            };
             let diff = this
                 .update(&mut cx, |this, cx| this.diff(new_text.clone(), cx))?
                 .await;
@@ -812,8 +937,9 @@ impl Buffer {
                 if this.version() == diff.base_version {
                     this.finalize_last_transaction();
                     this.apply_diff(diff, cx);
-                    tx.send(this.finalize_last_transaction().cloned()).ok();
+                    tx.send(Ok(this.finalize_last_transaction().cloned())).ok();
                     this.has_conflict = false;
+                    this.encoding = new_encoding;
                     this.did_reload(this.version(), this.line_ending(), new_mtime, cx);
                 } else {
                     if !diff.edits.is_empty()
@@ -905,6 +1031,9 @@ impl Buffer {
     /// Sets the text that will be used to compute a Git diff
     /// against the buffer text.
     pub fn set_diff_base(&mut self, diff_base: Option<String>, cx: &mut ModelContext<Self>) {
+        if self.large_file {
+            return;
+        }
         self.diff_base = diff_base
             .map(|mut raw_diff_base| {
                 LineEnding::normalize(&mut raw_diff_base);
@@ -1034,7 +1163,7 @@ impl Buffer {
     /// for the same buffer, we only initiate a new parse if we are not already
     /// parsing in the background.
     pub fn reparse(&mut self, cx: &mut ModelContext<Self>) {
-        if self.parsing_in_background {
+        if self.parsing_in_background || self.large_file {
             return;
         }
         let language = if let Some(language) = self.language.clone() {
@@ -336,6 +336,35 @@ fn test_edit_events(cx: &mut gpui::AppContext) {
     );
 }
 
+#[gpui::test]
+fn test_append_loaded_text(cx: &mut gpui::AppContext) {
+    let buffer = cx.new_model(|cx| Buffer::local("", cx).with_large_file());
+    let operations = Arc::new(Mutex::new(Vec::new()));
+    cx.subscribe(&buffer, {
+        let operations = operations.clone();
+        move |_, event, _| {
+            if let Event::Operation(operation) = event {
+                operations.lock().push(operation.clone());
+            }
+        }
+    })
+    .detach();
+
+    buffer.update(cx, |buffer, cx| {
+        buffer.append_loaded_text("one\ntwo\n", LineEnding::Windows, Some(0.5), cx);
+        assert_eq!(buffer.loading_progress(), Some(0.5));
+        buffer.append_loaded_text("three\n", LineEnding::Windows, None, cx);
+        assert_eq!(buffer.text(), "one\ntwo\nthree\n");
+        assert_eq!(buffer.line_ending(), LineEnding::Windows);
+        assert_eq!(buffer.loading_progress(), None);
+        assert!(!buffer.is_dirty());
+        assert!(buffer.undo(cx).is_none());
+    });
+
+    // Loading the file isn't an edit, so collaborators aren't sent any operations.
+    assert!(operations.lock().is_empty());
+}
+
 #[gpui::test]
 async fn test_apply_diff(cx: &mut TestAppContext) {
     let text = "a\nbb\nccc\ndddd\neeeee\nffffff\n";
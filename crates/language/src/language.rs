@@ -12,6 +12,7 @@ mod highlight_map;
 mod language_registry;
 pub mod language_settings;
 mod outline;
+mod project_manifest;
 pub mod proto;
 mod syntax_map;
 mod task_context;
@@ -73,8 +74,9 @@ pub use language_registry::{
 };
 pub use lsp::LanguageServerId;
 pub use outline::{Outline, OutlineItem};
+pub use project_manifest::{ManifestProvider, ProjectManifest};
 pub use syntax_map::{OwnedSyntaxLayer, SyntaxLayer};
-pub use text::LineEnding;
+pub use text::{Encoding, LineEnding, LineEndingNormalizer};
 pub use tree_sitter::{Node, Parser, Tree, TreeCursor};
 
 /// Initializes the `language` crate.
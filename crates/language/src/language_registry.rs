@@ -4,7 +4,7 @@ use crate::{
     },
     task_context::ContextProvider,
     with_parser, CachedLspAdapter, File, Language, LanguageConfig, LanguageId, LanguageMatcher,
-    LanguageServerName, LspAdapter, LspAdapterDelegate, PLAIN_TEXT,
+    LanguageServerName, LspAdapter, LspAdapterDelegate, ManifestProvider, PLAIN_TEXT,
 };
 use anyhow::{anyhow, Context as _, Result};
 use collections::{hash_map, HashMap};
@@ -49,6 +49,7 @@ struct LanguageRegistryState {
     available_lsp_adapters:
         HashMap<LanguageServerName, Arc<dyn Fn() -> Arc<CachedLspAdapter> + 'static + Send + Sync>>,
     loading_languages: HashMap<LanguageId, Vec<oneshot::Sender<Result<Arc<Language>>>>>,
+    manifest_providers: Vec<Arc<dyn ManifestProvider>>,
     subscription: (watch::Sender<()>, watch::Receiver<()>),
     theme: Option<Arc<Theme>>,
     version: usize,
@@ -158,6 +159,7 @@ impl LanguageRegistry {
                 loading_languages: Default::default(),
                 lsp_adapters: Default::default(),
                 available_lsp_adapters: HashMap::default(),
+                manifest_providers: Vec::new(),
                 subscription: watch::channel(),
                 theme: Default::default(),
                 version: 0,
@@ -259,6 +261,15 @@ impl LanguageRegistry {
             .push(CachedLspAdapter::new(adapter, true));
     }
 
+    /// Registers a provider that describes projects from the manifest files of an ecosystem.
+    pub fn register_manifest_provider(&self, provider: Arc<dyn ManifestProvider>) {
+        self.state.write().manifest_providers.push(provider);
+    }
+
+    pub fn manifest_providers(&self) -> Vec<Arc<dyn ManifestProvider>> {
+        self.state.read().manifest_providers.clone()
+    }
+
     pub fn register_secondary_lsp_adapter(
         &self,
         language_name: Arc<str>,
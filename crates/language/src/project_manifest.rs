@@ -0,0 +1,27 @@
+use anyhow::Result;
+
+/// Summarizes a project from the manifest file of its ecosystem, such as `Cargo.toml` or
+/// `package.json`, so that the assistant can be told what kind of project it's working in.
+///
+/// Providers are registered with [`crate::LanguageRegistry::register_manifest_provider`].
+pub trait ManifestProvider: Send + Sync {
+    /// The name of the ecosystem, e.g. "Rust" or "Node.js".
+    fn ecosystem(&self) -> &'static str;
+
+    /// The name of the manifest file, looked up at the root of each worktree.
+    fn file_name(&self) -> &'static str;
+
+    /// Parses the contents of the manifest file.
+    fn parse(&self, contents: &str) -> Result<ProjectManifest>;
+}
+
+/// The parts of a manifest that are worth describing to a language model.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProjectManifest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// The paths or patterns of the workspace members, for manifests that describe several packages.
+    pub members: Vec<String>,
+    /// The names of the project's direct dependencies.
+    pub dependencies: Vec<String>,
+}
@@ -317,8 +317,50 @@ async fn get_cached_server_binary(container_dir: PathBuf) -> Option<LanguageServ
     .log_err()
 }
 
+pub struct CMakeManifestProvider;
+
+impl ManifestProvider for CMakeManifestProvider {
+    fn ecosystem(&self) -> &'static str {
+        "C/C++ (CMake)"
+    }
+
+    fn file_name(&self) -> &'static str {
+        "CMakeLists.txt"
+    }
+
+    fn parse(&self, contents: &str) -> Result<ProjectManifest> {
+        let mut manifest = ProjectManifest::default();
+        for (command, arguments) in cmake_commands(contents) {
+            let first_argument = arguments.split_whitespace().next().map(str::to_string);
+            match command.to_lowercase().as_str() {
+                "project" => manifest.name = manifest.name.or(first_argument),
+                "add_subdirectory" => manifest.members.extend(first_argument),
+                "find_package" => manifest.dependencies.extend(first_argument),
+                _ => {}
+            }
+        }
+        Ok(manifest)
+    }
+}
+
+/// Returns the name and the raw arguments of each CMake command invocation.
+fn cmake_commands(contents: &str) -> impl Iterator<Item = (&str, &str)> {
+    contents.lines().filter_map(|line| {
+        let line = line.trim();
+        if line.starts_with('#') {
+            return None;
+        }
+        let (command, arguments) = line.split_once('(')?;
+        let arguments = arguments
+            .rsplit_once(')')
+            .map_or(arguments, |(args, _)| args);
+        Some((command.trim(), arguments))
+    })
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
     use gpui::{BorrowAppContext, Context, TestAppContext};
     use language::{language_settings::AllLanguageSettings, AutoindentMode, Buffer};
     use settings::SettingsStore;
@@ -363,4 +405,22 @@ mod tests {
             buffer
         });
     }
+
+    #[test]
+    fn test_cmake_manifest() {
+        let manifest = CMakeManifestProvider
+            .parse(
+                "
+                cmake_minimum_required(VERSION 3.20)
+                project(engine LANGUAGES CXX)
+                # add_subdirectory(disabled)
+                add_subdirectory(src)
+                find_package(Boost REQUIRED)
+            ",
+            )
+            .unwrap();
+        assert_eq!(manifest.name.as_deref(), Some("engine"));
+        assert_eq!(manifest.members, ["src"]);
+        assert_eq!(manifest.dependencies, ["Boost"]);
+    }
 }
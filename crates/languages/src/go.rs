@@ -438,6 +438,50 @@ fn adjust_runs(
     runs
 }
 
+pub struct GoModManifestProvider;
+
+impl ManifestProvider for GoModManifestProvider {
+    fn ecosystem(&self) -> &'static str {
+        "Go"
+    }
+
+    fn file_name(&self) -> &'static str {
+        "go.mod"
+    }
+
+    fn parse(&self, contents: &str) -> Result<ProjectManifest> {
+        let mut manifest = ProjectManifest::default();
+        let mut in_require_block = false;
+        for line in contents.lines() {
+            let line = line.trim();
+            if in_require_block {
+                if line == ")" {
+                    in_require_block = false;
+                } else if !line.is_empty() && !line.starts_with("//") {
+                    push_go_requirement(line, &mut manifest.dependencies);
+                }
+            } else if let Some(module) = line.strip_prefix("module ") {
+                manifest.name = Some(module.trim().to_string());
+            } else if line.starts_with("require (") || line == "require(" {
+                in_require_block = true;
+            } else if let Some(requirement) = line.strip_prefix("require ") {
+                push_go_requirement(requirement, &mut manifest.dependencies);
+            }
+        }
+        Ok(manifest)
+    }
+}
+
+fn push_go_requirement(requirement: &str, dependencies: &mut Vec<String>) {
+    // Indirect requirements are dependencies of dependencies, so they aren't interesting.
+    if requirement.ends_with("// indirect") {
+        return;
+    }
+    if let Some(module) = requirement.split_whitespace().next() {
+        dependencies.push(module.to_string());
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -532,4 +576,29 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn test_go_mod_manifest() {
+        let manifest = GoModManifestProvider
+            .parse(
+                "
+                module github.com/example/server
+
+                go 1.22
+
+                require github.com/google/uuid v1.6.0
+
+                require (
+                    golang.org/x/sync v0.7.0
+                    golang.org/x/sys v0.20.0 // indirect
+                )
+            ",
+            )
+            .unwrap();
+        assert_eq!(manifest.name.as_deref(), Some("github.com/example/server"));
+        assert_eq!(
+            manifest.dependencies,
+            ["github.com/google/uuid", "golang.org/x/sync"]
+        );
+    }
 }
@@ -15,6 +15,7 @@ mod c;
 mod css;
 mod go;
 mod json;
+mod manifests;
 mod python;
 mod rust;
 mod tailwind;
@@ -157,6 +158,20 @@ pub fn init(
     );
     language!("proto");
 
+    // Register the manifests that the assistant uses to describe a project.
+    let manifest_providers: [Arc<dyn ManifestProvider>; 7] = [
+        Arc::new(rust::CargoManifestProvider),
+        Arc::new(typescript::PackageJsonManifestProvider),
+        Arc::new(python::PyprojectManifestProvider),
+        Arc::new(go::GoModManifestProvider),
+        Arc::new(manifests::GemfileManifestProvider),
+        Arc::new(manifests::MavenManifestProvider),
+        Arc::new(c::CMakeManifestProvider),
+    ];
+    for provider in manifest_providers {
+        languages.register_manifest_provider(provider);
+    }
+
     // Register globally available language servers.
     //
     // This will allow users to add support for a built-in language server (e.g., Tailwind)
@@ -0,0 +1,168 @@
+//! Manifests of ecosystems whose languages are provided by extensions, along
+//! with helpers shared by the manifests of the built-in languages.
+
+use anyhow::Result;
+use language::{ManifestProvider, ProjectManifest};
+use toml::Value as TomlValue;
+
+pub struct GemfileManifestProvider;
+
+impl ManifestProvider for GemfileManifestProvider {
+    fn ecosystem(&self) -> &'static str {
+        "Ruby"
+    }
+
+    fn file_name(&self) -> &'static str {
+        "Gemfile"
+    }
+
+    fn parse(&self, contents: &str) -> Result<ProjectManifest> {
+        let dependencies = contents
+            .lines()
+            .filter_map(|line| {
+                let arguments = line.trim().strip_prefix("gem ")?.trim_start();
+                let quote = arguments
+                    .chars()
+                    .next()
+                    .filter(|c| *c == '"' || *c == '\'')?;
+                let name = arguments[1..].split(quote).next()?;
+                Some(name.to_string())
+            })
+            .collect();
+        Ok(ProjectManifest {
+            dependencies,
+            ..Default::default()
+        })
+    }
+}
+
+pub struct MavenManifestProvider;
+
+impl ManifestProvider for MavenManifestProvider {
+    fn ecosystem(&self) -> &'static str {
+        "Java (Maven)"
+    }
+
+    fn file_name(&self) -> &'static str {
+        "pom.xml"
+    }
+
+    fn parse(&self, contents: &str) -> Result<ProjectManifest> {
+        // The project's own coordinates come before its dependencies, but after the parent's.
+        let project = xml_elements(contents, "parent")
+            .into_iter()
+            .next()
+            .and_then(|parent| contents.split_once(parent))
+            .map_or(contents, |(_, rest)| rest);
+        let project = project.split("<dependencies>").next().unwrap_or(project);
+        Ok(ProjectManifest {
+            name: xml_elements(project, "name")
+                .into_iter()
+                .chain(xml_elements(project, "artifactId"))
+                .next()
+                .map(str::to_string),
+            description: xml_elements(project, "description")
+                .into_iter()
+                .next()
+                .map(str::to_string),
+            members: xml_elements(contents, "module")
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+            dependencies: xml_elements(contents, "dependency")
+                .into_iter()
+                .filter_map(|dependency| {
+                    let group = xml_elements(dependency, "groupId").into_iter().next();
+                    let artifact = xml_elements(dependency, "artifactId").into_iter().next()?;
+                    Some(match group {
+                        Some(group) => format!("{group}:{artifact}"),
+                        None => artifact.to_string(),
+                    })
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Returns the trimmed contents of every `<tag>…</tag>` element, without parsing the XML.
+fn xml_elements<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let open = format!("<{tag}>");
+    let close = format!("</{tag}>");
+    let mut elements = Vec::new();
+    let mut rest = xml;
+    while let Some((_, after_open)) = rest.split_once(&open) {
+        let Some((element, after_close)) = after_open.split_once(&close) else {
+            break;
+        };
+        elements.push(element.trim());
+        rest = after_close;
+    }
+    elements
+}
+
+pub(crate) fn toml_str(table: Option<&TomlValue>, key: &str) -> Option<String> {
+    Some(table?.get(key)?.as_str()?.to_string())
+}
+
+pub(crate) fn toml_strings(array: Option<&TomlValue>) -> Vec<String> {
+    array
+        .and_then(|array| array.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|value| Some(value.as_str()?.to_string()))
+        .collect()
+}
+
+pub(crate) fn toml_keys(table: Option<&TomlValue>) -> Vec<String> {
+    table
+        .and_then(|table| table.as_table())
+        .map(|table| table.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gemfile_manifest() {
+        let manifest = GemfileManifestProvider
+            .parse(
+                r#"
+                source "https://rubygems.org"
+                gem "rails", "~> 7.1"
+                gem 'puma'
+            "#,
+            )
+            .unwrap();
+        assert_eq!(manifest.dependencies, ["rails", "puma"]);
+    }
+
+    #[test]
+    fn test_maven_manifest() {
+        let manifest = MavenManifestProvider
+            .parse(
+                "
+                <project>
+                    <parent>
+                        <artifactId>parent</artifactId>
+                    </parent>
+                    <artifactId>service</artifactId>
+                    <modules>
+                        <module>api</module>
+                    </modules>
+                    <dependencies>
+                        <dependency>
+                            <groupId>org.slf4j</groupId>
+                            <artifactId>slf4j-api</artifactId>
+                        </dependency>
+                    </dependencies>
+                </project>
+            ",
+            )
+            .unwrap();
+        assert_eq!(manifest.name.as_deref(), Some("service"));
+        assert_eq!(manifest.members, ["api"]);
+        assert_eq!(manifest.dependencies, ["org.slf4j:slf4j-api"]);
+    }
+}
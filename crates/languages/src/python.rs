@@ -1,6 +1,9 @@
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 use async_trait::async_trait;
-use language::{ContextProviderWithTasks, LanguageServerName, LspAdapter, LspAdapterDelegate};
+use language::{
+    ContextProviderWithTasks, LanguageServerName, LspAdapter, LspAdapterDelegate, ManifestProvider,
+    ProjectManifest,
+};
 use lsp::LanguageServerBinary;
 use node_runtime::NodeRuntime;
 use std::{
@@ -10,8 +13,11 @@ use std::{
     sync::Arc,
 };
 use task::{TaskTemplate, TaskTemplates, VariableName};
+use toml::Value as TomlValue;
 use util::ResultExt;
 
+use crate::manifests::{toml_keys, toml_str, toml_strings};
+
 const SERVER_PATH: &str = "node_modules/pyright/langserver.index.js";
 
 fn server_binary_arguments(server_path: &Path) -> Vec<OsString> {
@@ -198,8 +204,59 @@ pub(super) fn python_task_context() -> ContextProviderWithTasks {
     ]))
 }
 
+pub struct PyprojectManifestProvider;
+
+impl ManifestProvider for PyprojectManifestProvider {
+    fn ecosystem(&self) -> &'static str {
+        "Python"
+    }
+
+    fn file_name(&self) -> &'static str {
+        "pyproject.toml"
+    }
+
+    fn parse(&self, contents: &str) -> Result<ProjectManifest> {
+        let manifest: TomlValue = toml::from_str(contents).context("invalid pyproject.toml")?;
+        let project = manifest.get("project");
+        let tool = manifest.get("tool");
+        let poetry = tool.and_then(|tool| tool.get("poetry"));
+        let uv_workspace = tool
+            .and_then(|tool| tool.get("uv"))
+            .and_then(|uv| uv.get("workspace"));
+
+        // PEP 621 lists requirement specifiers, while Poetry uses a table keyed by package name.
+        let mut dependencies =
+            toml_strings(project.and_then(|project| project.get("dependencies")))
+                .iter()
+                .map(|requirement| python_requirement_name(requirement).to_string())
+                .collect::<Vec<_>>();
+        dependencies.extend(
+            toml_keys(poetry.and_then(|poetry| poetry.get("dependencies")))
+                .into_iter()
+                .filter(|name| name != "python"),
+        );
+
+        Ok(ProjectManifest {
+            name: toml_str(project, "name").or_else(|| toml_str(poetry, "name")),
+            description: toml_str(project, "description")
+                .or_else(|| toml_str(poetry, "description")),
+            members: toml_strings(uv_workspace.and_then(|workspace| workspace.get("members"))),
+            dependencies,
+        })
+    }
+}
+
+/// Extracts the package name from a requirement specifier, such as `requests>=2.0; python_version > "3.8"`.
+fn python_requirement_name(requirement: &str) -> &str {
+    requirement
+        .split(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_' || c == '.'))
+        .next()
+        .unwrap_or(requirement)
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
     use gpui::{BorrowAppContext, Context, ModelContext, TestAppContext};
     use language::{language_settings::AllLanguageSettings, AutoindentMode, Buffer};
     use settings::SettingsStore;
@@ -314,4 +371,23 @@ mod tests {
             buffer
         });
     }
+
+    #[test]
+    fn test_pyproject_manifest() {
+        let manifest = PyprojectManifestProvider
+            .parse(
+                r#"
+                [project]
+                name = "tool"
+                dependencies = ["requests>=2.0", "rich[jupyter] ; python_version > '3.8'"]
+
+                [tool.uv.workspace]
+                members = ["packages/*"]
+            "#,
+            )
+            .unwrap();
+        assert_eq!(manifest.name.as_deref(), Some("tool"));
+        assert_eq!(manifest.members, ["packages/*"]);
+        assert_eq!(manifest.dependencies, ["requests", "rich"]);
+    }
 }
@@ -19,8 +19,11 @@ use std::{
     sync::Arc,
 };
 use task::{TaskTemplate, TaskTemplates, TaskVariables, VariableName};
+use toml::Value as TomlValue;
 use util::{fs::remove_matching, maybe, ResultExt};
 
+use crate::manifests::{toml_keys, toml_str, toml_strings};
+
 pub struct RustLspAdapter;
 
 impl RustLspAdapter {
@@ -482,6 +485,35 @@ async fn get_cached_server_binary(container_dir: PathBuf) -> Option<LanguageServ
     .log_err()
 }
 
+pub struct CargoManifestProvider;
+
+impl ManifestProvider for CargoManifestProvider {
+    fn ecosystem(&self) -> &'static str {
+        "Rust"
+    }
+
+    fn file_name(&self) -> &'static str {
+        "Cargo.toml"
+    }
+
+    fn parse(&self, contents: &str) -> Result<ProjectManifest> {
+        let manifest: TomlValue = toml::from_str(contents).context("invalid Cargo.toml")?;
+        let package = manifest.get("package");
+        let workspace = manifest.get("workspace");
+        Ok(ProjectManifest {
+            name: toml_str(package, "name"),
+            description: toml_str(package, "description"),
+            members: toml_strings(workspace.and_then(|workspace| workspace.get("members"))),
+            dependencies: toml_keys(manifest.get("dependencies"))
+                .into_iter()
+                .chain(toml_keys(
+                    workspace.and_then(|workspace| workspace.get("dependencies")),
+                ))
+                .collect(),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::num::NonZeroU32;
@@ -778,4 +810,28 @@ mod tests {
             assert_eq!(package_name_from_pkgid(input), Some(expected));
         }
     }
+
+    #[test]
+    fn test_cargo_manifest() {
+        let manifest = CargoManifestProvider
+            .parse(
+                r#"
+                [package]
+                name = "zed"
+                description = "The editor"
+
+                [workspace]
+                members = ["crates/*"]
+
+                [dependencies]
+                anyhow = "1.0"
+                gpui = { path = "crates/gpui" }
+            "#,
+            )
+            .unwrap();
+        assert_eq!(manifest.name.as_deref(), Some("zed"));
+        assert_eq!(manifest.description.as_deref(), Some("The editor"));
+        assert_eq!(manifest.members, ["crates/*"]);
+        assert_eq!(manifest.dependencies, ["anyhow", "gpui"]);
+    }
 }
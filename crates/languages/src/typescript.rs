@@ -1,11 +1,13 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context as _, Result};
 use async_compression::futures::bufread::GzipDecoder;
 use async_tar::Archive;
 use async_trait::async_trait;
 use collections::HashMap;
 use gpui::AsyncAppContext;
 use http::github::{build_tarball_url, GitHubLspBinaryVersion};
-use language::{LanguageServerName, LspAdapter, LspAdapterDelegate};
+use language::{
+    LanguageServerName, LspAdapter, LspAdapterDelegate, ManifestProvider, ProjectManifest,
+};
 use lsp::{CodeActionKind, LanguageServerBinary};
 use node_runtime::NodeRuntime;
 use project::project_settings::ProjectSettings;
@@ -424,8 +426,46 @@ async fn get_cached_eslint_server_binary(
     .log_err()
 }
 
+pub struct PackageJsonManifestProvider;
+
+impl ManifestProvider for PackageJsonManifestProvider {
+    fn ecosystem(&self) -> &'static str {
+        "Node.js"
+    }
+
+    fn file_name(&self) -> &'static str {
+        "package.json"
+    }
+
+    fn parse(&self, contents: &str) -> Result<ProjectManifest> {
+        let manifest: Value = serde_json::from_str(contents).context("invalid package.json")?;
+        let json_str = |key: &str| manifest.get(key)?.as_str().map(str::to_string);
+        // Workspaces are either a list of globs, or an object with a `packages` list.
+        let workspaces = manifest.get("workspaces").and_then(|workspaces| {
+            workspaces
+                .as_array()
+                .or_else(|| workspaces.get("packages")?.as_array())
+        });
+        Ok(ProjectManifest {
+            name: json_str("name"),
+            description: json_str("description"),
+            members: workspaces
+                .into_iter()
+                .flatten()
+                .filter_map(|member| Some(member.as_str()?.to_string()))
+                .collect(),
+            dependencies: ["dependencies", "devDependencies", "peerDependencies"]
+                .into_iter()
+                .filter_map(|key| manifest.get(key)?.as_object())
+                .flat_map(|dependencies| dependencies.keys().cloned())
+                .collect(),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
     use gpui::{Context, TestAppContext};
     use unindent::Unindent;
 
@@ -466,4 +506,23 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_package_json_manifest() {
+        let manifest = PackageJsonManifestProvider
+            .parse(
+                r#"
+                {
+                    "name": "web",
+                    "workspaces": { "packages": ["packages/*"] },
+                    "dependencies": { "react": "^18.0.0" },
+                    "devDependencies": { "typescript": "^5.0.0" }
+                }
+            "#,
+            )
+            .unwrap();
+        assert_eq!(manifest.name.as_deref(), Some("web"));
+        assert_eq!(manifest.members, ["packages/*"]);
+        assert_eq!(manifest.dependencies, ["react", "typescript"]);
+    }
 }
@@ -22,7 +22,6 @@ use smol::process::windows::CommandExt;
 use std::{
     ffi::OsString,
     fmt,
-    io::Write,
     path::PathBuf,
     pin::Pin,
     str::{self, FromStr as _},
@@ -425,20 +424,7 @@ impl LanguageServer {
         loop {
             buffer.clear();
 
-            read_headers(&mut stdout, &mut buffer).await?;
-
-            let headers = std::str::from_utf8(&buffer)?;
-
-            let message_len = headers
-                .split('\n')
-                .find(|line| line.starts_with(CONTENT_LEN_HEADER))
-                .and_then(|line| line.strip_prefix(CONTENT_LEN_HEADER))
-                .ok_or_else(|| anyhow!("invalid LSP message header {headers:?}"))?
-                .trim_end()
-                .parse()?;
-
-            buffer.resize(message_len, 0);
-            stdout.read_exact(&mut buffer).await?;
+            read_message(&mut stdout, &mut buffer).await?;
 
             if let Ok(message) = str::from_utf8(&buffer) {
                 log::trace!("incoming message: {message}");
@@ -538,20 +524,13 @@ impl LanguageServer {
                 response_handlers.lock().take();
             }
         });
-        let mut content_len_buffer = Vec::new();
         while let Ok(message) = outbound_rx.recv().await {
             log::trace!("outgoing message:{}", message);
             for handler in io_handlers.lock().values_mut() {
                 handler(IoKind::StdIn, &message);
             }
 
-            content_len_buffer.clear();
-            write!(content_len_buffer, "{}", message.len()).unwrap();
-            stdin.write_all(CONTENT_LEN_HEADER.as_bytes()).await?;
-            stdin.write_all(&content_len_buffer).await?;
-            stdin.write_all("\r\n\r\n".as_bytes()).await?;
-            stdin.write_all(message.as_bytes()).await?;
-            stdin.flush().await?;
+            write_message(&mut stdin, &message).await?;
         }
         drop(output_done_tx);
         Ok(())
@@ -1361,12 +1340,52 @@ impl FakeLanguageServer {
     }
 }
 
+/// Reads the next JSON-RPC message from `reader` into `buffer`, using the
+/// `Content-Length` framing that language servers use.
+///
+/// Any existing contents of `buffer` are replaced with the message body.
+pub async fn read_message<Stdout>(
+    reader: &mut BufReader<Stdout>,
+    buffer: &mut Vec<u8>,
+) -> Result<()>
+where
+    Stdout: AsyncRead + Unpin,
+{
+    buffer.clear();
+    read_headers(reader, buffer).await?;
+
+    let headers = std::str::from_utf8(buffer)?;
+    let message_len = headers
+        .split('\n')
+        .find(|line| line.starts_with(CONTENT_LEN_HEADER))
+        .and_then(|line| line.strip_prefix(CONTENT_LEN_HEADER))
+        .ok_or_else(|| anyhow!("invalid LSP message header {headers:?}"))?
+        .trim_end()
+        .parse()?;
+
+    buffer.resize(message_len, 0);
+    reader.read_exact(buffer).await?;
+    Ok(())
+}
+
+/// Writes a JSON-RPC message to `writer`, preceded by its `Content-Length` header.
+pub async fn write_message<Stdin>(writer: &mut Stdin, message: &str) -> Result<()>
+where
+    Stdin: AsyncWrite + Unpin,
+{
+    let header = format!("{CONTENT_LEN_HEADER}{}\r\n\r\n", message.len());
+    writer.write_all(header.as_bytes()).await?;
+    writer.write_all(message.as_bytes()).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
 pub(self) async fn read_headers<Stdout>(
     reader: &mut BufReader<Stdout>,
     buffer: &mut Vec<u8>,
 ) -> Result<()>
 where
-    Stdout: AsyncRead + Unpin + Send + 'static,
+    Stdout: AsyncRead + Unpin,
 {
     loop {
         if buffer.len() >= HEADER_DELIMITER.len()
@@ -1488,6 +1507,29 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_write_and_read_message() {
+        let mut output = Vec::new();
+        write_message(&mut output, r#"{"jsonrpc":"2.0","id":1}"#)
+            .await
+            .unwrap();
+        write_message(&mut output, r#"{"jsonrpc":"2.0","id":2}"#)
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            b"Content-Length: 24\r\n\r\n{\"jsonrpc\":\"2.0\",\"id\":1}Content-Length: 24\r\n\r\n{\"jsonrpc\":\"2.0\",\"id\":2}"
+        );
+
+        let mut reader = smol::io::BufReader::new(output.as_slice());
+        let mut buf = Vec::new();
+        read_message(&mut reader, &mut buf).await.unwrap();
+        assert_eq!(buf, br#"{"jsonrpc":"2.0","id":1}"#);
+        read_message(&mut reader, &mut buf).await.unwrap();
+        assert_eq!(buf, br#"{"jsonrpc":"2.0","id":2}"#);
+        assert!(read_message(&mut reader, &mut buf).await.is_err());
+    }
+
     #[gpui::test]
     fn test_deserialize_string_digit_id() {
         let json = r#"{"jsonrpc":"2.0","id":"2","method":"workspace/configuration","params":{"items":[{"scopeUri":"file:///Users/mph/Devel/personal/hello-scala/","section":"metals"}]}}"#;
@@ -0,0 +1,210 @@
+use anyhow::{anyhow, Context, Result};
+use futures::{io::BufReader, stream::BoxStream, AsyncBufReadExt, AsyncReadExt, StreamExt};
+use http::{AsyncBody, HttpClient, Method, Request as HttpRequest};
+use isahc::config::Configurable;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+pub const OLLAMA_API_URL: &str = "http://localhost:11434";
+
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Model {
+    pub name: String,
+    /// Overrides the name shown to the user, which is otherwise derived from `name`.
+    pub display_name: Option<String>,
+    pub max_tokens: usize,
+    pub keep_alive: Option<String>,
+    /// The tiktoken model whose tokenizer approximates this model's, e.g. `gpt-4`.
+    pub tokenizer: Option<String>,
+}
+
+impl Default for Model {
+    fn default() -> Self {
+        Self::new("llama3")
+    }
+}
+
+/// Guesses the context length of a model from its name, as the tags endpoint doesn't report it.
+fn get_max_tokens(name: &str) -> usize {
+    // Ollama's own default for models it knows nothing about.
+    const DEFAULT_TOKENS: usize = 2048;
+    const MAXIMUM_TOKENS: usize = 16384;
+
+    let family = name.split(':').next().unwrap_or(name);
+    let tokens = match family {
+        "phi" | "tinyllama" | "orca-mini" => 2048,
+        "llama2" | "yi" | "vicuna" | "stablelm2" => 4096,
+        "llama3" | "gemma" | "mistral" | "mixtral" | "codegemma" => 8192,
+        "codellama" | "starcoder2" | "deepseek-coder" | "qwen2" => 16384,
+        _ => DEFAULT_TOKENS,
+    };
+    tokens.min(MAXIMUM_TOKENS)
+}
+
+impl Model {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            display_name: None,
+            max_tokens: get_max_tokens(name),
+            keep_alive: None,
+            tokenizer: None,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.name
+    }
+
+    pub fn display_name(&self) -> &str {
+        self.display_name
+            .as_deref()
+            .unwrap_or_else(|| self.name.strip_suffix(":latest").unwrap_or(&self.name))
+    }
+
+    pub fn max_token_count(&self) -> usize {
+        self.max_tokens
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+#[serde(tag = "role", rename_all = "lowercase")]
+pub enum ChatMessage {
+    Assistant { content: String },
+    User { content: String },
+    System { content: String },
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChatRequest {
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+    pub stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_alive: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<ChatOptions>,
+}
+
+// https://github.com/ollama/ollama/blob/main/docs/modelfile.md#valid-parameters-and-values
+#[derive(Debug, Default, Serialize)]
+pub struct ChatOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_ctx: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ChatResponseDelta {
+    pub model: String,
+    pub message: ChatMessage,
+    #[serde(default)]
+    pub done_reason: Option<String>,
+    pub done: bool,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct LocalModelsResponse {
+    pub models: Vec<LocalModelListing>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct LocalModelListing {
+    pub name: String,
+    #[serde(default)]
+    pub size: u64,
+}
+
+pub async fn stream_chat_completion(
+    client: &dyn HttpClient,
+    api_url: &str,
+    request: ChatRequest,
+    low_speed_timeout: Option<Duration>,
+) -> Result<BoxStream<'static, Result<ChatResponseDelta>>> {
+    let uri = format!("{api_url}/api/chat");
+    let mut request_builder = HttpRequest::builder()
+        .method(Method::POST)
+        .uri(uri)
+        .header("Content-Type", "application/json");
+
+    if let Some(low_speed_timeout) = low_speed_timeout {
+        request_builder = request_builder.low_speed_timeout(100, low_speed_timeout);
+    };
+
+    let request = request_builder.body(AsyncBody::from(serde_json::to_string(&request)?))?;
+    let mut response = client.send(request).await?;
+    if response.status().is_success() {
+        // Ollama streams one JSON object per line.
+        let reader = BufReader::new(response.into_body());
+        Ok(reader
+            .lines()
+            .filter_map(|line| async move {
+                match line {
+                    Ok(line) if line.trim().is_empty() => None,
+                    Ok(line) => match serde_json::from_str(&line) {
+                        Ok(response) => Some(Ok(response)),
+                        Err(error) => Some(Err(anyhow!(error))),
+                    },
+                    Err(error) => Some(Err(anyhow!(error))),
+                }
+            })
+            .boxed())
+    } else {
+        Err(error_from_response(response).await)
+    }
+}
+
+pub async fn get_models(
+    client: &dyn HttpClient,
+    api_url: &str,
+    low_speed_timeout: Option<Duration>,
+) -> Result<Vec<LocalModelListing>> {
+    let uri = format!("{api_url}/api/tags");
+    let mut request_builder = HttpRequest::builder()
+        .method(Method::GET)
+        .uri(uri)
+        .header("Accept", "application/json");
+
+    if let Some(low_speed_timeout) = low_speed_timeout {
+        request_builder = request_builder.low_speed_timeout(100, low_speed_timeout);
+    };
+
+    let request = request_builder.body(AsyncBody::default())?;
+    let mut response = client.send(request).await?;
+    if response.status().is_success() {
+        let mut body = String::new();
+        response.body_mut().read_to_string(&mut body).await?;
+        let response: LocalModelsResponse =
+            serde_json::from_str(&body).context("Unable to parse Ollama tag listing")?;
+        Ok(response.models)
+    } else {
+        Err(error_from_response(response).await)
+    }
+}
+
+async fn error_from_response(mut response: http::Response<AsyncBody>) -> anyhow::Error {
+    let mut body = String::new();
+    if let Err(error) = response.body_mut().read_to_string(&mut body).await {
+        return anyhow!(error);
+    }
+
+    #[derive(Deserialize)]
+    struct OllamaError {
+        error: String,
+    }
+
+    match serde_json::from_str::<OllamaError>(&body) {
+        Ok(response) if !response.error.is_empty() => {
+            anyhow!("Failed to connect to Ollama API: {}", response.error)
+        }
+        _ => anyhow!(
+            "Failed to connect to Ollama API: {} {}",
+            response.status(),
+            body,
+        ),
+    }
+}
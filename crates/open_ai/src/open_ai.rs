@@ -98,7 +98,7 @@ impl Model {
 
 #[derive(Debug, Serialize)]
 pub struct Request {
-    pub model: Model,
+    pub model: String,
     pub messages: Vec<RequestMessage>,
     pub stream: bool,
     pub stop: Vec<String>,
@@ -33,7 +33,15 @@ pub fn toggle(editor: View<Editor>, _: &Toggle, cx: &mut WindowContext) {
         .snapshot(cx)
         .outline(Some(&cx.theme().syntax()));
 
-    if let Some((workspace, outline)) = editor.read(cx).workspace().zip(outline) {
+    if let Some(outline) = outline {
+        deploy(editor, outline, cx);
+    }
+}
+
+/// Shows an outline view for the editor containing only the given items, such as the siblings
+/// of one of the symbols in the buffer.
+pub fn deploy(editor: View<Editor>, outline: Outline<Anchor>, cx: &mut WindowContext) {
+    if let Some(workspace) = editor.read(cx).workspace() {
         workspace.update(cx, |workspace, cx| {
             workspace.toggle_modal(cx, |cx| OutlineView::new(outline, editor, cx));
         })
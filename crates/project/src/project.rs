@@ -91,13 +91,13 @@ use std::{
     process::Stdio,
     str::{self, FromStr},
     sync::{
-        atomic::{AtomicUsize, Ordering::SeqCst},
+        atomic::{AtomicU64, AtomicUsize, Ordering::SeqCst},
         Arc,
     },
     time::{Duration, Instant},
 };
 use task::static_source::{StaticSource, TrackedFile};
-use terminals::Terminals;
+use terminals::{SshCommand, Terminals};
 use text::{Anchor, BufferId, LineEnding};
 use util::{
     debug_panic, defer, maybe, merge_json_value_into, parse_env_output,
@@ -332,6 +332,8 @@ pub enum Event {
     CollaboratorLeft(proto::PeerId),
     RefreshInlayHints,
     RevealInProjectPanel(ProjectEntryId),
+    /// A terminal was created, such as the one a spawned task runs in.
+    TerminalCreated(Model<terminal::Terminal>),
 }
 
 pub enum LanguageServerState {
@@ -617,7 +619,13 @@ impl Project {
     pub fn init(client: &Arc<Client>, cx: &mut AppContext) {
         connection_manager::init(client.clone(), cx);
         Self::init_settings(cx);
+        Self::register_client_handlers(client);
+    }
 
+    /// Registers the handlers for the messages that a project receives on the given client. This
+    /// is done by [`Project::init`] for the app's client, and is needed for any other client that
+    /// projects use, such as one connected directly to a headless server.
+    pub fn register_client_handlers(client: &Arc<Client>) {
         client.add_model_message_handler(Self::handle_add_collaborator);
         client.add_model_message_handler(Self::handle_update_project_collaborator);
         client.add_model_message_handler(Self::handle_remove_collaborator);
@@ -729,6 +737,8 @@ impl Project {
                 nonce: StdRng::from_entropy().gen(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    task_diagnostics: HashMap::default(),
+                    ssh_command: None,
                 },
                 current_lsp_settings: ProjectSettings::get_global(cx).lsp.clone(),
                 node: Some(node),
@@ -789,6 +799,46 @@ impl Project {
         .await
     }
 
+    /// Opens a project at the given path on a headless server that the client is connected to
+    /// directly, such as one launched over SSH, rather than through the collaboration server.
+    /// Terminals and tasks of the project are run on the remote machine with `ssh_command`.
+    pub async fn ssh(
+        path: String,
+        ssh_command: SshCommand,
+        client: Arc<Client>,
+        user_store: Model<UserStore>,
+        languages: Arc<LanguageRegistry>,
+        fs: Arc<dyn Fs>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Model<Self>> {
+        static NEXT_SSH_PROJECT_ID: AtomicU64 = AtomicU64::new(1);
+
+        // There's no server to assign the project an id, so the id is chosen here and the
+        // headless server shares the project under it.
+        let remote_id = NEXT_SSH_PROJECT_ID.fetch_add(1, SeqCst);
+        let subscription = client.subscribe_to_entity(remote_id)?;
+        let response = client
+            .request_envelope(proto::JoinSshProject {
+                project_id: remote_id,
+                path,
+            })
+            .await?;
+        let project = Self::from_join_project_response(
+            response,
+            subscription,
+            client,
+            user_store,
+            languages,
+            fs,
+            cx.clone(),
+        )
+        .await?;
+        project.update(&mut cx, |project, _| {
+            project.terminals.ssh_command = Some(ssh_command);
+        })?;
+        Ok(project)
+    }
+
     async fn from_join_project_response(
         response: TypedEnvelope<proto::JoinProjectResponse>,
         subscription: PendingEntitySubscription<Project>,
@@ -881,6 +931,8 @@ impl Project {
                 nonce: StdRng::from_entropy().gen(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    task_diagnostics: HashMap::default(),
+                    ssh_command: None,
                 },
                 current_lsp_settings: ProjectSettings::get_global(cx).lsp.clone(),
                 node: None,
@@ -1265,6 +1317,16 @@ impl Project {
         &self.tasks
     }
 
+    /// Returns a delegate that gives read access to the given worktree, as
+    /// language servers and extensions see it.
+    pub fn lsp_adapter_delegate(
+        &self,
+        worktree: &Model<Worktree>,
+        cx: &ModelContext<Self>,
+    ) -> Arc<dyn LspAdapterDelegate> {
+        ProjectLspAdapterDelegate::new(self, worktree, cx)
+    }
+
     pub fn search_history(&self) -> &SearchHistory {
         &self.search_history
     }
@@ -1935,6 +1997,11 @@ impl Project {
         !self.is_local()
     }
 
+    /// Whether the project is on a machine the app is connected to over SSH.
+    pub fn is_via_ssh(&self) -> bool {
+        self.terminals.ssh_command.is_some()
+    }
+
     pub fn create_buffer(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<Model<Buffer>>> {
         if self.is_remote() {
             let create = self.client.request(proto::OpenNewBuffer {
@@ -2365,6 +2432,9 @@ impl Project {
     ) {
         let buffer = buffer_handle.read(cx);
         let buffer_id = buffer.remote_id();
+        if buffer.is_large_file() {
+            return;
+        }
 
         if let Some(file) = File::from_dyn(buffer.file()) {
             if !file.is_local() {
@@ -3060,6 +3130,9 @@ impl Project {
         new_language: Arc<Language>,
         cx: &mut ModelContext<Self>,
     ) {
+        if buffer.read(cx).is_large_file() {
+            return;
+        }
         buffer.update(cx, |buffer, cx| {
             if buffer.language().map_or(true, |old_language| {
                 !Arc::ptr_eq(old_language, &new_language)
@@ -6447,6 +6520,9 @@ impl Project {
         cx: &mut ModelContext<Self>,
     ) -> Task<anyhow::Result<Vec<InlayHint>>> {
         let buffer = buffer_handle.read(cx);
+        if buffer.is_large_file() {
+            return Task::ready(Ok(Vec::new()));
+        }
         let range_start = range.start;
         let range_end = range.end;
         let buffer_id = buffer.remote_id().into();
@@ -6664,11 +6740,16 @@ impl Project {
                 }
             })
             .sum();
-        if path_count == 0 {
+        // Worktrees that scan lazily may have directories that haven't been loaded yet, whose
+        // files are read straight from the file system instead.
+        let has_unloaded_dirs = snapshots
+            .iter()
+            .any(|snapshot| snapshot.unloaded_dirs().next().is_some());
+        if path_count == 0 && !has_unloaded_dirs {
             let (_, rx) = smol::channel::bounded(1024);
             return rx;
         }
-        let workers = background.num_cpus().min(path_count);
+        let workers = background.num_cpus().min(path_count).max(1);
         let (matching_paths_tx, matching_paths_rx) = smol::channel::bounded(1024);
         let mut unnamed_files = vec![];
         let opened_buffers = self
@@ -6823,6 +6904,7 @@ impl Project {
         let query = &query;
         let matching_paths_tx = &matching_paths_tx;
         let snapshots = &snapshots;
+        let opened_buffers = &opened_buffers;
         for buffer in unnamed_buffers {
             matching_paths_tx
                 .send(SearchMatchCandidate::OpenBuffer {
@@ -6851,7 +6933,6 @@ impl Project {
                 for worker_ix in 0..workers {
                     let worker_start_ix = worker_ix * paths_per_worker;
                     let worker_end_ix = worker_start_ix + paths_per_worker;
-                    let opened_buffers = opened_buffers.clone();
                     let limiter = Arc::clone(&max_concurrent_workers);
                     scope.spawn({
                         async move {
@@ -6862,7 +6943,7 @@ impl Project {
                                 worker_end_ix,
                                 query,
                                 matching_paths_tx,
-                                &opened_buffers,
+                                opened_buffers,
                                 include_root,
                                 fs,
                             )
@@ -6871,6 +6952,25 @@ impl Project {
                     });
                 }
 
+                for snapshot in snapshots {
+                    for unloaded_dir in snapshot.unloaded_dirs() {
+                        let limiter = Arc::clone(&max_concurrent_workers);
+                        scope.spawn(async move {
+                            let _guard = limiter.acquire().await;
+                            search_unloaded_dir(
+                                snapshot,
+                                unloaded_dir,
+                                fs,
+                                query,
+                                include_root,
+                                opened_buffers,
+                                matching_paths_tx,
+                            )
+                            .await;
+                        });
+                    }
+                }
+
                 if query.include_ignored() {
                     for snapshot in snapshots {
                         for ignored_entry in snapshot.entries(true).filter(|e| e.is_ignored) {
@@ -10405,6 +10505,60 @@ async fn search_snapshots(
     }
 }
 
+async fn search_unloaded_dir(
+    snapshot: &LocalSnapshot,
+    unloaded_dir: &Entry,
+    fs: &Arc<dyn Fs>,
+    query: &SearchQuery,
+    include_root: bool,
+    opened_buffers: &HashMap<Arc<Path>, (Model<Buffer>, BufferSnapshot)>,
+    results_tx: &Sender<SearchMatchCandidate>,
+) {
+    let mut abs_path = PathBuf::new();
+    for path in snapshot
+        .files_in_unloaded_dir(unloaded_dir, fs.as_ref())
+        .await
+    {
+        if results_tx.is_closed() {
+            return;
+        }
+        if opened_buffers.contains_key(&path) {
+            continue;
+        }
+
+        let matched_path = if include_root {
+            let mut full_path = PathBuf::from(snapshot.root_name());
+            full_path.push(&path);
+            query.file_matches(Some(&full_path))
+        } else {
+            query.file_matches(Some(&path))
+        };
+        if !matched_path {
+            continue;
+        }
+
+        abs_path.clear();
+        abs_path.push(&snapshot.abs_path());
+        abs_path.push(&path);
+        let matches = if let Some(file) = fs.open_sync(&abs_path).await.log_err() {
+            query.detect(file).unwrap_or(false)
+        } else {
+            false
+        };
+
+        if matches {
+            let project_path = SearchMatchCandidate::Path {
+                worktree_id: snapshot.id(),
+                path,
+                is_ignored: false,
+            };
+            if results_tx.send(project_path).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
 async fn search_ignored_entry(
     snapshot: &LocalSnapshot,
     ignored_entry: &Entry,
@@ -10568,15 +10722,18 @@ impl<'a> Iterator for PathMatchCandidateSetIter<'a> {
 
     fn next(&mut self) -> Option<Self::Item> {
         self.traversal.next().map(|entry| match entry.kind {
-            EntryKind::Dir => fuzzy::PathMatchCandidate {
-                path: &entry.path,
-                char_bag: CharBag::from_iter(entry.path.to_string_lossy().to_lowercase().chars()),
-            },
+            EntryKind::Dir | EntryKind::UnloadedDir | EntryKind::PendingDir => {
+                fuzzy::PathMatchCandidate {
+                    path: &entry.path,
+                    char_bag: CharBag::from_iter(
+                        entry.path.to_string_lossy().to_lowercase().chars(),
+                    ),
+                }
+            }
             EntryKind::File(char_bag) => fuzzy::PathMatchCandidate {
                 path: &entry.path,
                 char_bag,
             },
-            EntryKind::UnloadedDir | EntryKind::PendingDir => unreachable!(),
         })
     }
 }
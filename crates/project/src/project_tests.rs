@@ -4,7 +4,7 @@ use futures::{future, StreamExt};
 use gpui::AppContext;
 use language::{
     language_settings::{AllLanguageSettings, LanguageSettingsContent},
-    tree_sitter_rust, tree_sitter_typescript, Diagnostic, FakeLspAdapter, LanguageConfig,
+    tree_sitter_rust, tree_sitter_typescript, Diagnostic, Encoding, FakeLspAdapter, LanguageConfig,
     LanguageMatcher, LineEnding, OffsetRangeExt, Point, ToPoint,
 };
 use lsp::Url;
@@ -2754,6 +2754,39 @@ async fn test_save_file(cx: &mut gpui::TestAppContext) {
     assert_eq!(new_text, buffer.update(cx, |buffer, _| buffer.text()));
 }
 
+#[gpui::test]
+async fn test_save_file_in_original_encoding(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let utf16 = Encoding::from_name("UTF-16LE with BOM").unwrap();
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_file(
+        "/dir/file1",
+        utf16.encode("one\ntwo\n").unwrap().into_owned(),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/file1", cx))
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, cx| {
+        assert_eq!(buffer.text(), "one\ntwo\n");
+        assert_eq!(buffer.encoding(), utf16);
+        buffer.edit([(0..0, "zero\n")], None, cx);
+    });
+
+    project
+        .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        fs.read_file_sync("/dir/file1").unwrap(),
+        utf16.encode("zero\none\ntwo\n").unwrap().as_ref()
+    );
+}
+
 #[gpui::test(iterations = 30)]
 async fn test_file_changes_multiple_times_on_disk(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -3918,6 +3951,71 @@ async fn test_search(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_search_in_lazily_scanned_worktree(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|store, cx| {
+            store.update_user_settings::<WorktreeSettings>(cx, |settings| {
+                settings.lazy_file_scan = Some(true);
+            });
+        });
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            ".gitignore": "target\n",
+            "one.rs": "const ONE: usize = 1;",
+            "src": {
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "nested": {
+                    "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+                },
+                ".gitignore": "generated\n",
+                "generated": {
+                    "five.rs": "const FIVE: usize = two::TWO;",
+                },
+            },
+            "docs": {
+                "notes.md": "ONE",
+            },
+            "target": {
+                "four.rs": "const FOUR: usize = two::TWO;",
+            },
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    project.read_with(cx, |project, cx| {
+        let worktree = project.worktrees().next().unwrap().read(cx);
+        assert!(worktree.entry_for_path("src").unwrap().kind.is_unloaded());
+    });
+
+    // Directories that haven't been scanned yet are searched without loading them, except for
+    // ignored ones.
+    assert_eq!(
+        search(
+            &project,
+            SearchQuery::text("TWO", false, true, false, Vec::new(), Vec::new()).unwrap(),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([
+            ("dir/src/two.rs".to_string(), vec![6..9]),
+            ("dir/src/nested/three.rs".to_string(), vec![37..40])
+        ])
+    );
+    project.read_with(cx, |project, cx| {
+        let worktree = project.worktrees().next().unwrap().read(cx);
+        let is_unloaded = |path: &str| worktree.entry_for_path(path).unwrap().kind.is_unloaded();
+        assert!(is_unloaded("docs"));
+        assert!(is_unloaded("target"));
+    });
+}
+
 #[gpui::test]
 async fn test_search_with_inclusions(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -4461,6 +4559,144 @@ fn test_glob_literal_prefix() {
     assert_eq!(glob_literal_prefix("foo/bar/baz.js"), "foo/bar/baz.js");
 }
 
+#[test]
+fn test_ssh_remote_shell() {
+    use terminal::terminal_settings::Shell;
+
+    let ssh_command = terminals::SshCommand {
+        arguments: vec![
+            "-p".into(),
+            "2222".into(),
+            "--".into(),
+            "me@example.com".into(),
+        ],
+    };
+    assert_eq!(
+        ssh_command.remote_shell(Some(Path::new("/srv/my app")), None, &HashMap::default()),
+        Shell::WithArguments {
+            program: "ssh".into(),
+            args: vec![
+                "-t".into(),
+                "-p".into(),
+                "2222".into(),
+                "--".into(),
+                "me@example.com".into(),
+                "cd '/srv/my app' && exec env \"$SHELL\" -l".into(),
+            ],
+        }
+    );
+
+    let env = HashMap::from_iter([("RUST_LOG".to_string(), "it's on".to_string())]);
+    let Shell::WithArguments { args, .. } = ssh_command.remote_shell(
+        None,
+        Some((
+            "cargo".into(),
+            vec!["test".into(), "-p".into(), "a b".into()],
+        )),
+        &env,
+    ) else {
+        panic!("expected a command with arguments");
+    };
+    assert_eq!(
+        args.last().unwrap(),
+        "exec env 'RUST_LOG=it'\\''s on' 'cargo' 'test' '-p' 'a b'"
+    );
+}
+
+#[gpui::test]
+async fn test_task_diagnostics(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "src": {
+                "main.rs": "fn main() {\n    let y = x;\n}\n",
+                "lib.rs": "pub fn f() {}\n",
+            }
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+
+    let first_run_output = r#"   Compiling zed v0.1.0 (/dir)
+error[E0425]: cannot find value `x` in this scope
+ --> src/main.rs:2:13
+  |
+warning: function `f` is never used
+ --> src/lib.rs:1:8
+  |
+error: could not compile `zed` (bin "zed") due to 1 previous error; 1 warning emitted
+"#;
+    let second_run_output = r#"   Compiling zed v0.1.0 (/dir)
+warning: function `f` is never used
+ --> src/lib.rs:1:8
+  |
+"#;
+    let matcher = task::ProblemMatcher::builtin("$rustc").unwrap();
+    let problems_in_output = |output: &str| {
+        matcher
+            .find_problems(output, Some(Path::new("/dir")))
+            .into_iter()
+            .map(|problem| (None, problem))
+            .collect::<Vec<_>>()
+    };
+    let summaries = |project: &Model<Project>, cx: &mut gpui::TestAppContext| {
+        project.read_with(cx, |project, cx| {
+            let mut summaries = project
+                .diagnostic_summaries(false, cx)
+                .map(|(path, _, summary)| {
+                    (
+                        path.path.to_string_lossy().into_owned(),
+                        summary.error_count,
+                        summary.warning_count,
+                    )
+                })
+                .collect::<Vec<_>>();
+            summaries.sort();
+            summaries
+        })
+    };
+
+    // The problems found in the task's output are published as diagnostics.
+    project.update(cx, |project, cx| {
+        project.update_task_diagnostics("cargo check", problems_in_output(first_run_output), cx)
+    });
+    assert_eq!(
+        summaries(&project, cx),
+        [
+            ("src/lib.rs".to_string(), 0, 1),
+            ("src/main.rs".to_string(), 1, 0)
+        ]
+    );
+
+    // Re-running the task clears the previous run's diagnostics as it starts.
+    project.update(cx, |project, cx| {
+        project.update_task_diagnostics("cargo check", Vec::new(), cx)
+    });
+    assert_eq!(summaries(&project, cx), []);
+
+    // Once it finishes, only the problems it reported remain.
+    project.update(cx, |project, cx| {
+        project.update_task_diagnostics("cargo check", problems_in_output(second_run_output), cx)
+    });
+    assert_eq!(summaries(&project, cx), [("src/lib.rs".to_string(), 0, 1)]);
+
+    // Another task's diagnostics are kept separately.
+    project.update(cx, |project, cx| {
+        project.update_task_diagnostics("cargo clippy", problems_in_output(first_run_output), cx);
+        project.update_task_diagnostics("cargo check", Vec::new(), cx);
+    });
+    assert_eq!(
+        summaries(&project, cx),
+        [
+            ("src/lib.rs".to_string(), 0, 1),
+            ("src/main.rs".to_string(), 1, 0)
+        ]
+    );
+}
+
 #[gpui::test]
 async fn test_create_entry(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -1,21 +1,85 @@
-use crate::Project;
-use collections::HashMap;
+use crate::{Event, Project};
+use collections::{HashMap, HashSet};
 use gpui::{AnyWindowHandle, Context, Entity, Model, ModelContext, WeakModel};
+use language::{Diagnostic, DiagnosticEntry, PointUtf16, Unclipped};
+use lsp::{DiagnosticSeverity, LanguageServerId};
 use settings::{Settings, SettingsLocation};
 use smol::channel::bounded;
 use std::path::{Path, PathBuf};
-use task::SpawnInTerminal;
+use task::{Problem, ProblemMatcher, ProblemSeverity, SpawnInTerminal};
 use terminal::{
     terminal_settings::{self, Shell, TerminalSettings, VenvSettingsContent},
     TaskState, TaskStatus, Terminal, TerminalBuilder,
 };
-use util::ResultExt;
+use util::{post_inc, ResultExt};
 
 // #[cfg(target_os = "macos")]
 // use std::os::unix::ffi::OsStrExt;
 
 pub struct Terminals {
     pub(crate) local_handles: Vec<WeakModel<terminal::Terminal>>,
+    pub(crate) task_diagnostics: HashMap<String, TaskDiagnostics>,
+    pub(crate) ssh_command: Option<SshCommand>,
+}
+
+/// How to reach the machine that an SSH project is on, for running its terminals and tasks there.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SshCommand {
+    /// The arguments that select the host for `ssh`, like `-p 2222 -- me@example.com`.
+    pub arguments: Vec<String>,
+}
+
+impl SshCommand {
+    /// Returns the shell that runs the program on the remote machine, in the given directory and
+    /// with the given environment. Without a program, the remote user's login shell is started.
+    pub fn remote_shell(
+        &self,
+        working_directory: Option<&Path>,
+        program: Option<(String, Vec<String>)>,
+        env: &HashMap<String, String>,
+    ) -> Shell {
+        let mut command = String::new();
+        if let Some(working_directory) = working_directory {
+            command.push_str("cd ");
+            command.push_str(&shell_quote(&working_directory.to_string_lossy()));
+            command.push_str(" && ");
+        }
+        command.push_str("exec env");
+        let mut env = env.iter().collect::<Vec<_>>();
+        env.sort();
+        for (name, value) in env {
+            command.push(' ');
+            command.push_str(&shell_quote(&format!("{name}={value}")));
+        }
+        match program {
+            Some((program, args)) => {
+                for arg in std::iter::once(&program).chain(&args) {
+                    command.push(' ');
+                    command.push_str(&shell_quote(arg));
+                }
+            }
+            None => command.push_str(" \"$SHELL\" -l"),
+        }
+
+        let mut args = vec!["-t".to_string()];
+        args.extend(self.arguments.iter().cloned());
+        args.push(command);
+        Shell::WithArguments {
+            program: "ssh".to_string(),
+            args,
+        }
+    }
+}
+
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+/// Diagnostics, produced by the problem matchers of a task.
+/// Every task label gets its own pseudo language server id, to keep its diagnostics apart from the others.
+pub(crate) struct TaskDiagnostics {
+    server_id: LanguageServerId,
+    abs_paths: HashSet<PathBuf>,
 }
 
 impl Project {
@@ -27,9 +91,10 @@ impl Project {
         cx: &mut ModelContext<Self>,
     ) -> anyhow::Result<Model<Terminal>> {
         anyhow::ensure!(
-            !self.is_remote(),
+            self.is_local() || self.is_via_ssh(),
             "creating terminals as a guest is not supported yet"
         );
+        let ssh_command = self.terminals.ssh_command.clone();
 
         // used only for TerminalSettings::get
         let worktree = {
@@ -61,12 +126,27 @@ impl Project {
             .as_deref()
             .unwrap_or_else(|| Path::new(""));
 
+        let mut problem_matchers = None;
         let (spawn_task, shell) = if let Some(spawn_task) = spawn_task {
             log::debug!("Spawning task: {spawn_task:?}");
+            if !spawn_task.problem_matchers.is_empty() {
+                problem_matchers = Some((
+                    spawn_task.full_label.clone(),
+                    spawn_task.problem_matchers,
+                    spawn_task.cwd.clone().or_else(|| working_directory.clone()),
+                ));
+            }
             env.extend(spawn_task.env);
             // Activate minimal Python virtual environment
-            if let Some(python_settings) = &python_settings.as_option() {
-                self.set_python_venv_path_for_tasks(python_settings, venv_base_directory, &mut env);
+            if let Some(python_settings) = python_settings
+                .as_option()
+                .filter(|_| ssh_command.is_none())
+            {
+                self.set_python_venv_path_for_tasks(
+                    &python_settings,
+                    venv_base_directory,
+                    &mut env,
+                );
             }
             (
                 Some(TaskState {
@@ -86,8 +166,25 @@ impl Project {
             (None, settings.shell.clone())
         };
 
+        let (terminal_working_directory, shell, env) = match &ssh_command {
+            Some(ssh_command) => {
+                // The shell setting and the working directory refer to this machine, so the
+                // remote user's shell is started in the remote project instead.
+                let program = match shell {
+                    Shell::WithArguments { program, args } if !is_terminal => Some((program, args)),
+                    _ => None,
+                };
+                let remote_working_directory =
+                    self.ssh_working_directory(working_directory.as_deref(), cx);
+                let shell =
+                    ssh_command.remote_shell(remote_working_directory.as_deref(), program, &env);
+                (None, shell, HashMap::default())
+            }
+            None => (working_directory.clone(), shell, env),
+        };
+
         let terminal = TerminalBuilder::new(
-            working_directory.clone(),
+            terminal_working_directory,
             spawn_task,
             shell,
             env,
@@ -118,8 +215,12 @@ impl Project {
             })
             .detach();
 
+            if let Some((task_label, problem_matchers, cwd)) = problem_matchers {
+                self.match_task_problems(task_label, problem_matchers, cwd, &terminal_handle, cx);
+            }
+
             // if the terminal is not a task, activate full Python virtual environment
-            if is_terminal {
+            if is_terminal && ssh_command.is_none() {
                 if let Some(python_settings) = &python_settings.as_option() {
                     if let Some(activate_script_path) =
                         self.find_activate_script_path(python_settings, venv_base_directory)
@@ -133,12 +234,156 @@ impl Project {
                     }
                 }
             }
+            cx.emit(Event::TerminalCreated(terminal_handle.clone()));
             terminal_handle
         });
 
         terminal
     }
 
+    /// The directory on the remote machine to start a terminal of an SSH project in: the given one
+    /// if it is within the project, or else the root of the project's first worktree.
+    fn ssh_working_directory(
+        &self,
+        working_directory: Option<&Path>,
+        cx: &ModelContext<Self>,
+    ) -> Option<PathBuf> {
+        let worktree_roots = self
+            .visible_worktrees(cx)
+            .map(|worktree| worktree.read(cx).abs_path())
+            .collect::<Vec<_>>();
+        working_directory
+            .filter(|directory| {
+                worktree_roots
+                    .iter()
+                    .any(|root| directory.starts_with(root))
+            })
+            .map(Path::to_path_buf)
+            .or_else(|| worktree_roots.first().map(|root| root.to_path_buf()))
+    }
+
+    /// Replaces the task's diagnostics with the problems found in its output, once the task finishes.
+    fn match_task_problems(
+        &mut self,
+        task_label: String,
+        problem_matchers: Vec<ProblemMatcher>,
+        cwd: Option<PathBuf>,
+        terminal: &Model<Terminal>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        // Problems of the previous run are stale now.
+        self.update_task_diagnostics(&task_label, Vec::new(), cx);
+
+        let task_completed =
+            terminal.update(cx, |terminal, cx| terminal.wait_for_completed_task(cx));
+        let terminal = terminal.downgrade();
+        cx.spawn(|project, mut cx| async move {
+            task_completed.await;
+            let Ok(output) = terminal.update(&mut cx, |terminal, _| terminal.text_content()) else {
+                return Ok(());
+            };
+            let problems = cx
+                .background_executor()
+                .spawn(async move {
+                    problem_matchers
+                        .iter()
+                        .flat_map(|matcher| {
+                            matcher
+                                .find_problems(&output, cwd.as_deref())
+                                .into_iter()
+                                .map(|problem| (matcher.source.clone(), problem))
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .await;
+            project.update(&mut cx, |project, cx| {
+                project.update_task_diagnostics(&task_label, problems, cx)
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Sets the diagnostics of the task with the given label, removing all previous ones.
+    /// Problems without their own source are reported with the task label as their source.
+    pub fn update_task_diagnostics(
+        &mut self,
+        task_label: &str,
+        problems: Vec<(Option<String>, Problem)>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if problems.is_empty() && !self.terminals.task_diagnostics.contains_key(task_label) {
+            return;
+        }
+
+        let languages = self.languages.clone();
+        let task_diagnostics = self
+            .terminals
+            .task_diagnostics
+            .entry(task_label.to_string())
+            .or_insert_with(|| TaskDiagnostics {
+                server_id: languages.next_language_server_id(),
+                abs_paths: HashSet::default(),
+            });
+        let server_id = task_diagnostics.server_id;
+        let old_abs_paths = std::mem::take(&mut task_diagnostics.abs_paths);
+
+        let mut diagnostics_by_path =
+            HashMap::<PathBuf, Vec<DiagnosticEntry<Unclipped<PointUtf16>>>>::default();
+        for (source, problem) in problems {
+            let start = PointUtf16::new(problem.start.row, problem.start.column);
+            let end = problem
+                .end
+                .map_or(start, |end| PointUtf16::new(end.row, end.column));
+            diagnostics_by_path
+                .entry(problem.path)
+                .or_default()
+                .push(DiagnosticEntry {
+                    range: Unclipped(start)..Unclipped(end),
+                    diagnostic: Diagnostic {
+                        source: Some(source.unwrap_or_else(|| task_label.to_string())),
+                        code: problem.code,
+                        severity: match problem.severity {
+                            ProblemSeverity::Error => DiagnosticSeverity::ERROR,
+                            ProblemSeverity::Warning => DiagnosticSeverity::WARNING,
+                            ProblemSeverity::Info => DiagnosticSeverity::INFORMATION,
+                            ProblemSeverity::Hint => DiagnosticSeverity::HINT,
+                        },
+                        message: problem.message,
+                        group_id: post_inc(&mut self.next_diagnostic_group_id),
+                        is_primary: true,
+                        // Task output describes the files as they were on disk.
+                        is_disk_based: true,
+                        is_unnecessary: false,
+                    },
+                });
+        }
+
+        for abs_path in old_abs_paths {
+            if !diagnostics_by_path.contains_key(&abs_path) {
+                self.update_diagnostic_entries(server_id, abs_path, None, Vec::new(), cx)
+                    .log_err();
+            }
+        }
+
+        let mut new_abs_paths = HashSet::default();
+        for (abs_path, diagnostics) in diagnostics_by_path {
+            // Problems in files outside of the project are ignored.
+            if self
+                .update_diagnostic_entries(server_id, abs_path.clone(), None, diagnostics, cx)
+                .is_ok()
+            {
+                new_abs_paths.insert(abs_path);
+            }
+        }
+        if let Some(task_diagnostics) = self.terminals.task_diagnostics.get_mut(task_label) {
+            task_diagnostics.abs_paths = new_abs_paths;
+        }
+
+        cx.emit(Event::DiskBasedDiagnosticsFinished {
+            language_server_id: server_id,
+        });
+    }
+
     pub fn find_activate_script_path(
         &mut self,
         settings: &VenvSettingsContent,
@@ -1,13 +1,16 @@
+use collections::HashSet;
 use editor::{scroll::Autoscroll, styled_runs_for_code_label, Bias, Editor};
 use fuzzy::{StringMatch, StringMatchCandidate};
 use gpui::{
     actions, rems, AppContext, DismissEvent, FontWeight, Model, ParentElement, StyledText, Task,
     View, ViewContext, WeakView, WindowContext,
 };
+use language::{CodeLabel, PointUtf16, Unclipped};
 use ordered_float::OrderedFloat;
 use picker::{Picker, PickerDelegate};
-use project::{Project, Symbol};
+use project::{Project, ProjectPath, Symbol};
 use std::{borrow::Cow, cmp::Reverse, sync::Arc};
+use symbol_index::{IndexedSymbol, ProjectSymbolIndex, SymbolIndex};
 use theme::ActiveTheme;
 use util::ResultExt;
 use workspace::{
@@ -17,14 +20,22 @@ use workspace::{
 
 actions!(project_symbols, [Toggle]);
 
+/// The most symbols to take from the tree-sitter index for each query.
+const MAX_INDEXED_SYMBOLS: usize = 100;
+
 pub fn init(cx: &mut AppContext) {
     cx.observe_new_views(
         |workspace: &mut Workspace, _: &mut ViewContext<Workspace>| {
             workspace.register_action(|workspace, _: &Toggle, cx| {
                 let project = workspace.project().clone();
+                let symbol_index = if project.read(cx).is_local() {
+                    SymbolIndex::project_index(project.clone(), cx)
+                } else {
+                    None
+                };
                 let handle = cx.view().downgrade();
                 workspace.toggle_modal(cx, move |cx| {
-                    let delegate = ProjectSymbolsDelegate::new(handle, project);
+                    let delegate = ProjectSymbolsDelegate::new(handle, project, symbol_index);
                     Picker::uniform_list(delegate, cx).width(rems(34.))
                 })
             });
@@ -35,11 +46,42 @@ pub fn init(cx: &mut AppContext) {
 
 pub type ProjectSymbols = View<Picker<ProjectSymbolsDelegate>>;
 
+/// A symbol reported by a language server, or found in the project's tree-sitter symbol index.
+#[derive(Clone, Debug)]
+enum ProjectSymbol {
+    Lsp(Symbol),
+    Indexed(IndexedSymbol),
+}
+
+impl ProjectSymbol {
+    fn label(&self) -> &CodeLabel {
+        match self {
+            ProjectSymbol::Lsp(symbol) => &symbol.label,
+            ProjectSymbol::Indexed(symbol) => &symbol.label,
+        }
+    }
+
+    fn path(&self) -> &ProjectPath {
+        match self {
+            ProjectSymbol::Lsp(symbol) => &symbol.path,
+            ProjectSymbol::Indexed(symbol) => &symbol.path,
+        }
+    }
+
+    fn start(&self) -> Unclipped<PointUtf16> {
+        match self {
+            ProjectSymbol::Lsp(symbol) => symbol.range.start,
+            ProjectSymbol::Indexed(symbol) => symbol.position,
+        }
+    }
+}
+
 pub struct ProjectSymbolsDelegate {
     workspace: WeakView<Workspace>,
     project: Model<Project>,
+    symbol_index: Option<Model<ProjectSymbolIndex>>,
     selected_match_index: usize,
-    symbols: Vec<Symbol>,
+    symbols: Vec<ProjectSymbol>,
     visible_match_candidates: Vec<StringMatchCandidate>,
     external_match_candidates: Vec<StringMatchCandidate>,
     show_worktree_root_name: bool,
@@ -47,10 +89,15 @@ pub struct ProjectSymbolsDelegate {
 }
 
 impl ProjectSymbolsDelegate {
-    fn new(workspace: WeakView<Workspace>, project: Model<Project>) -> Self {
+    fn new(
+        workspace: WeakView<Workspace>,
+        project: Model<Project>,
+        symbol_index: Option<Model<ProjectSymbolIndex>>,
+    ) -> Self {
         Self {
             workspace,
             project,
+            symbol_index,
             selected_match_index: 0,
             symbols: Default::default(),
             visible_match_candidates: Default::default(),
@@ -79,10 +126,10 @@ impl ProjectSymbolsDelegate {
             cx.background_executor().clone(),
         ));
         let sort_key_for_match = |mat: &StringMatch| {
-            let symbol = &self.symbols[mat.candidate_id];
+            let label = self.symbols[mat.candidate_id].label();
             (
                 Reverse(OrderedFloat(mat.score)),
-                &symbol.label.text[symbol.label.filter_range.clone()],
+                &label.text[label.filter_range.clone()],
             )
         };
 
@@ -92,8 +139,7 @@ impl ProjectSymbolsDelegate {
         matches.append(&mut external_matches);
 
         for mat in &mut matches {
-            let symbol = &self.symbols[mat.candidate_id];
-            let filter_start = symbol.label.filter_range.start;
+            let filter_start = self.symbols[mat.candidate_id].label().filter_range.start;
             for position in &mut mat.positions {
                 *position += filter_start;
             }
@@ -116,17 +162,15 @@ impl PickerDelegate for ProjectSymbolsDelegate {
             .get(self.selected_match_index)
             .map(|mat| self.symbols[mat.candidate_id].clone())
         {
-            let buffer = self.project.update(cx, |project, cx| {
-                project.open_buffer_for_symbol(&symbol, cx)
+            let buffer = self.project.update(cx, |project, cx| match &symbol {
+                ProjectSymbol::Lsp(symbol) => project.open_buffer_for_symbol(symbol, cx),
+                ProjectSymbol::Indexed(symbol) => project.open_buffer(symbol.path.clone(), cx),
             });
-            let symbol = symbol.clone();
             let workspace = self.workspace.clone();
             cx.spawn(|_, mut cx| async move {
                 let buffer = buffer.await?;
                 workspace.update(&mut cx, |workspace, cx| {
-                    let position = buffer
-                        .read(cx)
-                        .clip_point_utf16(symbol.range.start, Bias::Left);
+                    let position = buffer.read(cx).clip_point_utf16(symbol.start(), Bias::Left);
                     let pane = if secondary {
                         workspace.adjacent_pane(cx)
                     } else {
@@ -165,12 +209,21 @@ impl PickerDelegate for ProjectSymbolsDelegate {
     fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
         self.filter(&query, cx);
         self.show_worktree_root_name = self.project.read(cx).visible_worktrees(cx).count() > 1;
-        let symbols = self
+        let lsp_symbols = self
             .project
             .update(cx, |project, cx| project.symbols(&query, cx));
+        let indexed_symbols = self
+            .symbol_index
+            .as_ref()
+            .map(|index| index.read(cx).search(&query, MAX_INDEXED_SYMBOLS, cx));
         cx.spawn(|this, mut cx| async move {
-            let symbols = symbols.await.log_err();
-            if let Some(symbols) = symbols {
+            let lsp_symbols = lsp_symbols.await.log_err();
+            let indexed_symbols = match indexed_symbols {
+                Some(indexed_symbols) => indexed_symbols.await,
+                None => Vec::new(),
+            };
+            if lsp_symbols.is_some() || !indexed_symbols.is_empty() {
+                let symbols = merge_symbols(lsp_symbols.unwrap_or_default(), indexed_symbols);
                 this.update(&mut cx, |this, cx| {
                     let delegate = &mut this.delegate;
                     let project = delegate.project.read(cx);
@@ -178,14 +231,15 @@ impl PickerDelegate for ProjectSymbolsDelegate {
                         .iter()
                         .enumerate()
                         .map(|(id, symbol)| {
+                            let label = symbol.label();
                             StringMatchCandidate::new(
                                 id,
-                                symbol.label.text[symbol.label.filter_range.clone()].to_string(),
+                                label.text[label.filter_range.clone()].to_string(),
                             )
                         })
                         .partition(|candidate| {
                             project
-                                .entry_for_path(&symbols[candidate.id].path, cx)
+                                .entry_for_path(symbols[candidate.id].path(), cx)
                                 .map_or(false, |e| !e.is_ignored)
                         });
 
@@ -207,12 +261,12 @@ impl PickerDelegate for ProjectSymbolsDelegate {
     ) -> Option<Self::ListItem> {
         let string_match = &self.matches[ix];
         let symbol = &self.symbols[string_match.candidate_id];
-        let syntax_runs = styled_runs_for_code_label(&symbol.label, cx.theme().syntax());
+        let syntax_runs = styled_runs_for_code_label(symbol.label(), cx.theme().syntax());
 
-        let mut path = symbol.path.path.to_string_lossy();
+        let mut path = symbol.path().path.to_string_lossy();
         if self.show_worktree_root_name {
             let project = self.project.read(cx);
-            if let Some(worktree) = project.worktree_for_id(symbol.path.worktree_id, cx) {
+            if let Some(worktree) = project.worktree_for_id(symbol.path().worktree_id, cx) {
                 path = Cow::Owned(format!(
                     "{}{}{}",
                     worktree.read(cx).root_name(),
@@ -221,7 +275,7 @@ impl PickerDelegate for ProjectSymbolsDelegate {
                 ));
             }
         }
-        let label = symbol.label.text.clone();
+        let label = symbol.label().text.clone();
         let path = path.to_string().clone();
 
         let highlights = gpui::combine_highlights(
@@ -256,12 +310,34 @@ impl PickerDelegate for ProjectSymbolsDelegate {
     }
 }
 
+/// Combines the symbols from language servers with those from the tree-sitter index, skipping
+/// indexed symbols that start on the same line as a language server's symbol in the same file.
+fn merge_symbols(
+    lsp_symbols: Vec<Symbol>,
+    indexed_symbols: Vec<IndexedSymbol>,
+) -> Vec<ProjectSymbol> {
+    let lsp_symbol_starts = lsp_symbols
+        .iter()
+        .map(|symbol| (symbol.path.clone(), symbol.range.start.0.row))
+        .collect::<HashSet<_>>();
+    let indexed_symbols = indexed_symbols.into_iter().filter(|symbol| {
+        !lsp_symbol_starts.contains(&(symbol.path.clone(), symbol.position.0.row))
+    });
+    lsp_symbols
+        .into_iter()
+        .map(ProjectSymbol::Lsp)
+        .chain(indexed_symbols.map(ProjectSymbol::Indexed))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use futures::StreamExt;
     use gpui::{TestAppContext, VisualContext};
-    use language::{FakeLspAdapter, Language, LanguageConfig, LanguageMatcher};
+    use language::{
+        tree_sitter_rust, FakeLspAdapter, Language, LanguageConfig, LanguageMatcher, Point,
+    };
     use project::FakeFs;
     use serde_json::json;
     use settings::SettingsStore;
@@ -345,7 +421,7 @@ mod tests {
         // Create the project symbols view.
         let symbols = cx.new_view(|cx| {
             Picker::uniform_list(
-                ProjectSymbolsDelegate::new(workspace.downgrade(), project.clone()),
+                ProjectSymbolsDelegate::new(workspace.downgrade(), project.clone(), None),
                 cx,
             )
         });
@@ -390,6 +466,73 @@ mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_indexed_project_symbols(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.update(symbol_index::init);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/indexed",
+            json!({ "main.rs": "fn main() {}\n\nfn helper() {}\n" }),
+        )
+        .await;
+
+        // The language has an outline query, but no language server.
+        let project = Project::test(fs.clone(), ["/indexed".as_ref()], cx).await;
+        project.read_with(cx, |project, _| {
+            project.languages().add(Arc::new(
+                Language::new(
+                    LanguageConfig {
+                        name: "Rust".into(),
+                        matcher: LanguageMatcher {
+                            path_suffixes: vec!["rs".to_string()],
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    Some(tree_sitter_rust::language()),
+                )
+                .with_outline_query(r#"(function_item "fn" @context name: (_) @name) @item"#)
+                .unwrap(),
+            ))
+        });
+        let symbol_index = cx.update(|cx| SymbolIndex::project_index(project.clone(), cx));
+        assert!(symbol_index.is_some());
+        cx.run_until_parked();
+
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let symbols = cx.new_view(|cx| {
+            Picker::uniform_list(
+                ProjectSymbolsDelegate::new(workspace.downgrade(), project.clone(), symbol_index),
+                cx,
+            )
+        });
+
+        symbols.update(cx, |p, cx| {
+            p.update_matches("help".to_string(), cx);
+        });
+        cx.run_until_parked();
+        symbols.update(cx, |symbols, cx| {
+            let delegate = &symbols.delegate;
+            assert_eq!(delegate.matches.len(), 1);
+            assert_eq!(delegate.matches[0].string, "helper");
+            assert_eq!(delegate.matches[0].positions, vec![3, 4, 5, 6]);
+            symbols.delegate.confirm(false, cx);
+        });
+        cx.run_until_parked();
+
+        let editor = workspace.update(cx, |workspace, cx| {
+            workspace.active_item_as::<Editor>(cx).unwrap()
+        });
+        editor.update(cx, |editor, cx| {
+            assert_eq!(
+                editor.selections.newest::<Point>(cx).head(),
+                Point::new(2, 0)
+            );
+        });
+    }
+
     fn init_test(cx: &mut TestAppContext) {
         cx.update(|cx| {
             let store = SettingsStore::test(cx);
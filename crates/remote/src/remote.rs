@@ -0,0 +1,65 @@
+mod ssh_session;
+
+use anyhow::Result;
+use client::Client;
+use gpui::{AppContext, Task, VisualContext, WindowHandle};
+use project::{terminals::SshCommand, Project};
+use release_channel::AppVersion;
+use std::sync::Arc;
+use workspace::{AppState, Workspace};
+
+pub use ssh_session::{SshConnectionOptions, SshSession};
+
+/// Opens a window for a project on another machine, by launching a headless server there over
+/// SSH and connecting to it directly rather than through the collaboration server.
+pub fn open_ssh_project(
+    options: SshConnectionOptions,
+    path: String,
+    app_state: Arc<AppState>,
+    cx: &mut AppContext,
+) -> Task<Result<WindowHandle<Workspace>>> {
+    // Each session gets its own client, since a client only has a single connection.
+    let client = Client::new(
+        Arc::new(clock::RealSystemClock),
+        app_state.client.http_client(),
+        cx,
+    );
+    Project::register_client_handlers(&client);
+    let version = AppVersion::global(cx);
+
+    cx.spawn(|mut cx| async move {
+        let (session, connection) = SshSession::start(&options, version).await?;
+        client.connect_directly(connection, &cx).await?;
+        let ssh_command = SshCommand {
+            arguments: options.ssh_args(),
+        };
+        let project = Project::ssh(
+            path,
+            ssh_command,
+            client,
+            app_state.user_store.clone(),
+            app_state.languages.clone(),
+            app_state.fs.clone(),
+            cx.clone(),
+        )
+        .await?;
+
+        // Shut the server down along with the project.
+        project.update(&mut cx, |_, cx| {
+            cx.on_release(move |_, _| drop(session)).detach();
+        })?;
+
+        let workspace = cx.update(|cx| {
+            let options = (app_state.build_window_options)(None, cx);
+            cx.open_window(options, |cx| {
+                cx.new_view(|cx| Workspace::new(Default::default(), project, app_state.clone(), cx))
+            })
+        })?;
+        workspace.update(&mut cx, |_, cx| {
+            cx.activate(true);
+            cx.activate_window();
+        })?;
+
+        Ok(workspace)
+    })
+}
@@ -0,0 +1,336 @@
+use anyhow::{anyhow, Context as _, Result};
+use gpui::SemanticVersion;
+use rpc::Connection;
+use smol::process::{Child, Command, Stdio};
+use std::path::PathBuf;
+
+/// The directory, relative to the remote user's home directory, that the headless server binary
+/// is uploaded to.
+const REMOTE_SERVER_DIR: &str = ".zed_server";
+/// The name of the headless server binary, which is built and bundled alongside the app.
+const SERVER_BINARY_NAME: &str = "remote_server";
+
+/// How to reach a machine over SSH.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SshConnectionOptions {
+    pub host: String,
+    pub username: Option<String>,
+    pub port: Option<u16>,
+}
+
+impl SshConnectionOptions {
+    /// Parses an `ssh://[user@]host[:port][/path]` URL into the options for connecting and the
+    /// path to open, which defaults to the remote user's home directory. A path starting with
+    /// `/~` is taken to be relative to the home directory.
+    pub fn parse_url(url: &str) -> Result<(Self, String)> {
+        let rest = url
+            .strip_prefix("ssh://")
+            .ok_or_else(|| anyhow!("{url:?} is not an ssh:// URL"))?;
+        let (authority, path) = match rest.find('/') {
+            Some(ix) => rest.split_at(ix),
+            None => (rest, ""),
+        };
+        let path = match path {
+            "" | "/" => "~".to_string(),
+            path => path
+                .strip_prefix('/')
+                .filter(|path| path.starts_with('~'))
+                .unwrap_or(path)
+                .to_string(),
+        };
+
+        let (username, host_and_port) = match authority.rsplit_once('@') {
+            Some((username, host_and_port)) => (Some(username.to_string()), host_and_port),
+            None => (None, authority),
+        };
+        let (host, port) = match host_and_port.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                Some(
+                    port.parse()
+                        .with_context(|| format!("invalid port {port:?} in {url:?}"))?,
+                ),
+            ),
+            None => (host_and_port, None),
+        };
+        if host.is_empty() {
+            return Err(anyhow!("{url:?} has no host"));
+        }
+        // `ssh` and `scp` would take these for options.
+        if host.starts_with('-') {
+            return Err(anyhow!("invalid host {host:?} in {url:?}"));
+        }
+        if username
+            .as_deref()
+            .map_or(false, |username| username.starts_with('-'))
+        {
+            return Err(anyhow!("invalid username in {url:?}"));
+        }
+
+        Ok((
+            Self {
+                host: host.to_string(),
+                username,
+                port,
+            },
+            path,
+        ))
+    }
+
+    fn destination(&self) -> String {
+        match &self.username {
+            Some(username) => format!("{}@{}", username, self.host),
+            None => self.host.clone(),
+        }
+    }
+
+    /// The arguments that select the host for `ssh`. The destination follows `--`, so that it's
+    /// never taken for an option.
+    pub fn ssh_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(port) = self.port {
+            args.push("-p".to_string());
+            args.push(port.to_string());
+        }
+        args.push("--".to_string());
+        args.push(self.destination());
+        args
+    }
+
+    /// Builds an `ssh` command that runs the given command on the remote machine. Batch mode
+    /// makes it fail rather than prompt for a password, since there's no terminal to prompt in.
+    fn ssh_command(&self, remote_command: &str) -> Command {
+        let mut command = Command::new("ssh");
+        command
+            .args(["-T", "-o", "BatchMode=yes"])
+            .args(self.ssh_args())
+            .arg(remote_command)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        command
+    }
+}
+
+/// The operating system and architecture of a machine, named as in [`std::env::consts`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Platform {
+    os: &'static str,
+    arch: &'static str,
+}
+
+impl Platform {
+    fn local() -> Self {
+        Self {
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+        }
+    }
+
+    /// Parses the output of `uname -sm`. Only the last line is used, since the startup files of
+    /// the remote shell may print something first.
+    fn parse_uname(output: &str) -> Result<Self> {
+        let line = output
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .last()
+            .ok_or_else(|| anyhow!("uname printed nothing"))?;
+        let (os, arch) = line
+            .split_once(' ')
+            .ok_or_else(|| anyhow!("unexpected output from uname: {line:?}"))?;
+        let os = match os {
+            "Linux" => "linux",
+            "Darwin" => "macos",
+            _ => return Err(anyhow!("unsupported remote operating system {os:?}")),
+        };
+        let arch = match arch.trim() {
+            "x86_64" | "amd64" => "x86_64",
+            "aarch64" | "arm64" => "aarch64",
+            arch => return Err(anyhow!("unsupported remote architecture {arch:?}")),
+        };
+        Ok(Self { os, arch })
+    }
+}
+
+/// A headless Zed server running on another machine, which the app talks to over the standard
+/// input and output of an SSH process. The server exits when the session is dropped.
+pub struct SshSession {
+    _process: Child,
+}
+
+impl SshSession {
+    /// Launches the headless server on the remote machine, uploading the server binary that's
+    /// bundled with the app first if the remote machine doesn't have this version of it yet.
+    /// Fails if the remote machine runs a different platform than this one, since the bundled
+    /// server couldn't run there.
+    pub async fn start(
+        options: &SshConnectionOptions,
+        version: SemanticVersion,
+    ) -> Result<(Self, Connection)> {
+        let uname = run(options.ssh_command("uname -sm"))
+            .await
+            .context("failed to detect the remote platform")?;
+        let remote_platform = Platform::parse_uname(&uname)?;
+        let local_platform = Platform::local();
+        if remote_platform != local_platform {
+            return Err(anyhow!(
+                "{} runs {} on {}, but this build of Zed is for {} on {}",
+                options.host,
+                remote_platform.os,
+                remote_platform.arch,
+                local_platform.os,
+                local_platform.arch,
+            ));
+        }
+
+        let server_path = format!("{REMOTE_SERVER_DIR}/{SERVER_BINARY_NAME}-{version}");
+        if run(options.ssh_command(&format!("test -x {server_path}")))
+            .await
+            .is_err()
+        {
+            upload_server(options, &server_path).await?;
+        }
+
+        log::info!("starting headless server on {}", options.host);
+        let mut process = options
+            .ssh_command(&server_path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("failed to start ssh")?;
+        let stdin = process
+            .stdin
+            .take()
+            .context("failed to get stdin for ssh")?;
+        let stdout = process
+            .stdout
+            .take()
+            .context("failed to get stdout for ssh")?;
+
+        Ok((
+            Self { _process: process },
+            Connection::framed(stdout, stdin),
+        ))
+    }
+}
+
+async fn upload_server(options: &SshConnectionOptions, server_path: &str) -> Result<()> {
+    let local_path = local_server_path()?;
+    log::info!("uploading {local_path:?} to {}:{server_path}", options.host);
+
+    run(options.ssh_command(&format!("mkdir -p {REMOTE_SERVER_DIR}"))).await?;
+    let mut scp = Command::new("scp");
+    scp.args(["-q", "-o", "BatchMode=yes"]);
+    if let Some(port) = options.port {
+        scp.arg("-P").arg(port.to_string());
+    }
+    scp.arg("--")
+        .arg(&local_path)
+        .arg(format!("{}:{server_path}", options.destination()))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    run(scp).await?;
+    run(options.ssh_command(&format!("chmod +x {server_path}"))).await?;
+    Ok(())
+}
+
+/// Finds the headless server binary, which sits next to the app's executable.
+fn local_server_path() -> Result<PathBuf> {
+    let app_path = std::env::current_exe().context("failed to find the current executable")?;
+    let server_path = app_path.with_file_name(SERVER_BINARY_NAME);
+    if server_path.is_file() {
+        Ok(server_path)
+    } else {
+        Err(anyhow!(
+            "the headless server wasn't found at {server_path:?}. Build it with `cargo build -p {SERVER_BINARY_NAME}`"
+        ))
+    }
+}
+
+/// Runs the command and returns its standard output.
+async fn run(mut command: Command) -> Result<String> {
+    let output = command.output().await?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(anyhow!(
+            "command failed with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_url() {
+        assert_eq!(
+            SshConnectionOptions::parse_url("ssh://me@example.com:2222/srv/app").unwrap(),
+            (
+                SshConnectionOptions {
+                    host: "example.com".into(),
+                    username: Some("me".into()),
+                    port: Some(2222),
+                },
+                "/srv/app".into()
+            )
+        );
+        assert_eq!(
+            SshConnectionOptions::parse_url("ssh://example.com/~/code").unwrap(),
+            (
+                SshConnectionOptions {
+                    host: "example.com".into(),
+                    username: None,
+                    port: None,
+                },
+                "~/code".into()
+            )
+        );
+        assert_eq!(
+            SshConnectionOptions::parse_url("ssh://example.com")
+                .unwrap()
+                .1,
+            "~"
+        );
+        assert!(SshConnectionOptions::parse_url("ssh://me@:22/").is_err());
+        assert!(SshConnectionOptions::parse_url("ssh://host:port/").is_err());
+        assert!(SshConnectionOptions::parse_url("https://example.com").is_err());
+        assert!(SshConnectionOptions::parse_url("ssh://-oProxyCommand=evil/").is_err());
+        assert!(SshConnectionOptions::parse_url("ssh://-oProxyCommand=evil@example.com/").is_err());
+    }
+
+    #[test]
+    fn test_ssh_args() {
+        let (options, _) = SshConnectionOptions::parse_url("ssh://me@example.com:2222").unwrap();
+        assert_eq!(options.ssh_args(), ["-p", "2222", "--", "me@example.com"]);
+    }
+
+    #[test]
+    fn test_parse_uname() {
+        assert_eq!(
+            Platform::parse_uname("Linux x86_64\n").unwrap(),
+            Platform {
+                os: "linux",
+                arch: "x86_64"
+            }
+        );
+        assert_eq!(
+            Platform::parse_uname("Welcome!\n\nDarwin arm64\n").unwrap(),
+            Platform {
+                os: "macos",
+                arch: "aarch64"
+            }
+        );
+        assert!(Platform::parse_uname("").is_err());
+        assert!(Platform::parse_uname("FreeBSD amd64").is_err());
+        assert!(Platform::parse_uname("Linux riscv64").is_err());
+    }
+}
@@ -0,0 +1,56 @@
+//! The headless server that the app uploads to another machine and launches over SSH. It serves
+//! projects over its standard input and output, and exits once the app disconnects.
+
+use client::{Client, UserStore};
+use fs::RealFs;
+use git::GitHostingProviderRegistry;
+use gpui::{App, Context as _, Task};
+use language::LanguageRegistry;
+use node_runtime::RealNodeRuntime;
+use std::sync::Arc;
+use util::{paths, ResultExt};
+
+fn main() {
+    // The standard output carries the connection to the app, so this only logs to the standard
+    // error.
+    env_logger::init();
+
+    let git_hosting_provider_registry = Arc::new(GitHostingProviderRegistry::new());
+    let fs = Arc::new(RealFs::new(git_hosting_provider_registry.clone(), None));
+
+    App::new().run(move |cx| {
+        release_channel::init(env!("CARGO_PKG_VERSION"), cx);
+        GitHostingProviderRegistry::set_global(git_hosting_provider_registry, cx);
+        settings::init(cx);
+        client::init_settings(cx);
+
+        let client = Client::production(cx);
+        let mut languages =
+            LanguageRegistry::new(Task::ready(()), cx.background_executor().clone());
+        languages.set_language_server_download_dir(paths::LANGUAGES_DIR.clone());
+        let languages = Arc::new(languages);
+        let node_runtime = RealNodeRuntime::new(client.http_client());
+
+        language::init(cx);
+        languages::init(languages.clone(), node_runtime.clone(), cx);
+        let user_store = cx.new_model(|cx| UserStore::new(client.clone(), cx));
+        project::Project::init(&client, cx);
+        client::init(&client, cx);
+
+        let serve = headless::serve_stdio(
+            client,
+            headless::AppState {
+                node_runtime,
+                user_store,
+                languages,
+                fs,
+            },
+            cx,
+        );
+        cx.spawn(|cx| async move {
+            serve.await.log_err();
+            cx.update(|cx| cx.quit()).log_err();
+        })
+        .detach();
+    });
+}
@@ -1240,6 +1240,30 @@ mod tests {
         assert_eq!(rope.text(), text);
     }
 
+    #[gpui::test(iterations = 10)]
+    fn test_push_chunks(mut rng: StdRng) {
+        let text = RandomCharIter::new(&mut rng)
+            .take(10 * CHUNK_BASE)
+            .collect::<String>();
+        let mut rope = Rope::new();
+        let mut remaining = text.as_str();
+        while !remaining.is_empty() {
+            let len = clip_offset(
+                remaining,
+                rng.gen_range(1..=remaining.len()).min(300),
+                Right,
+            );
+            rope.push(&remaining[..len]);
+            remaining = &remaining[len..];
+        }
+
+        let expected = Rope::from(text.as_str());
+        assert_eq!(rope.text(), text);
+        assert_eq!(rope.summary(), expected.summary());
+        assert_eq!(rope.max_point(), expected.max_point());
+        rope.check_invariants();
+    }
+
     #[test]
     fn test_clip() {
         let rope = Rope::from("🧘");
@@ -1,6 +1,15 @@
 use async_tungstenite::tungstenite::Message as WebSocketMessage;
 use futures::{SinkExt as _, StreamExt as _};
 
+/// Starts every connection created with [`Connection::framed`].
+const FRAMED_PREAMBLE: &[u8] = b"\0zed-rpc\0";
+/// The version of the framing, written after the preamble.
+const FRAMED_VERSION: u32 = 1;
+/// How many bytes may precede the preamble of a framed connection.
+const MAX_FRAMED_PREAMBLE_OFFSET: usize = 1024 * 1024;
+/// The largest payload of a framed message, so that a corrupt length can't exhaust the memory.
+const MAX_FRAME_LEN: usize = 256 * 1024 * 1024;
+
 pub struct Connection {
     pub(crate) tx:
         Box<dyn 'static + Send + Unpin + futures::Sink<WebSocketMessage, Error = anyhow::Error>>,
@@ -32,6 +41,128 @@ impl Connection {
         self.tx.send(message).await
     }
 
+    /// Creates a connection over a pair of byte streams, such as the standard input and output of
+    /// a process, rather than a WebSocket. Each side first writes [`FRAMED_PREAMBLE`] and the
+    /// protocol version. Each message is then written as a frame containing its kind and length,
+    /// followed by its payload.
+    ///
+    /// Anything the other side writes before its preamble is skipped, such as a banner printed
+    /// by the startup files of the shell that runs it.
+    pub fn framed<R, W>(reader: R, writer: W) -> Self
+    where
+        R: 'static + Send + Unpin + futures::AsyncRead,
+        W: 'static + Send + Unpin + futures::AsyncWrite,
+    {
+        use anyhow::{anyhow, Context as _};
+        use futures::{AsyncReadExt as _, AsyncWriteExt as _};
+
+        const BINARY: u8 = 0;
+        const PING: u8 = 1;
+        const PONG: u8 = 2;
+        const CLOSE: u8 = 3;
+
+        let tx = futures::sink::unfold(
+            (writer, false),
+            |(mut writer, mut wrote_preamble), message: WebSocketMessage| async move {
+                if !wrote_preamble {
+                    writer.write_all(FRAMED_PREAMBLE).await?;
+                    writer.write_all(&FRAMED_VERSION.to_le_bytes()).await?;
+                    wrote_preamble = true;
+                }
+                let (kind, payload) = match message {
+                    WebSocketMessage::Ping(payload) => (PING, payload),
+                    WebSocketMessage::Pong(payload) => (PONG, payload),
+                    WebSocketMessage::Close(_) => (CLOSE, Vec::new()),
+                    message => (BINARY, message.into_data()),
+                };
+                anyhow::ensure!(
+                    payload.len() <= MAX_FRAME_LEN,
+                    "message of {} bytes is too large to send",
+                    payload.len()
+                );
+                let len = payload.len() as u32;
+                writer.write_all(&[kind]).await?;
+                writer.write_all(&len.to_le_bytes()).await?;
+                writer.write_all(&payload).await?;
+                writer.flush().await?;
+                anyhow::Ok((writer, wrote_preamble))
+            },
+        );
+
+        let rx = futures::stream::unfold(Some((reader, false)), |state| async move {
+            let (mut reader, mut read_preamble) = state?;
+            if !read_preamble {
+                let result = async {
+                    let mut skipped = 0;
+                    let mut window = Vec::with_capacity(FRAMED_PREAMBLE.len());
+                    let mut byte = [0];
+                    while window != FRAMED_PREAMBLE {
+                        anyhow::ensure!(
+                            skipped < MAX_FRAMED_PREAMBLE_OFFSET,
+                            "the connection did not start with a zed preamble"
+                        );
+                        reader.read_exact(&mut byte).await?;
+                        if window.len() == FRAMED_PREAMBLE.len() {
+                            window.remove(0);
+                            skipped += 1;
+                        }
+                        window.push(byte[0]);
+                    }
+                    if skipped > 0 {
+                        tracing::warn!("skipped {skipped} bytes before the start of the connection");
+                    }
+
+                    let mut version = [0; 4];
+                    reader.read_exact(&mut version).await?;
+                    let version = u32::from_le_bytes(version);
+                    anyhow::ensure!(
+                        version == FRAMED_VERSION,
+                        "the other side speaks version {version} of the protocol, expected {FRAMED_VERSION}"
+                    );
+                    anyhow::Ok(())
+                }
+                .await
+                .context("failed to start the connection");
+                if let Err(error) = result {
+                    return Some((Err(error), None));
+                }
+                read_preamble = true;
+            }
+
+            let mut header = [0; 5];
+            if reader.read_exact(&mut header).await.is_err() {
+                return None;
+            }
+            let [kind, len @ ..] = header;
+            let len = u32::from_le_bytes(len) as usize;
+            if len > MAX_FRAME_LEN {
+                return Some((
+                    Err(anyhow!(
+                        "received a frame of {len} bytes, which is too large"
+                    )),
+                    None,
+                ));
+            }
+            let mut payload = vec![0; len];
+            let message = match reader.read_exact(&mut payload).await {
+                Ok(()) => match kind {
+                    BINARY => Ok(WebSocketMessage::Binary(payload)),
+                    PING => Ok(WebSocketMessage::Ping(payload)),
+                    PONG => Ok(WebSocketMessage::Pong(payload)),
+                    CLOSE => Ok(WebSocketMessage::Close(None)),
+                    _ => Err(anyhow!("invalid message kind {kind}")),
+                },
+                Err(error) => Err(error.into()),
+            };
+            Some((message, Some((reader, read_preamble))))
+        });
+
+        Self {
+            tx: Box::new(Box::pin(tx)),
+            rx: Box::new(Box::pin(rx)),
+        }
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     pub fn in_memory(
         executor: gpui::BackgroundExecutor,
@@ -105,3 +236,66 @@ impl Connection {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{executor::block_on, io::Cursor, StreamExt as _};
+
+    #[test]
+    fn test_framed_skips_banner() {
+        let mut input = b"Welcome to example.com\n".to_vec();
+        input.extend_from_slice(FRAMED_PREAMBLE);
+        input.extend_from_slice(&FRAMED_VERSION.to_le_bytes());
+        input.extend_from_slice(&frame(0, b"hello"));
+        input.extend_from_slice(&frame(1, b""));
+
+        let mut connection = Connection::framed(Cursor::new(input), futures::io::sink());
+        block_on(async {
+            assert_eq!(
+                connection.rx.next().await.unwrap().unwrap(),
+                WebSocketMessage::Binary(b"hello".to_vec())
+            );
+            assert_eq!(
+                connection.rx.next().await.unwrap().unwrap(),
+                WebSocketMessage::Ping(Vec::new())
+            );
+            assert!(connection.rx.next().await.is_none());
+        });
+    }
+
+    #[test]
+    fn test_framed_rejects_invalid_input() {
+        let mut input = FRAMED_PREAMBLE.to_vec();
+        input.extend_from_slice(&(FRAMED_VERSION + 1).to_le_bytes());
+        input.extend_from_slice(&frame(0, b"hello"));
+        let mut connection = Connection::framed(Cursor::new(input), futures::io::sink());
+        block_on(async {
+            assert!(connection.rx.next().await.unwrap().is_err());
+            assert!(connection.rx.next().await.is_none());
+        });
+
+        let mut input = FRAMED_PREAMBLE.to_vec();
+        input.extend_from_slice(&FRAMED_VERSION.to_le_bytes());
+        input.push(0);
+        input.extend_from_slice(&u32::MAX.to_le_bytes());
+        let mut connection = Connection::framed(Cursor::new(input), futures::io::sink());
+        block_on(async {
+            assert!(connection.rx.next().await.unwrap().is_err());
+            assert!(connection.rx.next().await.is_none());
+        });
+
+        let input = vec![b'x'; MAX_FRAMED_PREAMBLE_OFFSET + FRAMED_PREAMBLE.len()];
+        let mut connection = Connection::framed(Cursor::new(input), futures::io::sink());
+        block_on(async {
+            assert!(connection.rx.next().await.unwrap().is_err());
+        });
+    }
+
+    fn frame(kind: u8, payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![kind];
+        frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        frame.extend_from_slice(payload);
+        frame
+    }
+}
@@ -56,6 +56,12 @@ macro_rules! request_messages {
         $(impl RequestMessage for $request_name {
             type Response = $response_name;
         })*
+
+        /// Returns whether messages with the given name are requests, whose senders wait for a
+        /// response.
+        pub fn is_request_message(name: &str) -> bool {
+            matches!(name, $(stringify!($request_name))|*)
+        }
     };
 }
 
@@ -148,7 +148,6 @@ impl Peer {
         self.epoch.load(SeqCst)
     }
 
-    #[instrument(skip_all)]
     pub fn add_connection<F, Fut, Out>(
         self: &Arc<Self>,
         connection: Connection,
@@ -158,6 +157,44 @@ impl Peer {
         impl Future<Output = anyhow::Result<()>> + Send,
         BoxStream<'static, Box<dyn AnyTypedEnvelope>>,
     )
+    where
+        F: Send + Fn(Duration) -> Fut,
+        Fut: Send + Future<Output = Out>,
+        Out: Send,
+    {
+        self.add_connection_internal(connection, false, create_timer)
+    }
+
+    /// Adds a connection straight to another peer, rather than to a server that forwards messages
+    /// between peers. Messages received on it are treated as originating from the connected peer.
+    pub fn add_direct_connection<F, Fut, Out>(
+        self: &Arc<Self>,
+        connection: Connection,
+        create_timer: F,
+    ) -> (
+        ConnectionId,
+        impl Future<Output = anyhow::Result<()>> + Send,
+        BoxStream<'static, Box<dyn AnyTypedEnvelope>>,
+    )
+    where
+        F: Send + Fn(Duration) -> Fut,
+        Fut: Send + Future<Output = Out>,
+        Out: Send,
+    {
+        self.add_connection_internal(connection, true, create_timer)
+    }
+
+    #[instrument(skip_all)]
+    fn add_connection_internal<F, Fut, Out>(
+        self: &Arc<Self>,
+        connection: Connection,
+        is_direct: bool,
+        create_timer: F,
+    ) -> (
+        ConnectionId,
+        impl Future<Output = anyhow::Result<()>> + Send,
+        BoxStream<'static, Box<dyn AnyTypedEnvelope>>,
+    )
     where
         F: Send + Fn(Duration) -> Fut,
         Fut: Send + Future<Output = Out>,
@@ -300,10 +337,13 @@ impl Peer {
             .write()
             .insert(connection_id, connection_state);
 
-        let incoming_rx = incoming_rx.filter_map(move |(incoming, received_at)| {
+        let incoming_rx = incoming_rx.filter_map(move |(mut incoming, received_at)| {
             let response_channels = response_channels.clone();
             let stream_response_channels = stream_response_channels.clone();
             async move {
+                if is_direct && incoming.original_sender_id.is_none() {
+                    incoming.original_sender_id = Some(connection_id.into());
+                }
                 let message_id = incoming.id;
                 tracing::trace!(?incoming, "incoming message future: start");
                 let _end = util::defer(move || {
@@ -680,6 +720,28 @@ impl Peer {
         Ok(())
     }
 
+    /// Responds to a request that was handled as a message. Peers that are connected directly
+    /// send requests that a server would otherwise have answered on the receiver's behalf.
+    pub fn acknowledge(
+        &self,
+        sender_id: ConnectionId,
+        message_id: u32,
+        result: Result<(), proto::Error>,
+    ) -> Result<()> {
+        let connection = self.connection_state(sender_id)?;
+        let response_id = connection
+            .next_message_id
+            .fetch_add(1, atomic::Ordering::SeqCst);
+        let response = match result {
+            Ok(()) => proto::Ack {}.into_envelope(response_id, Some(message_id), None),
+            Err(error) => error.into_envelope(response_id, Some(message_id), None),
+        };
+        connection
+            .outgoing_tx
+            .unbounded_send(proto::Message::Envelope(response))?;
+        Ok(())
+    }
+
     fn connection_state(&self, connection_id: ConnectionId) -> Result<ConnectionState> {
         let connections = self.connections.read();
         let connection = connections
@@ -1079,4 +1141,30 @@ mod tests {
             "connection was closed"
         );
     }
+
+    #[gpui::test(iterations = 10)]
+    async fn test_direct_connection(cx: &mut TestAppContext) {
+        let executor = cx.executor();
+        let host = Peer::new(0);
+        let guest = Peer::new(0);
+
+        let (host_conn, guest_conn, _kill) = Connection::in_memory(executor.clone());
+        let (_, io_task1, mut host_incoming) = host.add_direct_connection(host_conn, {
+            let executor = executor.clone();
+            move |duration| executor.timer(duration)
+        });
+        let (guest_conn_id, io_task2, _guest_incoming) =
+            guest.add_test_connection(guest_conn, executor.clone());
+        executor.spawn(io_task1).detach();
+        executor.spawn(io_task2).detach();
+
+        let response = executor.spawn(guest.request(guest_conn_id, proto::Ping {}));
+        let request = host_incoming.next().await.unwrap();
+        let connection_id = request.sender_id();
+        assert_eq!(request.original_sender_id(), Some(connection_id.into()));
+
+        host.acknowledge(connection_id, request.message_id(), Ok(()))
+            .unwrap();
+        assert_eq!(response.await.unwrap(), proto::Ack {});
+    }
 }
@@ -315,6 +315,7 @@ messages!(
     (ReconnectDevServerResponse, Foreground),
     (ShareDevServerProject, Foreground),
     (JoinDevServerProject, Foreground),
+    (JoinSshProject, Foreground),
     (RejoinRemoteProjects, Foreground),
     (RejoinRemoteProjectsResponse, Foreground),
     (MultiLspQuery, Background),
@@ -427,6 +428,7 @@ request_messages!(
     (ShutdownDevServer, Ack),
     (ShareDevServerProject, ShareProjectResponse),
     (JoinDevServerProject, JoinProjectResponse),
+    (JoinSshProject, JoinProjectResponse),
     (RejoinRemoteProjects, RejoinRemoteProjectsResponse),
     (ReconnectDevServer, ReconnectDevServerResponse),
     (ValidateDevServerProjectRequest, Ack),
@@ -18,22 +18,24 @@ use gpui::{
     SharedString, Styled, Subscription, Task, TextStyle, View, ViewContext, VisualContext,
     WeakModel, WeakView, WhiteSpace, WindowContext,
 };
+use language::Bias;
 use menu::Confirm;
 use project::{search::SearchQuery, search_history::SearchHistoryCursor, Project, ProjectPath};
+use semantic_index::SemanticIndex;
 use settings::Settings;
 use smol::stream::StreamExt;
 use std::{
     any::{Any, TypeId},
     mem,
     ops::{Not, Range},
-    path::Path,
+    path::{Path, PathBuf},
 };
 use theme::ThemeSettings;
 use ui::{
     h_flex, prelude::*, v_flex, Icon, IconButton, IconName, Label, LabelCommon, LabelSize,
     Selectable, Tooltip,
 };
-use util::paths::PathMatcher;
+use util::{paths::PathMatcher, ResultExt};
 use workspace::{
     item::{BreadcrumbText, Item, ItemEvent, ItemHandle, TabContentParams},
     searchable::{Direction, SearchableItem, SearchableItemHandle},
@@ -43,10 +45,17 @@ use workspace::{
 
 const MIN_INPUT_WIDTH_REMS: f32 = 15.;
 const MAX_INPUT_WIDTH_REMS: f32 = 30.;
+const SEMANTIC_SEARCH_LIMIT: usize = 50;
 
 actions!(
     project_search,
-    [SearchInNew, ToggleFocus, NextField, ToggleFilters]
+    [
+        SearchInNew,
+        ToggleFocus,
+        NextField,
+        ToggleFilters,
+        ToggleSemanticSearch
+    ]
 );
 
 #[derive(Default)]
@@ -63,6 +72,12 @@ pub fn init(cx: &mut AppContext) {
         register_workspace_action(workspace, move |search_bar, _: &ToggleFilters, cx| {
             search_bar.toggle_filters(cx);
         });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleSemanticSearch, cx| {
+                search_bar.toggle_semantic_search(cx);
+            },
+        );
         register_workspace_action(workspace, move |search_bar, _: &ToggleCaseSensitive, cx| {
             search_bar.toggle_search_option(SearchOptions::CASE_SENSITIVE, cx);
         });
@@ -123,6 +138,7 @@ struct ProjectSearch {
     last_search_query_text: Option<String>,
     search_id: usize,
     no_results: Option<bool>,
+    search_error: Option<SharedString>,
     limit_reached: bool,
     search_history_cursor: SearchHistoryCursor,
 }
@@ -149,6 +165,7 @@ pub struct ProjectSearchView {
     excluded_files_editor: View<Editor>,
     filters_enabled: bool,
     replace_enabled: bool,
+    semantic_search_enabled: bool,
     _subscriptions: Vec<Subscription>,
 }
 
@@ -177,6 +194,7 @@ impl ProjectSearch {
             last_search_query_text: None,
             search_id: 0,
             no_results: None,
+            search_error: None,
             limit_reached: false,
             search_history_cursor: Default::default(),
         }
@@ -194,6 +212,7 @@ impl ProjectSearch {
             last_search_query_text: self.last_search_query_text.clone(),
             search_id: self.search_id,
             no_results: self.no_results,
+            search_error: self.search_error.clone(),
             limit_reached: self.limit_reached,
             search_history_cursor: self.search_history_cursor.clone(),
         })
@@ -217,6 +236,7 @@ impl ProjectSearch {
                 this.match_ranges.clear();
                 this.excerpts.update(cx, |this, cx| this.clear(cx));
                 this.no_results = Some(true);
+                this.search_error = None;
                 this.limit_reached = false;
             })
             .ok()?;
@@ -262,6 +282,154 @@ impl ProjectSearch {
         }));
         cx.notify();
     }
+
+    /// Searches the project's semantic index, which finds code related to the query even when it
+    /// doesn't contain the query's exact text. Only the query's text and its included and excluded
+    /// files are used.
+    fn semantic_search(&mut self, query: SearchQuery, cx: &mut ModelContext<Self>) {
+        let query_text = query.as_str().to_string();
+        let project_index = cx.update_global(|semantic_index: &mut SemanticIndex, cx| {
+            semantic_index.project_index(self.project.clone(), cx)
+        });
+        let search =
+            project_index
+                .read(cx)
+                .search_hybrid(query_text.clone(), SEMANTIC_SEARCH_LIMIT, cx);
+        self.project.update(cx, |project, _| {
+            project
+                .search_history_mut()
+                .add(&mut self.search_history_cursor, query_text.clone());
+        });
+        self.last_search_query_text = Some(query_text);
+        self.search_id += 1;
+        self.active_query = None;
+        self.match_ranges.clear();
+        self.pending_search = Some(cx.spawn(|this, mut cx| async move {
+            let search_result = search.await;
+            let this = this.upgrade()?;
+            let results = match search_result {
+                Ok(results) => results,
+                Err(error) => {
+                    this.update(&mut cx, |this, cx| {
+                        this.match_ranges.clear();
+                        this.excerpts.update(cx, |this, cx| this.clear(cx));
+                        this.no_results = Some(true);
+                        this.search_error = Some(format!("{error:#}").into());
+                        this.limit_reached = false;
+                        this.pending_search.take();
+                        cx.notify();
+                    })
+                    .ok()?;
+                    return None;
+                }
+            };
+
+            // Match paths the same way as a text search, prefixing them with the worktree's root
+            // name when the project has several worktrees.
+            let include_root = this
+                .update(&mut cx, |this, cx| {
+                    this.project.read(cx).visible_worktrees(cx).count() > 1
+                })
+                .ok()?;
+            let mut filtered_results = Vec::with_capacity(results.len());
+            for result in results {
+                let (worktree_id, root_name) = result
+                    .worktree
+                    .read_with(&cx, |worktree, _| {
+                        (worktree.id(), worktree.root_name().to_string())
+                    })
+                    .ok()?;
+                let matches = if include_root {
+                    let mut full_path = PathBuf::from(root_name);
+                    full_path.push(&result.path);
+                    query.file_matches(Some(&full_path))
+                } else {
+                    query.file_matches(Some(&result.path))
+                };
+                if matches {
+                    filtered_results.push((worktree_id, result));
+                }
+            }
+
+            this.update(&mut cx, |this, cx| {
+                this.match_ranges.clear();
+                this.excerpts.update(cx, |this, cx| this.clear(cx));
+                this.no_results = Some(filtered_results.is_empty());
+                this.search_error = None;
+                this.limit_reached = false;
+            })
+            .ok()?;
+
+            // Keep each file's excerpts together, ordered by the file's best result.
+            let mut ranges_by_path = Vec::<(ProjectPath, Vec<Range<usize>>)>::new();
+            for (worktree_id, result) in filtered_results {
+                let project_path = ProjectPath {
+                    worktree_id,
+                    path: result.path,
+                };
+                if let Some((_, ranges)) = ranges_by_path
+                    .iter_mut()
+                    .find(|(path, _)| *path == project_path)
+                {
+                    ranges.push(result.range);
+                } else {
+                    ranges_by_path.push((project_path, vec![result.range]));
+                }
+            }
+
+            for (project_path, mut ranges) in ranges_by_path {
+                let open_buffer = this
+                    .update(&mut cx, |this, cx| {
+                        this.project
+                            .update(cx, |project, cx| project.open_buffer(project_path, cx))
+                    })
+                    .ok()?;
+                let Some(buffer) = open_buffer.await.log_err() else {
+                    continue;
+                };
+
+                ranges.sort_unstable_by_key(|range| range.start);
+                let mut match_ranges = this
+                    .update(&mut cx, |this, cx| {
+                        let snapshot = buffer.read(cx).snapshot();
+                        let ranges = ranges
+                            .into_iter()
+                            .map(|range| {
+                                let start = snapshot
+                                    .clip_offset(range.start.min(snapshot.len()), Bias::Left);
+                                let end = snapshot
+                                    .clip_offset(range.end.min(snapshot.len()), Bias::Right);
+                                snapshot.anchor_before(start)..snapshot.anchor_after(end)
+                            })
+                            .collect();
+                        this.excerpts.update(cx, |excerpts, cx| {
+                            excerpts.stream_excerpts_with_context_lines(
+                                buffer,
+                                ranges,
+                                editor::DEFAULT_MULTIBUFFER_CONTEXT,
+                                cx,
+                            )
+                        })
+                    })
+                    .ok()?;
+
+                while let Some(range) = match_ranges.next().await {
+                    this.update(&mut cx, |this, _| this.match_ranges.push(range))
+                        .ok()?;
+                }
+                this.update(&mut cx, |_, cx| cx.notify()).ok()?;
+            }
+
+            this.update(&mut cx, |this, cx| {
+                this.pending_search.take();
+                cx.notify();
+            })
+            .ok()?;
+
+            None
+        }));
+        cx.notify();
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -288,6 +456,8 @@ impl Render for ProjectSearchView {
             let is_search_underway = model.pending_search.is_some();
             let major_text = if is_search_underway {
                 Label::new("Searching...")
+            } else if model.search_error.is_some() {
+                Label::new("Search failed")
             } else if has_no_results {
                 Label::new("No results")
             } else {
@@ -297,7 +467,9 @@ impl Render for ProjectSearchView {
             let major_text = div().justify_center().max_w_96().child(major_text);
 
             let minor_text: Option<SharedString> = if let Some(no_results) = model.no_results {
-                if model.pending_search.is_none() && no_results {
+                if model.pending_search.is_none() && model.search_error.is_some() {
+                    model.search_error.clone()
+                } else if model.pending_search.is_none() && no_results {
                     Some("No results found in this project for the provided query".into())
                 } else {
                     None
@@ -721,6 +893,7 @@ impl ProjectSearchView {
             excluded_files_editor,
             filters_enabled,
             replace_enabled: false,
+            semantic_search_enabled: false,
             _subscriptions: subscriptions,
         };
         this.model_changed(cx);
@@ -853,49 +1026,34 @@ impl ProjectSearchView {
     }
 
     fn search(&mut self, cx: &mut ViewContext<Self>) {
-        if let Some(query) = self.build_search_query(cx) {
+        if self.semantic_search_enabled && cx.has_global::<SemanticIndex>() {
+            if let Some(query) = self.build_semantic_search_query(cx) {
+                self.model
+                    .update(cx, |model, cx| model.semantic_search(query, cx));
+            }
+        } else if let Some(query) = self.build_search_query(cx) {
             self.model.update(cx, |model, cx| model.search(query, cx));
         }
     }
 
+    /// Builds a plain text query for the semantic search, ignoring the text search options which
+    /// don't apply to it.
+    fn build_semantic_search_query(&mut self, cx: &mut ViewContext<Self>) -> Option<SearchQuery> {
+        let text = self.query_editor.read(cx).text(cx);
+        let (included_files, excluded_files) = self.parse_included_and_excluded_files(cx);
+        if self.panels_with_errors.remove(&InputPanel::Query) {
+            cx.notify();
+        }
+        if !self.panels_with_errors.is_empty() || text.is_empty() {
+            return None;
+        }
+        SearchQuery::text(text, false, false, false, included_files, excluded_files).log_err()
+    }
+
     fn build_search_query(&mut self, cx: &mut ViewContext<Self>) -> Option<SearchQuery> {
         // Do not bail early in this function, as we want to fill out `self.panels_with_errors`.
         let text = self.query_editor.read(cx).text(cx);
-        let included_files =
-            match Self::parse_path_matches(&self.included_files_editor.read(cx).text(cx)) {
-                Ok(included_files) => {
-                    let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Include);
-                    if should_unmark_error {
-                        cx.notify();
-                    }
-                    included_files
-                }
-                Err(_e) => {
-                    let should_mark_error = self.panels_with_errors.insert(InputPanel::Include);
-                    if should_mark_error {
-                        cx.notify();
-                    }
-                    vec![]
-                }
-            };
-        let excluded_files =
-            match Self::parse_path_matches(&self.excluded_files_editor.read(cx).text(cx)) {
-                Ok(excluded_files) => {
-                    let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Exclude);
-                    if should_unmark_error {
-                        cx.notify();
-                    }
-
-                    excluded_files
-                }
-                Err(_e) => {
-                    let should_mark_error = self.panels_with_errors.insert(InputPanel::Exclude);
-                    if should_mark_error {
-                        cx.notify();
-                    }
-                    vec![]
-                }
-            };
+        let (included_files, excluded_files) = self.parse_included_and_excluded_files(cx);
 
         let query = if self.search_options.contains(SearchOptions::REGEX) {
             match SearchQuery::regex(
@@ -959,6 +1117,48 @@ impl ProjectSearchView {
         query
     }
 
+    fn parse_included_and_excluded_files(
+        &mut self,
+        cx: &mut ViewContext<Self>,
+    ) -> (Vec<PathMatcher>, Vec<PathMatcher>) {
+        let included_files =
+            match Self::parse_path_matches(&self.included_files_editor.read(cx).text(cx)) {
+                Ok(included_files) => {
+                    let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Include);
+                    if should_unmark_error {
+                        cx.notify();
+                    }
+                    included_files
+                }
+                Err(_e) => {
+                    let should_mark_error = self.panels_with_errors.insert(InputPanel::Include);
+                    if should_mark_error {
+                        cx.notify();
+                    }
+                    vec![]
+                }
+            };
+        let excluded_files =
+            match Self::parse_path_matches(&self.excluded_files_editor.read(cx).text(cx)) {
+                Ok(excluded_files) => {
+                    let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Exclude);
+                    if should_unmark_error {
+                        cx.notify();
+                    }
+
+                    excluded_files
+                }
+                Err(_e) => {
+                    let should_mark_error = self.panels_with_errors.insert(InputPanel::Exclude);
+                    if should_mark_error {
+                        cx.notify();
+                    }
+                    vec![]
+                }
+            };
+        (included_files, excluded_files)
+    }
+
     fn parse_path_matches(text: &str) -> anyhow::Result<Vec<PathMatcher>> {
         text.split(',')
             .map(str::trim)
@@ -1193,6 +1393,19 @@ impl ProjectSearchBar {
         }
     }
 
+    fn toggle_semantic_search(&mut self, cx: &mut ViewContext<Self>) -> bool {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.semantic_search_enabled = !search_view.semantic_search_enabled;
+                cx.notify();
+            });
+            cx.notify();
+            true
+        } else {
+            false
+        }
+    }
+
     fn toggle_filters(&mut self, cx: &mut ViewContext<Self>) -> bool {
         if let Some(search_view) = self.active_project_search.as_ref() {
             search_view.update(cx, |search_view, cx| {
@@ -1402,7 +1615,28 @@ impl Render for ProjectSearchBar {
                                 .unwrap_or_default(),
                         )
                         .tooltip(|cx| Tooltip::for_action("Toggle replace", &ToggleReplace, cx)),
-                ),
+                )
+                .when(cx.has_global::<SemanticIndex>(), |this| {
+                    this.child(
+                        IconButton::new("project-search-toggle-semantic", IconName::Ai)
+                            .on_click(cx.listener(|this, _, cx| {
+                                this.toggle_semantic_search(cx);
+                            }))
+                            .selected(
+                                self.active_project_search
+                                    .as_ref()
+                                    .map(|search| search.read(cx).semantic_search_enabled)
+                                    .unwrap_or_default(),
+                            )
+                            .tooltip(|cx| {
+                                Tooltip::for_action(
+                                    "Toggle semantic search",
+                                    &ToggleSemanticSearch,
+                                    cx,
+                                )
+                            }),
+                    )
+                }),
         );
 
         let match_text = search
@@ -1563,6 +1797,9 @@ impl Render for ProjectSearchBar {
             .on_action(cx.listener(|this, _: &ToggleFilters, cx| {
                 this.toggle_filters(cx);
             }))
+            .on_action(cx.listener(|this, _: &ToggleSemanticSearch, cx| {
+                this.toggle_semantic_search(cx);
+            }))
             .capture_action(cx.listener(|this, action, cx| {
                 this.tab(action, cx);
                 cx.stop_propagation();
@@ -1689,11 +1926,13 @@ fn register_workspace_action_for_present_search<A: Action>(
 pub mod tests {
     use super::*;
     use editor::{display_map::DisplayRow, DisplayPoint};
+    use futures::{future::BoxFuture, FutureExt as _};
     use gpui::{Action, TestAppContext, WindowHandle};
     use project::FakeFs;
+    use semantic_index::{Embedding, EmbeddingProvider, TextToEmbed};
     use serde_json::json;
     use settings::SettingsStore;
-    use std::sync::Arc;
+    use std::{future, sync::Arc};
     use workspace::DeploySearch;
 
     #[gpui::test]
@@ -1819,6 +2058,73 @@ pub mod tests {
             .unwrap();
     }
 
+    #[gpui::test]
+    async fn test_semantic_search(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "notes.txt": "ONE comes before TWO",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let semantic_index = SemanticIndex::new(
+            temp_dir.path().into(),
+            Arc::new(TestEmbeddingProvider),
+            &mut cx.to_async(),
+        )
+        .await
+        .unwrap();
+        cx.update(|cx| {
+            cx.set_global(semantic_index);
+            cx.update_global(|semantic_index: &mut SemanticIndex, cx| {
+                semantic_index.project_index(project.clone(), cx);
+            });
+        });
+        cx.background_executor.run_until_parked();
+
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.semantic_search_enabled = true;
+                search_view.excluded_files_editor.update(cx, |editor, cx| {
+                    editor.set_text("*.txt", cx);
+                });
+            })
+            .unwrap();
+
+        perform_search(search_view, "ONE", cx);
+        search.update(cx, |search, cx| {
+            assert_eq!(search.search_error, None);
+            assert_eq!(search.no_results, Some(false));
+            let mut paths = search
+                .excerpts
+                .read(cx)
+                .all_buffers()
+                .into_iter()
+                .map(|buffer| buffer.read(cx).file().unwrap().path().to_path_buf())
+                .collect::<Vec<_>>();
+            paths.sort();
+            assert_eq!(paths, [PathBuf::from("one.rs"), PathBuf::from("two.rs")]);
+        });
+
+        perform_search(search_view, "unembeddable", cx);
+        search.update(cx, |search, cx| {
+            assert!(search.search_error.is_some());
+            assert_eq!(search.no_results, Some(true));
+            assert!(search.excerpts.read(cx).all_buffers().is_empty());
+        });
+    }
+
     #[gpui::test]
     async fn test_deploy_project_search_focus(cx: &mut TestAppContext) {
         init_test(cx);
@@ -3224,6 +3530,32 @@ pub mod tests {
             .expect("unable to update search view");
     }
 
+    /// Embeds every text the same way, failing for text containing "unembeddable".
+    struct TestEmbeddingProvider;
+
+    impl EmbeddingProvider for TestEmbeddingProvider {
+        fn embed<'a>(
+            &'a self,
+            texts: &'a [TextToEmbed<'a>],
+        ) -> BoxFuture<'a, anyhow::Result<Vec<Embedding>>> {
+            let embeddings = texts
+                .iter()
+                .map(|text| {
+                    if text.text.contains("unembeddable") {
+                        Err(anyhow::anyhow!("cannot embed {:?}", text.text))
+                    } else {
+                        Ok(Embedding::new(vec![1., 0.]))
+                    }
+                })
+                .collect();
+            future::ready(embeddings).boxed()
+        }
+
+        fn batch_size(&self) -> usize {
+            16
+        }
+    }
+
     fn init_test(cx: &mut TestAppContext) {
         cx.update(|cx| {
             let settings = SettingsStore::test(cx);
@@ -3236,6 +3568,7 @@ pub mod tests {
             editor::init(cx);
             workspace::init_settings(cx);
             Project::init_settings(cx);
+            semantic_index::init(cx);
             super::init(cx);
         });
     }
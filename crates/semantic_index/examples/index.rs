@@ -4,13 +4,27 @@ use gpui::App;
 use http::HttpClientWithUrl;
 use language::language_settings::AllLanguageSettings;
 use project::Project;
-use semantic_index::{OpenAiEmbeddingModel, OpenAiEmbeddingProvider, SemanticIndex};
+use semantic_index::{
+    EmbeddingProvider, OpenAiEmbeddingModel, OpenAiEmbeddingProvider, SemanticIndex,
+    SemanticIndexSettings, TextToEmbed, VectorStoreKind,
+};
 use settings::SettingsStore;
 use std::{
     path::{Path, PathBuf},
     sync::Arc,
+    time::{Duration, Instant},
 };
 
+/// The queries that are timed once the project has been indexed.
+const BENCHMARK_QUERIES: &[&str] = &[
+    "converting an anchor to a point",
+    "parsing a settings file",
+    "rendering a list of items",
+    "handling a keystroke",
+];
+/// How many times each query is run when timing searches.
+const BENCHMARK_ITERATIONS: u32 = 10;
+
 fn main() {
     env_logger::init();
 
@@ -21,6 +35,7 @@ fn main() {
         cx.set_global(store);
         language::init(cx);
         Project::init_settings(cx);
+        semantic_index::init(cx);
         SettingsStore::update(cx, |store, cx| {
             store.update_user_settings::<AllLanguageSettings>(cx, |_| {});
         });
@@ -33,10 +48,26 @@ fn main() {
 
         let args: Vec<String> = std::env::args().collect();
         if args.len() < 2 {
-            eprintln!("Usage: cargo run --example index -p semantic_index -- <project_path>");
+            eprintln!(
+                "Usage: cargo run --example index -p semantic_index -- <project_path> [brute-force|hnsw]"
+            );
             cx.quit();
             return;
         }
+        let vector_store_kind = match args.get(2).map(String::as_str) {
+            None | Some("hnsw") => VectorStoreKind::Hnsw,
+            Some("brute-force") => VectorStoreKind::BruteForce,
+            Some(kind) => {
+                eprintln!("Unknown vector store {kind:?}, expected brute-force or hnsw");
+                cx.quit();
+                return;
+            }
+        };
+        SettingsStore::update(cx, |store, cx| {
+            store.update_user_settings::<SemanticIndexSettings>(cx, |settings| {
+                settings.vector_store = Some(vector_store_kind);
+            });
+        });
 
         // let embedding_provider = semantic_index::FakeEmbeddingProvider;
 
@@ -52,7 +83,7 @@ fn main() {
         cx.spawn(|mut cx| async move {
             let semantic_index = SemanticIndex::new(
                 PathBuf::from("/tmp/semantic-index-db.mdb"),
-                embedding_provider,
+                embedding_provider.clone(),
                 &mut cx,
             );
 
@@ -88,6 +119,31 @@ fn main() {
             drop(subscription);
             println!("Index time: {:?}", index_start.elapsed());
 
+            // The queries are embedded up front, so that only the vector store is timed.
+            let queries = BENCHMARK_QUERIES
+                .iter()
+                .map(|query| TextToEmbed::new(query))
+                .collect::<Vec<_>>();
+            let query_embeddings = embedding_provider.embed(&queries).await.unwrap();
+
+            println!("Timing searches with the {vector_store_kind:?} vector store");
+            for (query, query_embedding) in BENCHMARK_QUERIES.iter().zip(query_embeddings) {
+                let mut elapsed = Duration::ZERO;
+                for _ in 0..BENCHMARK_ITERATIONS {
+                    let search = cx
+                        .update(|cx| {
+                            project_index
+                                .read(cx)
+                                .search_embedding(query_embedding.clone(), 4, cx)
+                        })
+                        .unwrap();
+                    let search_start = Instant::now();
+                    search.await.unwrap();
+                    elapsed += search_start.elapsed();
+                }
+                println!("{query:?}: {:?} per search", elapsed / BENCHMARK_ITERATIONS);
+            }
+
             let results = cx
                 .update(|cx| {
                     let project_index = project_index.read(cx);
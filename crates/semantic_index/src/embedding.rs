@@ -36,6 +36,10 @@ impl Embedding {
         self.0.len()
     }
 
+    pub(crate) fn values(&self) -> &[f32] {
+        &self.0
+    }
+
     pub fn similarity(self, other: &Embedding) -> f32 {
         debug_assert_eq!(self.0.len(), other.0.len());
         self.0
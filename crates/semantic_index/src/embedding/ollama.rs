@@ -1,13 +1,17 @@
 use anyhow::{Context as _, Result};
 use futures::{future::BoxFuture, AsyncReadExt, FutureExt};
 use http::HttpClient;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
 use crate::{Embedding, EmbeddingProvider, TextToEmbed};
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum OllamaEmbeddingModel {
+    #[serde(rename = "nomic-embed-text")]
     NomicEmbedText,
+    #[serde(rename = "mxbai-embed-large")]
     MxbaiEmbedLarge,
 }
 
@@ -0,0 +1,445 @@
+use crate::{
+    chunking::Chunk, db_key_for_path, path_for_db_key, vector_store::ChunkMatch, EmbeddedChunk,
+    EmbeddedFile,
+};
+use anyhow::{Context as _, Result};
+use collections::{BTreeMap, BTreeSet, HashMap};
+use fs::Fs;
+use gpui::{BackgroundExecutor, Task};
+use heed::types::{DecodeIgnore, SerdeBincode, Str};
+use serde::{Deserialize, Serialize};
+use std::{
+    ops::{Bound, Range},
+    path::Path,
+    sync::Arc,
+};
+use util::ResultExt;
+
+type FilesDb = heed::Database<Str, SerdeBincode<LexicalFile>>;
+type PostingsDb = heed::Database<Str, SerdeBincode<Vec<Posting>>>;
+
+/// BM25's term frequency saturation.
+const K1: f32 = 1.2;
+/// BM25's document length normalization.
+const B: f32 = 0.75;
+const MIN_TERM_LEN: usize = 2;
+const MAX_TERM_LEN: usize = 64;
+/// Separates the term from the file key in the keys of the postings database. It can't appear in
+/// terms, which only contain alphanumeric characters and underscores.
+const TERM_SEPARATOR: char = '\u{1}';
+/// The character following [`TERM_SEPARATOR`], which bounds the keys of a term's postings.
+const TERM_SEPARATOR_END: char = '\u{2}';
+/// The key under which the corpus statistics are stored in the postings database, which can't
+/// collide with a posting's key as terms are never empty.
+const STATS_KEY: &str = "\u{1}";
+/// The number of files whose contents are read at once when indexing files that were embedded
+/// before the lexical index existed.
+const BACKFILL_BATCH_SIZE: usize = 256;
+
+/// The terms of a file's chunks, as stored to remove them from the index when the file changes.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct LexicalFile {
+    chunks: Vec<LexicalChunk>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LexicalChunk {
+    range: Range<usize>,
+    /// The number of terms in the chunk.
+    length: u32,
+    terms: Vec<(String, u32)>,
+}
+
+/// An occurrence of a term in one of a file's chunks.
+#[derive(Debug, Serialize, Deserialize)]
+struct Posting {
+    range: Range<usize>,
+    term_frequency: u32,
+    length: u32,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Stats {
+    chunk_count: u64,
+    total_length: u64,
+}
+
+impl LexicalFile {
+    pub fn new(text: &str, chunks: &[Chunk]) -> Self {
+        Self {
+            chunks: chunks
+                .iter()
+                .map(|chunk| {
+                    let mut terms = BTreeMap::<String, u32>::default();
+                    let mut length = 0;
+                    for term in tokenize(text.get(chunk.range.clone()).unwrap_or_default()) {
+                        *terms.entry(term).or_default() += 1;
+                        length += 1;
+                    }
+                    LexicalChunk {
+                        range: chunk.range.clone(),
+                        length,
+                        terms: terms.into_iter().collect(),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Splits text into lowercase terms. Identifiers are indexed both whole and by the words they're
+/// made of, so that `ProjectIndex` can be found by searching for `project index` and vice versa.
+pub(crate) fn tokenize(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    for word in text.split(|c: char| !(c.is_alphanumeric() || c == '_')) {
+        let word = word.trim_matches('_');
+        if word.len() < MIN_TERM_LEN || word.len() > MAX_TERM_LEN {
+            continue;
+        }
+
+        terms.push(word.to_lowercase());
+        let parts = split_identifier(word);
+        if parts.len() > 1 {
+            terms.extend(
+                parts
+                    .into_iter()
+                    .filter(|part| part.len() >= MIN_TERM_LEN)
+                    .map(str::to_lowercase),
+            );
+        }
+    }
+    terms
+}
+
+/// Splits an identifier at underscores and case changes, keeping acronyms together.
+fn split_identifier(identifier: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    for segment in identifier.split('_').filter(|segment| !segment.is_empty()) {
+        let chars = segment.char_indices().collect::<Vec<_>>();
+        let mut start = 0;
+        for (ix, (offset, char)) in chars.iter().enumerate().skip(1) {
+            let previous = chars[ix - 1].1;
+            let next_is_lowercase = chars
+                .get(ix + 1)
+                .map_or(false, |(_, next)| next.is_lowercase());
+            let is_boundary = char.is_uppercase()
+                && (previous.is_lowercase()
+                    || previous.is_numeric()
+                    || (previous.is_uppercase() && next_is_lowercase));
+            if is_boundary {
+                parts.push(&segment[start..*offset]);
+                start = *offset;
+            }
+        }
+        parts.push(&segment[start..]);
+    }
+    parts
+}
+
+fn posting_key(term: &str, file_key: &str) -> String {
+    format!("{term}{TERM_SEPARATOR}{file_key}")
+}
+
+/// A BM25 index of the terms in a worktree's chunks, persisted next to their embeddings.
+pub(crate) struct LexicalIndex {
+    db_connection: heed::Env,
+    files_db: FilesDb,
+    postings_db: PostingsDb,
+}
+
+impl LexicalIndex {
+    pub fn open(db_connection: &heed::Env, db_name: &str) -> Result<Self> {
+        let mut txn = db_connection.write_txn()?;
+        let files_db =
+            db_connection.create_database(&mut txn, Some(&format!("{db_name}:lexical-files")))?;
+        let postings_db = db_connection
+            .create_database(&mut txn, Some(&format!("{db_name}:lexical-postings")))?;
+        txn.commit()?;
+        Ok(Self {
+            db_connection: db_connection.clone(),
+            files_db,
+            postings_db,
+        })
+    }
+
+    /// Indexes the files that were embedded before the lexical index existed, reading their
+    /// contents from disk rather than embedding them again.
+    pub async fn backfill(
+        &self,
+        embeddings_db: heed::Database<Str, SerdeBincode<EmbeddedFile>>,
+        fs: Arc<dyn Fs>,
+        worktree_abs_path: Arc<Path>,
+    ) -> Result<()> {
+        let mut missing_files = Vec::new();
+        {
+            let txn = self
+                .db_connection
+                .read_txn()
+                .context("failed to create read transaction")?;
+            for entry in embeddings_db.iter(&txn)? {
+                let (key, file) = entry?;
+                let indexed_file = self
+                    .files_db
+                    .remap_data_type::<DecodeIgnore>()
+                    .get(&txn, key)?;
+                if indexed_file.is_none() {
+                    let chunks = file
+                        .chunks
+                        .into_iter()
+                        .map(|EmbeddedChunk { chunk, .. }| chunk)
+                        .collect::<Vec<_>>();
+                    missing_files.push((file.path, chunks));
+                }
+            }
+        }
+        if missing_files.is_empty() {
+            return Ok(());
+        }
+
+        log::info!(
+            "indexing the terms of {} files in {:?}",
+            missing_files.len(),
+            worktree_abs_path
+        );
+        for batch in missing_files.chunks(BACKFILL_BATCH_SIZE) {
+            let mut files = Vec::new();
+            for (path, chunks) in batch {
+                if let Some(text) = fs.load(&worktree_abs_path.join(path)).await.log_err() {
+                    files.push((db_key_for_path(path), LexicalFile::new(&text, chunks)));
+                }
+            }
+
+            let mut txn = self.db_connection.write_txn()?;
+            let files = files
+                .iter()
+                .map(|(key, file)| (key.as_str(), file))
+                .collect::<Vec<_>>();
+            self.update(&mut txn, &[], &files)?;
+            txn.commit()?;
+        }
+        Ok(())
+    }
+
+    /// Reflects the deletion and re-indexing of files, in the transaction that writes their
+    /// embeddings.
+    pub fn update(
+        &self,
+        txn: &mut heed::RwTxn,
+        deleted_keys: &[String],
+        files: &[(&str, &LexicalFile)],
+    ) -> Result<()> {
+        let stats_db = self.postings_db.remap_data_type::<SerdeBincode<Stats>>();
+        let mut stats = stats_db.get(txn, STATS_KEY)?.unwrap_or_default();
+
+        let replaced_keys = files.iter().map(|(key, _)| *key);
+        for key in deleted_keys.iter().map(String::as_str).chain(replaced_keys) {
+            let Some(file) = self.files_db.get(txn, key)? else {
+                continue;
+            };
+            let mut terms = BTreeSet::default();
+            for chunk in &file.chunks {
+                stats.chunk_count = stats.chunk_count.saturating_sub(1);
+                stats.total_length = stats.total_length.saturating_sub(chunk.length as u64);
+                terms.extend(chunk.terms.iter().map(|(term, _)| term.as_str()));
+            }
+            for term in terms {
+                self.postings_db.delete(txn, &posting_key(term, key))?;
+            }
+            self.files_db.delete(txn, key)?;
+        }
+
+        for (key, file) in files {
+            let mut postings = BTreeMap::<&str, Vec<Posting>>::default();
+            for chunk in &file.chunks {
+                stats.chunk_count += 1;
+                stats.total_length += chunk.length as u64;
+                for (term, term_frequency) in &chunk.terms {
+                    postings.entry(term.as_str()).or_default().push(Posting {
+                        range: chunk.range.clone(),
+                        term_frequency: *term_frequency,
+                        length: chunk.length,
+                    });
+                }
+            }
+            for (term, postings) in postings {
+                self.postings_db
+                    .put(txn, &posting_key(term, key), &postings)?;
+            }
+            self.files_db.put(txn, key, file)?;
+        }
+
+        stats_db.put(txn, STATS_KEY, &stats)?;
+        Ok(())
+    }
+
+    /// Returns the `limit` chunks with the highest BM25 score for the query's terms, best first.
+    pub fn search(
+        &self,
+        query: &str,
+        limit: usize,
+        executor: &BackgroundExecutor,
+    ) -> Task<Result<Vec<ChunkMatch>>> {
+        let terms = tokenize(query).into_iter().collect::<BTreeSet<_>>();
+        let db_connection = self.db_connection.clone();
+        let postings_db = self.postings_db;
+        executor.spawn(async move {
+            let txn = db_connection
+                .read_txn()
+                .context("failed to create read transaction")?;
+            let stats = postings_db
+                .remap_data_type::<SerdeBincode<Stats>>()
+                .get(&txn, STATS_KEY)?
+                .unwrap_or_default();
+            if stats.chunk_count == 0 {
+                return Ok(Vec::new());
+            }
+            let average_length = stats.total_length as f32 / stats.chunk_count as f32;
+
+            let mut scores = HashMap::<(String, Range<usize>), f32>::default();
+            for term in &terms {
+                let start = posting_key(term, "");
+                let end = format!("{term}{TERM_SEPARATOR_END}");
+                let mut postings_by_file = Vec::new();
+                let mut document_frequency = 0;
+                for entry in postings_db.range(
+                    &txn,
+                    &(
+                        Bound::Included(start.as_str()),
+                        Bound::Excluded(end.as_str()),
+                    ),
+                )? {
+                    let (key, postings) = entry?;
+                    document_frequency += postings.len();
+                    postings_by_file.push((key[start.len()..].to_string(), postings));
+                }
+
+                let idf = inverse_document_frequency(stats.chunk_count, document_frequency);
+                for (file_key, postings) in postings_by_file {
+                    for posting in postings {
+                        let term_frequency = posting.term_frequency as f32;
+                        let length_norm =
+                            K1 * (1. - B + B * posting.length as f32 / average_length.max(1.));
+                        *scores.entry((file_key.clone(), posting.range)).or_default() +=
+                            idf * term_frequency * (K1 + 1.) / (term_frequency + length_norm);
+                    }
+                }
+            }
+
+            let mut results = scores
+                .into_iter()
+                .map(|((file_key, range), score)| ChunkMatch {
+                    path: path_for_db_key(&file_key),
+                    range,
+                    score,
+                })
+                .collect::<Vec<_>>();
+            results.sort_unstable_by(|a, b| {
+                b.score
+                    .total_cmp(&a.score)
+                    .then_with(|| a.path.cmp(&b.path))
+                    .then_with(|| a.range.start.cmp(&b.range.start))
+            });
+            results.truncate(limit);
+            Ok(results)
+        })
+    }
+}
+
+fn inverse_document_frequency(chunk_count: u64, document_frequency: usize) -> f32 {
+    let chunk_count = chunk_count as f32;
+    let document_frequency = document_frequency as f32;
+    (1. + (chunk_count - document_frequency + 0.5) / (document_frequency + 0.5)).ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("fn ProjectIndex::search(query: &str) -> HTTPServer_v2"),
+            [
+                "fn",
+                "projectindex",
+                "project",
+                "index",
+                "search",
+                "query",
+                "str",
+                "httpserver_v2",
+                "http",
+                "server",
+                "v2",
+            ]
+        );
+    }
+
+    fn chunks(text: &str) -> Vec<Chunk> {
+        let mut offset = 0;
+        text.split_inclusive('\n')
+            .map(|line| {
+                let range = offset..offset + line.len();
+                offset = range.end;
+                Chunk {
+                    range,
+                    digest: Default::default(),
+                }
+            })
+            .collect()
+    }
+
+    #[gpui::test]
+    async fn test_lexical_search(cx: &mut TestAppContext) {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db_connection = unsafe {
+            heed::EnvOpenOptions::new()
+                .map_size(16 * 1024 * 1024)
+                .max_dbs(4)
+                .open(temp_dir.path())
+                .unwrap()
+        };
+        let index = LexicalIndex::open(&db_connection, "worktree").unwrap();
+
+        let first_text = "fn open_buffer() {}\nfn save_buffer() {}\n";
+        let second_text = "struct BufferStore;\nfn render_pane() {}\n";
+        let first = LexicalFile::new(first_text, &chunks(first_text));
+        let second = LexicalFile::new(second_text, &chunks(second_text));
+        let first_key = db_key_for_path(&Path::new("src/first.rs").into());
+        let second_key = db_key_for_path(&Path::new("src/second.rs").into());
+        let mut txn = db_connection.write_txn().unwrap();
+        index
+            .update(
+                &mut txn,
+                &[],
+                &[(&first_key, &first), (&second_key, &second)],
+            )
+            .unwrap();
+        txn.commit().unwrap();
+
+        let results = index
+            .search("save_buffer", 10, &cx.executor())
+            .await
+            .unwrap();
+        assert_eq!(results[0].path.as_ref(), Path::new("src/first.rs"));
+        assert_eq!(results[0].range, 20..40);
+        assert_eq!(results.len(), 3, "every chunk mentioning a buffer matches");
+
+        let mut txn = db_connection.write_txn().unwrap();
+        index.update(&mut txn, &[first_key], &[]).unwrap();
+        txn.commit().unwrap();
+
+        let results = index
+            .search("save_buffer", 10, &cx.executor())
+            .await
+            .unwrap();
+        assert_eq!(
+            results
+                .iter()
+                .map(|result| (result.path.as_ref(), result.range.clone()))
+                .collect::<Vec<_>>(),
+            [(Path::new("src/second.rs"), 0..20)]
+        );
+    }
+}
@@ -0,0 +1,68 @@
+use crate::{EmbeddingProvider, TextToEmbed};
+use anyhow::{anyhow, Result};
+use futures::{future::BoxFuture, FutureExt};
+use std::sync::Arc;
+
+/// Trait for rerankers, which score how relevant texts are to a query more precisely, but more
+/// slowly, than the index's own rankings.
+pub trait Reranker: Sync + Send {
+    /// Returns a score for each text, where higher scores are more relevant.
+    fn rerank<'a>(
+        &'a self,
+        query: &'a str,
+        texts: &'a [&'a str],
+    ) -> BoxFuture<'a, Result<Vec<f32>>>;
+}
+
+/// Reranks results by their similarity to the query according to another embedding model, such
+/// as a larger one running locally through Ollama.
+pub struct EmbeddingReranker {
+    embedding_provider: Arc<dyn EmbeddingProvider>,
+}
+
+impl EmbeddingReranker {
+    pub fn new(embedding_provider: Arc<dyn EmbeddingProvider>) -> Self {
+        Self { embedding_provider }
+    }
+}
+
+impl Reranker for EmbeddingReranker {
+    fn rerank<'a>(
+        &'a self,
+        query: &'a str,
+        texts: &'a [&'a str],
+    ) -> BoxFuture<'a, Result<Vec<f32>>> {
+        async move {
+            let query_embedding = self
+                .embedding_provider
+                .embed(&[TextToEmbed::new(query)])
+                .await?
+                .into_iter()
+                .next()
+                .ok_or_else(|| anyhow!("no embedding for query"))?;
+
+            let texts = texts
+                .iter()
+                .map(|text| TextToEmbed::new(text))
+                .collect::<Vec<_>>();
+            let mut scores = Vec::with_capacity(texts.len());
+            for batch in texts.chunks(self.embedding_provider.batch_size()) {
+                let embeddings = self.embedding_provider.embed(batch).await?;
+                if embeddings.len() != batch.len() {
+                    return Err(anyhow!(
+                        "embedding provider returned unexpected embedding count {}, expected {}",
+                        embeddings.len(),
+                        batch.len()
+                    ));
+                }
+                scores.extend(
+                    embeddings
+                        .into_iter()
+                        .map(|embedding| embedding.similarity(&query_embedding)),
+                );
+            }
+            Ok(scores)
+        }
+        .boxed()
+    }
+}
@@ -1,10 +1,14 @@
 mod chunking;
 mod embedding;
+mod lexical_index;
 mod project_index_debug_view;
+mod reranking;
+mod semantic_index_settings;
+mod vector_store;
 
 use anyhow::{anyhow, Context as _, Result};
 use chunking::{chunk_text, Chunk};
-use collections::{Bound, HashMap, HashSet};
+use collections::{hash_map, Bound, HashMap, HashSet};
 pub use embedding::*;
 use fs::Fs;
 use futures::stream::StreamExt;
@@ -13,15 +17,18 @@ use gpui::{
     AppContext, AsyncAppContext, BorrowAppContext, Context, Entity, EntityId, EventEmitter, Global,
     Model, ModelContext, Subscription, Task, WeakModel,
 };
-use heed::types::{SerdeBincode, Str};
+use heed::types::{DecodeIgnore, SerdeBincode, Str};
 use language::LanguageRegistry;
+use lexical_index::{LexicalFile, LexicalIndex};
 use parking_lot::Mutex;
 use project::{Entry, Project, ProjectEntryId, UpdatedEntriesSet, Worktree, WorktreeId};
 use serde::{Deserialize, Serialize};
+use settings::Settings;
 use smol::channel;
 use std::{
     cmp::Ordering,
     future::Future,
+    hash::Hash,
     iter,
     num::NonZeroUsize,
     ops::Range,
@@ -30,9 +37,22 @@ use std::{
     time::{Duration, SystemTime},
 };
 use util::ResultExt;
+use vector_store::VectorStore;
 use worktree::LocalSnapshot;
 
 pub use project_index_debug_view::ProjectIndexDebugView;
+pub use reranking::*;
+pub use semantic_index_settings::*;
+pub use vector_store::VectorStoreKind;
+
+/// How many more candidates than requested each index contributes to a hybrid search.
+const HYBRID_CANDIDATE_FACTOR: usize = 3;
+/// Dampens the influence of the top ranks in reciprocal rank fusion.
+const RRF_K: f32 = 60.;
+
+pub fn init(cx: &mut AppContext) {
+    SemanticIndexSettings::register(cx);
+}
 
 pub struct SemanticIndex {
     embedding_provider: Arc<dyn EmbeddingProvider>,
@@ -86,6 +106,7 @@ impl SemanticIndex {
             .detach();
         });
 
+        let vector_store_kind = SemanticIndexSettings::get_global(cx).vector_store;
         self.project_indices
             .entry(project.downgrade())
             .or_insert_with(|| {
@@ -94,6 +115,7 @@ impl SemanticIndex {
                         project,
                         self.db_connection.clone(),
                         self.embedding_provider.clone(),
+                        vector_store_kind,
                         cx,
                     )
                 })
@@ -111,6 +133,7 @@ pub struct ProjectIndex {
     last_status: Status,
     status_tx: channel::Sender<()>,
     embedding_provider: Arc<dyn EmbeddingProvider>,
+    vector_store_kind: VectorStoreKind,
     _maintain_status: Task<()>,
     _subscription: Subscription,
 }
@@ -125,6 +148,7 @@ impl ProjectIndex {
         project: Model<Project>,
         db_connection: heed::Env,
         embedding_provider: Arc<dyn EmbeddingProvider>,
+        vector_store_kind: VectorStoreKind,
         cx: &mut ModelContext<Self>,
     ) -> Self {
         let language_registry = project.read(cx).languages().clone();
@@ -139,6 +163,7 @@ impl ProjectIndex {
             status_tx,
             last_status: Status::Idle,
             embedding_provider,
+            vector_store_kind,
             _subscription: cx.subscribe(&project, Self::handle_project_event),
             _maintain_status: cx.spawn(|this, mut cx| async move {
                 while status_rx.next().await.is_some() {
@@ -209,6 +234,7 @@ impl ProjectIndex {
                     self.fs.clone(),
                     self.status_tx.clone(),
                     self.embedding_provider.clone(),
+                    self.vector_store_kind,
                     cx,
                 );
 
@@ -276,37 +302,7 @@ impl ProjectIndex {
         limit: usize,
         cx: &AppContext,
     ) -> Task<Result<Vec<SearchResult>>> {
-        let (chunks_tx, chunks_rx) = channel::bounded(1024);
-        let mut worktree_scan_tasks = Vec::new();
-        for worktree_index in self.worktree_indices.values() {
-            if let WorktreeIndexHandle::Loaded { index, .. } = worktree_index {
-                let chunks_tx = chunks_tx.clone();
-                index.read_with(cx, |index, cx| {
-                    let worktree_id = index.worktree.read(cx).id();
-                    let db_connection = index.db_connection.clone();
-                    let db = index.db;
-                    worktree_scan_tasks.push(cx.background_executor().spawn({
-                        async move {
-                            let txn = db_connection
-                                .read_txn()
-                                .context("failed to create read transaction")?;
-                            let db_entries = db.iter(&txn).context("failed to iterate database")?;
-                            for db_entry in db_entries {
-                                let (_key, db_embedded_file) = db_entry?;
-                                for chunk in db_embedded_file.chunks {
-                                    chunks_tx
-                                        .send((worktree_id, db_embedded_file.path.clone(), chunk))
-                                        .await?;
-                                }
-                            }
-                            anyhow::Ok(())
-                        }
-                    }));
-                })
-            }
-        }
-        drop(chunks_tx);
-
+        let vector_stores = self.vector_stores(cx);
         let project = self.project.clone();
         let embedding_provider = self.embedding_provider.clone();
         cx.spawn(|cx| async move {
@@ -322,74 +318,128 @@ impl ProjectIndex {
                 .next()
                 .ok_or_else(|| anyhow!("no embedding for query"))?;
 
-            let mut results_by_worker = Vec::new();
-            for _ in 0..cx.background_executor().num_cpus() {
-                results_by_worker.push(Vec::<WorktreeSearchResult>::new());
-            }
-
             #[cfg(debug_assertions)]
-            let search_start = std::time::Instant::now();
+            log::debug!("embedding query took {:?}", embedding_query_start.elapsed());
 
-            cx.background_executor()
-                .scoped(|cx| {
-                    for results in results_by_worker.iter_mut() {
-                        cx.spawn(async {
-                            while let Ok((worktree_id, path, chunk)) = chunks_rx.recv().await {
-                                let score = chunk.embedding.similarity(&query_embedding);
-                                let ix = match results.binary_search_by(|probe| {
-                                    score.partial_cmp(&probe.score).unwrap_or(Ordering::Equal)
-                                }) {
-                                    Ok(ix) | Err(ix) => ix,
-                                };
-                                results.insert(
-                                    ix,
-                                    WorktreeSearchResult {
-                                        worktree_id,
-                                        path: path.clone(),
-                                        range: chunk.chunk.range.clone(),
-                                        score,
-                                    },
-                                );
-                                results.truncate(limit);
-                            }
-                        });
-                    }
-                })
-                .await;
+            search_vector_stores(vector_stores, project, query_embedding, limit, cx).await
+        })
+    }
 
-            futures::future::try_join_all(worktree_scan_tasks).await?;
-
-            project.read_with(&cx, |project, cx| {
-                let mut search_results = Vec::with_capacity(results_by_worker.len() * limit);
-                for worker_results in results_by_worker {
-                    search_results.extend(worker_results.into_iter().filter_map(|result| {
-                        Some(SearchResult {
-                            worktree: project.worktree_for_id(result.worktree_id, cx)?,
-                            path: result.path,
-                            range: result.range,
-                            score: result.score,
-                        })
-                    }));
+    /// Searches for the chunks most similar to an embedding that was already computed, such as
+    /// when the same query is searched repeatedly.
+    pub fn search_embedding(
+        &self,
+        query_embedding: Embedding,
+        limit: usize,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<SearchResult>>> {
+        let vector_stores = self.vector_stores(cx);
+        let project = self.project.clone();
+        cx.spawn(|cx| search_vector_stores(vector_stores, project, query_embedding, limit, cx))
+    }
+
+    fn vector_stores(&self, cx: &AppContext) -> Vec<(WorktreeId, Arc<dyn VectorStore>)> {
+        self.worktree_indices
+            .values()
+            .filter_map(|worktree_index| {
+                if let WorktreeIndexHandle::Loaded { index, .. } = worktree_index {
+                    let index = index.read(cx);
+                    Some((index.worktree.read(cx).id(), index.vector_store.clone()))
+                } else {
+                    None
                 }
-                search_results.sort_unstable_by(|a, b| {
-                    b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal)
-                });
-                search_results.truncate(limit);
-
-                #[cfg(debug_assertions)]
-                {
-                    let search_elapsed = search_start.elapsed();
-                    log::debug!(
-                        "searched {} entries in {:?}",
-                        search_results.len(),
-                        search_elapsed
+            })
+            .collect()
+    }
+
+    /// Searches for chunks that are either similar to the query or contain its terms, fusing the
+    /// rankings of the vector and lexical indices so that exact identifiers are found as reliably
+    /// as related code. The results are reordered by the reranker configured in the settings,
+    /// if there's one.
+    pub fn search_hybrid(
+        &self,
+        query: String,
+        limit: usize,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<SearchResult>>> {
+        let candidate_count = limit * HYBRID_CANDIDATE_FACTOR;
+        let vector_search = self.search(query.clone(), candidate_count, cx);
+        let lexical_searches = self
+            .worktree_indices
+            .values()
+            .filter_map(|worktree_index| {
+                if let WorktreeIndexHandle::Loaded { index, .. } = worktree_index {
+                    let index = index.read(cx);
+                    let search = index.lexical_index.search(
+                        &query,
+                        candidate_count,
+                        cx.background_executor(),
                     );
-                    let embedding_query_elapsed = embedding_query_start.elapsed();
-                    log::debug!("embedding query took {:?}", embedding_query_elapsed);
+                    Some((index.worktree.clone(), search))
+                } else {
+                    None
                 }
+            })
+            .collect::<Vec<_>>();
 
-                search_results
+        let fs = self.fs.clone();
+        let reranker = SemanticIndexSettings::get_global(cx).reranker.map(|model| {
+            let embedding_provider = OllamaEmbeddingProvider::new(cx.http_client(), model);
+            Arc::new(EmbeddingReranker::new(Arc::new(embedding_provider))) as Arc<dyn Reranker>
+        });
+        cx.spawn(|cx| async move {
+            let mut lexical_results = Vec::new();
+            for (worktree, search) in lexical_searches {
+                lexical_results.extend(search.await?.into_iter().map(|result| SearchResult {
+                    worktree: worktree.clone(),
+                    path: result.path,
+                    range: result.range,
+                    score: result.score,
+                }));
+            }
+            lexical_results.sort_by(|a, b| b.score.total_cmp(&a.score));
+            lexical_results.truncate(candidate_count);
+            let vector_results = vector_search.await?;
+
+            let mut results = reciprocal_rank_fusion([vector_results, lexical_results], |result| {
+                (
+                    result.worktree.entity_id(),
+                    result.path.clone(),
+                    result.range.start,
+                    result.range.end,
+                )
             })
+            .into_iter()
+            .map(|(score, result)| SearchResult { score, ..result })
+            .collect::<Vec<_>>();
+
+            if let Some(reranker) = reranker {
+                results.truncate(candidate_count);
+                let abs_paths = cx.update(|cx| {
+                    results
+                        .iter()
+                        .map(|result| result.worktree.read(cx).abs_path().join(&result.path))
+                        .collect::<Vec<_>>()
+                })?;
+                let mut texts = Vec::with_capacity(results.len());
+                for (result, abs_path) in results.iter().zip(abs_paths) {
+                    let text = fs.load(&abs_path).await.log_err().unwrap_or_default();
+                    texts.push(
+                        text.get(result.range.clone())
+                            .unwrap_or_default()
+                            .to_string(),
+                    );
+                }
+                let texts = texts.iter().map(String::as_str).collect::<Vec<_>>();
+                let scores = reranker.rerank(&query, &texts).await?;
+                for (result, score) in results.iter_mut().zip(scores) {
+                    result.score = score;
+                }
+                results.sort_by(|a, b| b.score.total_cmp(&a.score));
+            }
+
+            results.truncate(limit);
+            Ok(results)
         })
     }
 
@@ -436,15 +486,79 @@ impl ProjectIndex {
     }
 }
 
-pub struct SearchResult {
-    pub worktree: Model<Worktree>,
-    pub path: Arc<Path>,
-    pub range: Range<usize>,
-    pub score: f32,
+async fn search_vector_stores(
+    vector_stores: Vec<(WorktreeId, Arc<dyn VectorStore>)>,
+    project: WeakModel<Project>,
+    query_embedding: Embedding,
+    limit: usize,
+    cx: AsyncAppContext,
+) -> Result<Vec<SearchResult>> {
+    #[cfg(debug_assertions)]
+    let search_start = std::time::Instant::now();
+
+    let worktree_searches = vector_stores
+        .into_iter()
+        .map(|(worktree_id, vector_store)| {
+            let search =
+                vector_store.search(query_embedding.clone(), limit, cx.background_executor());
+            async move { anyhow::Ok((worktree_id, search.await?)) }
+        });
+    let results_by_worktree = futures::future::try_join_all(worktree_searches).await?;
+
+    project.read_with(&cx, |project, cx| {
+        let mut search_results = Vec::with_capacity(results_by_worktree.len() * limit);
+        for (worktree_id, worktree_results) in results_by_worktree {
+            let Some(worktree) = project.worktree_for_id(worktree_id, cx) else {
+                continue;
+            };
+            search_results.extend(worktree_results.into_iter().map(|result| SearchResult {
+                worktree: worktree.clone(),
+                path: result.path,
+                range: result.range,
+                score: result.score,
+            }));
+        }
+        search_results
+            .sort_unstable_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+        search_results.truncate(limit);
+
+        #[cfg(debug_assertions)]
+        log::debug!(
+            "searched {} entries in {:?}",
+            search_results.len(),
+            search_start.elapsed()
+        );
+
+        search_results
+    })
+}
+
+/// Combines rankings by scoring each item with the sum of the reciprocals of its ranks, which
+/// doesn't require the rankings' scores to be comparable. Items are identified by `key`.
+fn reciprocal_rank_fusion<T, K: Hash + Eq>(
+    rankings: impl IntoIterator<Item = Vec<T>>,
+    key: impl Fn(&T) -> K,
+) -> Vec<(f32, T)> {
+    let mut fused = Vec::<(f32, T)>::new();
+    let mut positions = HashMap::<K, usize>::default();
+    for ranking in rankings {
+        for (rank, item) in ranking.into_iter().enumerate() {
+            let score = 1. / (RRF_K + rank as f32 + 1.);
+            match positions.entry(key(&item)) {
+                hash_map::Entry::Occupied(entry) => fused[*entry.get()].0 += score,
+                hash_map::Entry::Vacant(entry) => {
+                    entry.insert(fused.len());
+                    fused.push((score, item));
+                }
+            }
+        }
+    }
+    fused.sort_by(|a, b| b.0.total_cmp(&a.0));
+    fused
 }
 
-pub struct WorktreeSearchResult {
-    pub worktree_id: WorktreeId,
+pub struct SearchResult {
+    pub worktree: Model<Worktree>,
     pub path: Arc<Path>,
     pub range: Range<usize>,
     pub score: f32,
@@ -463,6 +577,8 @@ struct WorktreeIndex {
     worktree: Model<Worktree>,
     db_connection: heed::Env,
     db: heed::Database<Str, SerdeBincode<EmbeddedFile>>,
+    vector_store: Arc<dyn VectorStore>,
+    lexical_index: Arc<LexicalIndex>,
     language_registry: Arc<LanguageRegistry>,
     fs: Arc<dyn Fs>,
     embedding_provider: Arc<dyn EmbeddingProvider>,
@@ -472,6 +588,7 @@ struct WorktreeIndex {
 }
 
 impl WorktreeIndex {
+    #[allow(clippy::too_many_arguments)]
     pub fn load(
         worktree: Model<Worktree>,
         db_connection: heed::Env,
@@ -479,20 +596,31 @@ impl WorktreeIndex {
         fs: Arc<dyn Fs>,
         status_tx: channel::Sender<()>,
         embedding_provider: Arc<dyn EmbeddingProvider>,
+        vector_store_kind: VectorStoreKind,
         cx: &mut AppContext,
     ) -> Task<Result<Model<Self>>> {
         let worktree_abs_path = worktree.read(cx).abs_path();
         cx.spawn(|mut cx| async move {
-            let db = cx
+            let (db, vector_store, lexical_index) = cx
                 .background_executor()
                 .spawn({
                     let db_connection = db_connection.clone();
+                    let fs = fs.clone();
                     async move {
                         let mut txn = db_connection.write_txn()?;
                         let db_name = worktree_abs_path.to_string_lossy();
                         let db = db_connection.create_database(&mut txn, Some(&db_name))?;
                         txn.commit()?;
-                        anyhow::Ok(db)
+                        let vector_store = vector_store_kind
+                            .open(&db_connection, db, &db_name)
+                            .context("failed to open vector store")?;
+                        let lexical_index = LexicalIndex::open(&db_connection, &db_name)
+                            .context("failed to open lexical index")?;
+                        lexical_index
+                            .backfill(db, fs, worktree_abs_path.clone())
+                            .await
+                            .context("failed to index the terms of embedded files")?;
+                        anyhow::Ok((db, vector_store, Arc::new(lexical_index)))
                     }
                 })
                 .await?;
@@ -501,6 +629,8 @@ impl WorktreeIndex {
                     worktree,
                     db_connection,
                     db,
+                    vector_store,
+                    lexical_index,
                     status_tx,
                     language_registry,
                     fs,
@@ -516,6 +646,8 @@ impl WorktreeIndex {
         worktree: Model<Worktree>,
         db_connection: heed::Env,
         db: heed::Database<Str, SerdeBincode<EmbeddedFile>>,
+        vector_store: Arc<dyn VectorStore>,
+        lexical_index: Arc<LexicalIndex>,
         status: channel::Sender<()>,
         language_registry: Arc<LanguageRegistry>,
         fs: Arc<dyn Fs>,
@@ -532,6 +664,8 @@ impl WorktreeIndex {
         Self {
             db_connection,
             db,
+            vector_store,
+            lexical_index,
             worktree,
             language_registry,
             fs,
@@ -813,6 +947,7 @@ impl WorktreeIndex {
 
                 let mut embeddings = embeddings.into_iter();
                 for chunked_file in chunked_files {
+                    let lexical_file = LexicalFile::new(&chunked_file.text, &chunked_file.chunks);
                     let mut embedded_file = EmbeddedFile {
                         path: chunked_file.path,
                         mtime: chunked_file.mtime,
@@ -834,7 +969,7 @@ impl WorktreeIndex {
 
                     if embedded_all_chunks {
                         embedded_files_tx
-                            .send((embedded_file, chunked_file.handle))
+                            .send((embedded_file, lexical_file, chunked_file.handle))
                             .await?;
                     }
                 }
@@ -851,29 +986,51 @@ impl WorktreeIndex {
     fn persist_embeddings(
         &self,
         mut deleted_entry_ranges: channel::Receiver<(Bound<String>, Bound<String>)>,
-        embedded_files: channel::Receiver<(EmbeddedFile, IndexingEntryHandle)>,
+        embedded_files: channel::Receiver<(EmbeddedFile, LexicalFile, IndexingEntryHandle)>,
         cx: &AppContext,
     ) -> Task<Result<()>> {
         let db_connection = self.db_connection.clone();
         let db = self.db;
+        let vector_store = self.vector_store.clone();
+        let lexical_index = self.lexical_index.clone();
         cx.background_executor().spawn(async move {
             while let Some(deletion_range) = deleted_entry_ranges.next().await {
                 let mut txn = db_connection.write_txn()?;
                 let start = deletion_range.0.as_ref().map(|start| start.as_str());
                 let end = deletion_range.1.as_ref().map(|end| end.as_str());
                 log::debug!("deleting embeddings in range {:?}", &(start, end));
+                let deleted_keys = db
+                    .remap_data_type::<DecodeIgnore>()
+                    .range(&txn, &(start, end))?
+                    .map(|entry| Ok(entry?.0.to_string()))
+                    .collect::<Result<Vec<_>>>()?;
                 db.delete_range(&mut txn, &(start, end))?;
+                vector_store.update(&mut txn, &deleted_keys, &[])?;
+                lexical_index.update(&mut txn, &deleted_keys, &[])?;
                 txn.commit()?;
             }
 
             let mut embedded_files = embedded_files.chunks_timeout(4096, Duration::from_secs(2));
             while let Some(embedded_files) = embedded_files.next().await {
                 let mut txn = db_connection.write_txn()?;
-                for (file, _) in &embedded_files {
+                let mut keys = Vec::with_capacity(embedded_files.len());
+                for (file, _, _) in &embedded_files {
                     log::debug!("saving embedding for file {:?}", file.path);
                     let key = db_key_for_path(&file.path);
                     db.put(&mut txn, &key, file)?;
+                    keys.push(key);
                 }
+                let files = embedded_files
+                    .iter()
+                    .map(|(file, _, _)| file)
+                    .collect::<Vec<_>>();
+                vector_store.update(&mut txn, &[], &files)?;
+                let lexical_files = keys
+                    .iter()
+                    .zip(&embedded_files)
+                    .map(|(key, (_, lexical_file, _))| (key.as_str(), lexical_file))
+                    .collect::<Vec<_>>();
+                lexical_index.update(&mut txn, &[], &lexical_files)?;
                 txn.commit()?;
 
                 drop(embedded_files);
@@ -949,7 +1106,7 @@ struct ChunkedFile {
 }
 
 struct EmbedFiles {
-    files: channel::Receiver<(EmbeddedFile, IndexingEntryHandle)>,
+    files: channel::Receiver<(EmbeddedFile, LexicalFile, IndexingEntryHandle)>,
     task: Task<Result<()>>,
 }
 
@@ -1014,6 +1171,10 @@ fn db_key_for_path(path: &Arc<Path>) -> String {
     path.to_string_lossy().replace('/', "\0")
 }
 
+fn path_for_db_key(key: &str) -> Arc<Path> {
+    Path::new(&key.replace('\0', "/")).into()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1030,6 +1191,7 @@ mod tests {
             cx.set_global(store);
             language::init(cx);
             Project::init_settings(cx);
+            crate::init(cx);
             SettingsStore::update(cx, |store, cx| {
                 store.update_user_settings::<AllLanguageSettings>(cx, |_| {});
             });
@@ -1161,6 +1323,71 @@ mod tests {
         assert!(content.contains("garbage in, garbage out"));
     }
 
+    #[gpui::test]
+    async fn test_search_hybrid(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        init_test(cx);
+
+        let temp_dir = tempfile::tempdir().unwrap();
+
+        // Embed everything except `main.rs` close to the query, so that the vector search alone
+        // ranks the file containing the query's identifier last.
+        let mut semantic_index = SemanticIndex::new(
+            temp_dir.path().into(),
+            Arc::new(TestEmbeddingProvider::new(16, |text| {
+                if text.contains("fn main") {
+                    Ok(Embedding::new(vec![-0.9, 0.1]))
+                } else {
+                    Ok(Embedding::new(vec![0.9, 0.1]))
+                }
+            })),
+            &mut cx.to_async(),
+        )
+        .await
+        .unwrap();
+
+        let project_path = Path::new("./fixture");
+
+        let project = cx
+            .spawn(|mut cx| async move { Project::example([project_path], &mut cx).await })
+            .await;
+
+        cx.update(|cx| {
+            let language_registry = project.read(cx).languages().clone();
+            let node_runtime = project.read(cx).node_runtime().unwrap().clone();
+            languages::init(language_registry, node_runtime, cx);
+        });
+
+        let project_index = cx.update(|cx| semantic_index.project_index(project.clone(), cx));
+
+        while project_index
+            .read_with(cx, |index, cx| index.path_count(cx))
+            .unwrap()
+            < 2
+        {
+            project_index.next_event(cx).await;
+        }
+
+        let vector_results = cx
+            .update(|cx| project_index.read(cx).search("Indexer".into(), 1, cx))
+            .await
+            .unwrap();
+        assert_eq!(vector_results.len(), 1);
+        assert_eq!(vector_results[0].path.to_string_lossy(), "needle.md");
+
+        let hybrid_results = cx
+            .update(|cx| {
+                project_index
+                    .read(cx)
+                    .search_hybrid("Indexer".into(), 1, cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(hybrid_results.len(), 1);
+        assert_eq!(hybrid_results[0].path.to_string_lossy(), "main.rs");
+    }
+
     #[gpui::test]
     async fn test_embed_files(cx: &mut TestAppContext) {
         cx.executor().allow_parking();
@@ -1219,7 +1446,7 @@ mod tests {
 
         let mut embedded_files_rx = embed_files_task.files;
         let mut embedded_files = Vec::new();
-        while let Some((embedded_file, _)) = embedded_files_rx.next().await {
+        while let Some((embedded_file, _, _)) = embedded_files_rx.next().await {
             embedded_files.push(embedded_file);
         }
 
@@ -1238,4 +1465,15 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn test_reciprocal_rank_fusion() {
+        let vector_ranking = vec!["a", "b", "c"];
+        let lexical_ranking = vec!["c", "d", "b"];
+        let fused = reciprocal_rank_fusion([vector_ranking, lexical_ranking], |item| *item)
+            .into_iter()
+            .map(|(_, item)| item)
+            .collect::<Vec<_>>();
+        assert_eq!(fused, vec!["c", "b", "a", "d"]);
+    }
 }
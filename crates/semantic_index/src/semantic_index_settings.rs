@@ -0,0 +1,38 @@
+use crate::{OllamaEmbeddingModel, VectorStoreKind};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct SemanticIndexSettings {
+    pub vector_store: VectorStoreKind,
+    pub reranker: Option<OllamaEmbeddingModel>,
+}
+
+/// Settings for the index used by semantic search.
+#[derive(Clone, Default, Deserialize, Serialize, JsonSchema)]
+pub struct SemanticIndexSettingsContent {
+    /// How the embeddings of a project are searched. Changes apply to the projects opened
+    /// afterwards.
+    ///
+    /// Default: hnsw
+    pub vector_store: Option<VectorStoreKind>,
+    /// The embedding model, served locally by Ollama, used to reorder the results of semantic
+    /// searches more precisely. Results aren't reordered when unset.
+    ///
+    /// Default: null
+    pub reranker: Option<OllamaEmbeddingModel>,
+}
+
+impl Settings for SemanticIndexSettings {
+    const KEY: Option<&'static str> = Some("semantic_index");
+
+    type FileContent = SemanticIndexSettingsContent;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut gpui::AppContext,
+    ) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+}
@@ -0,0 +1,1071 @@
+use crate::{db_key_for_path, EmbeddedFile, Embedding};
+use anyhow::{Context as _, Result};
+use collections::{HashMap, HashSet};
+use gpui::{BackgroundExecutor, Task};
+use heed::types::{SerdeBincode, Str};
+use parking_lot::RwLock;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use smol::channel;
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+    mem,
+    ops::Range,
+    path::Path,
+    sync::Arc,
+    time::SystemTime,
+};
+
+type EmbeddingsDb = heed::Database<Str, SerdeBincode<EmbeddedFile>>;
+type GraphDb = heed::Database<SerdeBincode<u32>, SerdeBincode<SerializedHnswNode>>;
+type VectorsDb = heed::Database<SerdeBincode<u32>, SerdeBincode<QuantizedVector>>;
+
+/// The number of neighbors each node links to on the upper layers of the graph. The bottom layer
+/// allows twice as many.
+const MAX_NEIGHBORS: usize = 16;
+/// The number of candidates considered when choosing the neighbors of a new node.
+const EF_CONSTRUCTION: usize = 100;
+/// The minimum number of candidates considered when searching the graph.
+const MIN_EF_SEARCH: usize = 64;
+/// How many more candidates than requested are taken from the graph and re-scored against the
+/// exact embeddings, to make up for the quantization of the graph's vectors.
+const RERANK_FACTOR: usize = 4;
+const MAX_LEVEL: usize = 16;
+/// The graph is rebuilt once it contains more deleted nodes than live ones, and at least this many.
+const MIN_DELETED_NODES_TO_COMPACT: usize = 1024;
+/// How many outdated files are reindexed per transaction when the graph is opened.
+const REINDEX_BATCH_SIZE: usize = 256;
+
+/// How [`ProjectIndex::search`](crate::ProjectIndex::search) finds the chunks closest to a query.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VectorStoreKind {
+    /// Compares the query with every embedding in the database.
+    BruteForce,
+    /// Walks a hierarchical navigable small world graph that's kept up to date as files are
+    /// re-embedded, trading exactness for search time.
+    #[default]
+    Hnsw,
+}
+
+/// A chunk returned by a [`VectorStore`] search.
+pub(crate) struct ChunkMatch {
+    pub path: Arc<Path>,
+    pub range: Range<usize>,
+    pub score: f32,
+}
+
+/// Finds the chunks of a worktree whose embeddings are the most similar to a query.
+pub(crate) trait VectorStore: Send + Sync {
+    /// Returns the `limit` chunks that are the most similar to the query, best first.
+    fn search(
+        &self,
+        query: Embedding,
+        limit: usize,
+        executor: &BackgroundExecutor,
+    ) -> Task<Result<Vec<ChunkMatch>>>;
+
+    /// Reflects the deletion and re-embedding of files, in the transaction that writes them to
+    /// the embeddings database.
+    fn update(
+        &self,
+        txn: &mut heed::RwTxn,
+        deleted_keys: &[String],
+        embedded_files: &[&EmbeddedFile],
+    ) -> Result<()>;
+}
+
+impl VectorStoreKind {
+    /// Opens the store for the worktree whose embeddings are stored in the given database.
+    pub(crate) fn open(
+        self,
+        db_connection: &heed::Env,
+        db: EmbeddingsDb,
+        db_name: &str,
+    ) -> Result<Arc<dyn VectorStore>> {
+        Ok(match self {
+            VectorStoreKind::BruteForce => Arc::new(BruteForceStore {
+                db_connection: db_connection.clone(),
+                db,
+            }),
+            VectorStoreKind::Hnsw => Arc::new(HnswStore::open(db_connection, db, db_name)?),
+        })
+    }
+}
+
+struct BruteForceStore {
+    db_connection: heed::Env,
+    db: EmbeddingsDb,
+}
+
+impl VectorStore for BruteForceStore {
+    fn search(
+        &self,
+        query: Embedding,
+        limit: usize,
+        executor: &BackgroundExecutor,
+    ) -> Task<Result<Vec<ChunkMatch>>> {
+        let db_connection = self.db_connection.clone();
+        let db = self.db;
+        let executor = executor.clone();
+        executor.clone().spawn(async move {
+            let (chunks_tx, chunks_rx) = channel::bounded(1024);
+            let scan = executor.spawn(async move {
+                let txn = db_connection
+                    .read_txn()
+                    .context("failed to create read transaction")?;
+                let db_entries = db.iter(&txn).context("failed to iterate database")?;
+                for db_entry in db_entries {
+                    let (_key, db_embedded_file) = db_entry?;
+                    for chunk in db_embedded_file.chunks {
+                        chunks_tx
+                            .send((db_embedded_file.path.clone(), chunk))
+                            .await?;
+                    }
+                }
+                anyhow::Ok(())
+            });
+
+            let mut results_by_worker = Vec::new();
+            for _ in 0..executor.num_cpus() {
+                results_by_worker.push(Vec::<ChunkMatch>::new());
+            }
+            executor
+                .scoped(|cx| {
+                    for results in results_by_worker.iter_mut() {
+                        cx.spawn(async {
+                            while let Ok((path, chunk)) = chunks_rx.recv().await {
+                                let score = chunk.embedding.similarity(&query);
+                                let ix = match results.binary_search_by(|probe| {
+                                    score.partial_cmp(&probe.score).unwrap_or(Ordering::Equal)
+                                }) {
+                                    Ok(ix) | Err(ix) => ix,
+                                };
+                                results.insert(
+                                    ix,
+                                    ChunkMatch {
+                                        path,
+                                        range: chunk.chunk.range.clone(),
+                                        score,
+                                    },
+                                );
+                                results.truncate(limit);
+                            }
+                        });
+                    }
+                })
+                .await;
+            scan.await?;
+
+            let mut results = results_by_worker.into_iter().flatten().collect::<Vec<_>>();
+            results.sort_unstable_by(|a, b| b.score.total_cmp(&a.score));
+            results.truncate(limit);
+            Ok(results)
+        })
+    }
+
+    fn update(&self, _: &mut heed::RwTxn, _: &[String], _: &[&EmbeddedFile]) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// An HNSW graph persisted next to the embeddings it indexes.
+///
+/// Only the links between the graph's nodes are kept in memory. The nodes are compared using
+/// quantized copies of the embeddings that are read from the database, and the best candidates
+/// a search finds are then re-scored using the exact embeddings.
+struct HnswStore {
+    db_connection: heed::Env,
+    embeddings_db: EmbeddingsDb,
+    graph_db: GraphDb,
+    vectors_db: VectorsDb,
+    graph: Arc<RwLock<Hnsw>>,
+}
+
+impl HnswStore {
+    /// Loads the graph, and brings it up to date with files that were embedded while it wasn't
+    /// being maintained, such as by an older version or while the brute force store was in use.
+    /// Files are reindexed in batches, so that the database isn't locked for other worktrees
+    /// meanwhile.
+    fn open(db_connection: &heed::Env, embeddings_db: EmbeddingsDb, db_name: &str) -> Result<Self> {
+        let mut txn = db_connection.write_txn()?;
+        let graph_db: GraphDb =
+            db_connection.create_database(&mut txn, Some(&format!("{db_name}:hnsw-graph")))?;
+        let vectors_db: VectorsDb =
+            db_connection.create_database(&mut txn, Some(&format!("{db_name}:hnsw-vectors")))?;
+        txn.commit()?;
+
+        let nodes = {
+            let txn = db_connection
+                .read_txn()
+                .context("failed to create read transaction")?;
+            graph_db.iter(&txn)?.collect::<heed::Result<Vec<_>>>()?
+        };
+        let mut graph = match Hnsw::from_nodes(nodes) {
+            Some(graph) => graph,
+            None => {
+                log::info!("rebuilding vector index for {db_name:?}");
+                let mut txn = db_connection.write_txn()?;
+                graph_db.clear(&mut txn)?;
+                vectors_db.clear(&mut txn)?;
+                txn.commit()?;
+                Hnsw::default()
+            }
+        };
+
+        let mut stale_keys = Vec::new();
+        {
+            let txn = db_connection
+                .read_txn()
+                .context("failed to create read transaction")?;
+            let mut indexed_files = graph.indexed_files();
+            for entry in embeddings_db.iter(&txn)? {
+                let (key, file) = entry?;
+                let is_up_to_date = indexed_files
+                    .remove(key)
+                    .map_or(false, |(mtime, chunk_count)| {
+                        mtime == file.mtime && chunk_count == file.chunks.len()
+                    });
+                if !is_up_to_date {
+                    stale_keys.push(key.to_string());
+                }
+            }
+            stale_keys.extend(indexed_files.into_keys());
+        }
+
+        if !stale_keys.is_empty() {
+            log::info!(
+                "updating the vector index of {} files in {db_name:?}",
+                stale_keys.len()
+            );
+        }
+        for batch in stale_keys.chunks(REINDEX_BATCH_SIZE) {
+            let mut txn = db_connection.write_txn()?;
+            let mut dirty = HashSet::default();
+            for key in batch {
+                graph.remove_file(key, &mut dirty);
+                if let Some(file) = embeddings_db.get(&txn, key)? {
+                    let mut vectors = WriteVectors {
+                        db: vectors_db,
+                        txn: &mut txn,
+                    };
+                    graph.insert_file(key, &file, &mut vectors, &mut dirty)?;
+                }
+            }
+            graph.write_nodes(graph_db, &mut txn, dirty)?;
+            txn.commit()?;
+        }
+
+        if graph.needs_compaction() {
+            let mut txn = db_connection.write_txn()?;
+            graph = compact_graph(&graph, graph_db, vectors_db, &mut txn)?;
+            txn.commit()?;
+        }
+
+        Ok(Self {
+            db_connection: db_connection.clone(),
+            embeddings_db,
+            graph_db,
+            vectors_db,
+            graph: Arc::new(RwLock::new(graph)),
+        })
+    }
+}
+
+/// Replaces the graph with a copy that only contains its live nodes, rewriting its nodes and
+/// their vectors in the database.
+fn compact_graph(
+    graph: &Hnsw,
+    graph_db: GraphDb,
+    vectors_db: VectorsDb,
+    txn: &mut heed::RwTxn,
+) -> Result<Hnsw> {
+    let compacted = graph.compact(&mut WriteVectors {
+        db: vectors_db,
+        txn: &mut *txn,
+    })?;
+    for id in compacted.nodes.len() as u32..graph.nodes.len() as u32 {
+        vectors_db.delete(txn, &id)?;
+    }
+    graph_db.clear(txn)?;
+    compacted.write_nodes(graph_db, txn, (0..compacted.nodes.len() as u32).collect())?;
+    Ok(compacted)
+}
+
+impl VectorStore for HnswStore {
+    fn search(
+        &self,
+        query: Embedding,
+        limit: usize,
+        executor: &BackgroundExecutor,
+    ) -> Task<Result<Vec<ChunkMatch>>> {
+        let db_connection = self.db_connection.clone();
+        let embeddings_db = self.embeddings_db;
+        let vectors_db = self.vectors_db;
+        let graph = self.graph.clone();
+        executor.spawn(async move {
+            let txn = db_connection
+                .read_txn()
+                .context("failed to create read transaction")?;
+            let vectors = ReadVectors {
+                db: vectors_db,
+                txn: &txn,
+            };
+            let candidates = graph
+                .read()
+                .search(&query, limit * RERANK_FACTOR, &vectors)?;
+
+            let mut ranges_by_file = HashMap::<String, Vec<Range<usize>>>::default();
+            for (file_key, range) in candidates {
+                ranges_by_file.entry(file_key).or_default().push(range);
+            }
+
+            let mut results = Vec::new();
+            for (file_key, ranges) in ranges_by_file {
+                let Some(file) = embeddings_db.get(&txn, &file_key)? else {
+                    continue;
+                };
+                for chunk in &file.chunks {
+                    if ranges.contains(&chunk.chunk.range) {
+                        results.push(ChunkMatch {
+                            path: file.path.clone(),
+                            range: chunk.chunk.range.clone(),
+                            score: chunk.embedding.clone().similarity(&query),
+                        });
+                    }
+                }
+            }
+            results.sort_unstable_by(|a, b| b.score.total_cmp(&a.score));
+            results.truncate(limit);
+            Ok(results)
+        })
+    }
+
+    fn update(
+        &self,
+        txn: &mut heed::RwTxn,
+        deleted_keys: &[String],
+        embedded_files: &[&EmbeddedFile],
+    ) -> Result<()> {
+        let mut graph = self.graph.write();
+        let mut dirty = HashSet::default();
+        for key in deleted_keys {
+            graph.remove_file(key, &mut dirty);
+        }
+        let mut vectors = WriteVectors {
+            db: self.vectors_db,
+            txn: &mut *txn,
+        };
+        for file in embedded_files {
+            let key = db_key_for_path(&file.path);
+            graph.remove_file(&key, &mut dirty);
+            graph.insert_file(&key, file, &mut vectors, &mut dirty)?;
+        }
+
+        if graph.needs_compaction() {
+            *graph = compact_graph(&graph, self.graph_db, self.vectors_db, txn)?;
+            Ok(())
+        } else {
+            graph.write_nodes(self.graph_db, txn, dirty)
+        }
+    }
+}
+
+/// Where the graph reads the vectors of its nodes, which aren't kept in memory.
+trait VectorSource {
+    /// Returns the vector of a node, or `None` if it was written by a transaction that isn't
+    /// visible yet.
+    fn get(&self, id: u32) -> Result<Option<QuantizedVector>>;
+}
+
+/// Where the graph writes the vectors of the nodes it inserts.
+trait VectorStorage: VectorSource {
+    fn put(&mut self, id: u32, vector: &QuantizedVector) -> Result<()>;
+}
+
+/// The vectors of a graph, read in a database transaction.
+struct ReadVectors<'a, 't> {
+    db: VectorsDb,
+    txn: &'a heed::RoTxn<'t>,
+}
+
+impl VectorSource for ReadVectors<'_, '_> {
+    fn get(&self, id: u32) -> Result<Option<QuantizedVector>> {
+        Ok(self.db.get(self.txn, &id)?)
+    }
+}
+
+/// The vectors of a graph, read and written in a database transaction.
+struct WriteVectors<'a, 't> {
+    db: VectorsDb,
+    txn: &'a mut heed::RwTxn<'t>,
+}
+
+impl VectorSource for WriteVectors<'_, '_> {
+    fn get(&self, id: u32) -> Result<Option<QuantizedVector>> {
+        Ok(self.db.get(self.txn, &id)?)
+    }
+}
+
+impl VectorStorage for WriteVectors<'_, '_> {
+    fn put(&mut self, id: u32, vector: &QuantizedVector) -> Result<()> {
+        Ok(self.db.put(self.txn, &id, vector)?)
+    }
+}
+
+/// An embedding quantized to one byte per dimension.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct QuantizedVector {
+    values: Vec<i8>,
+    scale: f32,
+}
+
+impl QuantizedVector {
+    fn new(embedding: &Embedding) -> Self {
+        let values = embedding.values();
+        let max = values.iter().fold(0f32, |max, value| max.max(value.abs()));
+        let scale = if max > 0. { max / i8::MAX as f32 } else { 1. };
+        Self {
+            values: values
+                .iter()
+                .map(|value| (value / scale).round() as i8)
+                .collect(),
+            scale,
+        }
+    }
+
+    fn similarity(&self, other: &QuantizedVector) -> f32 {
+        let dot = self
+            .values
+            .iter()
+            .zip(&other.values)
+            .map(|(a, b)| *a as i32 * *b as i32)
+            .sum::<i32>();
+        dot as f32 * self.scale * other.scale
+    }
+}
+
+/// A chunk in the graph, whose vector is stored in the database under the node's id.
+struct HnswNode {
+    /// The chunk's file, as an index into [`Hnsw::files`].
+    file: u32,
+    range: Range<usize>,
+    /// The node's neighbors on each of the layers it belongs to, starting from the bottom one.
+    neighbors: Vec<Vec<u32>>,
+    /// Deleted nodes are kept in the graph until it's compacted, as other nodes are reached
+    /// through them.
+    deleted: bool,
+}
+
+/// A node as it's persisted, which refers to its file by key.
+#[derive(Debug, Serialize, Deserialize)]
+struct SerializedHnswNode {
+    file_key: String,
+    mtime: Option<SystemTime>,
+    range: Range<usize>,
+    neighbors: Vec<Vec<u32>>,
+    deleted: bool,
+}
+
+/// A file whose chunks were inserted in the graph.
+struct HnswFile {
+    key: Arc<str>,
+    mtime: Option<SystemTime>,
+    /// The file's live nodes, which are deleted when it's removed.
+    nodes: Vec<u32>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Candidate {
+    score: f32,
+    id: u32,
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score
+            .total_cmp(&other.score)
+            .then_with(|| other.id.cmp(&self.id))
+    }
+}
+
+struct Hnsw {
+    nodes: Vec<HnswNode>,
+    /// Every file that had chunks inserted since the graph was last compacted.
+    files: Vec<HnswFile>,
+    /// The index of each file in `files`, by the file's key in the embeddings database.
+    file_ids: HashMap<Arc<str>, u32>,
+    entry_point: Option<u32>,
+    deleted_count: usize,
+    rng: StdRng,
+}
+
+impl Default for Hnsw {
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            files: Vec::new(),
+            file_ids: HashMap::default(),
+            entry_point: None,
+            deleted_count: 0,
+            rng: StdRng::seed_from_u64(0),
+        }
+    }
+}
+
+impl Hnsw {
+    /// Rebuilds the graph from its persisted nodes, returning `None` if they aren't consistent.
+    fn from_nodes(mut nodes: Vec<(u32, SerializedHnswNode)>) -> Option<Self> {
+        nodes.sort_unstable_by_key(|(id, _)| *id);
+        let mut graph = Self {
+            rng: StdRng::seed_from_u64(nodes.len() as u64),
+            ..Default::default()
+        };
+        for (ix, (id, node)) in nodes.into_iter().enumerate() {
+            if id as usize != ix || node.neighbors.is_empty() {
+                return None;
+            }
+            let file_id = graph.file_id(&node.file_key);
+            if node.deleted {
+                graph.deleted_count += 1;
+            } else {
+                let file = &mut graph.files[file_id as usize];
+                file.mtime = node.mtime;
+                file.nodes.push(id);
+            }
+            if graph.entry_point.map_or(true, |entry_point| {
+                node.neighbors.len() > graph.nodes[entry_point as usize].neighbors.len()
+            }) {
+                graph.entry_point = Some(id);
+            }
+            graph.nodes.push(HnswNode {
+                file: file_id,
+                range: node.range,
+                neighbors: node.neighbors,
+                deleted: node.deleted,
+            });
+        }
+
+        let node_count = graph.nodes.len() as u32;
+        let is_consistent = graph.nodes.iter().all(|node| {
+            node.neighbors
+                .iter()
+                .flatten()
+                .all(|neighbor| *neighbor < node_count)
+        });
+        is_consistent.then_some(graph)
+    }
+
+    fn live_count(&self) -> usize {
+        self.nodes.len() - self.deleted_count
+    }
+
+    fn needs_compaction(&self) -> bool {
+        self.deleted_count >= MIN_DELETED_NODES_TO_COMPACT && self.deleted_count > self.live_count()
+    }
+
+    /// Returns the index of the file with the given key, adding it if needed.
+    fn file_id(&mut self, key: &str) -> u32 {
+        if let Some(id) = self.file_ids.get(key) {
+            return *id;
+        }
+        let id = self.files.len() as u32;
+        let key = Arc::<str>::from(key);
+        self.files.push(HnswFile {
+            key: key.clone(),
+            mtime: None,
+            nodes: Vec::new(),
+        });
+        self.file_ids.insert(key, id);
+        id
+    }
+
+    /// Returns the modification time and chunk count of each indexed file.
+    fn indexed_files(&self) -> HashMap<String, (Option<SystemTime>, usize)> {
+        self.files
+            .iter()
+            .filter(|file| !file.nodes.is_empty())
+            .map(|file| (file.key.to_string(), (file.mtime, file.nodes.len())))
+            .collect()
+    }
+
+    fn insert_file(
+        &mut self,
+        key: &str,
+        file: &EmbeddedFile,
+        vectors: &mut impl VectorStorage,
+        dirty: &mut HashSet<u32>,
+    ) -> Result<()> {
+        let file_id = self.file_id(key);
+        self.files[file_id as usize].mtime = file.mtime;
+        for chunk in &file.chunks {
+            self.insert(
+                file_id,
+                chunk.chunk.range.clone(),
+                QuantizedVector::new(&chunk.embedding),
+                vectors,
+                dirty,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn remove_file(&mut self, key: &str, dirty: &mut HashSet<u32>) {
+        let Some(file_id) = self.file_ids.get(key) else {
+            return;
+        };
+        for id in mem::take(&mut self.files[*file_id as usize].nodes) {
+            self.nodes[id as usize].deleted = true;
+            self.deleted_count += 1;
+            dirty.insert(id);
+        }
+    }
+
+    /// Builds a new graph out of the live nodes of this one. As the live nodes keep their order,
+    /// each one's vector is moved to an id that's no greater than its current one, so the
+    /// vectors can be rewritten in place.
+    fn compact(&self, vectors: &mut impl VectorStorage) -> Result<Self> {
+        let mut graph = Self::default();
+        let mut dirty = HashSet::default();
+        for (id, node) in self.nodes.iter().enumerate() {
+            if node.deleted {
+                continue;
+            }
+            let Some(vector) = vectors.get(id as u32)? else {
+                continue;
+            };
+            let file = &self.files[node.file as usize];
+            let file_id = graph.file_id(&file.key);
+            graph.files[file_id as usize].mtime = file.mtime;
+            graph.insert(file_id, node.range.clone(), vector, vectors, &mut dirty)?;
+        }
+        Ok(graph)
+    }
+
+    fn write_nodes(&self, db: GraphDb, txn: &mut heed::RwTxn, ids: HashSet<u32>) -> Result<()> {
+        for id in ids {
+            let node = &self.nodes[id as usize];
+            let file = &self.files[node.file as usize];
+            let node = SerializedHnswNode {
+                file_key: file.key.to_string(),
+                mtime: file.mtime,
+                range: node.range.clone(),
+                neighbors: node.neighbors.clone(),
+                deleted: node.deleted,
+            };
+            db.put(txn, &id, &node)?;
+        }
+        Ok(())
+    }
+
+    fn random_level(&mut self) -> usize {
+        let level_multiplier = 1. / (MAX_NEIGHBORS as f64).ln();
+        let sample = 1. - self.rng.gen::<f64>();
+        ((-sample.ln() * level_multiplier) as usize).min(MAX_LEVEL)
+    }
+
+    fn top_level(&self) -> usize {
+        self.entry_point.map_or(0, |entry_point| {
+            self.nodes[entry_point as usize].neighbors.len() - 1
+        })
+    }
+
+    fn insert(
+        &mut self,
+        file: u32,
+        range: Range<usize>,
+        vector: QuantizedVector,
+        vectors: &mut impl VectorStorage,
+        dirty: &mut HashSet<u32>,
+    ) -> Result<()> {
+        let id = self.nodes.len() as u32;
+        let level = self.random_level();
+        vectors.put(id, &vector)?;
+        self.files[file as usize].nodes.push(id);
+        self.nodes.push(HnswNode {
+            file,
+            range,
+            neighbors: vec![Vec::new(); level + 1],
+            deleted: false,
+        });
+        dirty.insert(id);
+
+        let Some(entry_point) = self.entry_point else {
+            self.entry_point = Some(id);
+            return Ok(());
+        };
+
+        let top_level = self.top_level();
+        let mut entry_points = vec![entry_point];
+        for layer in (level + 1..=top_level).rev() {
+            entry_points = self.closest_in_layer(&vector, &entry_points, 1, layer, &*vectors)?;
+        }
+        for layer in (0..=level.min(top_level)).rev() {
+            let candidates =
+                self.search_layer(&vector, &entry_points, EF_CONSTRUCTION, layer, &*vectors)?;
+            let neighbors = candidates
+                .iter()
+                .filter(|candidate| !self.nodes[candidate.id as usize].deleted)
+                .take(MAX_NEIGHBORS)
+                .map(|candidate| candidate.id)
+                .collect::<Vec<_>>();
+            for neighbor in &neighbors {
+                self.connect(*neighbor, id, layer, &*vectors)?;
+                dirty.insert(*neighbor);
+            }
+            self.nodes[id as usize].neighbors[layer] = neighbors;
+            entry_points = candidates.iter().map(|candidate| candidate.id).collect();
+        }
+
+        if level > top_level {
+            self.entry_point = Some(id);
+        }
+        Ok(())
+    }
+
+    /// Links `from` to `to`, dropping `from`'s least similar neighbor if it has too many.
+    fn connect(
+        &mut self,
+        from: u32,
+        to: u32,
+        layer: usize,
+        vectors: &impl VectorSource,
+    ) -> Result<()> {
+        let max_neighbors = if layer == 0 {
+            MAX_NEIGHBORS * 2
+        } else {
+            MAX_NEIGHBORS
+        };
+        self.nodes[from as usize].neighbors[layer].push(to);
+        if self.nodes[from as usize].neighbors[layer].len() <= max_neighbors {
+            return Ok(());
+        }
+        let Some(vector) = vectors.get(from)? else {
+            return Ok(());
+        };
+
+        let mut candidates = Vec::new();
+        for id in &self.nodes[from as usize].neighbors[layer] {
+            if let Some(neighbor_vector) = vectors.get(*id)? {
+                candidates.push(Candidate {
+                    score: vector.similarity(&neighbor_vector),
+                    id: *id,
+                });
+            }
+        }
+        candidates.sort_unstable_by(|a, b| b.cmp(a));
+        self.nodes[from as usize].neighbors[layer] = candidates
+            .into_iter()
+            .take(max_neighbors)
+            .map(|candidate| candidate.id)
+            .collect();
+        Ok(())
+    }
+
+    /// Returns the file key and range of the live chunks closest to the query, best first.
+    fn search(
+        &self,
+        query: &Embedding,
+        limit: usize,
+        vectors: &impl VectorSource,
+    ) -> Result<Vec<(String, Range<usize>)>> {
+        let Some(entry_point) = self.entry_point else {
+            return Ok(Vec::new());
+        };
+        let query = QuantizedVector::new(query);
+        let mut entry_points = vec![entry_point];
+        for layer in (1..=self.top_level()).rev() {
+            entry_points = self.closest_in_layer(&query, &entry_points, 1, layer, vectors)?;
+        }
+
+        let ef = limit.max(MIN_EF_SEARCH) + self.deleted_count.min(limit);
+        Ok(self
+            .search_layer(&query, &entry_points, ef, 0, vectors)?
+            .into_iter()
+            .map(|candidate| &self.nodes[candidate.id as usize])
+            .filter(|node| !node.deleted)
+            .take(limit)
+            .map(|node| {
+                let file_key = self.files[node.file as usize].key.to_string();
+                (file_key, node.range.clone())
+            })
+            .collect())
+    }
+
+    fn closest_in_layer(
+        &self,
+        query: &QuantizedVector,
+        entry_points: &[u32],
+        count: usize,
+        layer: usize,
+        vectors: &impl VectorSource,
+    ) -> Result<Vec<u32>> {
+        Ok(self
+            .search_layer(query, entry_points, count, layer, vectors)?
+            .into_iter()
+            .map(|candidate| candidate.id)
+            .collect())
+    }
+
+    /// Greedily explores a layer from the entry points, returning the `ef` nodes closest to the
+    /// query that it found, best first.
+    fn search_layer(
+        &self,
+        query: &QuantizedVector,
+        entry_points: &[u32],
+        ef: usize,
+        layer: usize,
+        vectors: &impl VectorSource,
+    ) -> Result<Vec<Candidate>> {
+        let mut visited = HashSet::default();
+        let mut candidates = BinaryHeap::new();
+        let mut results = BinaryHeap::new();
+        for id in entry_points {
+            if !visited.insert(*id) {
+                continue;
+            }
+            let Some(vector) = vectors.get(*id)? else {
+                continue;
+            };
+            let candidate = Candidate {
+                score: query.similarity(&vector),
+                id: *id,
+            };
+            candidates.push(candidate);
+            results.push(Reverse(candidate));
+        }
+        while results.len() > ef {
+            results.pop();
+        }
+
+        while let Some(candidate) = candidates.pop() {
+            let Some(Reverse(worst)) = results.peek() else {
+                break;
+            };
+            if results.len() >= ef && candidate.score < worst.score {
+                break;
+            }
+
+            let node = &self.nodes[candidate.id as usize];
+            for neighbor in node.neighbors.get(layer).into_iter().flatten() {
+                if !visited.insert(*neighbor) {
+                    continue;
+                }
+                let Some(vector) = vectors.get(*neighbor)? else {
+                    continue;
+                };
+                let neighbor = Candidate {
+                    score: query.similarity(&vector),
+                    id: *neighbor,
+                };
+                let is_better = results
+                    .peek()
+                    .map_or(true, |Reverse(worst)| neighbor.score > worst.score);
+                if results.len() < ef || is_better {
+                    candidates.push(neighbor);
+                    results.push(Reverse(neighbor));
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+
+        let mut results = results
+            .into_iter()
+            .map(|Reverse(candidate)| candidate)
+            .collect::<Vec<_>>();
+        results.sort_unstable_by(|a, b| b.cmp(a));
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{chunking::Chunk, EmbeddedChunk};
+
+    impl VectorSource for HashMap<u32, QuantizedVector> {
+        fn get(&self, id: u32) -> Result<Option<QuantizedVector>> {
+            Ok(HashMap::get(self, &id).cloned())
+        }
+    }
+
+    impl VectorStorage for HashMap<u32, QuantizedVector> {
+        fn put(&mut self, id: u32, vector: &QuantizedVector) -> Result<()> {
+            self.insert(id, vector.clone());
+            Ok(())
+        }
+    }
+
+    fn random_embedding(rng: &mut StdRng, dimensions: usize) -> Embedding {
+        Embedding::new(
+            (0..dimensions)
+                .map(|_| rng.gen_range(-1f32..1f32))
+                .collect(),
+        )
+    }
+
+    fn embedded_file(path: &str, embeddings: Vec<Embedding>) -> EmbeddedFile {
+        EmbeddedFile {
+            path: Path::new(path).into(),
+            mtime: None,
+            chunks: embeddings
+                .into_iter()
+                .enumerate()
+                .map(|(ix, embedding)| EmbeddedChunk {
+                    chunk: Chunk {
+                        range: ix..ix + 1,
+                        digest: Default::default(),
+                    },
+                    embedding,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_hnsw_recall() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let files = (0..200)
+            .map(|ix| {
+                let embeddings = (0..10).map(|_| random_embedding(&mut rng, 32)).collect();
+                embedded_file(&format!("file-{ix}"), embeddings)
+            })
+            .collect::<Vec<_>>();
+
+        let mut graph = Hnsw::default();
+        let mut vectors = HashMap::default();
+        let mut dirty = HashSet::default();
+        for file in &files {
+            graph
+                .insert_file(&db_key_for_path(&file.path), file, &mut vectors, &mut dirty)
+                .unwrap();
+        }
+        assert_eq!(dirty.len(), 2000);
+        assert_eq!(graph.files.len(), 200);
+
+        let limit = 10;
+        let mut found = 0;
+        for _ in 0..20 {
+            let query = random_embedding(&mut rng, 32);
+            let mut exact = files
+                .iter()
+                .flat_map(|file| {
+                    file.chunks.iter().map(|chunk| {
+                        (
+                            chunk.embedding.clone().similarity(&query),
+                            db_key_for_path(&file.path),
+                            chunk.chunk.range.clone(),
+                        )
+                    })
+                })
+                .collect::<Vec<_>>();
+            exact.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+            let approximate = graph
+                .search(&query, limit * RERANK_FACTOR, &vectors)
+                .unwrap();
+            found += exact[..limit]
+                .iter()
+                .filter(|(_, key, range)| approximate.contains(&(key.clone(), range.clone())))
+                .count();
+        }
+        let recall = found as f32 / (20 * limit) as f32;
+        assert!(recall > 0.8, "recall was {recall}");
+    }
+
+    #[test]
+    fn test_hnsw_removal_and_compaction() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut graph = Hnsw::default();
+        let mut vectors = HashMap::default();
+        let mut dirty = HashSet::default();
+        let removed = embedded_file("removed", vec![random_embedding(&mut rng, 8); 3]);
+        let kept = embedded_file("kept", vec![random_embedding(&mut rng, 8); 2]);
+        graph
+            .insert_file("removed", &removed, &mut vectors, &mut dirty)
+            .unwrap();
+        graph
+            .insert_file("kept", &kept, &mut vectors, &mut dirty)
+            .unwrap();
+
+        graph.remove_file("removed", &mut dirty);
+        assert_eq!(graph.live_count(), 2);
+        let query = removed.chunks[0].embedding.clone();
+        assert!(graph
+            .search(&query, 10, &vectors)
+            .unwrap()
+            .iter()
+            .all(|(key, _)| key == "kept"));
+
+        // The live nodes' vectors are moved to the start of the storage.
+        let graph = graph.compact(&mut vectors).unwrap();
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.files.len(), 1);
+        assert_eq!(graph.deleted_count, 0);
+        assert_eq!(
+            vectors[&0].values,
+            QuantizedVector::new(&kept.chunks[0].embedding).values
+        );
+        assert_eq!(graph.search(&query, 10, &vectors).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_hnsw_persistence() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db_connection = unsafe {
+            heed::EnvOpenOptions::new()
+                .map_size(16 * 1024 * 1024)
+                .max_dbs(4)
+                .open(temp_dir.path())
+                .unwrap()
+        };
+        let mut txn = db_connection.write_txn().unwrap();
+        let embeddings_db: EmbeddingsDb = db_connection
+            .create_database(&mut txn, Some("worktree"))
+            .unwrap();
+        let mut rng = StdRng::seed_from_u64(3);
+        let first = embedded_file("a/first.rs", vec![random_embedding(&mut rng, 8); 2]);
+        embeddings_db
+            .put(&mut txn, &db_key_for_path(&first.path), &first)
+            .unwrap();
+        txn.commit().unwrap();
+
+        // Files embedded before the store existed are indexed when it's opened.
+        let store = HnswStore::open(&db_connection, embeddings_db, "worktree").unwrap();
+        assert_eq!(store.graph.read().live_count(), 2);
+
+        let second = embedded_file("b/second.rs", vec![random_embedding(&mut rng, 8); 3]);
+        let mut txn = db_connection.write_txn().unwrap();
+        let first_key = db_key_for_path(&first.path);
+        embeddings_db.delete(&mut txn, &first_key).unwrap();
+        embeddings_db
+            .put(&mut txn, &db_key_for_path(&second.path), &second)
+            .unwrap();
+        store.update(&mut txn, &[first_key], &[&second]).unwrap();
+        txn.commit().unwrap();
+        drop(store);
+
+        let store = HnswStore::open(&db_connection, embeddings_db, "worktree").unwrap();
+        let graph = store.graph.read();
+        assert_eq!(graph.nodes.len(), 5);
+        assert_eq!(graph.live_count(), 3);
+        assert_eq!(
+            graph.indexed_files().into_keys().collect::<Vec<_>>(),
+            [db_key_for_path(&second.path)]
+        );
+        let txn = db_connection.read_txn().unwrap();
+        assert_eq!(store.vectors_db.len(&txn).unwrap(), 5);
+    }
+}
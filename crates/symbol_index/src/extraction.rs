@@ -0,0 +1,171 @@
+use language::{with_parser, with_query_cursor, Language};
+use serde::{Deserialize, Serialize};
+use std::{ops::Range, sync::Arc};
+use util::ResultExt as _;
+
+/// A symbol defined in a file, as found by its language's outline query.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileSymbol {
+    /// The symbol's outline text, such as `fn main` or `impl Display for Point`.
+    pub text: String,
+    /// The range of the symbol's name within `text`.
+    pub name_range: Range<usize>,
+    /// The row on which the symbol starts.
+    pub row: u32,
+    /// The UTF-16 column at which the symbol starts.
+    pub column: u32,
+}
+
+impl FileSymbol {
+    pub fn name(&self) -> &str {
+        &self.text[self.name_range.clone()]
+    }
+}
+
+/// Parses the text and returns the items matched by the language's outline query, in the same
+/// form as they'd appear in a buffer's outline.
+pub fn extract_symbols(text: &str, language: &Arc<Language>) -> Vec<FileSymbol> {
+    let Some(grammar) = language.grammar() else {
+        return Vec::new();
+    };
+    let Some(outline) = grammar.outline_config.as_ref() else {
+        return Vec::new();
+    };
+    let tree = with_parser(|parser| {
+        parser.set_language(&grammar.ts_language).log_err()?;
+        parser.parse(text, None)
+    });
+    let Some(tree) = tree else {
+        return Vec::new();
+    };
+
+    with_query_cursor(|cursor| {
+        cursor
+            .matches(&outline.query, tree.root_node(), text.as_bytes())
+            .filter_map(|mat| {
+                let item = mat
+                    .captures
+                    .iter()
+                    .find(|capture| capture.index == outline.item_capture_ix)?;
+
+                let mut ranges = mat
+                    .captures
+                    .iter()
+                    .filter_map(|capture| {
+                        let is_name = capture.index == outline.name_capture_ix;
+                        if !is_name && Some(capture.index) != outline.context_capture_ix {
+                            return None;
+                        }
+
+                        // Only keep the first line of captures that span several lines.
+                        let mut range = capture.node.byte_range();
+                        if let Some(newline_ix) = text[range.clone()].find('\n') {
+                            range.end = range.start + newline_ix;
+                        }
+                        (!range.is_empty()).then_some((range, is_name))
+                    })
+                    .collect::<Vec<_>>();
+                ranges.sort_by_key(|(range, _)| range.start);
+
+                let mut symbol_text = String::new();
+                let mut name_range: Option<Range<usize>> = None;
+                let mut last_end = None;
+                for (range, is_name) in ranges {
+                    if last_end.map_or(false, |last_end| range.start > last_end) {
+                        symbol_text.push(' ');
+                    }
+                    last_end = Some(range.end);
+                    let start = symbol_text.len();
+                    symbol_text.push_str(&text[range]);
+                    if is_name {
+                        let start = name_range.map_or(start, |name_range| name_range.start);
+                        name_range = Some(start..symbol_text.len());
+                    }
+                }
+
+                let position = item.node.start_position();
+                let line_start = item.node.start_byte() - position.column;
+                Some(FileSymbol {
+                    name_range: name_range?,
+                    text: symbol_text,
+                    row: position.row as u32,
+                    column: text[line_start..item.node.start_byte()]
+                        .encode_utf16()
+                        .count() as u32,
+                })
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use language::{tree_sitter_rust, LanguageConfig, LanguageMatcher};
+    use unindent::Unindent as _;
+
+    #[test]
+    fn test_extract_symbols() {
+        let text = "
+            struct Point {
+                x: f32,
+            }
+
+            impl Display for Point {
+                fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+                    todo!()
+                }
+            }
+
+            /* ö */ fn main() {}
+        "
+        .unindent();
+
+        let symbols = extract_symbols(&text, &rust_language());
+        assert_eq!(
+            symbols
+                .iter()
+                .map(|symbol| (
+                    symbol.text.as_str(),
+                    symbol.name(),
+                    symbol.row,
+                    symbol.column
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("struct Point", "Point", 0, 0),
+                ("impl Display for Point", "Display for Point", 4, 0),
+                ("fn fmt", "fmt", 5, 4),
+                ("fn main", "main", 10, 8),
+            ]
+        );
+    }
+
+    fn rust_language() -> Arc<Language> {
+        Arc::new(
+            Language::new(
+                LanguageConfig {
+                    name: "Rust".into(),
+                    matcher: LanguageMatcher {
+                        path_suffixes: vec!["rs".to_string()],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                Some(tree_sitter_rust::language()),
+            )
+            .with_outline_query(
+                r#"
+                (struct_item "struct" @context name: (_) @name) @item
+                (impl_item
+                    "impl" @context
+                    trait: (_)? @name
+                    "for"? @context
+                    type: (_) @name) @item
+                (function_item "fn" @context name: (_) @name) @item
+                "#,
+            )
+            .unwrap(),
+        )
+    }
+}
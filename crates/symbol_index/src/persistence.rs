@@ -0,0 +1,44 @@
+use std::path::PathBuf;
+
+use db::{define_connection, query, sqlez_macros::sql};
+
+define_connection!(pub static ref SYMBOL_INDEX_DB: SymbolIndexDb<()> =
+    &[sql!(
+        CREATE TABLE IF NOT EXISTS indexed_files(
+            worktree_path BLOB NOT NULL,
+            path BLOB NOT NULL,
+            mtime INTEGER NOT NULL,
+            symbols TEXT NOT NULL,
+            PRIMARY KEY(worktree_path, path)
+        ) STRICT;
+    )];
+);
+
+impl SymbolIndexDb {
+    query! {
+        pub fn get_files(worktree_path: PathBuf) -> Result<Vec<(PathBuf, i64, String)>> {
+            SELECT path, mtime, symbols
+            FROM indexed_files
+            WHERE worktree_path = ?
+        }
+    }
+
+    query! {
+        pub async fn save_file(
+            worktree_path: PathBuf,
+            path: PathBuf,
+            mtime: i64,
+            symbols: String
+        ) -> Result<()> {
+            INSERT OR REPLACE INTO indexed_files(worktree_path, path, mtime, symbols)
+            VALUES (?1, ?2, ?3, ?4)
+        }
+    }
+
+    query! {
+        pub async fn delete_file(worktree_path: PathBuf, path: PathBuf) -> Result<()> {
+            DELETE FROM indexed_files
+            WHERE worktree_path = ?1 AND path = ?2
+        }
+    }
+}
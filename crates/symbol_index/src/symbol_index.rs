@@ -0,0 +1,540 @@
+mod extraction;
+mod persistence;
+
+use anyhow::{Context as _, Result};
+use collections::HashMap;
+use fs::Fs;
+use fuzzy::{match_strings, StringMatchCandidate};
+use gpui::{
+    AppContext, AsyncAppContext, BorrowAppContext, Context, Entity, EntityId, Global, Model,
+    ModelContext, Subscription, Task, ViewContext, WeakModel,
+};
+use language::{CodeLabel, LanguageRegistry, PointUtf16, Unclipped};
+use persistence::SYMBOL_INDEX_DB;
+use project::{Project, ProjectPath, UpdatedEntriesSet, Worktree};
+use smol::channel;
+use std::{
+    path::Path,
+    sync::{atomic::AtomicBool, Arc},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use sum_tree::TreeMap;
+use util::ResultExt;
+use workspace::Workspace;
+use worktree::{PathChange, Snapshot};
+
+pub use extraction::{extract_symbols, FileSymbol};
+
+/// Files larger than this are skipped, as they're rarely hand-written source code.
+const MAX_INDEXED_FILE_LEN: usize = 1024 * 1024;
+
+pub fn init(cx: &mut AppContext) {
+    cx.set_global(SymbolIndex::default());
+    cx.observe_new_views(
+        |workspace: &mut Workspace, cx: &mut ViewContext<Workspace>| {
+            let project = workspace.project().clone();
+            if project.read(cx).is_local() {
+                SymbolIndex::project_index(project, cx);
+            }
+        },
+    )
+    .detach();
+}
+
+/// Indexes the symbols defined in local projects using each language's outline query, so that
+/// they can be searched without a language server.
+#[derive(Default)]
+pub struct SymbolIndex {
+    project_indices: HashMap<WeakModel<Project>, Model<ProjectSymbolIndex>>,
+}
+
+impl Global for SymbolIndex {}
+
+impl SymbolIndex {
+    /// Returns the symbol index for the project, creating it if needed. Returns `None` if
+    /// [`init`] hasn't been called.
+    pub fn project_index(
+        project: Model<Project>,
+        cx: &mut AppContext,
+    ) -> Option<Model<ProjectSymbolIndex>> {
+        let existing_index = cx
+            .try_global::<Self>()?
+            .project_indices
+            .get(&project.downgrade())
+            .cloned();
+        if let Some(index) = existing_index {
+            return Some(index);
+        }
+
+        let project_weak = project.downgrade();
+        project.update(cx, move |_, cx| {
+            cx.on_release(move |_, cx| {
+                if cx.has_global::<SymbolIndex>() {
+                    cx.update_global::<SymbolIndex, _>(|this, _| {
+                        this.project_indices.remove(&project_weak);
+                    })
+                }
+            })
+            .detach();
+        });
+
+        let index = cx.new_model(|cx| ProjectSymbolIndex::new(project.clone(), cx));
+        cx.update_global::<Self, _>(|this, _| {
+            this.project_indices
+                .insert(project.downgrade(), index.clone());
+        });
+        Some(index)
+    }
+}
+
+/// A symbol found by searching a [`ProjectSymbolIndex`].
+#[derive(Clone, Debug)]
+pub struct IndexedSymbol {
+    pub path: ProjectPath,
+    pub name: String,
+    pub label: CodeLabel,
+    pub position: Unclipped<PointUtf16>,
+}
+
+pub struct ProjectSymbolIndex {
+    project: WeakModel<Project>,
+    worktree_indices: HashMap<EntityId, Model<WorktreeSymbolIndex>>,
+    language_registry: Arc<LanguageRegistry>,
+    fs: Arc<dyn Fs>,
+    _subscription: Subscription,
+}
+
+impl ProjectSymbolIndex {
+    fn new(project: Model<Project>, cx: &mut ModelContext<Self>) -> Self {
+        let language_registry = project.read(cx).languages().clone();
+        let fs = project.read(cx).fs().clone();
+        let mut this = ProjectSymbolIndex {
+            project: project.downgrade(),
+            worktree_indices: HashMap::default(),
+            language_registry,
+            fs,
+            _subscription: cx.subscribe(&project, Self::handle_project_event),
+        };
+        this.update_worktree_indices(cx);
+        this
+    }
+
+    fn handle_project_event(
+        &mut self,
+        _: Model<Project>,
+        event: &project::Event,
+        cx: &mut ModelContext<Self>,
+    ) {
+        match event {
+            project::Event::WorktreeAdded | project::Event::WorktreeRemoved(_) => {
+                self.update_worktree_indices(cx);
+            }
+            _ => {}
+        }
+    }
+
+    fn update_worktree_indices(&mut self, cx: &mut ModelContext<Self>) {
+        let Some(project) = self.project.upgrade() else {
+            return;
+        };
+
+        let worktrees = project
+            .read(cx)
+            .visible_worktrees(cx)
+            .filter_map(|worktree| {
+                if worktree.read(cx).is_local() {
+                    Some((worktree.entity_id(), worktree))
+                } else {
+                    None
+                }
+            })
+            .collect::<HashMap<_, _>>();
+
+        self.worktree_indices
+            .retain(|worktree_id, _| worktrees.contains_key(worktree_id));
+        for (worktree_id, worktree) in worktrees {
+            self.worktree_indices.entry(worktree_id).or_insert_with(|| {
+                let language_registry = self.language_registry.clone();
+                let fs = self.fs.clone();
+                cx.new_model(|cx| WorktreeSymbolIndex::new(worktree, language_registry, fs, cx))
+            });
+        }
+    }
+
+    /// Fuzzy-matches the query against the names of all indexed symbols, returning at most
+    /// `limit` of the best matches.
+    pub fn search(&self, query: &str, limit: usize, cx: &AppContext) -> Task<Vec<IndexedSymbol>> {
+        if query.is_empty() {
+            return Task::ready(Vec::new());
+        }
+
+        let worktrees = self
+            .worktree_indices
+            .values()
+            .map(|index| {
+                let index = index.read(cx);
+                (index.worktree.read(cx).id(), index.files.clone())
+            })
+            .collect::<Vec<_>>();
+        let query = query.to_string();
+        let executor = cx.background_executor().clone();
+        cx.background_executor().spawn(async move {
+            let symbols = worktrees
+                .iter()
+                .flat_map(|(worktree_id, files)| {
+                    files.iter().flat_map(move |(path, file)| {
+                        file.symbols
+                            .iter()
+                            .map(move |symbol| (*worktree_id, path, symbol))
+                    })
+                })
+                .collect::<Vec<_>>();
+            let candidates = symbols
+                .iter()
+                .enumerate()
+                .map(|(id, (_, _, symbol))| StringMatchCandidate::new(id, symbol.name().into()))
+                .collect::<Vec<_>>();
+            let matches = match_strings(
+                &candidates,
+                &query,
+                false,
+                limit,
+                &AtomicBool::default(),
+                executor,
+            )
+            .await;
+
+            matches
+                .into_iter()
+                .map(|mat| {
+                    let (worktree_id, path, symbol) = symbols[mat.candidate_id];
+                    IndexedSymbol {
+                        path: ProjectPath {
+                            worktree_id,
+                            path: path.clone(),
+                        },
+                        name: symbol.name().to_string(),
+                        label: CodeLabel {
+                            text: symbol.text.clone(),
+                            runs: Vec::new(),
+                            filter_range: symbol.name_range.clone(),
+                        },
+                        position: Unclipped(PointUtf16::new(symbol.row, symbol.column)),
+                    }
+                })
+                .collect()
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+struct IndexedFile {
+    mtime: i64,
+    symbols: Arc<[FileSymbol]>,
+}
+
+struct WorktreeSymbolIndex {
+    worktree: Model<Worktree>,
+    files: TreeMap<Arc<Path>, IndexedFile>,
+    _index_files: Task<Result<()>>,
+    _subscription: Subscription,
+}
+
+impl WorktreeSymbolIndex {
+    fn new(
+        worktree: Model<Worktree>,
+        language_registry: Arc<LanguageRegistry>,
+        fs: Arc<dyn Fs>,
+        cx: &mut ModelContext<Self>,
+    ) -> Self {
+        let (updated_entries_tx, updated_entries_rx) = channel::unbounded();
+        let _subscription = cx.subscribe(&worktree, move |_, _, event, _| {
+            if let worktree::Event::UpdatedEntries(update) = event {
+                _ = updated_entries_tx.try_send(update.clone());
+            }
+        });
+
+        Self {
+            worktree,
+            files: TreeMap::default(),
+            _index_files: cx.spawn(|this, cx| {
+                Self::index_files(this, updated_entries_rx, language_registry, fs, cx)
+            }),
+            _subscription,
+        }
+    }
+
+    async fn index_files(
+        this: WeakModel<Self>,
+        updated_entries: channel::Receiver<UpdatedEntriesSet>,
+        language_registry: Arc<LanguageRegistry>,
+        fs: Arc<dyn Fs>,
+        mut cx: AsyncAppContext,
+    ) -> Result<()> {
+        let (worktree_abs_path, scan_complete) = this.update(&mut cx, |this, cx| {
+            let worktree = this
+                .worktree
+                .read(cx)
+                .as_local()
+                .context("symbols can only be indexed in local worktrees")?;
+            anyhow::Ok((worktree.abs_path().clone(), worktree.scan_complete()))
+        })??;
+
+        // Symbols indexed in previous sessions can be searched while the worktree is scanned.
+        let saved_files = cx
+            .background_executor()
+            .spawn({
+                let worktree_abs_path = worktree_abs_path.to_path_buf();
+                async move { SYMBOL_INDEX_DB.get_files(worktree_abs_path) }
+            })
+            .await?;
+        this.update(&mut cx, |this, _| {
+            for (path, mtime, symbols) in saved_files {
+                if let Some(symbols) = serde_json::from_str::<Vec<FileSymbol>>(&symbols).log_err() {
+                    let file = IndexedFile {
+                        mtime,
+                        symbols: symbols.into(),
+                    };
+                    this.files.insert(path.into(), file);
+                }
+            }
+        })?;
+
+        scan_complete.await;
+        let (snapshot, indexed_paths) = this.update(&mut cx, |this, cx| {
+            let indexed_paths = this
+                .files
+                .iter()
+                .map(|(path, _)| path.clone())
+                .collect::<Vec<_>>();
+            (this.worktree.read(cx).snapshot(), indexed_paths)
+        })?;
+        let paths = indexed_paths
+            .into_iter()
+            .chain(snapshot.files(false, 0).map(|entry| entry.path.clone()))
+            .collect::<Vec<_>>();
+        for path in paths {
+            Self::index_file(
+                &this,
+                &snapshot,
+                &worktree_abs_path,
+                path,
+                &language_registry,
+                &fs,
+                &mut cx,
+            )
+            .await
+            .log_err();
+        }
+
+        while let Ok(updated_entries) = updated_entries.recv().await {
+            let snapshot = this.update(&mut cx, |this, cx| this.worktree.read(cx).snapshot())?;
+            for (path, _, change) in updated_entries.iter() {
+                if *change == PathChange::Loaded {
+                    continue;
+                }
+                Self::index_file(
+                    &this,
+                    &snapshot,
+                    &worktree_abs_path,
+                    path.clone(),
+                    &language_registry,
+                    &fs,
+                    &mut cx,
+                )
+                .await
+                .log_err();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Re-extracts the symbols of the file if it changed since it was last indexed, or forgets
+    /// them if it no longer exists.
+    async fn index_file(
+        this: &WeakModel<Self>,
+        snapshot: &Snapshot,
+        worktree_abs_path: &Path,
+        path: Arc<Path>,
+        language_registry: &Arc<LanguageRegistry>,
+        fs: &Arc<dyn Fs>,
+        cx: &mut AsyncAppContext,
+    ) -> Result<()> {
+        let indexed_mtime = this.update(cx, |this, _| this.files.get(&path).map(|f| f.mtime))?;
+        let entry = snapshot
+            .entry_for_path(&path)
+            .filter(|entry| entry.is_file() && !entry.is_ignored && !entry.is_external);
+        let Some(entry) = entry else {
+            if indexed_mtime.is_some() {
+                this.update(cx, |this, _| this.files.remove(&path))?;
+                SYMBOL_INDEX_DB
+                    .delete_file(worktree_abs_path.to_path_buf(), path.to_path_buf())
+                    .await?;
+            }
+            return Ok(());
+        };
+
+        let mtime = entry.mtime.map_or(0, mtime_key);
+        if indexed_mtime == Some(mtime) {
+            return Ok(());
+        }
+        let Ok(language) = language_registry.language_for_file_path(&path).await else {
+            return Ok(());
+        };
+        if language
+            .grammar()
+            .map_or(true, |grammar| grammar.outline_config.is_none())
+        {
+            return Ok(());
+        }
+
+        let text = fs.load(&worktree_abs_path.join(&path)).await?;
+        let symbols = if text.len() > MAX_INDEXED_FILE_LEN {
+            Vec::new()
+        } else {
+            cx.background_executor()
+                .spawn(async move { extract_symbols(&text, &language) })
+                .await
+        };
+        let serialized_symbols = serde_json::to_string(&symbols)?;
+        this.update(cx, |this, _| {
+            let file = IndexedFile {
+                mtime,
+                symbols: symbols.into(),
+            };
+            this.files.insert(path.clone(), file);
+        })?;
+        SYMBOL_INDEX_DB
+            .save_file(
+                worktree_abs_path.to_path_buf(),
+                path.to_path_buf(),
+                mtime,
+                serialized_symbols,
+            )
+            .await
+    }
+}
+
+fn mtime_key(mtime: SystemTime) -> i64 {
+    mtime
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_nanos() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use language::{tree_sitter_rust, Language, LanguageConfig, LanguageMatcher};
+    use serde_json::json;
+    use settings::SettingsStore;
+
+    #[gpui::test]
+    async fn test_index_symbols(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/symbol-index-root",
+            json!({
+                "main.rs": "fn main() {}\nfn helper() {}\n",
+                "src": {
+                    "point.rs": "struct Point {}\n",
+                },
+                "notes.txt": "fn not_rust() {}",
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/symbol-index-root".as_ref()], cx).await;
+        project.update(cx, |project, _| {
+            project.languages().add(rust_language());
+        });
+        let index = cx
+            .update(|cx| SymbolIndex::project_index(project.clone(), cx))
+            .unwrap();
+        cx.run_until_parked();
+
+        assert_eq!(search(&index, "hel", cx).await, vec!["main.rs:1 helper"]);
+        assert_eq!(
+            search(&index, "point", cx).await,
+            vec!["src/point.rs:0 Point"]
+        );
+        assert_eq!(search(&index, "not", cx).await, Vec::<String>::new());
+
+        fs.save(
+            "/symbol-index-root/src/point.rs".as_ref(),
+            &"\nstruct Point {}\nfn pointer() {}\n".into(),
+            Default::default(),
+        )
+        .await
+        .unwrap();
+        fs.remove_file("/symbol-index-root/main.rs".as_ref(), Default::default())
+            .await
+            .unwrap();
+        cx.run_until_parked();
+
+        assert_eq!(search(&index, "hel", cx).await, Vec::<String>::new());
+        assert_eq!(
+            search(&index, "point", cx).await,
+            vec!["src/point.rs:1 Point", "src/point.rs:2 pointer"]
+        );
+    }
+
+    async fn search(
+        index: &Model<ProjectSymbolIndex>,
+        query: &str,
+        cx: &mut TestAppContext,
+    ) -> Vec<String> {
+        let mut results = index
+            .update(cx, |index, cx| index.search(query, 10, cx))
+            .await
+            .into_iter()
+            .map(|symbol| {
+                format!(
+                    "{}:{} {}",
+                    symbol.path.path.to_string_lossy(),
+                    symbol.position.0.row,
+                    symbol.name
+                )
+            })
+            .collect::<Vec<_>>();
+        results.sort();
+        results
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+            cx.set_global(SymbolIndex::default());
+        });
+    }
+
+    fn rust_language() -> Arc<Language> {
+        Arc::new(
+            Language::new(
+                LanguageConfig {
+                    name: "Rust".into(),
+                    matcher: LanguageMatcher {
+                        path_suffixes: vec!["rs".to_string()],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                Some(tree_sitter_rust::language()),
+            )
+            .with_outline_query(
+                r#"
+                (struct_item "struct" @context name: (_) @name) @item
+                (function_item "fn" @context name: (_) @name) @item
+                "#,
+            )
+            .unwrap(),
+        )
+    }
+}
@@ -1,6 +1,7 @@
 //! Baseline interface of Tasks in Zed: all tasks in Zed are intended to use those for implementing their own logic.
 #![deny(missing_docs)]
 
+mod problem_matcher;
 pub mod static_source;
 mod task_template;
 mod vscode_format;
@@ -11,6 +12,10 @@ use serde::Serialize;
 use std::borrow::Cow;
 use std::path::PathBuf;
 
+pub use problem_matcher::{
+    FileLocation, Problem, ProblemMatcher, ProblemMatcherDefinition, ProblemPattern,
+    ProblemPosition, ProblemSeverity,
+};
 pub use task_template::{RevealStrategy, TaskTemplate, TaskTemplates};
 pub use vscode_format::VsCodeTaskFile;
 
@@ -45,6 +50,8 @@ pub struct SpawnInTerminal {
     pub allow_concurrent_runs: bool,
     /// What to do with the terminal pane and tab, after the command was started.
     pub reveal: RevealStrategy,
+    /// Problem matchers to apply to the task output, after the task finishes.
+    pub problem_matchers: Vec<ProblemMatcher>,
 }
 
 /// A final form of the [`TaskTemplate`], that got resolved with a particualar [`TaskContext`] and now is ready to spawn the actual task.
@@ -0,0 +1,455 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use regex::{Captures, Regex};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use util::ResultExt;
+
+/// A reference to a problem matcher in the task template: either a name of a built-in matcher
+/// (e.g. `$rustc`), or a full matcher definition.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ProblemMatcherDefinition {
+    /// A name of one of the built-in matchers, such as `$rustc`, `$tsc` or `$gcc`.
+    Builtin(String),
+    /// A custom matcher.
+    Custom(ProblemMatcher),
+}
+
+impl ProblemMatcherDefinition {
+    /// Returns the matcher definition, looking up the built-in ones by their names.
+    pub fn resolve(&self) -> Option<ProblemMatcher> {
+        match self {
+            Self::Builtin(name) => {
+                let matcher = ProblemMatcher::builtin(name);
+                if matcher.is_none() {
+                    log::warn!("Unknown problem matcher name: {name}");
+                }
+                matcher
+            }
+            Self::Custom(matcher) => Some(matcher.clone()),
+        }
+    }
+}
+
+/// Describes how to extract problems (errors, warnings, etc.) from a task's output.
+///
+/// Matches are VS Code compatible: every pattern is a regex, matched against consecutive output lines,
+/// with capture group indices pointing at the parts of the problem.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProblemMatcher {
+    /// A name to show as the diagnostics source, defaults to the task label.
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Severity of the problems that do not capture one.
+    #[serde(default)]
+    pub severity: ProblemSeverity,
+    /// How to interpret file paths that were found in the output.
+    #[serde(default)]
+    pub file_location: FileLocation,
+    /// Patterns to match against consecutive output lines.
+    /// Captures of all patterns are combined into a single problem.
+    pub pattern: Vec<ProblemPattern>,
+}
+
+/// A regex with capture group indices of a problem's parts.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProblemPattern {
+    /// The regular expression to match an output line with.
+    pub regexp: String,
+    /// Capture group of the file path.
+    #[serde(default)]
+    pub file: Option<usize>,
+    /// Capture group of the 1-based line number.
+    #[serde(default)]
+    pub line: Option<usize>,
+    /// Capture group of the 1-based column number.
+    #[serde(default)]
+    pub column: Option<usize>,
+    /// Capture group of the 1-based line number where the problem ends.
+    #[serde(default)]
+    pub end_line: Option<usize>,
+    /// Capture group of the 1-based column number where the problem ends.
+    #[serde(default)]
+    pub end_column: Option<usize>,
+    /// Capture group of the severity: `error`, `warning`, `info` or `hint`.
+    #[serde(default)]
+    pub severity: Option<usize>,
+    /// Capture group of the problem code.
+    #[serde(default)]
+    pub code: Option<usize>,
+    /// Capture group of the problem message.
+    #[serde(default)]
+    pub message: Option<usize>,
+}
+
+/// How severe the problem is.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemSeverity {
+    /// An error.
+    #[default]
+    Error,
+    /// A warning.
+    Warning,
+    /// An informational message.
+    Info,
+    /// A hint.
+    Hint,
+}
+
+impl ProblemSeverity {
+    fn parse(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "error" | "fatal error" | "fatal" => Some(Self::Error),
+            "warning" | "warn" => Some(Self::Warning),
+            "info" | "information" | "note" => Some(Self::Info),
+            "hint" | "help" => Some(Self::Hint),
+            _ => None,
+        }
+    }
+}
+
+/// How to interpret file paths that were found in the output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FileLocation {
+    /// Paths are relative to the task's working directory.
+    #[default]
+    Relative,
+    /// Paths are absolute.
+    Absolute,
+    /// Absolute paths are used as is, the rest are relative to the task's working directory.
+    AutoDetect,
+}
+
+/// A zero-based position in a file, found in the task output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ProblemPosition {
+    /// Zero-based row.
+    pub row: u32,
+    /// Zero-based column.
+    pub column: u32,
+}
+
+/// A problem, found in the task output.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Problem {
+    /// Path to the file with the problem, resolved according to the matcher's [`FileLocation`].
+    pub path: PathBuf,
+    /// Where the problem starts.
+    pub start: ProblemPosition,
+    /// Where the problem ends, if the output reports that.
+    pub end: Option<ProblemPosition>,
+    /// Severity of the problem.
+    pub severity: ProblemSeverity,
+    /// Problem code, e.g. `E0308`.
+    pub code: Option<String>,
+    /// Problem description.
+    pub message: String,
+}
+
+impl ProblemMatcher {
+    /// Looks up a built-in matcher by its VS Code-compatible name.
+    pub fn builtin(name: &str) -> Option<Self> {
+        let matcher = match name {
+            "$rustc" => Self {
+                source: None,
+                severity: ProblemSeverity::Error,
+                file_location: FileLocation::Relative,
+                pattern: vec![
+                    ProblemPattern {
+                        regexp: r"^(warning|warn|error)(?:\[(.*?)\])?: (.*)$".to_string(),
+                        severity: Some(1),
+                        code: Some(2),
+                        message: Some(3),
+                        ..ProblemPattern::default()
+                    },
+                    ProblemPattern {
+                        regexp: r"^[\s\->=]*(.*?):(\d*):(\d*)\s*$".to_string(),
+                        file: Some(1),
+                        line: Some(2),
+                        column: Some(3),
+                        ..ProblemPattern::default()
+                    },
+                ],
+            },
+            "$tsc" => Self {
+                source: None,
+                severity: ProblemSeverity::Error,
+                file_location: FileLocation::Relative,
+                pattern: vec![ProblemPattern {
+                    regexp: r"^([^\s].*)[\(:](\d+)[,:](\d+)(?:\):\s+|\s+-\s+)(error|warning|info)\s+TS(\d+)\s*:\s*(.*)$".to_string(),
+                    file: Some(1),
+                    line: Some(2),
+                    column: Some(3),
+                    severity: Some(4),
+                    code: Some(5),
+                    message: Some(6),
+                    ..ProblemPattern::default()
+                }],
+            },
+            "$gcc" => Self {
+                source: None,
+                severity: ProblemSeverity::Error,
+                file_location: FileLocation::AutoDetect,
+                pattern: vec![ProblemPattern {
+                    regexp: r"^(.*?):(\d+):(\d*):?\s+(?:fatal\s+)?(warning|error):\s+(.*)$"
+                        .to_string(),
+                    file: Some(1),
+                    line: Some(2),
+                    column: Some(3),
+                    severity: Some(4),
+                    message: Some(5),
+                    ..ProblemPattern::default()
+                }],
+            },
+            _ => return None,
+        };
+        Some(matcher)
+    }
+
+    /// Finds all problems in the task output.
+    /// Relative paths are resolved against `cwd`, if it is given.
+    pub fn find_problems(&self, output: &str, cwd: Option<&Path>) -> Vec<Problem> {
+        let Some(regexes) = self
+            .pattern
+            .iter()
+            .map(|pattern| {
+                Regex::new(&pattern.regexp)
+                    .with_context(|| format!("compiling problem pattern {:?}", pattern.regexp))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()
+            .log_err()
+        else {
+            return Vec::new();
+        };
+        if regexes.is_empty() {
+            return Vec::new();
+        }
+
+        let lines = output.lines().collect::<Vec<_>>();
+        let mut problems = Vec::new();
+        let mut line_ix = 0;
+        while line_ix + regexes.len() <= lines.len() {
+            let mut problem = PartialProblem::default();
+            let matched = self
+                .pattern
+                .iter()
+                .zip(&regexes)
+                .zip(&lines[line_ix..])
+                .all(|((pattern, regex), line)| match regex.captures(line) {
+                    Some(captures) => {
+                        problem.extend(pattern, &captures);
+                        true
+                    }
+                    None => false,
+                });
+
+            match problem.into_problem(self, cwd).filter(|_| matched) {
+                Some(problem) => {
+                    problems.push(problem);
+                    line_ix += regexes.len();
+                }
+                None => line_ix += 1,
+            }
+        }
+        problems
+    }
+
+    fn resolve_path(&self, file: &str, cwd: Option<&Path>) -> PathBuf {
+        let path = PathBuf::from(file);
+        match (self.file_location, cwd) {
+            (FileLocation::Absolute, _) | (_, None) => path,
+            (FileLocation::AutoDetect, Some(_)) if path.is_absolute() => path,
+            (FileLocation::Relative | FileLocation::AutoDetect, Some(cwd)) => cwd.join(path),
+        }
+    }
+}
+
+#[derive(Default)]
+struct PartialProblem {
+    file: Option<String>,
+    line: Option<u32>,
+    column: Option<u32>,
+    end_line: Option<u32>,
+    end_column: Option<u32>,
+    severity: Option<ProblemSeverity>,
+    code: Option<String>,
+    message: Option<String>,
+}
+
+impl PartialProblem {
+    fn extend(&mut self, pattern: &ProblemPattern, captures: &Captures) {
+        let text = |group: Option<usize>| {
+            group
+                .and_then(|group| captures.get(group))
+                .map(|capture| capture.as_str().trim())
+                .filter(|text| !text.is_empty())
+        };
+        let number = |group: Option<usize>| text(group).and_then(|text| text.parse::<u32>().ok());
+
+        if let Some(file) = text(pattern.file) {
+            self.file = Some(file.to_string());
+        }
+        if let Some(line) = number(pattern.line) {
+            self.line = Some(line);
+        }
+        if let Some(column) = number(pattern.column) {
+            self.column = Some(column);
+        }
+        if let Some(end_line) = number(pattern.end_line) {
+            self.end_line = Some(end_line);
+        }
+        if let Some(end_column) = number(pattern.end_column) {
+            self.end_column = Some(end_column);
+        }
+        if let Some(severity) = text(pattern.severity).and_then(ProblemSeverity::parse) {
+            self.severity = Some(severity);
+        }
+        if let Some(code) = text(pattern.code) {
+            self.code = Some(code.to_string());
+        }
+        if let Some(message) = text(pattern.message) {
+            self.message = Some(message.to_string());
+        }
+    }
+
+    fn into_problem(self, matcher: &ProblemMatcher, cwd: Option<&Path>) -> Option<Problem> {
+        let file = self.file?;
+        let start = ProblemPosition {
+            row: self.line.unwrap_or(1).saturating_sub(1),
+            column: self.column.unwrap_or(1).saturating_sub(1),
+        };
+        let end = self.end_line.map(|end_line| ProblemPosition {
+            row: end_line.saturating_sub(1),
+            column: self
+                .end_column
+                .map_or(start.column, |column| column.saturating_sub(1)),
+        });
+        Some(Problem {
+            path: matcher.resolve_path(&file, cwd),
+            start,
+            end,
+            severity: self.severity.unwrap_or(matcher.severity),
+            code: self.code,
+            message: self.message.unwrap_or_default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rustc_matcher() {
+        let output = r#"   Compiling zed v0.1.0 (/work/zed)
+error[E0425]: cannot find value `x` in this scope
+ --> src/main.rs:2:13
+  |
+2 |     let y = x;
+  |             ^ not found in this scope
+
+warning: unused variable: `y`
+ --> src/main.rs:2:9
+  |
+error: could not compile `zed` (bin "zed") due to 1 previous error; 1 warning emitted
+"#;
+        let matcher = ProblemMatcher::builtin("$rustc").unwrap();
+        let problems = matcher.find_problems(output, Some(Path::new("/work/zed")));
+        assert_eq!(
+            problems,
+            vec![
+                Problem {
+                    path: PathBuf::from("/work/zed/src/main.rs"),
+                    start: ProblemPosition { row: 1, column: 12 },
+                    end: None,
+                    severity: ProblemSeverity::Error,
+                    code: Some("E0425".to_string()),
+                    message: "cannot find value `x` in this scope".to_string(),
+                },
+                Problem {
+                    path: PathBuf::from("/work/zed/src/main.rs"),
+                    start: ProblemPosition { row: 1, column: 8 },
+                    end: None,
+                    severity: ProblemSeverity::Warning,
+                    code: None,
+                    message: "unused variable: `y`".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_single_line_matchers() {
+        let tsc_output =
+            "src/index.ts(3,7): error TS2322: Type 'string' is not assignable to type 'number'.\n\
+            Found 1 error in src/index.ts:3\n";
+        let problems = ProblemMatcher::builtin("$tsc")
+            .unwrap()
+            .find_problems(tsc_output, None);
+        assert_eq!(
+            problems,
+            vec![Problem {
+                path: PathBuf::from("src/index.ts"),
+                start: ProblemPosition { row: 2, column: 6 },
+                end: None,
+                severity: ProblemSeverity::Error,
+                code: Some("2322".to_string()),
+                message: "Type 'string' is not assignable to type 'number'.".to_string(),
+            }]
+        );
+
+        let gcc_output = "/abs/main.c:10:5: warning: implicit declaration of function 'foo'\n\
+            lib.c:1:1: fatal error: bar.h: No such file or directory\n";
+        let problems = ProblemMatcher::builtin("$gcc")
+            .unwrap()
+            .find_problems(gcc_output, Some(Path::new("/work")));
+        assert_eq!(
+            problems
+                .iter()
+                .map(|problem| (problem.path.clone(), problem.severity))
+                .collect::<Vec<_>>(),
+            vec![
+                (PathBuf::from("/abs/main.c"), ProblemSeverity::Warning),
+                (PathBuf::from("/work/lib.c"), ProblemSeverity::Error),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_custom_matcher_definition() {
+        let definitions: Vec<ProblemMatcherDefinition> = serde_json_lenient::from_str(
+            r#"[
+                "$rustc",
+                "$unknown",
+                {
+                    "source": "lint",
+                    "severity": "warning",
+                    "pattern": [{ "regexp": "^(.+)#(\\d+)-(\\d+): (.*)$", "file": 1, "line": 2, "end_line": 3, "message": 4 }]
+                }
+            ]"#,
+        )
+        .unwrap();
+        let matchers = definitions
+            .iter()
+            .filter_map(|definition| definition.resolve())
+            .collect::<Vec<_>>();
+        assert_eq!(matchers.len(), 2);
+        assert_eq!(
+            matchers[1].find_problems("a.txt#3-5: too long", None),
+            vec![Problem {
+                path: PathBuf::from("a.txt"),
+                start: ProblemPosition { row: 2, column: 0 },
+                end: Some(ProblemPosition { row: 4, column: 0 }),
+                severity: ProblemSeverity::Warning,
+                code: None,
+                message: "too long".to_string(),
+            }]
+        );
+    }
+}
@@ -8,7 +8,8 @@ use sha2::{Digest, Sha256};
 use util::{truncate_and_remove_front, ResultExt};
 
 use crate::{
-    ResolvedTask, SpawnInTerminal, TaskContext, TaskId, VariableName, ZED_VARIABLE_NAME_PREFIX,
+    ProblemMatcherDefinition, ResolvedTask, SpawnInTerminal, TaskContext, TaskId, VariableName,
+    ZED_VARIABLE_NAME_PREFIX,
 };
 
 /// A template definition of a Zed task to run.
@@ -48,6 +49,11 @@ pub struct TaskTemplate {
     /// Represents the tags which this template attaches to. Adding this removes this task from other UI.
     #[serde(default)]
     pub tags: Vec<String>,
+
+    /// Problem matchers to turn the task output into diagnostics: either names of the built-in matchers
+    /// (`$rustc`, `$tsc`, `$gcc`) or custom matcher definitions.
+    #[serde(default)]
+    pub problem_matchers: Vec<ProblemMatcherDefinition>,
 }
 
 /// What to do with the terminal pane and tab, after the command was started.
@@ -191,6 +197,11 @@ impl TaskTemplate {
                 use_new_terminal: self.use_new_terminal,
                 allow_concurrent_runs: self.allow_concurrent_runs,
                 reveal: self.reveal,
+                problem_matchers: self
+                    .problem_matchers
+                    .iter()
+                    .filter_map(|definition| definition.resolve())
+                    .collect(),
             }),
         })
     }
@@ -3,7 +3,10 @@ use collections::HashMap;
 use serde::Deserialize;
 use util::ResultExt;
 
-use crate::{TaskTemplate, TaskTemplates, VariableName};
+use crate::{
+    FileLocation, ProblemMatcher, ProblemMatcherDefinition, ProblemPattern, ProblemSeverity,
+    TaskTemplate, TaskTemplates, VariableName,
+};
 
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -22,6 +25,113 @@ struct VsCodeTaskDefinition {
     #[serde(flatten)]
     other_attributes: HashMap<String, serde_json_lenient::Value>,
     options: Option<TaskOptions>,
+    problem_matcher: Option<OneOrMany<VsCodeProblemMatcher>>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+impl<T> OneOrMany<T> {
+    fn into_vec(self) -> Vec<T> {
+        match self {
+            Self::One(item) => vec![item],
+            Self::Many(items) => items,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeProblemMatcher {
+    Named(String),
+    Custom(VsCodeProblemMatcherDefinition),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct VsCodeProblemMatcherDefinition {
+    base: Option<String>,
+    owner: Option<String>,
+    source: Option<String>,
+    severity: Option<ProblemSeverity>,
+    /// Either a location kind, or a location kind followed by the base directory.
+    file_location: Option<OneOrMany<String>>,
+    pattern: Option<OneOrMany<VsCodeProblemPattern>>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct VsCodeProblemPattern {
+    regexp: String,
+    file: Option<usize>,
+    line: Option<usize>,
+    column: Option<usize>,
+    end_line: Option<usize>,
+    end_column: Option<usize>,
+    severity: Option<usize>,
+    code: Option<usize>,
+    message: Option<usize>,
+}
+
+impl VsCodeProblemMatcher {
+    /// Converts the matcher into Zed's format, skipping the matchers that Zed does not know about,
+    /// such as the background (`-watch`) ones.
+    fn to_zed_format(self) -> Option<ProblemMatcherDefinition> {
+        match self {
+            Self::Named(name) => {
+                ProblemMatcher::builtin(&name).map(|_| ProblemMatcherDefinition::Builtin(name))
+            }
+            Self::Custom(definition) => {
+                let mut matcher = match &definition.base {
+                    Some(base) => ProblemMatcher::builtin(base)?,
+                    None => ProblemMatcher {
+                        source: None,
+                        severity: ProblemSeverity::default(),
+                        file_location: FileLocation::default(),
+                        pattern: Vec::new(),
+                    },
+                };
+                if let Some(source) = definition.source.or(definition.owner) {
+                    matcher.source = Some(source);
+                }
+                if let Some(severity) = definition.severity {
+                    matcher.severity = severity;
+                }
+                let file_location = definition
+                    .file_location
+                    .map(OneOrMany::into_vec)
+                    .and_then(|location| location.into_iter().next());
+                match file_location.as_deref() {
+                    Some("absolute") => matcher.file_location = FileLocation::Absolute,
+                    Some("relative") => matcher.file_location = FileLocation::Relative,
+                    Some("autoDetect") => matcher.file_location = FileLocation::AutoDetect,
+                    _ => {}
+                }
+                if let Some(pattern) = definition.pattern {
+                    matcher.pattern = pattern
+                        .into_vec()
+                        .into_iter()
+                        .map(|pattern| ProblemPattern {
+                            regexp: pattern.regexp,
+                            file: pattern.file,
+                            line: pattern.line,
+                            column: pattern.column,
+                            end_line: pattern.end_line,
+                            end_column: pattern.end_column,
+                            severity: pattern.severity,
+                            code: pattern.code,
+                            message: pattern.message,
+                        })
+                        .collect();
+                }
+                (!matcher.pattern.is_empty()).then_some(ProblemMatcherDefinition::Custom(matcher))
+            }
+        }
+    }
 }
 
 #[derive(Clone, Deserialize, PartialEq, Debug)]
@@ -104,10 +214,18 @@ impl VsCodeTaskDefinition {
         // Per VSC docs, only `command`, `args` and `options` support variable substitution.
         let command = replacer.replace(&command);
         let args = args.into_iter().map(|arg| replacer.replace(&arg)).collect();
+        let problem_matchers = self
+            .problem_matcher
+            .map(OneOrMany::into_vec)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(VsCodeProblemMatcher::to_zed_format)
+            .collect();
         let mut ret = TaskTemplate {
             label: self.label,
             command,
             args,
+            problem_matchers,
             ..Default::default()
         };
         if let Some(options) = self.options {
@@ -155,7 +273,7 @@ mod tests {
 
     use crate::{
         vscode_format::{Command, VsCodeTaskDefinition},
-        TaskTemplate, TaskTemplates, VsCodeTaskFile,
+        ProblemMatcher, ProblemMatcherDefinition, TaskTemplate, TaskTemplates, VsCodeTaskFile,
     };
 
     use super::EnvVariableReplacer;
@@ -164,10 +282,12 @@ mod tests {
         assert_eq!(
             VsCodeTaskDefinition {
                 other_attributes: Default::default(),
+                problem_matcher: None,
                 ..lhs
             },
             VsCodeTaskDefinition {
                 other_attributes: Default::default(),
+                problem_matcher: None,
                 ..rhs
             },
         );
@@ -212,6 +332,7 @@ mod tests {
                 }),
                 other_attributes: Default::default(),
                 options: None,
+                problem_matcher: None,
             },
             VsCodeTaskDefinition {
                 label: "tsc: watch ./src".to_string(),
@@ -226,6 +347,7 @@ mod tests {
                 }),
                 other_attributes: Default::default(),
                 options: None,
+                problem_matcher: None,
             },
             VsCodeTaskDefinition {
                 label: "npm: build:compiler".to_string(),
@@ -234,6 +356,7 @@ mod tests {
                 }),
                 other_attributes: Default::default(),
                 options: None,
+                problem_matcher: None,
             },
             VsCodeTaskDefinition {
                 label: "npm: build:tests".to_string(),
@@ -242,6 +365,7 @@ mod tests {
                 }),
                 other_attributes: Default::default(),
                 options: None,
+                problem_matcher: None,
             },
         ];
 
@@ -257,6 +381,7 @@ mod tests {
                 label: "gulp: tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matchers: vec![ProblemMatcherDefinition::Builtin("$tsc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
@@ -300,6 +425,7 @@ mod tests {
                     script: "watch".to_string(),
                 }),
                 options: None,
+                problem_matcher: None,
                 other_attributes: Default::default(),
             },
             VsCodeTaskDefinition {
@@ -308,6 +434,7 @@ mod tests {
                     script: "build".to_string(),
                 }),
                 options: None,
+                problem_matcher: None,
                 other_attributes: Default::default(),
             },
             VsCodeTaskDefinition {
@@ -317,6 +444,7 @@ mod tests {
                     args: Default::default(),
                 }),
                 options: None,
+                problem_matcher: None,
                 other_attributes: Default::default(),
             },
             VsCodeTaskDefinition {
@@ -326,6 +454,7 @@ mod tests {
                     args: Default::default(),
                 }),
                 options: None,
+                problem_matcher: None,
                 other_attributes: Default::default(),
             },
             VsCodeTaskDefinition {
@@ -334,18 +463,21 @@ mod tests {
                     script: "pretest".to_string(),
                 }),
                 options: None,
+                problem_matcher: None,
                 other_attributes: Default::default(),
             },
             VsCodeTaskDefinition {
                 label: "Build Server and Extension".to_string(),
                 command: None,
                 options: None,
+                problem_matcher: None,
                 other_attributes: Default::default(),
             },
             VsCodeTaskDefinition {
                 label: "Build Server (Release) and Extension".to_string(),
                 command: None,
                 options: None,
+                problem_matcher: None,
                 other_attributes: Default::default(),
             },
         ];
@@ -366,22 +498,30 @@ mod tests {
                 label: "Build Extension".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build".to_string()],
+                problem_matchers: vec![ProblemMatcherDefinition::Custom(
+                    ProblemMatcher::builtin("$tsc").unwrap(),
+                )],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server".to_string(),
                 command: "cargo build --package rust-analyzer".to_string(),
+                problem_matchers: vec![ProblemMatcherDefinition::Builtin("$rustc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server (Release)".to_string(),
                 command: "cargo build --release --package rust-analyzer".to_string(),
+                problem_matchers: vec![ProblemMatcherDefinition::Builtin("$rustc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Pretest".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "pretest".to_string()],
+                problem_matchers: vec![ProblemMatcherDefinition::Custom(
+                    ProblemMatcher::builtin("$tsc").unwrap(),
+                )],
                 ..Default::default()
             },
         ];
@@ -124,6 +124,12 @@ impl PtyProcessInfo {
         Some(info)
     }
 
+    /// Whether the process the terminal was started with, usually a shell, is the foreground
+    /// process, rather than a program it started.
+    pub fn is_shell_in_foreground(&self) -> bool {
+        self.pid_getter.pid() == Some(Pid::from_u32(self.pid_getter.fallback_pid))
+    }
+
     /// Updates the cached process info, returns whether the Zed-relevant info has changed
     pub fn has_changed(&mut self) -> bool {
         let current = self.load();
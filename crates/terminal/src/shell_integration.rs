@@ -0,0 +1,1066 @@
+//! Shell integration support: tracking of prompt, command and output regions
+//! reported by the shell through OSC 133 (FinalTerm) and OSC 633 (VS Code) sequences.
+//!
+//! Alacritty ignores these sequences, so the raw PTY output is scanned before it reaches
+//! the Alacritty parser. After each mark, the reader injects a notification that Alacritty
+//! reports as soon as it parses it, and holds back the rest of the output until the terminal
+//! has handled the mark. This way, marks are positioned using the terminal cursor exactly
+//! as it was when the mark was written, no matter how much output followed it.
+
+use std::io::{self, Read};
+
+use alacritty_terminal::{
+    grid::Dimensions,
+    index::{Column, Line, Point as AlacPoint},
+    term::TermMode,
+    Term,
+};
+use collections::VecDeque;
+use futures::channel::mpsc::{UnboundedReceiver, UnboundedSender};
+
+/// OSC payloads longer than this are not shell integration sequences and are skipped.
+const MAX_OSC_LENGTH: usize = 4096;
+/// How many commands are remembered per terminal.
+const MAX_TRACKED_COMMANDS: usize = 1024;
+/// How far the scrollback may grow past its limit between two calls to
+/// [`ShellCommands::trim_scrollback`]. Alacritty does not report the lines it evicts, so the
+/// lines are evicted by the trimming instead, which counts them.
+pub const SCROLLBACK_SLACK_LINES: usize = 10_000;
+
+/// The text of the clipboard store sequence, that the reader injects after every mark.
+pub(crate) const MARK_PARSED: &str = "zed-shell-integration-mark";
+/// An OSC 52 clipboard store of [`MARK_PARSED`], which Alacritty reports with an event as soon
+/// as it is parsed.
+const MARK_PARSED_SEQUENCE: &[u8] = b"\x1b]52;c;emVkLXNoZWxsLWludGVncmF0aW9uLW1hcms=\x07";
+
+/// A shell integration mark, as reported by the shell.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShellIntegrationEvent {
+    /// `A`: the shell started drawing the prompt.
+    PromptStart,
+    /// `B`: the prompt is drawn, user input starts.
+    CommandStart,
+    /// `C`: the command was submitted, its output starts.
+    CommandExecuted,
+    /// `D`: the command finished, optionally with its exit code.
+    CommandFinished { exit_code: Option<i32> },
+    /// `633;E`: the exact command line that is about to be executed.
+    CommandLine(String),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum ParserState {
+    #[default]
+    Ground,
+    Escape,
+    Osc,
+    OscEscape,
+}
+
+/// A minimal escape sequence scanner, that only recognizes OSC 133 and OSC 633 sequences
+/// and lets everything else through untouched.
+#[derive(Default)]
+pub struct ShellIntegrationParser {
+    state: ParserState,
+    osc: Vec<u8>,
+    osc_overflowed: bool,
+}
+
+impl ShellIntegrationParser {
+    pub fn advance(&mut self, mut bytes: &[u8], mut on_event: impl FnMut(ShellIntegrationEvent)) {
+        loop {
+            let (scanned, event) = self.advance_until_event(bytes);
+            bytes = &bytes[scanned..];
+            match event {
+                Some(event) => on_event(event),
+                None => break,
+            }
+        }
+    }
+
+    /// Scans the bytes up to the end of the first shell integration sequence among them.
+    /// Returns how many bytes were scanned, and the event of the sequence, if there was one.
+    pub fn advance_until_event(&mut self, bytes: &[u8]) -> (usize, Option<ShellIntegrationEvent>) {
+        for (ix, &byte) in bytes.iter().enumerate() {
+            if let Some(event) = self.advance_byte(byte) {
+                return (ix + 1, Some(event));
+            }
+        }
+        (bytes.len(), None)
+    }
+
+    fn advance_byte(&mut self, byte: u8) -> Option<ShellIntegrationEvent> {
+        match self.state {
+            ParserState::Ground => {
+                if byte == 0x1b {
+                    self.state = ParserState::Escape;
+                }
+            }
+            ParserState::Escape => self.advance_escape(byte),
+            ParserState::Osc => match byte {
+                // BEL terminator
+                0x07 => return self.finish_osc(),
+                0x1b => self.state = ParserState::OscEscape,
+                // CAN and SUB abort the sequence
+                0x18 | 0x1a => self.state = ParserState::Ground,
+                _ => self.push_osc_byte(byte),
+            },
+            ParserState::OscEscape => {
+                if byte == b'\\' {
+                    return self.finish_osc();
+                } else {
+                    self.advance_escape(byte);
+                }
+            }
+        }
+        None
+    }
+
+    fn advance_escape(&mut self, byte: u8) {
+        match byte {
+            b']' => {
+                self.osc.clear();
+                self.osc_overflowed = false;
+                self.state = ParserState::Osc;
+            }
+            0x1b => self.state = ParserState::Escape,
+            _ => self.state = ParserState::Ground,
+        }
+    }
+
+    fn push_osc_byte(&mut self, byte: u8) {
+        if self.osc.len() < MAX_OSC_LENGTH {
+            self.osc.push(byte);
+        } else {
+            self.osc_overflowed = true;
+        }
+    }
+
+    fn finish_osc(&mut self) -> Option<ShellIntegrationEvent> {
+        self.state = ParserState::Ground;
+        if self.osc_overflowed {
+            return None;
+        }
+        parse_osc(&self.osc)
+    }
+}
+
+fn parse_osc(payload: &[u8]) -> Option<ShellIntegrationEvent> {
+    let payload = std::str::from_utf8(payload).ok()?;
+    let mut params = payload.split(';');
+    let is_vscode = match params.next()? {
+        "133" => false,
+        "633" => true,
+        _ => return None,
+    };
+
+    match params.next()? {
+        "A" => Some(ShellIntegrationEvent::PromptStart),
+        "B" => Some(ShellIntegrationEvent::CommandStart),
+        "C" => Some(ShellIntegrationEvent::CommandExecuted),
+        "D" => Some(ShellIntegrationEvent::CommandFinished {
+            exit_code: params.next().and_then(|code| code.trim().parse().ok()),
+        }),
+        "E" if is_vscode => Some(ShellIntegrationEvent::CommandLine(unescape_vscode_value(
+            params.next().unwrap_or_default(),
+        ))),
+        _ => None,
+    }
+}
+
+/// VS Code escapes `\` as `\\` and any byte below 0x20 plus `;` as `\xAB`.
+fn unescape_vscode_value(value: &str) -> String {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut input = value.as_bytes();
+    while let Some((&byte, rest)) = input.split_first() {
+        input = rest;
+        if byte != b'\\' {
+            bytes.push(byte);
+            continue;
+        }
+        match input {
+            [b'\\', rest @ ..] => {
+                bytes.push(b'\\');
+                input = rest;
+            }
+            [b'x', high, low, rest @ ..] => {
+                match std::str::from_utf8(&[*high, *low])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                {
+                    Some(decoded) => {
+                        bytes.push(decoded);
+                        input = rest;
+                    }
+                    None => bytes.push(b'\\'),
+                }
+            }
+            _ => bytes.push(b'\\'),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// A reader that reports shell integration sequences found in the data passing through it.
+///
+/// Every mark is followed by [`MARK_PARSED_SEQUENCE`], after which the reader is paused:
+/// it returns [`io::ErrorKind::WouldBlock`] until [`ShellIntegrationReader::resume`] is called,
+/// once the terminal has handled the mark.
+pub struct ShellIntegrationReader<R> {
+    inner: R,
+    parser: ShellIntegrationParser,
+    events_tx: UnboundedSender<ShellIntegrationEvent>,
+    /// Bytes read past a mark, that were not scanned yet.
+    unscanned: Vec<u8>,
+    /// Scanned bytes that were not returned yet.
+    pending: Vec<u8>,
+    pending_ix: usize,
+    /// The mark to report once the pending bytes are returned.
+    pending_event: Option<ShellIntegrationEvent>,
+    paused: bool,
+}
+
+impl<R> ShellIntegrationReader<R> {
+    pub fn new(inner: R, events_tx: UnboundedSender<ShellIntegrationEvent>) -> Self {
+        Self {
+            inner,
+            parser: ShellIntegrationParser::default(),
+            events_tx,
+            unscanned: Vec::new(),
+            pending: Vec::new(),
+            pending_ix: 0,
+            pending_event: None,
+            paused: false,
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Lets the reader continue past the last mark.
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    fn push_mark(&mut self, event: ShellIntegrationEvent) {
+        self.pending.extend_from_slice(MARK_PARSED_SEQUENCE);
+        self.pending_event = Some(event);
+    }
+}
+
+impl<R: Read> Read for ShellIntegrationReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.paused {
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+
+        if self.pending_ix == self.pending.len() {
+            self.pending.clear();
+            self.pending_ix = 0;
+
+            if self.unscanned.is_empty() {
+                // Nothing is held back, so read straight into the buffer.
+                let read = self.inner.read(buf)?;
+                let (scanned, event) = self.parser.advance_until_event(&buf[..read]);
+                let Some(event) = event else {
+                    return Ok(read);
+                };
+                self.unscanned.extend_from_slice(&buf[scanned..read]);
+                self.push_mark(event);
+                return Ok(scanned);
+            }
+
+            let (scanned, event) = self.parser.advance_until_event(&self.unscanned);
+            self.pending.extend(self.unscanned.drain(..scanned));
+            if let Some(event) = event {
+                self.push_mark(event);
+            }
+        }
+
+        let len = buf.len().min(self.pending.len() - self.pending_ix);
+        buf[..len].copy_from_slice(&self.pending[self.pending_ix..self.pending_ix + len]);
+        self.pending_ix += len;
+        if self.pending_ix == self.pending.len() {
+            if let Some(event) = self.pending_event.take() {
+                self.events_tx.unbounded_send(event).ok();
+                self.paused = true;
+            }
+        }
+        Ok(len)
+    }
+}
+
+/// The marks reported by the shell integration of a terminal, and the way to let its PTY reader
+/// continue past each of them.
+///
+/// Only created on Unix, where the PTY output is read through [`ShellIntegrationPty`].
+/// Elsewhere terminals have no shell integration, and don't track commands.
+pub struct ShellIntegration {
+    pub events_rx: UnboundedReceiver<ShellIntegrationEvent>,
+    pub resume: ShellIntegrationResume,
+}
+
+/// Lets the PTY reader continue past a mark, once the terminal has handled it.
+pub struct ShellIntegrationResume {
+    #[cfg(unix)]
+    stream: std::os::unix::net::UnixStream,
+}
+
+impl ShellIntegrationResume {
+    pub fn resume(&self) {
+        #[cfg(unix)]
+        io::Write::write(&mut &self.stream, &[0]).ok();
+    }
+}
+
+#[cfg(unix)]
+pub use pty::ShellIntegrationPty;
+
+#[cfg(unix)]
+mod pty {
+    use std::{
+        fs::File,
+        io::{self, Read},
+        os::{
+            fd::{AsRawFd, BorrowedFd, RawFd},
+            unix::net::UnixStream,
+        },
+        sync::Arc,
+    };
+
+    use alacritty_terminal::{
+        event::{OnResize, WindowSize},
+        tty::{ChildEvent, EventedPty, EventedReadWrite, Pty},
+    };
+    use futures::channel::mpsc::unbounded;
+    use polling::{Event, PollMode, Poller};
+    use util::ResultExt;
+
+    use super::{ShellIntegration, ShellIntegrationReader, ShellIntegrationResume};
+
+    /// The key Alacritty polls the PTY with. Resume notifications are polled with the same key,
+    /// so that they make the event loop read from the PTY again.
+    const PTY_READ_WRITE_TOKEN: usize = 0;
+
+    /// A PTY that scans everything the shell writes for shell integration sequences.
+    ///
+    /// Reads go through a duplicate of the PTY file descriptor, while polling, writes
+    /// and child process tracking are delegated to the original PTY. While the reader
+    /// is paused after a mark, the PTY's registration with the poller drops the read
+    /// interest, and the event loop is woken up by the [`ShellIntegrationResume`] instead.
+    pub struct ShellIntegrationPty {
+        pty: Pty,
+        reader: PtyReader,
+        resume_rx: UnixStream,
+    }
+
+    impl ShellIntegrationPty {
+        pub fn new(pty: Pty) -> io::Result<(Self, ShellIntegration)> {
+            let (events_tx, events_rx) = unbounded();
+            let reader = PtyReader {
+                reader: ShellIntegrationReader::new(pty.file().try_clone()?, events_tx),
+                pty_fd: pty.file().as_raw_fd(),
+                registration: None,
+            };
+            let (resume_tx, resume_rx) = UnixStream::pair()?;
+            resume_tx.set_nonblocking(true)?;
+            resume_rx.set_nonblocking(true)?;
+            let shell_integration = ShellIntegration {
+                events_rx,
+                resume: ShellIntegrationResume { stream: resume_tx },
+            };
+            Ok((
+                Self {
+                    pty,
+                    reader,
+                    resume_rx,
+                },
+                shell_integration,
+            ))
+        }
+    }
+
+    /// Reads the PTY output through a [`ShellIntegrationReader`], and keeps the PTY from being
+    /// polled for reading while that reader is paused. The PTY stays readable while its output is
+    /// held back, so polling it would wake up the event loop over and over.
+    pub struct PtyReader {
+        reader: ShellIntegrationReader<File>,
+        /// The file descriptor of the PTY, as registered with the poller.
+        pty_fd: RawFd,
+        registration: Option<Registration>,
+    }
+
+    struct Registration {
+        poller: Arc<Poller>,
+        /// The interest Alacritty last registered the PTY with.
+        interest: Event,
+        mode: PollMode,
+        /// Whether the PTY is currently polled for reading.
+        readable: bool,
+    }
+
+    impl PtyReader {
+        /// Remembers the interest Alacritty registers the PTY with, returning the interest to
+        /// actually register it with.
+        fn register(&mut self, poller: &Arc<Poller>, interest: Event, mode: PollMode) -> Event {
+            let mut pty_interest = interest;
+            pty_interest.readable &= !self.reader.is_paused();
+            self.registration = Some(Registration {
+                poller: poller.clone(),
+                interest,
+                mode,
+                readable: pty_interest.readable,
+            });
+            pty_interest
+        }
+
+        /// Adds or removes the read interest of the PTY's registration, according to whether
+        /// the reader is paused.
+        fn update_registration(&mut self) -> io::Result<()> {
+            let Some(registration) = &mut self.registration else {
+                return Ok(());
+            };
+            let readable = registration.interest.readable && !self.reader.is_paused();
+            if readable == registration.readable {
+                return Ok(());
+            }
+
+            let mut interest = registration.interest;
+            interest.key = PTY_READ_WRITE_TOKEN;
+            interest.readable = readable;
+            // SAFETY: the descriptor belongs to the PTY, which outlives this reader's use, as
+            // both are only reached through the same `ShellIntegrationPty`.
+            let pty_fd = unsafe { BorrowedFd::borrow_raw(self.pty_fd) };
+            registration
+                .poller
+                .modify_with_mode(pty_fd, interest, registration.mode)?;
+            registration.readable = readable;
+            Ok(())
+        }
+    }
+
+    impl Read for PtyReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let result = self.reader.read(buf);
+            if self.reader.is_paused() {
+                self.update_registration().log_err();
+            }
+            result
+        }
+    }
+
+    impl EventedReadWrite for ShellIntegrationPty {
+        type Reader = PtyReader;
+        type Writer = File;
+
+        unsafe fn register(
+            &mut self,
+            poll: &Arc<Poller>,
+            interest: Event,
+            poll_opts: PollMode,
+        ) -> io::Result<()> {
+            let interest = self.reader.register(poll, interest, poll_opts);
+            self.pty.register(poll, interest, poll_opts)?;
+            poll.add_with_mode(
+                &self.resume_rx,
+                Event::readable(PTY_READ_WRITE_TOKEN),
+                PollMode::Level,
+            )
+        }
+
+        fn reregister(
+            &mut self,
+            poll: &Arc<Poller>,
+            interest: Event,
+            poll_opts: PollMode,
+        ) -> io::Result<()> {
+            let interest = self.reader.register(poll, interest, poll_opts);
+            self.pty.reregister(poll, interest, poll_opts)?;
+            poll.modify_with_mode(
+                &self.resume_rx,
+                Event::readable(PTY_READ_WRITE_TOKEN),
+                PollMode::Level,
+            )
+        }
+
+        fn deregister(&mut self, poll: &Arc<Poller>) -> io::Result<()> {
+            self.reader.registration = None;
+            self.pty.deregister(poll)?;
+            poll.delete(&self.resume_rx)
+        }
+
+        fn reader(&mut self) -> &mut Self::Reader {
+            let mut resumed = false;
+            let mut buf = [0; 64];
+            while let Ok(1..) = self.resume_rx.read(&mut buf) {
+                resumed = true;
+            }
+            if resumed {
+                self.reader.reader.resume();
+                self.reader.update_registration().log_err();
+            }
+            &mut self.reader
+        }
+
+        fn writer(&mut self) -> &mut Self::Writer {
+            self.pty.writer()
+        }
+    }
+
+    impl EventedPty for ShellIntegrationPty {
+        fn next_child_event(&mut self) -> Option<ChildEvent> {
+            self.pty.next_child_event()
+        }
+    }
+
+    impl OnResize for ShellIntegrationPty {
+        fn on_resize(&mut self, window_size: WindowSize) {
+            self.pty.on_resize(window_size)
+        }
+    }
+}
+
+/// A point in the terminal grid, that stays stable while the output scrolls into history.
+/// The line is counted from the first line the terminal ever had, including the lines
+/// evicted from the scrollback since, see [`ShellCommands::absolute_line`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AbsolutePoint {
+    pub line: usize,
+    pub column: Column,
+}
+
+/// A command, delimited by the shell integration marks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShellCommand {
+    /// Where the prompt of this command starts.
+    pub prompt_start: AbsolutePoint,
+    /// Where the user input starts, after the prompt.
+    pub command_start: Option<AbsolutePoint>,
+    /// The first line of the command output.
+    pub output_start: Option<usize>,
+    /// The line after the last line of the command output.
+    pub output_end: Option<usize>,
+    /// The command line, as reported by the shell or read from the terminal.
+    pub command_line: Option<String>,
+    pub exit_code: Option<i32>,
+}
+
+impl ShellCommand {
+    pub fn is_running(&self) -> bool {
+        self.output_start.is_some() && self.output_end.is_none()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.output_end.is_some()
+    }
+
+    pub fn failed(&self) -> bool {
+        self.exit_code.is_some_and(|code| code != 0)
+    }
+}
+
+/// A command status marker, to be rendered next to the visible prompt lines.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CommandMarker {
+    /// Line of the prompt, relative to the top of the viewport.
+    pub display_line: usize,
+    pub exit_code: i32,
+}
+
+/// Commands seen in the terminal, in the order they were run.
+pub struct ShellCommands {
+    commands: VecDeque<ShellCommand>,
+    /// The scrollback size, past which lines are evicted by [`Self::trim_scrollback`].
+    history_limit: usize,
+    /// The scrollback size after the last trimming.
+    history_size: usize,
+    /// How many lines were evicted from the scrollback so far.
+    evicted_lines: usize,
+}
+
+impl ShellCommands {
+    /// Creates the command list of a terminal, whose scrollback is configured to hold
+    /// `history_limit` plus [`SCROLLBACK_SLACK_LINES`] lines.
+    pub fn new(history_limit: usize) -> Self {
+        Self {
+            commands: VecDeque::new(),
+            history_limit,
+            history_size: 0,
+            evicted_lines: 0,
+        }
+    }
+
+    /// Whether the shell has reported any marks, i.e. has shell integration enabled.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &ShellCommand> {
+        self.commands.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+
+    /// The command that is currently executing, if any.
+    pub fn running(&self) -> Option<&ShellCommand> {
+        self.commands.back().filter(|command| command.is_running())
+    }
+
+    /// Whether the shell has drawn its prompt and is waiting for the user to submit a command.
+    pub fn is_at_prompt(&self) -> bool {
+        self.commands.back().map_or(false, |command| {
+            command.command_start.is_some() && command.output_start.is_none()
+        })
+    }
+
+    /// The most recent command that ran to completion.
+    pub fn last_finished(&self) -> Option<&ShellCommand> {
+        self.commands
+            .iter()
+            .rev()
+            .find(|command| command.is_finished())
+    }
+
+    /// The most recent command that has a known command line.
+    pub fn last_command_line(&self) -> Option<&str> {
+        self.commands
+            .iter()
+            .rev()
+            .filter(|command| command.output_start.is_some())
+            .find_map(|command| command.command_line.as_deref())
+            .filter(|command_line| !command_line.is_empty())
+    }
+
+    /// Evicts the scrollback lines past the history limit, keeping count of them so that
+    /// absolute lines remain stable. Must be called more often than the slack lines fill up.
+    pub fn trim_scrollback<T>(&mut self, term: &mut Term<T>) {
+        // The alternate screen has no scrollback, the primary one is left untouched meanwhile.
+        if term.mode().contains(TermMode::ALT_SCREEN) {
+            return;
+        }
+        let grid = term.grid_mut();
+        let history_size = grid.history_size();
+        // The scrollback was cleared.
+        self.evicted_lines += self.history_size.saturating_sub(history_size);
+
+        if history_size >= self.history_limit + SCROLLBACK_SLACK_LINES {
+            // Alacritty may have evicted lines itself, so the commands can't be located anymore.
+            self.commands.clear();
+        }
+        if history_size > self.history_limit {
+            self.evicted_lines += history_size - self.history_limit;
+            grid.update_history(self.history_limit);
+            grid.update_history(self.history_limit + SCROLLBACK_SLACK_LINES);
+        }
+        self.history_size = grid.history_size();
+
+        while self.commands.len() > 1 && self.commands[1].prompt_start.line <= self.evicted_lines {
+            self.commands.pop_front();
+        }
+    }
+
+    /// Returns the absolute line of a line in the terminal grid.
+    pub fn absolute_line<T>(&self, term: &Term<T>, line: Line) -> usize {
+        (self.evicted_lines as i64 + term.grid().history_size() as i64 + line.0 as i64).max(0)
+            as usize
+    }
+
+    /// Returns the grid line of an absolute line, clamped to the lines still in the scrollback.
+    pub fn grid_line<T>(&self, term: &Term<T>, line: usize) -> Line {
+        let line = line as i64 - self.evicted_lines as i64 - term.grid().history_size() as i64;
+        Line(line.clamp(
+            term.topmost_line().0 as i64,
+            term.bottommost_line().0 as i64,
+        ) as i32)
+    }
+
+    fn point_from_grid<T>(&self, term: &Term<T>, point: AlacPoint) -> AbsolutePoint {
+        AbsolutePoint {
+            line: self.absolute_line(term, point.line),
+            column: point.column,
+        }
+    }
+
+    fn point_to_grid<T>(&self, term: &Term<T>, point: AbsolutePoint) -> AlacPoint {
+        AlacPoint::new(self.grid_line(term, point.line), point.column)
+    }
+
+    /// Applies the event to the command list, using the current cursor position as its location.
+    ///
+    /// The terminal is expected to have parsed the output exactly up to the mark, see
+    /// [`ShellIntegrationReader`], and [`Self::trim_scrollback`] to be called before.
+    pub fn handle_event<T>(&mut self, event: ShellIntegrationEvent, term: &Term<T>) {
+        let cursor = self.point_from_grid(term, term.grid().cursor.point);
+        match event {
+            ShellIntegrationEvent::PromptStart => {
+                if self.commands.len() == MAX_TRACKED_COMMANDS {
+                    self.commands.pop_front();
+                }
+                self.commands.push_back(ShellCommand {
+                    prompt_start: AbsolutePoint {
+                        line: cursor.line,
+                        column: Column(0),
+                    },
+                    command_start: None,
+                    output_start: None,
+                    output_end: None,
+                    command_line: None,
+                    exit_code: None,
+                });
+            }
+            ShellIntegrationEvent::CommandStart => {
+                if let Some(command) = self.commands.back_mut() {
+                    command.command_start = Some(cursor);
+                }
+            }
+            ShellIntegrationEvent::CommandLine(command_line) => {
+                if let Some(command) = self.commands.back_mut() {
+                    command.command_line = Some(command_line);
+                }
+            }
+            ShellIntegrationEvent::CommandExecuted => {
+                let typed_command = self
+                    .commands
+                    .back()
+                    .filter(|command| command.command_line.is_none())
+                    .and_then(|command| command.command_start)
+                    .and_then(|start| self.command_text(term, start, cursor.line));
+                if let Some(command) = self.commands.back_mut() {
+                    command.output_start = Some(cursor.line);
+                    if command.command_line.is_none() {
+                        command.command_line = typed_command;
+                    }
+                }
+            }
+            ShellIntegrationEvent::CommandFinished { exit_code } => {
+                if let Some(command) = self
+                    .commands
+                    .back_mut()
+                    .filter(|command| command.output_end.is_none())
+                {
+                    let output_start = *command.output_start.get_or_insert(cursor.line);
+                    command.output_end = Some(cursor.line.max(output_start));
+                    command.exit_code = exit_code;
+                }
+            }
+        }
+    }
+
+    /// Returns the start of the closest prompt above or below the given line.
+    pub fn prompt_near(&self, line: usize, direction_up: bool) -> Option<AbsolutePoint> {
+        let mut prompts = self.commands.iter().map(|command| command.prompt_start);
+        if direction_up {
+            prompts.rev().find(|prompt| prompt.line < line)
+        } else {
+            prompts.find(|prompt| prompt.line > line)
+        }
+    }
+
+    /// Returns markers for every finished command, whose prompt is currently visible.
+    pub fn visible_markers<T>(&self, term: &Term<T>) -> Vec<CommandMarker> {
+        let top_line = self.absolute_line(term, Line(-(term.grid().display_offset() as i32)));
+        let screen_lines = term.screen_lines();
+        self.commands
+            .iter()
+            .filter_map(|command| {
+                let exit_code = command.exit_code?;
+                let display_line = command.prompt_start.line.checked_sub(top_line)?;
+                (display_line < screen_lines).then_some(CommandMarker {
+                    display_line,
+                    exit_code,
+                })
+            })
+            .collect()
+    }
+
+    /// Reads the command that was typed after the prompt, from the command start up to the line
+    /// the output starts at.
+    fn command_text<T>(
+        &self,
+        term: &Term<T>,
+        start: AbsolutePoint,
+        output_start: usize,
+    ) -> Option<String> {
+        let last_line = output_start.checked_sub(1)?.max(start.line);
+        let end = AbsolutePoint {
+            line: last_line,
+            column: term.last_column(),
+        };
+        let text = term.bounds_to_string(
+            self.point_to_grid(term, start),
+            self.point_to_grid(term, end),
+        );
+        let text = text.trim();
+        (!text.is_empty()).then(|| text.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alacritty_terminal::{
+        event::{Event as AlacTermEvent, VoidListener},
+        term::Config,
+        vte::ansi::Processor,
+        Term,
+    };
+    use futures::channel::mpsc::unbounded;
+
+    use super::*;
+    use crate::{TerminalSize, ZedListener};
+
+    fn parse(input: &[u8]) -> Vec<ShellIntegrationEvent> {
+        let mut parser = ShellIntegrationParser::default();
+        let mut events = Vec::new();
+        parser.advance(input, |event| events.push(event));
+        events
+    }
+
+    #[test]
+    fn test_parse_osc_133() {
+        assert_eq!(
+            parse(b"\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n\x1b]133;C\x07a b\r\n\x1b]133;D;2\x07"),
+            vec![
+                ShellIntegrationEvent::PromptStart,
+                ShellIntegrationEvent::CommandStart,
+                ShellIntegrationEvent::CommandExecuted,
+                ShellIntegrationEvent::CommandFinished { exit_code: Some(2) },
+            ]
+        );
+        assert_eq!(
+            parse(b"\x1b]133;D\x1b\\\x1b]0;title\x07\x1b]133;A;cl=m\x1b\\"),
+            vec![
+                ShellIntegrationEvent::CommandFinished { exit_code: None },
+                ShellIntegrationEvent::PromptStart,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_osc_633() {
+        assert_eq!(
+            parse(b"\x1b]633;E;echo a\\x3bb \\\\ c;nonce\x07\x1b]633;C\x07"),
+            vec![
+                ShellIntegrationEvent::CommandLine("echo a;b \\ c".to_string()),
+                ShellIntegrationEvent::CommandExecuted,
+            ]
+        );
+        assert_eq!(parse(b"\x1b]133;E;ls\x07"), Vec::new());
+    }
+
+    #[test]
+    fn test_parse_split_sequences() {
+        let mut parser = ShellIntegrationParser::default();
+        let mut events = Vec::new();
+        for chunk in [&b"output\x1b"[..], b"]13", b"3;D;", b"1\x1b", b"\\more"] {
+            parser.advance(chunk, |event| events.push(event));
+        }
+        assert_eq!(
+            events,
+            vec![ShellIntegrationEvent::CommandFinished { exit_code: Some(1) }]
+        );
+
+        let mut overflowing = b"\x1b]133;A".to_vec();
+        overflowing.extend(std::iter::repeat(b'x').take(MAX_OSC_LENGTH));
+        overflowing.extend(b"\x07\x1b]133;B\x07");
+        assert_eq!(
+            parse(&overflowing),
+            vec![ShellIntegrationEvent::CommandStart]
+        );
+    }
+
+    #[test]
+    fn test_track_commands() {
+        let mut term = Term::new(Config::default(), &TerminalSize::default(), VoidListener);
+        let mut processor: Processor = Processor::new();
+        let mut commands = ShellCommands::new(100);
+        let mut feed = |term: &mut Term<VoidListener>,
+                        commands: &mut ShellCommands,
+                        output: &[u8],
+                        event: Option<ShellIntegrationEvent>| {
+            for byte in output {
+                processor.advance(term, *byte);
+            }
+            if let Some(event) = event {
+                commands.handle_event(event, term);
+            }
+        };
+
+        feed(
+            &mut term,
+            &mut commands,
+            b"",
+            Some(ShellIntegrationEvent::PromptStart),
+        );
+        assert!(!commands.is_at_prompt());
+        feed(
+            &mut term,
+            &mut commands,
+            b"$ ",
+            Some(ShellIntegrationEvent::CommandStart),
+        );
+        assert!(commands.is_at_prompt());
+        feed(
+            &mut term,
+            &mut commands,
+            b"false\r\n",
+            Some(ShellIntegrationEvent::CommandExecuted),
+        );
+        assert!(!commands.is_at_prompt());
+        assert_eq!(
+            commands.running().and_then(|c| c.command_line.clone()),
+            Some("false".to_string())
+        );
+        feed(
+            &mut term,
+            &mut commands,
+            b"oops\r\n",
+            Some(ShellIntegrationEvent::CommandFinished { exit_code: Some(1) }),
+        );
+        feed(
+            &mut term,
+            &mut commands,
+            b"",
+            Some(ShellIntegrationEvent::PromptStart),
+        );
+
+        assert!(commands.running().is_none());
+        let finished = commands.last_finished().unwrap();
+        assert!(finished.failed());
+        assert_eq!(finished.output_start, Some(1));
+        assert_eq!(finished.output_end, Some(2));
+        assert_eq!(commands.last_command_line(), Some("false"));
+        assert_eq!(
+            commands.prompt_near(2, true).map(|prompt| prompt.line),
+            Some(0)
+        );
+        assert_eq!(
+            commands.prompt_near(0, false).map(|prompt| prompt.line),
+            Some(2)
+        );
+        assert_eq!(
+            commands.visible_markers(&term),
+            vec![CommandMarker {
+                display_line: 0,
+                exit_code: 1
+            }]
+        );
+    }
+
+    /// Feeds the output to the terminal through a [`ShellIntegrationReader`], handling the marks
+    /// when their notifications are parsed, the way the terminal does.
+    fn run_shell(
+        output: &[u8],
+        read_len: usize,
+        history_limit: usize,
+    ) -> (Term<ZedListener>, ShellCommands) {
+        let (term_events_tx, mut term_events_rx) = unbounded();
+        let config = Config {
+            scrolling_history: history_limit + SCROLLBACK_SLACK_LINES,
+            ..Config::default()
+        };
+        let mut term = Term::new(
+            config,
+            &TerminalSize::default(),
+            ZedListener(term_events_tx),
+        );
+        let mut processor: Processor = Processor::new();
+        let mut commands = ShellCommands::new(history_limit);
+        let (events_tx, mut events_rx) = unbounded();
+        let mut reader = ShellIntegrationReader::new(output, events_tx);
+        let mut buf = vec![0; read_len];
+        loop {
+            let read = reader.read(&mut buf).unwrap();
+            if read == 0 {
+                break;
+            }
+            for byte in &buf[..read] {
+                processor.advance(&mut term, *byte);
+            }
+            while let Ok(Some(event)) = term_events_rx.try_next() {
+                let AlacTermEvent::ClipboardStore(_, text) = event else {
+                    continue;
+                };
+                assert_eq!(text, MARK_PARSED);
+                assert!(reader.is_paused());
+                assert_eq!(
+                    reader.read(&mut buf).unwrap_err().kind(),
+                    io::ErrorKind::WouldBlock
+                );
+                commands.trim_scrollback(&mut term);
+                while let Ok(Some(event)) = events_rx.try_next() {
+                    commands.handle_event(event, &term);
+                }
+                reader.resume();
+            }
+        }
+        (term, commands)
+    }
+
+    #[test]
+    fn test_track_commands_from_single_read() {
+        let output = b"\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n\x1b]133;C\x07a\r\nb\r\n\x1b]133;D;0\x07\x1b]133;A\x07$ ";
+        for read_len in [4096, 5, 1] {
+            let (term, commands) = run_shell(output, read_len, 100);
+            let commands = commands.iter().collect::<Vec<_>>();
+            assert_eq!(commands.len(), 2, "read length {read_len}");
+            assert_eq!(
+                commands[0],
+                &ShellCommand {
+                    prompt_start: AbsolutePoint {
+                        line: 0,
+                        column: Column(0)
+                    },
+                    command_start: Some(AbsolutePoint {
+                        line: 0,
+                        column: Column(2)
+                    }),
+                    output_start: Some(1),
+                    output_end: Some(3),
+                    command_line: Some("ls".to_string()),
+                    exit_code: Some(0),
+                },
+                "read length {read_len}"
+            );
+            assert_eq!(commands[1].prompt_start.line, 3, "read length {read_len}");
+            assert_eq!(term.grid().cursor.point, AlacPoint::new(Line(3), Column(2)));
+        }
+    }
+
+    #[test]
+    fn test_track_commands_past_scrollback_limit() {
+        let mut output =
+            b"\x1b]133;A\x07$ \x1b]133;B\x07one\r\n\x1b]133;C\x07x\r\n\x1b]133;D;0\x07".to_vec();
+        output.extend(b"\x1b]133;A\x07$ \x1b]133;B\x07two\r\n\x1b]133;C\x07");
+        for ix in 1..=10 {
+            output.extend(format!("line {ix}\r\n").bytes());
+        }
+        output.extend(b"\x1b]133;D;1\x07\x1b]133;A\x07$ ");
+
+        let (term, commands) = run_shell(&output, 4096, 2);
+        assert_eq!(term.grid().history_size(), 2);
+        // The first command was evicted from the scrollback.
+        assert_eq!(
+            commands
+                .iter()
+                .map(|command| command.prompt_start.line)
+                .collect::<Vec<_>>(),
+            vec![2, 13]
+        );
+        let command = commands.last_finished().unwrap();
+        assert_eq!(command.output_start, Some(3));
+        assert_eq!(command.output_end, Some(13));
+        assert_eq!(commands.grid_line(&term, 13), term.grid().cursor.point.line);
+
+        let last_output_line = commands.grid_line(&term, 12);
+        let text = term.bounds_to_string(
+            AlacPoint::new(last_output_line, Column(0)),
+            AlacPoint::new(last_output_line, term.last_column()),
+        );
+        assert_eq!(text.trim(), "line 10");
+        // Evicted lines are clamped to the top of the scrollback.
+        assert_eq!(commands.grid_line(&term, 0), term.topmost_line());
+    }
+}
@@ -3,6 +3,7 @@ pub mod mappings;
 pub use alacritty_terminal;
 
 mod pty_info;
+pub mod shell_integration;
 pub mod terminal_settings;
 
 use alacritty_terminal::{
@@ -38,6 +39,10 @@ use futures::StreamExt;
 use pty_info::PtyProcessInfo;
 use serde::{Deserialize, Serialize};
 use settings::Settings;
+use shell_integration::{
+    CommandMarker, ShellCommand, ShellCommands, ShellIntegration, ShellIntegrationEvent,
+    SCROLLBACK_SLACK_LINES,
+};
 use smol::channel::{Receiver, Sender};
 use task::TaskId;
 use terminal_settings::{AlternateScroll, Shell, TerminalBlink, TerminalSettings};
@@ -64,7 +69,17 @@ use crate::mappings::{colors::to_alac_rgb, keys::to_esc_str};
 
 actions!(
     terminal,
-    [Clear, Copy, Paste, ShowCharacterPalette, SearchTest,]
+    [
+        Clear,
+        Copy,
+        Paste,
+        ShowCharacterPalette,
+        SearchTest,
+        ScrollToPreviousPrompt,
+        ScrollToNextPrompt,
+        CopyLastCommandOutput,
+        RerunLastCommand,
+    ]
 );
 
 ///Scrolling is unbearably sluggish by default. Alacritty supports a configurable
@@ -346,8 +361,9 @@ impl TerminalBuilder {
                 .unwrap_or(DEFAULT_SCROLL_HISTORY_LINES)
                 .min(MAX_SCROLL_HISTORY_LINES)
         };
+        // Lines past the limit are evicted by the shell integration, which keeps count of them.
         let config = Config {
-            scrolling_history,
+            scrolling_history: scrolling_history + SCROLLBACK_SLACK_LINES,
             ..Config::default()
         };
 
@@ -391,6 +407,15 @@ impl TerminalBuilder {
 
         let pty_info = PtyProcessInfo::new(&pty);
 
+        //Watch the shell output for the shell integration marks, which is only supported on Unix
+        #[cfg(unix)]
+        let (pty, shell_integration) = {
+            let (pty, shell_integration) = shell_integration::ShellIntegrationPty::new(pty)?;
+            (pty, Some(shell_integration))
+        };
+        #[cfg(not(unix))]
+        let shell_integration = None;
+
         //And connect them together
         let event_loop = EventLoop::new(
             term.clone(),
@@ -427,6 +452,8 @@ impl TerminalBuilder {
             hovered_word: false,
             url_regex,
             word_regex,
+            shell_commands: ShellCommands::new(scrolling_history),
+            shell_integration,
         };
 
         Ok(TerminalBuilder {
@@ -525,6 +552,7 @@ pub struct TerminalContent {
     pub cursor_char: char,
     pub size: TerminalSize,
     pub last_hovered_word: Option<HoveredWord>,
+    pub command_markers: Vec<CommandMarker>,
 }
 
 #[derive(Clone)]
@@ -549,6 +577,7 @@ impl Default for TerminalContent {
             cursor_char: Default::default(),
             size: Default::default(),
             last_hovered_word: None,
+            command_markers: Vec::new(),
         }
     }
 }
@@ -581,6 +610,9 @@ pub struct Terminal {
     url_regex: RegexSearch,
     word_regex: RegexSearch,
     task: Option<TaskState>,
+    shell_commands: ShellCommands,
+    /// The shell integration of the terminal, when the platform supports it.
+    shell_integration: Option<ShellIntegration>,
 }
 
 pub struct TaskState {
@@ -629,6 +661,9 @@ impl Terminal {
                 self.breadcrumb_text = String::new();
                 cx.emit(Event::BreadcrumbsChanged);
             }
+            AlacTermEvent::ClipboardStore(_, data) if data == shell_integration::MARK_PARSED => {
+                self.process_shell_integration_events(cx)
+            }
             AlacTermEvent::ClipboardStore(_, data) => {
                 cx.write_to_clipboard(ClipboardItem::new(data.to_string()))
             }
@@ -652,6 +687,7 @@ impl Terminal {
                 //NOOP, Handled in render
             }
             AlacTermEvent::Wakeup => {
+                self.shell_commands.trim_scrollback(&mut self.term.lock());
                 cx.emit(Event::Wakeup);
 
                 if self.pty_info.has_changed() {
@@ -703,6 +739,7 @@ impl Terminal {
             InternalEvent::Clear => {
                 // Clear back buffer
                 term.clear_screen(ClearMode::Saved);
+                self.shell_commands.clear();
 
                 let cursor = term.grid().cursor.point;
 
@@ -969,6 +1006,34 @@ impl Terminal {
         self.set_selection(Some((make_selection(&(start..=end)), end)));
     }
 
+    /// Returns the whole terminal text, including its scrollback history.
+    pub fn text_content(&self) -> String {
+        let term = self.term.lock();
+        let start = AlacPoint::new(term.topmost_line(), Column(0));
+        let end = AlacPoint::new(term.bottommost_line(), term.last_column());
+        term.bounds_to_string(start, end)
+    }
+
+    /// Returns the text of the last `count` lines up to and including the cursor line,
+    /// reaching into the scrollback history if needed.
+    pub fn last_lines(&self, count: usize) -> String {
+        last_lines(&self.term.lock(), count)
+    }
+
+    /// Whether the shell is waiting for a command at its prompt, so that input sent to the
+    /// terminal is edited there rather than read by a running program.
+    ///
+    /// Uses the shell integration marks when the shell reports them, and otherwise whether the
+    /// shell itself is the foreground process of the terminal.
+    pub fn is_at_prompt(&self) -> bool {
+        if self.shell_commands.is_empty() {
+            !self.term.lock().mode().contains(TermMode::ALT_SCREEN)
+                && self.pty_info.is_shell_in_foreground()
+        } else {
+            self.shell_commands.is_at_prompt()
+        }
+    }
+
     fn set_selection(&mut self, selection: Option<(Selection, AlacPoint)>) {
         self.events
             .push_back(InternalEvent::SetSelection(selection));
@@ -1053,6 +1118,7 @@ impl Terminal {
         }
 
         self.last_content = Self::make_content(&terminal, &self.last_content);
+        self.last_content.command_markers = self.shell_commands.visible_markers(&terminal);
     }
 
     fn make_content(term: &Term<ZedListener>, last_content: &TerminalContent) -> TerminalContent {
@@ -1080,6 +1146,7 @@ impl Terminal {
             cursor_char: term.grid()[content.cursor.point].c,
             size: last_content.size,
             last_hovered_word: last_content.last_hovered_word.clone(),
+            command_markers: Vec::new(),
         }
     }
 
@@ -1390,16 +1457,21 @@ impl Terminal {
                         .map(|name| name.to_string_lossy().to_string())
                         .unwrap_or_default();
 
-                    let argv = fpi.argv.clone();
-                    let process_name = format!(
-                        "{}{}",
-                        fpi.name,
-                        if argv.len() >= 1 {
-                            format!(" {}", (argv[1..]).join(" "))
-                        } else {
-                            "".to_string()
+                    let process_name = match self.running_command_line() {
+                        Some(command_line) => command_line.to_string(),
+                        None => {
+                            let argv = fpi.argv.clone();
+                            format!(
+                                "{}{}",
+                                fpi.name,
+                                if argv.len() >= 1 {
+                                    format!(" {}", (argv[1..]).join(" "))
+                                } else {
+                                    "".to_string()
+                                }
+                            )
                         }
-                    );
+                    };
                     let (process_file, process_name) = if truncate {
                         (
                             truncate_and_trailoff(&process_file, MAX_CHARS),
@@ -1414,6 +1486,107 @@ impl Terminal {
         }
     }
 
+    /// Commands, reported by the shell integration marks of the terminal shell.
+    pub fn shell_commands(&self) -> impl DoubleEndedIterator<Item = &ShellCommand> {
+        self.shell_commands.iter()
+    }
+
+    /// The command line of the command that is currently executing, if the shell reports it.
+    pub fn running_command_line(&self) -> Option<&str> {
+        self.shell_commands
+            .running()
+            .and_then(|command| command.command_line.as_deref())
+    }
+
+    /// Handles the marks reported by the shell integration. The PTY reader is paused after each
+    /// mark until it is resumed here, so the terminal contains the output up to the mark exactly.
+    fn process_shell_integration_events(&mut self, cx: &mut ModelContext<Self>) {
+        let Some(shell_integration) = self.shell_integration.as_mut() else {
+            return;
+        };
+        let mut handled = false;
+        let mut title_changed = false;
+        let term = self.term.clone();
+        let mut term = term.lock();
+        self.shell_commands.trim_scrollback(&mut term);
+        while let Ok(Some(event)) = shell_integration.events_rx.try_next() {
+            title_changed |= matches!(
+                event,
+                ShellIntegrationEvent::CommandExecuted
+                    | ShellIntegrationEvent::CommandFinished { .. }
+            );
+            self.shell_commands.handle_event(event, &term);
+            handled = true;
+        }
+        drop(term);
+
+        if !handled {
+            return;
+        }
+        shell_integration.resume.resume();
+        if title_changed {
+            cx.emit(Event::TitleChanged);
+        }
+        cx.emit(Event::Wakeup);
+    }
+
+    /// Scrolls the viewport, so that the previous (or next) prompt is at its top.
+    pub fn scroll_to_prompt(&mut self, direction_up: bool) {
+        let term = self.term.lock();
+        let display_offset = term.grid().display_offset();
+        let top_line = self
+            .shell_commands
+            .absolute_line(&term, Line(-(display_offset as i32)));
+        let target_offset = match self.shell_commands.prompt_near(top_line, direction_up) {
+            Some(prompt) => (-self.shell_commands.grid_line(&term, prompt.line).0).max(0) as usize,
+            None if direction_up => return,
+            None => 0,
+        };
+        drop(term);
+
+        let delta = target_offset as i32 - display_offset as i32;
+        if delta != 0 {
+            self.events
+                .push_back(InternalEvent::Scroll(AlacScroll::Delta(delta)));
+        }
+    }
+
+    /// Copies the output of the most recently finished command into the clipboard.
+    pub fn copy_last_command_output(&mut self, cx: &mut ModelContext<Self>) {
+        let Some((output_start, output_end)) = self
+            .shell_commands
+            .last_finished()
+            .and_then(|command| Some((command.output_start?, command.output_end?)))
+            .filter(|(start, end)| start < end)
+        else {
+            return;
+        };
+
+        let term = self.term.lock();
+        let start = AlacPoint::new(
+            self.shell_commands.grid_line(&term, output_start),
+            Column(0),
+        );
+        let end = AlacPoint::new(
+            self.shell_commands.grid_line(&term, output_end - 1),
+            term.last_column(),
+        );
+        let output = term.bounds_to_string(start, end);
+        drop(term);
+        cx.write_to_clipboard(ClipboardItem::new(output));
+    }
+
+    /// Runs the most recent command again, unless some command is still running.
+    pub fn rerun_last_command(&mut self) {
+        if self.shell_commands.running().is_some() {
+            return;
+        }
+        if let Some(command_line) = self.shell_commands.last_command_line() {
+            let input = format!("{}\r", command_line.replace('\n', "\r"));
+            self.input(input);
+        }
+    }
+
     pub fn can_navigate_to_selected_word(&self) -> bool {
         self.secondary_pressed && self.hovered_word
     }
@@ -1665,19 +1838,50 @@ pub fn rgba_color(r: u8, g: u8, b: u8) -> Hsla {
     .into()
 }
 
+/// Returns the text of the last `count` lines up to and including the cursor line.
+fn last_lines<T>(term: &Term<T>, count: usize) -> String {
+    let end = AlacPoint::new(term.grid().cursor.point.line, term.last_column());
+    let start_line = cmp::max(
+        term.topmost_line(),
+        Line(end.line.0 - count.saturating_sub(1) as i32),
+    );
+    term.bounds_to_string(AlacPoint::new(start_line, Column(0)), end)
+}
+
 #[cfg(test)]
 mod tests {
     use alacritty_terminal::{
+        event::VoidListener,
         index::{Column, Line, Point as AlacPoint},
-        term::cell::Cell,
+        term::{cell::Cell, Config},
+        vte::ansi::Processor,
+        Term,
     };
     use gpui::{point, size, Pixels};
     use rand::{distributions::Alphanumeric, rngs::ThreadRng, thread_rng, Rng};
 
     use crate::{
-        content_index_for_mouse, rgb_for_index, IndexedCell, TerminalContent, TerminalSize,
+        content_index_for_mouse, last_lines, rgb_for_index, IndexedCell, TerminalContent,
+        TerminalSize,
     };
 
+    #[test]
+    fn test_last_lines() {
+        // The default size has room for 6 lines, so the first lines scroll into the history.
+        let mut term = Term::new(Config::default(), &TerminalSize::default(), VoidListener);
+        let mut processor: Processor = Processor::new();
+        for byte in b"one\r\ntwo\r\nthree\r\nfour\r\nfive\r\nsix\r\nseven\r\n$ ls" {
+            processor.advance(&mut term, *byte);
+        }
+
+        assert_eq!(last_lines(&term, 1), "$ ls");
+        assert_eq!(last_lines(&term, 3), "six\nseven\n$ ls");
+        assert_eq!(
+            last_lines(&term, 20),
+            "one\ntwo\nthree\nfour\nfive\nsix\nseven\n$ ls"
+        );
+    }
+
     #[test]
     fn test_rgb_for_index() {
         // Test every possible value in the color cube.
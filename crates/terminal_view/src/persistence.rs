@@ -34,6 +34,9 @@ define_connection! {
             DROP TABLE terminals;
 
             ALTER TABLE terminals2 RENAME TO terminals;
+        ),
+        sql!(
+            ALTER TABLE terminals ADD COLUMN custom_title TEXT;
         )];
 }
 
@@ -56,8 +59,10 @@ impl TerminalDb {
             workspace_id: WorkspaceId,
             working_directory: PathBuf
         ) -> Result<()> {
-            INSERT OR REPLACE INTO terminals(item_id, workspace_id, working_directory)
+            INSERT INTO terminals(item_id, workspace_id, working_directory)
             VALUES (?, ?, ?)
+            ON CONFLICT(workspace_id, item_id) DO UPDATE SET
+                working_directory = excluded.working_directory
         }
     }
 
@@ -68,4 +73,25 @@ impl TerminalDb {
             WHERE item_id = ? AND workspace_id = ?
         }
     }
+
+    query! {
+        pub async fn save_custom_title(
+            item_id: ItemId,
+            workspace_id: WorkspaceId,
+            custom_title: Option<String>
+        ) -> Result<()> {
+            INSERT INTO terminals(item_id, workspace_id, custom_title)
+            VALUES (?, ?, ?)
+            ON CONFLICT(workspace_id, item_id) DO UPDATE SET
+                custom_title = excluded.custom_title
+        }
+    }
+
+    query! {
+        pub fn get_custom_title(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<String>> {
+            SELECT custom_title
+            FROM terminals
+            WHERE item_id = ? AND workspace_id = ?
+        }
+    }
 }
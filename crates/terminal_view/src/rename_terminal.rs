@@ -0,0 +1,176 @@
+use editor::{actions::SelectAll, Editor, EditorEvent};
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Render, Subscription, View,
+    ViewContext, VisualContext, WeakView,
+};
+use ui::{prelude::*, v_flex, Label};
+use workspace::ModalView;
+
+use crate::TerminalView;
+
+/// A modal to give a terminal its own name, shown instead of the title its shell reports.
+pub struct RenameTerminalModal {
+    title_editor: View<Editor>,
+    terminal_view: WeakView<TerminalView>,
+    _subscription: Subscription,
+}
+
+impl ModalView for RenameTerminalModal {}
+
+impl EventEmitter<DismissEvent> for RenameTerminalModal {}
+
+impl FocusableView for RenameTerminalModal {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.title_editor.focus_handle(cx)
+    }
+}
+
+impl RenameTerminalModal {
+    pub fn new(terminal_view: View<TerminalView>, cx: &mut ViewContext<Self>) -> Self {
+        let (custom_title, terminal_title) = {
+            let terminal_view = terminal_view.read(cx);
+            (
+                terminal_view.custom_title().map(ToOwned::to_owned),
+                terminal_view.terminal().read(cx).title(true),
+            )
+        };
+        let title_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text(terminal_title, cx);
+            if let Some(custom_title) = custom_title {
+                editor.set_text(custom_title, cx);
+                editor.select_all(&SelectAll, cx);
+            }
+            editor
+        });
+        let subscription = cx.subscribe(&title_editor, |_, _, event: &EditorEvent, cx| {
+            if let EditorEvent::Blurred = event {
+                cx.emit(DismissEvent);
+            }
+        });
+
+        Self {
+            title_editor,
+            terminal_view: terminal_view.downgrade(),
+            _subscription: subscription,
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let title = self.title_editor.read(cx).text(cx).trim().to_string();
+        let custom_title = (!title.is_empty()).then_some(title);
+        self.terminal_view
+            .update(cx, |terminal_view, cx| {
+                terminal_view.set_custom_title(custom_title, cx);
+                cx.focus_self();
+            })
+            .ok();
+        cx.emit(DismissEvent);
+    }
+}
+
+impl Render for RenameTerminalModal {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .elevation_2(cx)
+            .key_context("RenameTerminalModal")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .w_96()
+            .child(div().px_2().py_1().child(self.title_editor.clone()))
+            .child(
+                div()
+                    .h_px()
+                    .w_full()
+                    .bg(cx.theme().colors().element_background),
+            )
+            .child(
+                div().px_2().py_1().child(
+                    Label::new("Name the terminal, or leave empty to use the shell title")
+                        .color(Color::Muted),
+                ),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::TERMINAL_DB;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::Project;
+    use workspace::{AppState, Workspace, WORKSPACE_DB};
+
+    #[gpui::test]
+    async fn test_rename_terminal(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        let app_state = cx.update(AppState::test);
+        cx.update(|cx| {
+            terminal::init(cx);
+            language::init(cx);
+            Project::init_settings(cx);
+            editor::init(cx);
+        });
+
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let workspace = window.root_view(cx).unwrap();
+        let cx = &mut VisualTestContext::from_window(*window, cx);
+        let workspace_id = WORKSPACE_DB.next_id().await.unwrap();
+        let terminal_view = workspace.update(cx, |workspace, cx| {
+            let window = cx.window_handle();
+            let terminal = workspace
+                .project()
+                .update(cx, |project, cx| {
+                    project.create_terminal(None, None, window, cx)
+                })
+                .unwrap();
+            let workspace = workspace.weak_handle();
+            cx.new_view(|cx| TerminalView::new(terminal, workspace, workspace_id, cx))
+        });
+        let item_id = terminal_view.entity_id().as_u64();
+
+        let modal = cx.new_view(|cx| RenameTerminalModal::new(terminal_view.clone(), cx));
+        modal.update(cx, |modal, cx| {
+            modal
+                .title_editor
+                .update(cx, |editor, cx| editor.set_text("  Build server ", cx));
+            modal.confirm(&menu::Confirm, cx);
+        });
+        terminal_view.update(cx, |terminal_view, _| {
+            assert_eq!(terminal_view.custom_title(), Some("Build server"));
+        });
+        cx.run_until_parked();
+        TERMINAL_DB.write(|_| ()).await;
+        assert_eq!(
+            TERMINAL_DB.get_custom_title(item_id, workspace_id).unwrap(),
+            Some("Build server".to_string())
+        );
+
+        // Renaming again starts from the current name, and clearing it restores the shell's title.
+        let modal = cx.new_view(|cx| RenameTerminalModal::new(terminal_view.clone(), cx));
+        modal.update(cx, |modal, cx| {
+            assert_eq!(modal.title_editor.read(cx).text(cx), "Build server");
+            modal
+                .title_editor
+                .update(cx, |editor, cx| editor.set_text("", cx));
+            modal.confirm(&menu::Confirm, cx);
+        });
+        terminal_view.update(cx, |terminal_view, _| {
+            assert_eq!(terminal_view.custom_title(), None);
+        });
+        cx.run_until_parked();
+        TERMINAL_DB.write(|_| ()).await;
+        assert_eq!(
+            TERMINAL_DB
+                .get_custom_title(item_id, workspace_id)
+                .unwrap()
+                .filter(|title| !title.is_empty()),
+            None
+        );
+    }
+}
@@ -20,6 +20,7 @@ use terminal::{
             CursorShape as AlacCursorShape, NamedColor,
         },
     },
+    shell_integration::CommandMarker,
     terminal_settings::TerminalSettings,
     HoveredWord, IndexedCell, Terminal, TerminalContent, TerminalSize,
 };
@@ -44,6 +45,8 @@ pub struct LayoutState {
     hyperlink_tooltip: Option<AnyElement>,
     gutter: Pixels,
     last_hovered_word: Option<HoveredWord>,
+    command_markers: Vec<CommandMarker>,
+    failed_command_color: Hsla,
 }
 
 /// Helper struct for converting data between Alacritty's cursor points, and displayed cursor points.
@@ -692,6 +695,7 @@ impl Element for TerminalElement {
                     cursor_char,
                     selection,
                     cursor,
+                    command_markers,
                     ..
                 } = &self.terminal.read(cx).last_content;
 
@@ -780,6 +784,8 @@ impl Element for TerminalElement {
                     hyperlink_tooltip,
                     gutter,
                     last_hovered_word,
+                    command_markers: command_markers.clone(),
+                    failed_command_color: theme.status().error,
                 }
             })
     }
@@ -837,6 +843,22 @@ impl Element for TerminalElement {
                     rect.paint(origin, &layout, cx);
                 }
 
+                // Mark the prompts of the failed commands in the gutter
+                for marker in &layout.command_markers {
+                    if marker.exit_code == 0 {
+                        continue;
+                    }
+                    let line_height = layout.dimensions.line_height;
+                    let marker_bounds = Bounds::new(
+                        point(
+                            bounds.origin.x,
+                            origin.y + line_height * marker.display_line as f32,
+                        ),
+                        gpui::size(layout.gutter * 0.5, line_height),
+                    );
+                    cx.paint_quad(fill(marker_bounds, layout.failed_command_color));
+                }
+
                 for (relative_highlighted_range, color) in layout.relative_highlighted_ranges.iter()
                 {
                     if let Some((start_y, highlighted_range_lines)) =
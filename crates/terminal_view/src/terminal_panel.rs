@@ -1,16 +1,17 @@
 use std::{ops::ControlFlow, path::PathBuf, sync::Arc};
 
 use crate::TerminalView;
-use collections::{HashMap, HashSet};
+use collections::HashMap;
 use db::kvp::KEY_VALUE_STORE;
 use futures::future::join_all;
 use gpui::{
-    actions, Action, AppContext, AsyncWindowContext, DismissEvent, Entity, EventEmitter,
-    ExternalPaths, FocusHandle, FocusableView, IntoElement, ParentElement, Pixels, Render, Styled,
-    Subscription, Task, View, ViewContext, VisualContext, WeakView, WindowContext,
+    actions, div, Action, AppContext, AsyncWindowContext, Axis, DismissEvent, Entity, EntityId,
+    EventEmitter, ExternalPaths, FocusHandle, FocusableView, IntoElement, Model, ParentElement,
+    Pixels, Render, Styled, Subscription, Task, View, ViewContext, VisualContext, WeakView,
+    WindowContext,
 };
 use itertools::Itertools;
-use project::{Fs, ProjectEntryId};
+use project::{Fs, Project, ProjectEntryId};
 use search::{buffer_search::DivRegistrar, BufferSearchBar};
 use serde::{Deserialize, Serialize};
 use settings::Settings;
@@ -26,7 +27,8 @@ use workspace::{
     item::Item,
     pane,
     ui::IconName,
-    DraggedTab, NewTerminal, Pane, ToggleZoom, Workspace,
+    DraggedTab, NewTerminal, Pane, PaneGroup, PaneGroupLayout, SplitDirection, ToggleZoom,
+    Workspace,
 };
 
 use anyhow::Result;
@@ -49,146 +51,25 @@ pub fn init(cx: &mut AppContext) {
 }
 
 pub struct TerminalPanel {
-    pane: View<Pane>,
+    center: PaneGroup,
+    active_pane: View<Pane>,
     fs: Arc<dyn Fs>,
     workspace: WeakView<Workspace>,
     width: Option<Pixels>,
     height: Option<Pixels>,
     pending_serialization: Task<Option<()>>,
     pending_terminals_to_add: usize,
+    pane_subscriptions: HashMap<EntityId, [Subscription; 2]>,
     _subscriptions: Vec<Subscription>,
     deferred_tasks: HashMap<TaskId, Task<()>>,
 }
 
 impl TerminalPanel {
     fn new(workspace: &Workspace, cx: &mut ViewContext<Self>) -> Self {
-        let pane = cx.new_view(|cx| {
-            let mut pane = Pane::new(
-                workspace.weak_handle(),
-                workspace.project().clone(),
-                Default::default(),
-                None,
-                NewTerminal.boxed_clone(),
-                cx,
-            );
-            pane.set_can_split(false, cx);
-            pane.set_can_navigate(false, cx);
-            pane.display_nav_history_buttons(None);
-            pane.set_should_display_tab_bar(|_| true);
-            pane.set_render_tab_bar_buttons(cx, move |pane, cx| {
-                h_flex()
-                    .gap_2()
-                    .child(
-                        IconButton::new("plus", IconName::Plus)
-                            .icon_size(IconSize::Small)
-                            .on_click(cx.listener(|pane, _, cx| {
-                                let focus_handle = pane.focus_handle(cx);
-                                let menu = ContextMenu::build(cx, |menu, _| {
-                                    menu.action(
-                                        "New Terminal",
-                                        workspace::NewTerminal.boxed_clone(),
-                                    )
-                                    .entry(
-                                        "Spawn task",
-                                        Some(tasks_ui::Spawn::modal().boxed_clone()),
-                                        move |cx| {
-                                            // We want the focus to go back to terminal panel once task modal is dismissed,
-                                            // hence we focus that first. Otherwise, we'd end up without a focused element, as
-                                            // context menu will be gone the moment we spawn the modal.
-                                            cx.focus(&focus_handle);
-                                            cx.dispatch_action(
-                                                tasks_ui::Spawn::modal().boxed_clone(),
-                                            );
-                                        },
-                                    )
-                                });
-                                cx.subscribe(&menu, |pane, _, _: &DismissEvent, _| {
-                                    pane.new_item_menu = None;
-                                })
-                                .detach();
-                                pane.new_item_menu = Some(menu);
-                            }))
-                            .tooltip(|cx| Tooltip::text("New...", cx)),
-                    )
-                    .when_some(pane.new_item_menu.as_ref(), |el, new_item_menu| {
-                        el.child(Pane::render_menu_overlay(new_item_menu))
-                    })
-                    .child({
-                        let zoomed = pane.is_zoomed();
-                        IconButton::new("toggle_zoom", IconName::Maximize)
-                            .icon_size(IconSize::Small)
-                            .selected(zoomed)
-                            .selected_icon(IconName::Minimize)
-                            .on_click(cx.listener(|pane, _, cx| {
-                                pane.toggle_zoom(&workspace::ToggleZoom, cx);
-                            }))
-                            .tooltip(move |cx| {
-                                Tooltip::for_action(
-                                    if zoomed { "Zoom Out" } else { "Zoom In" },
-                                    &ToggleZoom,
-                                    cx,
-                                )
-                            })
-                    })
-                    .into_any_element()
-            });
-
-            let workspace = workspace.weak_handle();
-            pane.set_custom_drop_handle(cx, move |pane, dropped_item, cx| {
-                if let Some(tab) = dropped_item.downcast_ref::<DraggedTab>() {
-                    let item = if &tab.pane == cx.view() {
-                        pane.item_for_index(tab.ix)
-                    } else {
-                        tab.pane.read(cx).item_for_index(tab.ix)
-                    };
-                    if let Some(item) = item {
-                        if item.downcast::<TerminalView>().is_some() {
-                            return ControlFlow::Continue(());
-                        } else if let Some(project_path) = item.project_path(cx) {
-                            if let Some(entry_path) = workspace
-                                .update(cx, |workspace, cx| {
-                                    workspace
-                                        .project()
-                                        .read(cx)
-                                        .absolute_path(&project_path, cx)
-                                })
-                                .log_err()
-                                .flatten()
-                            {
-                                add_paths_to_terminal(pane, &[entry_path], cx);
-                            }
-                        }
-                    }
-                } else if let Some(&entry_id) = dropped_item.downcast_ref::<ProjectEntryId>() {
-                    if let Some(entry_path) = workspace
-                        .update(cx, |workspace, cx| {
-                            let project = workspace.project().read(cx);
-                            project
-                                .path_for_entry(entry_id, cx)
-                                .and_then(|project_path| project.absolute_path(&project_path, cx))
-                        })
-                        .log_err()
-                        .flatten()
-                    {
-                        add_paths_to_terminal(pane, &[entry_path], cx);
-                    }
-                } else if let Some(paths) = dropped_item.downcast_ref::<ExternalPaths>() {
-                    add_paths_to_terminal(pane, paths.paths(), cx);
-                }
-
-                ControlFlow::Break(())
-            });
-            let buffer_search_bar = cx.new_view(search::BufferSearchBar::new);
-            pane.toolbar()
-                .update(cx, |toolbar, cx| toolbar.add_item(buffer_search_bar, cx));
-            pane
-        });
-        let subscriptions = vec![
-            cx.observe(&pane, |_, _, cx| cx.notify()),
-            cx.subscribe(&pane, Self::handle_pane_event),
-        ];
-        let this = Self {
-            pane,
+        let pane = new_terminal_pane(workspace.weak_handle(), workspace.project().clone(), cx);
+        let mut this = Self {
+            center: PaneGroup::new(pane.clone()),
+            active_pane: pane.clone(),
             fs: workspace.app_state().fs.clone(),
             workspace: workspace.weak_handle(),
             pending_serialization: Task::ready(None),
@@ -196,11 +77,36 @@ impl TerminalPanel {
             height: None,
             pending_terminals_to_add: 0,
             deferred_tasks: HashMap::default(),
-            _subscriptions: subscriptions,
+            pane_subscriptions: HashMap::default(),
+            _subscriptions: Vec::new(),
         };
+        this.subscribe_to_pane(&pane, cx);
         this
     }
 
+    fn subscribe_to_pane(&mut self, pane: &View<Pane>, cx: &mut ViewContext<Self>) {
+        self.pane_subscriptions.insert(
+            pane.entity_id(),
+            [
+                cx.observe(pane, |_, _, cx| cx.notify()),
+                cx.subscribe(pane, Self::handle_pane_event),
+            ],
+        );
+    }
+
+    /// Removes the pane from the panel's splits, returning whether it was removed,
+    /// which it isn't when it's the last pane.
+    fn remove_pane(&mut self, pane: &View<Pane>) -> Result<bool> {
+        let removed = self.center.remove(pane)?;
+        if removed {
+            self.pane_subscriptions.remove(&pane.entity_id());
+            if &self.active_pane == pane {
+                self.active_pane = self.center.first_pane();
+            }
+        }
+        Ok(removed)
+    }
+
     pub async fn load(
         workspace: WeakView<Workspace>,
         mut cx: AsyncWindowContext,
@@ -216,34 +122,32 @@ impl TerminalPanel {
             .log_err()
             .flatten();
 
-        let (panel, pane, items) = workspace.update(&mut cx, |workspace, cx| {
+        let (panel, pending_items) = workspace.update(&mut cx, |workspace, cx| {
             let panel = cx.new_view(|cx| TerminalPanel::new(workspace, cx));
-            let items = if let Some(serialized_panel) = serialized_panel.as_ref() {
+            let pending_items = if let Some(serialized_panel) = serialized_panel.as_ref() {
                 panel.update(cx, |panel, cx| {
                     cx.notify();
                     panel.height = serialized_panel.height.map(|h| h.round());
                     panel.width = serialized_panel.width.map(|w| w.round());
-                    panel.pane.update(cx, |_, cx| {
-                        serialized_panel
-                            .items
-                            .iter()
-                            .map(|item_id| {
-                                TerminalView::deserialize(
-                                    workspace.project().clone(),
-                                    workspace.weak_handle(),
-                                    workspace.database_id(),
-                                    *item_id,
-                                    cx,
-                                )
-                            })
-                            .collect::<Vec<_>>()
-                    })
+                    let panes = serialized_panel.panes.clone().unwrap_or_else(|| {
+                        SerializedTerminalPanes::Pane {
+                            items: serialized_panel.items.clone(),
+                            active_item_id: serialized_panel.active_item_id,
+                            active: true,
+                        }
+                    });
+                    let mut pending_items = Vec::new();
+                    panel.pane_subscriptions.clear();
+                    let (layout, active_pane) =
+                        panel.deserialize_panes(&panes, workspace, &mut pending_items, cx);
+                    panel.center = PaneGroup::from_layout(layout);
+                    panel.active_pane = active_pane.unwrap_or_else(|| panel.center.first_pane());
+                    pending_items
                 })
             } else {
                 Vec::new()
             };
-            let pane = panel.read(cx).pane.clone();
-            (panel, pane, items)
+            (panel, pending_items)
         })?;
 
         if let Some(workspace) = workspace.upgrade() {
@@ -261,47 +165,129 @@ impl TerminalPanel {
                 .ok();
         }
 
-        let pane = pane.downgrade();
+        let (panes, items): (Vec<_>, Vec<_>) = pending_items
+            .into_iter()
+            .map(|(pane, activate, item)| ((pane, activate), item))
+            .unzip();
         let items = futures::future::join_all(items).await;
-        pane.update(&mut cx, |pane, cx| {
-            let active_item_id = serialized_panel
-                .as_ref()
-                .and_then(|panel| panel.active_item_id);
-            let mut active_ix = None;
-            for item in items {
+        panel.update(&mut cx, |panel, cx| {
+            for ((pane, activate), item) in panes.into_iter().zip(items) {
                 if let Some(item) = item.log_err() {
-                    let item_id = item.entity_id().as_u64();
-                    pane.add_item(Box::new(item), false, false, None, cx);
-                    if Some(item_id) == active_item_id {
-                        active_ix = Some(pane.items_len() - 1);
-                    }
+                    pane.update(cx, |pane, cx| {
+                        pane.add_item(Box::new(item), false, false, None, cx);
+                        if activate {
+                            pane.activate_item(pane.items_len() - 1, false, false, cx)
+                        }
+                    });
                 }
             }
 
-            if let Some(active_ix) = active_ix {
-                pane.activate_item(active_ix, false, false, cx)
+            // Panes, none of whose terminals could be restored, are dropped from the layout.
+            let empty_panes = panel
+                .center
+                .panes()
+                .into_iter()
+                .filter(|pane| pane.read(cx).items_len() == 0)
+                .cloned()
+                .collect::<Vec<_>>();
+            for pane in empty_panes {
+                panel.remove_pane(&pane).log_err();
             }
         })?;
 
         Ok(panel)
     }
 
+    fn deserialize_panes(
+        &mut self,
+        serialized_panes: &SerializedTerminalPanes,
+        workspace: &Workspace,
+        pending_items: &mut Vec<(View<Pane>, bool, Task<Result<View<TerminalView>>>)>,
+        cx: &mut ViewContext<Self>,
+    ) -> (PaneGroupLayout, Option<View<Pane>>) {
+        match serialized_panes {
+            SerializedTerminalPanes::Group {
+                axis,
+                flexes,
+                children,
+            } => {
+                let mut active_pane = None;
+                let members = children
+                    .iter()
+                    .map(|child| {
+                        let (member, active) =
+                            self.deserialize_panes(child, workspace, pending_items, cx);
+                        active_pane = active_pane.take().or(active);
+                        member
+                    })
+                    .collect::<Vec<_>>();
+                let member = if members.len() == 1 {
+                    members.into_iter().next().unwrap()
+                } else {
+                    PaneGroupLayout::Axis {
+                        axis: (*axis).into(),
+                        flexes: flexes.clone(),
+                        members,
+                    }
+                };
+                (member, active_pane)
+            }
+            SerializedTerminalPanes::Pane {
+                items,
+                active_item_id,
+                active,
+            } => {
+                let pane =
+                    new_terminal_pane(workspace.weak_handle(), workspace.project().clone(), cx);
+                self.subscribe_to_pane(&pane, cx);
+                pane.update(cx, |_, cx| {
+                    for item_id in items {
+                        let item = TerminalView::deserialize(
+                            workspace.project().clone(),
+                            workspace.weak_handle(),
+                            workspace.database_id(),
+                            *item_id,
+                            cx,
+                        );
+                        pending_items.push((pane.clone(), Some(*item_id) == *active_item_id, item));
+                    }
+                });
+                let active_pane = active.then(|| pane.clone());
+                (PaneGroupLayout::Pane(pane), active_pane)
+            }
+        }
+    }
+
     fn handle_pane_event(
         &mut self,
-        _pane: View<Pane>,
+        pane: View<Pane>,
         event: &pane::Event,
         cx: &mut ViewContext<Self>,
     ) {
         match event {
             pane::Event::ActivateItem { .. } => self.serialize(cx),
             pane::Event::RemoveItem { .. } => self.serialize(cx),
-            pane::Event::Remove => cx.emit(PanelEvent::Close),
+            pane::Event::Remove => {
+                let was_active = self.active_pane == pane;
+                if let Some(removed) = self.remove_pane(&pane).log_err() {
+                    if removed {
+                        if was_active {
+                            cx.focus_view(&self.active_pane);
+                        }
+                        self.serialize(cx);
+                        cx.notify();
+                    } else {
+                        cx.emit(PanelEvent::Close);
+                    }
+                }
+            }
+            pane::Event::Split(direction) => self.split_pane(pane, *direction, cx),
+            pane::Event::Focus => self.active_pane = pane,
             pane::Event::ZoomIn => cx.emit(PanelEvent::ZoomIn),
             pane::Event::ZoomOut => cx.emit(PanelEvent::ZoomOut),
 
             pane::Event::AddItem { item } => {
                 if let Some(workspace) = self.workspace.upgrade() {
-                    let pane = self.pane.clone();
                     workspace.update(cx, |workspace, cx| item.added_to_pane(workspace, pane, cx))
                 }
             }
@@ -310,6 +296,42 @@ impl TerminalPanel {
         }
     }
 
+    /// Splits the pane, opening a new terminal in the working directory of its active terminal.
+    fn split_pane(
+        &mut self,
+        pane: View<Pane>,
+        direction: SplitDirection,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let working_directory = pane
+            .read(cx)
+            .active_item()
+            .and_then(|item| item.downcast::<TerminalView>())
+            .and_then(|terminal_view| terminal_view.read(cx).terminal().read(cx).get_cwd());
+        let project = workspace.read(cx).project().clone();
+        let new_pane = new_terminal_pane(self.workspace.clone(), project, cx);
+        if self
+            .center
+            .split(&pane, &new_pane, direction)
+            .log_err()
+            .is_none()
+        {
+            return;
+        }
+        self.subscribe_to_pane(&new_pane, cx);
+        if pane.read(cx).is_zoomed() {
+            new_pane.update(cx, |new_pane, cx| new_pane.set_zoomed(true, cx));
+        }
+
+        self.active_pane = new_pane.clone();
+        cx.focus_view(&new_pane);
+        self.add_terminal(working_directory, None, RevealStrategy::Always, cx);
+        cx.notify();
+    }
+
     pub fn open_terminal(
         workspace: &mut Workspace,
         action: &workspace::OpenTerminal,
@@ -369,7 +391,7 @@ impl TerminalPanel {
             self.spawn_in_new_terminal(spawn_task, working_directory, cx);
             return;
         }
-        let (existing_item_index, existing_terminal) = terminals_for_task
+        let existing_terminal = terminals_for_task
             .last()
             .expect("covered no terminals case above")
             .clone();
@@ -378,14 +400,9 @@ impl TerminalPanel {
                 !use_new_terminal,
                 "Should have handled 'allow_concurrent_runs && use_new_terminal' case above"
             );
-            self.replace_terminal(
-                working_directory,
-                spawn_task,
-                existing_item_index,
-                existing_terminal,
-                cx,
-            );
+            self.replace_terminal(working_directory, spawn_task, existing_terminal, cx);
         } else {
+            let terminal_to_replace = existing_terminal.clone();
             self.deferred_tasks.insert(
                 spawn_in_terminal.id.clone(),
                 cx.spawn(|terminal_panel, mut cx| async move {
@@ -402,8 +419,7 @@ impl TerminalPanel {
                                 terminal_panel.replace_terminal(
                                     working_directory,
                                     spawn_task,
-                                    existing_item_index,
-                                    existing_terminal,
+                                    terminal_to_replace,
                                     cx,
                                 );
                             }
@@ -414,7 +430,7 @@ impl TerminalPanel {
 
             match reveal {
                 RevealStrategy::Always => {
-                    self.activate_terminal_view(existing_item_index, cx);
+                    self.activate_terminal_view(&existing_terminal, cx);
                     let task_workspace = self.workspace.clone();
                     cx.spawn(|_, mut cx| async move {
                         task_workspace
@@ -453,31 +469,32 @@ impl TerminalPanel {
         });
     }
 
-    fn terminals_for_task(
-        &self,
-        label: &str,
-        cx: &mut AppContext,
-    ) -> Vec<(usize, View<TerminalView>)> {
-        self.pane
-            .read(cx)
-            .items()
-            .enumerate()
-            .filter_map(|(index, item)| Some((index, item.act_as::<TerminalView>(cx)?)))
-            .filter_map(|(index, terminal_view)| {
-                let task_state = terminal_view.read(cx).terminal().read(cx).task()?;
-                if &task_state.full_label == label {
-                    Some((index, terminal_view))
-                } else {
-                    None
-                }
+    fn terminals_for_task(&self, label: &str, cx: &mut AppContext) -> Vec<View<TerminalView>> {
+        self.center
+            .panes()
+            .into_iter()
+            .flat_map(|pane| pane.read(cx).items())
+            .filter_map(|item| item.act_as::<TerminalView>(cx))
+            .filter(|terminal_view| {
+                terminal_view
+                    .read(cx)
+                    .terminal()
+                    .read(cx)
+                    .task()
+                    .map_or(false, |task_state| task_state.full_label == label)
             })
             .collect()
     }
 
-    fn activate_terminal_view(&self, item_index: usize, cx: &mut WindowContext) {
-        self.pane.update(cx, |pane, cx| {
-            pane.activate_item(item_index, true, true, cx)
-        })
+    fn activate_terminal_view(&self, terminal_view: &View<TerminalView>, cx: &mut WindowContext) {
+        for pane in self.center.panes() {
+            if let Some(item_index) = pane.read(cx).index_for_item(terminal_view) {
+                pane.update(cx, |pane, cx| {
+                    pane.activate_item(item_index, true, true, cx)
+                });
+                return;
+            }
+        }
     }
 
     fn add_terminal(
@@ -491,9 +508,10 @@ impl TerminalPanel {
         self.pending_terminals_to_add += 1;
 
         cx.spawn(|terminal_panel, mut cx| async move {
-            let pane = terminal_panel.update(&mut cx, |this, _| this.pane.clone())?;
+            let pane = terminal_panel.update(&mut cx, |this, _| this.active_pane.clone())?;
             workspace.update(&mut cx, |workspace, cx| {
-                if workspace.project().read(cx).is_remote() {
+                let project = workspace.project().read(cx);
+                if project.is_remote() && !project.is_via_ssh() {
                     workspace.show_error(
                         &anyhow::anyhow!("Cannot open terminals on remote projects (yet!)"),
                         cx,
@@ -542,28 +560,7 @@ impl TerminalPanel {
     }
 
     fn serialize(&mut self, cx: &mut ViewContext<Self>) {
-        let mut items_to_serialize = HashSet::default();
-        let items = self
-            .pane
-            .read(cx)
-            .items()
-            .filter_map(|item| {
-                let terminal_view = item.act_as::<TerminalView>(cx)?;
-                if terminal_view.read(cx).terminal().read(cx).task().is_some() {
-                    None
-                } else {
-                    let id = item.item_id().as_u64();
-                    items_to_serialize.insert(id);
-                    Some(id)
-                }
-            })
-            .collect::<Vec<_>>();
-        let active_item_id = self
-            .pane
-            .read(cx)
-            .active_item()
-            .map(|item| item.item_id().as_u64())
-            .filter(|active_id| items_to_serialize.contains(active_id));
+        let panes = serialize_panes(&self.center.layout(), &self.active_pane, cx);
         let height = self.height;
         let width = self.width;
         self.pending_serialization = cx.background_executor().spawn(
@@ -572,8 +569,9 @@ impl TerminalPanel {
                     .write_kvp(
                         TERMINAL_PANEL_KEY.into(),
                         serde_json::to_string(&SerializedTerminalPanel {
-                            items,
-                            active_item_id,
+                            items: Vec::new(),
+                            active_item_id: None,
+                            panes: Some(panes),
                             height,
                             width,
                         })?,
@@ -589,7 +587,6 @@ impl TerminalPanel {
         &self,
         working_directory: Option<PathBuf>,
         spawn_task: SpawnInTerminal,
-        terminal_item_index: usize,
         terminal_to_replace: View<TerminalView>,
         cx: &mut ViewContext<'_, Self>,
     ) -> Option<()> {
@@ -611,7 +608,7 @@ impl TerminalPanel {
 
         match reveal {
             RevealStrategy::Always => {
-                self.activate_terminal_view(terminal_item_index, cx);
+                self.activate_terminal_view(&terminal_to_replace, cx);
                 let task_workspace = self.workspace.clone();
                 cx.spawn(|_, mut cx| async move {
                     task_workspace
@@ -626,20 +623,29 @@ impl TerminalPanel {
         Some(())
     }
 
+    /// The pane with the focused terminal, or the one focused last.
     pub fn pane(&self) -> &View<Pane> {
-        &self.pane
+        &self.active_pane
+    }
+
+    fn terminals_count(&self, cx: &AppContext) -> usize {
+        self.center
+            .panes()
+            .into_iter()
+            .map(|pane| pane.read(cx).items_len())
+            .sum()
     }
 
     fn has_no_terminals(&mut self, cx: &mut ViewContext<'_, Self>) -> bool {
-        self.pane.read(cx).items_len() == 0 && self.pending_terminals_to_add == 0
+        self.terminals_count(cx) == 0 && self.pending_terminals_to_add == 0
     }
 }
 
 async fn wait_for_terminals_tasks(
-    terminals_for_task: Vec<(usize, View<TerminalView>)>,
+    terminals_for_task: Vec<View<TerminalView>>,
     cx: &mut AsyncWindowContext,
 ) {
-    let pending_tasks = terminals_for_task.iter().filter_map(|(_, terminal)| {
+    let pending_tasks = terminals_for_task.iter().filter_map(|terminal| {
         terminal
             .update(cx, |terminal_view, cx| {
                 terminal_view
@@ -651,6 +657,157 @@ async fn wait_for_terminals_tasks(
     let _: Vec<()> = join_all(pending_tasks).await;
 }
 
+/// Creates a pane for the terminal panel, which only holds terminals and is split by the panel itself.
+fn new_terminal_pane(
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    cx: &mut ViewContext<TerminalPanel>,
+) -> View<Pane> {
+    let pane = cx.new_view(|cx| {
+        let mut pane = Pane::new(
+            workspace.clone(),
+            project,
+            Default::default(),
+            None,
+            NewTerminal.boxed_clone(),
+            cx,
+        );
+        // Dropping tabs onto the pane edges would split the workspace center instead,
+        // so terminal panes are only split by their split actions.
+        pane.set_can_split(false, cx);
+        pane.set_can_navigate(false, cx);
+        pane.display_nav_history_buttons(None);
+        pane.set_should_display_tab_bar(|_| true);
+        pane.set_render_tab_bar_buttons(cx, move |pane, cx| {
+            h_flex()
+                .gap_2()
+                .child(
+                    IconButton::new("plus", IconName::Plus)
+                        .icon_size(IconSize::Small)
+                        .on_click(cx.listener(|pane, _, cx| {
+                            let focus_handle = pane.focus_handle(cx);
+                            let pane_handle = cx.view().downgrade();
+                            let menu = ContextMenu::build(cx, |menu, _| {
+                                menu.action("New Terminal", workspace::NewTerminal.boxed_clone())
+                                    .entry(
+                                        "Spawn task",
+                                        Some(tasks_ui::Spawn::modal().boxed_clone()),
+                                        move |cx| {
+                                            // We want the focus to go back to terminal panel once task modal is dismissed,
+                                            // hence we focus that first. Otherwise, we'd end up without a focused element, as
+                                            // context menu will be gone the moment we spawn the modal.
+                                            cx.focus(&focus_handle);
+                                            cx.dispatch_action(
+                                                tasks_ui::Spawn::modal().boxed_clone(),
+                                            );
+                                        },
+                                    )
+                                    .separator()
+                                    .entry("Split Right", Some(pane::SplitRight.boxed_clone()), {
+                                        let pane_handle = pane_handle.clone();
+                                        move |cx| {
+                                            pane_handle
+                                                .update(cx, |pane, cx| {
+                                                    pane.split(SplitDirection::Right, cx)
+                                                })
+                                                .ok();
+                                        }
+                                    })
+                                    .entry(
+                                        "Split Down",
+                                        Some(pane::SplitDown.boxed_clone()),
+                                        move |cx| {
+                                            pane_handle
+                                                .update(cx, |pane, cx| {
+                                                    pane.split(SplitDirection::Down, cx)
+                                                })
+                                                .ok();
+                                        },
+                                    )
+                            });
+                            cx.subscribe(&menu, |pane, _, _: &DismissEvent, _| {
+                                pane.new_item_menu = None;
+                            })
+                            .detach();
+                            pane.new_item_menu = Some(menu);
+                        }))
+                        .tooltip(|cx| Tooltip::text("New...", cx)),
+                )
+                .when_some(pane.new_item_menu.as_ref(), |el, new_item_menu| {
+                    el.child(Pane::render_menu_overlay(new_item_menu))
+                })
+                .child({
+                    let zoomed = pane.is_zoomed();
+                    IconButton::new("toggle_zoom", IconName::Maximize)
+                        .icon_size(IconSize::Small)
+                        .selected(zoomed)
+                        .selected_icon(IconName::Minimize)
+                        .on_click(cx.listener(|pane, _, cx| {
+                            pane.toggle_zoom(&workspace::ToggleZoom, cx);
+                        }))
+                        .tooltip(move |cx| {
+                            Tooltip::for_action(
+                                if zoomed { "Zoom Out" } else { "Zoom In" },
+                                &ToggleZoom,
+                                cx,
+                            )
+                        })
+                })
+                .into_any_element()
+        });
+
+        pane.set_custom_drop_handle(cx, move |pane, dropped_item, cx| {
+            if let Some(tab) = dropped_item.downcast_ref::<DraggedTab>() {
+                let item = if &tab.pane == cx.view() {
+                    pane.item_for_index(tab.ix)
+                } else {
+                    tab.pane.read(cx).item_for_index(tab.ix)
+                };
+                if let Some(item) = item {
+                    if item.downcast::<TerminalView>().is_some() {
+                        return ControlFlow::Continue(());
+                    } else if let Some(project_path) = item.project_path(cx) {
+                        if let Some(entry_path) = workspace
+                            .update(cx, |workspace, cx| {
+                                workspace
+                                    .project()
+                                    .read(cx)
+                                    .absolute_path(&project_path, cx)
+                            })
+                            .log_err()
+                            .flatten()
+                        {
+                            add_paths_to_terminal(pane, &[entry_path], cx);
+                        }
+                    }
+                }
+            } else if let Some(&entry_id) = dropped_item.downcast_ref::<ProjectEntryId>() {
+                if let Some(entry_path) = workspace
+                    .update(cx, |workspace, cx| {
+                        let project = workspace.project().read(cx);
+                        project
+                            .path_for_entry(entry_id, cx)
+                            .and_then(|project_path| project.absolute_path(&project_path, cx))
+                    })
+                    .log_err()
+                    .flatten()
+                {
+                    add_paths_to_terminal(pane, &[entry_path], cx);
+                }
+            } else if let Some(paths) = dropped_item.downcast_ref::<ExternalPaths>() {
+                add_paths_to_terminal(pane, paths.paths(), cx);
+            }
+
+            ControlFlow::Break(())
+        });
+        let buffer_search_bar = cx.new_view(search::BufferSearchBar::new);
+        pane.toolbar()
+            .update(cx, |toolbar, cx| toolbar.add_item(buffer_search_bar, cx));
+        pane
+    });
+    pane
+}
+
 fn add_paths_to_terminal(pane: &mut Pane, paths: &[PathBuf], cx: &mut ViewContext<'_, Pane>) {
     if let Some(terminal_view) = pane
         .active_item()
@@ -674,7 +831,7 @@ impl Render for TerminalPanel {
         let mut registrar = DivRegistrar::new(
             |panel, cx| {
                 panel
-                    .pane
+                    .active_pane
                     .read(cx)
                     .toolbar()
                     .read(cx)
@@ -683,13 +840,27 @@ impl Render for TerminalPanel {
             cx,
         );
         BufferSearchBar::register(&mut registrar);
-        registrar.into_div().size_full().child(self.pane.clone())
+        let registrar = registrar.into_div().size_full();
+        let panel = cx.view().downgrade();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                registrar.child(self.center.render_in_panel(
+                    workspace.project(),
+                    &self.active_pane,
+                    workspace.app_state(),
+                    move |cx| {
+                        panel.update(cx, |panel, cx| panel.serialize(cx)).ok();
+                    },
+                    cx,
+                ))
+            })
+            .unwrap_or_else(|_| div())
     }
 }
 
 impl FocusableView for TerminalPanel {
     fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
-        self.pane.focus_handle(cx)
+        self.active_pane.focus_handle(cx)
     }
 }
 
@@ -737,11 +908,13 @@ impl Panel for TerminalPanel {
     }
 
     fn is_zoomed(&self, cx: &WindowContext) -> bool {
-        self.pane.read(cx).is_zoomed()
+        self.active_pane.read(cx).is_zoomed()
     }
 
     fn set_zoomed(&mut self, zoomed: bool, cx: &mut ViewContext<Self>) {
-        self.pane.update(cx, |pane, cx| pane.set_zoomed(zoomed, cx));
+        for pane in self.center.panes() {
+            pane.update(cx, |pane, cx| pane.set_zoomed(zoomed, cx));
+        }
     }
 
     fn set_active(&mut self, active: bool, cx: &mut ViewContext<Self>) {
@@ -751,7 +924,7 @@ impl Panel for TerminalPanel {
     }
 
     fn icon_label(&self, cx: &WindowContext) -> Option<String> {
-        let count = self.pane.read(cx).items_len();
+        let count = self.terminals_count(cx);
         if count == 0 {
             None
         } else {
@@ -778,10 +951,245 @@ impl Panel for TerminalPanel {
     }
 }
 
+fn serialize_panes(
+    layout: &PaneGroupLayout,
+    active_pane: &View<Pane>,
+    cx: &AppContext,
+) -> SerializedTerminalPanes {
+    match layout {
+        PaneGroupLayout::Axis {
+            axis,
+            flexes,
+            members,
+        } => SerializedTerminalPanes::Group {
+            axis: (*axis).into(),
+            flexes: flexes.clone(),
+            children: members
+                .iter()
+                .map(|member| serialize_panes(member, active_pane, cx))
+                .collect(),
+        },
+        PaneGroupLayout::Pane(pane) => {
+            let pane_ref = pane.read(cx);
+            // Task terminals are spawned anew by their tasks, so they're not restored.
+            let items = pane_ref
+                .items()
+                .filter_map(|item| {
+                    let terminal_view = item.act_as::<TerminalView>(cx)?;
+                    if terminal_view.read(cx).terminal().read(cx).task().is_some() {
+                        None
+                    } else {
+                        Some(item.item_id().as_u64())
+                    }
+                })
+                .collect::<Vec<_>>();
+            let active_item_id = pane_ref
+                .active_item()
+                .map(|item| item.item_id().as_u64())
+                .filter(|active_id| items.contains(active_id));
+            SerializedTerminalPanes::Pane {
+                items,
+                active_item_id,
+                active: pane == active_pane,
+            }
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 struct SerializedTerminalPanel {
+    /// Terminals of the panel, serialized before it could be split.
+    #[serde(default)]
     items: Vec<u64>,
+    #[serde(default)]
     active_item_id: Option<u64>,
+    #[serde(default)]
+    panes: Option<SerializedTerminalPanes>,
     width: Option<Pixels>,
     height: Option<Pixels>,
 }
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+enum SerializedTerminalPanes {
+    Group {
+        axis: SerializedAxis,
+        flexes: Vec<f32>,
+        children: Vec<SerializedTerminalPanes>,
+    },
+    Pane {
+        items: Vec<u64>,
+        active_item_id: Option<u64>,
+        active: bool,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+enum SerializedAxis {
+    Horizontal,
+    Vertical,
+}
+
+impl From<Axis> for SerializedAxis {
+    fn from(axis: Axis) -> Self {
+        match axis {
+            Axis::Horizontal => Self::Horizontal,
+            Axis::Vertical => Self::Vertical,
+        }
+    }
+}
+
+impl From<SerializedAxis> for Axis {
+    fn from(axis: SerializedAxis) -> Self {
+        match axis {
+            SerializedAxis::Horizontal => Self::Horizontal,
+            SerializedAxis::Vertical => Self::Vertical,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_split_serialize_and_restore_panes(cx: &mut TestAppContext) {
+        let (workspace, cx) = init_test(cx).await;
+        let panel = load_panel(&workspace, cx).await;
+        panel.update(cx, |panel, cx| {
+            panel.add_terminal(None, None, RevealStrategy::Never, cx)
+        });
+        cx.run_until_parked();
+
+        let first_pane = panel.update(cx, |panel, _| panel.active_pane.clone());
+        first_pane.update(cx, |pane, cx| pane.split(SplitDirection::Right, cx));
+        cx.run_until_parked();
+        let second_pane = panel.update(cx, |panel, cx| {
+            assert_eq!(panel.center.panes().len(), 2);
+            assert_eq!(panel.pane_subscriptions.len(), 2);
+            assert_eq!(panel.terminals_count(cx), 2);
+            assert_ne!(panel.active_pane, first_pane);
+            panel.active_pane.clone()
+        });
+
+        second_pane.update(cx, |pane, cx| pane.split(SplitDirection::Down, cx));
+        cx.run_until_parked();
+        panel.update(cx, |panel, cx| {
+            assert_eq!(panel.center.panes().len(), 3);
+            assert_eq!(panel.terminals_count(cx), 3);
+            assert_eq!(
+                serialize_panes(&panel.center.layout(), &panel.active_pane, cx),
+                SerializedTerminalPanes::Group {
+                    axis: SerializedAxis::Horizontal,
+                    flexes: vec![1., 1.],
+                    children: vec![
+                        pane_with_items(&first_pane, false, cx),
+                        SerializedTerminalPanes::Group {
+                            axis: SerializedAxis::Vertical,
+                            flexes: vec![1., 1.],
+                            children: vec![
+                                pane_with_items(&second_pane, false, cx),
+                                pane_with_items(&panel.active_pane, true, cx),
+                            ],
+                        },
+                    ],
+                }
+            );
+        });
+
+        // Closing the last terminal of a split removes the split and drops its subscriptions.
+        second_pane.update(cx, |pane, cx| {
+            pane.close_active_item(&Default::default(), cx)
+                .unwrap()
+                .detach_and_log_err(cx)
+        });
+        cx.run_until_parked();
+        panel.update(cx, |panel, cx| {
+            assert_eq!(panel.center.panes().len(), 2);
+            assert_eq!(panel.pane_subscriptions.len(), 2);
+            assert_eq!(panel.terminals_count(cx), 2);
+            assert!(!panel.center.panes().contains(&&second_pane));
+        });
+
+        // Simulate dragging the border between the splits, which changes their flexes
+        // and then serializes the panel.
+        panel.update(cx, |panel, cx| {
+            let PaneGroupLayout::Axis { axis, members, .. } = panel.center.layout() else {
+                panic!("expected the panel to be split");
+            };
+            panel.center = PaneGroup::from_layout(PaneGroupLayout::Axis {
+                axis,
+                flexes: vec![0.5, 1.5],
+                members,
+            });
+            panel.serialize(cx);
+        });
+        let pending_serialization = panel.update(cx, |panel, _| {
+            std::mem::replace(&mut panel.pending_serialization, Task::ready(None))
+        });
+        pending_serialization.await.unwrap();
+
+        let restored_panel = load_panel(&workspace, cx).await;
+        cx.run_until_parked();
+        restored_panel.update(cx, |panel, cx| {
+            assert_eq!(panel.terminals_count(cx), 2);
+            assert_eq!(panel.pane_subscriptions.len(), 2);
+            let panes = panel.center.panes();
+            assert_eq!(panel.active_pane, *panes[1]);
+            assert_eq!(
+                serialize_panes(&panel.center.layout(), &panel.active_pane, cx),
+                SerializedTerminalPanes::Group {
+                    axis: SerializedAxis::Horizontal,
+                    flexes: vec![0.5, 1.5],
+                    children: vec![
+                        pane_with_items(panes[0], false, cx),
+                        pane_with_items(panes[1], true, cx),
+                    ],
+                }
+            );
+        });
+    }
+
+    fn pane_with_items(
+        pane: &View<Pane>,
+        active: bool,
+        cx: &AppContext,
+    ) -> SerializedTerminalPanes {
+        let pane = pane.read(cx);
+        SerializedTerminalPanes::Pane {
+            items: pane.items().map(|item| item.item_id().as_u64()).collect(),
+            active_item_id: pane.active_item().map(|item| item.item_id().as_u64()),
+            active,
+        }
+    }
+
+    async fn load_panel(
+        workspace: &View<Workspace>,
+        cx: &mut VisualTestContext,
+    ) -> View<TerminalPanel> {
+        let async_cx = cx.update(|cx| cx.to_async());
+        let panel = TerminalPanel::load(workspace.downgrade(), async_cx)
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        panel
+    }
+
+    async fn init_test(cx: &mut TestAppContext) -> (View<Workspace>, &mut VisualTestContext) {
+        cx.executor().allow_parking();
+        let app_state = cx.update(AppState::test);
+        cx.update(|cx| {
+            terminal::init(cx);
+            language::init(cx);
+            Project::init_settings(cx);
+            editor::init(cx);
+        });
+
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window.root_view(cx).unwrap();
+        let cx = VisualTestContext::from_window(*window, cx).as_mut();
+        (workspace, cx)
+    }
+}
@@ -1,4 +1,5 @@
 mod persistence;
+pub mod rename_terminal;
 pub mod terminal_element;
 pub mod terminal_panel;
 
@@ -6,13 +7,15 @@ use collections::HashSet;
 use editor::{scroll::Autoscroll, Editor};
 use futures::{stream::FuturesUnordered, StreamExt};
 use gpui::{
-    anchored, deferred, div, impl_actions, AnyElement, AppContext, DismissEvent, EventEmitter,
-    FocusHandle, FocusableView, KeyContext, KeyDownEvent, Keystroke, Model, MouseButton,
-    MouseDownEvent, Pixels, Render, Styled, Subscription, Task, View, VisualContext, WeakView,
+    actions, anchored, deferred, div, impl_actions, AnyElement, AnyView, AppContext, DismissEvent,
+    EventEmitter, FocusHandle, FocusableView, KeyContext, KeyDownEvent, Keystroke, Model,
+    MouseButton, MouseDownEvent, Pixels, Render, Styled, Subscription, Task, View, VisualContext,
+    WeakView,
 };
 use language::Bias;
 use persistence::TERMINAL_DB;
 use project::{search::SearchQuery, Fs, LocalWorktree, Metadata, Project};
+use rename_terminal::RenameTerminalModal;
 use settings::SettingsStore;
 use terminal::{
     alacritty_terminal::{
@@ -20,7 +23,8 @@ use terminal::{
         term::{search::RegexSearch, TermMode},
     },
     terminal_settings::{TerminalBlink, TerminalSettings, WorkingDirectory},
-    Clear, Copy, Event, MaybeNavigationTarget, Paste, ShowCharacterPalette, TaskStatus, Terminal,
+    Clear, Copy, CopyLastCommandOutput, Event, MaybeNavigationTarget, Paste, RerunLastCommand,
+    ScrollToNextPrompt, ScrollToPreviousPrompt, ShowCharacterPalette, TaskStatus, Terminal,
 };
 use terminal_element::TerminalElement;
 use ui::{h_flex, prelude::*, ContextMenu, Icon, IconName, Label};
@@ -65,6 +69,8 @@ pub struct SendKeystroke(String);
 
 impl_actions!(terminal, [SendText, SendKeystroke]);
 
+actions!(terminal, [RenameTerminal]);
+
 pub fn init(cx: &mut AppContext) {
     terminal_panel::init(cx);
     terminal::init(cx);
@@ -92,6 +98,8 @@ pub struct TerminalView {
     can_navigate_to_selected_word: bool,
     workspace_id: WorkspaceId,
     show_title: bool,
+    custom_title: Option<String>,
+    bottom_block: Option<AnyView>,
     _subscriptions: Vec<Subscription>,
     _terminal_subscriptions: Vec<Subscription>,
 }
@@ -168,6 +176,8 @@ impl TerminalView {
             can_navigate_to_selected_word: false,
             workspace_id,
             show_title: TerminalSettings::get_global(cx).toolbar.title,
+            custom_title: None,
+            bottom_block: None,
             _subscriptions: vec![
                 focus_in,
                 focus_out,
@@ -190,13 +200,70 @@ impl TerminalView {
         cx.emit(Event::Wakeup);
     }
 
+    /// The name given to the terminal by the user, if any.
+    pub fn custom_title(&self) -> Option<&str> {
+        self.custom_title.as_deref()
+    }
+
+    pub fn set_custom_title(&mut self, custom_title: Option<String>, cx: &mut ViewContext<Self>) {
+        if self.custom_title == custom_title {
+            return;
+        }
+        self.custom_title = custom_title.clone();
+        cx.emit(ItemEvent::UpdateTab);
+        cx.notify();
+
+        if self.terminal.read(cx).task().is_none() {
+            let item_id = cx.entity_id().as_u64();
+            let workspace_id = self.workspace_id;
+            cx.background_executor()
+                .spawn(async move {
+                    TERMINAL_DB
+                        .save_custom_title(item_id, workspace_id, custom_title)
+                        .await
+                        .log_err();
+                })
+                .detach();
+        }
+    }
+
+    /// Shows a view below the terminal content, such as the inline assistant's prompt.
+    pub fn set_bottom_block(&mut self, block: AnyView, cx: &mut ViewContext<Self>) {
+        self.bottom_block = Some(block);
+        cx.notify();
+    }
+
+    pub fn clear_bottom_block(&mut self, cx: &mut ViewContext<Self>) {
+        if self.bottom_block.take().is_some() {
+            cx.notify();
+        }
+    }
+
+    fn rename_terminal(&mut self, _: &RenameTerminal, cx: &mut ViewContext<Self>) {
+        let terminal_view = cx.view().clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace.toggle_modal(cx, move |cx| RenameTerminalModal::new(terminal_view, cx));
+            })
+            .ok();
+    }
+
     pub fn deploy_context_menu(
         &mut self,
         position: gpui::Point<Pixels>,
         cx: &mut ViewContext<Self>,
     ) {
+        let has_shell_integration = self.terminal.read(cx).shell_commands().next().is_some();
         let context_menu = ContextMenu::build(cx, |menu, _| {
-            menu.action("Clear", Box::new(Clear))
+            let menu = if has_shell_integration {
+                menu.action("Copy Last Command Output", Box::new(CopyLastCommandOutput))
+                    .action("Rerun Last Command", Box::new(RerunLastCommand))
+                    .separator()
+            } else {
+                menu
+            };
+            menu.action("Rename Terminal…", Box::new(RenameTerminal))
+                .action("Clear", Box::new(Clear))
                 .action("Close", Box::new(CloseActiveItem { save_intent: None }))
         });
 
@@ -250,6 +317,33 @@ impl TerminalView {
         cx.notify();
     }
 
+    fn scroll_to_previous_prompt(
+        &mut self,
+        _: &ScrollToPreviousPrompt,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_prompt(true));
+        cx.notify();
+    }
+
+    fn scroll_to_next_prompt(&mut self, _: &ScrollToNextPrompt, cx: &mut ViewContext<Self>) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_prompt(false));
+        cx.notify();
+    }
+
+    fn copy_last_command_output(&mut self, _: &CopyLastCommandOutput, cx: &mut ViewContext<Self>) {
+        self.terminal
+            .update(cx, |term, cx| term.copy_last_command_output(cx));
+    }
+
+    fn rerun_last_command(&mut self, _: &RerunLastCommand, cx: &mut ViewContext<Self>) {
+        self.clear_bell(cx);
+        self.terminal
+            .update(cx, |term, _| term.rerun_last_command());
+    }
+
     pub fn should_show_cursor(&self, focused: bool, cx: &mut gpui::ViewContext<Self>) -> bool {
         //Don't blink the cursor when not focused, blinking is disabled, or paused
         if !focused
@@ -731,8 +825,12 @@ impl Render for TerminalView {
 
         let focused = self.focus_handle.is_focused(cx);
 
-        div()
-            .size_full()
+        // The bottom block is rendered outside of the terminal's key context, so that
+        // keystrokes typed into it aren't sent to the terminal.
+        let terminal = div()
+            .w_full()
+            .flex_1()
+            .min_h_0()
             .relative()
             .track_focus(&self.focus_handle)
             .key_context(self.dispatch_context(cx))
@@ -741,6 +839,11 @@ impl Render for TerminalView {
             .on_action(cx.listener(TerminalView::copy))
             .on_action(cx.listener(TerminalView::paste))
             .on_action(cx.listener(TerminalView::clear))
+            .on_action(cx.listener(TerminalView::rename_terminal))
+            .on_action(cx.listener(TerminalView::scroll_to_previous_prompt))
+            .on_action(cx.listener(TerminalView::scroll_to_next_prompt))
+            .on_action(cx.listener(TerminalView::copy_last_command_output))
+            .on_action(cx.listener(TerminalView::rerun_last_command))
             .on_action(cx.listener(TerminalView::show_character_palette))
             .on_action(cx.listener(TerminalView::select_all))
             .on_key_down(cx.listener(Self::key_down))
@@ -772,7 +875,12 @@ impl Render for TerminalView {
                         .child(menu.clone()),
                 )
                 .with_priority(1)
-            }))
+            }));
+
+        v_flex()
+            .size_full()
+            .child(terminal)
+            .children(self.bottom_block.clone())
     }
 }
 
@@ -785,7 +893,10 @@ impl Item for TerminalView {
 
     fn tab_content(&self, params: TabContentParams, cx: &WindowContext) -> AnyElement {
         let terminal = self.terminal().read(cx);
-        let title = terminal.title(true);
+        let title = match &self.custom_title {
+            Some(custom_title) => custom_title.clone(),
+            None => terminal.title(true),
+        };
         let icon = match terminal.task() {
             Some(terminal_task) => match &terminal_task.status {
                 TaskStatus::Unknown => IconName::ExclamationTriangle,
@@ -894,11 +1005,22 @@ impl Item for TerminalView {
                 })
                 .filter(|cwd| !cwd.as_os_str().is_empty());
 
+            let custom_title = TERMINAL_DB
+                .get_custom_title(item_id, workspace_id)
+                .log_err()
+                .flatten()
+                .filter(|title| !title.is_empty());
+
             let terminal = project.update(&mut cx, |project, cx| {
                 project.create_terminal(cwd, None, window, cx)
             })??;
             pane.update(&mut cx, |_, cx| {
-                cx.new_view(|cx| TerminalView::new(terminal, workspace, workspace_id, cx))
+                cx.new_view(|cx| {
+                    let mut terminal_view =
+                        TerminalView::new(terminal, workspace, workspace_id, cx);
+                    terminal_view.custom_title = custom_title;
+                    terminal_view
+                })
             })
         })
     }
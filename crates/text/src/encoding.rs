@@ -0,0 +1,269 @@
+use anyhow::{anyhow, Result};
+use std::{borrow::Cow, fmt};
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+const UTF16LE_BOM: &[u8] = &[0xFF, 0xFE];
+const UTF16BE_BOM: &[u8] = &[0xFE, 0xFF];
+
+/// The number of bytes inspected when guessing the encoding of a file without a byte order mark.
+const DETECTION_SAMPLE_LEN: usize = 64 * 1024;
+
+/// Legacy encodings offered when choosing an encoding, after the Unicode ones.
+static LEGACY_ENCODINGS: [&encoding_rs::Encoding; 20] = [
+    &encoding_rs::WINDOWS_1252_INIT,
+    &encoding_rs::ISO_8859_2_INIT,
+    &encoding_rs::ISO_8859_15_INIT,
+    &encoding_rs::WINDOWS_1250_INIT,
+    &encoding_rs::WINDOWS_1251_INIT,
+    &encoding_rs::KOI8_R_INIT,
+    &encoding_rs::WINDOWS_1253_INIT,
+    &encoding_rs::WINDOWS_1254_INIT,
+    &encoding_rs::WINDOWS_1255_INIT,
+    &encoding_rs::WINDOWS_1256_INIT,
+    &encoding_rs::WINDOWS_1257_INIT,
+    &encoding_rs::WINDOWS_874_INIT,
+    &encoding_rs::WINDOWS_1258_INIT,
+    &encoding_rs::SHIFT_JIS_INIT,
+    &encoding_rs::EUC_JP_INIT,
+    &encoding_rs::ISO_2022_JP_INIT,
+    &encoding_rs::GBK_INIT,
+    &encoding_rs::GB18030_INIT,
+    &encoding_rs::BIG5_INIT,
+    &encoding_rs::EUC_KR_INIT,
+];
+
+/// The character encoding of a file, along with whether it starts with a byte order mark.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Encoding {
+    encoding: &'static encoding_rs::Encoding,
+    bom: bool,
+}
+
+impl Default for Encoding {
+    fn default() -> Self {
+        Self {
+            encoding: encoding_rs::UTF_8,
+            bom: false,
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.bom {
+            write!(f, "{} with BOM", self.name())
+        } else {
+            write!(f, "{}", self.name())
+        }
+    }
+}
+
+impl Encoding {
+    /// Looks up an encoding by a name produced by its [`fmt::Display`] implementation, or by
+    /// any of the labels that browsers accept for it. The encoding has a byte order mark only if
+    /// the name ends with " with BOM".
+    pub fn from_name(name: &str) -> Option<Self> {
+        let (label, bom) = match name.strip_suffix(" with BOM") {
+            Some(label) => (label, true),
+            None => (name, false),
+        };
+        let encoding = encoding_rs::Encoding::for_label(label.as_bytes())?;
+        Some(
+            Self {
+                encoding,
+                bom: false,
+            }
+            .with_bom(bom),
+        )
+    }
+
+    /// The encodings that can be chosen for a buffer.
+    pub fn all() -> impl Iterator<Item = Self> {
+        [
+            Self::default(),
+            Self::default().with_bom(true),
+            Self::new(encoding_rs::UTF_16LE),
+            Self::new(encoding_rs::UTF_16LE).with_bom(false),
+            Self::new(encoding_rs::UTF_16BE),
+            Self::new(encoding_rs::UTF_16BE).with_bom(false),
+        ]
+        .into_iter()
+        .chain(LEGACY_ENCODINGS.iter().copied().map(Self::new))
+    }
+
+    fn new(encoding: &'static encoding_rs::Encoding) -> Self {
+        Self {
+            encoding,
+            bom: Self::is_utf16(encoding),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.encoding.name()
+    }
+
+    pub fn has_bom(&self) -> bool {
+        self.bom
+    }
+
+    /// Returns this encoding with or without a byte order mark. Only Unicode encodings have one.
+    pub fn with_bom(self, bom: bool) -> Self {
+        Self {
+            encoding: self.encoding,
+            bom: bom && !self.bom_bytes().is_empty(),
+        }
+    }
+
+    fn is_utf16(encoding: &'static encoding_rs::Encoding) -> bool {
+        encoding == encoding_rs::UTF_16LE || encoding == encoding_rs::UTF_16BE
+    }
+
+    fn bom_bytes(&self) -> &'static [u8] {
+        if self.encoding == encoding_rs::UTF_8 {
+            UTF8_BOM
+        } else if self.encoding == encoding_rs::UTF_16LE {
+            UTF16LE_BOM
+        } else if self.encoding == encoding_rs::UTF_16BE {
+            UTF16BE_BOM
+        } else {
+            &[]
+        }
+    }
+
+    /// Guesses the encoding of a file's contents, preferring a byte order mark, then UTF-8 if the
+    /// contents are valid UTF-8, then the most likely legacy encoding. Returns `None` if the
+    /// contents look like binary data rather than text.
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        Self::detect_from_prefix(bytes, true)
+    }
+
+    /// Like [`Encoding::detect`], but for the first part of a file's contents when `is_complete`
+    /// is false, in which case they may end partway through a character.
+    pub fn detect_from_prefix(bytes: &[u8], is_complete: bool) -> Option<Self> {
+        if let Some((encoding, _)) = encoding_rs::Encoding::for_bom(bytes) {
+            return Some(Self {
+                encoding,
+                bom: true,
+            });
+        }
+
+        let sample = &bytes[..bytes.len().min(DETECTION_SAMPLE_LEN)];
+        if let Some(encoding) = detect_utf16_without_bom(sample) {
+            return Some(Self {
+                encoding,
+                bom: false,
+            });
+        }
+        match std::str::from_utf8(bytes) {
+            Ok(_) => return Some(Self::default()),
+            Err(error) if !is_complete && error.error_len().is_none() => {
+                return Some(Self::default())
+            }
+            Err(_) => {}
+        }
+        if sample.contains(&0) {
+            return None;
+        }
+
+        let mut detector = chardetng::EncodingDetector::new();
+        detector.feed(sample, is_complete && sample.len() == bytes.len());
+        Some(Self::new(detector.guess(None, false)))
+    }
+
+    /// Decodes a file's contents in the encoding detected from them.
+    pub fn detect_and_decode(bytes: &[u8]) -> Result<(String, Self)> {
+        Self::detect(bytes)
+            .ok_or_else(|| anyhow!("file appears to be binary"))?
+            .decode(bytes)
+    }
+
+    /// Decodes a file's contents, returning an error if they aren't valid in this encoding.
+    /// The returned encoding records whether the contents started with a byte order mark.
+    pub fn decode(self, bytes: &[u8]) -> Result<(String, Self)> {
+        let bom = self.bom_bytes();
+        let (bytes, has_bom) = match bytes.strip_prefix(bom) {
+            Some(rest) if !bom.is_empty() => (rest, true),
+            _ => (bytes, false),
+        };
+        let text = self
+            .encoding
+            .decode_without_bom_handling_and_without_replacement(bytes)
+            .ok_or_else(|| anyhow!("file is not valid {}", self.name()))?;
+        Ok((text.into_owned(), self.with_bom(has_bom)))
+    }
+
+    /// Returns a decoder for a file's contents that are read in chunks. Unlike
+    /// [`Encoding::decode`], it replaces malformed sequences rather than failing.
+    pub fn new_decoder(self) -> Decoder {
+        Decoder(self.encoding.new_decoder_with_bom_removal())
+    }
+
+    /// Encodes text for writing to a file, returning an error if it contains characters that
+    /// can't be represented in this encoding.
+    pub fn encode<'a>(&self, text: &'a str) -> Result<Cow<'a, [u8]>> {
+        let bom = if self.bom { self.bom_bytes() } else { &[] };
+        if self.encoding == encoding_rs::UTF_16LE || self.encoding == encoding_rs::UTF_16BE {
+            let big_endian = self.encoding == encoding_rs::UTF_16BE;
+            let mut bytes = Vec::with_capacity(bom.len() + text.len() * 2);
+            bytes.extend_from_slice(bom);
+            for unit in text.encode_utf16() {
+                if big_endian {
+                    bytes.extend_from_slice(&unit.to_be_bytes());
+                } else {
+                    bytes.extend_from_slice(&unit.to_le_bytes());
+                }
+            }
+            return Ok(Cow::Owned(bytes));
+        }
+
+        let (bytes, _, has_unmappable_chars) = self.encoding.encode(text);
+        if has_unmappable_chars {
+            return Err(anyhow!(
+                "text contains characters that can't be represented in {}",
+                self.name()
+            ));
+        }
+        if bom.is_empty() {
+            Ok(bytes)
+        } else {
+            Ok(Cow::Owned([bom, &bytes].concat()))
+        }
+    }
+}
+
+/// Decodes a file's contents incrementally, handling characters that are split between chunks.
+pub struct Decoder(encoding_rs::Decoder);
+
+impl Decoder {
+    pub fn decode(&mut self, bytes: &[u8], is_last: bool) -> String {
+        let capacity = self
+            .0
+            .max_utf8_buffer_length(bytes.len())
+            .unwrap_or(bytes.len());
+        let mut text = String::with_capacity(capacity);
+        let _ = self.0.decode_to_string(bytes, &mut text, is_last);
+        text
+    }
+}
+
+/// Recognizes UTF-16 without a byte order mark by its NUL bytes, which are common in the high or
+/// low half of each code unit of mostly-ASCII text but rare in other text files.
+fn detect_utf16_without_bom(sample: &[u8]) -> Option<&'static encoding_rs::Encoding> {
+    if sample.len() < 4 || sample.len() % 2 != 0 {
+        return None;
+    }
+    let units = sample.len() / 2;
+    let even_nuls = sample.iter().step_by(2).filter(|byte| **byte == 0).count();
+    let odd_nuls = sample[1..]
+        .iter()
+        .step_by(2)
+        .filter(|byte| **byte == 0)
+        .count();
+    if odd_nuls * 2 > units && even_nuls * 10 < units {
+        Some(encoding_rs::UTF_16LE)
+    } else if even_nuls * 2 > units && odd_nuls * 10 < units {
+        Some(encoding_rs::UTF_16BE)
+    } else {
+        None
+    }
+}
@@ -181,6 +181,76 @@ fn test_line_endings() {
     buffer.check_invariants();
 }
 
+#[test]
+fn test_encodings() {
+    assert_eq!(Encoding::detect(b"plain text\n"), Some(Encoding::default()));
+    assert_eq!(
+        Encoding::detect(b"\xEF\xBB\xBFwith bom"),
+        Some(Encoding::default().with_bom(true))
+    );
+    assert_eq!(Encoding::detect(b"\x7FELF\x02\x01\x01\0\0\0\xFF"), None);
+
+    let utf16 = Encoding::from_name("UTF-16LE with BOM").unwrap();
+    assert!(utf16.has_bom());
+    assert!(!Encoding::from_name("UTF-16LE").unwrap().has_bom());
+    let bytes = utf16.encode("one\ntwo").unwrap();
+    assert_eq!(&bytes[..4], b"\xFF\xFEo\0");
+    assert_eq!(Encoding::detect(&bytes), Some(utf16));
+    assert_eq!(
+        Encoding::detect_and_decode(&bytes[2..]).unwrap(),
+        ("one\ntwo".to_string(), utf16.with_bom(false))
+    );
+
+    let latin1 = b"Le caf\xE9 est tr\xE8s bon, et le th\xE9 aussi.\n";
+    let (text, encoding) = Encoding::detect_and_decode(latin1).unwrap();
+    assert_eq!(encoding.name(), "windows-1252");
+    assert_eq!(text, "Le café est très bon, et le thé aussi.\n");
+    assert_eq!(encoding.encode(&text).unwrap().as_ref(), latin1);
+    assert!(encoding.encode("🍐").is_err());
+
+    let shift_jis = Encoding::from_name("Shift_JIS").unwrap();
+    let bytes = shift_jis.encode("こんにちは").unwrap();
+    assert_eq!(bytes.as_ref(), b"\x82\xB1\x82\xF1\x82\xC9\x82\xBF\x82\xCD");
+    assert_eq!(shift_jis.decode(&bytes).unwrap().0, "こんにちは");
+    assert!(Encoding::default().decode(&bytes).is_err());
+
+    for encoding in Encoding::all() {
+        assert_eq!(Encoding::from_name(&encoding.to_string()), Some(encoding));
+    }
+}
+
+#[test]
+fn test_chunked_loading() {
+    let text = "one\r\ntwo 🍐\r\n\r\nthree ✅\rfour\r\n";
+    let expected = Buffer::new(0, BufferId::new(1).unwrap(), text.into());
+
+    let utf16 = Encoding::from_name("UTF-16BE with BOM").unwrap();
+    let encodings = [Encoding::default(), utf16];
+    for encoding in encodings {
+        let bytes = encoding.encode(text).unwrap();
+        for chunk_len in 1..=7 {
+            let mut decoder = Encoding::detect_from_prefix(&bytes[..8], false)
+                .unwrap()
+                .new_decoder();
+            let mut normalizer = LineEndingNormalizer::default();
+            let mut rope = Rope::new();
+            let chunks = bytes.chunks(chunk_len).collect::<Vec<_>>();
+            for (ix, chunk) in chunks.iter().enumerate() {
+                let is_last = ix == chunks.len() - 1;
+                rope.push(&normalizer.push(decoder.decode(chunk, is_last)));
+            }
+            rope.push(&normalizer.finish());
+
+            assert_eq!(
+                rope.to_string(),
+                expected.text(),
+                "chunk length {chunk_len}"
+            );
+            assert_eq!(normalizer.line_ending(), LineEnding::Windows);
+        }
+    }
+}
+
 #[test]
 fn test_line_len() {
     let mut buffer = Buffer::new(0, BufferId::new(1).unwrap(), "".into());
@@ -1,4 +1,5 @@
 mod anchor;
+mod encoding;
 pub mod locator;
 #[cfg(any(test, feature = "test-support"))]
 pub mod network;
@@ -14,6 +15,7 @@ pub use anchor::*;
 use anyhow::{anyhow, Context as _, Result};
 pub use clock::ReplicaId;
 use collections::{HashMap, HashSet};
+pub use encoding::{Decoder, Encoding};
 use locator::Locator;
 use operation_queue::OperationQueue;
 pub use patch::Patch;
@@ -29,6 +31,7 @@ use std::{
     fmt::Display,
     future::Future,
     iter::Iterator,
+    mem,
     num::NonZeroU64,
     ops::{self, Deref, Range, Sub},
     str,
@@ -2787,3 +2790,50 @@ impl LineEnding {
         }
     }
 }
+
+/// Normalizes the line endings of text that arrives in chunks, where a `\r\n`
+/// may be split between two chunks.
+#[derive(Debug, Default)]
+pub struct LineEndingNormalizer {
+    pending_carriage_return: bool,
+    line_ending: Option<LineEnding>,
+}
+
+impl LineEndingNormalizer {
+    /// Normalizes the next chunk, holding back a trailing `\r` until it's
+    /// known whether the following chunk starts with `\n`.
+    pub fn push(&mut self, mut chunk: String) -> String {
+        if mem::take(&mut self.pending_carriage_return) {
+            chunk.insert(0, '\r');
+        }
+        if chunk.ends_with('\r') {
+            chunk.pop();
+            self.pending_carriage_return = true;
+        }
+        if self.line_ending.is_none() {
+            if let Some(ix) = chunk.find('\n') {
+                self.line_ending = Some(if chunk[..ix].ends_with('\r') {
+                    LineEnding::Windows
+                } else {
+                    LineEnding::Unix
+                });
+            }
+        }
+        LineEnding::normalize(&mut chunk);
+        chunk
+    }
+
+    /// Returns the text held back from the last chunk.
+    pub fn finish(&mut self) -> String {
+        if mem::take(&mut self.pending_carriage_return) {
+            "\n".into()
+        } else {
+            String::new()
+        }
+    }
+
+    /// The line ending of the first line seen so far.
+    pub fn line_ending(&self) -> LineEnding {
+        self.line_ending.unwrap_or_default()
+    }
+}
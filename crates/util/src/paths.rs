@@ -70,6 +70,7 @@ lazy_static::lazy_static! {
     pub static ref LOCAL_SETTINGS_RELATIVE_PATH: &'static Path = Path::new(".zed/settings.json");
     pub static ref LOCAL_TASKS_RELATIVE_PATH: &'static Path = Path::new(".zed/tasks.json");
     pub static ref LOCAL_VSCODE_TASKS_RELATIVE_PATH: &'static Path = Path::new(".vscode/tasks.json");
+    pub static ref LOCAL_ASSISTANT_CONTEXT_RELATIVE_PATH: &'static Path = Path::new(".zed/assistant_context.md");
     pub static ref TEMP_DIR: PathBuf = if cfg!(target_os = "windows") {
         dirs::cache_dir()
             .expect("failed to determine LocalAppData directory")
@@ -333,6 +334,7 @@ impl<P> PathLikeWithPosition<P> {
 #[derive(Clone, Debug)]
 pub struct PathMatcher {
     maybe_path: PathBuf,
+    literal_prefix: PathBuf,
     glob: GlobMatcher,
 }
 
@@ -352,12 +354,27 @@ impl Eq for PathMatcher {}
 
 impl PathMatcher {
     pub fn new(maybe_glob: &str) -> Result<Self, globset::Error> {
+        let maybe_path = PathBuf::from(maybe_glob);
         Ok(PathMatcher {
             glob: Glob::new(maybe_glob)?.compile_matcher(),
-            maybe_path: PathBuf::from(maybe_glob),
+            literal_prefix: maybe_path
+                .components()
+                .take_while(|component| {
+                    !component
+                        .as_os_str()
+                        .to_string_lossy()
+                        .contains(['*', '?', '[', '{'])
+                })
+                .collect(),
+            maybe_path,
         })
     }
 
+    /// The leading components of the pattern that don't contain any glob syntax.
+    pub fn literal_prefix(&self) -> &Path {
+        &self.literal_prefix
+    }
+
     pub fn is_match<P: AsRef<Path>>(&self, other: P) -> bool {
         let other_path = other.as_ref();
         other_path.starts_with(&self.maybe_path)
@@ -619,4 +636,21 @@ mod tests {
             "Path matcher {path_matcher} should match {path:?}"
         );
     }
+
+    #[test]
+    fn path_matcher_literal_prefix() {
+        let prefix = |glob| {
+            PathMatcher::new(glob)
+                .unwrap()
+                .literal_prefix()
+                .to_path_buf()
+        };
+        assert_eq!(
+            prefix("services/payments/**"),
+            Path::new("services/payments")
+        );
+        assert_eq!(prefix("libs/*/src"), Path::new("libs"));
+        assert_eq!(prefix("libs/common"), Path::new("libs/common"));
+        assert_eq!(prefix("**/*.rs"), Path::new(""));
+    }
 }
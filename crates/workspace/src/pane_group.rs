@@ -9,13 +9,17 @@ use gpui::{
 use parking_lot::Mutex;
 use project::Project;
 use serde::Deserialize;
-use std::sync::Arc;
+use std::{rc::Rc, sync::Arc};
 use ui::prelude::*;
+use util::ResultExt;
 
 pub const HANDLE_HITBOX_SIZE: f32 = 4.0;
 const HORIZONTAL_MIN_SIZE: f32 = 80.;
 const VERTICAL_MIN_SIZE: f32 = 100.;
 
+/// Called after the user resizes the splits of a [`PaneGroup`].
+type OnResize = Rc<dyn Fn(&mut WindowContext)>;
+
 /// One or many panes, arranged in a horizontal or vertical axis due to a split.
 /// Panes have all their tabs and capabilities preserved, and can be split again or resized.
 /// Single-pane group is a regular pane.
@@ -29,6 +33,11 @@ impl PaneGroup {
         Self { root }
     }
 
+    /// Restores a group from a layout returned by [`PaneGroup::layout`].
+    pub fn from_layout(layout: PaneGroupLayout) -> Self {
+        Self::with_root(Member::from_layout(layout))
+    }
+
     pub fn new(pane: View<Pane>) -> Self {
         Self {
             root: Member::Pane(pane),
@@ -102,6 +111,12 @@ impl PaneGroup {
         app_state: &Arc<AppState>,
         cx: &mut ViewContext<Workspace>,
     ) -> impl IntoElement {
+        let workspace = cx.view().downgrade();
+        let on_resize: OnResize = Rc::new(move |cx| {
+            workspace
+                .update(cx, |this, cx| this.serialize_workspace(cx))
+                .log_err();
+        });
         self.root.render(
             project,
             0,
@@ -110,19 +125,63 @@ impl PaneGroup {
             active_pane,
             zoomed,
             app_state,
+            &on_resize,
+            cx,
+        )
+    }
+
+    /// Renders the group outside of the workspace center, e.g. in a dock panel,
+    /// where its panes are neither followed nor zoomed separately.
+    ///
+    /// `on_resize` is called whenever the user resizes the group's splits.
+    pub fn render_in_panel(
+        &self,
+        project: &Model<Project>,
+        active_pane: &View<Pane>,
+        app_state: &Arc<AppState>,
+        on_resize: impl Fn(&mut WindowContext) + 'static,
+        cx: &mut ViewContext<Workspace>,
+    ) -> impl IntoElement {
+        let on_resize: OnResize = Rc::new(on_resize);
+        self.root.render(
+            project,
+            0,
+            &HashMap::default(),
+            None,
+            active_pane,
+            None,
+            app_state,
+            &on_resize,
             cx,
         )
     }
 
-    pub(crate) fn panes(&self) -> Vec<&View<Pane>> {
+    pub fn panes(&self) -> Vec<&View<Pane>> {
         let mut panes = Vec::new();
         self.root.collect_panes(&mut panes);
         panes
     }
 
-    pub(crate) fn first_pane(&self) -> View<Pane> {
+    pub fn first_pane(&self) -> View<Pane> {
         self.root.first_pane()
     }
+
+    /// Returns the arrangement of the group's panes, so that it can be persisted
+    /// by groups that live outside of the workspace center.
+    pub fn layout(&self) -> PaneGroupLayout {
+        self.root.layout()
+    }
+}
+
+/// A snapshot of the arrangement of a [`PaneGroup`]'s panes.
+#[derive(Clone)]
+pub enum PaneGroupLayout {
+    Axis {
+        axis: Axis,
+        flexes: Vec<f32>,
+        members: Vec<PaneGroupLayout>,
+    },
+    Pane(View<Pane>),
 }
 
 #[derive(Clone)]
@@ -163,6 +222,35 @@ impl Member {
         }
     }
 
+    fn layout(&self) -> PaneGroupLayout {
+        match self {
+            Member::Axis(axis) => PaneGroupLayout::Axis {
+                axis: axis.axis,
+                flexes: axis.flexes.lock().clone(),
+                members: axis.members.iter().map(Member::layout).collect(),
+            },
+            Member::Pane(pane) => PaneGroupLayout::Pane(pane.clone()),
+        }
+    }
+
+    fn from_layout(layout: PaneGroupLayout) -> Self {
+        match layout {
+            PaneGroupLayout::Axis {
+                axis,
+                flexes,
+                members,
+            } => {
+                let members = members
+                    .into_iter()
+                    .map(Member::from_layout)
+                    .collect::<Vec<_>>();
+                let flexes = Some(flexes).filter(|flexes| flexes.len() == members.len());
+                Member::Axis(PaneAxis::load(axis, members, flexes))
+            }
+            PaneGroupLayout::Pane(pane) => Member::Pane(pane),
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn render(
         &self,
@@ -173,6 +261,7 @@ impl Member {
         active_pane: &View<Pane>,
         zoomed: Option<&AnyWeakView>,
         app_state: &Arc<AppState>,
+        on_resize: &OnResize,
         cx: &mut ViewContext<Workspace>,
     ) -> impl IntoElement {
         match self {
@@ -297,6 +386,7 @@ impl Member {
                     active_pane,
                     zoomed,
                     app_state,
+                    on_resize,
                     cx,
                 )
                 .into_any(),
@@ -488,6 +578,7 @@ impl PaneAxis {
         active_pane: &View<Pane>,
         zoomed: Option<&AnyWeakView>,
         app_state: &Arc<AppState>,
+        on_resize: &OnResize,
         cx: &mut ViewContext<Workspace>,
     ) -> gpui::AnyElement {
         debug_assert!(self.members.len() == self.flexes.lock().len());
@@ -498,7 +589,7 @@ impl PaneAxis {
             basis,
             self.flexes.clone(),
             self.bounding_boxes.clone(),
-            cx.view().downgrade(),
+            on_resize.clone(),
         )
         .children(self.members.iter().enumerate().map(|(ix, member)| {
             if member.contains(active_pane) {
@@ -513,6 +604,7 @@ impl PaneAxis {
                     active_pane,
                     zoomed,
                     app_state,
+                    on_resize,
                     cx,
                 )
                 .into_any_element()
@@ -599,20 +691,17 @@ mod element {
     use gpui::{
         px, relative, Along, AnyElement, Axis, Bounds, Element, GlobalElementId, IntoElement,
         MouseDownEvent, MouseMoveEvent, MouseUpEvent, ParentElement, Pixels, Point, Size, Style,
-        WeakView, WindowContext,
+        WindowContext,
     };
     use gpui::{CursorStyle, Hitbox};
     use parking_lot::Mutex;
     use settings::Settings;
     use smallvec::SmallVec;
     use ui::prelude::*;
-    use util::ResultExt;
-
-    use crate::Workspace;
 
     use crate::WorkspaceSettings;
 
-    use super::{HANDLE_HITBOX_SIZE, HORIZONTAL_MIN_SIZE, VERTICAL_MIN_SIZE};
+    use super::{OnResize, HANDLE_HITBOX_SIZE, HORIZONTAL_MIN_SIZE, VERTICAL_MIN_SIZE};
 
     const DIVIDER_SIZE: f32 = 1.0;
 
@@ -621,7 +710,7 @@ mod element {
         basis: usize,
         flexes: Arc<Mutex<Vec<f32>>>,
         bounding_boxes: Arc<Mutex<Vec<Option<Bounds<Pixels>>>>>,
-        workspace: WeakView<Workspace>,
+        on_resize: OnResize,
     ) -> PaneAxisElement {
         PaneAxisElement {
             axis,
@@ -630,7 +719,7 @@ mod element {
             bounding_boxes,
             children: SmallVec::new(),
             active_pane_ix: None,
-            workspace,
+            on_resize,
         }
     }
 
@@ -641,7 +730,7 @@ mod element {
         bounding_boxes: Arc<Mutex<Vec<Option<Bounds<Pixels>>>>>,
         children: SmallVec<[AnyElement; 2]>,
         active_pane_ix: Option<usize>,
-        workspace: WeakView<Workspace>,
+        on_resize: OnResize,
     }
 
     pub struct PaneAxisLayout {
@@ -674,7 +763,7 @@ mod element {
             axis: Axis,
             child_start: Point<Pixels>,
             container_size: Size<Pixels>,
-            workspace: WeakView<Workspace>,
+            on_resize: &OnResize,
             cx: &mut WindowContext,
         ) {
             let min_size = match axis {
@@ -748,9 +837,8 @@ mod element {
                 proposed_current_pixel_change -= current_pixel_change;
             }
 
-            workspace
-                .update(cx, |this, cx| this.serialize_workspace(cx))
-                .log_err();
+            drop(flexes);
+            on_resize(cx);
             cx.stop_propagation();
             cx.refresh();
         }
@@ -926,7 +1014,7 @@ mod element {
                     cx.on_mouse_event({
                         let dragged_handle = layout.dragged_handle.clone();
                         let flexes = self.flexes.clone();
-                        let workspace = self.workspace.clone();
+                        let on_resize = self.on_resize.clone();
                         let handle_hitbox = handle.hitbox.clone();
                         move |e: &MouseDownEvent, phase, cx| {
                             if phase.bubble() && handle_hitbox.is_hovered(cx) {
@@ -934,9 +1022,8 @@ mod element {
                                 if e.click_count >= 2 {
                                     let mut borrow = flexes.lock();
                                     *borrow = vec![1.; borrow.len()];
-                                    workspace
-                                        .update(cx, |this, cx| this.serialize_workspace(cx))
-                                        .log_err();
+                                    drop(borrow);
+                                    on_resize(cx);
 
                                     cx.refresh();
                                 }
@@ -945,7 +1032,7 @@ mod element {
                         }
                     });
                     cx.on_mouse_event({
-                        let workspace = self.workspace.clone();
+                        let on_resize = self.on_resize.clone();
                         let dragged_handle = layout.dragged_handle.clone();
                         let flexes = self.flexes.clone();
                         let child_bounds = child.bounds;
@@ -961,7 +1048,7 @@ mod element {
                                         axis,
                                         child_bounds.origin,
                                         bounds.size,
-                                        workspace.clone(),
+                                        &on_resize,
                                         cx,
                                     )
                                 }
@@ -13,7 +13,7 @@ pub fn task_cwd(workspace: &Workspace, cx: &mut WindowContext) -> anyhow::Result
         .filter(|worktree| {
             let worktree = worktree.read(cx);
             worktree.is_visible()
-                && worktree.is_local()
+                && (worktree.is_local() || project.is_via_ssh())
                 && worktree.root_entry().map_or(false, |e| e.is_dir())
         })
         .collect::<Vec<_>>();
@@ -27,13 +27,14 @@ use git::{
 };
 use gpui::{
     AppContext, AsyncAppContext, BackgroundExecutor, Context, EventEmitter, Model, ModelContext,
-    Task,
+    Task, WeakModel,
 };
 use ignore::IgnoreStack;
 use itertools::Itertools;
 use language::{
     proto::{deserialize_version, serialize_line_ending, serialize_version},
-    Buffer, Capability, DiagnosticEntry, File as _, LineEnding, PointUtf16, Rope, Unclipped,
+    Buffer, Capability, DiagnosticEntry, Encoding, File as _, LineEnding, LineEndingNormalizer,
+    PointUtf16, Rope, Unclipped,
 };
 use lsp::{DiagnosticSeverity, LanguageServerId};
 use parking_lot::Mutex;
@@ -76,6 +77,9 @@ pub const FS_WATCH_LATENCY: Duration = Duration::from_millis(100);
 #[cfg(not(feature = "test-support"))]
 pub const FS_WATCH_LATENCY: Duration = Duration::from_millis(100);
 
+/// How many bytes of a large file are read and appended to its buffer at a time.
+const LARGE_FILE_CHUNK_LEN: u64 = 4 * 1024 * 1024;
+
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Hash, PartialOrd, Ord)]
 pub struct WorktreeId(usize);
 
@@ -284,7 +288,13 @@ pub struct LocalSnapshot {
     /// id of their parent directory.
     git_repositories: TreeMap<ProjectEntryId, LocalRepositoryEntry>,
     file_scan_exclusions: Vec<PathMatcher>,
+    /// If not empty, the only files that are included in the worktree. Other
+    /// paths are treated as if they were ignored.
+    file_scan_inclusions: Vec<PathMatcher>,
     private_files: Vec<PathMatcher>,
+    /// Whether directories are only scanned once they're requested, rather
+    /// than all being scanned up front.
+    lazy_file_scan: bool,
 }
 
 struct BackgroundScannerState {
@@ -393,6 +403,12 @@ impl Worktree {
                             .as_deref(),
                         "file_scan_exclusions",
                     );
+                    let new_file_scan_inclusions = path_matchers(
+                        WorktreeSettings::get_global(cx)
+                            .file_scan_inclusions
+                            .as_deref(),
+                        "file_scan_inclusions",
+                    );
                     let new_private_files = path_matchers(
                         WorktreeSettings::get(Some(settings::SettingsLocation {
                             worktree_id: cx.handle().entity_id().as_u64() as usize,
@@ -400,12 +416,19 @@ impl Worktree {
                         }), cx).private_files.as_deref(),
                         "private_files",
                     );
+                    let new_lazy_file_scan = WorktreeSettings::get_global(cx)
+                        .lazy_file_scan
+                        .unwrap_or(false);
 
                     if new_file_scan_exclusions != this.snapshot.file_scan_exclusions
+                        || new_file_scan_inclusions != this.snapshot.file_scan_inclusions
                         || new_private_files != this.snapshot.private_files
+                        || new_lazy_file_scan != this.snapshot.lazy_file_scan
                     {
                         this.snapshot.file_scan_exclusions = new_file_scan_exclusions;
+                        this.snapshot.file_scan_inclusions = new_file_scan_inclusions;
                         this.snapshot.private_files = new_private_files;
+                        this.snapshot.lazy_file_scan = new_lazy_file_scan;
 
                         log::info!(
                             "Re-scanning directories, new scan exclude files: {:?}, new dotenv files: {:?}",
@@ -452,6 +475,12 @@ impl Worktree {
                         .as_deref(),
                     "file_scan_exclusions",
                 ),
+                file_scan_inclusions: path_matchers(
+                    WorktreeSettings::get_global(cx)
+                        .file_scan_inclusions
+                        .as_deref(),
+                    "file_scan_inclusions",
+                ),
                 private_files: path_matchers(
                     WorktreeSettings::get(Some(SettingsLocation {
                         worktree_id: cx.handle().entity_id().as_u64() as usize,
@@ -459,6 +488,9 @@ impl Worktree {
                     }), cx).private_files.as_deref(),
                     "private_files",
                 ),
+                lazy_file_scan: WorktreeSettings::get_global(cx)
+                    .lazy_file_scan
+                    .unwrap_or(false),
                 ignores_by_parent_abs_path: Default::default(),
                 git_repositories: Default::default(),
                 snapshot: Snapshot {
@@ -782,6 +814,76 @@ fn path_matchers(values: Option<&[String]>, context: &'static str) -> Vec<PathMa
         .collect()
 }
 
+/// The text of a file that's being opened in a buffer.
+enum LoadedText {
+    Text(String, Encoding),
+    /// The file is too large to load at once, so its buffer is filled incrementally.
+    Large {
+        abs_path: PathBuf,
+        len: u64,
+    },
+}
+
+/// Appends a large file's text to its buffer a chunk at a time, reading and
+/// decoding each chunk in the background.
+async fn load_large_file(
+    fs: Arc<dyn Fs>,
+    abs_path: PathBuf,
+    len: u64,
+    buffer: WeakModel<Buffer>,
+    mut cx: AsyncAppContext,
+) -> Result<()> {
+    let (chunks_tx, chunks_rx) = channel::bounded(1);
+    let load = cx.background_executor().spawn(async move {
+        let mut decoder = None;
+        let mut encoding = None;
+        let mut line_endings = LineEndingNormalizer::default();
+        let mut offset = 0;
+        loop {
+            let bytes = fs
+                .load_range(&abs_path, offset..offset + LARGE_FILE_CHUNK_LEN)
+                .await?;
+            offset += bytes.len() as u64;
+            let is_last = (bytes.len() as u64) < LARGE_FILE_CHUNK_LEN || offset >= len;
+            let decoder = decoder.get_or_insert_with(|| {
+                let detected = Encoding::detect_from_prefix(&bytes, is_last).unwrap_or_default();
+                encoding = Some(detected);
+                detected.new_decoder()
+            });
+            let mut text = line_endings.push(decoder.decode(&bytes, is_last));
+            if is_last {
+                text.push_str(&line_endings.finish());
+            }
+            let progress = (!is_last).then(|| (offset as f32 / len as f32).min(1.));
+            if chunks_tx
+                .send((text, encoding.take(), line_endings.line_ending(), progress))
+                .await
+                .is_err()
+                || is_last
+            {
+                return anyhow::Ok(());
+            }
+        }
+    });
+
+    while let Ok((text, encoding, line_ending, progress)) = chunks_rx.recv().await {
+        buffer.update(&mut cx, |buffer, cx| {
+            // The encoding is detected from the first chunk, and saving uses it too.
+            if let Some(encoding) = encoding {
+                buffer.set_encoding(encoding, cx);
+            }
+            buffer.append_loaded_text(&text, line_ending, progress, cx)
+        })?;
+    }
+    let result = load.await;
+    if let Err(error) = &result {
+        buffer.update(&mut cx, |buffer, cx| {
+            buffer.fail_loading(format!("{error:#}"), cx)
+        })?;
+    }
+    result
+}
+
 impl LocalWorktree {
     pub fn contains_abs_path(&self, path: &Path) -> bool {
         path.starts_with(&self.abs_path)
@@ -795,10 +897,33 @@ impl LocalWorktree {
         let path = Arc::from(path);
         let reservation = cx.reserve_model();
         let buffer_id = BufferId::from(reservation.entity_id().as_non_zero_u64());
+        let fs = self.fs.clone();
         cx.spawn(move |this, mut cx| async move {
-            let (file, contents, diff_base) = this
+            let (file, loaded_text, diff_base) = this
                 .update(&mut cx, |t, cx| t.as_local().unwrap().load(&path, cx))?
                 .await?;
+            let (contents, encoding) = match loaded_text {
+                LoadedText::Text(contents, encoding) => (contents, encoding),
+                LoadedText::Large { abs_path, len } => {
+                    let buffer = cx.insert_model(reservation, |_| {
+                        Buffer::build(
+                            text::Buffer::new(0, buffer_id, String::new()),
+                            None,
+                            Some(Arc::new(file)),
+                            Capability::ReadOnly,
+                        )
+                        .with_large_file()
+                    })?;
+                    let weak_buffer = buffer.downgrade();
+                    cx.spawn(|cx| async move {
+                        load_large_file(fs, abs_path, len, weak_buffer, cx)
+                            .await
+                            .log_err();
+                    })
+                    .detach();
+                    return Ok(buffer);
+                }
+            };
             let text_buffer = cx
                 .background_executor()
                 .spawn(async move { text::Buffer::new(0, buffer_id, contents) })
@@ -810,6 +935,7 @@ impl LocalWorktree {
                     Some(Arc::new(file)),
                     Capability::ReadWrite,
                 )
+                .with_encoding(encoding)
             })
         })
     }
@@ -1104,18 +1230,42 @@ impl LocalWorktree {
         &self,
         path: &Path,
         cx: &mut ModelContext<Worktree>,
-    ) -> Task<Result<(File, String, Option<String>)>> {
-        let path = Arc::from(path);
+    ) -> Task<Result<(File, LoadedText, Option<String>)>> {
+        let path: Arc<Path> = Arc::from(path);
         let abs_path = self.absolutize(&path);
         let fs = self.fs.clone();
+        let large_file_threshold = WorktreeSettings::get(
+            Some(SettingsLocation {
+                worktree_id: cx.handle().entity_id().as_u64() as usize,
+                path: &path,
+            }),
+            cx,
+        )
+        .large_file_threshold
+        .unwrap_or(u64::MAX);
         let entry = self.refresh_entry(path.clone(), None, cx);
 
         cx.spawn(|this, mut cx| async move {
             let abs_path = abs_path?;
-            let text = fs.load(&abs_path).await?;
+            let len = fs
+                .metadata(&abs_path)
+                .await?
+                .map_or(0, |metadata| metadata.len);
+            let text = if len >= large_file_threshold {
+                LoadedText::Large {
+                    abs_path: abs_path.clone(),
+                    len,
+                }
+            } else {
+                let (text, encoding) = fs.load_with_encoding(&abs_path, None).await?;
+                LoadedText::Text(text, encoding)
+            };
             let mut index_task = None;
             let snapshot = this.update(&mut cx, |this, _| this.as_local().unwrap().snapshot())?;
-            if let Some(repo) = snapshot.repository_for_path(&path) {
+            let repo = snapshot
+                .repository_for_path(&path)
+                .filter(|_| matches!(text, LoadedText::Text(..)));
+            if let Some(repo) = repo {
                 if let Some(repo_path) = repo.relativize(&snapshot, &path).log_err() {
                     if let Some(git_repo) = snapshot.git_repositories.get(&*repo.work_directory) {
                         let git_repo = git_repo.repo_ptr.clone();
@@ -1213,7 +1363,13 @@ impl LocalWorktree {
 
         let text = buffer.as_rope().clone();
         let version = buffer.version();
-        let save = self.write_file(path.as_ref(), text, buffer.line_ending(), cx);
+        let save = self.write_file(
+            path.as_ref(),
+            text,
+            buffer.line_ending(),
+            buffer.encoding(),
+            cx,
+        );
         let fs = Arc::clone(&self.fs);
         let abs_path = self.absolutize(&path);
         let is_private = self.snapshot.is_path_private(&path);
@@ -1352,14 +1508,16 @@ impl LocalWorktree {
         path: impl Into<Arc<Path>>,
         text: Rope,
         line_ending: LineEnding,
+        encoding: Encoding,
         cx: &mut ModelContext<Worktree>,
     ) -> Task<Result<Option<Entry>>> {
         let path: Arc<Path> = path.into();
         let abs_path = self.absolutize(&path);
         let fs = self.fs.clone();
-        let write = cx
-            .background_executor()
-            .spawn(async move { fs.save(&abs_path?, &text, line_ending).await });
+        let write = cx.background_executor().spawn(async move {
+            fs.save_with_encoding(&abs_path?, &text, line_ending, encoding)
+                .await
+        });
 
         cx.spawn(|this, mut cx| async move {
             write.await?;
@@ -2524,6 +2682,97 @@ impl LocalSnapshot {
         })
     }
 
+    /// Directories that haven't been scanned because the worktree scans lazily,
+    /// other than ignored and external ones.
+    pub fn unloaded_dirs(&self) -> impl Iterator<Item = &Entry> {
+        self.lazy_file_scan
+            .then(|| {
+                self.directories(false, 0)
+                    .filter(|entry| entry.kind.is_unloaded() && !entry.is_external)
+            })
+            .into_iter()
+            .flatten()
+    }
+
+    /// Lists the files within a directory that hasn't been scanned, reading
+    /// them straight from the file system without adding them to the snapshot.
+    /// Ignored, excluded and symlinked paths are skipped.
+    pub async fn files_in_unloaded_dir(&self, dir: &Entry, fs: &dyn Fs) -> Vec<Arc<Path>> {
+        let root_abs_path = self.abs_path.clone();
+        let dir_abs_path: Arc<Path> = root_abs_path.join(&dir.path).into();
+        let ignore_stack = self.ignore_stack_for_abs_path(&dir_abs_path, true);
+        let mut dirs_to_read = VecDeque::from([(dir_abs_path, ignore_stack)]);
+        let mut files = Vec::new();
+
+        while let Some((dir_abs_path, mut ignore_stack)) = dirs_to_read.pop_front() {
+            let Some(mut child_paths) = fs
+                .read_dir(&dir_abs_path)
+                .await
+                .with_context(|| format!("listing unloaded directory {dir_abs_path:?}"))
+                .log_err()
+            else {
+                continue;
+            };
+            let mut child_abs_paths = Vec::new();
+            while let Some(child_abs_path) = child_paths.next().await {
+                if let Some(child_abs_path) = child_abs_path.log_err() {
+                    child_abs_paths.push(child_abs_path);
+                }
+            }
+
+            if let Some(gitignore_path) = child_abs_paths
+                .iter()
+                .find(|path| path.file_name() == Some(*GITIGNORE))
+            {
+                if let Some(ignore) = build_gitignore(gitignore_path, fs).await.log_err() {
+                    ignore_stack = ignore_stack.append(dir_abs_path.clone(), Arc::new(ignore));
+                }
+            }
+
+            for child_abs_path in child_abs_paths {
+                let Some(metadata) = fs.metadata(&child_abs_path).await.log_err().flatten() else {
+                    continue;
+                };
+                if metadata.is_symlink
+                    || ignore_stack.is_abs_path_ignored(&child_abs_path, metadata.is_dir)
+                {
+                    continue;
+                }
+                let Ok(path) = child_abs_path.strip_prefix(&root_abs_path) else {
+                    continue;
+                };
+                if self.is_path_excluded(path.to_path_buf())
+                    || self.is_path_outside_inclusions(path, metadata.is_dir)
+                {
+                    continue;
+                }
+
+                if metadata.is_dir {
+                    dirs_to_read.push_back((child_abs_path.into(), ignore_stack.clone()));
+                } else {
+                    files.push(path.into());
+                }
+            }
+        }
+
+        files
+    }
+
+    /// Whether the path falls outside of the `file_scan_inclusions`, when there
+    /// are any. A directory is only outside of them if none of the included
+    /// files could be inside it.
+    pub fn is_path_outside_inclusions(&self, path: &Path, is_dir: bool) -> bool {
+        if self.file_scan_inclusions.is_empty() {
+            return false;
+        }
+        !self.file_scan_inclusions.iter().any(|include_matcher| {
+            include_matcher.is_match(path)
+                || (is_dir
+                    && (include_matcher.literal_prefix().starts_with(path)
+                        || path.starts_with(include_matcher.literal_prefix())))
+        })
+    }
+
     pub fn is_path_excluded(&self, mut path: PathBuf) -> bool {
         loop {
             if self
@@ -2542,7 +2791,7 @@ impl LocalSnapshot {
 
 impl BackgroundScannerState {
     fn should_scan_directory(&self, entry: &Entry) -> bool {
-        (!entry.is_external && !entry.is_ignored)
+        (!entry.is_external && !entry.is_ignored && !self.snapshot.lazy_file_scan)
             || entry.path.file_name() == Some(*DOT_GIT)
             || self.scanned_dirs.contains(&entry.id) // If we've ever scanned it, keep scanning
             || self
@@ -3104,12 +3353,12 @@ impl language::LocalFile for File {
         }
     }
 
-    fn load(&self, cx: &AppContext) -> Task<Result<String>> {
+    fn load(&self, encoding: Encoding, cx: &AppContext) -> Task<Result<(String, Encoding)>> {
         let worktree = self.worktree.read(cx).as_local().unwrap();
         let abs_path = worktree.absolutize(&self.path);
         let fs = worktree.fs.clone();
         cx.background_executor()
-            .spawn(async move { fs.load(&abs_path?).await })
+            .spawn(async move { fs.load_with_encoding(&abs_path?, Some(encoding)).await })
     }
 
     fn buffer_reloaded(
@@ -3895,6 +4144,13 @@ impl BackgroundScanner {
             .await;
     }
 
+    fn is_path_outside_inclusions(&self, path: &Path, is_dir: bool) -> bool {
+        self.state
+            .lock()
+            .snapshot
+            .is_path_outside_inclusions(path, is_dir)
+    }
+
     fn send_status_update(&self, scanning: bool, barrier: Option<barrier::Sender>) -> bool {
         let mut state = self.state.lock();
         if state.changed_paths.is_empty() && scanning {
@@ -3977,8 +4233,9 @@ impl BackgroundScanner {
                 let mut new_jobs = new_jobs.iter_mut();
                 for entry in &mut new_entries {
                     let entry_abs_path = root_abs_path.join(&entry.path);
-                    entry.is_ignored =
-                        ignore_stack.is_abs_path_ignored(&entry_abs_path, entry.is_dir());
+                    entry.is_ignored = ignore_stack
+                        .is_abs_path_ignored(&entry_abs_path, entry.is_dir())
+                        || self.is_path_outside_inclusions(&entry.path, entry.is_dir());
 
                     if entry.is_dir() {
                         if let Some(job) = new_jobs.next().expect("missing scan job for entry") {
@@ -4057,7 +4314,8 @@ impl BackgroundScanner {
             }
 
             if child_entry.is_dir() {
-                child_entry.is_ignored = ignore_stack.is_abs_path_ignored(&child_abs_path, true);
+                child_entry.is_ignored = ignore_stack.is_abs_path_ignored(&child_abs_path, true)
+                    || self.is_path_outside_inclusions(&child_path, true);
 
                 // Avoid recursing until crash in the case of a recursive symlink
                 if job.ancestor_inodes.contains(&child_entry.inode) {
@@ -4081,7 +4339,8 @@ impl BackgroundScanner {
                     }));
                 }
             } else {
-                child_entry.is_ignored = ignore_stack.is_abs_path_ignored(&child_abs_path, false);
+                child_entry.is_ignored = ignore_stack.is_abs_path_ignored(&child_abs_path, false)
+                    || self.is_path_outside_inclusions(&child_path, false);
                 if !child_entry.is_ignored {
                     if let Some((repository_dir, repository, staged_statuses)) =
                         &job.containing_repository
@@ -4220,7 +4479,8 @@ impl BackgroundScanner {
                         state.snapshot.root_char_bag,
                     );
                     let is_dir = fs_entry.is_dir();
-                    fs_entry.is_ignored = ignore_stack.is_abs_path_ignored(&abs_path, is_dir);
+                    fs_entry.is_ignored = ignore_stack.is_abs_path_ignored(&abs_path, is_dir)
+                        || state.snapshot.is_path_outside_inclusions(path, is_dir);
                     fs_entry.is_external = !canonical_path.starts_with(&root_canonical_path);
                     fs_entry.is_private = state.snapshot.is_path_private(path);
 
@@ -4382,7 +4642,8 @@ impl BackgroundScanner {
         for mut entry in snapshot.child_entries(path).cloned() {
             let was_ignored = entry.is_ignored;
             let abs_path: Arc<Path> = snapshot.abs_path().join(&entry.path).into();
-            entry.is_ignored = ignore_stack.is_abs_path_ignored(&abs_path, entry.is_dir());
+            entry.is_ignored = ignore_stack.is_abs_path_ignored(&abs_path, entry.is_dir())
+                || snapshot.is_path_outside_inclusions(&entry.path, entry.is_dir());
             if entry.is_dir() {
                 let child_ignore_stack = if entry.is_ignored {
                     IgnoreStack::all()
@@ -20,9 +20,29 @@ pub struct WorktreeSettings {
     #[serde(default)]
     pub file_scan_exclusions: Option<Vec<String>>,
 
+    /// Only include files matching these globs in the worktree. Everything
+    /// else is treated like an ignored file: it's left out of the file finder,
+    /// project search and git status, but can still be expanded in the project
+    /// panel and opened. Leave empty to include all files.
+    ///
+    /// Default: []
+    #[serde(default)]
+    pub file_scan_inclusions: Option<Vec<String>>,
+
     /// Treat the files matching these globs as `.env` files.
     /// Default: [ "**/.env*" ]
     pub private_files: Option<Vec<String>>,
+
+    /// Files of at least this many bytes are opened read-only and loaded
+    /// incrementally, without syntax highlighting or language servers.
+    /// Default: 20000000
+    pub large_file_threshold: Option<u64>,
+
+    /// Only scan directories when they're expanded in the project panel or
+    /// opened via the file finder, rather than scanning the whole worktree
+    /// up front.
+    /// Default: false
+    pub lazy_file_scan: Option<bool>,
 }
 
 impl Settings for WorktreeSettings {
@@ -1,14 +1,15 @@
 use crate::{
-    worktree_settings::WorktreeSettings, Entry, EntryKind, Event, PathChange, Snapshot, Worktree,
-    WorktreeModelHandle,
+    load_large_file, worktree_settings::WorktreeSettings, Entry, EntryKind, Event, PathChange,
+    Snapshot, Worktree, WorktreeModelHandle,
 };
 use anyhow::Result;
 use client::Client;
 use clock::FakeSystemClock;
 use fs::{FakeFs, Fs, RealFs, RemoveOptions};
 use git::{repository::GitFileStatus, GITIGNORE};
-use gpui::{BorrowAppContext, ModelContext, Task, TestAppContext};
+use gpui::{BorrowAppContext, Context as _, ModelContext, Task, TestAppContext};
 use http::FakeHttpClient;
+use language::{Buffer, Capability};
 use parking_lot::Mutex;
 use postage::stream::Stream;
 use pretty_assertions::assert_eq;
@@ -21,7 +22,9 @@ use std::{
     mem,
     path::{Path, PathBuf},
     sync::Arc,
+    time::Instant,
 };
+use text::BufferId;
 use util::{test::temp_tree, ResultExt};
 
 #[gpui::test]
@@ -794,6 +797,121 @@ async fn test_dirs_no_longer_ignored(cx: &mut TestAppContext) {
     assert_eq!(read_dir_count_3 - read_dir_count_2, 2);
 }
 
+#[gpui::test]
+async fn test_open_large_file(cx: &mut TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|store, cx| {
+            store.update_user_settings::<WorktreeSettings>(cx, |project_settings| {
+                project_settings.large_file_threshold = Some(16);
+            });
+        });
+    });
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        "/root",
+        json!({
+            "small.txt": "one\ntwo\n",
+            "large.txt": "one\r\ntwo\r\nthree\r\nfour\r\n",
+        }),
+    )
+    .await;
+    let mut utf16_contents = vec![0xFF, 0xFE];
+    utf16_contents.extend("one\ntwo\n".encode_utf16().flat_map(u16::to_le_bytes));
+    fs.insert_file("/root/utf16.txt", utf16_contents).await;
+
+    let tree = Worktree::local(
+        build_client(cx),
+        "/root".as_ref(),
+        true,
+        fs.clone(),
+        Default::default(),
+        &mut cx.to_async(),
+    )
+    .await
+    .unwrap();
+    cx.read(|cx| tree.read(cx).as_local().unwrap().scan_complete())
+        .await;
+
+    let small_buffer = tree
+        .update(cx, |tree, cx| {
+            tree.as_local_mut()
+                .unwrap()
+                .load_buffer("small.txt".as_ref(), cx)
+        })
+        .await
+        .unwrap();
+    let large_buffer = tree
+        .update(cx, |tree, cx| {
+            tree.as_local_mut()
+                .unwrap()
+                .load_buffer("large.txt".as_ref(), cx)
+        })
+        .await
+        .unwrap();
+    let utf16_buffer = tree
+        .update(cx, |tree, cx| {
+            tree.as_local_mut()
+                .unwrap()
+                .load_buffer("utf16.txt".as_ref(), cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    small_buffer.read_with(cx, |buffer, _| {
+        assert!(!buffer.is_large_file());
+        assert!(!buffer.read_only());
+    });
+    large_buffer.read_with(cx, |buffer, _| {
+        assert!(buffer.is_large_file());
+        assert!(buffer.read_only());
+        assert!(!buffer.is_dirty());
+        assert_eq!(buffer.loading_progress(), None);
+        assert_eq!(buffer.text(), "one\ntwo\nthree\nfour\n");
+        assert_eq!(buffer.line_ending(), language::LineEnding::Windows);
+    });
+    utf16_buffer.read_with(cx, |buffer, _| {
+        assert!(buffer.is_large_file());
+        assert_eq!(buffer.text(), "one\ntwo\n");
+        assert_eq!(buffer.encoding().name(), "UTF-16LE");
+        assert!(buffer.encoding().has_bom());
+    });
+}
+
+#[gpui::test]
+async fn test_large_file_load_error(cx: &mut TestAppContext) {
+    init_test(cx);
+    let fs = FakeFs::new(cx.background_executor.clone());
+    let buffer = cx.new_model(|_| {
+        Buffer::build(
+            text::Buffer::new(0, BufferId::new(1).unwrap(), String::new()),
+            None,
+            None,
+            Capability::ReadOnly,
+        )
+        .with_large_file()
+    });
+
+    let result = load_large_file(
+        fs,
+        PathBuf::from("/root/missing.txt"),
+        1024,
+        buffer.downgrade(),
+        cx.to_async(),
+    )
+    .await;
+    assert!(result.is_err());
+
+    // The buffer keeps showing how far loading got, rather than looking complete.
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(buffer.loading_progress(), Some(0.));
+        assert!(buffer.loading_error().is_some());
+        assert!(buffer.has_conflict());
+        assert_eq!(buffer.text(), "");
+    });
+}
+
 #[gpui::test(iterations = 10)]
 async fn test_rescan_with_gitignore(cx: &mut TestAppContext) {
     init_test(cx);
@@ -980,6 +1098,7 @@ async fn test_write_file(cx: &mut TestAppContext) {
             Path::new("tracked-dir/file.txt"),
             "hello".into(),
             Default::default(),
+            Default::default(),
             cx,
         )
     })
@@ -990,6 +1109,7 @@ async fn test_write_file(cx: &mut TestAppContext) {
             Path::new("ignored-dir/file.txt"),
             "world".into(),
             Default::default(),
+            Default::default(),
             cx,
         )
     })
@@ -1101,6 +1221,123 @@ async fn test_file_scan_exclusions(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_file_scan_inclusions(cx: &mut TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|store, cx| {
+            store.update_user_settings::<WorktreeSettings>(cx, |project_settings| {
+                project_settings.file_scan_inclusions = Some(vec![
+                    "services/payments/**".to_string(),
+                    "libs/common/**".to_string(),
+                ]);
+            });
+        });
+    });
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        "/root",
+        json!({
+            "README.md": "",
+            "libs": {
+                "common": {
+                    "util.rs": "",
+                },
+                "other": {
+                    "other.rs": "",
+                },
+            },
+            "services": {
+                "payments": {
+                    "pay.rs": "",
+                    "src": {
+                        "lib.rs": "",
+                    },
+                },
+                "search": {
+                    "search.rs": "",
+                },
+            },
+        }),
+    )
+    .await;
+
+    let tree = Worktree::local(
+        build_client(cx),
+        Path::new("/root"),
+        true,
+        fs.clone(),
+        Default::default(),
+        &mut cx.to_async(),
+    )
+    .await
+    .unwrap();
+    cx.read(|cx| tree.read(cx).as_local().unwrap().scan_complete())
+        .await;
+
+    // Paths outside of the inclusions are treated as ignored, and aren't scanned.
+    tree.read_with(cx, |tree, _| {
+        assert_eq!(
+            tree.entries(false)
+                .map(|entry| entry.path.as_ref())
+                .collect::<Vec<_>>(),
+            vec![
+                Path::new(""),
+                Path::new("libs"),
+                Path::new("libs/common"),
+                Path::new("libs/common/util.rs"),
+                Path::new("services"),
+                Path::new("services/payments"),
+                Path::new("services/payments/pay.rs"),
+                Path::new("services/payments/src"),
+                Path::new("services/payments/src/lib.rs"),
+            ]
+        );
+        check_worktree_entries(
+            tree,
+            &["libs/other/other.rs", "services/search/search.rs"],
+            &["README.md", "libs/other", "services/search"],
+            &[],
+        );
+    });
+
+    // They can still be loaded on demand.
+    tree.read_with(cx, |tree, _| {
+        tree.as_local()
+            .unwrap()
+            .refresh_entries_for_paths(vec![Path::new("libs/other/other.rs").into()])
+    })
+    .recv()
+    .await;
+    tree.read_with(cx, |tree, _| {
+        check_worktree_entries(tree, &[], &["libs/other/other.rs"], &[]);
+    });
+
+    // Without any inclusions, the whole worktree is included.
+    cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|store, cx| {
+            store.update_user_settings::<WorktreeSettings>(cx, |project_settings| {
+                project_settings.file_scan_inclusions = Some(Vec::new());
+            });
+        });
+    });
+    cx.executor().run_until_parked();
+    tree.read_with(cx, |tree, _| {
+        check_worktree_entries(
+            tree,
+            &[],
+            &[],
+            &[
+                "README.md",
+                "libs/common/util.rs",
+                "libs/other/other.rs",
+                "services/payments/src/lib.rs",
+                "services/search/search.rs",
+            ],
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_fs_events_in_exclusions(cx: &mut TestAppContext) {
     init_test(cx);
@@ -1243,6 +1480,227 @@ async fn test_fs_events_in_exclusions(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_lazy_file_scan(cx: &mut TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|store, cx| {
+            store.update_user_settings::<WorktreeSettings>(cx, |project_settings| {
+                project_settings.lazy_file_scan = Some(true);
+            });
+        });
+    });
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        "/root",
+        json!({
+            ".gitignore": "target\n",
+            "README.md": "",
+            "a": {
+                "a1.rs": "",
+                "b": {
+                    ".gitignore": "generated\n",
+                    "b1.rs": "",
+                    "c": {
+                        "c1.rs": "",
+                    },
+                    "generated": {
+                        "g1.rs": "",
+                    },
+                },
+            },
+            "d": {
+                "d1.rs": "",
+                "e": {
+                    "e1.rs": "",
+                },
+            },
+            "target": {
+                "debug": {
+                    "out": "",
+                },
+            },
+        }),
+    )
+    .await;
+
+    let tree = Worktree::local(
+        build_client(cx),
+        Path::new("/root"),
+        true,
+        fs.clone(),
+        Default::default(),
+        &mut cx.to_async(),
+    )
+    .await
+    .unwrap();
+    cx.read(|cx| tree.read(cx).as_local().unwrap().scan_complete())
+        .await;
+
+    // Only the root directory is scanned up front.
+    tree.read_with(cx, |tree, _| {
+        assert_eq!(
+            loaded_entries(tree),
+            vec![
+                (Path::new(""), true),
+                (Path::new(".gitignore"), true),
+                (Path::new("README.md"), true),
+                (Path::new("a"), false),
+                (Path::new("d"), false),
+                (Path::new("target"), false),
+            ]
+        );
+    });
+
+    // Expanding a directory loads its children, but not their children.
+    let a_id = tree.read_with(cx, |tree, _| tree.entry_for_path("a").unwrap().id);
+    tree.update(cx, |tree, cx| {
+        tree.as_local_mut().unwrap().expand_entry(a_id, cx).unwrap()
+    })
+    .await
+    .unwrap();
+
+    // Loading a path loads the directories above it.
+    tree.read_with(cx, |tree, _| {
+        tree.as_local()
+            .unwrap()
+            .refresh_entries_for_paths(vec![Path::new("d/e/e1.rs").into()])
+    })
+    .recv()
+    .await;
+
+    tree.read_with(cx, |tree, _| {
+        assert_eq!(
+            loaded_entries(tree),
+            vec![
+                (Path::new(""), true),
+                (Path::new(".gitignore"), true),
+                (Path::new("README.md"), true),
+                (Path::new("a"), true),
+                (Path::new("a/a1.rs"), true),
+                (Path::new("a/b"), false),
+                (Path::new("d"), true),
+                (Path::new("d/d1.rs"), true),
+                (Path::new("d/e"), true),
+                (Path::new("d/e/e1.rs"), true),
+                (Path::new("target"), false),
+            ]
+        );
+    });
+
+    // The files in directories that haven't been loaded can be listed straight from the file
+    // system, without adding them to the worktree. Ignored paths are skipped.
+    let snapshot = tree.read_with(cx, |tree, _| tree.as_local().unwrap().snapshot());
+    assert_eq!(
+        snapshot
+            .unloaded_dirs()
+            .map(|entry| entry.path.as_ref())
+            .collect::<Vec<_>>(),
+        vec![Path::new("a/b")]
+    );
+    let mut files = snapshot
+        .files_in_unloaded_dir(snapshot.entry_for_path("a/b").unwrap(), fs.as_ref())
+        .await;
+    files.sort();
+    assert_eq!(
+        files,
+        vec![
+            Path::new("a/b/.gitignore").into(),
+            Path::new("a/b/b1.rs").into(),
+            Path::new("a/b/c/c1.rs").into(),
+        ] as Vec<Arc<Path>>
+    );
+    tree.read_with(cx, |tree, _| {
+        assert!(tree.entry_for_path("a/b").unwrap().kind.is_unloaded());
+    });
+
+    // Changes in loaded directories are still picked up.
+    fs.insert_file("/root/d/e/e2.rs", "".into()).await;
+    tree.flush_fs_events(cx).await;
+    tree.read_with(cx, |tree, _| {
+        assert!(tree.entry_for_path("d/e/e2.rs").is_some());
+    });
+}
+
+/// Compares scanning a large worktree up front with scanning it lazily. Use `BENCHMARK_DIRS` and
+/// `BENCHMARK_FILES_PER_DIR` to scale the tree up, and `--nocapture` to see the timings.
+#[gpui::test]
+async fn test_lazy_file_scan_benchmark(cx: &mut TestAppContext) {
+    init_test(cx);
+    cx.executor().allow_parking();
+
+    let dir_count = env::var("BENCHMARK_DIRS")
+        .map(|o| o.parse().unwrap())
+        .unwrap_or(10);
+    let files_per_dir = env::var("BENCHMARK_FILES_PER_DIR")
+        .map(|o| o.parse().unwrap())
+        .unwrap_or(10);
+    let dir = temp_tree(json!({}));
+    for i in 0..dir_count {
+        for j in 0..dir_count {
+            let subdir = dir.path().join(format!("dir-{i}/dir-{j}"));
+            std::fs::create_dir_all(&subdir).unwrap();
+            for k in 0..files_per_dir {
+                std::fs::write(subdir.join(format!("file-{k}.rs")), "").unwrap();
+            }
+        }
+    }
+
+    let mut entry_counts = Vec::new();
+    for lazy_file_scan in [false, true] {
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<WorktreeSettings>(cx, |project_settings| {
+                    project_settings.lazy_file_scan = Some(lazy_file_scan);
+                });
+            });
+        });
+
+        let start = Instant::now();
+        let tree = Worktree::local(
+            build_client(cx),
+            dir.path(),
+            true,
+            Arc::new(RealFs::default()),
+            Default::default(),
+            &mut cx.to_async(),
+        )
+        .await
+        .unwrap();
+        cx.read(|cx| tree.read(cx).as_local().unwrap().scan_complete())
+            .await;
+        let initial_scan_time = start.elapsed();
+        let entry_count = tree.read_with(cx, |tree, _| tree.entries(true).count());
+
+        let start = Instant::now();
+        tree.read_with(cx, |tree, _| {
+            tree.as_local()
+                .unwrap()
+                .refresh_entries_for_paths(vec![Path::new("dir-0/dir-0/file-0.rs").into()])
+        })
+        .recv()
+        .await;
+        let open_file_time = start.elapsed();
+        tree.read_with(cx, |tree, _| {
+            assert!(tree.entry_for_path("dir-0/dir-0/file-0.rs").is_some());
+        });
+
+        println!(
+            "lazy_file_scan: {lazy_file_scan}, entries: {entry_count}, \
+            initial scan: {initial_scan_time:?}, opening a file: {open_file_time:?}"
+        );
+        entry_counts.push(entry_count);
+    }
+
+    assert_eq!(
+        entry_counts,
+        [
+            1 + dir_count + dir_count * dir_count * (1 + files_per_dir),
+            1 + dir_count
+        ]
+    );
+}
+
 #[gpui::test]
 async fn test_fs_events_in_dot_git_worktree(cx: &mut TestAppContext) {
     init_test(cx);
@@ -1807,8 +2265,13 @@ fn randomly_mutate_worktree(
                 })
             } else {
                 log::info!("overwriting file {:?} ({})", entry.path, entry.id.0);
-                let task =
-                    worktree.write_file(entry.path.clone(), "".into(), Default::default(), cx);
+                let task = worktree.write_file(
+                    entry.path.clone(),
+                    "".into(),
+                    Default::default(),
+                    Default::default(),
+                    cx,
+                );
                 cx.background_executor().spawn(async move {
                     task.await?;
                     Ok(())
@@ -2703,6 +3166,12 @@ fn check_worktree_entries(
     }
 }
 
+fn loaded_entries(tree: &Worktree) -> Vec<(&Path, bool)> {
+    tree.entries(true)
+        .map(|entry| (entry.path.as_ref(), !entry.kind.is_unloaded()))
+        .collect()
+}
+
 fn init_test(cx: &mut gpui::TestAppContext) {
     cx.update(|cx| {
         let settings_store = SettingsStore::test(cx);